@@ -1,8 +1,8 @@
 // SPP example (pseudo range based direct positioning).
 // This is simply here to demonstrate how to operate the API, and does not generate actual results.
 use gnss_rtk::prelude::{
-    Candidate, Carrier, Config, Epoch, Error, Frame, InvalidationCause, Method, Observation, Orbit,
-    OrbitSource, Solver, EARTH_J2000, SV,
+    Candidate, Carrier, Config, Epoch, ErrorCategory, ErrorKind, Frame, Interpolation,
+    InvalidationCause, Method, Observation, Orbit, OrbitSource, Solver, EARTH_J2000, SV,
 };
 
 // Orbit source example
@@ -12,11 +12,11 @@ impl OrbitSource for Orbits {
     // For each requested "t" and "sv",
     // if we can, we should resolve the SV [Orbit].
     // If interpolation is to be used (depending on your apps), you can
-    // use the interpolation order that we recommend here, or decide to ignore it.
+    // use the interpolation method and orders that we recommend here, or decide to ignore them.
     // If you're not in position to determine [Orbit], simply return None.
     // If None is returned for too long, this [Epoch] will eventually be dropped out,
     // and we will move on to the next
-    fn next_at(&mut self, t: Epoch, _sv: SV, _fr: Frame, _order: usize) -> Option<Orbit> {
+    fn next_at(&mut self, t: Epoch, _sv: SV, _fr: Frame, _interp: Interpolation) -> Option<Orbit> {
         let (x_km, y_km, z_km) = (0.0_f64, 0.0_f64, 0.0_f64);
         Some(Orbit::from_position(x_km, y_km, z_km, t, EARTH_J2000))
     }
@@ -102,27 +102,34 @@ pub fn main() {
                     // impacting selected signal from this spacecraft
                     let _bias_m = info.tropo_bias;
                     // Dilution of Precision informs on geometric performances
-                    let (_tdop, _gdop, _pdop) = (solution.tdop, solution.gdop, solution.pdop);
+                    let (_tdop, _gdop, _pdop) = (solution.tdop(), solution.gdop(), solution.pdop());
                     // Determine the Vertical DoP for these lat,lon coordinates
                     let (lat_ddeg, lon_ddeg) = (45.0, 13.0);
                     let _vdop = solution.vdop(lat_ddeg, lon_ddeg);
                 }
             },
-            Err(Error::InvalidatedSolution(cause)) => match cause {
-                InvalidationCause::FirstSolution => {
-                    // The current behavior will always discard the first solution.
-                    // We will always wind up here on the first iteration
+            Err(e) => match e.kind() {
+                ErrorKind::InvalidatedSolution(cause) => match cause {
+                    InvalidationCause::FirstSolution => {
+                        // The current behavior will always discard the first solution.
+                        // We will always wind up here on the first iteration
+                    },
+                    _other => {
+                        // Conditions are either degrading and no longer fit preset criteria,
+                        // or we need more iterations to finally meet preset criteria.
+                    },
                 },
-                _other => {
-                    // Conditions are either degrading and no longer fit preset criteria,
-                    // or we need more iterations to finally meet preset criteria.
+                _ => match e.category() {
+                    ErrorCategory::Measurement => {
+                        // "e.sv()" tells which spacecraft should be excluded
+                    },
+                    _ => {
+                        // Something went wrong, use "e" to get more info on what is wrong.
+                        // The most plausible cause is the lack of observations, at this point in time.
+                        // But that should not last for too long, otherwise something's wrong in your setup
+                    },
                 },
             },
-            Err(_e) => {
-                // Something went wrong, use "e" to get more info on what is wrong.
-                // The most plausible cause is the lack of observations, at this point in time.
-                // But that should not last for too long, otherwise something's wrong in your setup
-            },
         }
     }
 }
@@ -0,0 +1,185 @@
+/*
+ * gnss-rtk C API (`capi` feature)
+ *
+ * Epochs are expressed as GPST seconds (since 1980-01-06 00:00:00 GPST).
+ * Missing (optional) values are expressed as NaN.
+ *
+ * The library never accesses the network nor the file system on its own:
+ * the almanac and the ECEF frame are described by the caller. Panics never
+ * cross the C boundary, they are reported as GNSS_RTK_INTERNAL_ERROR.
+ */
+#ifndef GNSS_RTK_H
+#define GNSS_RTK_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Success */
+#define GNSS_RTK_OK 0
+/* Null pointer or invalid argument */
+#define GNSS_RTK_INVALID_ARGUMENT -1
+/* No solution could be resolved at this epoch */
+#define GNSS_RTK_RESOLUTION_FAILURE -2
+/* Internal error (caught panic): the solver should be released */
+#define GNSS_RTK_INTERNAL_ERROR -3
+
+/* Opaque solver handle */
+typedef struct GnssRtkSolver GnssRtkSolver;
+
+/*
+ * SV state callback. Writes the SV state at t_gpst_s as ECEF position [km]
+ * and velocity [km/s] (6 values) into state_km, and returns true on success.
+ * Velocity may be left null (zero) if unknown.
+ */
+typedef bool (*GnssRtkOrbitCallback)(
+    void *user_data,
+    uint8_t constellation,
+    uint8_t prn,
+    double t_gpst_s,
+    double *state_km);
+
+/* Solver configuration */
+typedef struct {
+    /* 0: SPP, 1: CPP, 2: PPP */
+    uint8_t method;
+    /* 0: PositionVelocityTime, 1: TimeOnly, 2: FixedPosition */
+    uint8_t sol_type;
+    /* Minimal SV elevation [deg], NaN to disable */
+    double min_sv_elev_deg;
+    /* Minimal SNR [dB.Hz], NaN to disable */
+    double min_snr_dbhz;
+} GnssRtkConfig;
+
+/*
+ * ECEF frame description. The gravitational parameter and shape are retrieved
+ * from the almanac when the frame is described there, otherwise they must be
+ * provided (for example 399, 3000, 398600.435436, 6378.1366 and 6356.7519
+ * for Earth ITRF93).
+ */
+typedef struct {
+    /* Ephemeris (center object) NAIF ID */
+    int32_t ephemeris_id;
+    /* Orientation NAIF ID */
+    int32_t orientation_id;
+    /* Gravitational parameter [km^3/s^2], NaN to use the almanac value */
+    double mu_km3_s2;
+    /* Equatorial radius [km], NaN to use the almanac shape */
+    double equatorial_radius_km;
+    /* Polar radius [km], NaN to use the almanac shape */
+    double polar_radius_km;
+} GnssRtkFrame;
+
+/* Apriori position */
+typedef struct {
+    double t_gpst_s;
+    /* ECEF coordinates [m] */
+    double x_m;
+    double y_m;
+    double z_m;
+} GnssRtkPosition;
+
+/* Candidate description: one SV observed at one epoch */
+typedef struct {
+    /* 0: GPS, 1: Galileo, 2: BeiDou, 3: QZSS, 4: SBAS, 5: GLONASS */
+    uint8_t constellation;
+    uint8_t prn;
+    double t_gpst_s;
+    /* SV clock correction [s], NaN if unknown */
+    double clock_correction_s;
+    /* Total group delay [s], NaN if unknown */
+    double tgd_s;
+} GnssRtkCandidate;
+
+/* Single signal observation */
+typedef struct {
+    /*
+     * 0: L1, 1: L2, 2: L5, 3: L6, 4: E1, 5: E5, 6: E5A, 7: E5B, 8: E6,
+     * 9: B1aB1c, 10: B1I, 11: B2iB2b, 12: B2, 13: B2A, 14: B3,
+     * 15: G1(k), 16: G2(k)
+     */
+    uint8_t carrier;
+    /* GLONASS frequency channel k (-7..6) of FDMA carriers (G1, G2), ignored otherwise */
+    int8_t glonass_channel;
+    /* Pseudo range [m], NaN if missing */
+    double pseudo_range_m;
+    /* Phase range [m], NaN if missing */
+    double phase_range_m;
+    /* Doppler shift [Hz], NaN if missing */
+    double doppler_hz;
+    /* SNR [dB.Hz], NaN if missing */
+    double snr_dbhz;
+} GnssRtkObservation;
+
+/* Resolved solution */
+typedef struct {
+    double t_gpst_s;
+    /* ECEF position [m] */
+    double x_m;
+    double y_m;
+    double z_m;
+    /* ECEF velocity [m/s] */
+    double vx_m_s;
+    double vy_m_s;
+    double vz_m_s;
+    /* Receiver clock offset [s] */
+    double dt_s;
+    /* Receiver clock drift [s/s] */
+    double d_dt_s_s;
+    double gdop;
+    double pdop;
+    double tdop;
+    /* Number of SV that contributed */
+    uint32_t num_sv;
+    /* Clock holdover duration [s], NaN for resolved solutions */
+    double holdover_s;
+} GnssRtkSolution;
+
+/* Writes the default configuration into cfg */
+int32_t gnss_rtk_config_default(GnssRtkConfig *cfg);
+
+/*
+ * Creates a new solver, from the almanac stored in these local almanac_files
+ * (ANISE or SPICE: BSP, PCA, BPC) and the ECEF frame description.
+ * almanac_files may be null when num_almanac_files is 0. apriori may be null,
+ * or is expressed in frame. user_data is passed to the orbit callback and must
+ * remain valid for the lifetime of the solver.
+ * Returns null on invalid arguments or failure.
+ */
+GnssRtkSolver *gnss_rtk_solver_new(
+    const GnssRtkConfig *cfg,
+    const char *const *almanac_files,
+    size_t num_almanac_files,
+    const GnssRtkFrame *frame,
+    const GnssRtkPosition *apriori,
+    GnssRtkOrbitCallback orbit,
+    void *user_data);
+
+/* Releases a solver */
+void gnss_rtk_solver_free(GnssRtkSolver *solver);
+
+/* Proposes a new candidate, with its observations, for the next resolution */
+int32_t gnss_rtk_push_candidate(
+    GnssRtkSolver *solver,
+    const GnssRtkCandidate *candidate,
+    const GnssRtkObservation *observations,
+    size_t size);
+
+/*
+ * Resolves a solution at t_gpst_s, from all candidates proposed since the
+ * previous resolution attempt. Candidates are consumed, whatever the outcome.
+ */
+int32_t gnss_rtk_resolve(
+    GnssRtkSolver *solver,
+    double t_gpst_s,
+    GnssRtkSolution *solution);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GNSS_RTK_H */
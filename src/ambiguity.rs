@@ -1,13 +1,21 @@
+use crate::constants::SPEED_OF_LIGHT_M_S;
+use crate::logging::{debug, error, warn};
 use crate::prelude::{Candidate, Carrier, Duration, Epoch, SV}; // Error
-use log::{debug, error, warn};
-use nyx::cosmic::SPEED_OF_LIGHT_M_S;
+use crate::svmap::SVMap;
 use polyfit_rs::polyfit_rs::polyfit;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, VecDeque};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Number of MW samples averaged, before the ambiguities are proposed
+const MIN_MW_SAMPLES: u64 = 10;
 
 /// Ambiguity, per SV and reference signal
-pub type Ambiguities = HashMap<(SV, Carrier), Ambiguity>;
+pub type Ambiguities = BTreeMap<(SV, Carrier), Ambiguity>;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Ambiguity {
     /// Reference signal ambiguity
     pub n_1: f64,
@@ -48,34 +56,40 @@ impl Averager {
     }
 }
 
+/// Ring buffer, bounded both in time (moving window) and in number of samples
 struct Buffer {
+    capacity: usize,
     window: Duration,
     gap_tolerance: Duration,
-    pub inner: Vec<(Epoch, f64)>,
+    pub inner: VecDeque<(Epoch, f64)>,
 }
 
 impl Buffer {
     /// Allocates a new Buffer
     pub fn malloc(capacity: usize, window: Duration, gap_tolerance: Duration) -> Self {
         Self {
+            capacity,
             window,
             gap_tolerance,
-            inner: Vec::with_capacity(capacity),
+            inner: VecDeque::with_capacity(capacity.min(128)),
         }
     }
     /// Push data into self
     pub fn push(&mut self, t: Epoch, y: f64) {
-        if let Some((t_last, _)) = self.inner.last() {
+        if let Some((t_last, _)) = self.inner.back() {
             if (t - *t_last) > self.gap_tolerance {
                 error!("{}: buffer reset on data gap", t);
                 self.reset();
             }
         }
-        self.inner.push((t, y));
+        self.inner.push_back((t, y));
 
-        let t0 = self.inner[0].0;
-        if (t - t0) > self.window {
-            self.inner.remove(0);
+        while let Some((t0, _)) = self.inner.front() {
+            if (t - *t0) > self.window || self.inner.len() > self.capacity {
+                self.inner.pop_front();
+            } else {
+                break;
+            }
         }
     }
     /// Resets self
@@ -137,13 +151,13 @@ struct SVTracker {
 }
 
 impl SVTracker {
-    pub fn new(last_seen: Epoch, gap_tolerance: Duration) -> Self {
+    pub fn new(last_seen: Epoch, gap_tolerance: Duration, capacity: usize) -> Self {
         Self {
             last_seen: Some(last_seen),
             n1_tracker: Averager::new(),
             mw_tracker: Averager::new(),
             gf_buffer: Buffer::malloc(
-                128,
+                capacity,
                 //TODO: programmble
                 Duration::from_seconds(1000.0),
                 gap_tolerance,
@@ -157,33 +171,146 @@ impl SVTracker {
     }
 }
 
+/// [AmbiguityTracking] is a plain copy of the ambiguity tracking
+/// of one [SV], that may be stored and restored later on.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AmbiguityTracking {
+    /// [SV] being tracked
+    pub sv: SV,
+    /// Last seen [Epoch]
+    pub last_seen: Option<Epoch>,
+    /// MW average, deviation and number of samples
+    pub mw: (f64, f64, u64),
+    /// N_1 average, deviation and number of samples
+    pub n1: (f64, f64, u64),
+    /// GF moving window
+    pub gf: Vec<(Epoch, f64)>,
+}
+
+/// [AmbiguityStatus] is a read-only view of the ambiguity resolution of one [SV]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AmbiguityStatus {
+    /// [SV] being tracked
+    pub sv: SV,
+    /// False while out of sight (tracking is reset once back in sight)
+    pub tracked: bool,
+    /// True once enough samples were averaged for the ambiguities to be proposed
+    pub resolved: bool,
+    /// Number of MW samples averaged, since latest reset (loss of sight, cycle slip)
+    pub samples: u64,
+    /// MW (wide lane) ambiguity average and deviation [cycles]
+    pub n_w: (f64, f64),
+    /// N_1 ambiguity average and deviation [cycles]
+    pub n_1: (f64, f64),
+}
+
 /// [AmbiguitySolver] resolves phase range ambiguities in real time.
 pub struct AmbiguitySolver {
     /// Sampling interval [Duration]
     gap_tolerance: Duration,
     /// [SV] tracker
-    sv_trackers: HashMap<SV, SVTracker>,
+    sv_trackers: SVMap<SVTracker>,
     /// [SV] out of sight
     untracked: Vec<SV>,
+    /// Maximal number of samples per [SV] tracker
+    capacity: usize,
 }
 
 impl AmbiguitySolver {
-    /// Builds new [AmbiguitySolver] to work with given sample interval [Duration].
-    pub fn new(gap_tolerance: Duration) -> Self {
+    /// Builds new [AmbiguitySolver] to work with given sample interval [Duration],
+    /// each [SV] tracker retaining at most `capacity` samples.
+    pub fn new(gap_tolerance: Duration, capacity: usize) -> Self {
         Self {
             gap_tolerance,
+            capacity,
             untracked: Vec::with_capacity(8),
-            sv_trackers: HashMap::with_capacity(8),
+            sv_trackers: SVMap::with_capacity(8),
+        }
+    }
+    /// Returns [AmbiguityTracking] snapshot, for each tracked [SV]
+    pub fn snapshot(&self) -> Vec<AmbiguityTracking> {
+        let mut snapshot = self
+            .sv_trackers
+            .iter()
+            .filter(|(sv, _)| !self.untracked.contains(sv))
+            .map(|(sv, tracker)| AmbiguityTracking {
+                sv: *sv,
+                last_seen: tracker.last_seen,
+                mw: (
+                    tracker.mw_tracker.y,
+                    tracker.mw_tracker.sigma,
+                    tracker.mw_tracker.n,
+                ),
+                n1: (
+                    tracker.n1_tracker.y,
+                    tracker.n1_tracker.sigma,
+                    tracker.n1_tracker.n,
+                ),
+                gf: tracker.gf_buffer.inner.iter().copied().collect(),
+            })
+            .collect::<Vec<_>>();
+        snapshot.sort_by_key(|tracking| tracking.sv);
+        snapshot
+    }
+    /// Returns [AmbiguityStatus], for each [SV] ever tracked
+    pub fn status(&self) -> Vec<AmbiguityStatus> {
+        let mut status = self
+            .sv_trackers
+            .iter()
+            .map(|(sv, tracker)| {
+                let tracked = !self.untracked.contains(sv);
+                AmbiguityStatus {
+                    sv: *sv,
+                    tracked,
+                    resolved: tracked && tracker.mw_tracker.n > MIN_MW_SAMPLES,
+                    samples: tracker.mw_tracker.n,
+                    n_w: (tracker.mw_tracker.y, tracker.mw_tracker.sigma),
+                    n_1: (tracker.n1_tracker.y, tracker.n1_tracker.sigma),
+                }
+            })
+            .collect::<Vec<_>>();
+        status.sort_by_key(|status| status.sv);
+        status
+    }
+    /// Restores [AmbiguityTracking] snapshot (warm start)
+    pub fn restore(&mut self, snapshot: &[AmbiguityTracking]) {
+        self.untracked.clear();
+        self.sv_trackers.clear();
+        for tracking in snapshot.iter() {
+            let last_seen = match tracking.last_seen {
+                Some(last_seen) => last_seen,
+                None => continue,
+            };
+            let mut tracker = SVTracker::new(last_seen, self.gap_tolerance, self.capacity);
+            (
+                tracker.mw_tracker.y,
+                tracker.mw_tracker.sigma,
+                tracker.mw_tracker.n,
+            ) = tracking.mw;
+            (
+                tracker.n1_tracker.y,
+                tracker.n1_tracker.sigma,
+                tracker.n1_tracker.n,
+            ) = tracking.n1;
+            tracker.gf_buffer.inner = tracking.gf.iter().copied().collect();
+            while tracker.gf_buffer.inner.len() > self.capacity {
+                tracker.gf_buffer.inner.pop_front();
+            }
+            self.sv_trackers.insert(tracking.sv, tracker);
         }
     }
     /// Resolve [Ambiguities]
     pub fn resolve(&mut self, pool: &[Candidate]) -> Ambiguities {
-        let mut ambiguities = Ambiguities::with_capacity(pool.len());
+        let mut ambiguities = Ambiguities::new();
 
         for cd in pool {
-            if self.sv_trackers.get(&cd.sv).is_none() {
-                self.sv_trackers
-                    .insert(cd.sv, SVTracker::new(cd.t, self.gap_tolerance));
+            if !self.sv_trackers.contains_key(&cd.sv) {
+                self.sv_trackers.insert(
+                    cd.sv,
+                    SVTracker::new(cd.t, self.gap_tolerance, self.capacity),
+                );
                 self.untracked.retain(|sv| *sv != cd.sv);
             }
 
@@ -247,7 +374,7 @@ impl AmbiguitySolver {
                     let n_1 = n_1.round();
                     let n_2 = (n_1 - n_w).round();
 
-                    if sv_tracker.mw_tracker.n > 10 {
+                    if sv_tracker.mw_tracker.n > MIN_MW_SAMPLES {
                         debug!(
                             "{}({}): n_w: {}({}), n_1: {}({}) n_2: {}",
                             cd.t, cd.sv, n_w, sigma_n_w, n_1, sigma_n_1, n_2
@@ -267,7 +394,8 @@ impl AmbiguitySolver {
 
 #[cfg(test)]
 mod test {
-    use super::Averager;
+    use super::{Averager, Buffer};
+    use crate::prelude::{Duration, Epoch};
     #[test]
     fn test_averager() {
         let mut avg = Averager::new();
@@ -281,4 +409,24 @@ mod test {
             );
         }
     }
+    #[test]
+    fn bounded_buffer() {
+        let t0 = Epoch::default();
+        let dt = Duration::from_seconds(1.0);
+
+        // capacity bound
+        let mut buffer = Buffer::malloc(10, Duration::from_seconds(1000.0), 2.0 * dt);
+        for i in 0..100 {
+            buffer.push(t0 + i as f64 * dt, i as f64);
+        }
+        assert_eq!(buffer.len(), 10);
+        assert_eq!(buffer.inner.front(), Some(&(t0 + 90.0 * dt, 90.0)));
+
+        // time window bound
+        let mut buffer = Buffer::malloc(100, 5.0 * dt, 2.0 * dt);
+        for i in 0..100 {
+            buffer.push(t0 + i as f64 * dt, i as f64);
+        }
+        assert_eq!(buffer.len(), 6);
+    }
 }
@@ -0,0 +1,364 @@
+//! ANTEX antenna calibrations
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader},
+    path::Path,
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use crate::{
+    antenna::{AntennaCalibration, PhaseCenter},
+    prelude::{Carrier, Constellation, Epoch, TimeScale, Vector3, SV},
+};
+
+/// ANTEX parsing errors
+#[derive(Debug, Error)]
+pub enum AntexError {
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("not a valid antex file")]
+    InvalidHeader,
+    #[error("invalid epoch description (line {0})")]
+    InvalidEpoch(usize),
+    #[error("invalid calibration record (line {0})")]
+    InvalidRecord(usize),
+}
+
+fn parse_constellation(c: char) -> Option<Constellation> {
+    match c {
+        'G' => Some(Constellation::GPS),
+        'E' => Some(Constellation::Galileo),
+        'C' => Some(Constellation::BeiDou),
+        'J' => Some(Constellation::QZSS),
+        'S' => Some(Constellation::SBAS),
+        'R' => Some(Constellation::Glonass),
+        _ => None,
+    }
+}
+
+/// Parses ANTEX frequency code (like "G01" or "E05")
+fn parse_carrier(code: &str) -> Option<Carrier> {
+    let constellation = parse_constellation(code.chars().next()?)?;
+    Carrier::from_rinex_code(constellation, code.get(1..)?)
+}
+
+fn parse_epoch(content: &str) -> Option<Epoch> {
+    let items = content.split_whitespace().collect::<Vec<_>>();
+    if items.len() < 6 {
+        return None;
+    }
+    let seconds = f64::from_str(items[5]).ok()?;
+    Some(Epoch::from_gregorian(
+        i32::from_str(items[0]).ok()?,
+        u8::from_str(items[1]).ok()?,
+        u8::from_str(items[2]).ok()?,
+        u8::from_str(items[3]).ok()?,
+        u8::from_str(items[4]).ok()?,
+        seconds.trunc() as u8,
+        (seconds.fract() * 1.0E9).round() as u32,
+        TimeScale::GPST,
+    ))
+}
+
+fn parse_floats(content: &str) -> Option<Vec<f64>> {
+    content
+        .split_whitespace()
+        .map(|item| f64::from_str(item).ok())
+        .collect()
+}
+
+/// Antenna type and radome, whitespace normalized ("TRM59800.00 NONE")
+fn antenna_name(content: &str) -> String {
+    let name = content.split_whitespace().collect::<Vec<_>>();
+    match name.len() {
+        0 => String::new(),
+        1 => format!("{} NONE", name[0]),
+        _ => name.join(" "),
+    }
+}
+
+/// [Antex] gathers satellite and receiver antenna calibrations
+/// described by an ANTEX (1.4) file. Values are converted to meters.
+/// Frequencies we do not support (GLONASS FDMA..) are dropped.
+#[derive(Debug, Clone, Default)]
+pub struct Antex {
+    /// Satellite antennas, per [SV], sorted by start of validity
+    satellites: BTreeMap<SV, Vec<AntennaCalibration>>,
+    /// Receiver antennas, per normalized type and radome
+    receivers: BTreeMap<String, AntennaCalibration>,
+}
+
+impl Antex {
+    /// Parses [Antex] from local ANTEX file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, AntexError> {
+        let fd = std::fs::File::open(path)?;
+        Self::from_reader(BufReader::new(fd))
+    }
+    /// Parses [Antex] from any ANTEX content
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, AntexError> {
+        let mut antex = Self::default();
+
+        let mut antenna = Option::<AntennaCalibration>::None;
+        let mut satellite = Option::<SV>::None;
+        let mut receiver = String::new();
+        let mut frequency = Option::<(Option<Carrier>, PhaseCenter)>::None;
+        let mut in_rms = false;
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line_num = index + 1;
+            let content = line.get(..60).unwrap_or(&line);
+            let label = line.get(60..).unwrap_or("").trim();
+
+            if index == 0 {
+                if label != "ANTEX VERSION / SYST" {
+                    return Err(AntexError::InvalidHeader);
+                }
+                continue;
+            }
+
+            // RMS values are not used
+            if in_rms && label != "END OF FREQ RMS" {
+                continue;
+            }
+
+            match label {
+                "START OF ANTENNA" => {
+                    antenna = Some(AntennaCalibration::default());
+                    satellite = None;
+                    receiver.clear();
+                },
+                "END OF ANTENNA" => {
+                    let mut cal = antenna.take().ok_or(AntexError::InvalidRecord(line_num))?;
+                    if let Some(sv) = satellite {
+                        let entries = antex.satellites.entry(sv).or_default();
+                        entries.push(cal);
+                        entries.sort_by_key(|cal| cal.valid_from);
+                    } else if !receiver.is_empty() {
+                        cal.valid_from = None;
+                        cal.valid_until = None;
+                        antex.receivers.insert(receiver.clone(), cal);
+                    }
+                },
+                "TYPE / SERIAL NO" => {
+                    let serial = line.get(20..40).unwrap_or("").trim();
+                    // satellite antennas are identified by their PRN code
+                    satellite = if serial.len() == 3 {
+                        SV::from_str(serial).ok()
+                    } else {
+                        None
+                    };
+                    if satellite.is_none() {
+                        receiver = antenna_name(line.get(..20).unwrap_or(""));
+                    }
+                },
+                "DAZI" => {
+                    let cal = antenna
+                        .as_mut()
+                        .ok_or(AntexError::InvalidRecord(line_num))?;
+                    cal.dazi_deg = parse_floats(content)
+                        .and_then(|values| values.first().copied())
+                        .ok_or(AntexError::InvalidRecord(line_num))?;
+                },
+                "ZEN1 / ZEN2 / DZEN" => {
+                    let cal = antenna
+                        .as_mut()
+                        .ok_or(AntexError::InvalidRecord(line_num))?;
+                    let values = parse_floats(content)
+                        .filter(|values| values.len() == 3)
+                        .ok_or(AntexError::InvalidRecord(line_num))?;
+                    cal.zen1_deg = values[0];
+                    cal.zen2_deg = values[1];
+                    cal.dzen_deg = values[2];
+                },
+                "VALID FROM" | "VALID UNTIL" => {
+                    let cal = antenna
+                        .as_mut()
+                        .ok_or(AntexError::InvalidRecord(line_num))?;
+                    let t = parse_epoch(content).ok_or(AntexError::InvalidEpoch(line_num))?;
+                    if label == "VALID FROM" {
+                        cal.valid_from = Some(t);
+                    } else {
+                        cal.valid_until = Some(t);
+                    }
+                },
+                "START OF FREQUENCY" => {
+                    let code = content.trim();
+                    frequency = Some((parse_carrier(code), PhaseCenter::default()));
+                },
+                "NORTH / EAST / UP" => {
+                    let (_, pc) = frequency
+                        .as_mut()
+                        .ok_or(AntexError::InvalidRecord(line_num))?;
+                    let values = parse_floats(content)
+                        .filter(|values| values.len() == 3)
+                        .ok_or(AntexError::InvalidRecord(line_num))?;
+                    pc.offset_m = Vector3::new(values[0], values[1], values[2]) / 1.0E3;
+                },
+                "END OF FREQUENCY" => {
+                    let cal = antenna
+                        .as_mut()
+                        .ok_or(AntexError::InvalidRecord(line_num))?;
+                    if let Some((Some(carrier), pc)) = frequency.take() {
+                        cal.frequencies.insert(carrier, pc);
+                    }
+                },
+                "START OF FREQ RMS" => in_rms = true,
+                "END OF FREQ RMS" => in_rms = false,
+                _ => {
+                    // PCV grid rows do not have a label
+                    if let Some((_, pc)) = frequency.as_mut() {
+                        let row = line.trim_start();
+                        if let Some(values) = row.strip_prefix("NOAZI") {
+                            pc.noazi_m = parse_floats(values)
+                                .ok_or(AntexError::InvalidRecord(line_num))?
+                                .iter()
+                                .map(|value| value / 1.0E3)
+                                .collect();
+                        } else if !row.is_empty() {
+                            let values =
+                                parse_floats(row).ok_or(AntexError::InvalidRecord(line_num))?;
+                            let (azimuth, values) = values
+                                .split_first()
+                                .ok_or(AntexError::InvalidRecord(line_num))?;
+                            pc.azi_m.push((
+                                *azimuth,
+                                values.iter().map(|value| value / 1.0E3).collect(),
+                            ));
+                        }
+                    }
+                },
+            }
+        }
+        Ok(antex)
+    }
+    /// Returns list of [SV] described by this product
+    pub fn sv(&self) -> Vec<SV> {
+        self.satellites.keys().copied().collect()
+    }
+    /// Returns list of receiver antennas (type and radome) described by this product
+    pub fn receivers(&self) -> Vec<&str> {
+        self.receivers.keys().map(|name| name.as_str()).collect()
+    }
+    /// Returns [SV] antenna [AntennaCalibration] valid at [Epoch]
+    pub fn satellite(&self, sv: SV, t: Epoch) -> Option<&AntennaCalibration> {
+        self.satellites
+            .get(&sv)?
+            .iter()
+            .rev()
+            .find(|cal| cal.is_valid(t))
+    }
+    /// Returns receiver antenna [AntennaCalibration], from its IGS type and radome
+    /// (like "TRM59800.00 SCIS"). Radome defaults to "NONE" when omitted.
+    /// When this very radome is not calibrated, we fall back to the radome-less calibration.
+    pub fn receiver(&self, antenna: &str) -> Option<&AntennaCalibration> {
+        let name = antenna_name(antenna);
+        self.receivers.get(&name).or_else(|| {
+            let model = name.split(' ').next()?;
+            self.receivers.get(&antenna_name(model))
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Antex;
+    use crate::prelude::{Carrier, Epoch, SV};
+    use std::str::FromStr;
+
+    fn labelled(content: &str, label: &str) -> String {
+        format!("{:<60}{}\n", content, label)
+    }
+
+    fn antex_content() -> String {
+        let mut content = labelled("     1.4            M", "ANTEX VERSION / SYST");
+        content.push_str(&labelled("", "END OF HEADER"));
+
+        // satellite antenna
+        content.push_str(&labelled("", "START OF ANTENNA"));
+        content.push_str(&labelled(
+            "BLOCK IIF           G01                 G063      2010-063A",
+            "TYPE / SERIAL NO",
+        ));
+        content.push_str(&labelled("     0.0", "DAZI"));
+        content.push_str(&labelled("     0.0    14.0     7.0", "ZEN1 / ZEN2 / DZEN"));
+        content.push_str(&labelled(
+            "  2010     5    28     0     0    0.0000000",
+            "VALID FROM",
+        ));
+        content.push_str(&labelled("   G01", "START OF FREQUENCY"));
+        content.push_str(&labelled(
+            "    394.00      0.00   1500.00",
+            "NORTH / EAST / UP",
+        ));
+        content.push_str("   NOAZI   -0.80   -0.40    0.60\n");
+        content.push_str(&labelled("   G01", "END OF FREQUENCY"));
+        content.push_str(&labelled("   R01", "START OF FREQUENCY"));
+        content.push_str(&labelled(
+            "    394.00      0.00   1500.00",
+            "NORTH / EAST / UP",
+        ));
+        content.push_str("   NOAZI   -0.80   -0.40    0.60\n");
+        content.push_str(&labelled("   R01", "END OF FREQUENCY"));
+        content.push_str(&labelled("", "END OF ANTENNA"));
+
+        // receiver antenna, with azimuth dependent PCV
+        content.push_str(&labelled("", "START OF ANTENNA"));
+        content.push_str(&labelled("TRM59800.00     NONE", "TYPE / SERIAL NO"));
+        content.push_str(&labelled("   180.0", "DAZI"));
+        content.push_str(&labelled("     0.0    90.0    45.0", "ZEN1 / ZEN2 / DZEN"));
+        content.push_str(&labelled("   G02", "START OF FREQUENCY"));
+        content.push_str(&labelled(
+            "      1.00     -0.50     90.00",
+            "NORTH / EAST / UP",
+        ));
+        content.push_str("   NOAZI    0.00   -2.00   -6.00\n");
+        content.push_str("       0.0    0.00   -1.00   -4.00\n");
+        content.push_str("     180.0    0.00   -3.00   -8.00\n");
+        content.push_str("     360.0    0.00   -1.00   -4.00\n");
+        content.push_str(&labelled("   G02", "END OF FREQUENCY"));
+        content.push_str(&labelled("   G02", "START OF FREQ RMS"));
+        content.push_str(&labelled(
+            "      0.10      0.10      0.10",
+            "NORTH / EAST / UP",
+        ));
+        content.push_str("   NOAZI    0.10    0.10    0.10\n");
+        content.push_str(&labelled("   G02", "END OF FREQ RMS"));
+        content.push_str(&labelled("", "END OF ANTENNA"));
+        content
+    }
+
+    #[test]
+    fn antex_parsing() {
+        let antex = Antex::from_reader(antex_content().as_bytes()).unwrap();
+
+        let g01 = SV::from_str("G01").unwrap();
+        assert_eq!(antex.sv(), vec![g01]);
+        assert_eq!(antex.receivers(), vec!["TRM59800.00 NONE"]);
+
+        let before = Epoch::from_str("2009-01-01T00:00:00 GPST").unwrap();
+        let t = Epoch::from_str("2020-01-01T00:00:00 GPST").unwrap();
+        assert!(antex.satellite(g01, before).is_none());
+
+        let sat = antex.satellite(g01, t).unwrap();
+        assert_eq!(sat.carriers(), vec![Carrier::L1]);
+        let pco = sat.pco_m(Carrier::L1).unwrap();
+        assert!((pco[0] - 0.394).abs() < 1.0E-9);
+        assert!((pco[2] - 1.5).abs() < 1.0E-9);
+        assert!((sat.pcv_m(Carrier::L1, 3.5, 0.0).unwrap() + 0.6E-3).abs() < 1.0E-9);
+
+        // radome falls back to NONE
+        let rx = antex.receiver("TRM59800.00 SCIS").unwrap();
+        assert_eq!(rx.carriers(), vec![Carrier::L2]);
+        let pco = rx.pco_m(Carrier::L2).unwrap();
+        assert!((pco[2] - 0.09).abs() < 1.0E-9);
+
+        // RMS values are ignored
+        assert!((rx.pcv_m(Carrier::L2, 45.0, 0.0).unwrap() + 1.0E-3).abs() < 1.0E-9);
+        assert!((rx.pcv_m(Carrier::L2, 45.0, 90.0).unwrap() + 2.0E-3).abs() < 1.0E-9);
+        assert!((rx.pcv_m(Carrier::L2, 90.0, 180.0).unwrap() + 8.0E-3).abs() < 1.0E-9);
+        assert!(antex.receiver("LEIAR25.R3").is_none());
+    }
+}
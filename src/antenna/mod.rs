@@ -0,0 +1,156 @@
+//! Antenna phase center calibrations (PCO/PCV)
+use std::collections::BTreeMap;
+
+use crate::prelude::{Carrier, Epoch, Vector3};
+
+#[cfg(feature = "antex")]
+mod antex;
+
+#[cfg(feature = "antex")]
+#[cfg_attr(docsrs, doc(cfg(feature = "antex")))]
+pub use antex::{Antex, AntexError};
+
+/// Linear interpolation of regularly spaced `values`, starting at `x0` with `dx` spacing.
+/// Clamped to the grid boundaries.
+fn interpolate(values: &[f64], x0: f64, dx: f64, x: f64) -> Option<f64> {
+    let (first, last) = (*values.first()?, *values.last()?);
+    if values.len() == 1 || dx <= 0.0 {
+        return Some(first);
+    }
+    let pos = (x - x0) / dx;
+    if pos <= 0.0 {
+        return Some(first);
+    }
+    let idx = pos.floor() as usize;
+    if idx >= values.len() - 1 {
+        return Some(last);
+    }
+    let frac = pos - idx as f64;
+    Some(values[idx] * (1.0 - frac) + values[idx + 1] * frac)
+}
+
+/// [PhaseCenter] calibration, for a single [Carrier]
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhaseCenter {
+    /// Phase Center Offset (PCO) [m], with respect to the reference point.
+    /// Expressed as (north, east, up) for receiver antennas,
+    /// as (x, y, z) in the spacecraft body frame for satellite antennas.
+    pub offset_m: Vector3<f64>,
+    /// Azimuth independent Phase Center Variations (PCV) [m], per zenith
+    /// (or nadir) angle node.
+    pub(crate) noazi_m: Vec<f64>,
+    /// Azimuth dependent PCV [m], per azimuth row. Empty when not calibrated.
+    pub(crate) azi_m: Vec<(f64, Vec<f64>)>,
+}
+
+/// [AntennaCalibration] describes the Phase Center Offset (PCO) and
+/// Phase Center Variations (PCV) of one antenna, per [Carrier].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AntennaCalibration {
+    /// First zenith (or nadir) angle node [°]
+    pub(crate) zen1_deg: f64,
+    /// Last zenith (or nadir) angle node [°]
+    pub(crate) zen2_deg: f64,
+    /// Zenith (or nadir) angle spacing [°]
+    pub(crate) dzen_deg: f64,
+    /// Azimuth spacing [°], null when PCV is azimuth independent
+    pub(crate) dazi_deg: f64,
+    /// Start of validity, if limited
+    pub(crate) valid_from: Option<Epoch>,
+    /// End of validity, if limited
+    pub(crate) valid_until: Option<Epoch>,
+    /// Calibrations, per [Carrier]
+    pub(crate) frequencies: BTreeMap<Carrier, PhaseCenter>,
+}
+
+impl AntennaCalibration {
+    /// Returns true if this calibration applies at [Epoch]
+    pub fn is_valid(&self, t: Epoch) -> bool {
+        let after_start = self.valid_from.map_or(true, |start| t >= start);
+        let before_end = self.valid_until.map_or(true, |end| t <= end);
+        after_start && before_end
+    }
+    /// Returns the (first, last) zenith (or nadir) angle nodes [°]
+    pub fn zenith_range_deg(&self) -> (f64, f64) {
+        (self.zen1_deg, self.zen2_deg)
+    }
+    /// Returns list of calibrated [Carrier]s
+    pub fn carriers(&self) -> Vec<Carrier> {
+        self.frequencies.keys().copied().collect()
+    }
+    /// Returns [PhaseCenter] calibration for this [Carrier]
+    pub fn phase_center(&self, carrier: Carrier) -> Option<&PhaseCenter> {
+        self.frequencies.get(&carrier)
+    }
+    /// Returns Phase Center Offset [m] for this [Carrier]
+    pub fn pco_m(&self, carrier: Carrier) -> Option<Vector3<f64>> {
+        Some(self.phase_center(carrier)?.offset_m)
+    }
+    /// Interpolates Phase Center Variation [m] for this [Carrier], at given zenith
+    /// (or nadir, for satellite antennas) angle and azimuth angle in degrees.
+    /// Azimuth dependent values are used when calibrated, azimuth independent values otherwise.
+    pub fn pcv_m(&self, carrier: Carrier, zenith_deg: f64, azimuth_deg: f64) -> Option<f64> {
+        let pc = self.phase_center(carrier)?;
+        if pc.azi_m.len() < 2 || self.dazi_deg <= 0.0 {
+            return interpolate(&pc.noazi_m, self.zen1_deg, self.dzen_deg, zenith_deg);
+        }
+        let azimuth_deg = azimuth_deg.rem_euclid(360.0);
+        let rows = pc
+            .azi_m
+            .iter()
+            .map(|(_, values)| interpolate(values, self.zen1_deg, self.dzen_deg, zenith_deg))
+            .collect::<Option<Vec<_>>>()?;
+        interpolate(&rows, pc.azi_m[0].0, self.dazi_deg, azimuth_deg)
+    }
+    /// Returns the range correction [m] due to this antenna, to be added to the geometric
+    /// range computed at the reference point, for this [Carrier].
+    /// `los` is the unit line of sight vector (towards the remote end), expressed in the same
+    /// frame as the [PhaseCenter] offset.
+    pub fn range_correction_m(
+        &self,
+        carrier: Carrier,
+        los: Vector3<f64>,
+        zenith_deg: f64,
+        azimuth_deg: f64,
+    ) -> Option<f64> {
+        let pco = self.pco_m(carrier)?;
+        let pcv = self.pcv_m(carrier, zenith_deg, azimuth_deg)?;
+        Some(pcv - pco.dot(&los))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AntennaCalibration, PhaseCenter};
+    use crate::prelude::{Carrier, Vector3};
+
+    #[test]
+    fn pcv_interpolation() {
+        let mut cal = AntennaCalibration {
+            zen1_deg: 0.0,
+            zen2_deg: 90.0,
+            dzen_deg: 45.0,
+            ..Default::default()
+        };
+        cal.frequencies.insert(
+            Carrier::L1,
+            PhaseCenter {
+                offset_m: Vector3::new(0.0, 0.0, 0.1),
+                noazi_m: vec![0.0, 0.01, 0.03],
+                azi_m: Vec::new(),
+            },
+        );
+
+        assert_eq!(cal.pcv_m(Carrier::L1, 0.0, 0.0), Some(0.0));
+        assert!((cal.pcv_m(Carrier::L1, 22.5, 0.0).unwrap() - 0.005).abs() < 1.0E-9);
+        assert!((cal.pcv_m(Carrier::L1, 67.5, 120.0).unwrap() - 0.02).abs() < 1.0E-9);
+        assert_eq!(cal.pcv_m(Carrier::L1, 95.0, 0.0), Some(0.03));
+        assert!(cal.pcv_m(Carrier::L2, 0.0, 0.0).is_none());
+
+        // zenith line of sight
+        let corr = cal
+            .range_correction_m(Carrier::L1, Vector3::new(0.0, 0.0, 1.0), 0.0, 0.0)
+            .unwrap();
+        assert!((corr + 0.1).abs() < 1.0E-9);
+    }
+}
@@ -0,0 +1,108 @@
+//! Apriori receiver position
+use crate::{
+    coordinates::Ellipsoid,
+    prelude::{Epoch, Frame, Orbit},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// [AprioriPosition] is the receiver position known prior the resolution
+/// (surveyed coordinates, previous session..), used to initialize the [Solver](crate::prelude::Solver)
+/// rather than surveying it. When its covariance is known, it also constrains the solution
+/// (Bayesian prior), which stabilizes the epochs of poor geometry.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AprioriPosition {
+    /// [Epoch] of the coordinates
+    pub t: Epoch,
+    /// ECEF coordinates [m]
+    pub ecef_m: (f64, f64, f64),
+    /// ECEF covariance [m²], when the coordinates should constrain the solution
+    pub covariance_m2: Option<[[f64; 3]; 3]>,
+}
+
+impl AprioriPosition {
+    /// Builds [AprioriPosition] from ECEF coordinates [m]
+    pub fn from_ecef_m(t: Epoch, ecef_m: (f64, f64, f64)) -> Self {
+        Self {
+            t,
+            ecef_m,
+            covariance_m2: None,
+        }
+    }
+    /// Builds [AprioriPosition] from geodetic coordinates: latitude and longitude [ddeg],
+    /// altitude above the WGS84 ellipsoid [m].
+    pub fn from_geodetic(t: Epoch, lat_ddeg: f64, long_ddeg: f64, alt_m: f64) -> Self {
+        Self::from_geodetic_ellipsoid(t, lat_ddeg, long_ddeg, alt_m, Ellipsoid::WGS84)
+    }
+    /// Builds [AprioriPosition] from geodetic coordinates: latitude and longitude [ddeg],
+    /// altitude above this [Ellipsoid] [m].
+    pub fn from_geodetic_ellipsoid(
+        t: Epoch,
+        lat_ddeg: f64,
+        long_ddeg: f64,
+        alt_m: f64,
+        ellipsoid: Ellipsoid,
+    ) -> Self {
+        let ecef_m =
+            ellipsoid.geodetic_to_ecef(lat_ddeg.to_radians(), long_ddeg.to_radians(), alt_m);
+        Self::from_ecef_m(t, ecef_m)
+    }
+    /// Copies and returns [AprioriPosition] with this ECEF covariance [m²]
+    pub fn with_covariance_m2(&self, covariance_m2: [[f64; 3]; 3]) -> Self {
+        let mut s = *self;
+        s.covariance_m2 = Some(covariance_m2);
+        s
+    }
+    /// Copies and returns [AprioriPosition] with this (isotropic) standard deviation [m]
+    pub fn with_sigma_m(&self, sigma_m: f64) -> Self {
+        let var_m2 = sigma_m.powi(2);
+        self.with_covariance_m2([[var_m2, 0.0, 0.0], [0.0, var_m2, 0.0], [0.0, 0.0, var_m2]])
+    }
+    /// Returns geodetic coordinates (latitude [ddeg], longitude [ddeg], altitude [m])
+    /// with respect to the WGS84 ellipsoid.
+    pub fn geodetic(&self) -> (f64, f64, f64) {
+        let (lat_rad, long_rad, alt_m) = Ellipsoid::WGS84.ecef_to_geodetic(self.ecef_m);
+        (lat_rad.to_degrees(), long_rad.to_degrees(), alt_m)
+    }
+    /// Expresses this [AprioriPosition] as [Orbit] in this (ECEF) [Frame]
+    pub fn to_orbit(&self, frame: Frame) -> Orbit {
+        let (x, y, z) = self.ecef_m;
+        Orbit::from_position(x / 1.0E3, y / 1.0E3, z / 1.0E3, self.t, frame)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::AprioriPosition;
+    use crate::{
+        coordinates::Ellipsoid,
+        prelude::{Epoch, Vector3, EARTH_ITRF93},
+    };
+    use std::str::FromStr;
+
+    #[test]
+    fn geodetic_apriori() {
+        let t = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let ecef_m = Vector3::new(4_696_989.6880, 723_994.2090, 4_239_678.3140);
+
+        let (lat_rad, long_rad, alt_m) =
+            Ellipsoid::WGS84.ecef_to_geodetic((ecef_m[0], ecef_m[1], ecef_m[2]));
+        let apriori =
+            AprioriPosition::from_geodetic(t, lat_rad.to_degrees(), long_rad.to_degrees(), alt_m);
+        let (x, y, z) = apriori.ecef_m;
+        assert!((Vector3::new(x, y, z) - ecef_m).norm() < 1.0E-6);
+
+        let (lat_ddeg, long_ddeg, alt) = apriori.geodetic();
+        assert!((lat_ddeg - lat_rad.to_degrees()).abs() < 1.0E-9);
+        assert!((long_ddeg - long_rad.to_degrees()).abs() < 1.0E-9);
+        assert!((alt - alt_m).abs() < 1.0E-6);
+
+        let orbit = apriori.to_orbit(EARTH_ITRF93);
+        assert_eq!(orbit.epoch, t);
+        let pos_m = orbit.to_cartesian_pos_vel() * 1.0E3;
+        assert!((pos_m[0] - ecef_m[0]).abs() < 1.0E-6);
+        assert!((pos_m[2] - ecef_m[2]).abs() < 1.0E-6);
+    }
+}
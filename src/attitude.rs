@@ -0,0 +1,75 @@
+//! Platform attitude and lever arms
+use crate::prelude::Vector3;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Vehicle [Attitude], expressed as Euler angles (aerospace sequence)
+/// of the body frame (x forward, y right, z down) relative to the local (North, East, Down) frame.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Attitude {
+    /// Roll angle, in degrees
+    pub roll_deg: f64,
+    /// Pitch angle, in degrees
+    pub pitch_deg: f64,
+    /// Yaw angle (heading, clockwise from North), in degrees
+    pub yaw_deg: f64,
+}
+
+impl Attitude {
+    /// Builds new [Attitude] from Euler angles in degrees
+    pub fn new(roll_deg: f64, pitch_deg: f64, yaw_deg: f64) -> Self {
+        Self {
+            roll_deg,
+            pitch_deg,
+            yaw_deg,
+        }
+    }
+    /// Rotates body frame vector into local ENU frame
+    pub(crate) fn body_to_enu(&self, body: Vector3<f64>) -> Vector3<f64> {
+        let (s_phi, c_phi) = self.roll_deg.to_radians().sin_cos();
+        let (s_theta, c_theta) = self.pitch_deg.to_radians().sin_cos();
+        let (s_psi, c_psi) = self.yaw_deg.to_radians().sin_cos();
+
+        let (x, y, z) = (body[0], body[1], body[2]);
+
+        let north = c_theta * c_psi * x
+            + (s_phi * s_theta * c_psi - c_phi * s_psi) * y
+            + (c_phi * s_theta * c_psi + s_phi * s_psi) * z;
+
+        let east = c_theta * s_psi * x
+            + (s_phi * s_theta * s_psi + c_phi * c_psi) * y
+            + (c_phi * s_theta * s_psi - s_phi * c_psi) * z;
+
+        let down = -s_theta * x + s_phi * c_theta * y + c_phi * c_theta * z;
+
+        Vector3::new(east, north, -down)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Attitude;
+    use crate::prelude::Vector3;
+    #[test]
+    fn body_to_enu() {
+        let forward = Vector3::new(1.0, 0.0, 0.0);
+        let down = Vector3::new(0.0, 0.0, 1.0);
+
+        // heading North
+        let enu = Attitude::default().body_to_enu(forward);
+        assert!((enu - Vector3::new(0.0, 1.0, 0.0)).norm() < 1.0E-9);
+
+        let enu = Attitude::default().body_to_enu(down);
+        assert!((enu - Vector3::new(0.0, 0.0, -1.0)).norm() < 1.0E-9);
+
+        // heading East
+        let enu = Attitude::new(0.0, 0.0, 90.0).body_to_enu(forward);
+        assert!((enu - Vector3::new(1.0, 0.0, 0.0)).norm() < 1.0E-9);
+
+        // nose up
+        let enu = Attitude::new(0.0, 90.0, 0.0).body_to_enu(forward);
+        assert!((enu - Vector3::new(0.0, 0.0, 1.0)).norm() < 1.0E-9);
+    }
+}
@@ -1,9 +1,9 @@
 //! Brancroft solver
-use crate::{prelude::Candidate, solver::Error};
-use log::error;
+use crate::logging::error;
+use crate::{prelude::Candidate, solver::ErrorKind};
 
+use crate::constants::SPEED_OF_LIGHT_M_S;
 use nalgebra::{Matrix4, Vector4};
-use nyx_space::cosmic::SPEED_OF_LIGHT_M_S;
 
 pub struct Bancroft {
     a: Vector4<f64>,
@@ -28,12 +28,12 @@ impl Bancroft {
         Vector4::<f64>::new(1.0_f64, 1.0_f64, 1.0_f64, 1.0_f64)
     }
     /// Builds new Bancroft solver
-    pub fn new(cd: &[Candidate]) -> Result<Self, Error> {
+    pub fn new(cd: &[Candidate]) -> Result<Self, ErrorKind> {
         let m = Self::m_matrix();
         let mut a = Vector4::<f64>::default();
         let mut b = Matrix4::<f64>::default();
         if cd.len() < 4 {
-            return Err(Error::NotEnoughCandidatesBancroft);
+            return Err(ErrorKind::NotEnoughCandidatesBancroft);
         }
 
         let mut j = 0;
@@ -74,7 +74,7 @@ impl Bancroft {
             }
         }
         if j != 4 {
-            Err(Error::BancroftError)
+            Err(ErrorKind::BancroftError)
         } else {
             Ok(Self {
                 a,
@@ -84,8 +84,11 @@ impl Bancroft {
             })
         }
     }
-    pub fn resolve(&self) -> Result<Vector4<f64>, Error> {
-        let b_inv = self.b.try_inverse().ok_or(Error::MatrixInversionError)?;
+    pub fn resolve(&self) -> Result<Vector4<f64>, ErrorKind> {
+        let b_inv = self
+            .b
+            .try_inverse()
+            .ok_or(ErrorKind::MatrixInversionError)?;
         let b_1 = b_inv * self.ones;
         let b_a = b_inv * self.a;
         let a = lorentz_4_4(b_1, b_1, &self.m);
@@ -114,7 +117,7 @@ impl Bancroft {
                 Ok(solutions.1)
             }
         } else if delta < 0.0 {
-            Err(Error::BancroftImaginarySolution)
+            Err(ErrorKind::BancroftImaginarySolution)
         } else {
             let x = -b / a / 2.0;
             Ok(self.m * b_inv * (x * self.ones + self.a))
@@ -0,0 +1,90 @@
+//! BeiDou-2 satellite induced code bias (Wanninger & Beer, 2015)
+use crate::prelude::{Carrier, Constellation, SV};
+
+/// Code bias corrections [m] of the BDS-2 IGSO vehicles, on B1I, B2I and B3I,
+/// from 0° to 90° elevation, by steps of 10°.
+const IGSO_CORRECTIONS_M: [[f64; 10]; 3] = [
+    [
+        -0.55, -0.40, -0.34, -0.23, -0.15, -0.04, 0.09, 0.19, 0.27, 0.35,
+    ],
+    [
+        -0.71, -0.36, -0.33, -0.19, -0.14, -0.03, 0.08, 0.17, 0.24, 0.33,
+    ],
+    [
+        -0.27, -0.23, -0.21, -0.15, -0.11, -0.04, 0.05, 0.14, 0.19, 0.32,
+    ],
+];
+
+/// Code bias corrections [m] of the BDS-2 MEO vehicles, on B1I, B2I and B3I,
+/// from 0° to 90° elevation, by steps of 10°.
+const MEO_CORRECTIONS_M: [[f64; 10]; 3] = [
+    [
+        -0.47, -0.38, -0.32, -0.23, -0.11, 0.06, 0.34, 0.69, 0.97, 1.05,
+    ],
+    [
+        -0.40, -0.31, -0.26, -0.18, -0.06, 0.09, 0.28, 0.48, 0.64, 0.69,
+    ],
+    [
+        -0.22, -0.15, -0.13, -0.10, -0.04, 0.05, 0.14, 0.27, 0.36, 0.47,
+    ],
+];
+
+/// Returns the corrections table that applies to this [SV], if it is
+/// a BDS-2 IGSO or MEO vehicle (GEO and BDS-3 vehicles are not affected).
+fn corrections_table(sv: SV) -> Option<&'static [[f64; 10]; 3]> {
+    if sv.constellation != Constellation::BeiDou {
+        return None;
+    }
+    match sv.prn {
+        6..=10 | 13 | 16 => Some(&IGSO_CORRECTIONS_M),
+        11 | 12 | 14 => Some(&MEO_CORRECTIONS_M),
+        _ => None,
+    }
+}
+
+/// Returns the elevation dependent correction [m], to add to this [SV] pseudo range
+/// on this [Carrier], observed at this elevation [deg]. Returns None when
+/// the vehicle or the signal are not affected.
+pub(crate) fn code_bias_correction_m(sv: SV, carrier: Carrier, elevation_deg: f64) -> Option<f64> {
+    let table = corrections_table(sv)?;
+    let row = match carrier {
+        Carrier::B1I => &table[0],
+        Carrier::B2iB2b => &table[1],
+        Carrier::B3 => &table[2],
+        _ => return None,
+    };
+    // linear interpolation
+    let x = elevation_deg.clamp(0.0, 90.0) / 10.0;
+    let i = (x.floor() as usize).min(8);
+    let frac = x - i as f64;
+    Some(row[i] + frac * (row[i + 1] - row[i]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::code_bias_correction_m;
+    use crate::prelude::{Carrier, SV};
+    use std::str::FromStr;
+
+    #[test]
+    fn bds2_code_bias() {
+        let (igso, meo) = (SV::from_str("C08").unwrap(), SV::from_str("C11").unwrap());
+
+        for (elevation_deg, expected_m) in [(0.0, -0.55), (90.0, 0.35), (45.0, -0.095)] {
+            let bias_m = code_bias_correction_m(igso, Carrier::B1I, elevation_deg).unwrap();
+            assert!((bias_m - expected_m).abs() < 1.0E-9, "{}", elevation_deg);
+        }
+        let bias_m = code_bias_correction_m(meo, Carrier::B3, 75.0).unwrap();
+        assert!((bias_m - 0.315).abs() < 1.0E-9);
+
+        // out of range elevations are clamped
+        let bias_m = code_bias_correction_m(meo, Carrier::B2iB2b, -5.0).unwrap();
+        assert!((bias_m + 0.40).abs() < 1.0E-9);
+
+        // GEO, BDS-3, other signals and constellations are not affected
+        assert!(code_bias_correction_m(SV::from_str("C01").unwrap(), Carrier::B1I, 30.0).is_none());
+        assert!(code_bias_correction_m(SV::from_str("C25").unwrap(), Carrier::B1I, 30.0).is_none());
+        assert!(code_bias_correction_m(igso, Carrier::B1aB1c, 30.0).is_none());
+        assert!(code_bias_correction_m(SV::from_str("G08").unwrap(), Carrier::L1, 30.0).is_none());
+    }
+}
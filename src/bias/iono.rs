@@ -6,6 +6,7 @@ use serde::{Deserialize, Serialize};
 
 /// Ionopheric delay components to attach to any attempt.
 #[derive(Default, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum IonoComponents {
     /// Unknown
     #[default]
@@ -22,6 +23,7 @@ pub enum IonoComponents {
 
 /// Klobuchar Model
 #[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct KbModel {
     /// alpha coefficients
     pub alpha: (f64, f64, f64, f64),
@@ -90,6 +92,7 @@ impl KbModel {
 
 /// Nequick-G Model: is not supported yet.
 #[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct NgModel {
     /// alpha coefficients
     pub a: (f64, f64, f64),
@@ -105,6 +108,7 @@ impl NgModel {
 
 /// BDGIM Model: is not supported yet.
 #[derive(Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BdModel {
     /// Alpha coefficients in TECu
     pub alpha: (f64, f64, f64, f64, f64, f64, f64, f64, f64),
@@ -125,7 +129,7 @@ impl IonoComponents {
             Self::KbModel(model) => model.value(rtm),
             Self::NgModel(model) => model.value(rtm),
             Self::BdModel(model) => model.value(rtm),
-            Self::Stec(..) => 0.0, //TODO
+            Self::Stec(tecu) => 40.3E16 * tecu / rtm.frequency.powi(2),
         }
     }
 }
@@ -6,6 +6,11 @@ pub use tropo::{TropoComponents, TropoModel};
 pub(crate) mod iono;
 pub use iono::{BdModel, IonoComponents, IonosphereBias, KbModel, NgModel};
 
+pub(crate) mod bds2;
+
+mod sbas;
+pub use sbas::{SbasIgp, SbasIonoDegradation, SbasIonoGrid};
+
 pub(crate) struct RuntimeParams {
     pub t: Epoch,
     pub frequency: f64,
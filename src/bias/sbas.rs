@@ -0,0 +1,320 @@
+//! SBAS ionospheric grid (message types 18 and 26)
+use std::collections::BTreeMap;
+
+use crate::prelude::{Carrier, Duration, Epoch};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Ionospheric shell height [km]
+const IONO_SHELL_HEIGHT_KM: f64 = 350.0;
+
+/// Earth radius [km], as defined by DO-229
+const EARTH_RADIUS_KM: f64 = 6378.1363;
+
+/// Default maximal age of the ionospheric corrections (timeout)
+const DEFAULT_MAX_AGE_S: f64 = 600.0;
+
+/// GIVEI indicating a grid point that is not monitored
+const GIVEI_NOT_MONITORED: u8 = 15;
+
+/// GIVE variances [m²], indexed by GIVEI
+const GIVE_VARIANCES_M2: [f64; 15] = [
+    0.0084, 0.0333, 0.0749, 0.1331, 0.2079, 0.2994, 0.4075, 0.5322, 0.6735, 0.8315, 1.1974, 1.8709,
+    3.3260, 20.7870, 187.0826,
+];
+
+/// [SbasIgp] describes the vertical delay at one Ionospheric Grid Point
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SbasIgp {
+    /// [Epoch] of reception
+    pub t: Epoch,
+    /// Vertical delay [m] on L1
+    pub vertical_delay_m: f64,
+    /// Grid Ionospheric Vertical Error Indicator: 15 means "not monitored"
+    pub givei: u8,
+}
+
+impl SbasIgp {
+    /// Returns GIVE variance [m²], if this IGP is monitored
+    fn give_variance_m2(&self) -> Option<f64> {
+        GIVE_VARIANCES_M2.get(self.givei as usize).copied()
+    }
+}
+
+/// [SbasIonoDegradation] parameters (message type 10), the ionospheric
+/// variance increases with the age of the corrections.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SbasIonoDegradation {
+    /// Bound on the difference between successive corrections [m]
+    pub step_m: f64,
+    /// Minimal update interval of the corrections [s]
+    pub interval_s: f64,
+    /// Rate of change of the corrections [m/s]
+    pub ramp_m_s: f64,
+}
+
+impl SbasIonoDegradation {
+    /// Returns degradation [m] of corrections of this age
+    fn epsilon_m(&self, age: Duration) -> f64 {
+        let age_s = age.to_seconds().max(0.0);
+        let steps = if self.interval_s > 0.0 {
+            (age_s / self.interval_s).floor()
+        } else {
+            0.0
+        };
+        self.step_m * steps + self.ramp_m_s * age_s
+    }
+}
+
+/// [SbasIonoGrid] interpolates SBAS Ionospheric Grid Point delays (DO-229 appendix A),
+/// for single frequency users within SBAS coverage. It is an additional
+/// ionospheric source to the [Solver] (see [Solver::set_sbas_iono_grid]),
+/// which prevails over [IonoComponents] of each [Candidate], when it applies.
+///
+/// IGPs that are not monitored, or older than [Self::with_max_age], are not used.
+/// The resulting variance (GIVE and its degradation)
+/// deweights the vehicle. Polar regions (beyond 75°) are not supported.
+#[derive(Debug, Clone)]
+pub struct SbasIonoGrid {
+    max_age: Duration,
+    degradation: SbasIonoDegradation,
+    /// IGPs, per (latitude, longitude) [°]
+    igps: BTreeMap<(i16, i16), SbasIgp>,
+}
+
+impl Default for SbasIonoGrid {
+    fn default() -> Self {
+        Self {
+            max_age: Duration::from_seconds(DEFAULT_MAX_AGE_S),
+            degradation: Default::default(),
+            igps: Default::default(),
+        }
+    }
+}
+
+impl SbasIonoGrid {
+    /// Builds new empty [SbasIonoGrid]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Copies and returns [SbasIonoGrid] with maximal age of the IGPs
+    pub fn with_max_age(&self, max_age: Duration) -> Self {
+        let mut s = self.clone();
+        s.max_age = max_age;
+        s
+    }
+    /// Copies and returns [SbasIonoGrid] with [SbasIonoDegradation] parameters
+    pub fn with_degradation(&self, degradation: SbasIonoDegradation) -> Self {
+        let mut s = self.clone();
+        s.degradation = degradation;
+        s
+    }
+    /// Updates [SbasIgp] located at this latitude and longitude [°]
+    pub fn insert(&mut self, lat_deg: i16, lon_deg: i16, igp: SbasIgp) {
+        self.igps
+            .insert((lat_deg, Self::wrap_longitude(lon_deg)), igp);
+    }
+    /// Ages out IGPs that may no longer be used after [Epoch]
+    pub fn discard_outdated(&mut self, t: Epoch) {
+        let max_age = self.max_age;
+        self.igps.retain(|_, igp| t - igp.t <= max_age);
+    }
+    /// Returns true if no IGP is known
+    pub fn is_empty(&self) -> bool {
+        self.igps.is_empty()
+    }
+    fn wrap_longitude(lon_deg: i16) -> i16 {
+        (lon_deg + 180).rem_euclid(360) - 180
+    }
+    /// Returns usable [SbasIgp] at this location, with its ionospheric grid variance [m²]
+    fn igp(&self, t: Epoch, lat_deg: i16, lon_deg: i16) -> Option<(f64, f64)> {
+        let igp = self.igps.get(&(lat_deg, Self::wrap_longitude(lon_deg)))?;
+        if igp.givei >= GIVEI_NOT_MONITORED || (t - igp.t).abs() > self.max_age {
+            return None;
+        }
+        let epsilon_m = self.degradation.epsilon_m(t - igp.t);
+        Some((
+            igp.vertical_delay_m,
+            igp.give_variance_m2()? + epsilon_m.powi(2),
+        ))
+    }
+    /// Returns Ionospheric Pierce Point (latitude, longitude) [rad],
+    /// from the receiver location [rad] and the vehicle elevation and azimuth [rad]
+    pub(crate) fn pierce_point(rx_rad: (f64, f64), elev_rad: f64, azim_rad: f64) -> (f64, f64) {
+        let (lat_rx, lon_rx) = rx_rad;
+        let fract = EARTH_RADIUS_KM / (EARTH_RADIUS_KM + IONO_SHELL_HEIGHT_KM);
+        let psi = std::f64::consts::FRAC_PI_2 - elev_rad - (fract * elev_rad.cos()).asin();
+        let lat_pp = (lat_rx.sin() * psi.cos() + lat_rx.cos() * psi.sin() * azim_rad.cos()).asin();
+        let lon_pp = lon_rx + (psi.sin() * azim_rad.sin() / lat_pp.cos()).asin();
+        (lat_pp, lon_pp)
+    }
+    /// Interpolates vertical delay [m] and variance [m²] on L1 at this pierce point [°].
+    /// Four grid points are used when available, otherwise three
+    /// if the pierce point lies within the triangle they define.
+    pub fn vertical_delay(&self, t: Epoch, lat_deg: f64, lon_deg: f64) -> Option<(f64, f64)> {
+        let lon_deg = (lon_deg + 180.0).rem_euclid(360.0) - 180.0;
+        let (lat_step, lon_step) = match lat_deg.abs() {
+            lat if lat <= 60.0 => (5.0, 5.0),
+            lat if lat <= 75.0 => (5.0, 10.0),
+            _ => return None,
+        };
+        let lat_0 = (lat_deg / lat_step).floor() * lat_step;
+        let lon_0 = (lon_deg / lon_step).floor() * lon_step;
+        let (x, y) = ((lon_deg - lon_0) / lon_step, (lat_deg - lat_0) / lat_step);
+
+        // cell corners, indexed by (x, y)
+        let corner = |dx: f64, dy: f64| {
+            self.igp(
+                t,
+                (lat_0 + dy * lat_step) as i16,
+                (lon_0 + dx * lon_step) as i16,
+            )
+        };
+        let corners = [
+            corner(0.0, 0.0),
+            corner(1.0, 0.0),
+            corner(0.0, 1.0),
+            corner(1.0, 1.0),
+        ];
+
+        let missing = corners
+            .iter()
+            .enumerate()
+            .filter(|(_, igp)| igp.is_none())
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+
+        match missing.as_slice() {
+            [] => {
+                let weights = [(1.0 - x) * (1.0 - y), x * (1.0 - y), (1.0 - x) * y, x * y];
+                let (mut delay_m, mut var_m2) = (0.0, 0.0);
+                for (w, igp) in weights.iter().zip(corners.iter()) {
+                    let (igp_delay_m, igp_var_m2) = igp.unwrap();
+                    delay_m += w * igp_delay_m;
+                    var_m2 += w * igp_var_m2;
+                }
+                Some((delay_m, var_m2))
+            },
+            [missing] => {
+                // origin is the corner opposite to the missing one
+                let origin = 3 - missing;
+                let (u, v) = (
+                    (x - (origin & 1) as f64).abs(),
+                    (y - (origin >> 1) as f64).abs(),
+                );
+                if u + v > 1.0 {
+                    return None;
+                }
+                let (d_o, var_o) = corners[origin].unwrap();
+                let (d_u, var_u) = corners[origin ^ 1].unwrap();
+                let (d_v, var_v) = corners[origin ^ 2].unwrap();
+                let (w_o, w_u, w_v) = (1.0 - u - v, u, v);
+                Some((
+                    w_o * d_o + w_u * d_u + w_v * d_v,
+                    w_o * var_o + w_u * var_u + w_v * var_v,
+                ))
+            },
+            _ => None,
+        }
+    }
+    /// Returns slant delay [m] and its standard deviation [m] on this [Carrier],
+    /// from the receiver location [rad] and the vehicle elevation and azimuth [rad]
+    pub(crate) fn slant_delay(
+        &self,
+        t: Epoch,
+        rx_rad: (f64, f64),
+        elev_rad: f64,
+        azim_rad: f64,
+        carrier: Carrier,
+    ) -> Option<(f64, f64)> {
+        let (lat_pp, lon_pp) = Self::pierce_point(rx_rad, elev_rad, azim_rad);
+        let (delay_m, var_m2) = self.vertical_delay(t, lat_pp.to_degrees(), lon_pp.to_degrees())?;
+        let fract = EARTH_RADIUS_KM * elev_rad.cos() / (EARTH_RADIUS_KM + IONO_SHELL_HEIGHT_KM);
+        let obliquity = 1.0 / (1.0 - fract.powi(2)).sqrt();
+        let scaling = (Carrier::L1.frequency() / carrier.frequency()).powi(2);
+        Some((
+            obliquity * scaling * delay_m,
+            obliquity * scaling * var_m2.sqrt(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SbasIgp, SbasIonoDegradation, SbasIonoGrid};
+    use crate::prelude::{Carrier, Duration, Epoch};
+    use std::str::FromStr;
+
+    #[test]
+    fn sbas_iono_grid_interpolation() {
+        let t = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let mut grid = SbasIonoGrid::new();
+        for (lat, lon, delay_m) in [(45, 0, 2.0), (45, 5, 4.0), (50, 0, 2.0), (50, 5, 4.0)] {
+            grid.insert(
+                lat,
+                lon,
+                SbasIgp {
+                    t,
+                    vertical_delay_m: delay_m,
+                    givei: 0,
+                },
+            );
+        }
+        let (delay_m, var_m2) = grid.vertical_delay(t, 47.5, 2.5).unwrap();
+        assert!((delay_m - 3.0).abs() < 1.0E-9);
+        assert!((var_m2 - 0.0084).abs() < 1.0E-9);
+
+        // outside of coverage
+        assert!(grid.vertical_delay(t, 40.0, 2.5).is_none());
+
+        // vertical at zenith, scaled on other frequencies
+        let (delay_m, _) = grid
+            .slant_delay(
+                t,
+                (47.5_f64.to_radians(), 2.5_f64.to_radians()),
+                90.0_f64.to_radians(),
+                0.0,
+                Carrier::L1,
+            )
+            .unwrap();
+        assert!((delay_m - 3.0).abs() < 1.0E-6);
+        let (l5_delay_m, _) = grid
+            .slant_delay(
+                t,
+                (47.5_f64.to_radians(), 2.5_f64.to_radians()),
+                90.0_f64.to_radians(),
+                0.0,
+                Carrier::L5,
+            )
+            .unwrap();
+        assert!(l5_delay_m > delay_m);
+
+        // not monitored IGP: three point interpolation, within the triangle only
+        grid.insert(
+            50,
+            5,
+            SbasIgp {
+                t,
+                vertical_delay_m: 4.0,
+                givei: 15,
+            },
+        );
+        let (delay_m, _) = grid.vertical_delay(t, 46.0, 1.0).unwrap();
+        assert!((delay_m - 2.4).abs() < 1.0E-9);
+        assert!(grid.vertical_delay(t, 49.0, 4.0).is_none());
+
+        // degradation and timeout
+        let grid = grid.with_degradation(SbasIonoDegradation {
+            ramp_m_s: 0.001,
+            ..Default::default()
+        });
+        let t = t + Duration::from_seconds(100.0);
+        let (_, var_m2) = grid.vertical_delay(t, 45.0, 0.0).unwrap();
+        assert!((var_m2 - 0.0084 - 0.01).abs() < 1.0E-9);
+        let t = t + Duration::from_seconds(600.0);
+        assert!(grid.vertical_delay(t, 45.0, 0.0).is_none());
+    }
+}
@@ -1,8 +1,11 @@
 use crate::bias::RuntimeParams;
 use crate::cfg::Error;
-use log::debug;
+use crate::logging::debug;
 use std::f64::consts::PI;
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 #[derive(Default, Copy, Clone, Debug)]
 pub enum TropoModel {
     #[default]
@@ -38,6 +41,7 @@ impl std::str::FromStr for TropoModel {
 
 /// Tropospheric delay components you can provide, to improve the internal model.
 #[derive(Default, Copy, Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TropoComponents {
     /// Use this to use the internal meteorological model
     #[default]
@@ -1,27 +1,27 @@
 //! Position solving candidate
+use crate::logging::debug;
 use hifitime::Unit;
 use itertools::Itertools;
-use log::debug;
-use map_3d::{ecef2aer, ecef2geodetic, Ellipsoid};
 use std::cmp::Ordering;
 use std::f64::consts::PI;
 
-use nyx::{
-    cosmic::SPEED_OF_LIGHT_M_S,
-    linalg::{OMatrix, OVector, U8},
-};
+use nalgebra::{base::dimension::U8, OMatrix, OVector};
 
 use crate::{
-    bias::RuntimeParams as BiasRuntimeParams,
-    constants::Constants,
-    navigation::SVInput,
+    bias::{bds2::code_bias_correction_m, RuntimeParams as BiasRuntimeParams},
+    constants::{Constants, SPEED_OF_LIGHT_M_S},
+    navigation::{LineOfSight, SVInput},
     prelude::{
-        Carrier, Config, Duration, Epoch, Error, IonoComponents, IonosphereBias, Method, Orbit,
-        TropoComponents, TropoModel, Vector3, SV,
+        Carrier, Config, Duration, Epoch, ErrorKind, IonoComponents, IonosphereBias, Method, Orbit,
+        SbasIonoGrid, TimeScale, TropoComponents, TropoModel, Vector3, SV,
     },
 };
 
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 #[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Observation {
     /// [Carrier]
     pub carrier: Carrier,
@@ -159,8 +159,16 @@ impl Combination {
     }
 }
 
+/// Secondary signal rank, in signal combinations (lowest first):
+/// the low noise AltBOC signals (Galileo E5, BeiDou B2) are prefered.
+fn secondary_rank(carrier: Carrier) -> u8 {
+    match carrier {
+        Carrier::E5 | Carrier::B2 => 0,
+        _ => 1,
+    }
+}
+
 /// Position solving candidate
-#[derive(Clone)]
 pub struct Candidate {
     /// [SV]
     pub sv: SV,
@@ -170,10 +178,18 @@ pub struct Candidate {
     pub(crate) orbit: Option<Orbit>,
     /// SV group delay expressed as a [Duration]
     pub(crate) tgd: Option<Duration>,
+    /// [GalileoNavMessage] the group delay was decoded from
+    pub(crate) tgd_nav: Option<GalileoNavMessage>,
+    /// [BeiDouGroupDelays], selected per ranging signal
+    pub(crate) tgd_bds: Option<BeiDouGroupDelays>,
+    /// Prefered ranging [Carrier], according to the signal priority
+    pub(crate) prefered_carrier: Option<Carrier>,
     /// Windup term in signal cycles
     pub(crate) wind_up: f64,
     /// [ClockCorrection]
     pub(crate) clock_corr: Option<ClockCorrection>,
+    /// Broadcast [ClockPolynomial], evaluated at transmission time
+    pub(crate) clock_poly: Option<ClockPolynomial>,
     /// Local [Observation]s
     pub(crate) observations: Vec<Observation>,
     /// Remote [Observation]s
@@ -184,6 +200,8 @@ pub struct Candidate {
     pub(crate) azimuth_deg: Option<f64>,
     /// Resolved bias
     pub(crate) iono_bias: f64,
+    /// Standard deviation [m] of the resolved iono bias, when known
+    pub(crate) iono_sigma: Option<f64>,
     /// Resolved bias
     pub(crate) tropo_bias: f64,
     /// [IonoComponents]
@@ -192,11 +210,144 @@ pub struct Candidate {
     pub(crate) tropo_components: TropoComponents,
 }
 
+impl Clone for Candidate {
+    fn clone(&self) -> Self {
+        Self {
+            sv: self.sv,
+            t: self.t,
+            orbit: self.orbit,
+            tgd: self.tgd,
+            tgd_nav: self.tgd_nav,
+            tgd_bds: self.tgd_bds,
+            prefered_carrier: self.prefered_carrier,
+            wind_up: self.wind_up,
+            clock_corr: self.clock_corr,
+            clock_poly: self.clock_poly,
+            observations: self.observations.clone(),
+            remote_obs: self.remote_obs.clone(),
+            elevation_deg: self.elevation_deg,
+            azimuth_deg: self.azimuth_deg,
+            iono_bias: self.iono_bias,
+            iono_sigma: self.iono_sigma,
+            tropo_bias: self.tropo_bias,
+            iono_components: self.iono_components,
+            tropo_components: self.tropo_components,
+        }
+    }
+    // reuses the observation buffers
+    fn clone_from(&mut self, source: &Self) {
+        self.sv = source.sv;
+        self.t = source.t;
+        self.orbit = source.orbit;
+        self.tgd = source.tgd;
+        self.tgd_nav = source.tgd_nav;
+        self.tgd_bds = source.tgd_bds;
+        self.prefered_carrier = source.prefered_carrier;
+        self.wind_up = source.wind_up;
+        self.clock_corr = source.clock_corr;
+        self.clock_poly = source.clock_poly;
+        self.observations.clone_from(&source.observations);
+        self.remote_obs.clone_from(&source.remote_obs);
+        self.elevation_deg = source.elevation_deg;
+        self.azimuth_deg = source.azimuth_deg;
+        self.iono_bias = source.iono_bias;
+        self.iono_sigma = source.iono_sigma;
+        self.tropo_bias = source.tropo_bias;
+        self.iono_components = source.iono_components;
+        self.tropo_components = source.tropo_components;
+    }
+}
+
+/// Galileo navigation message, clock corrections and broadcast group delays (BGD)
+/// were decoded from. Each message comes with its own clock model and BGD reference,
+/// that only apply to its own signals.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GalileoNavMessage {
+    /// I/NAV (E1-B, E5b-I), with BGD(E1, E5b)
+    INav,
+    /// F/NAV (E5a-I), with BGD(E1, E5a)
+    FNav,
+}
+
+impl GalileoNavMessage {
+    /// Returns the [Carrier] that BGD is referenced to, besides [Carrier::E1]
+    pub fn bgd_carrier(&self) -> Carrier {
+        match self {
+            Self::INav => Carrier::E5B,
+            Self::FNav => Carrier::E5A,
+        }
+    }
+    /// Returns the group delay that applies to this ranging [Carrier],
+    /// from BGD of this message. None if BGD does not apply to this [Carrier].
+    pub(crate) fn group_delay(&self, bgd: Duration, carrier: Carrier) -> Option<Duration> {
+        // carriers are compared by frequency (E1 may be described as L1)
+        let (f1, f) = (Carrier::E1.frequency(), carrier.frequency());
+        if f == f1 {
+            Some(bgd)
+        } else if f == self.bgd_carrier().frequency() {
+            Some(bgd * (f1 / f).powi(2))
+        } else {
+            None
+        }
+    }
+}
+
+/// [BeiDouGroupDelays] as broadcasted by BeiDou vehicles. The BeiDou clock
+/// is referenced to B3I: each other signal has its own group delay.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BeiDouGroupDelays {
+    /// TGD1 (B1I), from D1/D2 messages
+    pub tgd1: Duration,
+    /// TGD2 (B2I), from D1/D2 messages
+    pub tgd2: Duration,
+    /// TGD B1Cp, from B-CNAV1 messages
+    pub tgd_b1cp: Option<Duration>,
+    /// TGD B2ap, from B-CNAV2 messages
+    pub tgd_b2ap: Option<Duration>,
+}
+
+impl BeiDouGroupDelays {
+    /// Builds [BeiDouGroupDelays] from the D1/D2 messages (TGD1, TGD2)
+    pub fn new(tgd1: Duration, tgd2: Duration) -> Self {
+        Self {
+            tgd1,
+            tgd2,
+            ..Default::default()
+        }
+    }
+    /// Copies and returns [BeiDouGroupDelays] with the B-CNAV group delays (B1Cp, B2ap)
+    pub fn with_cnav(&self, tgd_b1cp: Duration, tgd_b2ap: Duration) -> Self {
+        let mut s = *self;
+        s.tgd_b1cp = Some(tgd_b1cp);
+        s.tgd_b2ap = Some(tgd_b2ap);
+        s
+    }
+    /// Returns the group delay that applies to this ranging [Carrier].
+    /// None when it is not broadcasted for this [Carrier] (B2 AltBOC).
+    pub(crate) fn group_delay(&self, carrier: Carrier) -> Option<Duration> {
+        match carrier {
+            Carrier::B1I => Some(self.tgd1),
+            Carrier::B2iB2b => Some(self.tgd2),
+            Carrier::B3 => Some(Duration::ZERO),
+            Carrier::B1aB1c => self.tgd_b1cp,
+            Carrier::B2A => self.tgd_b2ap,
+            _ => None,
+        }
+    }
+}
+
 #[derive(Default, Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ClockCorrection {
     /// Correction to associated timescale, expressed as [Duration]
     pub duration: Duration,
     pub(crate) needs_relativistic_correction: bool,
+    /// [GalileoNavMessage] this correction was decoded from
+    pub(crate) galileo_nav: Option<GalileoNavMessage>,
+    /// Clock drift [s/s], when known
+    pub(crate) drift: Option<f64>,
 }
 
 impl ClockCorrection {
@@ -205,6 +356,8 @@ impl ClockCorrection {
         Self {
             duration,
             needs_relativistic_correction: false,
+            galileo_nav: None,
+            drift: None,
         }
     }
     /// Define a new [ClockCorrection] that does not integrate relativistic corrections
@@ -212,6 +365,71 @@ impl ClockCorrection {
         Self {
             duration,
             needs_relativistic_correction: true,
+            galileo_nav: None,
+            drift: None,
+        }
+    }
+    /// Copies and returns [ClockCorrection] decoded from this [GalileoNavMessage]
+    pub fn with_galileo_nav(&self, nav: GalileoNavMessage) -> Self {
+        let mut s = *self;
+        s.galileo_nav = Some(nav);
+        s
+    }
+    /// Copies and returns [ClockCorrection] with this clock drift [s/s].
+    /// The drift is then accounted for in the range rate model (Doppler).
+    pub fn with_drift(&self, drift_s_s: f64) -> Self {
+        let mut s = *self;
+        s.drift = Some(drift_s_s);
+        s
+    }
+}
+
+/// [ClockPolynomial] is the SV clock model of the broadcast navigation message.
+/// When proposed rather than a [ClockCorrection], the correction is evaluated
+/// at the signal transmission time by the [Solver](crate::prelude::Solver).
+#[derive(Default, Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClockPolynomial {
+    /// Time of Clock
+    pub toc: Epoch,
+    /// Clock bias [s]
+    pub af0: f64,
+    /// Clock drift [s/s]
+    pub af1: f64,
+    /// Clock drift rate [s/s^2]
+    pub af2: f64,
+    /// [GalileoNavMessage] this polynomial was decoded from
+    pub(crate) galileo_nav: Option<GalileoNavMessage>,
+}
+
+impl ClockPolynomial {
+    /// Define a new [ClockPolynomial], from its reference time and coefficients
+    pub fn new(toc: Epoch, af0: f64, af1: f64, af2: f64) -> Self {
+        Self {
+            toc,
+            af0,
+            af1,
+            af2,
+            galileo_nav: None,
+        }
+    }
+    /// Copies and returns [ClockPolynomial] decoded from this [GalileoNavMessage]
+    pub fn with_galileo_nav(&self, nav: GalileoNavMessage) -> Self {
+        let mut s = *self;
+        s.galileo_nav = Some(nav);
+        s
+    }
+    /// Returns the [ClockCorrection] at [Epoch]. The relativistic effect is not included:
+    /// it is resolved from the SV state (see `modeling.relativistic_clock_bias`).
+    pub fn clock_correction(&self, t: Epoch) -> ClockCorrection {
+        let dt = (t - self.toc).to_seconds();
+        let correction = ClockCorrection::without_relativistic_correction(Duration::from_seconds(
+            self.af0 + self.af1 * dt + self.af2 * dt.powi(2),
+        ))
+        .with_drift(self.af1 + 2.0 * self.af2 * dt);
+        match self.galileo_nav {
+            Some(nav) => correction.with_galileo_nav(nav),
+            None => correction,
         }
     }
 }
@@ -241,6 +459,7 @@ impl Candidate {
             t,
             observations,
             iono_bias: 0.0,
+            iono_sigma: None,
             tropo_bias: 0.0,
             wind_up: 0.0_f64,
             remote_obs: Vec::new(),
@@ -248,11 +467,84 @@ impl Candidate {
             elevation_deg: None,
             orbit: None,
             tgd: None,
+            tgd_nav: None,
+            tgd_bds: None,
+            prefered_carrier: None,
             clock_corr: None,
+            clock_poly: None,
             iono_components: IonoComponents::Unknown,
             tropo_components: TropoComponents::Unknown,
         }
     }
+    /// Builds a new [Candidate] from any source of [Observation]s
+    /// (for example, decoded straight from a receiver frame), without intermediate [Vec].
+    pub fn from_observations<I: IntoIterator<Item = Observation>>(
+        sv: SV,
+        t: Epoch,
+        observations: I,
+    ) -> Self {
+        Self::new(sv, t, observations.into_iter().collect())
+    }
+    /// Refreshes this [Candidate] for a new sampling [Epoch], replacing its [Observation]s.
+    /// The observation buffers are reused, so a real-time pool can be maintained
+    /// without allocation at each [Epoch]. All other attributes (corrections, remote
+    /// observations) are reset and need to be defined again.
+    pub fn refresh<I: IntoIterator<Item = Observation>>(&mut self, t: Epoch, observations: I) {
+        let mut buffer = std::mem::take(&mut self.observations);
+        let mut remote = std::mem::take(&mut self.remote_obs);
+        buffer.clear();
+        buffer.extend(observations);
+        remote.clear();
+        *self = Self::new(self.sv, t, buffer);
+        self.remote_obs = remote;
+    }
+    /// Returns the approximate transmission [Epoch], from the prefered pseudo range
+    /// (SV clock offset and group delay not accounted for).
+    pub(crate) fn approximate_transmission_time(&self) -> Epoch {
+        let dt_tx = self
+            .prefered_pseudorange()
+            .and_then(|obs| obs.pseudo)
+            .map(|pr| Duration::from_seconds(pr / SPEED_OF_LIGHT_M_S))
+            .unwrap_or_default();
+        self.t - dt_tx
+    }
+    /// Expresses the sampling [Epoch] in this [TimeScale] (same instant)
+    pub(crate) fn align_time_scale(&mut self, ts: TimeScale) {
+        if self.t.time_scale != ts {
+            self.t = self.t.to_time_scale(ts);
+        }
+    }
+    /// Propagates the observations to this [Epoch] (same timescale), using the range rate
+    /// derived from the Doppler observations: pseudo and phase ranges then read as if they had been
+    /// sampled at `t`. Signals without Doppler use the range rate of another signal.
+    /// Returns false if no Doppler observation exists (observations are left untouched).
+    pub(crate) fn align_epoch(&mut self, t: Epoch) -> bool {
+        if self.t == t {
+            return true;
+        }
+        // geometric range rate, which dominates over the (dispersive) ionospheric drift
+        let Some(range_rate_m_s) = self.observations.iter().find_map(|obs| {
+            obs.doppler
+                .map(|doppler| -doppler * obs.carrier.wavelength())
+        }) else {
+            return false;
+        };
+        let dt_s = (t - self.t).to_seconds();
+        for obs in self.observations.iter_mut() {
+            let range_rate_m_s = obs
+                .doppler
+                .map(|doppler| -doppler * obs.carrier.wavelength())
+                .unwrap_or(range_rate_m_s);
+            if let Some(pseudo) = obs.pseudo.as_mut() {
+                *pseudo += range_rate_m_s * dt_s;
+            }
+            if let Some(phase) = obs.phase.as_mut() {
+                *phase += range_rate_m_s * dt_s;
+            }
+        }
+        self.t = t;
+        true
+    }
     /// Define Total Group Delay [TDG] if you know it.
     /// This will increase your accuracy in PPP opmode for up to 10m.
     /// If you know the [TGD] value, you should specifiy especially on first iteration,
@@ -260,12 +552,37 @@ impl Candidate {
     /// negatively.
     pub fn set_group_delay(&mut self, tgd: Duration) {
         self.tgd = Some(tgd);
+        self.tgd_nav = None;
+        self.tgd_bds = None;
+    }
+    /// Define Galileo BGD, decoded from this [GalileoNavMessage].
+    /// The BGD is then scaled to the ranging signal. This [GalileoNavMessage]
+    /// must match the one the [ClockCorrection] was decoded from (if specified),
+    /// and the ranging signal: otherwise, the resolution attempt fails.
+    pub fn set_galileo_group_delay(&mut self, bgd: Duration, nav: GalileoNavMessage) {
+        self.tgd = Some(bgd);
+        self.tgd_nav = Some(nav);
+        self.tgd_bds = None;
+    }
+    /// Define [BeiDouGroupDelays]: the group delay of the ranging signal is then selected
+    /// (TGD1 for B1I, TGD2 for B2I, none for B3I..), rather than applied as is.
+    pub fn set_beidou_group_delays(&mut self, delays: BeiDouGroupDelays) {
+        self.tgd = None;
+        self.tgd_nav = None;
+        self.tgd_bds = Some(delays);
     }
     /// Define on board Clock Correction if you know it.
     /// This is mandatory for PPP and will increase your accuracy by hundreds of km.
     pub fn set_clock_correction(&mut self, corr: ClockCorrection) {
         self.clock_corr = Some(corr);
     }
+    /// Define the broadcast [ClockPolynomial], rather than a precomputed [ClockCorrection]:
+    /// the correction is then evaluated at transmission time. Any [ClockCorrection]
+    /// that was defined is discarded.
+    pub fn set_clock_polynomial(&mut self, poly: ClockPolynomial) {
+        self.clock_corr = None;
+        self.clock_poly = Some(poly);
+    }
     /// Define [TropoComponents] that should apply to self and bypass our
     /// internal Meteorological table model. Accurate Tropospheric perturbation compensation
     /// will increase your PPP accuracy by tens of meters. This has no effect
@@ -283,7 +600,7 @@ impl Candidate {
     }
     /// Provide remote [Observation]s observed by remote reference site. Not required if you intend to navigate in PPP mode.
     pub fn set_remote_observations(&mut self, remote: Vec<Observation>) {
-        self.remote_obs = remote.clone();
+        self.remote_obs = remote;
     }
     /// Provide one remote [Observation] realized on remote reference site. Not required if you intend to navigate in PPP mode.
     pub fn add_remote_observation(&mut self, remote: Observation) {
@@ -312,7 +629,8 @@ impl Candidate {
         y: &mut OVector<f64, U8>,
         g: &mut OMatrix<f64, U8, U8>,
         apriori: (f64, f64, f64),
-    ) -> Result<SVInput, Error> {
+        los: Option<LineOfSight>,
+    ) -> Result<SVInput, ErrorKind> {
         // When RTK is feasible, it is always prefered,
         // because it is much easier and has immediate accuracy.
         if self.is_rtk_compatible() {
@@ -320,7 +638,7 @@ impl Candidate {
             self.rtk_matrix_contribution(cfg, row, y, g)
         } else {
             debug!("{}({}): ppp resolution attempt", self.t, self.sv);
-            self.ppp_matrix_contribution(cfg, row, y, g, apriori)
+            self.ppp_matrix_contribution(cfg, row, y, g, apriori, los)
         }
     }
     /// Matrix conribution, in case of PPP resolution.
@@ -335,22 +653,20 @@ impl Candidate {
         y: &mut OVector<f64, U8>,
         g: &mut OMatrix<f64, U8, U8>,
         apriori: (f64, f64, f64),
-    ) -> Result<SVInput, Error> {
+        los: Option<LineOfSight>,
+    ) -> Result<SVInput, ErrorKind> {
         let mut sv_input = SVInput::default();
-        let orbit = self.orbit.ok_or(Error::UnresolvedState)?;
+        let orbit = self.orbit.ok_or(ErrorKind::UnresolvedState)?;
+        let los = los.ok_or(ErrorKind::UnresolvedState)?;
         let state = orbit.to_cartesian_pos_vel() * 1.0E3;
 
         let (x0_m, y0_m, z0_m) = apriori;
         let (sv_x_m, sv_y_m, sv_z_m) = (state[0], state[1], state[2]);
 
-        let (lat0, lon0, alt0) = ecef2geodetic(x0_m, y0_m, z0_m, Ellipsoid::WGS84);
-        let (azimuth, elevation, _) =
-            ecef2aer(sv_x_m, sv_y_m, sv_z_m, lat0, lon0, alt0, Ellipsoid::WGS84);
-        sv_input.elevation = elevation.to_degrees();
-        sv_input.azimuth = azimuth.to_degrees();
+        sv_input.elevation = los.elevation_deg;
+        sv_input.azimuth = los.azimuth_deg;
 
-        let mut rho =
-            ((sv_x_m - x0_m).powi(2) + (sv_y_m - y0_m).powi(2) + (sv_z_m - z0_m).powi(2)).sqrt();
+        let mut rho = los.range_m;
 
         if cfg.modeling.relativistic_path_range {
             let mu = Constants::EARTH_GRAVITATION;
@@ -366,11 +682,9 @@ impl Candidate {
             rho += dr;
         }
 
-        let (x_i, y_i, z_i) = (
-            (x0_m - sv_x_m) / rho,
-            (y0_m - sv_y_m) / rho,
-            (z0_m - sv_z_m) / rho,
-        );
+        // unit vector, scaled by the (relativistic) path range
+        let scale = los.range_m / rho;
+        let (x_i, y_i, z_i) = (los.unit.0 * scale, los.unit.1 * scale, los.unit.2 * scale);
 
         g[(row, 0)] = x_i;
         g[(row, 1)] = y_i;
@@ -380,26 +694,26 @@ impl Candidate {
         let mut models = 0.0_f64;
 
         if cfg.modeling.sv_clock_bias {
-            let corr = self.clock_corr.ok_or(Error::UnknownClockCorrection)?;
+            let corr = self.clock_corr.ok_or(ErrorKind::UnknownClockCorrection)?;
             sv_input.clock_correction = Some(corr.duration);
             models -= corr.duration.to_seconds() * SPEED_OF_LIGHT_M_S;
         }
 
         if cfg.modeling.sv_total_group_delay {
-            models -= self.tgd.unwrap_or_default().to_seconds();
+            models -= self.group_delay()?.unwrap_or_default().to_seconds();
         }
 
         let (pr, frequency) = match cfg.method {
             Method::SPP => {
                 let pr = self
                     .prefered_pseudorange()
-                    .ok_or(Error::MissingPseudoRange)?;
+                    .ok_or(ErrorKind::MissingPseudoRange)?;
                 (pr.pseudo.unwrap(), pr.carrier.frequency())
             },
             Method::CPP | Method::PPP => {
                 let pr = self
                     .code_if_combination()
-                    .ok_or(Error::PseudoRangeCombination)?;
+                    .ok_or(ErrorKind::PseudoRangeCombination)?;
                 (pr.value, pr.rhs.frequency())
             },
         };
@@ -438,6 +752,68 @@ impl Candidate {
         y[row] = pr - rho - models;
         Ok(sv_input)
     }
+    /// Returns a-priori standard deviation [m] of the pseudo range
+    /// this [Candidate] contributes with, according to the [NoiseModel].
+    /// The IF combination amplifies the noise of each signal.
+    pub(crate) fn code_sigma(&self, cfg: &Config) -> f64 {
+        let noise = &cfg.solver.noise;
+        let constellation = self.sv.constellation;
+        match cfg.method {
+            Method::SPP => {
+                let sigma_m = match self.prefered_pseudorange() {
+                    Some(pr) => noise.code_sigma(constellation, pr.carrier),
+                    None => noise.code_sigma_m,
+                };
+                match self.iono_sigma {
+                    Some(iono_sigma_m) => (sigma_m.powi(2) + iono_sigma_m.powi(2)).sqrt(),
+                    None => sigma_m,
+                }
+            },
+            Method::CPP | Method::PPP => match self.code_if_combination() {
+                Some(cmb) => Self::if_sigma(
+                    cmb,
+                    noise.code_sigma(constellation, cmb.rhs),
+                    noise.code_sigma(constellation, cmb.lhs),
+                ),
+                None => noise.code_sigma_m,
+            },
+        }
+    }
+    /// Returns a-priori standard deviation [m] of the phase range
+    /// this [Candidate] contributes with, according to the [NoiseModel].
+    pub(crate) fn phase_sigma(&self, cfg: &Config) -> f64 {
+        let noise = &cfg.solver.noise;
+        let constellation = self.sv.constellation;
+        match cfg.method {
+            Method::SPP => match self.prefered_pseudorange() {
+                Some(pr) => noise.phase_sigma(constellation, pr.carrier),
+                None => noise.phase_sigma_m,
+            },
+            Method::CPP | Method::PPP => match self.phase_if_combination() {
+                Some(cmb) => Self::if_sigma(
+                    cmb,
+                    noise.phase_sigma(constellation, cmb.rhs),
+                    noise.phase_sigma(constellation, cmb.lhs),
+                ),
+                None => noise.phase_sigma_m,
+            },
+        }
+    }
+    /// Returns a-priori standard deviation [m/s] of the Doppler observation
+    /// this [Candidate] is screened with, according to the [NoiseModel].
+    pub(crate) fn doppler_sigma(&self, cfg: &Config) -> f64 {
+        let noise = &cfg.solver.noise;
+        match self.observations.iter().find(|obs| obs.doppler.is_some()) {
+            Some(obs) => noise.doppler_sigma(self.sv.constellation, obs.carrier),
+            None => noise.doppler_sigma_m_s,
+        }
+    }
+    /// Propagates individual standard deviations to the IF [Combination]
+    fn if_sigma(cmb: Combination, sigma_1: f64, sigma_j: f64) -> f64 {
+        let (f_1, f_j) = (cmb.rhs.frequency(), cmb.lhs.frequency());
+        (f_1.powi(4) * sigma_1.powi(2) + f_j.powi(4) * sigma_j.powi(2)).sqrt()
+            / (f_1.powi(2) - f_j.powi(2))
+    }
     /// Matrix contribution, in case of RTK resolution.
     fn rtk_matrix_contribution(
         &self,
@@ -445,8 +821,8 @@ impl Candidate {
         _: usize,
         _: &mut OVector<f64, U8>,
         _: &mut OMatrix<f64, U8, U8>,
-    ) -> Result<SVInput, Error> {
-        Err(Error::MissingRemoteRTKObservation(self.t, self.sv))
+    ) -> Result<SVInput, ErrorKind> {
+        Err(ErrorKind::MissingRemoteRTKObservation)
     }
 }
 
@@ -463,10 +839,11 @@ impl Candidate {
         elevation_deg: f64,
         rx_geo: (f64, f64, f64),
         rx_rad: (f64, f64),
-    ) -> Result<(), Error> {
+        iono_grid: Option<&SbasIonoGrid>,
+    ) -> Result<(), ErrorKind> {
         let pr = self
             .prefered_pseudorange()
-            .ok_or(Error::MissingPseudoRange)?;
+            .ok_or(ErrorKind::MissingPseudoRange)?;
         let rtm = BiasRuntimeParams {
             t: self.t,
             rx_geo,
@@ -481,11 +858,69 @@ impl Candidate {
         }
         if iono_modeling {
             if method == Method::SPP {
-                self.iono_bias = self.iono_components.value(&rtm);
+                let sbas = iono_grid.and_then(|grid| {
+                    grid.slant_delay(
+                        self.t,
+                        rx_rad,
+                        rtm.elevation_rad,
+                        rtm.azimuth_rad,
+                        pr.carrier,
+                    )
+                });
+                if let Some((bias_m, sigma_m)) = sbas {
+                    self.iono_bias = bias_m;
+                    self.iono_sigma = Some(sigma_m);
+                } else {
+                    self.iono_bias = self.iono_components.value(&rtm);
+                    self.iono_sigma = None;
+                }
             }
         }
         Ok(())
     }
+    /// Corrects the BeiDou-2 satellite induced code bias, at this elevation [deg].
+    /// Has no effect on other vehicles.
+    pub(crate) fn apply_bds2_code_bias(&mut self, elevation_deg: f64) {
+        let sv = self.sv;
+        for obs in self.observations.iter_mut() {
+            if let Some(pseudo) = obs.pseudo.as_mut() {
+                if let Some(bias_m) = code_bias_correction_m(sv, obs.carrier, elevation_deg) {
+                    debug!(
+                        "{}({}) - {} code bias {:.3}[m]",
+                        self.t, sv, obs.carrier, bias_m
+                    );
+                    *pseudo += bias_m;
+                }
+            }
+        }
+    }
+    /// Returns the difference [m/s] between the range rate measured by the Doppler
+    /// observation, and the range rate predicted from the SV velocity and this
+    /// receiver state (ECEF position [m] and velocity [m/s]), and the SV clock drift when known.
+    /// The difference contains the receiver clock drift, common to all vehicles.
+    /// Returns None when the SV velocity or the Doppler observation are unknown.
+    pub(crate) fn range_rate_mismatch_m_s(
+        &self,
+        rx_pos_m: Vector3<f64>,
+        rx_vel_m_s: Vector3<f64>,
+    ) -> Option<f64> {
+        let orbit = self.orbit?;
+        if orbit.vmag_km_s() == 0.0 {
+            return None;
+        }
+        let measured_m_s = self.observations.iter().find_map(|obs| {
+            obs.doppler
+                .map(|doppler| -doppler * obs.carrier.wavelength())
+        })?;
+        let pos_vel_m = orbit.to_cartesian_pos_vel() * 1.0E3;
+        let los = Vector3::new(pos_vel_m[0], pos_vel_m[1], pos_vel_m[2]) - rx_pos_m;
+        let sv_vel_m_s = Vector3::new(pos_vel_m[3], pos_vel_m[4], pos_vel_m[5]);
+        let mut predicted_m_s = (sv_vel_m_s - rx_vel_m_s).dot(&los) / los.norm();
+        if let Some(drift_s_s) = self.clock_corr.and_then(|corr| corr.drift) {
+            predicted_m_s -= drift_s_s * SPEED_OF_LIGHT_M_S;
+        }
+        Some(measured_m_s - predicted_m_s)
+    }
     // Pseudo range iterator
     fn pseudo_range_iter(&self) -> Box<dyn Iterator<Item = (Carrier, f64)> + '_> {
         Box::new(self.observations.iter().filter_map(|ob| {
@@ -520,14 +955,60 @@ impl Candidate {
             .map(|c| c.snr)?
     }
     /// Returns one pseudo range observation [m], whatever the frequency.
+    /// Returns group delay that applies to the prefered ranging signal.
+    /// BeiDou group delays are selected for that signal.
+    /// Galileo BGDs are scaled to that signal, and fail with
+    /// [ErrorKind::InconsistentGalileoNavMessage] if the [GalileoNavMessage]s do not match.
+    pub(crate) fn group_delay(&self) -> Result<Option<Duration>, ErrorKind> {
+        if let Some(delays) = self.tgd_bds {
+            let carrier = self
+                .prefered_pseudorange()
+                .ok_or(ErrorKind::MissingPseudoRange)?
+                .carrier;
+            return Ok(delays.group_delay(carrier));
+        }
+        let (tgd, nav) = match (self.tgd, self.tgd_nav) {
+            (Some(tgd), Some(nav)) => (tgd, nav),
+            (tgd, _) => return Ok(tgd),
+        };
+        if let Some(clock_nav) = self.clock_corr.and_then(|corr| corr.galileo_nav) {
+            if clock_nav != nav {
+                return Err(ErrorKind::InconsistentGalileoNavMessage);
+            }
+        }
+        let carrier = self
+            .prefered_pseudorange()
+            .ok_or(ErrorKind::MissingPseudoRange)?
+            .carrier;
+        nav.group_delay(tgd, carrier)
+            .map(Some)
+            .ok_or(ErrorKind::InconsistentGalileoNavMessage)
+    }
+    /// Selects the prefered ranging signal: first observed [Carrier] of this priority list
+    pub(crate) fn prioritize(&mut self, priority: &[Carrier]) {
+        self.prefered_carrier = priority.iter().copied().find(|carrier| {
+            self.observations
+                .iter()
+                .any(|ob| ob.carrier == *carrier && ob.pseudo.is_some())
+        });
+    }
     pub(crate) fn prefered_pseudorange(&self) -> Option<Observation> {
+        if let Some(carrier) = self.prefered_carrier {
+            if let Some(ob) = self
+                .observations
+                .iter()
+                .find(|ob| ob.carrier == carrier && ob.pseudo.is_some())
+            {
+                return Some(ob.clone());
+            }
+        }
         if let Some(c1) = self
             .observations
             .iter()
             .filter(|ob| {
                 matches!(
                     ob.carrier,
-                    Carrier::L1 | Carrier::E1 | Carrier::B1aB1c | Carrier::B1I
+                    Carrier::L1 | Carrier::E1 | Carrier::B1aB1c | Carrier::B1I | Carrier::G1(_)
                 ) && ob.pseudo.is_some()
             })
             .reduce(|k, _| k)
@@ -540,7 +1021,11 @@ impl Candidate {
                     ob.pseudo.is_some()
                         && !matches!(
                             ob.carrier,
-                            Carrier::L1 | Carrier::E1 | Carrier::B1aB1c | Carrier::B1I
+                            Carrier::L1
+                                | Carrier::E1
+                                | Carrier::B1aB1c
+                                | Carrier::B1I
+                                | Carrier::G1(_)
                         )
                 })
                 .reduce(|k, _| k)
@@ -577,7 +1062,7 @@ impl Candidate {
             .filter(|(signal, _)| {
                 matches!(
                     signal,
-                    Carrier::L1 | Carrier::E1 | Carrier::B1aB1c | Carrier::B1I
+                    Carrier::L1 | Carrier::E1 | Carrier::B1aB1c | Carrier::B1I | Carrier::G1(_)
                 )
             })
             .reduce(|k, _| k)
@@ -588,7 +1073,7 @@ impl Candidate {
             .filter(|(signal, _)| {
                 matches!(
                     signal,
-                    Carrier::L1 | Carrier::E1 | Carrier::B1aB1c | Carrier::B1I
+                    Carrier::L1 | Carrier::E1 | Carrier::B1aB1c | Carrier::B1I | Carrier::G1(_)
                 )
             })
             .reduce(|k, _| k)
@@ -599,10 +1084,10 @@ impl Candidate {
             .filter(|(signal, _)| {
                 !matches!(
                     signal,
-                    Carrier::L1 | Carrier::E1 | Carrier::B1aB1c | Carrier::B1I
+                    Carrier::L1 | Carrier::E1 | Carrier::B1aB1c | Carrier::B1I | Carrier::G1(_)
                 )
             })
-            .reduce(|k, _| k)
+            .min_by_key(|(c, _)| secondary_rank(*c))
     }
     // Returns the Lj Phase Range observation [m] if it exists
     pub(crate) fn lj_phaserange(&self) -> Option<(Carrier, f64)> {
@@ -610,10 +1095,10 @@ impl Candidate {
             .filter(|(signal, _)| {
                 !matches!(
                     signal,
-                    Carrier::L1 | Carrier::E1 | Carrier::B1aB1c | Carrier::B1I
+                    Carrier::L1 | Carrier::E1 | Carrier::B1aB1c | Carrier::B1I | Carrier::G1(_)
                 )
             })
-            .reduce(|k, _| k)
+            .min_by_key(|(c, _)| secondary_rank(*c))
     }
     /// Returns IF code range combination
     pub(crate) fn code_if_combination(&self) -> Option<Combination> {
@@ -623,7 +1108,7 @@ impl Candidate {
         let (c_lx, lx_pr) = self
             .pseudo_range_iter()
             .filter(|(c, _)| *c != c_l1)
-            .reduce(|k, _| k)?;
+            .min_by_key(|(c, _)| secondary_rank(*c))?;
 
         let freq_lx = c_lx.frequency();
 
@@ -644,7 +1129,7 @@ impl Candidate {
         let (c_lx, lx_ph) = self
             .phase_range_iter()
             .filter(|(c, _)| *c != c_1)
-            .reduce(|k, _| k)?;
+            .min_by_key(|(c, _)| secondary_rank(*c))?;
 
         let f_lx = c_lx.frequency();
 
@@ -663,7 +1148,7 @@ impl Candidate {
         let (c_j, l_j) = self
             .phase_range_iter()
             .filter(|(c, _)| *c != c_1)
-            .reduce(|k, _| k)?;
+            .min_by_key(|(c, _)| secondary_rank(*c))?;
 
         let (f_1, f_j) = (c_1.frequency(), c_j.frequency());
         Some(Combination::new(
@@ -678,7 +1163,7 @@ impl Candidate {
         let (c_j, l_j) = self
             .pseudo_range_iter()
             .filter(|(c, _)| *c != c_1)
-            .reduce(|k, _| k)?;
+            .min_by_key(|(c, _)| secondary_rank(*c))?;
 
         let (f_1, f_j) = (c_1.frequency(), c_j.frequency());
 
@@ -701,13 +1186,18 @@ impl Candidate {
     pub(crate) fn phase_gf_combination(&self) -> Option<Combination> {
         let (c_1, l_1) = self
             .phase_range_iter()
-            .filter(|(c, _)| matches!(c, Carrier::L1 | Carrier::E1 | Carrier::B1aB1c))
+            .filter(|(c, _)| {
+                matches!(
+                    c,
+                    Carrier::L1 | Carrier::E1 | Carrier::B1aB1c | Carrier::G1(_)
+                )
+            })
             .reduce(|k, _| k)?;
 
         let (c_j, l_j) = self
             .phase_range_iter()
             .filter(|(c, _)| *c != c_1)
-            .reduce(|k, _| k)?;
+            .min_by_key(|(c, _)| secondary_rank(*c))?;
 
         Some(Combination::new(c_j, c_1, l_1 - l_j))
     }
@@ -715,13 +1205,18 @@ impl Candidate {
     pub(crate) fn code_gf_combination(&self) -> Option<Combination> {
         let (c_1, pr_1) = self
             .pseudo_range_iter()
-            .filter(|(c, _)| matches!(c, Carrier::L1 | Carrier::E1 | Carrier::B1aB1c))
+            .filter(|(c, _)| {
+                matches!(
+                    c,
+                    Carrier::L1 | Carrier::E1 | Carrier::B1aB1c | Carrier::G1(_)
+                )
+            })
             .reduce(|k, _| k)?;
 
         let (c_j, pr_j) = self
             .phase_range_iter()
             .filter(|(c, _)| *c != c_1)
-            .reduce(|k, _| k)?;
+            .min_by_key(|(c, _)| secondary_rank(*c))?;
 
         Some(Combination::new(c_j, c_1, pr_j - pr_1))
     }
@@ -753,11 +1248,14 @@ impl Candidate {
         // self.delta_phi
         // self.wind_up =
     }
-    // Retains only observations with SNR >= min_snr
-    pub(crate) fn min_snr_mask(&mut self, min_snr: f64) {
+    // Retains only observations with SNR >= min_snr (possibly carrier dependent)
+    pub(crate) fn min_snr_mask(&mut self, cfg: &Config) {
         self.observations.retain(|ob| {
             if let Some(snr) = ob.snr {
-                snr >= min_snr
+                match cfg.snr_mask(ob.carrier) {
+                    Some(min_snr) => snr >= min_snr,
+                    None => true,
+                }
             } else {
                 // no SNR information: we decide to still retain
                 // because old or exotic software might not provide SNR information
@@ -772,14 +1270,14 @@ impl Candidate {
     /// - "t_tx": TX [Epoch] in required timescale
     /// - "dt_ttx" elapsed [Duration] in said timescale
     //TODO: remove dt_ttx and simply use t_tx.duration (newly available)
-    pub(crate) fn transmission_time(&self, cfg: &Config) -> Result<(Epoch, Duration), Error> {
+    pub(crate) fn transmission_time(&self, cfg: &Config) -> Result<(Epoch, Duration), ErrorKind> {
         let (t, ts) = (self.t, self.t.time_scale);
         let seconds_ts = t.to_duration_in_time_scale(t.time_scale).to_seconds();
 
         let dt_tx = seconds_ts
             - self
                 .prefered_pseudorange()
-                .ok_or(Error::MissingPseudoRange)?
+                .ok_or(ErrorKind::MissingPseudoRange)?
                 .pseudo
                 .unwrap()
                 / SPEED_OF_LIGHT_M_S;
@@ -787,7 +1285,7 @@ impl Candidate {
         let mut e_tx = Epoch::from_duration(dt_tx * Unit::Second, ts);
 
         if cfg.modeling.sv_clock_bias {
-            let clock_corr = self.clock_corr.ok_or(Error::UnknownClockCorrection)?;
+            let clock_corr = self.clock_corr.ok_or(ErrorKind::UnknownClockCorrection)?;
             debug!(
                 "{} ({}) clock correction: {}",
                 t, self.sv, clock_corr.duration
@@ -796,7 +1294,7 @@ impl Candidate {
         }
 
         if cfg.modeling.sv_total_group_delay {
-            if let Some(tgd) = self.tgd {
+            if let Some(tgd) = self.group_delay()? {
                 debug!("{} ({}) {} tgd", t, self.sv, tgd);
                 e_tx -= tgd;
             }
@@ -819,11 +1317,6 @@ impl Candidate {
         );
         Ok((e_tx, dt))
     }
-    pub(crate) fn with_orbit(&self, orbit: Orbit) -> Self {
-        let mut s = self.clone();
-        s.orbit = Some(orbit);
-        s
-    }
     pub(crate) fn attitude(&self) -> Option<(f64, f64)> {
         let el = self.elevation_deg?;
         let az = self.azimuth_deg?;
@@ -852,7 +1345,13 @@ impl Candidate {
 
 #[cfg(test)]
 mod test {
-    use crate::prelude::{Candidate, Carrier, Epoch, Observation, SV};
+    use crate::constants::SPEED_OF_LIGHT_M_S;
+    use crate::prelude::{
+        BeiDouGroupDelays, Candidate, Carrier, CarrierSnrMask, ClockCorrection, ClockPolynomial,
+        Config, Duration, Epoch, ErrorKind, GalileoNavMessage, Observation, Orbit, TimeScale,
+        Vector3, EARTH_ITRF93, SV,
+    };
+    use std::str::FromStr;
     #[test]
     fn cpp_compatibility() {
         for (observations, cpp_compatible) in [(
@@ -880,4 +1379,338 @@ mod test {
             assert_eq!(cd.cpp_compatible(), cpp_compatible);
         }
     }
+    #[test]
+    fn range_rate_mismatch() {
+        let t = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let rx_pos_m = Vector3::new(4_696_989.0, 723_994.0, 4_239_678.0);
+        // SV straight above the X axis, receding at 500 m/s
+        let orbit = Orbit::from_cartesian_pos_vel(
+            nalgebra::Vector6::new(26_000.0, 723.994, 4_239.678, 0.5, 0.0, 0.0),
+            t,
+            EARTH_ITRF93,
+        );
+        let doppler = -500.0 / Carrier::L1.wavelength();
+        let mut cd = Candidate::new(
+            SV::default(),
+            t,
+            vec![Observation::pseudo_range(Carrier::L1, 20.0E6, None).with_doppler(doppler)],
+        );
+        assert!(cd
+            .range_rate_mismatch_m_s(rx_pos_m, Vector3::zeros())
+            .is_none());
+
+        cd.set_orbit(orbit);
+        let mismatch = cd
+            .range_rate_mismatch_m_s(rx_pos_m, Vector3::zeros())
+            .unwrap();
+        assert!(mismatch.abs() < 1.0E-6);
+
+        // receiver moving towards the SV
+        let mismatch = cd
+            .range_rate_mismatch_m_s(rx_pos_m, Vector3::new(10.0, 0.0, 0.0))
+            .unwrap();
+        assert!((mismatch - 10.0).abs() < 1.0E-6);
+
+        // SV clock drift
+        cd.set_clock_correction(
+            ClockCorrection::with_relativistic_correction(Duration::ZERO).with_drift(1.0E-9),
+        );
+        let mismatch = cd
+            .range_rate_mismatch_m_s(rx_pos_m, Vector3::zeros())
+            .unwrap();
+        assert!((mismatch - 1.0E-9 * SPEED_OF_LIGHT_M_S).abs() < 1.0E-6);
+    }
+    #[test]
+    fn carrier_snr_mask() {
+        let observations = [
+            (Carrier::L1, 30.0),
+            (Carrier::L5, 30.0),
+            (Carrier::L2, 20.0),
+        ]
+        .iter()
+        .map(|(carrier, snr)| Observation {
+            snr: Some(*snr),
+            pseudo: Some(1.0),
+            phase: None,
+            ambiguity: None,
+            doppler: None,
+            carrier: *carrier,
+        })
+        .collect();
+
+        let mut cfg = Config::default();
+        cfg.min_snr = Some(25.0);
+        cfg.min_snr_per_carrier = vec![CarrierSnrMask {
+            carrier: Carrier::L5,
+            min_snr: 35.0,
+        }];
+
+        let mut cd = Candidate::new(SV::default(), Epoch::default(), observations);
+        cd.min_snr_mask(&cfg);
+
+        let carriers = cd
+            .observations
+            .iter()
+            .map(|ob| ob.carrier)
+            .collect::<Vec<_>>();
+        assert_eq!(carriers, vec![Carrier::L1]);
+    }
+    #[test]
+    fn galileo_nav_group_delay() {
+        let bgd = Duration::from_nanoseconds(10.0);
+        let observation = |carrier: Carrier| Observation {
+            snr: None,
+            pseudo: Some(1.0),
+            phase: None,
+            ambiguity: None,
+            doppler: None,
+            carrier,
+        };
+        let clock = ClockCorrection::without_relativistic_correction(Duration::ZERO);
+
+        let mut cd = Candidate::new(
+            SV::default(),
+            Epoch::default(),
+            vec![observation(Carrier::E1)],
+        );
+        cd.set_galileo_group_delay(bgd, GalileoNavMessage::FNav);
+        assert_eq!(cd.group_delay(), Ok(Some(bgd)));
+
+        cd.set_clock_correction(clock.with_galileo_nav(GalileoNavMessage::FNav));
+        assert_eq!(cd.group_delay(), Ok(Some(bgd)));
+
+        // clock and BGD decoded from different messages
+        cd.set_clock_correction(clock.with_galileo_nav(GalileoNavMessage::INav));
+        assert_eq!(
+            cd.group_delay(),
+            Err(ErrorKind::InconsistentGalileoNavMessage)
+        );
+
+        // BGD scaled to E5b
+        let mut cd = Candidate::new(
+            SV::default(),
+            Epoch::default(),
+            vec![observation(Carrier::E5B)],
+        );
+        cd.set_galileo_group_delay(bgd, GalileoNavMessage::INav);
+        let ratio = (Carrier::E1.frequency() / Carrier::E5B.frequency()).powi(2);
+        assert_eq!(cd.group_delay(), Ok(Some(bgd * ratio)));
+
+        // F/NAV BGD does not apply to E5b
+        cd.set_galileo_group_delay(bgd, GalileoNavMessage::FNav);
+        assert_eq!(
+            cd.group_delay(),
+            Err(ErrorKind::InconsistentGalileoNavMessage)
+        );
+
+        // legacy group delay
+        cd.set_group_delay(bgd);
+        assert_eq!(cd.group_delay(), Ok(Some(bgd)));
+    }
+    #[test]
+    fn signal_priority() {
+        let observations = vec![
+            Observation::pseudo_range(Carrier::L1, 20.0E6, None),
+            Observation::pseudo_range(Carrier::L2, 20.0E6 + 1.0, None),
+            Observation::pseudo_range(Carrier::L5, 20.0E6 + 2.0, None),
+        ];
+        let mut cd = Candidate::new(SV::default(), Epoch::default(), observations);
+
+        for (priority, expected) in [
+            (vec![], Carrier::L1),
+            (vec![Carrier::L5, Carrier::L2], Carrier::L5),
+            (vec![Carrier::L6, Carrier::L2, Carrier::L1], Carrier::L2),
+            (vec![Carrier::E1], Carrier::L1),
+        ] {
+            cd.prioritize(&priority);
+            assert_eq!(cd.prefered_pseudorange().unwrap().carrier, expected);
+        }
+    }
+    #[test]
+    fn clock_polynomial() {
+        let t = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let poly =
+            ClockPolynomial::new(t - Duration::from_seconds(100.0), 1.0E-4, 1.0E-11, 1.0E-18);
+
+        let corr = poly.clock_correction(t);
+        let expected = 1.0E-4 + 1.0E-9 + 1.0E-14;
+        assert!((corr.duration.to_seconds() - expected).abs() < 1.0E-15);
+        assert!(corr.needs_relativistic_correction);
+        assert!(corr.galileo_nav.is_none());
+        assert!((corr.drift.unwrap() - (1.0E-11 + 2.0E-16)).abs() < 1.0E-20);
+
+        let corr = poly
+            .with_galileo_nav(GalileoNavMessage::FNav)
+            .clock_correction(t);
+        assert_eq!(corr.galileo_nav, Some(GalileoNavMessage::FNav));
+
+        let mut cd = Candidate::new(
+            SV::default(),
+            t,
+            vec![Observation::pseudo_range(
+                Carrier::L1,
+                0.075 * SPEED_OF_LIGHT_M_S,
+                None,
+            )],
+        );
+        assert!(
+            (cd.approximate_transmission_time() - (t - Duration::from_seconds(0.075)))
+                .abs()
+                .to_seconds()
+                < 1.0E-9
+        );
+
+        // the polynomial supersedes any precomputed correction
+        cd.set_clock_correction(ClockCorrection::with_relativistic_correction(
+            Duration::ZERO,
+        ));
+        cd.set_clock_polynomial(poly);
+        assert!(cd.clock_corr.is_none());
+        assert_eq!(cd.clock_poly, Some(poly));
+    }
+    #[test]
+    fn altboc_combinations() {
+        let observations = [Carrier::E1, Carrier::E5A, Carrier::E5, Carrier::E6]
+            .iter()
+            .map(|carrier| Observation {
+                carrier: *carrier,
+                pseudo: Some(20.0E6),
+                phase: Some(20.0E6),
+                ..Default::default()
+            })
+            .collect();
+        let cd = Candidate::new(SV::from_str("E05").unwrap(), Epoch::default(), observations);
+
+        for combination in [
+            cd.code_if_combination().unwrap(),
+            cd.phase_if_combination().unwrap(),
+            cd.mw_combination().unwrap(),
+        ] {
+            assert_eq!(
+                (combination.lhs, combination.rhs),
+                (Carrier::E5, Carrier::E1)
+            );
+        }
+        assert_eq!(cd.lj_pseudorange().unwrap().0, Carrier::E5);
+    }
+    #[test]
+    fn beidou_group_delays() {
+        let (tgd1, tgd2) = (
+            Duration::from_nanoseconds(5.0),
+            Duration::from_nanoseconds(-3.0),
+        );
+        let b1cp = Duration::from_nanoseconds(2.0);
+        let delays = BeiDouGroupDelays::new(tgd1, tgd2);
+        let sv = SV::from_str("C20").unwrap();
+
+        for (carrier, expected) in [
+            (Carrier::B1I, Some(tgd1)),
+            (Carrier::B2iB2b, Some(tgd2)),
+            (Carrier::B3, Some(Duration::ZERO)),
+            (Carrier::B1aB1c, None),
+            (Carrier::B2, None),
+        ] {
+            let mut cd = Candidate::new(
+                sv,
+                Epoch::default(),
+                vec![Observation::pseudo_range(carrier, 20.0E6, None)],
+            );
+            cd.set_beidou_group_delays(delays);
+            assert_eq!(cd.group_delay(), Ok(expected), "{}", carrier);
+        }
+
+        // B-CNAV1
+        let mut cd = Candidate::new(
+            sv,
+            Epoch::default(),
+            vec![
+                Observation::pseudo_range(Carrier::B3, 20.0E6, None),
+                Observation::pseudo_range(Carrier::B1aB1c, 20.0E6, None),
+            ],
+        );
+        cd.set_beidou_group_delays(delays.with_cnav(b1cp, Duration::ZERO));
+        assert_eq!(cd.group_delay(), Ok(Some(b1cp)));
+    }
+    #[test]
+    fn buffer_reuse() {
+        let t = Epoch::default();
+        let observations = [
+            Observation::pseudo_range(Carrier::L1, 20.0E6, None),
+            Observation::pseudo_range(Carrier::L5, 20.0E6 + 1.0, None),
+        ];
+
+        let mut cd = Candidate::from_observations(SV::default(), t, observations.clone());
+        cd.set_clock_correction(ClockCorrection::without_relativistic_correction(
+            Duration::ZERO,
+        ));
+        let capacity = cd.observations.capacity();
+
+        let t = t + Duration::from_seconds(1.0);
+        cd.refresh(t, [Observation::pseudo_range(Carrier::L1, 21.0E6, None)]);
+        assert_eq!(cd.t, t);
+        assert_eq!(cd.observations.len(), 1);
+        assert_eq!(cd.observations[0].pseudo, Some(21.0E6));
+        assert_eq!(cd.observations.capacity(), capacity);
+        assert!(cd.clock_corr.is_none());
+
+        let mut copy = Candidate::from_observations(SV::default(), t, observations);
+        let ptr = copy.observations.as_ptr();
+        copy.clone_from(&cd);
+        assert_eq!(copy.observations.len(), 1);
+        assert_eq!(copy.observations.as_ptr(), ptr);
+    }
+    #[test]
+    fn time_scale_alignment() {
+        let t_gpst = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        for ts in [TimeScale::GST, TimeScale::BDT, TimeScale::UTC] {
+            let mut cd = Candidate::new(SV::default(), t_gpst.to_time_scale(ts), vec![]);
+            cd.align_time_scale(TimeScale::GPST);
+            assert_eq!(cd.t.time_scale, TimeScale::GPST);
+            assert_eq!(cd.t, t_gpst, "failed for {}", ts);
+        }
+        let t_bdt = Epoch::from_str("2020-06-25T12:00:00 BDT").unwrap();
+        assert_eq!(
+            t_bdt.to_time_scale(TimeScale::GPST),
+            Epoch::from_str("2020-06-25T12:00:14 GPST").unwrap()
+        );
+    }
+    #[test]
+    fn epoch_alignment() {
+        let t = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let t_obs = t - Duration::from_milliseconds(20.0);
+
+        // approaching vehicle: positive doppler, decreasing range
+        let doppler = 1000.0;
+        let range_m = 20.0E6;
+        let range_rate_m_s = -doppler * Carrier::L1.wavelength();
+
+        let mut cd = Candidate::new(
+            SV::default(),
+            t_obs,
+            vec![
+                Observation::pseudo_range(Carrier::L1, range_m, None)
+                    .with_phase_range(range_m + 1.0)
+                    .with_doppler(doppler),
+                Observation::pseudo_range(Carrier::L5, range_m + 2.0, None),
+            ],
+        );
+        assert!(cd.align_epoch(t));
+        assert_eq!(cd.t, t);
+
+        let dr_m = range_rate_m_s * 0.020;
+        assert!((cd.observations[0].pseudo.unwrap() - range_m - dr_m).abs() < 1.0E-6);
+        assert!((cd.observations[0].phase.unwrap() - range_m - 1.0 - dr_m).abs() < 1.0E-6);
+        // L5 without doppler: propagated at the L1 range rate
+        assert!((cd.observations[1].pseudo.unwrap() - range_m - 2.0 - dr_m).abs() < 1.0E-6);
+
+        // no doppler: cannot be aligned
+        let mut cd = Candidate::new(
+            SV::default(),
+            t_obs,
+            vec![Observation::pseudo_range(Carrier::L1, range_m, None)],
+        );
+        assert!(!cd.align_epoch(t));
+        assert_eq!(cd.t, t_obs);
+        assert!(cd.align_epoch(t_obs));
+    }
 }
@@ -0,0 +1,632 @@
+//! C API: a stable C ABI to embed the [Solver] in C/C++ receiver firmware.
+//! Declarations are provided by `include/gnss_rtk.h`. Build the library with
+//! `cargo rustc --release --features capi --crate-type staticlib` (or `cdylib`).
+//!
+//! [Epoch]s are expressed as GPST seconds (since 1980-01-06 00:00:00 GPST).
+//! Missing (optional) values are expressed as NaN.
+//!
+//! The C API never accesses the network nor the file system on its own: the [Almanac]
+//! and the ECEF [Frame] are described by the caller. Panics never cross the C boundary,
+//! they are reported as [GNSS_RTK_INTERNAL_ERROR].
+use std::{
+    ffi::{c_char, c_void, CStr},
+    panic::{self, AssertUnwindSafe},
+    ptr,
+};
+
+use crate::prelude::{
+    Almanac, Candidate, Carrier, ClockCorrection, Config, Constellation, Duration, Epoch, Frame,
+    Interpolation, Method, Observation, Orbit, OrbitSource, PVTSolutionType, Solver, SV,
+};
+
+use anise::structure::planetocentric::ellipsoid::Ellipsoid;
+
+/// Success
+pub const GNSS_RTK_OK: i32 = 0;
+/// Null pointer or invalid argument
+pub const GNSS_RTK_INVALID_ARGUMENT: i32 = -1;
+/// No solution could be resolved at this [Epoch]
+pub const GNSS_RTK_RESOLUTION_FAILURE: i32 = -2;
+/// Internal error (caught panic): the solver should be released
+pub const GNSS_RTK_INTERNAL_ERROR: i32 = -3;
+
+/// SV state callback. Writes the SV state at `t_gpst_s` as ECEF position [km]
+/// and velocity [km/s] (6 values) into `state_km`, and returns true on success.
+/// Velocity may be left null (zero) if unknown.
+pub type GnssRtkOrbitCallback = Option<
+    unsafe extern "C" fn(
+        user_data: *mut c_void,
+        constellation: u8,
+        prn: u8,
+        t_gpst_s: f64,
+        state_km: *mut f64,
+    ) -> bool,
+>;
+
+/// Solver configuration
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GnssRtkConfig {
+    /// 0: SPP, 1: CPP, 2: PPP
+    pub method: u8,
+    /// 0: PositionVelocityTime, 1: TimeOnly, 2: FixedPosition
+    pub sol_type: u8,
+    /// Minimal SV elevation [°], NaN to disable
+    pub min_sv_elev_deg: f64,
+    /// Minimal SNR [dB.Hz], NaN to disable
+    pub min_snr_dbhz: f64,
+}
+
+/// ECEF [Frame] description. The gravitational parameter and shape are retrieved
+/// from the [Almanac] when the frame is described there, otherwise they must be provided
+/// (for example 399, 3000, 398600.435436, 6378.1366 and 6356.7519 for Earth ITRF93).
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GnssRtkFrame {
+    /// Ephemeris (center object) NAIF ID
+    pub ephemeris_id: i32,
+    /// Orientation NAIF ID
+    pub orientation_id: i32,
+    /// Gravitational parameter [km^3/s^2], NaN to use the [Almanac] value
+    pub mu_km3_s2: f64,
+    /// Equatorial radius [km], NaN to use the [Almanac] shape
+    pub equatorial_radius_km: f64,
+    /// Polar radius [km], NaN to use the [Almanac] shape
+    pub polar_radius_km: f64,
+}
+
+/// Apriori position
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GnssRtkPosition {
+    /// [Epoch] of this position
+    pub t_gpst_s: f64,
+    /// ECEF coordinates [m]
+    pub x_m: f64,
+    pub y_m: f64,
+    pub z_m: f64,
+}
+
+/// Candidate description: one SV observed at one [Epoch]
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GnssRtkCandidate {
+    /// 0: GPS, 1: Galileo, 2: BeiDou, 3: QZSS, 4: SBAS, 5: GLONASS
+    pub constellation: u8,
+    /// PRN number
+    pub prn: u8,
+    /// Sampling [Epoch]
+    pub t_gpst_s: f64,
+    /// SV clock correction [s], NaN if unknown
+    pub clock_correction_s: f64,
+    /// Total group delay [s], NaN if unknown
+    pub tgd_s: f64,
+}
+
+/// Single signal observation
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct GnssRtkObservation {
+    /// [Carrier] index: 0: L1, 1: L2, 2: L5, 3: L6, 4: E1, 5: E5, 6: E5A, 7: E5B, 8: E6,
+    /// 9: B1aB1c, 10: B1I, 11: B2iB2b, 12: B2, 13: B2A, 14: B3, 15: G1(k), 16: G2(k)
+    pub carrier: u8,
+    /// GLONASS frequency channel k (-7..=6) of FDMA carriers (G1, G2), ignored otherwise
+    pub glonass_channel: i8,
+    /// Pseudo range [m], NaN if missing
+    pub pseudo_range_m: f64,
+    /// Phase range [m], NaN if missing
+    pub phase_range_m: f64,
+    /// Doppler shift [Hz], NaN if missing
+    pub doppler_hz: f64,
+    /// SNR [dB.Hz], NaN if missing
+    pub snr_dbhz: f64,
+}
+
+/// Resolved solution
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GnssRtkSolution {
+    /// [Epoch] of this solution
+    pub t_gpst_s: f64,
+    /// ECEF position [m]
+    pub x_m: f64,
+    pub y_m: f64,
+    pub z_m: f64,
+    /// ECEF velocity [m/s]
+    pub vx_m_s: f64,
+    pub vy_m_s: f64,
+    pub vz_m_s: f64,
+    /// Receiver clock offset [s]
+    pub dt_s: f64,
+    /// Receiver clock drift [s/s]
+    pub d_dt_s_s: f64,
+    pub gdop: f64,
+    pub pdop: f64,
+    pub tdop: f64,
+    /// Number of SV that contributed
+    pub num_sv: u32,
+    /// Clock holdover duration [s], NaN for resolved solutions
+    pub holdover_s: f64,
+}
+
+/// Opaque solver handle
+pub struct GnssRtkSolver {
+    solver: Solver<CallbackOrbits>,
+    pool: Vec<Candidate>,
+}
+
+/// [OrbitSource] backed by the C callback
+struct CallbackOrbits {
+    callback: unsafe extern "C" fn(*mut c_void, u8, u8, f64, *mut f64) -> bool,
+    user_data: *mut c_void,
+}
+
+fn constellation_from_c(constellation: u8) -> Option<Constellation> {
+    match constellation {
+        0 => Some(Constellation::GPS),
+        1 => Some(Constellation::Galileo),
+        2 => Some(Constellation::BeiDou),
+        3 => Some(Constellation::QZSS),
+        4 => Some(Constellation::SBAS),
+        5 => Some(Constellation::Glonass),
+        _ => None,
+    }
+}
+
+fn constellation_to_c(constellation: Constellation) -> Option<u8> {
+    (0..=5).find(|c| constellation_from_c(*c) == Some(constellation))
+}
+
+fn carrier_from_c(carrier: u8, glonass_channel: i8) -> Option<Carrier> {
+    match carrier {
+        0 => Some(Carrier::L1),
+        1 => Some(Carrier::L2),
+        2 => Some(Carrier::L5),
+        3 => Some(Carrier::L6),
+        4 => Some(Carrier::E1),
+        5 => Some(Carrier::E5),
+        6 => Some(Carrier::E5A),
+        7 => Some(Carrier::E5B),
+        8 => Some(Carrier::E6),
+        9 => Some(Carrier::B1aB1c),
+        10 => Some(Carrier::B1I),
+        11 => Some(Carrier::B2iB2b),
+        12 => Some(Carrier::B2),
+        13 => Some(Carrier::B2A),
+        14 => Some(Carrier::B3),
+        15 | 16 if !(-7..=6).contains(&glonass_channel) => None,
+        15 => Some(Carrier::G1(glonass_channel)),
+        16 => Some(Carrier::G2(glonass_channel)),
+        _ => None,
+    }
+}
+
+/// NaN (missing) values to [Option]
+fn optional(value: f64) -> Option<f64> {
+    if value.is_nan() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+/// Runs `body`, returning `on_panic` if it panicked, so panics never unwind into C
+fn guarded<T>(on_panic: T, body: impl FnOnce() -> T) -> T {
+    panic::catch_unwind(AssertUnwindSafe(body)).unwrap_or(on_panic)
+}
+
+/// Loads the [Almanac] from these files and describes the ECEF [Frame]
+///
+/// # Safety
+/// `files` must contain valid, NUL terminated, paths.
+unsafe fn almanac_frame(files: &[*const c_char], desc: &GnssRtkFrame) -> Option<(Almanac, Frame)> {
+    let mut almanac = Almanac::default();
+    for file in files {
+        if file.is_null() {
+            return None;
+        }
+        let path = CStr::from_ptr(*file).to_str().ok()?;
+        almanac = almanac.load(path).ok()?;
+    }
+
+    let frame = Frame::new(desc.ephemeris_id, desc.orientation_id);
+    let mut frame = almanac.frame_from_uid(frame).unwrap_or(frame);
+
+    if let Some(mu_km3_s2) = optional(desc.mu_km3_s2) {
+        frame.mu_km3_s2 = Some(mu_km3_s2);
+    }
+    if let (Some(a_km), Some(c_km)) = (
+        optional(desc.equatorial_radius_km),
+        optional(desc.polar_radius_km),
+    ) {
+        frame.shape = Some(Ellipsoid {
+            semi_major_equatorial_radius_km: a_km,
+            semi_minor_equatorial_radius_km: a_km,
+            polar_radius_km: c_km,
+        });
+    }
+
+    // geodetic coordinates (elevation masks, atmospheric models) require a complete description
+    if frame.mu_km3_s2.is_none() || frame.shape.is_none() {
+        return None;
+    }
+    Some((almanac, frame))
+}
+
+impl OrbitSource for CallbackOrbits {
+    fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, _: Interpolation) -> Option<Orbit> {
+        let constellation = constellation_to_c(sv.constellation)?;
+        let mut state_km = [0.0_f64; 6];
+        let t_gpst_s = t.to_gpst_seconds();
+        // SAFETY: the callback contract is described in the header file
+        let valid = unsafe {
+            (self.callback)(
+                self.user_data,
+                constellation,
+                sv.prn,
+                t_gpst_s,
+                state_km.as_mut_ptr(),
+            )
+        };
+        if !valid {
+            return None;
+        }
+        let [x, y, z, vx, vy, vz] = state_km;
+        Some(Orbit::from_position(x, y, z, t, fr).with_velocity_km_s([vx, vy, vz].into()))
+    }
+}
+
+/// Writes the default [GnssRtkConfig] into `cfg`.
+///
+/// # Safety
+/// `cfg` must point to a writable [GnssRtkConfig].
+#[no_mangle]
+pub unsafe extern "C" fn gnss_rtk_config_default(cfg: *mut GnssRtkConfig) -> i32 {
+    let Some(c_cfg) = cfg.as_mut() else {
+        return GNSS_RTK_INVALID_ARGUMENT;
+    };
+    guarded(GNSS_RTK_INTERNAL_ERROR, || {
+        let cfg = Config::default();
+        *c_cfg = GnssRtkConfig {
+            method: match cfg.method {
+                Method::SPP => 0,
+                Method::CPP => 1,
+                Method::PPP => 2,
+            },
+            sol_type: match cfg.sol_type {
+                PVTSolutionType::PositionVelocityTime => 0,
+                PVTSolutionType::TimeOnly => 1,
+                PVTSolutionType::FixedPosition => 2,
+            },
+            min_sv_elev_deg: cfg.min_sv_elev.unwrap_or(f64::NAN),
+            min_snr_dbhz: cfg.min_snr.unwrap_or(f64::NAN),
+        };
+        GNSS_RTK_OK
+    })
+}
+
+/// Creates a new solver, from the [Almanac] stored in these local `almanac_files`
+/// (ANISE or SPICE: BSP, PCA, BPC) and the ECEF `frame` description.
+/// Returns null on invalid arguments or failure.
+///
+/// # Safety
+/// `cfg` must point to a valid [GnssRtkConfig] and `frame` to a valid [GnssRtkFrame].
+/// `almanac_files` must point to `num_almanac_files` NUL terminated paths (it may be null
+/// when `num_almanac_files` is 0). `apriori` may be null, or must point to a valid
+/// [GnssRtkPosition], expressed in `frame`. `user_data` is passed to the `orbit` callback
+/// and must remain valid for the lifetime of the solver.
+#[no_mangle]
+pub unsafe extern "C" fn gnss_rtk_solver_new(
+    cfg: *const GnssRtkConfig,
+    almanac_files: *const *const c_char,
+    num_almanac_files: usize,
+    frame: *const GnssRtkFrame,
+    apriori: *const GnssRtkPosition,
+    orbit: GnssRtkOrbitCallback,
+    user_data: *mut c_void,
+) -> *mut GnssRtkSolver {
+    let (Some(c_cfg), Some(c_frame), Some(callback)) = (cfg.as_ref(), frame.as_ref(), orbit) else {
+        return ptr::null_mut();
+    };
+    if almanac_files.is_null() && num_almanac_files > 0 {
+        return ptr::null_mut();
+    }
+
+    guarded(ptr::null_mut(), || {
+        let mut cfg = Config::default();
+        cfg.method = match c_cfg.method {
+            0 => Method::SPP,
+            1 => Method::CPP,
+            2 => Method::PPP,
+            _ => return ptr::null_mut(),
+        };
+        cfg.sol_type = match c_cfg.sol_type {
+            0 => PVTSolutionType::PositionVelocityTime,
+            1 => PVTSolutionType::TimeOnly,
+            2 => PVTSolutionType::FixedPosition,
+            _ => return ptr::null_mut(),
+        };
+        cfg.min_sv_elev = optional(c_cfg.min_sv_elev_deg);
+        cfg.min_snr = optional(c_cfg.min_snr_dbhz);
+
+        let files = if num_almanac_files == 0 {
+            &[][..]
+        } else {
+            std::slice::from_raw_parts(almanac_files, num_almanac_files)
+        };
+        let Some((almanac, frame)) = almanac_frame(files, c_frame) else {
+            return ptr::null_mut();
+        };
+
+        let initial = apriori.as_ref().map(|pos| {
+            Orbit::from_position(
+                pos.x_m / 1.0E3,
+                pos.y_m / 1.0E3,
+                pos.z_m / 1.0E3,
+                Epoch::from_gpst_seconds(pos.t_gpst_s),
+                frame,
+            )
+        });
+
+        let orbits = CallbackOrbits {
+            callback,
+            user_data,
+        };
+
+        let solver = Solver::new_almanac_frame(&cfg, initial, orbits, almanac, frame);
+        Box::into_raw(Box::new(GnssRtkSolver {
+            solver,
+            pool: Vec::new(),
+        }))
+    })
+}
+
+/// Releases a solver created with [gnss_rtk_solver_new].
+///
+/// # Safety
+/// `solver` must be null or obtained from [gnss_rtk_solver_new], and not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn gnss_rtk_solver_free(solver: *mut GnssRtkSolver) {
+    if !solver.is_null() {
+        guarded((), || drop(Box::from_raw(solver)));
+    }
+}
+
+/// Proposes a new candidate, with its observations, for the next resolution.
+///
+/// # Safety
+/// `solver` must be obtained from [gnss_rtk_solver_new], `candidate` must point to a valid
+/// [GnssRtkCandidate] and `observations` must point to `size` valid [GnssRtkObservation]s.
+#[no_mangle]
+pub unsafe extern "C" fn gnss_rtk_push_candidate(
+    solver: *mut GnssRtkSolver,
+    candidate: *const GnssRtkCandidate,
+    observations: *const GnssRtkObservation,
+    size: usize,
+) -> i32 {
+    let (Some(solver), Some(candidate)) = (solver.as_mut(), candidate.as_ref()) else {
+        return GNSS_RTK_INVALID_ARGUMENT;
+    };
+    if observations.is_null() || size == 0 {
+        return GNSS_RTK_INVALID_ARGUMENT;
+    }
+    let Some(constellation) = constellation_from_c(candidate.constellation) else {
+        return GNSS_RTK_INVALID_ARGUMENT;
+    };
+
+    guarded(GNSS_RTK_INTERNAL_ERROR, || {
+        let mut signals = Vec::with_capacity(size);
+        for obs in std::slice::from_raw_parts(observations, size) {
+            let Some(carrier) = carrier_from_c(obs.carrier, obs.glonass_channel) else {
+                return GNSS_RTK_INVALID_ARGUMENT;
+            };
+            signals.push(Observation {
+                carrier,
+                pseudo: optional(obs.pseudo_range_m),
+                phase: optional(obs.phase_range_m),
+                doppler: optional(obs.doppler_hz),
+                snr: optional(obs.snr_dbhz),
+                ambiguity: None,
+            });
+        }
+
+        let sv = SV::new(constellation, candidate.prn);
+        let t = Epoch::from_gpst_seconds(candidate.t_gpst_s);
+        let mut cd = Candidate::new(sv, t, signals);
+        if let Some(corr_s) = optional(candidate.clock_correction_s) {
+            cd.set_clock_correction(ClockCorrection::without_relativistic_correction(
+                Duration::from_seconds(corr_s),
+            ));
+        }
+        if let Some(tgd_s) = optional(candidate.tgd_s) {
+            cd.set_group_delay(Duration::from_seconds(tgd_s));
+        }
+        solver.pool.push(cd);
+        GNSS_RTK_OK
+    })
+}
+
+/// Resolves a solution at `t_gpst_s`, from all candidates proposed since the previous
+/// resolution attempt. Candidates are consumed, whatever the outcome.
+///
+/// # Safety
+/// `solver` must be obtained from [gnss_rtk_solver_new], and `solution` must point
+/// to a writable [GnssRtkSolution].
+#[no_mangle]
+pub unsafe extern "C" fn gnss_rtk_resolve(
+    solver: *mut GnssRtkSolver,
+    t_gpst_s: f64,
+    solution: *mut GnssRtkSolution,
+) -> i32 {
+    let (Some(solver), Some(solution)) = (solver.as_mut(), solution.as_mut()) else {
+        return GNSS_RTK_INVALID_ARGUMENT;
+    };
+    guarded(GNSS_RTK_INTERNAL_ERROR, || {
+        let pool = std::mem::take(&mut solver.pool);
+        let t = Epoch::from_gpst_seconds(t_gpst_s);
+        match solver.solver.resolve(t, &pool) {
+            Ok((t, pvt)) => {
+                let pos_vel_m = pvt.state.to_cartesian_pos_vel() * 1.0E3;
+                *solution = GnssRtkSolution {
+                    t_gpst_s: t.to_gpst_seconds(),
+                    x_m: pos_vel_m[0],
+                    y_m: pos_vel_m[1],
+                    z_m: pos_vel_m[2],
+                    vx_m_s: pos_vel_m[3],
+                    vy_m_s: pos_vel_m[4],
+                    vz_m_s: pos_vel_m[5],
+                    dt_s: pvt.dt.to_seconds(),
+                    d_dt_s_s: pvt.d_dt,
+                    gdop: pvt.gdop(),
+                    pdop: pvt.pdop(),
+                    tdop: pvt.tdop(),
+                    num_sv: pvt.sv.len() as u32,
+                    holdover_s: pvt.holdover.map_or(f64::NAN, |dt| dt.to_seconds()),
+                };
+                GNSS_RTK_OK
+            },
+            Err(_) => GNSS_RTK_RESOLUTION_FAILURE,
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    unsafe extern "C" fn no_orbit(_: *mut c_void, _: u8, _: u8, _: f64, _: *mut f64) -> bool {
+        false
+    }
+
+    /// Earth ITRF93, fully described: no almanac file is needed
+    const ITRF93: GnssRtkFrame = GnssRtkFrame {
+        ephemeris_id: 399,
+        orientation_id: 3000,
+        mu_km3_s2: 398600.435436,
+        equatorial_radius_km: 6378.1366,
+        polar_radius_km: 6356.7519,
+    };
+
+    #[test]
+    fn capi_carriers() {
+        assert_eq!(carrier_from_c(14, 0), Some(Carrier::B3));
+        assert_eq!(carrier_from_c(15, -7), Some(Carrier::G1(-7)));
+        assert_eq!(carrier_from_c(16, 6), Some(Carrier::G2(6)));
+        assert!(carrier_from_c(15, 7).is_none(), "invalid frequency channel");
+        assert!(
+            carrier_from_c(16, -8).is_none(),
+            "invalid frequency channel"
+        );
+        assert!(carrier_from_c(17, 0).is_none());
+        assert_eq!(
+            constellation_to_c(Constellation::Galileo),
+            Some(1),
+            "constellation mapping"
+        );
+    }
+
+    #[test]
+    fn capi_roundtrip() {
+        unsafe {
+            let mut cfg = GnssRtkConfig {
+                method: 0,
+                sol_type: 0,
+                min_sv_elev_deg: f64::NAN,
+                min_snr_dbhz: f64::NAN,
+            };
+            assert_eq!(gnss_rtk_config_default(&mut cfg), GNSS_RTK_OK);
+            assert_eq!(cfg.method, 1);
+            assert_eq!(
+                gnss_rtk_config_default(ptr::null_mut()),
+                GNSS_RTK_INVALID_ARGUMENT
+            );
+
+            let new = |frame: &GnssRtkFrame, orbit: GnssRtkOrbitCallback| {
+                gnss_rtk_solver_new(
+                    &cfg,
+                    ptr::null(),
+                    0,
+                    frame,
+                    ptr::null(),
+                    orbit,
+                    ptr::null_mut(),
+                )
+            };
+            assert!(new(&ITRF93, None).is_null());
+
+            let undescribed = GnssRtkFrame {
+                polar_radius_km: f64::NAN,
+                ..ITRF93
+            };
+            assert!(new(&undescribed, Some(no_orbit)).is_null(), "missing shape");
+
+            let solver = new(&ITRF93, Some(no_orbit));
+            assert!(!solver.is_null());
+
+            let mut obs = GnssRtkObservation {
+                carrier: 0,
+                glonass_channel: 0,
+                pseudo_range_m: 20.0E6,
+                phase_range_m: f64::NAN,
+                doppler_hz: f64::NAN,
+                snr_dbhz: 45.0,
+            };
+            let t_gpst_s = 1.0E9;
+            let mut candidate = GnssRtkCandidate {
+                constellation: 0,
+                prn: 1,
+                t_gpst_s,
+                clock_correction_s: f64::NAN,
+                tgd_s: f64::NAN,
+            };
+            assert_eq!(
+                gnss_rtk_push_candidate(solver, &candidate, &obs, 1),
+                GNSS_RTK_OK
+            );
+
+            candidate.constellation = 5;
+            obs.carrier = 15;
+            obs.glonass_channel = -3;
+            assert_eq!(
+                gnss_rtk_push_candidate(solver, &candidate, &obs, 1),
+                GNSS_RTK_OK
+            );
+            obs.glonass_channel = 9;
+            assert_eq!(
+                gnss_rtk_push_candidate(solver, &candidate, &obs, 1),
+                GNSS_RTK_INVALID_ARGUMENT
+            );
+
+            candidate.constellation = 9;
+            assert_eq!(
+                gnss_rtk_push_candidate(solver, &candidate, &obs, 1),
+                GNSS_RTK_INVALID_ARGUMENT
+            );
+            assert_eq!((*solver).pool.len(), 2);
+
+            let mut solution = GnssRtkSolution::default();
+            assert_eq!(
+                gnss_rtk_resolve(solver, t_gpst_s, &mut solution),
+                GNSS_RTK_RESOLUTION_FAILURE
+            );
+            assert!((*solver).pool.is_empty());
+            assert_eq!(
+                gnss_rtk_resolve(ptr::null_mut(), t_gpst_s, &mut solution),
+                GNSS_RTK_INVALID_ARGUMENT
+            );
+            gnss_rtk_solver_free(solver);
+        }
+    }
+
+    #[test]
+    fn capi_panic_guard() {
+        assert_eq!(
+            guarded(GNSS_RTK_INTERNAL_ERROR, || GNSS_RTK_OK),
+            GNSS_RTK_OK
+        );
+        assert_eq!(
+            guarded(GNSS_RTK_INTERNAL_ERROR, || -> i32 { panic!("unwinding") }),
+            GNSS_RTK_INTERNAL_ERROR
+        );
+    }
+}
@@ -1,5 +1,31 @@
-use nyx::cosmic::SPEED_OF_LIGHT_M_S;
+use std::sync::RwLock;
 
+use crate::{constants::SPEED_OF_LIGHT_M_S, prelude::Constellation, sync};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// User defined [Carrier]s, indexed by [Carrier::Custom] identifier
+static CUSTOM_CARRIERS: RwLock<Vec<CarrierDefinition>> = RwLock::new(Vec::new());
+
+/// [CarrierDefinition] declares a signal this crate does not know of
+/// (LEO-PNT, experimental signals..), see [Carrier::register].
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CarrierDefinition {
+    /// Signal name
+    pub name: String,
+    /// Carrier frequency [Hz]
+    pub frequency_hz: f64,
+    /// [Constellation] broadcasting this signal
+    pub constellation: Constellation,
+    /// RINEX frequency band (like '1' in "C1C"), if this signal has one
+    pub rinex_band: Option<char>,
+}
+
+/// [Carrier::Custom] identifiers only make sense within the process that registered
+/// them: user defined signals are serialized by their [CarrierDefinition], and registered
+/// again (if need be) when deserialized.
 #[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Eq, Ord, Hash)]
 pub enum Carrier {
     /// L1 (GPS/QZSS/SBAS) same frequency as E1 and B1aB1c
@@ -13,7 +39,7 @@ pub enum Carrier {
     L6,
     /// E1 (Galileo)
     E1,
-    /// E5 (Galileo) same frequency as B2
+    /// E5 AltBOC full band (Galileo) same frequency as B2
     E5,
     /// E5A (Galileo) same frequency as L5
     E5A,
@@ -27,12 +53,98 @@ pub enum Carrier {
     B1I,
     /// B2I/B2B (BDS) same frequency as E5b
     B2iB2b,
-    /// B2 (BDS) same frequency as E5
+    /// B2 AltBOC full band (BDS) same frequency as E5
     B2,
     /// B2A (BDS) same frequency as L5 and E5A
     B2A,
     /// B3 (BDS)
     B3,
+    /// G1 (GLONASS FDMA), on this frequency channel
+    G1(i8),
+    /// G2 (GLONASS FDMA), on this frequency channel
+    G2(i8),
+    /// User defined signal, see [Carrier::register]
+    Custom(u16),
+}
+
+/// Serialized [Carrier]
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Carrier")]
+enum CarrierRecord {
+    L1,
+    L2,
+    L5,
+    L6,
+    E1,
+    E5,
+    E5A,
+    E5B,
+    E6,
+    B1aB1c,
+    B1I,
+    B2iB2b,
+    B2,
+    B2A,
+    B3,
+    G1(i8),
+    G2(i8),
+    Custom(CarrierDefinition),
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for Carrier {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let record = match *self {
+            Self::L1 => CarrierRecord::L1,
+            Self::L2 => CarrierRecord::L2,
+            Self::L5 => CarrierRecord::L5,
+            Self::L6 => CarrierRecord::L6,
+            Self::E1 => CarrierRecord::E1,
+            Self::E5 => CarrierRecord::E5,
+            Self::E5A => CarrierRecord::E5A,
+            Self::E5B => CarrierRecord::E5B,
+            Self::E6 => CarrierRecord::E6,
+            Self::B1aB1c => CarrierRecord::B1aB1c,
+            Self::B1I => CarrierRecord::B1I,
+            Self::B2iB2b => CarrierRecord::B2iB2b,
+            Self::B2 => CarrierRecord::B2,
+            Self::B2A => CarrierRecord::B2A,
+            Self::B3 => CarrierRecord::B3,
+            Self::G1(k) => CarrierRecord::G1(k),
+            Self::G2(k) => CarrierRecord::G2(k),
+            Self::Custom(id) => CarrierRecord::Custom(self.definition().ok_or_else(|| {
+                <S::Error as serde::ser::Error>::custom(format!("unregistered carrier X{}", id))
+            })?),
+        };
+        record.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Carrier {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(match CarrierRecord::deserialize(deserializer)? {
+            CarrierRecord::L1 => Self::L1,
+            CarrierRecord::L2 => Self::L2,
+            CarrierRecord::L5 => Self::L5,
+            CarrierRecord::L6 => Self::L6,
+            CarrierRecord::E1 => Self::E1,
+            CarrierRecord::E5 => Self::E5,
+            CarrierRecord::E5A => Self::E5A,
+            CarrierRecord::E5B => Self::E5B,
+            CarrierRecord::E6 => Self::E6,
+            CarrierRecord::B1aB1c => Self::B1aB1c,
+            CarrierRecord::B1I => Self::B1I,
+            CarrierRecord::B2iB2b => Self::B2iB2b,
+            CarrierRecord::B2 => Self::B2,
+            CarrierRecord::B2A => Self::B2A,
+            CarrierRecord::B3 => Self::B3,
+            CarrierRecord::G1(k) => Self::G1(k),
+            CarrierRecord::G2(k) => Self::G2(k),
+            CarrierRecord::Custom(definition) => Self::register(definition),
+        })
+    }
 }
 
 impl std::fmt::Display for Carrier {
@@ -53,11 +165,47 @@ impl std::fmt::Display for Carrier {
             Self::B2 => write!(f, "B2"),
             Self::B3 => write!(f, "B3"),
             Self::B2A => write!(f, "B2A"),
+            Self::G1(k) => write!(f, "G1({})", k),
+            Self::G2(k) => write!(f, "G2({})", k),
+            Self::Custom(id) => match self.definition() {
+                Some(definition) => write!(f, "{}", definition.name),
+                None => write!(f, "X{}", id),
+            },
         }
     }
 }
 
 impl Carrier {
+    /// Declares a new [Carrier] at runtime, so signals this crate does not know of
+    /// can be processed without forking it. Declaring an existing signal name of
+    /// the same [Constellation] updates its definition.
+    pub fn register(definition: CarrierDefinition) -> Self {
+        let mut table = sync::write(&CUSTOM_CARRIERS);
+        let id = match table.iter().position(|def| {
+            def.name == definition.name && def.constellation == definition.constellation
+        }) {
+            Some(id) => {
+                table[id] = definition;
+                id
+            },
+            None => {
+                table.push(definition);
+                table.len() - 1
+            },
+        };
+        Self::Custom(id as u16)
+    }
+    /// Returns [CarrierDefinition] of a user defined [Carrier]
+    pub fn definition(&self) -> Option<CarrierDefinition> {
+        match self {
+            Self::Custom(id) => {
+                let table = sync::read(&CUSTOM_CARRIERS);
+                table.get(*id as usize).cloned()
+            },
+            _ => None,
+        }
+    }
+    /// Returns carrier frequency [Hz]. Undeclared custom carriers have no frequency (NaN).
     pub fn frequency(&self) -> f64 {
         match self {
             Self::L1 | Self::E1 | Self::B1aB1c => 1575.42E6_f64,
@@ -68,11 +216,86 @@ impl Carrier {
             Self::B3 => 1268.52E6_f64,
             Self::E5B | Self::B2iB2b => 1207.14E6_f64,
             Self::B1I => 1561.098E6_f64,
+            Self::G1(k) => 1602.0E6_f64 + *k as f64 * 562.5E3_f64,
+            Self::G2(k) => 1246.0E6_f64 + *k as f64 * 437.5E3_f64,
+            Self::Custom(_) => self
+                .definition()
+                .map(|definition| definition.frequency_hz)
+                .unwrap_or(f64::NAN),
         }
     }
     pub fn wavelength(&self) -> f64 {
         SPEED_OF_LIGHT_M_S / self.frequency()
     }
+    /// Returns the GLONASS frequency channel number, of FDMA signals
+    pub fn glonass_channel(&self) -> Option<i8> {
+        match self {
+            Self::G1(k) | Self::G2(k) => Some(*k),
+            _ => None,
+        }
+    }
+    /// Identifies the [Carrier] of a RINEX observable code (like "C1C", "L5Q" or "P2"),
+    /// for given [Constellation]. User defined carriers (see [Self::register]) are identified
+    /// by their RINEX band. Returns None for unsupported signals (IRNSS..) and
+    /// GLONASS FDMA signals, that need their frequency channel (see [Self::from_glonass_rinex_code]).
+    pub fn from_rinex_code(constellation: Constellation, code: &str) -> Option<Self> {
+        let band = code.chars().nth(1)?;
+        Self::builtin_rinex_band(constellation, band)
+            .or_else(|| Self::custom_rinex_band(constellation, band))
+    }
+    /// Identifies the GLONASS FDMA [Carrier] of a RINEX observable code (like "C1C" or "L2P"),
+    /// broadcasted on this frequency channel (-7..=6, see `GlonassEphemeris.channel`).
+    pub fn from_glonass_rinex_code(code: &str, channel: i8) -> Option<Self> {
+        if !(-7..=6).contains(&channel) {
+            return None;
+        }
+        match code.chars().nth(1)? {
+            '1' => Some(Self::G1(channel)),
+            '2' => Some(Self::G2(channel)),
+            _ => None,
+        }
+    }
+    fn custom_rinex_band(constellation: Constellation, band: char) -> Option<Self> {
+        let table = sync::read(&CUSTOM_CARRIERS);
+        table
+            .iter()
+            .position(|def| def.constellation == constellation && def.rinex_band == Some(band))
+            .map(|id| Self::Custom(id as u16))
+    }
+    fn builtin_rinex_band(constellation: Constellation, band: char) -> Option<Self> {
+        match constellation {
+            Constellation::GPS | Constellation::QZSS => match band {
+                '1' => Some(Self::L1),
+                '2' => Some(Self::L2),
+                '5' => Some(Self::L5),
+                '6' => Some(Self::L6),
+                _ => None,
+            },
+            Constellation::Galileo => match band {
+                '1' => Some(Self::E1),
+                '5' => Some(Self::E5A),
+                '6' => Some(Self::E6),
+                '7' => Some(Self::E5B),
+                '8' => Some(Self::E5),
+                _ => None,
+            },
+            Constellation::BeiDou => match band {
+                '1' => Some(Self::B1aB1c),
+                '2' => Some(Self::B1I),
+                '5' => Some(Self::B2A),
+                '6' => Some(Self::B3),
+                '7' => Some(Self::B2iB2b),
+                '8' => Some(Self::B2),
+                _ => None,
+            },
+            c if c.is_sbas() => match band {
+                '1' => Some(Self::L1),
+                '5' => Some(Self::L5),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
 }
 
 /// Signal used in [PVTSolution] resolution
@@ -99,3 +322,105 @@ impl std::fmt::Display for Signal {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::Carrier;
+    use crate::{constants::SPEED_OF_LIGHT_M_S, prelude::Constellation};
+    #[test]
+    fn rinex_codes() {
+        for (constellation, code, expected) in [
+            (Constellation::GPS, "C1C", Some(Carrier::L1)),
+            (Constellation::GPS, "L2W", Some(Carrier::L2)),
+            (Constellation::GPS, "P2", Some(Carrier::L2)),
+            (Constellation::Galileo, "C7Q", Some(Carrier::E5B)),
+            (Constellation::Galileo, "L5Q", Some(Carrier::E5A)),
+            (Constellation::BeiDou, "C2I", Some(Carrier::B1I)),
+            (Constellation::BeiDou, "C6I", Some(Carrier::B3)),
+            (Constellation::QZSS, "C5X", Some(Carrier::L5)),
+            (Constellation::QZSS, "C1C", Some(Carrier::L1)),
+            (Constellation::QZSS, "L2L", Some(Carrier::L2)),
+            (Constellation::QZSS, "C6L", Some(Carrier::L6)),
+            (Constellation::QZSS, "C6E", Some(Carrier::L6)),
+            (Constellation::Glonass, "C1C", None),
+            (Constellation::GPS, "C", None),
+        ] {
+            assert_eq!(
+                Carrier::from_rinex_code(constellation, code),
+                expected,
+                "failed for {}:{}",
+                constellation,
+                code
+            );
+        }
+    }
+    #[test]
+    fn glonass_fdma() {
+        for (code, channel, expected) in [
+            ("C1C", 0, Some(Carrier::G1(0))),
+            ("L1P", -7, Some(Carrier::G1(-7))),
+            ("C2P", 6, Some(Carrier::G2(6))),
+            ("C3X", 1, None),
+            ("C1C", 7, None),
+        ] {
+            assert_eq!(Carrier::from_glonass_rinex_code(code, channel), expected);
+        }
+
+        for (carrier, frequency_hz) in [
+            (Carrier::G1(0), 1602.0E6),
+            (Carrier::G1(-7), 1598.0625E6),
+            (Carrier::G1(6), 1605.375E6),
+            (Carrier::G2(0), 1246.0E6),
+            (Carrier::G2(-7), 1242.9375E6),
+            (Carrier::G2(6), 1248.625E6),
+        ] {
+            assert!(
+                (carrier.frequency() - frequency_hz).abs() < 1.0E-3,
+                "{}",
+                carrier
+            );
+            assert_eq!(
+                carrier.wavelength(),
+                SPEED_OF_LIGHT_M_S / carrier.frequency()
+            );
+        }
+
+        // G1/G2 ratio is 9/7 on every channel
+        for k in -7..=6 {
+            let ratio = Carrier::G1(k).frequency() / Carrier::G2(k).frequency();
+            assert!((ratio - 9.0 / 7.0).abs() < 1.0E-12);
+        }
+        assert_eq!(Carrier::G2(-3).glonass_channel(), Some(-3));
+        assert_eq!(Carrier::L1.glonass_channel(), None);
+        assert_eq!(Carrier::G1(-3).to_string(), "G1(-3)");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn custom_carrier_serde() {
+        let content = "{\"Custom\":{\"name\":\"X1S\",\"frequency_hz\":1610.0E6,\
+            \"constellation\":\"GPS\",\"rinex_band\":null}}";
+
+        // unknown to this process: registered when deserialized
+        let carrier: Carrier = serde_json::from_str(content).unwrap();
+        let definition = carrier.definition().unwrap();
+        assert_eq!(definition.name, "X1S");
+        assert_eq!(definition.constellation, Constellation::GPS);
+        assert_eq!(carrier.frequency(), 1610.0E6);
+
+        // serialized by definition, not by identifier
+        let serialized = serde_json::to_string(&carrier).unwrap();
+        assert!(serialized.contains("\"name\":\"X1S\""), "{}", serialized);
+        assert_eq!(
+            serde_json::from_str::<Carrier>(&serialized).unwrap(),
+            carrier
+        );
+
+        // built-in signals are not affected
+        assert_eq!(serde_json::to_string(&Carrier::L1).unwrap(), "\"L1\"");
+        assert_eq!(
+            serde_json::to_string(&Carrier::G1(-3)).unwrap(),
+            "{\"G1\":-3}"
+        );
+    }
+}
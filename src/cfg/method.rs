@@ -1,11 +1,11 @@
-use crate::prelude::Error;
+use crate::prelude::ErrorKind;
 
 #[cfg(feature = "serde")]
-use serde::Deserialize; //, Serialize};
+use serde::{Deserialize, Serialize};
 
 /// Solving method
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Method {
     /// Single Point Positioning (SPP).
     /// Code based navigation on a single carrier frequency.
@@ -36,13 +36,13 @@ impl std::fmt::Display for Method {
 }
 
 impl std::str::FromStr for Method {
-    type Err = Error;
+    type Err = ErrorKind;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.trim().to_lowercase().as_str() {
             "spp" => Ok(Self::SPP),
             "cpp" => Ok(Self::CPP),
             "ppp" => Ok(Self::PPP),
-            _ => Err(Error::InvalidStrategy),
+            _ => Err(ErrorKind::InvalidStrategy),
         }
     }
 }
@@ -4,8 +4,10 @@ use thiserror::Error;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    navigation::Filter,
-    prelude::{PVTSolutionType, TimeScale},
+    frame::{DatumTransformation, ReferenceFrame},
+    navigation::{Decomposition, Filter},
+    prelude::{Carrier, Duration, Epoch, PVTSolutionType, TimeScale},
+    time::{LeapSecondPolicy, Timestamping},
 };
 
 use nalgebra::{base::dimension::U8, OMatrix};
@@ -13,16 +15,32 @@ use nalgebra::{base::dimension::U8, OMatrix};
 mod method;
 pub use method::Method;
 
+mod noise;
+pub use noise::{NoiseModel, NoiseSpec};
+
+mod report;
+pub use report::ConfigDiff;
+
+/// Current [Config] format version. Serialized [Config]s carry it,
+/// so stored setups can be traced back to the format that generated them.
+/// Version 2 describes user defined [Carrier]s by their definition.
+pub const CONFIG_VERSION: u32 = 2;
+
 /// Configuration Error
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("unknown tropo model")]
     UnknownTropoModel,
+    #[error(
+        "unsupported config version {0} (current version is {})",
+        CONFIG_VERSION
+    )]
+    UnsupportedVersion(u32),
 }
 
 /// Geometry strategy
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum GeometryStrategy {
     /// Algorithm selects best elevation angles
     #[default]
@@ -31,11 +49,24 @@ pub enum GeometryStrategy {
     SpreadAzimuth,
 }
 
+/// Cold start method, used to initialize the [Solver]
+/// when no apriori position is known.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ColdStart {
+    /// Bancroft closed form solution, on 4 vehicles exactly.
+    #[default]
+    Bancroft,
+    /// Gauss-Newton iterations starting from Earth's center,
+    /// using all vehicles in sight.
+    GaussNewton,
+}
+
 /// Rover or receiver use case Profile, to the [Solver]
 /// selects appropriate settings. Failing to select
 /// the apropriate [Profile] will degrade the solutions.
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Profile {
     /// Receiver held in static.
     /// Typically used in Geodetic surveys (GNSS stations Referencing)
@@ -47,7 +78,7 @@ pub enum Profile {
 }
 
 #[derive(Default, Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ElevationMappingFunction {
     /// a + b * e-elev/c
     pub a: f64,
@@ -63,8 +94,46 @@ impl ElevationMappingFunction {
     }
 }
 
+/// Interpolation scheme, used to resolve [SV] states from discrete products.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum InterpolationMethod {
+    /// Lagrangian interpolation, over positions only.
+    #[default]
+    Lagrange,
+    /// Hermite interpolation, over positions and velocities.
+    /// Requires velocities to be known at each node: [OrbitSource]s
+    /// that do not describe them fall back to [InterpolationMethod::Lagrange].
+    Hermite,
+}
+
+/// [Interpolation] setup, that we propose to the [OrbitSource].
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Interpolation {
+    /// [InterpolationMethod]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub method: InterpolationMethod,
+    /// Interpolation order for SV positions
+    #[cfg_attr(feature = "serde", serde(default = "default_orbit_interp"))]
+    pub orbit_order: usize,
+    /// Interpolation order for SV clock states
+    #[cfg_attr(feature = "serde", serde(default = "default_clock_interp"))]
+    pub clock_order: usize,
+}
+
+impl Default for Interpolation {
+    fn default() -> Self {
+        Self {
+            method: InterpolationMethod::default(),
+            orbit_order: default_orbit_interp(),
+            clock_order: default_clock_interp(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum WeightMatrix {
     /// a + b e-elev/c
     MappingFunction(ElevationMappingFunction),
@@ -72,14 +141,36 @@ pub enum WeightMatrix {
     Covar,
 }
 
+fn default_config_version() -> u32 {
+    CONFIG_VERSION
+}
+
+/// Rejects [Config] versions that we do not know how to interpret.
+#[cfg(feature = "serde")]
+fn deserialize_config_version<'de, D>(deserializer: D) -> Result<u32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let version = u32::deserialize(deserializer)?;
+    if (1..=CONFIG_VERSION).contains(&version) {
+        Ok(version)
+    } else {
+        Err(serde::de::Error::custom(Error::UnsupportedVersion(version)))
+    }
+}
+
 fn default_timescale() -> TimeScale {
     TimeScale::GPST
 }
 
-fn default_interp() -> usize {
+fn default_orbit_interp() -> usize {
     11
 }
 
+fn default_clock_interp() -> usize {
+    1
+}
+
 fn default_smoothing() -> bool {
     false
 }
@@ -124,6 +215,10 @@ fn default_cable_delay() -> bool {
     true
 }
 
+fn default_bds2_code_bias() -> bool {
+    true
+}
+
 fn default_postfit_kf() -> bool {
     false
 }
@@ -161,8 +256,12 @@ fn default_tdop_threshold() -> Option<f64> {
     None
 }
 
+fn default_tracking_buffer_capacity() -> usize {
+    1024
+}
+
 #[derive(Default, Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 /// System Internal Delay as defined by BIPM in
 /// "GPS Receivers Accurate Time Comparison" : the (frequency dependent)
 /// time delay introduced by the combination of:
@@ -177,8 +276,66 @@ pub struct InternalDelay {
     pub frequency: f64,
 }
 
+/// Minimal SNR for a specific [Carrier]. Signal designs have
+/// different nominal C/N0, so you may want to be stricter on some of them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CarrierSnrMask {
+    /// [Carrier] this mask applies to
+    pub carrier: Carrier,
+    /// Minimal SNR for this [Carrier]
+    pub min_snr: f64,
+}
+
+/// Height constraint (pseudo-observation), for platforms whose altitude
+/// is essentially known (marine vessels, rail..).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HeightConstraint {
+    /// Altitude above the ellipsoid [m]
+    pub altitude_m: f64,
+    /// Standard deviation [m]
+    pub sigma_m: f64,
+}
+
+/// [ConstellationMonitor] compares the mean pseudo range residual of each constellation
+/// to the other vehicles in sight: a constellation showing a systematic bias (broadcast outage..)
+/// is excluded, then restored once consistent again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConstellationMonitor {
+    /// Systematic bias [m] above which a constellation is faulty
+    pub max_bias_m: f64,
+    /// Number of consecutive epochs before a constellation is excluded (or restored)
+    pub persistence: usize,
+}
+
+/// Baseline length constraint, for antennas mounted on a rigid platform:
+/// the distance between the rover and the remote site is known.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct BaselineConstraint {
+    /// Baseline length [m]
+    pub length_m: f64,
+    /// Standard deviation [m]
+    pub sigma_m: f64,
+}
+
+/// Clock constraint, for receivers driven by a stable external frequency reference:
+/// the clock offset is predicted from the previous solution and the clock drift,
+/// and this prediction constrains the clock estimate (pseudo-observation).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClockConstraint {
+    /// Calibrated clock drift [s/s], held fixed between calibrations.
+    /// The latest estimated drift is used when undefined.
+    pub drift_s_s: Option<f64>,
+    /// Clock drift stability (standard deviation) [s/s], must be positive
+    pub drift_sigma_s_s: f64,
+}
+
 #[derive(Clone, Debug, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct SolverOpts {
     /// GDOP threshold to invalidate ongoing GDOP
     #[cfg_attr(feature = "serde", serde(default = "default_gdop_threshold"))]
@@ -192,10 +349,73 @@ pub struct SolverOpts {
     /// Filter options
     #[cfg_attr(feature = "serde", serde(default = "default_filter_opts"))]
     pub filter_opts: Option<FilterOpts>,
+    /// [Decomposition] used to solve the least squares. Prefer
+    /// [Decomposition::QR] or [Decomposition::SVD] in ill conditioned geometries.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub decomposition: Decomposition,
     /// Deploy a post-fit denoising Kalman Filter to denoise and further improve PVT solutions,
     /// at the expense of more calculations.
     #[cfg_attr(feature = "serde", serde(default = "default_postfit_kf"))]
     pub postfit_kf: bool,
+    /// A-priori measurement [NoiseModel], used to weight each observation
+    /// in the navigation process.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub noise: NoiseModel,
+    /// [ColdStart] method, when deployed without apriori position.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub cold_start: ColdStart,
+    /// Deterministic processing: [Candidate]s and their observations are sorted
+    /// in a canonical order prior any processing, so identical inputs
+    /// always generate bit-identical solutions, whatever order they were provided in.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub deterministic: bool,
+    /// Clock holdover: when fewer vehicles than the navigation technique requires
+    /// are in sight, keep publishing (position-held) solutions by propagating
+    /// the latest clock offset and drift, for at most this [Duration].
+    /// Such solutions expose the elapsed holdover duration (quality indicator).
+    /// Disabled by default: resolution attempts fail instead.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_holdover: Option<Duration>,
+    /// Barometric altitude aiding (see [Aiding](crate::prelude::Aiding)):
+    /// only applies when the PDOP exceeds this threshold (degraded geometry)
+    /// and while dead reckoning. Continuous aiding when undefined (default).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub baro_max_pdop: Option<f64>,
+    /// Kalman filter innovation gate: measurements are processed one at a time,
+    /// and rejected when their innovation exceeds this many standard deviations.
+    /// No rejection when undefined (default).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_innovation_sigma: Option<f64>,
+    /// Maximal number of samples retained per SV by the signal trackers
+    /// (ambiguity resolution, cycle slip detection). The oldest samples are
+    /// dropped first, so memory usage remains flat during long sessions.
+    #[cfg_attr(feature = "serde", serde(default = "default_tracking_buffer_capacity"))]
+    pub tracking_buffer_capacity: usize,
+    /// Estimates the GPS to Galileo time offset (GGTO) as a slowly varying state,
+    /// when GPS and Galileo vehicles are mixed. Galileo clocks are then
+    /// expressed in GPST, and the estimate is exposed in the solution.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub estimate_ggto: bool,
+    /// Asynchronous observations: [Candidate]s sampled within this [Duration] of the solution
+    /// [Epoch](crate::prelude::Epoch) are propagated to it, using their Doppler observations
+    /// (different message latencies). Candidates further away, or without Doppler, are rejected.
+    /// Candidates are processed at their own sampling instant when undefined (default).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_epoch_misalignment: Option<Duration>,
+    /// Detects and repairs the 1 ms jumps of receivers that steer their clock,
+    /// so observations remain continuous and the filters and trackers are not reset.
+    /// Requires Doppler or phase range observations.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub repair_clock_jumps: bool,
+    /// Iterative light-time solution: the transmission [Epoch](crate::prelude::Epoch)
+    /// is refined from the geometric range to the previous solution, rather than
+    /// solely derived from the pseudo range. Improves the geometric consistency of precise modes.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub light_time_iteration: bool,
+    /// Monitors the consistency of each constellation, see [ConstellationMonitor].
+    /// Disabled when undefined (default).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub constellation_monitor: Option<ConstellationMonitor>,
 }
 
 impl Default for SolverOpts {
@@ -205,13 +425,26 @@ impl Default for SolverOpts {
             gdop_threshold: default_gdop_threshold(),
             tdop_threshold: default_tdop_threshold(),
             filter_opts: default_filter_opts(),
+            decomposition: Decomposition::default(),
             postfit_kf: default_postfit_kf(),
+            noise: NoiseModel::default(),
+            cold_start: ColdStart::default(),
+            deterministic: false,
+            max_holdover: None,
+            baro_max_pdop: None,
+            max_innovation_sigma: None,
+            tracking_buffer_capacity: default_tracking_buffer_capacity(),
+            estimate_ggto: false,
+            max_epoch_misalignment: None,
+            repair_clock_jumps: false,
+            light_time_iteration: false,
+            constellation_monitor: None,
         }
     }
 }
 
 #[derive(Default, Clone, Debug, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FilterOpts {
     /// Weight Matrix
     #[cfg_attr(feature = "serde", serde(default = "default_weight_matrix"))]
@@ -269,6 +502,12 @@ pub struct Modeling {
     /// (static +5/+10m eastern error).
     #[cfg_attr(feature = "serde", serde(default))]
     pub earth_rotation: bool,
+    /// Compute the signal propagation in an inertial frame, then rotate back
+    /// to ECEF at reception time: rigorous alternative to the ECEF rotation
+    /// (Sagnac) correction, that also preserves the SV velocity.
+    /// Only effective when [Modeling::earth_rotation] is enabled.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub eci_geometry: bool,
     /// Compensate for signal phase windup. This only impacts
     /// strategies that use raw phase like [Method::PPP].
     #[cfg_attr(feature = "serde", serde(default))]
@@ -283,6 +522,10 @@ pub struct Modeling {
     /// gravitational effect.
     #[cfg_attr(feature = "serde", serde(default))]
     pub solid_tides: bool,
+    /// Compensate for the elevation dependent code bias of the BeiDou-2
+    /// IGSO and MEO vehicles (+/- 1m), on B1I, B2I and B3I.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bds2_code_bias: bool,
 }
 
 impl Default for Modeling {
@@ -293,25 +536,60 @@ impl Default for Modeling {
             tropo_delay: default_tropo(),
             sv_total_group_delay: default_sv_tgd(),
             earth_rotation: default_earth_rot(),
+            eci_geometry: false,
             phase_windup: default_phase_windup(),
             solid_tides: default_solid_tides(),
             cable_delay: default_cable_delay(),
             relativistic_clock_bias: default_relativistic_clock_bias(),
             relativistic_path_range: default_relativistic_path_range(),
+            bds2_code_bias: default_bds2_code_bias(),
         }
     }
 }
 
-#[derive(Default, Debug, Clone, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Config {
+    /// [Config] format version (see [CONFIG_VERSION]).
+    /// Unversioned [Config]s are interpreted in the current format, unknown versions
+    /// are rejected ([Error::UnsupportedVersion]). Unknown fields are ignored when deserializing.
+    #[cfg_attr(
+        feature = "serde",
+        serde(
+            default = "default_config_version",
+            deserialize_with = "deserialize_config_version"
+        )
+    )]
+    pub version: u32,
     /// Type of solutions to form.
     #[cfg_attr(feature = "serde", serde(default))]
     pub sol_type: PVTSolutionType,
-    /// Time scale in which we express the PVT solutions,
+    /// Time scale in which we express the PVT solutions:
+    /// both solution [Epoch]s and clock offsets are converted to this [TimeScale].
     /// [TimeScale::GPST] is the default value.
     #[cfg_attr(feature = "serde", serde(default = "default_timescale"))]
     pub timescale: TimeScale,
+    /// [LeapSecondPolicy] of the UTC timestamped GLONASS candidates,
+    /// around leap second events.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub glonass_leap_second: LeapSecondPolicy,
+    /// [Timestamping] convention of the solution [Epoch](crate::prelude::Epoch)s:
+    /// receiver timestamp (default) or corrected by the estimated clock offset.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub timestamping: Timestamping,
+    /// [ReferenceFrame] realized by the orbital states proposed to the [Solver](crate::prelude::Solver)
+    /// (ITRF2020 for IGS20 precise products, WGS84 for GPS broadcast ephemerides).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub orbit_frame: ReferenceFrame,
+    /// [ReferenceFrame] in which the solutions are expressed. When it differs from
+    /// the `orbit_frame`, the resolved position is transformed at the solution [Epoch](crate::prelude::Epoch).
+    /// Solutions are expressed in the `orbit_frame` by default.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub output_frame: Option<ReferenceFrame>,
+    /// [DatumTransformation] (7 or 14 parameters), applied to the solutions
+    /// once expressed in the output [ReferenceFrame], for users working in national datums.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub datum: Option<DatumTransformation>,
     /// Solver method (strategy) used.
     #[cfg_attr(feature = "serde", serde(default))]
     pub method: Method,
@@ -320,13 +598,27 @@ pub struct Config {
     pub profile: Profile,
     /// Possible remote reference site coordinates, in ECEF [m].
     /// Must be defined in case RTK navigation is selected.
+    /// Expressed in the output [ReferenceFrame], or in the `datum` when it applies to the remote site.
     pub remote_site: Option<(f64, f64, f64)>,
-    /// Interpolation order
-    #[cfg_attr(feature = "serde", serde(default = "default_interp"))]
-    pub interp_order: usize,
+    /// Known baseline length between the rover and the `remote_site` (rigid platform),
+    /// applied as a pseudo-observation at every iteration. Requires the `remote_site`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub baseline_length: Option<BaselineConstraint>,
+    /// [Interpolation] method and orders, proposed to the [OrbitSource]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub interpolation: Interpolation,
     /// Fixed altitude: reduces the need of 4 to 3 SV to obtain 3D solutions.
     #[cfg_attr(feature = "serde", serde(default))]
     pub fixed_altitude: Option<f64>,
+    /// Height constraint: unlike [Self::fixed_altitude], the altitude remains estimated
+    /// but is constrained by this pseudo-observation, at every iteration.
+    /// Improves the horizontal accuracy and availability.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub height_constraint: Option<HeightConstraint>,
+    /// External frequency reference: constrains the clock estimate by the [ClockConstraint]
+    /// prediction, which improves the TDOP-limited solutions. Unconstrained when undefined (default).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub clock_constraint: Option<ClockConstraint>,
     /// Pseudo Range smoothing. Use this to improve solutions accuracy.
     /// This applies to all positioning strategies.
     #[cfg_attr(feature = "serde", serde(default = "default_smoothing"))]
@@ -339,6 +631,12 @@ pub struct Config {
     /// Antenna Reference Point (ARP) expressed as ENU offset [m]
     #[cfg_attr(feature = "serde", serde(default))]
     pub arp_enu: Option<(f64, f64, f64)>,
+    /// Antenna lever arm, from the vehicle reference point to the antenna,
+    /// expressed in the body frame (forward, right, down) [m].
+    /// Rotated into the local frame using the [Attitude] provided at each [Epoch],
+    /// see [Solver::resolve_with_attitude].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub lever_arm_body: Option<(f64, f64, f64)>,
     /// Solver customization
     #[cfg_attr(feature = "serde", serde(default))]
     pub solver: SolverOpts,
@@ -373,6 +671,14 @@ pub struct Config {
     /// Minimal SNR for an SV to contribute to the solution.
     #[cfg_attr(feature = "serde", serde(default))]
     pub min_snr: Option<f64>,
+    /// Minimal SNR per [Carrier]. Overrides [Self::min_snr] for the listed carriers.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub min_snr_per_carrier: Vec<CarrierSnrMask>,
+    /// Ranging signals priority, from most to least prefered [Carrier].
+    /// The pseudo range of the first observed one is used (single frequency navigation),
+    /// the default selection (L1 first) applies when none of them is observed.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub signal_priority: Vec<Carrier>,
     /// Maximal tropo bias that we tolerate (in [m]).
     /// Has no effect if modeling.tropo_delay is disabled.
     #[cfg_attr(feature = "serde", serde(default = "max_tropo_bias"))]
@@ -381,12 +687,71 @@ pub struct Config {
     /// Has no effect if modeling.iono_delay is disabled.
     #[cfg_attr(feature = "serde", serde(default = "max_iono_bias"))]
     pub max_iono_bias: f64,
+    /// Maximal difference [m/s] between the range rate measured by Doppler and the range rate
+    /// predicted from the SV velocity and the receiver motion (receiver clock drift removed).
+    /// Vehicles exceeding it (or 3 Doppler standard deviations of their signal, if larger,
+    /// see [NoiseModel]) are discarded: a cheap sanity check against bad ephemerides.
+    /// Disabled when undefined (default).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub max_doppler_mismatch: Option<f64>,
     /// Atmospherical and Physical [Modeling] used to improve the accuracy of solution.
     #[cfg_attr(feature = "serde", serde(default))]
     pub modeling: Modeling,
 }
 
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: default_config_version(),
+            sol_type: PVTSolutionType::default(),
+            timescale: default_timescale(),
+            glonass_leap_second: LeapSecondPolicy::default(),
+            timestamping: Timestamping::default(),
+            orbit_frame: ReferenceFrame::default(),
+            output_frame: None,
+            datum: None,
+            method: Method::default(),
+            profile: Profile::default(),
+            remote_site: None,
+            baseline_length: None,
+            interpolation: Interpolation::default(),
+            fixed_altitude: None,
+            height_constraint: None,
+            clock_constraint: None,
+            code_smoothing: default_smoothing(),
+            int_delay: Vec::new(),
+            arp_enu: None,
+            lever_arm_body: None,
+            solver: SolverOpts::default(),
+            externalref_delay: None,
+            max_sv_occultation_percent: None,
+            min_sv_elev: None,
+            min_sv_azim: None,
+            max_sv_azim: None,
+            min_snr: None,
+            min_snr_per_carrier: Vec::new(),
+            signal_priority: Vec::new(),
+            max_tropo_bias: max_tropo_bias(),
+            max_iono_bias: max_iono_bias(),
+            max_doppler_mismatch: None,
+            modeling: Modeling::default(),
+        }
+    }
+}
+
 impl Config {
+    /// Returns minimal SNR that applies to this [Carrier], if any
+    pub(crate) fn snr_mask(&self, carrier: Carrier) -> Option<f64> {
+        self.min_snr_per_carrier
+            .iter()
+            .find(|mask| mask.carrier == carrier)
+            .map(|mask| mask.min_snr)
+            .or(self.min_snr)
+    }
+    /// Returns true if a SNR mask applies
+    pub(crate) fn has_snr_mask(&self) -> bool {
+        self.min_snr.is_some() || !self.min_snr_per_carrier.is_empty()
+    }
     /// Returns [Config] for static PPP positioning, with desired [Method].
     /// You can then customize [Self] as you will.
     pub fn static_ppp_preset(method: Method) -> Self {
@@ -409,6 +774,18 @@ impl Config {
         s.max_iono_bias = max_iono_bias();
         s
     }
+    /// Returns the remote site coordinates in ECEF [m] at this [Epoch](crate::prelude::Epoch),
+    /// expressed in the `orbit_frame` (see `remote_site`).
+    pub fn remote_site_ecef_m(&self, t: Epoch) -> Option<(f64, f64, f64)> {
+        let mut remote_site = self.remote_site?;
+        if let Some(datum) = self.datum.filter(|datum| datum.remote_site) {
+            remote_site = datum.helmert.inverse().apply(remote_site, t);
+        }
+        if let Some(output_frame) = self.output_frame {
+            remote_site = output_frame.transform(self.orbit_frame, remote_site, t);
+        }
+        Some(remote_site)
+    }
     /// Returns [Config] for static RTK positioning, with desired [Method],
     /// Remote site coordinates expressed in meters ECEF.
     /// You can then customize [Self] as you will.
@@ -440,3 +817,58 @@ impl Config {
         s
     }
 }
+
+#[cfg(all(test, feature = "serde"))]
+mod test {
+    use super::{CarrierSnrMask, Config, InternalDelay, CONFIG_VERSION};
+    use crate::prelude::{Carrier, Duration, Filter, Method, NoiseSpec, TimeScale};
+
+    #[test]
+    fn config_serde_roundtrip() {
+        let mut cfg = Config::static_rtk_preset(Method::PPP, (1.0, 2.0, 3.0));
+        cfg.timescale = TimeScale::GST;
+        cfg.int_delay.push(InternalDelay {
+            delay: 10.0E-9,
+            frequency: 1575.42E6,
+        });
+        cfg.min_snr_per_carrier.push(CarrierSnrMask {
+            carrier: Carrier::L5,
+            min_snr: 35.0,
+        });
+        cfg.solver.filter = Filter::Kalman;
+        cfg.solver.max_holdover = Some(Duration::from_seconds(30.0));
+        cfg.solver.baro_max_pdop = Some(3.0);
+        cfg.solver.noise.specs.push(NoiseSpec {
+            carrier: Some(Carrier::L1),
+            code_sigma_m: Some(0.5),
+            ..Default::default()
+        });
+        cfg.modeling.solid_tides = true;
+
+        let content = serde_json::to_string(&cfg).unwrap();
+        let parsed: Config = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed, cfg);
+        assert!(parsed.diff(&cfg).is_empty());
+
+        // unversioned and unknown fields are tolerated
+        let parsed: Config =
+            serde_json::from_str("{\"method\": \"CPP\", \"future_option\": 1.0}").unwrap();
+        assert_eq!(parsed.version, CONFIG_VERSION);
+        assert_eq!(parsed.method, Method::CPP);
+    }
+
+    #[test]
+    fn config_unsupported_version() {
+        let content = format!("{{\"version\": {}, \"method\": \"CPP\"}}", CONFIG_VERSION);
+        let parsed: Config = serde_json::from_str(&content).unwrap();
+        assert_eq!(parsed.version, CONFIG_VERSION);
+
+        for version in [0, CONFIG_VERSION + 1] {
+            let content = format!("{{\"version\": {}, \"method\": \"CPP\"}}", version);
+            let error = serde_json::from_str::<Config>(&content).unwrap_err();
+            assert!(error
+                .to_string()
+                .contains(&format!("unsupported config version {}", version)));
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::prelude::{Carrier, Constellation};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+fn default_code_sigma() -> f64 {
+    1.0
+}
+
+fn default_phase_sigma() -> f64 {
+    0.01
+}
+
+fn default_doppler_sigma() -> f64 {
+    0.1
+}
+
+/// SBAS GEO vehicles are weaker ranging sources (broadcast orbits, signal quality)
+fn default_specs() -> Vec<NoiseSpec> {
+    vec![NoiseSpec {
+        constellation: Some(Constellation::SBAS),
+        code_sigma_m: Some(5.0),
+        ..Default::default()
+    }]
+}
+
+/// [NoiseSpec] supersedes the default [NoiseModel] values,
+/// for a given [Constellation] and/or [Carrier].
+/// When both are defined, the specification only applies to that very signal.
+/// Undefined standard deviations fall back to the [NoiseModel] defaults.
+#[derive(Default, Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NoiseSpec {
+    /// [Constellation] this specification applies to (all if undefined).
+    /// [Constellation::SBAS] applies to all augmentation systems.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub constellation: Option<Constellation>,
+    /// [Carrier] this specification applies to (all if undefined)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub carrier: Option<Carrier>,
+    /// Pseudo range standard deviation [m]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub code_sigma_m: Option<f64>,
+    /// Phase range standard deviation [m]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub phase_sigma_m: Option<f64>,
+    /// Doppler standard deviation [m/s]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub doppler_sigma_m_s: Option<f64>,
+}
+
+impl NoiseSpec {
+    fn matches(spec: Constellation, constellation: Constellation) -> bool {
+        spec == constellation || (spec == Constellation::SBAS && constellation.is_sbas())
+    }
+    /// Returns specification score, for this [Constellation] and [Carrier]:
+    /// None if it does not apply, higher scores for more specific definitions.
+    fn score(&self, constellation: Constellation, carrier: Carrier) -> Option<u8> {
+        match (self.constellation, self.carrier) {
+            (Some(c), Some(s)) => (Self::matches(c, constellation) && s == carrier).then_some(3),
+            (None, Some(s)) => (s == carrier).then_some(2),
+            (Some(c), None) => Self::matches(c, constellation).then_some(1),
+            (None, None) => Some(0),
+        }
+    }
+}
+
+/// A-priori measurement noise, expressed as standard deviations.
+/// This is used to form the weight matrix of the navigation process,
+/// whatever the [Filter] in use.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NoiseModel {
+    /// Default pseudo range standard deviation [m]
+    #[cfg_attr(feature = "serde", serde(default = "default_code_sigma"))]
+    pub code_sigma_m: f64,
+    /// Default phase range standard deviation [m]
+    #[cfg_attr(feature = "serde", serde(default = "default_phase_sigma"))]
+    pub phase_sigma_m: f64,
+    /// Default doppler standard deviation [m/s]
+    #[cfg_attr(feature = "serde", serde(default = "default_doppler_sigma"))]
+    pub doppler_sigma_m_s: f64,
+    /// Per [Constellation] and per [Carrier] [NoiseSpec]ifications.
+    /// SBAS GEO vehicles are de-weighted by default.
+    #[cfg_attr(feature = "serde", serde(default = "default_specs"))]
+    pub specs: Vec<NoiseSpec>,
+}
+
+impl Default for NoiseModel {
+    fn default() -> Self {
+        Self {
+            code_sigma_m: default_code_sigma(),
+            phase_sigma_m: default_phase_sigma(),
+            doppler_sigma_m_s: default_doppler_sigma(),
+            specs: default_specs(),
+        }
+    }
+}
+
+impl NoiseModel {
+    /// Adds one [NoiseSpec] to [Self]
+    pub fn with_spec(&self, spec: NoiseSpec) -> Self {
+        let mut s = self.clone();
+        s.specs.push(spec);
+        s
+    }
+    /// Returns most specific value, picked by `f`, that applies to this signal
+    fn lookup<F: Fn(&NoiseSpec) -> Option<f64>>(
+        &self,
+        constellation: Constellation,
+        carrier: Carrier,
+        f: F,
+    ) -> Option<f64> {
+        self.specs
+            .iter()
+            .filter_map(|spec| {
+                let score = spec.score(constellation, carrier)?;
+                let value = f(spec)?;
+                Some((score, value))
+            })
+            .max_by_key(|(score, _)| *score)
+            .map(|(_, value)| value)
+    }
+    /// Returns pseudo range standard deviation [m] for this signal
+    pub fn code_sigma(&self, constellation: Constellation, carrier: Carrier) -> f64 {
+        self.lookup(constellation, carrier, |spec| spec.code_sigma_m)
+            .unwrap_or(self.code_sigma_m)
+    }
+    /// Returns phase range standard deviation [m] for this signal
+    pub fn phase_sigma(&self, constellation: Constellation, carrier: Carrier) -> f64 {
+        self.lookup(constellation, carrier, |spec| spec.phase_sigma_m)
+            .unwrap_or(self.phase_sigma_m)
+    }
+    /// Returns doppler standard deviation [m/s] for this signal
+    pub fn doppler_sigma(&self, constellation: Constellation, carrier: Carrier) -> f64 {
+        self.lookup(constellation, carrier, |spec| spec.doppler_sigma_m_s)
+            .unwrap_or(self.doppler_sigma_m_s)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{NoiseModel, NoiseSpec};
+    use crate::prelude::{Carrier, Constellation};
+    #[test]
+    fn noise_spec_priority() {
+        let model = NoiseModel::default()
+            .with_spec(NoiseSpec {
+                constellation: Some(Constellation::Galileo),
+                code_sigma_m: Some(0.5),
+                ..Default::default()
+            })
+            .with_spec(NoiseSpec {
+                carrier: Some(Carrier::L5),
+                code_sigma_m: Some(0.3),
+                ..Default::default()
+            })
+            .with_spec(NoiseSpec {
+                constellation: Some(Constellation::Galileo),
+                carrier: Some(Carrier::E5A),
+                code_sigma_m: Some(0.2),
+                ..Default::default()
+            });
+
+        assert_eq!(model.code_sigma(Constellation::GPS, Carrier::L1), 1.0);
+        assert_eq!(model.code_sigma(Constellation::GPS, Carrier::L5), 0.3);
+        assert_eq!(model.code_sigma(Constellation::Galileo, Carrier::E1), 0.5);
+        assert_eq!(model.code_sigma(Constellation::Galileo, Carrier::E5A), 0.2);
+        assert_eq!(
+            model.phase_sigma(Constellation::Galileo, Carrier::E5A),
+            0.01
+        );
+        assert_eq!(model.doppler_sigma(Constellation::GPS, Carrier::L1), 0.1);
+
+        // SBAS GEO vehicles, whatever the augmentation system
+        assert_eq!(model.code_sigma(Constellation::EGNOS, Carrier::L1), 5.0);
+        assert_eq!(model.code_sigma(Constellation::WAAS, Carrier::L5), 5.0);
+        assert_eq!(model.phase_sigma(Constellation::EGNOS, Carrier::L1), 0.01);
+    }
+}
@@ -0,0 +1,229 @@
+//! Configuration summary and diff reporting
+use super::{Config, Modeling, SolverOpts};
+
+/// [ConfigDiff] describes a parameter that differs between two [Config]s.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDiff {
+    /// Parameter name
+    pub key: &'static str,
+    /// Value in the reference [Config]
+    pub lhs: String,
+    /// Value in the compared [Config]
+    pub rhs: String,
+}
+
+impl std::fmt::Display for ConfigDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}: {} -> {}", self.key, self.lhs, self.rhs)
+    }
+}
+
+macro_rules! entries {
+    ($($key:literal => $value:expr),* $(,)?) => {
+        vec![$(($key, format!("{:?}", $value))),*]
+    };
+}
+
+impl Modeling {
+    fn entries(&self) -> Vec<(&'static str, String)> {
+        // destructured so new fields can't be forgotten
+        let Self {
+            sv_clock_bias,
+            sv_total_group_delay,
+            relativistic_clock_bias,
+            relativistic_path_range,
+            tropo_delay,
+            iono_delay,
+            earth_rotation,
+            eci_geometry,
+            phase_windup,
+            cable_delay,
+            solid_tides,
+            bds2_code_bias,
+        } = self;
+        entries!(
+            "modeling.sv_clock_bias" => sv_clock_bias,
+            "modeling.sv_total_group_delay" => sv_total_group_delay,
+            "modeling.relativistic_clock_bias" => relativistic_clock_bias,
+            "modeling.relativistic_path_range" => relativistic_path_range,
+            "modeling.tropo_delay" => tropo_delay,
+            "modeling.iono_delay" => iono_delay,
+            "modeling.earth_rotation" => earth_rotation,
+            "modeling.eci_geometry" => eci_geometry,
+            "modeling.phase_windup" => phase_windup,
+            "modeling.cable_delay" => cable_delay,
+            "modeling.solid_tides" => solid_tides,
+            "modeling.bds2_code_bias" => bds2_code_bias,
+        )
+    }
+}
+
+impl SolverOpts {
+    fn entries(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            gdop_threshold,
+            tdop_threshold,
+            filter,
+            filter_opts,
+            decomposition,
+            postfit_kf,
+            noise,
+            cold_start,
+            deterministic,
+            max_holdover,
+            baro_max_pdop,
+            max_innovation_sigma,
+            tracking_buffer_capacity,
+            estimate_ggto,
+            max_epoch_misalignment,
+            repair_clock_jumps,
+            light_time_iteration,
+            constellation_monitor,
+        } = self;
+        entries!(
+            "solver.gdop_threshold" => gdop_threshold,
+            "solver.tdop_threshold" => tdop_threshold,
+            "solver.filter" => filter,
+            "solver.filter_opts" => filter_opts,
+            "solver.decomposition" => decomposition,
+            "solver.postfit_kf" => postfit_kf,
+            "solver.noise.code_sigma_m" => noise.code_sigma_m,
+            "solver.noise.phase_sigma_m" => noise.phase_sigma_m,
+            "solver.noise.doppler_sigma_m_s" => noise.doppler_sigma_m_s,
+            "solver.noise.specs" => noise.specs,
+            "solver.cold_start" => cold_start,
+            "solver.deterministic" => deterministic,
+            "solver.max_holdover" => max_holdover,
+            "solver.baro_max_pdop" => baro_max_pdop,
+            "solver.max_innovation_sigma" => max_innovation_sigma,
+            "solver.tracking_buffer_capacity" => tracking_buffer_capacity,
+            "solver.estimate_ggto" => estimate_ggto,
+            "solver.max_epoch_misalignment" => max_epoch_misalignment,
+            "solver.repair_clock_jumps" => repair_clock_jumps,
+            "solver.light_time_iteration" => light_time_iteration,
+            "solver.constellation_monitor" => constellation_monitor,
+        )
+    }
+}
+
+impl Config {
+    /// Returns every effective parameter of this [Config] (defaults included),
+    /// as (name, value) pairs. Nested structures are flattened with dotted names.
+    pub fn entries(&self) -> Vec<(&'static str, String)> {
+        let Self {
+            version,
+            sol_type,
+            timescale,
+            glonass_leap_second,
+            timestamping,
+            orbit_frame,
+            output_frame,
+            datum,
+            method,
+            profile,
+            remote_site,
+            baseline_length,
+            interpolation,
+            fixed_altitude,
+            height_constraint,
+            clock_constraint,
+            code_smoothing,
+            int_delay,
+            arp_enu,
+            lever_arm_body,
+            solver,
+            externalref_delay,
+            max_sv_occultation_percent,
+            min_sv_elev,
+            min_sv_azim,
+            max_sv_azim,
+            min_snr,
+            min_snr_per_carrier,
+            signal_priority,
+            max_tropo_bias,
+            max_iono_bias,
+            max_doppler_mismatch,
+            modeling,
+        } = self;
+        let mut entries = entries!(
+            "version" => version,
+            "sol_type" => sol_type,
+            "timescale" => timescale,
+            "glonass_leap_second" => glonass_leap_second,
+            "timestamping" => timestamping,
+            "orbit_frame" => orbit_frame,
+            "output_frame" => output_frame,
+            "datum" => datum,
+            "method" => method,
+            "profile" => profile,
+            "remote_site" => remote_site,
+            "baseline_length" => baseline_length,
+            "interpolation.method" => interpolation.method,
+            "interpolation.orbit_order" => interpolation.orbit_order,
+            "interpolation.clock_order" => interpolation.clock_order,
+            "fixed_altitude" => fixed_altitude,
+            "height_constraint" => height_constraint,
+            "clock_constraint" => clock_constraint,
+            "code_smoothing" => code_smoothing,
+            "int_delay" => int_delay,
+            "arp_enu" => arp_enu,
+            "lever_arm_body" => lever_arm_body,
+            "externalref_delay" => externalref_delay,
+            "max_sv_occultation_percent" => max_sv_occultation_percent,
+            "min_sv_elev" => min_sv_elev,
+            "min_sv_azim" => min_sv_azim,
+            "max_sv_azim" => max_sv_azim,
+            "min_snr" => min_snr,
+            "min_snr_per_carrier" => min_snr_per_carrier,
+            "signal_priority" => signal_priority,
+            "max_tropo_bias" => max_tropo_bias,
+            "max_iono_bias" => max_iono_bias,
+            "max_doppler_mismatch" => max_doppler_mismatch,
+        );
+        entries.extend(solver.entries());
+        entries.extend(modeling.entries());
+        entries
+    }
+    /// Renders the effective configuration, one `name = value` parameter per line,
+    /// so processing logs capture exactly what a run used.
+    pub fn summary(&self) -> String {
+        self.entries()
+            .iter()
+            .map(|(key, value)| format!("{} = {}", key, value))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+    /// Returns the list of parameters that differ between [Self] and `rhs`.
+    pub fn diff(&self, rhs: &Self) -> Vec<ConfigDiff> {
+        self.entries()
+            .into_iter()
+            .zip(rhs.entries())
+            .filter_map(|((key, lhs), (_, rhs))| {
+                if lhs != rhs {
+                    Some(ConfigDiff { key, lhs, rhs })
+                } else {
+                    None
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::prelude::{Config, Filter, Method};
+    #[test]
+    fn config_diff() {
+        let default = Config::default();
+        assert!(default.diff(&default).is_empty());
+        assert!(default.summary().contains("modeling.sv_clock_bias = true"));
+
+        let mut cfg = Config::static_ppp_preset(Method::PPP);
+        cfg.solver.filter = Filter::Kalman;
+
+        let diff = default.diff(&cfg);
+        let keys = diff.iter().map(|diff| diff.key).collect::<Vec<_>>();
+        assert_eq!(keys, vec!["method", "min_sv_elev", "solver.filter"]);
+        assert_eq!(diff[0].to_string(), "method: CPP -> PPP");
+    }
+}
@@ -0,0 +1,519 @@
+//! RINEX CLK precise clock products
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader},
+    path::Path,
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use crate::{
+    clock::ClockSource,
+    prelude::{ClockCorrection, Duration, Epoch, Interpolation, TimeScale, SV},
+};
+
+/// RINEX CLK parsing errors
+#[derive(Debug, Error)]
+pub enum ClkError {
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("not a valid clock file: missing header")]
+    InvalidHeader,
+    #[error("invalid clock record (line {0})")]
+    InvalidRecord(usize),
+}
+
+/// Default outlier screening threshold [s]
+const DEFAULT_OUTLIER_THRESHOLD_S: f64 = 1.0E-9;
+
+/// [PreciseClocks] is a [ClockSource] backed by RINEX CLK precise clock products
+/// (typically 30s or 5s sampling), to be paired with precise orbits for PPP.
+/// Clock offsets are interpolated with a polynomial of the [Interpolation::clock_order]
+/// (linear by default), over the surrounding samples:
+/// we never extrapolate, nor interpolate over more than a single missing sample.
+/// Samples that are not consistent with their neighbors (outliers) are discarded at parsing time.
+#[derive(Debug, Clone, Default)]
+pub struct PreciseClocks {
+    /// Product [TimeScale]
+    timescale: TimeScale,
+    /// Product sampling period
+    sampling: Option<Duration>,
+    /// Clock offsets [s], per [SV]
+    clocks: BTreeMap<SV, Vec<(Epoch, f64)>>,
+}
+
+fn parse_timescale(content: &str) -> TimeScale {
+    match content.trim() {
+        "GAL" => TimeScale::GST,
+        "BDT" => TimeScale::BDT,
+        "QZS" => TimeScale::QZSST,
+        "UTC" | "GLO" => TimeScale::UTC,
+        "TAI" => TimeScale::TAI,
+        _ => TimeScale::GPST,
+    }
+}
+
+impl PreciseClocks {
+    /// Parses [PreciseClocks] from local RINEX CLK file,
+    /// with default outlier screening.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ClkError> {
+        let fd = std::fs::File::open(path)?;
+        Self::from_reader(BufReader::new(fd))
+    }
+    /// Parses [PreciseClocks] from any RINEX CLK content,
+    /// with default outlier screening.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, ClkError> {
+        let mut clk = Self::from_reader_unscreened(reader)?;
+        clk.screen(DEFAULT_OUTLIER_THRESHOLD_S);
+        Ok(clk)
+    }
+    /// Parses [PreciseClocks] from any RINEX CLK content, without outlier screening.
+    /// Use [Self::screen] to apply a custom threshold.
+    pub fn from_reader_unscreened<R: BufRead>(reader: R) -> Result<Self, ClkError> {
+        let mut clk = Self::default();
+        let mut end_of_header = false;
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line_num = index + 1;
+
+            if !end_of_header {
+                let marker = line.get(60..).unwrap_or_default().trim();
+                if marker == "TIME SYSTEM ID" {
+                    clk.timescale = parse_timescale(&line[..60]);
+                } else if marker == "END OF HEADER" {
+                    end_of_header = true;
+                }
+                continue;
+            }
+
+            // satellite clock records only
+            if !line.starts_with("AS ") {
+                continue;
+            }
+
+            let items = line.split_whitespace().collect::<Vec<_>>();
+            if items.len() < 10 {
+                return Err(ClkError::InvalidRecord(line_num));
+            }
+
+            let sv = SV::from_str(items[1]).map_err(|_| ClkError::InvalidRecord(line_num))?;
+            let t = Self::parse_epoch(&items[2..8], clk.timescale)
+                .ok_or(ClkError::InvalidRecord(line_num))?;
+            let bias = f64::from_str(&items[9].replace('D', "E"))
+                .map_err(|_| ClkError::InvalidRecord(line_num))?;
+
+            clk.clocks.entry(sv).or_default().push((t, bias));
+        }
+
+        if !end_of_header {
+            return Err(ClkError::InvalidHeader);
+        }
+
+        for clocks in clk.clocks.values_mut() {
+            clocks.sort_by(|(t_a, _), (t_b, _)| t_a.cmp(t_b));
+        }
+
+        // sampling period: shortest interval
+        clk.sampling = clk
+            .clocks
+            .values()
+            .flat_map(|clocks| clocks.windows(2).map(|w| w[1].0 - w[0].0))
+            .filter(|dt| *dt > Duration::ZERO)
+            .min();
+
+        Ok(clk)
+    }
+    fn parse_epoch(items: &[&str], ts: TimeScale) -> Option<Epoch> {
+        let seconds = f64::from_str(items[5]).ok()?;
+        Some(Epoch::from_gregorian(
+            i32::from_str(items[0]).ok()?,
+            u8::from_str(items[1]).ok()?,
+            u8::from_str(items[2]).ok()?,
+            u8::from_str(items[3]).ok()?,
+            u8::from_str(items[4]).ok()?,
+            seconds.trunc() as u8,
+            (seconds.fract() * 1.0E9).round() as u32,
+            ts,
+        ))
+    }
+    /// Discards samples that deviate from the linear prediction formed by their
+    /// two neighbors, by more than `threshold_s` (in seconds). Clock jumps
+    /// are preserved, because the samples that follow a jump remain self consistent.
+    pub fn screen(&mut self, threshold_s: f64) {
+        for clocks in self.clocks.values_mut() {
+            if clocks.len() < 3 {
+                continue;
+            }
+            let mut outliers = vec![false; clocks.len()];
+            for i in 1..clocks.len() - 1 {
+                let (t_prev, prev) = clocks[i - 1];
+                let (t, value) = clocks[i];
+                let (t_next, next) = clocks[i + 1];
+                let ratio = (t - t_prev).to_seconds() / (t_next - t_prev).to_seconds();
+                let predicted = prev + (next - prev) * ratio;
+
+                // a single spike disturbs both neighbors' predictions,
+                // a jump is consistent on one side at least
+                let consistent_prev = i < 2 || {
+                    let (t_pp, pp) = clocks[i - 2];
+                    let slope = (prev - pp) / (t_prev - t_pp).to_seconds();
+                    (prev + slope * (t - t_prev).to_seconds() - value).abs() <= threshold_s
+                };
+                let consistent_next = i + 2 >= clocks.len() || {
+                    let (t_nn, nn) = clocks[i + 2];
+                    let slope = (nn - next) / (t_nn - t_next).to_seconds();
+                    (next - slope * (t_next - t).to_seconds() - value).abs() <= threshold_s
+                };
+
+                if (value - predicted).abs() > threshold_s && !consistent_prev && !consistent_next {
+                    outliers[i] = true;
+                }
+            }
+            let mut index = 0;
+            clocks.retain(|_| {
+                index += 1;
+                !outliers[index - 1]
+            });
+        }
+    }
+    /// Stitches the following (typically next day) product to this one.
+    /// Daily clock solutions are aligned to different clock datums: the common offset between
+    /// the two products (median over the vehicles, at the first epoch of `next`) is removed from `next`,
+    /// so the clock offsets, and the receiver clock solutions, remain continuous across the boundary.
+    /// Samples of epochs described by both products are then averaged.
+    /// Returns the datum offset [s] that was removed, if it could be estimated.
+    pub fn stitch(&mut self, next: Self) -> Option<f64> {
+        let mut offsets = next
+            .clocks
+            .iter()
+            .filter_map(|(sv, clocks)| {
+                let (t, value) = clocks.first()?;
+                let prev = self.boundary_offset(*sv, t.to_time_scale(self.timescale))?;
+                Some(value - prev)
+            })
+            .collect::<Vec<_>>();
+
+        offsets.sort_by(|a, b| a.total_cmp(b));
+        let datum_s = offsets.get(offsets.len() / 2).copied();
+
+        for (sv, clocks) in next.clocks {
+            let merged = self.clocks.entry(sv).or_default();
+            for (t, value) in clocks {
+                let t = t.to_time_scale(self.timescale);
+                let value = value - datum_s.unwrap_or_default();
+                match merged.binary_search_by(|(t_i, _)| t_i.cmp(&t)) {
+                    Ok(idx) => merged[idx].1 = (merged[idx].1 + value) / 2.0,
+                    Err(idx) => merged.insert(idx, (t, value)),
+                }
+            }
+        }
+        self.sampling = match (self.sampling, next.sampling) {
+            (Some(lhs), Some(rhs)) => Some(lhs.min(rhs)),
+            (lhs, rhs) => lhs.or(rhs),
+        };
+        datum_s
+    }
+    /// Clock offset [s] of this product at the boundary [Epoch]: interpolated when overlapping,
+    /// linearly extrapolated over (at most) two sampling periods otherwise.
+    fn boundary_offset(&self, sv: SV, t: Epoch) -> Option<f64> {
+        if let Some(offset) = self.clock_offset(sv, t) {
+            return Some(offset);
+        }
+        let clocks = self.clocks.get(&sv)?;
+        let [.., (t_prev, prev), (t_last, last)] = clocks.as_slice() else {
+            return None;
+        };
+        let sampling = self.sampling?;
+        if t < *t_last || t - *t_last > sampling * 2.0 {
+            return None;
+        }
+        let slope = (last - prev) / (*t_last - *t_prev).to_seconds();
+        Some(last + slope * (t - *t_last).to_seconds())
+    }
+    /// [TimeScale] of this product
+    pub fn timescale(&self) -> TimeScale {
+        self.timescale
+    }
+    /// Sampling period of this product
+    pub fn sampling(&self) -> Option<Duration> {
+        self.sampling
+    }
+    /// Returns list of [SV] described by this product
+    pub fn sv(&self) -> Vec<SV> {
+        self.clocks.keys().copied().collect()
+    }
+    /// Linearly interpolates [SV] clock offset [s] at [Epoch]
+    pub fn clock_offset(&self, sv: SV, t: Epoch) -> Option<f64> {
+        self.interpolate(sv, t, 1)
+    }
+    /// Interpolates [SV] clock offset [s] at [Epoch], with a Lagrange polynomial
+    /// of given order (at least linear), over the `order + 1` samples surrounding [Epoch].
+    /// The window is shifted at the edges of the product: we never extrapolate.
+    pub fn interpolate(&self, sv: SV, t: Epoch, order: usize) -> Option<f64> {
+        let clocks = self.clocks.get(&sv)?;
+        let idx = clocks.partition_point(|(t_i, _)| *t_i < t);
+        let (t_next, next) = *clocks.get(idx)?;
+        if t_next == t {
+            return Some(next);
+        }
+        if idx == 0 {
+            return None;
+        }
+
+        let size = order.max(1) + 1;
+        if clocks.len() < size {
+            return None;
+        }
+        let start = (idx - 1)
+            .saturating_sub((size - 2) / 2)
+            .min(clocks.len() - size);
+        let window = &clocks[start..start + size];
+
+        // tolerate a single missing (or screened) sample
+        if let Some(sampling) = self.sampling {
+            if window.windows(2).any(|w| w[1].0 - w[0].0 > sampling * 2.0) {
+                return None;
+            }
+        }
+
+        let t0 = window[0].0;
+        let x = (t - t0).to_seconds();
+        let nodes = window
+            .iter()
+            .map(|(t_i, value)| ((*t_i - t0).to_seconds(), *value))
+            .collect::<Vec<_>>();
+
+        let mut offset = 0.0;
+        for (i, (x_i, y_i)) in nodes.iter().enumerate() {
+            let mut li = 1.0;
+            for (j, (x_j, _)) in nodes.iter().enumerate() {
+                if i != j {
+                    li *= (x - x_j) / (x_i - x_j);
+                }
+            }
+            offset += li * y_i;
+        }
+        Some(offset)
+    }
+    /// Returns [SV] clock rate [s/s] at [Epoch], from the samples surrounding it
+    pub fn clock_rate(&self, sv: SV, t: Epoch) -> Option<f64> {
+        let clocks = self.clocks.get(&sv)?;
+        let idx = clocks.partition_point(|(t_i, _)| *t_i <= t).max(1);
+        let (t_next, next) = *clocks.get(idx)?;
+        let (t_prev, prev) = *clocks.get(idx - 1)?;
+        if let Some(sampling) = self.sampling {
+            if t_next - t_prev > sampling * 2.0 {
+                return None;
+            }
+        }
+        Some((next - prev) / (t_next - t_prev).to_seconds())
+    }
+}
+
+impl ClockSource for PreciseClocks {
+    fn next_clock_at(
+        &mut self,
+        t: Epoch,
+        sv: SV,
+        interp: Interpolation,
+    ) -> Option<ClockCorrection> {
+        // precise clocks do not include the periodic relativistic effect
+        let correction = ClockCorrection::without_relativistic_correction(Duration::from_seconds(
+            self.interpolate(sv, t, interp.clock_order)?,
+        ));
+        match self.clock_rate(sv, t) {
+            Some(rate) => Some(correction.with_drift(rate)),
+            None => Some(correction),
+        }
+    }
+    fn clock_validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        let clocks = self.clocks.get(&sv)?;
+        Some((clocks.first()?.0, clocks.last()?.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PreciseClocks;
+    use crate::{
+        clock::ClockSource,
+        prelude::{Constellation, Duration, Epoch, Interpolation, TimeScale, SV},
+    };
+    use std::str::FromStr;
+
+    const BIAS_S: f64 = 1.0E-4;
+    const DRIFT_S_S: f64 = 1.0E-10;
+
+    /// PRN 1, sampled every 30s. Sample `spike` is an outlier, sample `missing` is missing.
+    fn clk_content(spike: usize, missing: usize) -> String {
+        let mut content =
+            "     3.04           C                   G                   RINEX VERSION / TYPE\n\
+            GPS                                                         TIME SYSTEM ID\n\
+            \x20                                                           END OF HEADER\n"
+                .to_string();
+        for i in 0..20 {
+            if i == missing {
+                continue;
+            }
+            let mut bias = BIAS_S + DRIFT_S_S * 30.0 * i as f64;
+            if i == spike {
+                bias += 1.0E-7;
+            }
+            content.push_str(&format!(
+                "AS G01  2020 06 25 00 {:02} {:>9.6}  1   {:19.12E}\n",
+                (30 * i) / 60,
+                ((30 * i) % 60) as f64,
+                bias
+            ));
+        }
+        content
+    }
+
+    #[test]
+    fn clk_interpolation() {
+        let clk = PreciseClocks::from_reader(clk_content(100, 100).as_bytes()).unwrap();
+        let sv = SV::new(Constellation::GPS, 1);
+        assert_eq!(clk.timescale(), TimeScale::GPST);
+        assert_eq!(clk.sampling(), Some(Duration::from_seconds(30.0)));
+        assert_eq!(clk.sv(), vec![sv]);
+
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        for dt_s in [0.0, 15.0, 100.0, 570.0] {
+            let t = t0 + Duration::from_seconds(dt_s);
+            let offset = clk.clock_offset(sv, t).unwrap();
+            assert!((offset - (BIAS_S + DRIFT_S_S * dt_s)).abs() < 1.0E-14);
+        }
+        for dt_s in [0.0, 15.0, 100.0] {
+            let rate = clk
+                .clock_rate(sv, t0 + Duration::from_seconds(dt_s))
+                .unwrap();
+            assert!((rate - DRIFT_S_S).abs() < 1.0E-16);
+        }
+
+        // no extrapolation
+        assert!(clk
+            .clock_offset(sv, t0 - Duration::from_seconds(1.0))
+            .is_none());
+        assert!(clk
+            .clock_offset(sv, t0 + Duration::from_seconds(571.0))
+            .is_none());
+    }
+
+    #[test]
+    fn clk_interpolation_order() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let sv = SV::new(Constellation::GPS, 1);
+        let dt = Duration::from_seconds(30.0);
+        let aging_s_s2 = 1.0E-12;
+        let model = |dt_s: f64| BIAS_S + DRIFT_S_S * dt_s + aging_s_s2 * dt_s * dt_s;
+
+        let mut clk = PreciseClocks {
+            timescale: TimeScale::GPST,
+            sampling: Some(dt),
+            clocks: Default::default(),
+        };
+        clk.clocks.insert(
+            sv,
+            (0..20)
+                .map(|i| (t0 + i as f64 * dt, model(30.0 * i as f64)))
+                .collect(),
+        );
+
+        // window is shifted at both edges
+        for dt_s in [5.0, 100.0, 565.0] {
+            let t = t0 + Duration::from_seconds(dt_s);
+            let linear = clk.interpolate(sv, t, 1).unwrap();
+            let quadratic = clk.interpolate(sv, t, 2).unwrap();
+            assert!((linear - model(dt_s)).abs() > 1.0E-10);
+            assert!((quadratic - model(dt_s)).abs() < 1.0E-14);
+        }
+
+        // the [ClockSource] follows the configured order
+        let t = t0 + Duration::from_seconds(100.0);
+        let interp = Interpolation {
+            clock_order: 2,
+            ..Default::default()
+        };
+        assert_eq!(
+            clk.next_clock_at(t, sv, interp).unwrap().duration,
+            Duration::from_seconds(clk.interpolate(sv, t, 2).unwrap()),
+        );
+
+        // no extrapolation
+        assert!(clk
+            .interpolate(sv, t0 + Duration::from_seconds(571.0), 2)
+            .is_none());
+    }
+
+    #[test]
+    fn clk_screening() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let sv = SV::new(Constellation::GPS, 1);
+
+        let raw = PreciseClocks::from_reader_unscreened(clk_content(5, 100).as_bytes()).unwrap();
+        let t = t0 + Duration::from_seconds(150.0);
+        assert!(raw.clock_offset(sv, t).unwrap() - BIAS_S > 1.0E-8);
+
+        // spike is removed, single missing sample is bridged
+        let clk = PreciseClocks::from_reader(clk_content(5, 100).as_bytes()).unwrap();
+        let offset = clk.clock_offset(sv, t).unwrap();
+        assert!((offset - (BIAS_S + DRIFT_S_S * 150.0)).abs() < 1.0E-14);
+
+        // two consecutive missing samples: gap
+        let clk = PreciseClocks::from_reader(clk_content(5, 6).as_bytes()).unwrap();
+        assert!(clk.clock_offset(sv, t).is_none());
+    }
+
+    #[test]
+    fn clk_day_boundary() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let dt = Duration::from_seconds(30.0);
+        let (g01, g02) = (
+            SV::new(Constellation::GPS, 1),
+            SV::new(Constellation::GPS, 2),
+        );
+
+        // next product: 2 ns datum jump, starting one sample after the end of this one
+        let product = |start: usize, end: usize, datum_s: f64| {
+            let mut clk = PreciseClocks {
+                timescale: TimeScale::GPST,
+                sampling: Some(dt),
+                clocks: Default::default(),
+            };
+            for (sv, bias_s) in [(g01, BIAS_S), (g02, -BIAS_S)] {
+                let clocks = (start..end)
+                    .map(|i| {
+                        let value = bias_s + DRIFT_S_S * 30.0 * i as f64 + datum_s;
+                        (t0 + i as f64 * dt, value)
+                    })
+                    .collect();
+                clk.clocks.insert(sv, clocks);
+            }
+            clk
+        };
+
+        let mut clk = product(0, 20, 0.0);
+        let datum_s = clk.stitch(product(20, 40, 2.0E-9)).unwrap();
+        assert!((datum_s - 2.0E-9).abs() < 1.0E-15);
+
+        // continuous across the boundary
+        for i in [19.0, 19.5, 20.0, 20.5, 30.0] {
+            let offset = clk.clock_offset(g01, t0 + i * dt).unwrap();
+            assert!((offset - (BIAS_S + DRIFT_S_S * 30.0 * i)).abs() < 1.0E-15);
+        }
+
+        // overlapping products
+        let mut clk = product(0, 20, 0.0);
+        let datum_s = clk.stitch(product(19, 40, -1.0E-9)).unwrap();
+        assert!((datum_s + 1.0E-9).abs() < 1.0E-15);
+        assert_eq!(clk.clocks[&g02].len(), 40);
+        let offset = clk.clock_offset(g02, t0 + 25.0 * dt).unwrap();
+        assert!((offset - (-BIAS_S + DRIFT_S_S * 30.0 * 25.0)).abs() < 1.0E-15);
+
+        // products too far apart: datum cannot be estimated
+        let mut clk = product(0, 20, 0.0);
+        assert!(clk.stitch(product(30, 40, 1.0E-9)).is_none());
+    }
+}
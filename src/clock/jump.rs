@@ -0,0 +1,228 @@
+//! Receiver millisecond clock jumps
+use std::collections::BTreeMap;
+
+use crate::logging::info;
+
+use crate::{
+    constants::SPEED_OF_LIGHT_M_S,
+    prelude::{Candidate, Carrier, Epoch, SV},
+};
+
+/// Range equivalent to one millisecond [m]
+const MS_RANGE_M: f64 = SPEED_OF_LIGHT_M_S * 1.0E-3;
+
+/// Largest deviation from a whole number of milliseconds [m]:
+/// the receiver jumps by exact milliseconds, so anything else is a genuine discontinuity
+const JUMP_TOLERANCE_M: f64 = 1.0E3;
+
+/// Raw observations of one signal, at the previous [Epoch]
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    pseudo: Option<f64>,
+    phase: Option<f64>,
+    doppler: Option<f64>,
+}
+
+/// [ClockJumps] detects the 1 ms jumps of the receivers that steer their clock
+/// (by jumping their time tag rather than slewing it), and repairs the observations
+/// so they remain continuous: trackers and filters are not reset by the jumps.
+///
+/// A jump shows on all signals at once: either on the pseudo ranges only
+/// (phase ranges remain continuous) or on both the pseudo and phase ranges.
+/// Epoch to epoch range variations are compared to the variation predicted from the
+/// Doppler observations (or from the phase ranges, when Doppler is not observed),
+/// and a jump is declared when most signals agree on the same millisecond multiple.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ClockJumps {
+    /// Previous [Epoch]
+    t: Option<Epoch>,
+    /// Previous raw observations
+    samples: BTreeMap<(SV, Carrier), Sample>,
+    /// Accumulated pseudo range jumps [ms]
+    code_ms: i64,
+    /// Accumulated phase range jumps [ms]
+    phase_ms: i64,
+}
+
+impl ClockJumps {
+    /// Returns accumulated (pseudo range, phase range) jumps [ms]
+    #[cfg(test)]
+    pub fn accumulated_ms(&self) -> (i64, i64) {
+        (self.code_ms, self.phase_ms)
+    }
+    /// Returns the millisecond multiple `delta_m` amounts to, if it is one
+    fn whole_ms(delta_m: f64) -> Option<i64> {
+        let k = (delta_m / MS_RANGE_M).round();
+        if (delta_m - k * MS_RANGE_M).abs() < JUMP_TOLERANCE_M {
+            Some(k as i64)
+        } else {
+            None
+        }
+    }
+    /// Returns the jump most signals agree on, if any
+    fn consensus(jumps: &[i64], signals: usize) -> i64 {
+        let mut counts = BTreeMap::<i64, usize>::new();
+        for jump in jumps.iter() {
+            *counts.entry(*jump).or_default() += 1;
+        }
+        counts
+            .into_iter()
+            .filter(|(jump, count)| *jump != 0 && 2 * count > signals)
+            .map(|(jump, _)| jump)
+            .next()
+            .unwrap_or_default()
+    }
+    /// Detects the jumps between the previous and this [Epoch], then repairs this pool
+    pub fn repair(&mut self, t: Epoch, pool: &mut [Candidate]) {
+        let dt_s = self.t.map(|t_prev| (t - t_prev).to_seconds());
+
+        let (mut code, mut phase) = (Vec::new(), Vec::new());
+        let mut signals = 0;
+
+        if let Some(dt_s) = dt_s {
+            for cd in pool.iter() {
+                for obs in cd.observations.iter() {
+                    let Some(prev) = self.samples.get(&(cd.sv, obs.carrier)) else {
+                        continue;
+                    };
+                    let lambda = obs.carrier.wavelength();
+                    // predicted range variation (mean Doppler over the interval)
+                    let predicted_m = match (prev.doppler, obs.doppler) {
+                        (Some(d_prev), Some(d)) => Some(-(d_prev + d) / 2.0 * lambda * dt_s),
+                        _ => None,
+                    };
+                    let d_phase_m = match (prev.phase, obs.phase) {
+                        (Some(prev), Some(phase)) => Some(phase - prev),
+                        _ => None,
+                    };
+                    let d_code_m = match (prev.pseudo, obs.pseudo) {
+                        (Some(prev), Some(pseudo)) => Some(pseudo - prev),
+                        _ => None,
+                    };
+
+                    let reference_m = match (predicted_m, d_phase_m) {
+                        (Some(predicted_m), _) => predicted_m,
+                        // assumes continuous phase
+                        (None, Some(d_phase_m)) => d_phase_m,
+                        (None, None) => continue,
+                    };
+
+                    signals += 1;
+                    if let Some(jump) = d_code_m.and_then(|d| Self::whole_ms(d - reference_m)) {
+                        code.push(jump);
+                    }
+                    if predicted_m.is_some() {
+                        if let Some(jump) = d_phase_m.and_then(|d| Self::whole_ms(d - reference_m))
+                        {
+                            phase.push(jump);
+                        }
+                    }
+                }
+            }
+        }
+
+        let (code_jump, phase_jump) = (
+            Self::consensus(&code, signals),
+            Self::consensus(&phase, signals),
+        );
+        if code_jump != 0 || phase_jump != 0 {
+            info!(
+                "{} - receiver clock jump: {} ms (pseudo range), {} ms (phase range)",
+                t, code_jump, phase_jump
+            );
+        }
+        self.code_ms += code_jump;
+        self.phase_ms += phase_jump;
+
+        // store raw observations, then repair
+        self.t = Some(t);
+        self.samples.clear();
+        for cd in pool.iter_mut() {
+            for obs in cd.observations.iter_mut() {
+                self.samples.insert(
+                    (cd.sv, obs.carrier),
+                    Sample {
+                        pseudo: obs.pseudo,
+                        phase: obs.phase,
+                        doppler: obs.doppler,
+                    },
+                );
+                if let Some(pseudo) = obs.pseudo.as_mut() {
+                    *pseudo -= self.code_ms as f64 * MS_RANGE_M;
+                }
+                if let Some(phase) = obs.phase.as_mut() {
+                    *phase -= self.phase_ms as f64 * MS_RANGE_M;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ClockJumps, MS_RANGE_M};
+    use crate::prelude::{Candidate, Carrier, Duration, Epoch, Observation, SV};
+    use std::str::FromStr;
+
+    /// Builds a pool of 5 vehicles, moving at constant range rate
+    fn candidates(t: Epoch, k: usize, code_jump_m: f64, phase_jump_m: f64) -> Vec<Candidate> {
+        (1..=5)
+            .map(|prn| {
+                let doppler = 500.0 * prn as f64;
+                let range_rate_m_s = -doppler * Carrier::L1.wavelength();
+                let range_m = 20.0E6 + prn as f64 * 1.0E5 + range_rate_m_s * 30.0 * k as f64;
+                Candidate::new(
+                    SV::from_str(&format!("G{:02}", prn)).unwrap(),
+                    t,
+                    vec![
+                        Observation::pseudo_range(Carrier::L1, range_m + code_jump_m, None)
+                            .with_phase_range(range_m + phase_jump_m)
+                            .with_doppler(doppler),
+                    ],
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn clock_jump_repair() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let dt = Duration::from_seconds(30.0);
+        let mut jumps = ClockJumps::default();
+
+        let mut accumulated = Vec::new();
+        for k in 0..6 {
+            let t = t0 + k as f64 * dt;
+            // jump on the pseudo ranges only (k=2), then on both (k=4)
+            let code_jump_m = if k >= 4 {
+                2.0 * MS_RANGE_M
+            } else if k >= 2 {
+                MS_RANGE_M
+            } else {
+                0.0
+            };
+            let phase_jump_m = if k >= 4 { MS_RANGE_M } else { 0.0 };
+
+            let mut pool = candidates(t, k, code_jump_m, phase_jump_m);
+            jumps.repair(t, &mut pool);
+
+            let continuous = candidates(t, k, 0.0, 0.0);
+            for (cd, reference) in pool.iter().zip(continuous.iter()) {
+                let (obs, reference) = (&cd.observations[0], &reference.observations[0]);
+                assert!((obs.pseudo.unwrap() - reference.pseudo.unwrap()).abs() < 1.0E-6);
+                assert!((obs.phase.unwrap() - reference.phase.unwrap()).abs() < 1.0E-6);
+            }
+            accumulated.push(jumps.accumulated_ms());
+        }
+        assert_eq!(
+            accumulated,
+            vec![(0, 0), (0, 0), (1, 0), (1, 0), (2, 1), (2, 1)]
+        );
+
+        // genuine (non millisecond) discontinuity: not a clock jump
+        let t = t0 + 6.0 * dt;
+        let mut pool = candidates(t, 6, 2.0 * MS_RANGE_M + 50.0E3, MS_RANGE_M);
+        jumps.repair(t, &mut pool);
+        assert_eq!(jumps.accumulated_ms(), (2, 1));
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{
+    prelude::{ClockCorrection, Epoch, Interpolation, SV},
+    sync,
+};
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "clk")]
+mod clk;
+
+mod jump;
+pub(crate) use jump::ClockJumps;
+
+#[cfg(feature = "clk")]
+#[cfg_attr(docsrs, doc(cfg(feature = "clk")))]
+pub use clk::{ClkError, PreciseClocks};
+
+/// [ClockSource] may be attached to the [Solver] (see [Solver::set_clock_source])
+/// to provide SV clock corrections, for each [Candidate] that was not proposed
+/// with its own [ClockCorrection].
+///
+/// [ClockSource] is implemented for `Arc<Mutex<C>>`, so a single provider
+/// may be shared between several [Solver]s.
+pub trait ClockSource {
+    /// Provide [SV] [ClockCorrection] (offset to its constellation [TimeScale])
+    /// at requested [Epoch], which is the approximate transmission time.
+    /// Sampled products should interpolate with the proposed [Interpolation::clock_order].
+    /// Returns None if the correction cannot be determined: the [Candidate]
+    /// is then processed without correction.
+    fn next_clock_at(&mut self, t: Epoch, sv: SV, interp: Interpolation)
+        -> Option<ClockCorrection>;
+    /// Returns the validity interval (first, last [Epoch]) of the corrections of this [SV],
+    /// when it is bounded. The [Solver] refuses to use corrections
+    /// outside of it, rather than extrapolating. Unbounded by default.
+    fn clock_validity(&self, _sv: SV) -> Option<(Epoch, Epoch)> {
+        None
+    }
+}
+
+impl<C: ClockSource> ClockSource for Arc<Mutex<C>> {
+    fn next_clock_at(
+        &mut self,
+        t: Epoch,
+        sv: SV,
+        interp: Interpolation,
+    ) -> Option<ClockCorrection> {
+        let mut source = sync::lock(self);
+        source.next_clock_at(t, sv, interp)
+    }
+    fn clock_validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        let source = sync::lock(self);
+        source.clock_validity(sv)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ClockSource;
+    use crate::{
+        prelude::{Duration, Epoch, Interpolation, SV},
+        tests::Counter,
+    };
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn shared_clock_source() {
+        let shared = Arc::new(Mutex::new(Counter::default()));
+        let (mut rover_a, mut rover_b) = (shared.clone(), shared.clone());
+
+        let t = Epoch::default();
+
+        assert!(rover_a
+            .next_clock_at(t, SV::default(), Interpolation::default())
+            .is_some());
+        assert!(rover_b
+            .next_clock_at(t, SV::default(), Interpolation::default())
+            .is_some());
+        assert_eq!(
+            rover_b.clock_validity(SV::default()),
+            Some((t, t + Duration::from_days(1.0)))
+        );
+
+        assert_eq!(shared.lock().unwrap().requests, 2);
+    }
+}
@@ -0,0 +1,178 @@
+//! Gauss-Newton cold start
+use crate::logging::{debug, error};
+use crate::{prelude::Candidate, solver::ErrorKind};
+
+use crate::constants::SPEED_OF_LIGHT_M_S;
+use nalgebra::{Matrix4, Vector3, Vector4};
+
+/// [GaussNewton] iterative solver, used to initialize the [Solver]
+/// when no apriori position is known. Iterations start from Earth's center
+/// (classical bootstrap) and unlike [Bancroft], any number (>= 4) of candidates
+/// contribute to the initial guess.
+pub struct GaussNewton {
+    /// SV positions in ECEF [m] and corrected pseudo range [m]
+    inner: Vec<(Vector3<f64>, f64)>,
+}
+
+impl GaussNewton {
+    /// Maximal number of iterations
+    const MAX_ITER: usize = 20;
+    /// Convergence criteria [m]
+    const CONVERGENCE_M: f64 = 1.0E-3;
+    /// Converged positions must lie within this range of Earth's center [m]:
+    /// iterations may settle on the mirror solution, far from Earth.
+    const MIN_RADIUS_M: f64 = 6.0E6;
+    const MAX_RADIUS_M: f64 = 1.0E7;
+    /// Builds new [GaussNewton] solver
+    pub fn new(cd: &[Candidate]) -> Result<Self, ErrorKind> {
+        let mut inner = Vec::with_capacity(cd.len());
+        for cd in cd.iter() {
+            let orbit = match cd.orbit {
+                Some(orbit) => orbit,
+                None => {
+                    error!("{}({}) cold start: unresolved orbital state", cd.t, cd.sv);
+                    continue;
+                },
+            };
+            let pr = match cd.prefered_pseudorange() {
+                Some(pr) => pr.pseudo.unwrap(),
+                None => {
+                    error!("{}({}) cold start needs 1 pseudo range", cd.t, cd.sv);
+                    continue;
+                },
+            };
+            let clock_corr = match cd.clock_corr {
+                Some(clock_corr) => clock_corr,
+                None => {
+                    error!(
+                        "{}({}) cold start needs onboard clock correction",
+                        cd.t, cd.sv
+                    );
+                    continue;
+                },
+            };
+            let state = orbit.to_cartesian_pos_vel() * 1.0E3;
+            let dt_s = clock_corr.duration.to_seconds();
+            let tgd_s = cd.tgd.unwrap_or_default().to_seconds();
+            let pr = pr + (dt_s - tgd_s) * SPEED_OF_LIGHT_M_S;
+            inner.push((Vector3::new(state[0], state[1], state[2]), pr));
+        }
+        if inner.len() < 4 {
+            Err(ErrorKind::NotEnoughCandidatesColdStart)
+        } else {
+            Ok(Self { inner })
+        }
+    }
+    /// Iterates from Earth's center until convergence.
+    /// Returns (x, y, z, c.dt) in [m]
+    pub fn resolve(&self) -> Result<Vector4<f64>, ErrorKind> {
+        let mut x = Vector4::<f64>::zeros();
+        for iter in 0..Self::MAX_ITER {
+            let mut hth = Matrix4::<f64>::zeros();
+            let mut hty = Vector4::<f64>::zeros();
+            let rx = Vector3::new(x[0], x[1], x[2]);
+
+            for (sv, pr) in self.inner.iter() {
+                let los = rx - sv;
+                let rho = los.norm();
+                let h = Vector4::new(los[0] / rho, los[1] / rho, los[2] / rho, 1.0);
+                let dy = pr - rho - x[3];
+                hth += h * h.transpose();
+                hty += h * dy;
+            }
+
+            let dx = hth.try_inverse().ok_or(ErrorKind::MatrixInversionError)? * hty;
+            x += dx;
+
+            let norm = Vector3::new(dx[0], dx[1], dx[2]).norm();
+            debug!("cold start iteration #{}: |dx|={:.3E}m", iter, norm);
+
+            if !x.iter().all(|x_i| x_i.is_finite()) {
+                return Err(ErrorKind::ColdStartDivergence);
+            }
+            if norm < Self::CONVERGENCE_M {
+                let radius_m = Vector3::new(x[0], x[1], x[2]).norm();
+                if !(Self::MIN_RADIUS_M..=Self::MAX_RADIUS_M).contains(&radius_m) {
+                    error!(
+                        "cold start converged to implausible position (|r|={:.3E}m)",
+                        radius_m
+                    );
+                    return Err(ErrorKind::ColdStartDivergence);
+                }
+                return Ok(x);
+            }
+        }
+        Err(ErrorKind::ColdStartDivergence)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GaussNewton;
+    use crate::prelude::{
+        Candidate, Carrier, ClockCorrection, Constellation, Duration, Epoch, ErrorKind,
+        Observation, Orbit, EARTH_J2000, SV,
+    };
+    use nalgebra::Vector3;
+
+    /// Candidates consistent with receiver position `rx` [m] and clock offset `cdt` [m]
+    fn pool(rx: Vector3<f64>, cdt: f64, t: Epoch) -> Vec<Candidate> {
+        [
+            (4577.077035843635, -22996.125649966143, 18062.46236437641),
+            (16576.946499220812, -4619.715035111092, 24092.50915107983),
+            (18846.557032585508, 16144.709835080192, 16160.045068828074),
+            (-15921.905530334785, -5399.928036329342, 24360.75165958442),
+            (10996.104343, -19841.200560, -13758.983598),
+        ]
+        .iter()
+        .enumerate()
+        .map(|(prn, (x_km, y_km, z_km))| {
+            let sv_pos = Vector3::new(*x_km, *y_km, *z_km) * 1.0E3;
+            let pr = (sv_pos - rx).norm() + cdt;
+            let sv = SV::new(Constellation::GPS, prn as u8 + 1);
+            let mut cd = Candidate::new(
+                sv,
+                t,
+                vec![Observation::pseudo_range(Carrier::L1, pr, None)],
+            );
+            cd.set_orbit(Orbit::from_position(*x_km, *y_km, *z_km, t, EARTH_J2000));
+            cd.set_clock_correction(ClockCorrection::without_relativistic_correction(
+                Duration::ZERO,
+            ));
+            cd
+        })
+        .collect()
+    }
+
+    #[test]
+    fn bootstrap_from_earth_center() {
+        let (x0, y0, z0) = (3628427.9118, 562059.0936, 5197872.215);
+        let cdt = 1.0E3;
+        let t = Epoch::default();
+        let pool = pool(Vector3::new(x0, y0, z0), cdt, t);
+
+        let solver = GaussNewton::new(&pool).unwrap();
+        assert_eq!(solver.inner.len(), 5);
+
+        let x = solver.resolve().unwrap();
+        assert!((x[0] - x0).abs() < 1.0E-2);
+        assert!((x[1] - y0).abs() < 1.0E-2);
+        assert!((x[2] - z0).abs() < 1.0E-2);
+        assert!((x[3] - cdt).abs() < 1.0E-2);
+
+        // candidates without clock correction do not contribute
+        let mut pool = pool[..4].to_vec();
+        pool[0] = Candidate::new(pool[0].sv, t, pool[0].observations.clone());
+        assert_eq!(
+            GaussNewton::new(&pool).err(),
+            Some(ErrorKind::NotEnoughCandidatesColdStart)
+        );
+    }
+
+    #[test]
+    fn implausible_position() {
+        let pool = pool(Vector3::zeros(), 0.0, Epoch::default());
+        let solver = GaussNewton::new(&pool).unwrap();
+        assert_eq!(solver.resolve(), Err(ErrorKind::ColdStartDivergence));
+    }
+}
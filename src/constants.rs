@@ -1,8 +1,14 @@
+/// Speed of light in vacuum [m/s]
+pub const SPEED_OF_LIGHT_M_S: f64 = 299_792_458.0;
+
+#[cfg(feature = "std")]
 pub struct Url;
 pub struct Constants;
 
+#[cfg(feature = "std")]
 use anise::almanac::metaload::MetaFile;
 
+#[cfg(feature = "std")]
 impl Url {
     pub fn nyx_anise_de440s_bsp() -> MetaFile {
         MetaFile {
@@ -37,8 +43,8 @@ impl Constants {
     pub const MOON_GRAVITATION: f64 = 4.9028695 * 10.0E6;
     /// Earth equatorial radis [km]
     pub const EARTH_EQUATORIAL_RADIUS_KM: f64 = 6378.1366;
-    /// WGS84 Earth Frame Ellipsoid semi-major axis
-    pub const EARTH_SEMI_MAJOR_AXIS_WGS84: f64 = 6378137.0_f64;
+    /// Sun (photosphere) radius [km]
+    pub const SUN_RADIUS_KM: f64 = 696_000.0;
     /// Love degree^2 term
     pub const LOVE_DEGREE2: f64 = 0.6078;
     /// Shida degree^2 term
@@ -0,0 +1,159 @@
+//! Runtime constellation definitions and health monitoring
+use std::{
+    collections::{BTreeMap, HashMap},
+    sync::RwLock,
+};
+
+use crate::logging::warn;
+
+use crate::{
+    prelude::{Constellation, ConstellationMonitor, TimeScale},
+    sync,
+};
+
+/// User defined [Constellation] time scales
+static TIMESCALES: RwLock<Option<HashMap<Constellation, TimeScale>>> = RwLock::new(None);
+
+/// [ConstellationTable] declares, at runtime, how to process a [Constellation]
+/// this crate does not natively support. Emerging systems (LEO-PNT, experimental..)
+/// are mapped onto a [Constellation] not otherwise in use, their signals
+/// are declared with [Carrier::register].
+pub struct ConstellationTable;
+
+impl ConstellationTable {
+    /// Declares the [TimeScale] this [Constellation] is expressed in,
+    /// replacing its native [TimeScale] if any.
+    pub fn register(constellation: Constellation, timescale: TimeScale) {
+        let mut table = sync::write(&TIMESCALES);
+        table
+            .get_or_insert_with(HashMap::new)
+            .insert(constellation, timescale);
+    }
+    /// Returns [TimeScale] of this [Constellation]: the declared one,
+    /// otherwise its native one.
+    pub fn timescale(constellation: Constellation) -> Option<TimeScale> {
+        let table = sync::read(&TIMESCALES);
+        table
+            .as_ref()
+            .and_then(|table| table.get(&constellation).copied())
+            .or_else(|| constellation.timescale())
+    }
+}
+
+/// Health status of a single [Constellation]
+#[derive(Debug, Clone, Copy, Default)]
+struct HealthStatus {
+    /// Currently excluded
+    faulty: bool,
+    /// Consecutive epochs contradicting the current status
+    count: usize,
+}
+
+/// [ConstellationHealth] tracks the status of each [Constellation],
+/// from their systematic biases (see [ConstellationMonitor]).
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ConstellationHealth {
+    status: BTreeMap<Constellation, HealthStatus>,
+}
+
+impl ConstellationHealth {
+    /// Updates the status with the systematic bias [m] of each [Constellation] in sight
+    pub fn update(&mut self, monitor: &ConstellationMonitor, biases: &[(Constellation, f64)]) {
+        for (constellation, bias_m) in biases.iter() {
+            let status = self.status.entry(*constellation).or_default();
+            let faulty = bias_m.abs() > monitor.max_bias_m;
+            if faulty == status.faulty {
+                status.count = 0;
+                continue;
+            }
+            status.count += 1;
+            if status.count >= monitor.persistence {
+                status.faulty = faulty;
+                status.count = 0;
+                if faulty {
+                    warn!("{} excluded: systematic bias {:.3}m", constellation, bias_m);
+                } else {
+                    warn!("{} restored", constellation);
+                }
+            }
+        }
+    }
+    /// Returns true if this [Constellation] is currently excluded
+    pub fn is_faulty(&self, constellation: Constellation) -> bool {
+        self.status
+            .get(&constellation)
+            .is_some_and(|status| status.faulty)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ConstellationHealth, ConstellationTable};
+    use crate::prelude::{
+        Carrier, CarrierDefinition, Constellation, ConstellationMonitor, TimeScale,
+    };
+
+    #[test]
+    fn custom_tables() {
+        assert_eq!(
+            ConstellationTable::timescale(Constellation::GPS),
+            Some(TimeScale::GPST)
+        );
+        ConstellationTable::register(Constellation::IRNSS, TimeScale::GPST);
+        assert_eq!(
+            ConstellationTable::timescale(Constellation::IRNSS),
+            Some(TimeScale::GPST)
+        );
+
+        let carrier = Carrier::register(CarrierDefinition {
+            name: "LEO-L1".to_string(),
+            frequency_hz: 1616.0E6,
+            constellation: Constellation::IRNSS,
+            rinex_band: Some('9'),
+        });
+        assert_eq!(carrier.frequency(), 1616.0E6);
+        assert_eq!(carrier.to_string(), "LEO-L1");
+        assert_eq!(
+            Carrier::from_rinex_code(Constellation::IRNSS, "C9X"),
+            Some(carrier)
+        );
+        assert_eq!(Carrier::from_rinex_code(Constellation::GPS, "C9X"), None);
+
+        // redefinition
+        let redefined = Carrier::register(CarrierDefinition {
+            name: "LEO-L1".to_string(),
+            frequency_hz: 1617.0E6,
+            constellation: Constellation::IRNSS,
+            rinex_band: Some('9'),
+        });
+        assert_eq!(redefined, carrier);
+        assert_eq!(carrier.frequency(), 1617.0E6);
+    }
+
+    #[test]
+    fn constellation_health() {
+        let monitor = ConstellationMonitor {
+            max_bias_m: 30.0,
+            persistence: 2,
+        };
+        let mut health = ConstellationHealth::default();
+
+        let faulty = [(Constellation::GPS, 1.0), (Constellation::Galileo, -50.0)];
+        let healthy = [(Constellation::GPS, 1.0), (Constellation::Galileo, 2.0)];
+
+        health.update(&monitor, &faulty);
+        assert!(!health.is_faulty(Constellation::Galileo));
+        health.update(&monitor, &faulty);
+        assert!(health.is_faulty(Constellation::Galileo));
+        assert!(!health.is_faulty(Constellation::GPS));
+
+        // a single healthy epoch is not enough
+        health.update(&monitor, &healthy);
+        health.update(&monitor, &faulty);
+        health.update(&monitor, &healthy);
+        assert!(health.is_faulty(Constellation::Galileo));
+        health.update(&monitor, &healthy);
+        assert!(!health.is_faulty(Constellation::Galileo));
+        assert!(!health.is_faulty(Constellation::BeiDou));
+    }
+}
@@ -0,0 +1,224 @@
+//! Coordinates conversions: ECEF to geodetic coordinates (and back),
+//! and rotations between the ECEF and local (ENU, NED) frames.
+use nalgebra::{Matrix3, Vector3};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Reference [Ellipsoid]
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Ellipsoid {
+    /// Semi major axis [m]
+    pub semi_major_axis_m: f64,
+    /// Flattening
+    pub flattening: f64,
+}
+
+impl Default for Ellipsoid {
+    fn default() -> Self {
+        Self::WGS84
+    }
+}
+
+impl Ellipsoid {
+    /// WGS84 [Ellipsoid]
+    pub const WGS84: Self = Self {
+        semi_major_axis_m: 6_378_137.0,
+        flattening: 1.0 / 298.257_223_563,
+    };
+    /// GRS80 [Ellipsoid] (ITRF, ETRF and most national datums)
+    pub const GRS80: Self = Self {
+        semi_major_axis_m: 6_378_137.0,
+        flattening: 1.0 / 298.257_222_101,
+    };
+    /// Semi minor axis [m]
+    pub fn semi_minor_axis_m(&self) -> f64 {
+        self.semi_major_axis_m * (1.0 - self.flattening)
+    }
+    /// First eccentricity squared
+    pub fn eccentricity_squared(&self) -> f64 {
+        self.flattening * (2.0 - self.flattening)
+    }
+    /// Prime vertical radius of curvature [m], at this latitude [rad]
+    fn prime_vertical_radius_m(&self, lat_rad: f64) -> f64 {
+        self.semi_major_axis_m / (1.0 - self.eccentricity_squared() * lat_rad.sin().powi(2)).sqrt()
+    }
+    /// Converts geodetic coordinates (latitude [rad], longitude [rad], altitude above ellipsoid [m])
+    /// to ECEF coordinates [m]
+    pub fn geodetic_to_ecef(&self, lat_rad: f64, lon_rad: f64, alt_m: f64) -> (f64, f64, f64) {
+        let e2 = self.eccentricity_squared();
+        let n = self.prime_vertical_radius_m(lat_rad);
+        let (s_lat, c_lat) = lat_rad.sin_cos();
+        let (s_lon, c_lon) = lon_rad.sin_cos();
+        (
+            (n + alt_m) * c_lat * c_lon,
+            (n + alt_m) * c_lat * s_lon,
+            (n * (1.0 - e2) + alt_m) * s_lat,
+        )
+    }
+    /// Converts ECEF coordinates [m] to geodetic coordinates
+    /// (latitude [rad], longitude [rad], altitude above ellipsoid [m]),
+    /// using the exact closed form solution of Vermeille (2004).
+    /// Valid anywhere but in the vicinity of the Earth center.
+    pub fn ecef_to_geodetic(&self, ecef_m: (f64, f64, f64)) -> (f64, f64, f64) {
+        let (x, y, z) = ecef_m;
+        let a2 = self.semi_major_axis_m.powi(2);
+        let e2 = self.eccentricity_squared();
+        let e4 = e2 * e2;
+        let rho = x.hypot(y);
+
+        let p = rho.powi(2) / a2;
+        let q = (1.0 - e2) / a2 * z.powi(2);
+        let r = (p + q - e4) / 6.0;
+        let s = e4 * p * q / (4.0 * r.powi(3));
+        let t = (1.0 + s + (s * (2.0 + s)).sqrt()).cbrt();
+        let u = r * (1.0 + t + 1.0 / t);
+        let v = (u.powi(2) + e4 * q).sqrt();
+        let w = e2 * (u + v - q) / (2.0 * v);
+        let k = (u + v + w.powi(2)).sqrt() - w;
+        let d = k * rho / (k + e2);
+        let d_z = d.hypot(z);
+
+        let lat_rad = 2.0 * z.atan2(d + d_z);
+        let alt_m = (k + e2 - 1.0) / k * d_z;
+        (lat_rad, y.atan2(x), alt_m)
+    }
+    /// Converts ECEF coordinates [m] to geodetic coordinates
+    /// (latitude [rad], longitude [rad], altitude above ellipsoid [m]),
+    /// iterating Bowring's (1976) formula on the parametric latitude.
+    pub fn ecef_to_geodetic_bowring(&self, ecef_m: (f64, f64, f64)) -> (f64, f64, f64) {
+        let (x, y, z) = ecef_m;
+        let (a, b) = (self.semi_major_axis_m, self.semi_minor_axis_m());
+        let e2 = self.eccentricity_squared();
+        let ep2 = e2 / (1.0 - e2);
+        let rho = x.hypot(y);
+
+        let mut beta = z.atan2((1.0 - self.flattening) * rho);
+        let mut lat_rad = beta;
+        // converges to sub millimeter in a couple of iterations, on Earth
+        for _ in 0..3 {
+            let (s_beta, c_beta) = beta.sin_cos();
+            lat_rad = (z + ep2 * b * s_beta.powi(3)).atan2(rho - e2 * a * c_beta.powi(3));
+            beta = ((1.0 - self.flattening) * lat_rad.sin()).atan2(lat_rad.cos());
+        }
+
+        let n = self.prime_vertical_radius_m(lat_rad);
+        let (s_lat, c_lat) = lat_rad.sin_cos();
+        let alt_m = rho * c_lat + (z + e2 * n * s_lat) * s_lat - n;
+        (lat_rad, y.atan2(x), alt_m)
+    }
+}
+
+/// Returns the ECEF to local ENU rotation matrix, at (latitude, longitude) in radians.
+/// Its rows are the East, North and Up unit vectors expressed in ECEF.
+pub fn ecef_to_enu_matrix(lat_rad: f64, lon_rad: f64) -> Matrix3<f64> {
+    let (s_lat, c_lat) = lat_rad.sin_cos();
+    let (s_lon, c_lon) = lon_rad.sin_cos();
+    Matrix3::new(
+        -s_lon,
+        c_lon,
+        0.0,
+        -s_lat * c_lon,
+        -s_lat * s_lon,
+        c_lat,
+        c_lat * c_lon,
+        c_lat * s_lon,
+        s_lat,
+    )
+}
+
+/// Rotates ECEF vector into local ENU frame, at (latitude, longitude) in radians
+pub fn ecef_to_enu(ecef: Vector3<f64>, lat_rad: f64, lon_rad: f64) -> Vector3<f64> {
+    ecef_to_enu_matrix(lat_rad, lon_rad) * ecef
+}
+
+/// Rotates local ENU vector into ECEF frame, at (latitude, longitude) in radians
+pub fn enu_to_ecef(enu: Vector3<f64>, lat_rad: f64, lon_rad: f64) -> Vector3<f64> {
+    ecef_to_enu_matrix(lat_rad, lon_rad).transpose() * enu
+}
+
+/// Converts local ENU vector to local NED vector
+pub fn enu_to_ned(enu: Vector3<f64>) -> Vector3<f64> {
+    Vector3::new(enu[1], enu[0], -enu[2])
+}
+
+/// Converts local NED vector to local ENU vector
+pub fn ned_to_enu(ned: Vector3<f64>) -> Vector3<f64> {
+    Vector3::new(ned[1], ned[0], -ned[2])
+}
+
+/// Rotates ECEF vector into local NED frame, at (latitude, longitude) in radians
+pub fn ecef_to_ned(ecef: Vector3<f64>, lat_rad: f64, lon_rad: f64) -> Vector3<f64> {
+    enu_to_ned(ecef_to_enu(ecef, lat_rad, lon_rad))
+}
+
+/// Rotates local NED vector into ECEF frame, at (latitude, longitude) in radians
+pub fn ned_to_ecef(ned: Vector3<f64>, lat_rad: f64, lon_rad: f64) -> Vector3<f64> {
+    enu_to_ecef(ned_to_enu(ned), lat_rad, lon_rad)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ecef_to_enu, ecef_to_ned, enu_to_ecef, ned_to_ecef, Ellipsoid};
+    use nalgebra::Vector3;
+
+    #[test]
+    fn geodetic_conversions() {
+        for (lat_ddeg, lon_ddeg, alt_m) in [
+            (48.85, 2.29, 100.0),
+            (-33.8, 151.2, -50.0),
+            (89.9, 10.0, 1000.0),
+            (-89.99, 0.0, 0.0),
+            (0.0, -70.0, 20_000.0E3),
+            (60.0, 179.9, 0.0),
+        ] {
+            let (lat_rad, lon_rad) = (f64::to_radians(lat_ddeg), f64::to_radians(lon_ddeg));
+            let ecef_m = Ellipsoid::WGS84.geodetic_to_ecef(lat_rad, lon_rad, alt_m);
+
+            // cross check
+            let (x, y, z) =
+                map_3d::geodetic2ecef(lat_rad, lon_rad, alt_m, map_3d::Ellipsoid::WGS84);
+            assert!((ecef_m.0 - x).abs() < 1.0E-6);
+            assert!((ecef_m.1 - y).abs() < 1.0E-6);
+            assert!((ecef_m.2 - z).abs() < 1.0E-6);
+
+            for (lat, lon, alt) in [
+                Ellipsoid::WGS84.ecef_to_geodetic(ecef_m),
+                Ellipsoid::WGS84.ecef_to_geodetic_bowring(ecef_m),
+            ] {
+                assert!((lat - lat_rad).abs() < 1.0E-11, "{} {}", lat_ddeg, lon_ddeg);
+                assert!((lon - lon_rad).abs() < 1.0E-11, "{} {}", lat_ddeg, lon_ddeg);
+                assert!((alt - alt_m).abs() < 1.0E-6, "{} {}", lat_ddeg, lon_ddeg);
+            }
+        }
+
+        // GRS80 and WGS84 only differ by 0.1 mm
+        let ecef_m = Ellipsoid::GRS80.geodetic_to_ecef(0.8, 0.1, 0.0);
+        let (_, _, alt_m) = Ellipsoid::WGS84.ecef_to_geodetic(ecef_m);
+        assert!(alt_m.abs() < 1.0E-3);
+    }
+
+    #[test]
+    fn local_rotations() {
+        // (0°, 0°): up is +X, east is +Y, north is +Z
+        let up = enu_to_ecef(Vector3::new(0.0, 0.0, 1.0), 0.0, 0.0);
+        assert!((up - Vector3::new(1.0, 0.0, 0.0)).norm() < 1.0E-9);
+        let east = enu_to_ecef(Vector3::new(1.0, 0.0, 0.0), 0.0, 0.0);
+        assert!((east - Vector3::new(0.0, 1.0, 0.0)).norm() < 1.0E-9);
+        let north = enu_to_ecef(Vector3::new(0.0, 1.0, 0.0), 0.0, 0.0);
+        assert!((north - Vector3::new(0.0, 0.0, 1.0)).norm() < 1.0E-9);
+
+        let down = ned_to_ecef(Vector3::new(0.0, 0.0, 1.0), 0.0, 0.0);
+        assert!((down - Vector3::new(-1.0, 0.0, 0.0)).norm() < 1.0E-9);
+
+        // round trips
+        let (lat_rad, lon_rad) = (0.8, -1.2);
+        let v = Vector3::new(1.0, -2.0, 3.0);
+        let enu = ecef_to_enu(v, lat_rad, lon_rad);
+        assert!((enu_to_ecef(enu, lat_rad, lon_rad) - v).norm() < 1.0E-9);
+        let ned = ecef_to_ned(v, lat_rad, lon_rad);
+        assert!((ned - Vector3::new(enu[1], enu[0], -enu[2])).norm() < 1.0E-9);
+        assert!((ned_to_ecef(ned, lat_rad, lon_rad) - v).norm() < 1.0E-9);
+    }
+}
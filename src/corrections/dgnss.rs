@@ -0,0 +1,177 @@
+//! Differential GNSS (RTCM SC-104 style) pseudo range corrections
+use crate::{
+    candidate::Candidate,
+    corrections::{Correction, CorrectionLoss, CorrectionManager, CorrectionStatus},
+    prelude::{Carrier, Duration, Epoch, SV},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Default maximal age of DGNSS corrections
+const DEFAULT_MAX_AGE_S: f64 = 60.0;
+
+/// [DgnssCorrection] to the pseudo range of one signal, broadcast by a reference station
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DgnssCorrection {
+    /// Reference [Epoch]
+    pub t: Epoch,
+    /// Issue of Data of the broadcast ephemeris this correction applies to
+    pub iode: u16,
+    /// Corrected [Carrier] signal
+    pub carrier: Carrier,
+    /// Pseudo range correction [m], to be added to the observation
+    pub prc_m: f64,
+    /// Range rate correction [m/s]
+    pub rrc_m_s: f64,
+}
+
+impl DgnssCorrection {
+    /// Returns pseudo range correction [m] at [Epoch]
+    pub fn correction_m(&self, t: Epoch) -> f64 {
+        self.prc_m + self.rrc_m_s * (t - self.t).to_seconds()
+    }
+}
+
+impl Correction for DgnssCorrection {
+    fn epoch(&self) -> Epoch {
+        self.t
+    }
+    fn iod(&self) -> u16 {
+        self.iode
+    }
+}
+
+/// [DgnssCorrections] stores the latest [DgnssCorrection] of each vehicle
+/// and applies them to [Candidate]s. Stale corrections are aged out
+/// (see [Self::with_max_age]) and the vehicles fall back to broadcast-only.
+#[derive(Debug, Clone)]
+pub struct DgnssCorrections {
+    manager: CorrectionManager<DgnssCorrection>,
+}
+
+impl Default for DgnssCorrections {
+    fn default() -> Self {
+        Self {
+            manager: CorrectionManager::new("dgnss", Duration::from_seconds(DEFAULT_MAX_AGE_S)),
+        }
+    }
+}
+
+impl DgnssCorrections {
+    /// Builds new empty [DgnssCorrections]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Copies and returns [DgnssCorrections] with maximal age of the corrections
+    pub fn with_max_age(&self, max_age: Duration) -> Self {
+        let mut s = self.clone();
+        s.manager.set_max_age(max_age);
+        s
+    }
+    /// Stores new [DgnssCorrection] for this [SV], replacing the previous one
+    pub fn insert(&mut self, sv: SV, correction: DgnssCorrection) {
+        self.manager.insert(sv, correction);
+    }
+    /// Ages out corrections that may no longer be used after [Epoch]
+    pub fn discard_outdated(&mut self, t: Epoch) {
+        self.manager.discard_outdated(t);
+    }
+    /// Returns [DgnssCorrection] for [SV], if it may be used at [Epoch]
+    /// along the ephemeris of this Issue of Data
+    pub fn correction(&self, sv: SV, t: Epoch, iode: u16) -> Option<&DgnssCorrection> {
+        self.manager.get_iod(sv, t, iode).ok()
+    }
+    /// Applies the [DgnssCorrection] to this [Candidate]'s pseudo range, when it matches
+    /// the Issue of Data of the ephemeris in use. Returns the resulting [CorrectionStatus].
+    pub fn apply(&mut self, candidate: &mut Candidate, iode: u16) -> CorrectionStatus {
+        let (sv, t) = (candidate.sv, candidate.t);
+        let status = match self.manager.get_iod(sv, t, iode) {
+            Ok(correction) => {
+                let correction_m = correction.correction_m(t);
+                let mut applied = false;
+                for obs in candidate.observations.iter_mut() {
+                    if obs.carrier == correction.carrier {
+                        if let Some(pseudo) = obs.pseudo.as_mut() {
+                            *pseudo += correction_m;
+                            applied = true;
+                        }
+                    }
+                }
+                if applied {
+                    CorrectionStatus::Corrected
+                } else {
+                    CorrectionStatus::BroadcastOnly(CorrectionLoss::Missing)
+                }
+            },
+            Err(loss) => CorrectionStatus::BroadcastOnly(loss),
+        };
+        self.manager.report(t, sv, status);
+        status
+    }
+    /// Returns latest [CorrectionStatus] of [SV]
+    pub fn status(&self, sv: SV) -> Option<CorrectionStatus> {
+        self.manager.status(sv)
+    }
+    /// Returns vehicles that fell back to broadcast-only, as of latest [Self::apply]
+    pub fn broadcast_only(&self) -> Vec<SV> {
+        self.manager.broadcast_only()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DgnssCorrection, DgnssCorrections};
+    use crate::{
+        corrections::{CorrectionLoss, CorrectionStatus},
+        prelude::{Candidate, Carrier, Constellation, Duration, Epoch, Observation, SV},
+    };
+    use std::str::FromStr;
+
+    #[test]
+    fn dgnss_corrections() {
+        let sv = SV::new(Constellation::GPS, 1);
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let t = t0 + Duration::from_seconds(10.0);
+
+        let mut dgnss = DgnssCorrections::new();
+        dgnss.insert(
+            sv,
+            DgnssCorrection {
+                t: t0,
+                iode: 42,
+                carrier: Carrier::L1,
+                prc_m: 2.0,
+                rrc_m_s: 0.1,
+            },
+        );
+
+        let mut candidate = Candidate::new(
+            sv,
+            t,
+            vec![Observation::pseudo_range(Carrier::L1, 20.0E6, None)],
+        );
+
+        assert_eq!(
+            dgnss.apply(&mut candidate.clone(), 41),
+            CorrectionStatus::BroadcastOnly(CorrectionLoss::IodMismatch)
+        );
+        assert_eq!(dgnss.broadcast_only(), vec![sv]);
+
+        assert_eq!(dgnss.apply(&mut candidate, 42), CorrectionStatus::Corrected);
+        assert_eq!(candidate.observations[0].pseudo, Some(20.0E6 + 3.0));
+        assert!(dgnss.broadcast_only().is_empty());
+
+        let mut candidate = Candidate::new(
+            sv,
+            t0 + Duration::from_seconds(61.0),
+            vec![Observation::pseudo_range(Carrier::L1, 20.0E6, None)],
+        );
+        assert_eq!(
+            dgnss.apply(&mut candidate, 42),
+            CorrectionStatus::BroadcastOnly(CorrectionLoss::Outdated)
+        );
+        assert_eq!(candidate.observations[0].pseudo, Some(20.0E6));
+    }
+}
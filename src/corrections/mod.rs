@@ -0,0 +1,202 @@
+//! Correction streams (SSR, DGNSS) lifecycle management
+use std::collections::BTreeMap;
+
+use crate::logging::{info, warn};
+
+use crate::prelude::{Duration, Epoch, SV};
+
+mod dgnss;
+
+pub use dgnss::{DgnssCorrection, DgnssCorrections};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Reason why no correction applies to a vehicle
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CorrectionLoss {
+    /// No correction was ever received
+    Missing,
+    /// Latest correction is older than the maximal age
+    Outdated,
+    /// Latest correction does not match the Issue of Data of the ephemeris in use
+    IodMismatch,
+}
+
+impl std::fmt::Display for CorrectionLoss {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Missing => write!(f, "missing"),
+            Self::Outdated => write!(f, "outdated"),
+            Self::IodMismatch => write!(f, "iod mismatch"),
+        }
+    }
+}
+
+/// [CorrectionStatus] of a vehicle, as of latest request
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CorrectionStatus {
+    /// Corrections were applied
+    Corrected,
+    /// Corrections did not apply and we fell back to broadcast-only
+    BroadcastOnly(CorrectionLoss),
+}
+
+impl CorrectionStatus {
+    /// Returns true if corrections were applied
+    pub fn is_corrected(&self) -> bool {
+        matches!(self, Self::Corrected)
+    }
+}
+
+/// Correction with a reference [Epoch] and an associated Issue of Data
+pub(crate) trait Correction {
+    /// Reference [Epoch]
+    fn epoch(&self) -> Epoch;
+    /// Issue of Data of the broadcast ephemeris this correction applies to
+    fn iod(&self) -> u16;
+}
+
+/// [CorrectionManager] tracks the latest correction of each vehicle, along with
+/// its validity window and the resulting [CorrectionStatus].
+/// Status changes are reported in the logs.
+#[derive(Debug, Clone)]
+pub(crate) struct CorrectionManager<T> {
+    /// Name of the correction stream, for reporting purposes
+    name: &'static str,
+    /// Maximal age of a correction
+    max_age: Duration,
+    corrections: BTreeMap<SV, T>,
+    status: BTreeMap<SV, CorrectionStatus>,
+}
+
+impl<T: Correction> CorrectionManager<T> {
+    pub fn new(name: &'static str, max_age: Duration) -> Self {
+        Self {
+            name,
+            max_age,
+            corrections: Default::default(),
+            status: Default::default(),
+        }
+    }
+    pub fn set_max_age(&mut self, max_age: Duration) {
+        self.max_age = max_age;
+    }
+    /// Stores new correction for [SV], replacing the previous one
+    pub fn insert(&mut self, sv: SV, correction: T) {
+        self.corrections.insert(sv, correction);
+    }
+    /// Returns latest correction for [SV] if it may be used at [Epoch]
+    pub fn get(&self, sv: SV, t: Epoch) -> Result<&T, CorrectionLoss> {
+        let correction = self.corrections.get(&sv).ok_or(CorrectionLoss::Missing)?;
+        if (t - correction.epoch()).abs() > self.max_age {
+            Err(CorrectionLoss::Outdated)
+        } else {
+            Ok(correction)
+        }
+    }
+    /// Returns latest correction for [SV] if it may be used at [Epoch],
+    /// along the ephemeris of this Issue of Data
+    pub fn get_iod(&self, sv: SV, t: Epoch, iod: u16) -> Result<&T, CorrectionLoss> {
+        let correction = self.get(sv, t)?;
+        if correction.iod() == iod {
+            Ok(correction)
+        } else {
+            Err(CorrectionLoss::IodMismatch)
+        }
+    }
+    /// Ages out corrections that may no longer be used after [Epoch]
+    pub fn discard_outdated(&mut self, t: Epoch) {
+        let max_age = self.max_age;
+        self.corrections
+            .retain(|_, correction| t - correction.epoch() <= max_age);
+    }
+    /// Updates [CorrectionStatus] of [SV] at [Epoch], reporting changes
+    pub fn report(&mut self, t: Epoch, sv: SV, status: CorrectionStatus) {
+        let previous = self.status.insert(sv, status);
+        if previous == Some(status) {
+            return;
+        }
+        match status {
+            CorrectionStatus::Corrected => {
+                if previous.is_some() {
+                    info!("{}({}) - {} corrections resumed", t, sv, self.name);
+                }
+            },
+            CorrectionStatus::BroadcastOnly(loss) => {
+                if previous != Some(CorrectionStatus::Corrected) && previous.is_some() {
+                    return;
+                }
+                warn!(
+                    "{}({}) - {} corrections {}: broadcast only",
+                    t, sv, self.name, loss
+                );
+            },
+        }
+    }
+    /// Returns latest [CorrectionStatus] of [SV]
+    pub fn status(&self, sv: SV) -> Option<CorrectionStatus> {
+        self.status.get(&sv).copied()
+    }
+    /// Returns vehicles that fell back to broadcast-only, as of latest request
+    pub fn broadcast_only(&self) -> Vec<SV> {
+        self.status
+            .iter()
+            .filter(|(_, status)| !status.is_corrected())
+            .map(|(sv, _)| *sv)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Correction, CorrectionLoss, CorrectionManager, CorrectionStatus};
+    use crate::prelude::{Constellation, Duration, Epoch, SV};
+    use std::str::FromStr;
+
+    #[derive(Debug, Clone)]
+    struct TestCorrection(Epoch, u16);
+
+    impl Correction for TestCorrection {
+        fn epoch(&self) -> Epoch {
+            self.0
+        }
+        fn iod(&self) -> u16 {
+            self.1
+        }
+    }
+
+    #[test]
+    fn correction_manager_lifecycle() {
+        let sv = SV::new(Constellation::GPS, 1);
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let mut manager = CorrectionManager::new("test", Duration::from_seconds(30.0));
+
+        assert_eq!(manager.get(sv, t0).err(), Some(CorrectionLoss::Missing));
+        manager.insert(sv, TestCorrection(t0, 10));
+
+        let t = t0 + Duration::from_seconds(10.0);
+        assert!(manager.get_iod(sv, t, 10).is_ok());
+        assert_eq!(
+            manager.get_iod(sv, t, 11).err(),
+            Some(CorrectionLoss::IodMismatch)
+        );
+
+        let t = t0 + Duration::from_seconds(31.0);
+        assert_eq!(manager.get(sv, t).err(), Some(CorrectionLoss::Outdated));
+
+        manager.report(t, sv, CorrectionStatus::Corrected);
+        assert!(manager.broadcast_only().is_empty());
+        manager.report(
+            t,
+            sv,
+            CorrectionStatus::BroadcastOnly(CorrectionLoss::Outdated),
+        );
+        assert_eq!(manager.broadcast_only(), vec![sv]);
+
+        manager.discard_outdated(t);
+        assert_eq!(manager.get(sv, t).err(), Some(CorrectionLoss::Missing));
+    }
+}
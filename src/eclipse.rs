@@ -0,0 +1,149 @@
+//! SV eclipses (Sun occultation by the Earth)
+use crate::{
+    constants::Constants,
+    prelude::{Epoch, Orbit, Vector3},
+};
+
+/// [SunEphemeris] evaluates the Sun position lazily (only when
+/// the eclipse filter is requested) and caches it for the current [Epoch],
+/// so it is not evaluated for each SV.
+/// The Sun position is obtained from a compact analytical model (low precision
+/// solar coordinates, ~0.01° accuracy): that is plenty for shadow determination,
+/// and does not require any planetary ephemeris.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct SunEphemeris {
+    /// Latest evaluation: [Epoch] and ECEF position [km]
+    cache: Option<(Epoch, Vector3<f64>)>,
+}
+
+impl SunEphemeris {
+    /// Returns Sun position [km] at [Epoch], as seen from the Earth, in ECEF
+    pub fn position_km(&mut self, t: Epoch) -> Vector3<f64> {
+        if let Some((cached_t, sun_km)) = self.cache {
+            if cached_t == t {
+                return sun_km;
+            }
+        }
+        let sun_km = sun_position_ecef_km(t);
+        self.cache = Some((t, sun_km));
+        sun_km
+    }
+}
+
+/// Low precision Sun position [km] in ECEF, at [Epoch]
+/// (Montenbruck & Gill, "Satellite Orbits", 3.3.2), rotated by the Greenwich mean sidereal time.
+fn sun_position_ecef_km(t: Epoch) -> Vector3<f64> {
+    // Julian centuries since J2000
+    let t_c = (t.to_jde_tt_days() - 2_451_545.0) / 36_525.0;
+
+    let m = (357.5256_f64 + 35_999.049 * t_c).to_radians();
+    let lambda = (282.9400_f64 + 1.3972 * t_c).to_radians()
+        + m
+        + ((6_892.0 * m.sin() + 72.0 * (2.0 * m).sin()) / 3_600.0).to_radians();
+    let r_km = (149.619 - 2.499 * m.cos() - 0.021 * (2.0 * m).cos()) * 1.0E6;
+
+    // ecliptic to equatorial
+    let eps = 23.439_291_11_f64.to_radians();
+    let (x, y, z) = (
+        r_km * lambda.cos(),
+        r_km * lambda.sin() * eps.cos(),
+        r_km * lambda.sin() * eps.sin(),
+    );
+
+    // equatorial to ECEF (UT1 ~ UTC)
+    let d_ut = t.to_jde_utc_days() - 2_451_545.0;
+    let gmst = (280.460_618_37 + 360.985_647_366_29 * d_ut + 0.000_387_933 * t_c.powi(2))
+        .rem_euclid(360.0)
+        .to_radians();
+
+    Vector3::new(
+        gmst.cos() * x + gmst.sin() * y,
+        -gmst.sin() * x + gmst.cos() * y,
+        z,
+    )
+}
+
+/// Returns percentage of the solar disk occulted by the Earth, as seen
+/// from this SV [Orbit] (conical shadow model), Sun position [km] being known.
+pub(crate) fn occultation_percent(sv_orbit: Orbit, sun_km: Vector3<f64>) -> f64 {
+    let sv_km = sv_orbit.radius_km;
+    let sv_sun_km = sun_km - sv_km;
+    let (sv_sun_mag, sv_mag) = (sv_sun_km.norm(), sv_km.norm());
+
+    // apparent radii and separation
+    let a = (Constants::SUN_RADIUS_KM / sv_sun_mag).asin();
+    let b = (Constants::EARTH_EQUATORIAL_RADIUS_KM / sv_mag)
+        .min(1.0)
+        .asin();
+    let c = (sv_sun_km.dot(&-sv_km) / sv_sun_mag / sv_mag)
+        .clamp(-1.0, 1.0)
+        .acos();
+
+    if c >= a + b {
+        // full sunlight
+        0.0
+    } else if c <= b - a {
+        // umbra
+        100.0
+    } else if c <= a - b {
+        // annular (Earth within the solar disk)
+        b.powi(2) / a.powi(2) * 100.0
+    } else {
+        // penumbra: overlapping disks
+        let x = (c.powi(2) + a.powi(2) - b.powi(2)) / 2.0 / c;
+        let y = (a.powi(2) - x.powi(2)).max(0.0).sqrt();
+        let area = a.powi(2) * (x / a).clamp(-1.0, 1.0).acos()
+            + b.powi(2) * ((c - x) / b).clamp(-1.0, 1.0).acos()
+            - c * y;
+        area / std::f64::consts::PI / a.powi(2) * 100.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{occultation_percent, SunEphemeris};
+    use crate::prelude::{Epoch, Orbit, Vector3, EARTH_ITRF93};
+    use std::str::FromStr;
+
+    #[test]
+    fn conical_shadow() {
+        let t = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let sun_km = Vector3::new(1.496E8, 0.0, 0.0);
+        let r_km = 26_560.0;
+
+        // sunlit side
+        let sv = Orbit::from_position(r_km, 0.0, 0.0, t, EARTH_ITRF93);
+        assert_eq!(occultation_percent(sv, sun_km), 0.0);
+
+        // behind the Earth
+        let sv = Orbit::from_position(-r_km, 0.0, 0.0, t, EARTH_ITRF93);
+        assert_eq!(occultation_percent(sv, sun_km), 100.0);
+
+        // side way: sunlit
+        let sv = Orbit::from_position(0.0, r_km, 0.0, t, EARTH_ITRF93);
+        assert_eq!(occultation_percent(sv, sun_km), 0.0);
+
+        // shadow boundary: partially occulted
+        let y_km = 6378.1366 + 26_560.0 * (696_000.0_f64 / 1.496E8) / 2.0;
+        let sv = Orbit::from_position(-r_km, y_km, 0.0, t, EARTH_ITRF93);
+        let percent = occultation_percent(sv, sun_km);
+        assert!(percent > 0.0 && percent < 100.0, "{}", percent);
+    }
+
+    #[test]
+    fn analytical_sun() {
+        // june solstice, noon UTC
+        let t = Epoch::from_str("2020-06-20T12:00:00 UTC").unwrap();
+        let sun_km = SunEphemeris::default().position_km(t);
+
+        let r_km = sun_km.norm();
+        assert!((r_km - 1.5202E8).abs() < 1.0E5, "{}", r_km);
+
+        let declination = (sun_km[2] / r_km).asin().to_degrees();
+        assert!((declination - 23.44).abs() < 0.05, "{}", declination);
+
+        // subsolar longitude, offset by the equation of time (-1.5')
+        let longitude = sun_km[1].atan2(sun_km[0]).to_degrees();
+        assert!((longitude - 0.4).abs() < 0.2, "{}", longitude);
+    }
+}
@@ -0,0 +1,452 @@
+//! CGGTTS (2E) common view tracks, for GNSS time transfer
+use std::{collections::BTreeMap, io::Write};
+
+use crate::{
+    constants::SPEED_OF_LIGHT_M_S,
+    prelude::{Duration, Epoch, PVTSolution, SV},
+};
+
+/// Standard track duration [s] (13')
+const TRACK_DURATION_S: f64 = 780.0;
+
+/// Standard schedule period: 3' warmup + 13' track
+const SCHEDULE_PERIOD_S: f64 = 960.0;
+
+/// Reference day of the BIPM schedule: first track at 00:02:00 UTC
+const SCHEDULE_REF_MJD: i64 = 50_722;
+
+/// Returns the CGGTTS checksum of this content (sum of ASCII values, modulo 256)
+fn checksum(content: &str) -> u8 {
+    content.bytes().fold(0_u8, |ck, byte| ck.wrapping_add(byte))
+}
+
+/// Returns the start of the standard (BIPM) track containing [Epoch], if any.
+/// Tracks last 13' and start every 16', the first track of MJD 50722
+/// started at 00:02:00 UTC and the schedule advances by 4' every day.
+pub fn cggtts_track_start(t: Epoch) -> Option<Epoch> {
+    let mjd = t.to_mjd_utc_days().floor();
+    let midnight = Epoch::from_mjd_utc(mjd);
+    let offset_s =
+        (120.0 - 240.0 * (mjd as i64 - SCHEDULE_REF_MJD) as f64).rem_euclid(SCHEDULE_PERIOD_S);
+
+    let elapsed_s = (t - midnight).to_seconds() - offset_s;
+    if elapsed_s < 0.0 {
+        return None;
+    }
+
+    let start_s = offset_s + (elapsed_s / SCHEDULE_PERIOD_S).floor() * SCHEDULE_PERIOD_S;
+    let within_s = (t - midnight).to_seconds() - start_s;
+
+    if within_s < TRACK_DURATION_S && start_s + TRACK_DURATION_S <= 86_400.0 {
+        Some(midnight + Duration::from_seconds(start_s))
+    } else {
+        None
+    }
+}
+
+/// Least squares line fit, returns (value at `x = 0`, slope, rms of the residuals)
+fn linear_fit(points: &[(f64, f64)]) -> (f64, f64, f64) {
+    let n = points.len() as f64;
+    let (sx, sy) = points
+        .iter()
+        .fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+    let (mx, my) = (sx / n, sy / n);
+    let (sxx, sxy) = points.iter().fold((0.0, 0.0), |(sxx, sxy), (x, y)| {
+        (sxx + (x - mx).powi(2), sxy + (x - mx) * (y - my))
+    });
+    let slope = if sxx > 0.0 { sxy / sxx } else { 0.0 };
+    let value = my - slope * mx;
+    let rms = (points
+        .iter()
+        .map(|(x, y)| (y - value - slope * x).powi(2))
+        .sum::<f64>()
+        / n)
+        .sqrt();
+    (value, slope, rms)
+}
+
+/// [CggttsTrack] is one SV track, fitted over the standard 13' period.
+/// Values are expressed at the middle of the track.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CggttsTrack {
+    /// [SV] being tracked
+    pub sv: SV,
+    /// Track start [Epoch] (UTC)
+    pub start: Epoch,
+    /// Elevation [°]
+    pub elevation_deg: f64,
+    /// Azimuth [°]
+    pub azimuth_deg: f64,
+    /// Local clock minus [SV] clock [s]
+    pub refsv_s: f64,
+    /// [Self::refsv_s] slope [s/s]
+    pub srsv_s_s: f64,
+    /// Local clock minus system time [s]
+    pub refsys_s: f64,
+    /// [Self::refsys_s] slope [s/s]
+    pub srsys_s_s: f64,
+    /// RMS of [Self::refsys_s] fit residuals [s]
+    pub dsg_s: f64,
+    /// Modeled troposphere delay [s]
+    pub mdtr_s: f64,
+    /// [Self::mdtr_s] slope [s/s]
+    pub smdt_s_s: f64,
+    /// Ionosphere delay [s]
+    pub mdio_s: f64,
+    /// [Self::mdio_s] slope [s/s]
+    pub smdi_s_s: f64,
+    /// Number of solutions this track was fitted to
+    pub samples: usize,
+}
+
+impl CggttsTrack {
+    /// Formats this [CggttsTrack] as a CGGTTS (2E) data line, for the given
+    /// frequency code (like "L1C"), including its checksum.
+    pub fn format(&self, frc: &str) -> String {
+        let (mjd, sttime) = {
+            let mjd = self.start.to_mjd_utc_days().floor();
+            let (_, _, _, hh, mm, ss, _) = self.start.to_gregorian_utc();
+            (mjd as u32, format!("{:02}{:02}{:02}", hh, mm, ss))
+        };
+        let line = format!(
+            "{} FF {:5} {} {:4} {:3} {:4} {:+11} {:+6} {:+11} {:+6} {:4} {:3} {:4} {:+4} {:4} {:+4} 9999 +999 999  0  0 {:3} ",
+            self.sv,
+            mjd,
+            sttime,
+            TRACK_DURATION_S as u32,
+            (self.elevation_deg * 10.0).round() as i32,
+            (self.azimuth_deg.rem_euclid(360.0) * 10.0).round() as i32,
+            (self.refsv_s * 1.0E10).round() as i64,
+            (self.srsv_s_s * 1.0E13).round() as i64,
+            (self.refsys_s * 1.0E10).round() as i64,
+            (self.srsys_s_s * 1.0E13).round() as i64,
+            (self.dsg_s * 1.0E10).round() as i64,
+            0,
+            (self.mdtr_s * 1.0E10).round() as i64,
+            (self.smdt_s_s * 1.0E13).round() as i64,
+            (self.mdio_s * 1.0E10).round() as i64,
+            (self.smdi_s_s * 1.0E13).round() as i64,
+            frc,
+        );
+        format!("{}{:02X}", line, checksum(&line))
+    }
+}
+
+/// Single [SV] contribution to a [PVTSolution]
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    t: Epoch,
+    elevation_deg: f64,
+    azimuth_deg: f64,
+    refsv_s: f64,
+    refsys_s: f64,
+    mdtr_s: f64,
+    mdio_s: f64,
+}
+
+/// [CggttsTracker] schedules the standard (BIPM) 13' tracks and fits each [SV]
+/// track from the [PVTSolution]s resolved in TimeOnly or FixedPosition mode.
+/// Solutions obtained in clock holdover are not used.
+#[derive(Debug, Clone)]
+pub struct CggttsTracker {
+    /// Current track start
+    start: Option<Epoch>,
+    /// Samples of the current track, per [SV]
+    samples: BTreeMap<SV, Vec<Sample>>,
+    /// Minimal time span a track must cover
+    min_span: Duration,
+}
+
+impl Default for CggttsTracker {
+    fn default() -> Self {
+        Self {
+            start: None,
+            samples: BTreeMap::new(),
+            min_span: Duration::from_seconds(TRACK_DURATION_S / 2.0),
+        }
+    }
+}
+
+impl CggttsTracker {
+    /// Builds new [CggttsTracker]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Copies and returns [CggttsTracker] with minimal time span each [SV]
+    /// track must cover (defaults to half the track duration). Shorter tracks are dropped.
+    pub fn with_min_span(&self, min_span: Duration) -> Self {
+        let mut s = self.clone();
+        s.min_span = min_span;
+        s
+    }
+    /// Stores the [PVTSolution] resolved at [Epoch]. Returns the tracks
+    /// that were completed, when the [Epoch] falls out of the current track.
+    pub fn push(&mut self, t: Epoch, solution: &PVTSolution) -> Vec<CggttsTrack> {
+        let start = cggtts_track_start(t);
+        let mut tracks = Vec::new();
+        if start != self.start {
+            tracks = self.flush();
+            self.start = start;
+        }
+        if start.is_none() || solution.is_holdover() {
+            return tracks;
+        }
+        let refsys_s = solution.dt.to_seconds();
+        for (sv, input) in solution.sv.iter() {
+            let clock_corr_s = input.clock_correction.unwrap_or_default().to_seconds();
+            self.samples.entry(*sv).or_default().push(Sample {
+                t,
+                elevation_deg: input.elevation,
+                azimuth_deg: input.azimuth,
+                refsv_s: refsys_s - clock_corr_s,
+                refsys_s,
+                mdtr_s: input.tropo_bias.unwrap_or_default() / SPEED_OF_LIGHT_M_S,
+                mdio_s: input.iono_bias.unwrap_or_default().value() / SPEED_OF_LIGHT_M_S,
+            });
+        }
+        tracks
+    }
+    /// Fits and returns the current tracks, whether they are complete or not.
+    pub fn flush(&mut self) -> Vec<CggttsTrack> {
+        let samples = std::mem::take(&mut self.samples);
+        let Some(start) = self.start else {
+            return Vec::new();
+        };
+        let mid = start + Duration::from_seconds(TRACK_DURATION_S / 2.0);
+        samples
+            .into_iter()
+            .filter_map(|(sv, samples)| {
+                let (first, last) = (samples.first()?, samples.last()?);
+                if last.t - first.t < self.min_span {
+                    return None;
+                }
+                let fit = |value: fn(&Sample) -> f64| {
+                    let points = samples
+                        .iter()
+                        .map(|s| ((s.t - mid).to_seconds(), value(s)))
+                        .collect::<Vec<_>>();
+                    linear_fit(&points)
+                };
+                let (elevation_deg, _, _) = fit(|s| s.elevation_deg);
+                let (refsv_s, srsv_s_s, _) = fit(|s| s.refsv_s);
+                let (refsys_s, srsys_s_s, dsg_s) = fit(|s| s.refsys_s);
+                let (mdtr_s, smdt_s_s, _) = fit(|s| s.mdtr_s);
+                let (mdio_s, smdi_s_s, _) = fit(|s| s.mdio_s);
+                // azimuth wraps around: closest to mid track
+                let azimuth_deg = samples
+                    .iter()
+                    .min_by_key(|s| (s.t - mid).abs())?
+                    .azimuth_deg;
+                Some(CggttsTrack {
+                    sv,
+                    start,
+                    elevation_deg,
+                    azimuth_deg,
+                    refsv_s,
+                    srsv_s_s,
+                    refsys_s,
+                    srsys_s_s,
+                    dsg_s,
+                    mdtr_s,
+                    smdt_s_s,
+                    mdio_s,
+                    smdi_s_s,
+                    samples: samples.len(),
+                })
+            })
+            .collect()
+    }
+}
+
+/// [CggttsHeader] describes the station and its calibration
+#[derive(Debug, Clone, PartialEq)]
+pub struct CggttsHeader {
+    /// Receiver description (maker, type, serial number, firmware)
+    pub receiver: String,
+    /// Number of receiver channels
+    pub channels: u16,
+    /// Laboratory name
+    pub lab: String,
+    /// Antenna position, ECEF [m]
+    pub position_ecef_m: (f64, f64, f64),
+    /// Reference frame of the antenna position (like "ITRF")
+    pub frame: String,
+    /// Local clock (reference) name
+    pub reference: String,
+    /// Frequency code of the tracks (like "L1C")
+    pub frc: String,
+    /// Receiver internal delay [s]
+    pub int_delay_s: f64,
+    /// Antenna cable delay [s]
+    pub cable_delay_s: f64,
+    /// Reference (local clock) delay [s]
+    pub ref_delay_s: f64,
+    /// Comments
+    pub comments: Option<String>,
+}
+
+impl Default for CggttsHeader {
+    fn default() -> Self {
+        Self {
+            receiver: "UNKNOWN".to_string(),
+            channels: 0,
+            lab: "UNKNOWN".to_string(),
+            position_ecef_m: (0.0, 0.0, 0.0),
+            frame: "ITRF".to_string(),
+            reference: "UNKNOWN".to_string(),
+            frc: "L1C".to_string(),
+            int_delay_s: 0.0,
+            cable_delay_s: 0.0,
+            ref_delay_s: 0.0,
+            comments: None,
+        }
+    }
+}
+
+impl CggttsHeader {
+    /// Formats this [CggttsHeader], including the tracks table header
+    pub fn format(&self) -> String {
+        let (x_m, y_m, z_m) = self.position_ecef_m;
+        let mut header = format!(
+            "CGGTTS     GENERIC DATA FORMAT VERSION = 2E\n\
+            REV DATE = 2014-02-20\n\
+            RCVR = {}\n\
+            CH = {}\n\
+            IMS = 99999\n\
+            LAB = {}\n\
+            X = {:+.2} m\n\
+            Y = {:+.2} m\n\
+            Z = {:+.2} m\n\
+            FRAME = {}\n\
+            COMMENTS = {}\n\
+            INT DLY = {:.1} ns ({})     CAL_ID = NA\n\
+            CAB DLY = {:.1} ns\n\
+            REF DLY = {:.1} ns\n\
+            REF = {}\n\
+            CKSUM = ",
+            self.receiver,
+            self.channels,
+            self.lab,
+            x_m,
+            y_m,
+            z_m,
+            self.frame,
+            self.comments.as_deref().unwrap_or("NO COMMENTS"),
+            self.int_delay_s * 1.0E9,
+            self.frc,
+            self.cable_delay_s * 1.0E9,
+            self.ref_delay_s * 1.0E9,
+            self.reference,
+        );
+        let ck = checksum(&header.replace('\n', ""));
+        header.push_str(&format!("{:02X}\n\n", ck));
+        header.push_str("SAT CL  MJD  STTIME TRKL ELV AZTH   REFSV      SRSV     REFSYS    SRSYS  DSG IOE MDTR SMDT MDIO SMDI MSIO SMSI ISG FR HC FRC CK\n");
+        header.push_str("             hhmmss  s  .1dg .1dg    .1ns     .1ps/s     .1ns    .1ps/s .1ns     .1ns.1ps/s.1ns.1ps/s.1ns.1ps/s.1ns            \n");
+        header
+    }
+}
+
+/// [CggttsWriter] writes CGGTTS (2E) files, track after track
+pub struct CggttsWriter<W: Write> {
+    writer: W,
+    frc: String,
+}
+
+impl<W: Write> CggttsWriter<W> {
+    /// Builds new [CggttsWriter], writing the [CggttsHeader] right away
+    pub fn new(mut writer: W, header: &CggttsHeader) -> std::io::Result<Self> {
+        writer.write_all(header.format().as_bytes())?;
+        Ok(Self {
+            writer,
+            frc: header.frc.clone(),
+        })
+    }
+    /// Writes a single [CggttsTrack]
+    pub fn write(&mut self, track: &CggttsTrack) -> std::io::Result<()> {
+        writeln!(self.writer, "{}", track.format(&self.frc))
+    }
+    /// Flushes and returns the inner writer
+    pub fn into_inner(mut self) -> std::io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{cggtts_track_start, CggttsHeader, CggttsTracker, CggttsWriter};
+    use crate::{
+        navigation::SVInput,
+        prelude::{Duration, Epoch, Orbit, PVTSolution, TimeScale, EARTH_ITRF93, SV},
+    };
+    use nalgebra::Matrix4;
+    use std::{collections::BTreeMap, str::FromStr};
+
+    #[test]
+    fn cggtts_schedule() {
+        let t0 = Epoch::from_mjd_utc(50_722.0);
+        assert!(cggtts_track_start(t0).is_none());
+        let start = cggtts_track_start(t0 + Duration::from_seconds(300.0)).unwrap();
+        assert_eq!(start, t0 + Duration::from_seconds(120.0));
+        assert!(cggtts_track_start(t0 + Duration::from_seconds(120.0 + 800.0)).is_none());
+
+        // 4' earlier every day
+        let t1 = Epoch::from_mjd_utc(50_723.0);
+        let start = cggtts_track_start(t1 + Duration::from_seconds(1000.0)).unwrap();
+        assert_eq!(start, t1 + Duration::from_seconds(840.0 + 120.0));
+    }
+
+    #[test]
+    fn cggtts_tracking() {
+        let g01 = SV::from_str("G01").unwrap();
+        let t0 = Epoch::from_mjd_utc(50_722.0) + Duration::from_seconds(120.0);
+        let mut tracker = CggttsTracker::new();
+        let mut tracks = Vec::new();
+
+        for i in 0..=30 {
+            let t = t0 + Duration::from_seconds(30.0 * i as f64);
+            let mut sv = BTreeMap::new();
+            sv.insert(
+                g01,
+                SVInput {
+                    elevation: 45.0,
+                    azimuth: 120.0,
+                    tropo_bias: Some(3.0),
+                    clock_correction: Some(Duration::from_seconds(1.0E-5)),
+                    ..Default::default()
+                },
+            );
+            let solution = PVTSolution {
+                state: Orbit::from_position(4_000.0, 300.0, 4_900.0, t, EARTH_ITRF93),
+                frame: Default::default(),
+                timescale: TimeScale::GPST,
+                dt: Duration::from_seconds(1.0E-6 + 1.0E-12 * 30.0 * i as f64),
+                d_dt: 0.0,
+                sv,
+                ambiguities: Default::default(),
+                holdover: None,
+                ggto: None,
+                q: Matrix4::zeros(),
+            };
+            tracks.extend(tracker.push(t, &solution));
+        }
+
+        assert_eq!(tracks.len(), 1);
+        let track = tracks[0];
+        assert_eq!(track.sv, g01);
+        assert_eq!(track.start, t0);
+        assert_eq!(track.samples, 26);
+        assert!((track.elevation_deg - 45.0).abs() < 1.0E-9);
+        assert!((track.refsys_s - (1.0E-6 + 390.0E-12)).abs() < 1.0E-15);
+        assert!((track.srsys_s_s - 1.0E-12).abs() < 1.0E-18);
+        assert!((track.refsv_s - track.refsys_s + 1.0E-5).abs() < 1.0E-15);
+        assert!(track.dsg_s < 1.0E-15);
+
+        let mut writer = CggttsWriter::new(Vec::new(), &CggttsHeader::default()).unwrap();
+        writer.write(&track).unwrap();
+        let content = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        let line = content.lines().last().unwrap();
+        assert!(line.starts_with("G01 FF 50722 000200  780 450 1200"));
+        assert!(content.contains("CKSUM = "));
+    }
+}
@@ -0,0 +1,181 @@
+//! JSON-lines solution logger
+use std::io::Write;
+
+use crate::coordinates::Ellipsoid;
+
+use crate::prelude::{Epoch, IonosphereBias, PVTSolution};
+
+/// Formats this number as JSON value (non finite numbers are not valid JSON)
+fn json_f64(value: f64) -> String {
+    if value.is_finite() {
+        format!("{}", value)
+    } else {
+        "null".to_string()
+    }
+}
+
+/// Formats this optional number as JSON value
+fn json_opt_f64(value: Option<f64>) -> String {
+    value.map(json_f64).unwrap_or_else(|| "null".to_string())
+}
+
+/// Formats this [PVTSolution] resolved at [Epoch] as a single JSON object
+/// (without line terminator), including per SV residuals and solution flags.
+pub fn solution_json(t: Epoch, solution: &PVTSolution) -> String {
+    let pos_vel_m = solution.state.to_cartesian_pos_vel() * 1.0E3;
+    let (lat_rad, lon_rad, alt_m) =
+        Ellipsoid::WGS84.ecef_to_geodetic((pos_vel_m[0], pos_vel_m[1], pos_vel_m[2]));
+
+    let mut flags = Vec::new();
+    if solution.is_holdover() {
+        flags.push("\"holdover\"");
+    }
+
+    let sv = solution
+        .sv
+        .iter()
+        .map(|(sv, input)| {
+            let iono = match input.iono_bias {
+                Some(IonosphereBias::Measured(bias_m)) => {
+                    format!("{},\"iono_measured\":true", json_f64(bias_m))
+                },
+                Some(IonosphereBias::Modeled(bias_m)) => {
+                    format!("{},\"iono_measured\":false", json_f64(bias_m))
+                },
+                None => "null,\"iono_measured\":null".to_string(),
+            };
+            format!(
+                "{{\"sv\":\"{}\",\"elevation_deg\":{},\"azimuth_deg\":{},\"residual_m\":{},\
+                \"tropo_m\":{},\"iono_m\":{},\"clock_correction_s\":{}}}",
+                sv,
+                json_f64(input.elevation),
+                json_f64(input.azimuth),
+                json_opt_f64(input.residual_m),
+                json_opt_f64(input.tropo_bias),
+                iono,
+                json_opt_f64(input.clock_correction.map(|dt| dt.to_seconds())),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!(
+        "{{\"epoch\":\"{}\",\"timescale\":\"{}\",\"x_m\":{},\"y_m\":{},\"z_m\":{},\
+        \"vx_m_s\":{},\"vy_m_s\":{},\"vz_m_s\":{},\"latitude_ddeg\":{},\
+        \"longitude_ddeg\":{},\"altitude_m\":{},\"dt_s\":{},\"d_dt_s_s\":{},\
+        \"gdop\":{},\"pdop\":{},\"tdop\":{},\"hdop\":{},\"vdop\":{},\"holdover_s\":{},\
+        \"flags\":[{}],\"sv\":[{}]}}",
+        t,
+        solution.timescale,
+        json_f64(pos_vel_m[0]),
+        json_f64(pos_vel_m[1]),
+        json_f64(pos_vel_m[2]),
+        json_f64(pos_vel_m[3]),
+        json_f64(pos_vel_m[4]),
+        json_f64(pos_vel_m[5]),
+        json_f64(lat_rad.to_degrees()),
+        json_f64(lon_rad.to_degrees()),
+        json_f64(alt_m),
+        json_f64(solution.dt.to_seconds()),
+        json_f64(solution.d_dt),
+        json_f64(solution.gdop()),
+        json_f64(solution.pdop()),
+        json_f64(solution.tdop()),
+        json_f64(solution.hdop(lat_rad, lon_rad)),
+        json_f64(solution.vdop(lat_rad, lon_rad)),
+        json_opt_f64(solution.holdover.map(|dt| dt.to_seconds())),
+        flags.join(","),
+        sv,
+    )
+}
+
+/// [SolutionLogger] writes one JSON object per [PVTSolution] and per line (JSON-lines),
+/// to be piped into `jq` or log pipelines during long runs.
+/// Each line is flushed right away, so consumers follow the processing live.
+pub struct SolutionLogger<W: Write> {
+    writer: W,
+    lines: usize,
+}
+
+impl<W: Write> SolutionLogger<W> {
+    /// Builds new [SolutionLogger]
+    pub fn new(writer: W) -> Self {
+        Self { writer, lines: 0 }
+    }
+    /// Logs this [PVTSolution] resolved at [Epoch]
+    pub fn log(&mut self, t: Epoch, solution: &PVTSolution) -> std::io::Result<()> {
+        writeln!(self.writer, "{}", solution_json(t, solution))?;
+        self.writer.flush()?;
+        self.lines += 1;
+        Ok(())
+    }
+    /// Returns number of solutions logged so far
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+    /// Flushes and returns the inner writer
+    pub fn into_inner(mut self) -> std::io::Result<W> {
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SolutionLogger;
+    use crate::{
+        navigation::SVInput,
+        prelude::{
+            Duration, Epoch, IonosphereBias, Orbit, PVTSolution, TimeScale, EARTH_ITRF93, SV,
+        },
+    };
+    use nalgebra::{Matrix4, Vector4};
+    use std::{collections::BTreeMap, str::FromStr};
+
+    #[test]
+    fn solution_logger() {
+        let t = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let mut sv = BTreeMap::new();
+        sv.insert(
+            SV::from_str("G01").unwrap(),
+            SVInput {
+                elevation: 45.0,
+                azimuth: 120.0,
+                residual_m: Some(0.5),
+                iono_bias: Some(IonosphereBias::Modeled(2.0)),
+                ..Default::default()
+            },
+        );
+        let mut solution = PVTSolution {
+            state: Orbit::from_position(4_000.0, 300.0, 4_900.0, t, EARTH_ITRF93),
+            frame: Default::default(),
+            timescale: TimeScale::GPST,
+            dt: Duration::from_seconds(1.0E-6),
+            d_dt: 0.0,
+            sv,
+            ambiguities: Default::default(),
+            holdover: None,
+            ggto: None,
+            q: Matrix4::from_diagonal(&Vector4::new(f64::NAN, 0.0, 0.0, 1.0)),
+        };
+
+        let mut logger = SolutionLogger::new(Vec::new());
+        logger.log(t, &solution).unwrap();
+        solution.holdover = Some(Duration::from_seconds(30.0));
+        logger.log(t, &solution).unwrap();
+        assert_eq!(logger.lines(), 2);
+
+        let content = String::from_utf8(logger.into_inner().unwrap()).unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+
+        assert!(lines[0].starts_with("{\"epoch\":\"2020-06-25T12:00:00 GPST\""));
+        assert!(lines[0].contains("\"pdop\":null,"));
+        assert!(lines[0].contains("\"holdover_s\":null,\"flags\":[],"));
+        assert!(lines[0].contains(
+            "\"sv\":[{\"sv\":\"G01\",\"elevation_deg\":45,\"azimuth_deg\":120,\"residual_m\":0.5,\
+            \"tropo_m\":null,\"iono_m\":2,\"iono_measured\":false,\"clock_correction_s\":null}]}"
+        ));
+        assert!(lines[1].contains("\"holdover_s\":30,\"flags\":[\"holdover\"],"));
+    }
+}
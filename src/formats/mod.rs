@@ -0,0 +1,74 @@
+//! Adaptors, from common GNSS formats to [Candidate] pools
+#[cfg(any(
+    feature = "rinex",
+    feature = "rtcm",
+    feature = "sbf",
+    feature = "ubx",
+    feature = "python"
+))]
+pub(crate) mod pool;
+
+#[cfg(feature = "rinex")]
+mod rinex;
+
+#[cfg(feature = "rtcm")]
+mod rtcm;
+
+#[cfg(feature = "nmea")]
+mod nmea;
+
+#[cfg(feature = "cggtts")]
+mod cggtts;
+
+#[cfg(feature = "stream")]
+mod stream;
+
+#[cfg(feature = "jsonl")]
+mod jsonl;
+
+#[cfg(feature = "ntrip")]
+mod ntrip;
+
+#[cfg(feature = "sbf")]
+mod sbf;
+
+#[cfg(feature = "ubx")]
+mod ubx;
+
+#[cfg(feature = "rinex")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rinex")))]
+pub use rinex::rinex_candidate_pools;
+
+#[cfg(feature = "rtcm")]
+#[cfg_attr(docsrs, doc(cfg(feature = "rtcm")))]
+pub use rtcm::{
+    ClasMessage, ClasStec, MsmObservations, ReferenceStation, RtcmDecoder, RtcmMessage, SsrMessage,
+};
+
+#[cfg(feature = "ntrip")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ntrip")))]
+pub use ntrip::{NtripClient, NtripConfig, NtripError};
+
+#[cfg(feature = "ubx")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ubx")))]
+pub use ubx::{NavPvt, RawxObservations, UbxDecoder, UbxMessage};
+
+#[cfg(feature = "sbf")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sbf")))]
+pub use sbf::{SbfDecoder, SbfMessage, SbfObservations};
+
+#[cfg(feature = "nmea")]
+#[cfg_attr(docsrs, doc(cfg(feature = "nmea")))]
+pub use nmea::{NmeaFix, NmeaReader, ReferenceTrack};
+
+#[cfg(feature = "cggtts")]
+#[cfg_attr(docsrs, doc(cfg(feature = "cggtts")))]
+pub use cggtts::{cggtts_track_start, CggttsHeader, CggttsTrack, CggttsTracker, CggttsWriter};
+
+#[cfg(feature = "stream")]
+#[cfg_attr(docsrs, doc(cfg(feature = "stream")))]
+pub use stream::{SolutionStream, StreamFormat};
+
+#[cfg(feature = "jsonl")]
+#[cfg_attr(docsrs, doc(cfg(feature = "jsonl")))]
+pub use jsonl::{solution_json, SolutionLogger};
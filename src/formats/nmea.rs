@@ -0,0 +1,290 @@
+//! NMEA (0183) GGA and RMC sentences
+use std::{collections::BTreeMap, str::FromStr};
+
+use crate::{
+    coordinates::{ecef_to_enu, Ellipsoid},
+    prelude::{Duration, Epoch, Frame, Orbit, PVTSolution, Vector3},
+};
+
+/// Validates the sentence checksum, returns its content (between '$' and '*')
+fn content(sentence: &str) -> Option<&str> {
+    let sentence = sentence.trim().strip_prefix('$')?;
+    let (content, checksum) = sentence.split_once('*')?;
+    let expected = u8::from_str_radix(checksum.get(..2)?, 16).ok()?;
+    let checksum = content.bytes().fold(0_u8, |ck, byte| ck ^ byte);
+    if checksum == expected {
+        Some(content)
+    } else {
+        None
+    }
+}
+
+/// Parses (d)ddmm.mmmm angle and its hemisphere, returns decimal degrees
+fn angle(value: &str, hemisphere: &str) -> Option<f64> {
+    let dot = value.find('.').unwrap_or(value.len());
+    if dot < 2 {
+        return None;
+    }
+    let degrees = f64::from_str(&value[..dot - 2]).ok()?;
+    let minutes = f64::from_str(&value[dot - 2..]).ok()?;
+    let angle = degrees + minutes / 60.0;
+    match hemisphere {
+        "N" | "E" => Some(angle),
+        "S" | "W" => Some(-angle),
+        _ => None,
+    }
+}
+
+/// Parses hhmmss.ss time of day
+fn time_of_day(value: &str) -> Option<Duration> {
+    let hours = f64::from_str(value.get(0..2)?).ok()?;
+    let minutes = f64::from_str(value.get(2..4)?).ok()?;
+    let seconds = f64::from_str(value.get(4..)?).ok()?;
+    Some(Duration::from_seconds(
+        hours * 3600.0 + minutes * 60.0 + seconds,
+    ))
+}
+
+/// [NmeaFix] position fix, from GGA or RMC sentence
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NmeaFix {
+    /// Fix [Epoch] (UTC)
+    pub t: Epoch,
+    /// Geodetic latitude [ddeg]
+    pub latitude_ddeg: f64,
+    /// Geodetic longitude [ddeg]
+    pub longitude_ddeg: f64,
+    /// Height above ellipsoid [m] (GGA only)
+    pub height_m: Option<f64>,
+    /// GGA fix quality indicator (1: GNSS, 2: DGNSS, 4: RTK fixed, 5: RTK float..)
+    pub quality: Option<u8>,
+    /// Number of SV used in the solution (GGA only)
+    pub num_sv: Option<u8>,
+    /// Horizontal dilution of precision (GGA only)
+    pub hdop: Option<f64>,
+}
+
+impl NmeaFix {
+    /// Returns ECEF position [m], when height is known
+    pub fn position_ecef_m(&self) -> Option<(f64, f64, f64)> {
+        Some(Ellipsoid::WGS84.geodetic_to_ecef(
+            self.latitude_ddeg.to_radians(),
+            self.longitude_ddeg.to_radians(),
+            self.height_m?,
+        ))
+    }
+    /// Returns apriori position as [Orbit], to initialize the `Solver`.
+    /// None if height is unknown (RMC).
+    pub fn apriori(&self, frame: Frame) -> Option<Orbit> {
+        let (x_m, y_m, z_m) = self.position_ecef_m()?;
+        Some(Orbit::from_position(
+            x_m / 1.0E3,
+            y_m / 1.0E3,
+            z_m / 1.0E3,
+            self.t,
+            frame,
+        ))
+    }
+}
+
+/// [NmeaReader] parses GGA and RMC sentences (any talker).
+/// GGA sentences do not include the date, which is learned from RMC sentences:
+/// GGA sentences are dropped until a first RMC sentence is parsed, unless
+/// the date is provided with [Self::with_date].
+#[derive(Debug, Clone, Default)]
+pub struct NmeaReader {
+    /// Current day (midnight UTC)
+    day: Option<Epoch>,
+}
+
+impl NmeaReader {
+    /// Builds new [NmeaReader]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Copies and returns [NmeaReader] with current date (UTC)
+    pub fn with_date(&self, year: i32, month: u8, day: u8) -> Self {
+        let mut s = self.clone();
+        s.day = Some(Epoch::from_gregorian_utc_at_midnight(year, month, day));
+        s
+    }
+    /// Parses a single sentence. Returns None for invalid, unsupported or
+    /// void (no fix) sentences.
+    pub fn parse(&mut self, sentence: &str) -> Option<NmeaFix> {
+        let content = content(sentence)?;
+        let fields = content.split(',').collect::<Vec<_>>();
+        let kind = fields[0].get(2..)?;
+        match kind {
+            "GGA" if fields.len() >= 12 => {
+                let day = self.day?;
+                let quality = u8::from_str(fields[6]).ok()?;
+                if quality == 0 {
+                    return None;
+                }
+                let altitude_m = f64::from_str(fields[9]).ok()?;
+                let separation_m = f64::from_str(fields[11]).unwrap_or(0.0);
+                Some(NmeaFix {
+                    t: day + time_of_day(fields[1])?,
+                    latitude_ddeg: angle(fields[2], fields[3])?,
+                    longitude_ddeg: angle(fields[4], fields[5])?,
+                    height_m: Some(altitude_m + separation_m),
+                    quality: Some(quality),
+                    num_sv: u8::from_str(fields[7]).ok(),
+                    hdop: f64::from_str(fields[8]).ok(),
+                })
+            },
+            "RMC" if fields.len() >= 10 => {
+                let date = fields[9];
+                let year = i32::from_str(date.get(4..6)?).ok()?;
+                let day = Epoch::from_gregorian_utc_at_midnight(
+                    if year < 80 { 2000 + year } else { 1900 + year },
+                    u8::from_str(date.get(2..4)?).ok()?,
+                    u8::from_str(date.get(0..2)?).ok()?,
+                );
+                self.day = Some(day);
+                if fields[2] != "A" {
+                    return None;
+                }
+                Some(NmeaFix {
+                    t: day + time_of_day(fields[1])?,
+                    latitude_ddeg: angle(fields[3], fields[4])?,
+                    longitude_ddeg: angle(fields[5], fields[6])?,
+                    height_m: None,
+                    quality: None,
+                    num_sv: None,
+                    hdop: None,
+                })
+            },
+            _ => None,
+        }
+    }
+}
+
+/// [ReferenceTrack] is formed from NMEA fixes (with height, GGA), to compare
+/// [PVTSolution]s to another receiver or a reference trajectory.
+#[derive(Debug, Clone, Default)]
+pub struct ReferenceTrack {
+    fixes: BTreeMap<Epoch, NmeaFix>,
+    capacity: Option<usize>,
+}
+
+impl ReferenceTrack {
+    /// Builds new empty [ReferenceTrack]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Copies and returns [ReferenceTrack] that retains at most `capacity` fixes:
+    /// the oldest fixes are dropped first, for continuous real-time comparisons.
+    pub fn with_capacity(&self, capacity: usize) -> Self {
+        let mut s = self.clone();
+        s.capacity = Some(capacity);
+        s
+    }
+    /// Stores new [NmeaFix]. Fixes without height are dropped.
+    pub fn insert(&mut self, fix: NmeaFix) {
+        if fix.height_m.is_some() {
+            self.fixes.insert(fix.t, fix);
+            if let Some(capacity) = self.capacity {
+                while self.fixes.len() > capacity {
+                    self.fixes.pop_first();
+                }
+            }
+        }
+    }
+    /// Returns closest [NmeaFix] within `tolerance` of [Epoch]
+    pub fn fix(&self, t: Epoch, tolerance: Duration) -> Option<&NmeaFix> {
+        let before = self.fixes.range(..=t).next_back();
+        let after = self.fixes.range(t..).next();
+        [before, after]
+            .into_iter()
+            .flatten()
+            .filter(|(t_i, _)| (**t_i - t).abs() <= tolerance)
+            .min_by_key(|(t_i, _)| (**t_i - t).abs())
+            .map(|(_, fix)| fix)
+    }
+    /// Returns the (east, north, up) error [m] of this [PVTSolution] resolved at [Epoch],
+    /// with respect to the closest reference fix within `tolerance`.
+    pub fn error_enu_m(
+        &self,
+        t: Epoch,
+        solution: &PVTSolution,
+        tolerance: Duration,
+    ) -> Option<(f64, f64, f64)> {
+        let fix = self.fix(t, tolerance)?;
+        let (x_m, y_m, z_m) = fix.position_ecef_m()?;
+        let state = solution.state.to_cartesian_pos_vel() * 1.0E3;
+        let enu = ecef_to_enu(
+            Vector3::new(state[0] - x_m, state[1] - y_m, state[2] - z_m),
+            fix.latitude_ddeg.to_radians(),
+            fix.longitude_ddeg.to_radians(),
+        );
+        Some((enu[0], enu[1], enu[2]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{content, NmeaReader, ReferenceTrack};
+    use crate::prelude::{Duration, Epoch, EARTH_ITRF93};
+    use std::str::FromStr;
+
+    const GGA: &str = "$GPGGA,123519.00,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*69";
+    const RMC: &str = "$GPRMC,123519,A,4807.038,N,01131.000,E,022.4,084.4,230394,003.1,W*6A";
+
+    #[test]
+    fn nmea_checksum() {
+        assert!(content(RMC).is_some());
+        assert!(content(&RMC.replace("4807", "4808")).is_none());
+        assert!(content("GPRMC,123519,A*6A").is_none());
+    }
+
+    #[test]
+    fn nmea_gga_rmc() {
+        // GGA is dropped until the date is known
+        let mut reader = NmeaReader::new();
+        assert!(reader.parse(GGA).is_none());
+
+        let rmc = reader.parse(RMC).unwrap();
+        assert_eq!(rmc.t, Epoch::from_str("1994-03-23T12:35:19 UTC").unwrap());
+        assert!((rmc.latitude_ddeg - (48.0 + 7.038 / 60.0)).abs() < 1.0E-9);
+        assert!((rmc.longitude_ddeg - (11.0 + 31.0 / 60.0)).abs() < 1.0E-9);
+        assert!(rmc.height_m.is_none());
+        assert!(rmc.apriori(EARTH_ITRF93).is_none());
+
+        let fix = reader.parse(GGA).unwrap();
+        assert_eq!(fix.t, rmc.t);
+        assert_eq!(fix.quality, Some(1));
+        assert_eq!(fix.num_sv, Some(8));
+        assert_eq!(fix.hdop, Some(0.9));
+        assert!((fix.height_m.unwrap() - 592.3).abs() < 1.0E-9);
+
+        let apriori = fix.apriori(EARTH_ITRF93).unwrap();
+        let pos_km = apriori.to_cartesian_pos_vel();
+        let radius_km = (pos_km[0].powi(2) + pos_km[1].powi(2) + pos_km[2].powi(2)).sqrt();
+        assert!(radius_km > 6_360.0 && radius_km < 6_380.0);
+
+        let mut track = ReferenceTrack::new();
+        track.insert(rmc);
+        track.insert(fix);
+        let tolerance = Duration::from_seconds(0.5);
+        assert!(track
+            .fix(fix.t + Duration::from_seconds(0.2), tolerance)
+            .is_some());
+        assert!(track
+            .fix(fix.t + Duration::from_seconds(1.0), tolerance)
+            .is_none());
+
+        // bounded track: latest fix is retained
+        let mut track = ReferenceTrack::new().with_capacity(1);
+        let mut later = fix;
+        later.t += Duration::from_seconds(10.0);
+        track.insert(fix);
+        track.insert(later);
+        assert!(track.fix(fix.t, tolerance).is_none());
+        assert!(track.fix(later.t, tolerance).is_some());
+
+        // date provided ahead of time
+        let mut reader = NmeaReader::new().with_date(1994, 3, 23);
+        assert_eq!(reader.parse(GGA).unwrap().t, rmc.t);
+    }
+}
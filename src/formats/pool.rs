@@ -0,0 +1,119 @@
+//! Candidate pools formation
+use std::collections::BTreeMap;
+
+use crate::prelude::{Candidate, Carrier, Epoch, Observation, SV};
+
+/// Gathers observations per [Epoch], [SV] and [Carrier]
+#[derive(Default)]
+pub(crate) struct PoolBuilder {
+    pub observations: BTreeMap<(Epoch, SV), Vec<Observation>>,
+}
+
+impl PoolBuilder {
+    /// Stores a new observation described by its RINEX code
+    /// (phase observations in cycles). Unsupported signals are dropped.
+    pub fn add(&mut self, t: Epoch, sv: SV, code: &str, value: f64) {
+        let carrier = match Carrier::from_rinex_code(sv.constellation, code) {
+            Some(carrier) => carrier,
+            None => return,
+        };
+        let kind = match code.chars().next() {
+            Some('P') => 'C',
+            Some(kind) => kind,
+            None => return,
+        };
+        let value = if kind == 'L' {
+            // cycles to meters
+            value * carrier.wavelength()
+        } else {
+            value
+        };
+        self.insert(t, sv, carrier, kind, value);
+    }
+    /// Stores a new observation of given kind: 'C' pseudo range [m], 'L' phase range [m],
+    /// 'D' doppler [Hz] or 'S' SNR [dB-Hz].
+    /// When several tracking codes exist on the same band, the first one wins.
+    pub fn insert(&mut self, t: Epoch, sv: SV, carrier: Carrier, kind: char, value: f64) {
+        let observations = self.observations.entry((t, sv)).or_default();
+        let index = match observations.iter().position(|obs| obs.carrier == carrier) {
+            Some(index) => index,
+            None => {
+                observations.push(Observation {
+                    carrier,
+                    ..Default::default()
+                });
+                observations.len() - 1
+            },
+        };
+
+        let obs = &mut observations[index];
+        match kind {
+            'C' => {
+                obs.pseudo.get_or_insert(value);
+            },
+            'L' => {
+                obs.phase.get_or_insert(value);
+            },
+            'D' => {
+                obs.doppler.get_or_insert(value);
+            },
+            'S' => {
+                obs.snr.get_or_insert(value);
+            },
+            _ => {},
+        }
+    }
+    /// Forms the [Candidate] pools, per [Epoch]
+    pub fn build(self) -> BTreeMap<Epoch, Vec<Candidate>> {
+        let mut pools = BTreeMap::<Epoch, Vec<Candidate>>::new();
+        for ((t, sv), observations) in self.observations {
+            pools
+                .entry(t)
+                .or_default()
+                .push(Candidate::new(sv, t, observations));
+        }
+        pools
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PoolBuilder;
+    use crate::prelude::{Carrier, Constellation, Epoch, SV};
+    use std::str::FromStr;
+
+    #[test]
+    fn pool_builder() {
+        let t = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let (g01, e11) = (
+            SV::new(Constellation::GPS, 1),
+            SV::new(Constellation::Galileo, 11),
+        );
+
+        let mut builder = PoolBuilder::default();
+        builder.add(t, g01, "C1C", 20_000_000.0);
+        builder.add(t, g01, "C1W", 20_000_001.0);
+        builder.add(t, g01, "L1C", 100.0);
+        builder.add(t, g01, "S1C", 45.0);
+        builder.add(t, g01, "P2", 20_000_002.0);
+        builder.add(t, e11, "C5Q", 21_000_000.0);
+        builder.add(t, SV::new(Constellation::Glonass, 1), "C1C", 19_000_000.0);
+
+        let pools = builder.build();
+        let pool = pools.get(&t).unwrap();
+        assert_eq!(pool.len(), 2);
+
+        let g01 = pool.iter().find(|cd| cd.sv == g01).unwrap();
+        assert_eq!(g01.observations.len(), 2);
+        let l1 = &g01.observations[0];
+        assert_eq!(l1.carrier, Carrier::L1);
+        assert_eq!(l1.pseudo, Some(20_000_000.0));
+        assert_eq!(l1.phase, Some(100.0 * Carrier::L1.wavelength()));
+        assert_eq!(l1.snr, Some(45.0));
+        assert_eq!(g01.observations[1].carrier, Carrier::L2);
+        assert_eq!(g01.observations[1].pseudo, Some(20_000_002.0));
+
+        let e11 = pool.iter().find(|cd| cd.sv == e11).unwrap();
+        assert_eq!(e11.observations[0].carrier, Carrier::E5A);
+    }
+}
@@ -0,0 +1,32 @@
+//! RINEX observations adaptor
+use std::{collections::BTreeMap, str::FromStr};
+
+use ::rinex::prelude::Rinex;
+
+use crate::{
+    formats::pool::PoolBuilder,
+    prelude::{Candidate, Epoch, SV},
+};
+
+/// Converts the observations of a RINEX Observation file into [Candidate] pools,
+/// indexed by sampling [Epoch], ready to be proposed to the [Solver].
+/// Observables are mapped to their [Carrier] (see [Carrier::from_rinex_code]),
+/// phase observations are converted to meters and SSI observations are used as SNR.
+/// Epochs that were not flagged as sane are discarded, as well as
+/// unsupported signals. Each [Candidate] still needs to be completed with information
+/// that RINEX observations do not provide (clock corrections, environmental models..).
+pub fn rinex_candidate_pools(rinex: &Rinex) -> BTreeMap<Epoch, Vec<Candidate>> {
+    let mut builder = PoolBuilder::default();
+    for (key, signal) in rinex.signal_observations_iter() {
+        if !key.flag.is_ok() {
+            continue;
+        }
+        // rinex and gnss-rtk may not share identical gnss-rs releases
+        let sv = match SV::from_str(&signal.sv.to_string()) {
+            Ok(sv) => sv,
+            Err(_) => continue,
+        };
+        builder.add(key.epoch, sv, &signal.observable.to_string(), signal.value);
+    }
+    builder.build()
+}
@@ -0,0 +1,541 @@
+//! QZSS CLAS Compact SSR messages (RTCM3 message 4073)
+use crate::logging::warn;
+
+use crate::{
+    formats::rtcm::{resolve_time_of_week, BitReader},
+    prelude::{
+        Carrier, Constellation, Epoch, SsrBias, SsrClockCorrection, SsrOrbitCorrection, SsrOrbits,
+        TimeScale, SV,
+    },
+};
+
+/// RTCM3 message number of Compact SSR messages
+pub(crate) const CSSR_MESSAGE: u16 = 4073;
+
+/// Number of bits of the satellite mask
+const SATELLITE_MASK_BITS: usize = 40;
+
+/// Number of bits of the signal mask
+const SIGNAL_MASK_BITS: usize = 16;
+
+/// Returns ([Constellation], first PRN, IODE bits) of supported GNSS IDs
+fn gnss(id: u64) -> Option<(Constellation, u8, usize)> {
+    match id {
+        0 => Some((Constellation::GPS, 1, 8)),
+        2 => Some((Constellation::Galileo, 1, 10)),
+        4 => Some((Constellation::QZSS, 1, 8)),
+        _ => None,
+    }
+}
+
+/// Converts CSSR signal mask bit to RINEX observable code (band and attribute)
+fn signal_code(constellation: Constellation, id: usize) -> Option<&'static str> {
+    let codes: &[&str] = match constellation {
+        Constellation::GPS => &[
+            "1C", "1P", "1W", "1S", "1L", "1X", "2S", "2L", "2X", "2P", "2W", "5I", "5Q", "5X",
+        ],
+        Constellation::QZSS => &["1C", "1S", "1L", "1X", "2S", "2L", "2X", "5I", "5Q", "5X"],
+        _ => &[
+            "1B", "1C", "1X", "5I", "5Q", "5X", "7I", "7Q", "7X", "8I", "8Q", "8X", "6B", "6C",
+            "6X",
+        ],
+    };
+    codes.get(id).copied()
+}
+
+/// Satellites and signals of one constellation, defined by the mask message
+#[derive(Debug, Clone, Default)]
+struct GnssMask {
+    /// IODE bits
+    iode_bits: usize,
+    /// Satellites in mask order
+    sv: Vec<SV>,
+    /// Signal mask bits in mask order
+    signals: Vec<usize>,
+    /// Signals of each satellite
+    cells: Vec<Vec<usize>>,
+}
+
+/// Compact SSR decoding state: all messages refer to the latest mask message
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CssrState {
+    /// Time of week of the mask [s]
+    tow_s: Option<u64>,
+    /// IOD SSR of the mask
+    iod_ssr: u8,
+    masks: Vec<GnssMask>,
+}
+
+impl CssrState {
+    fn satellites(&self) -> impl Iterator<Item = (&GnssMask, usize)> + '_ {
+        self.masks
+            .iter()
+            .flat_map(|mask| (0..mask.sv.len()).map(move |i| (mask, i)))
+    }
+    fn num_sat(&self) -> usize {
+        self.masks.iter().map(|mask| mask.sv.len()).sum()
+    }
+}
+
+/// [ClasStec] polynomial slant ionospheric correction of one network (subtype 8)
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct ClasStec {
+    /// Reference [Epoch]
+    pub t: Epoch,
+    /// Quality indicator
+    pub quality: u8,
+    /// C00 [TECu], C01, C10 [TECu/°], C11, C02, C20 [TECu/°²] coefficients
+    pub coefficients: [f64; 6],
+}
+
+impl ClasStec {
+    /// Returns slant TEC [TECu], at this latitude and longitude offset [°] from
+    /// the network reference point (CLAS grid definition)
+    pub fn stec_tecu(&self, dlat_deg: f64, dlon_deg: f64) -> f64 {
+        let [c00, c01, c10, c11, c02, c20] = self.coefficients;
+        c00 + c01 * dlat_deg
+            + c10 * dlon_deg
+            + c11 * dlat_deg * dlon_deg
+            + c02 * dlat_deg.powi(2)
+            + c20 * dlon_deg.powi(2)
+    }
+}
+
+/// [ClasMessage] decoded from a single QZSS CLAS Compact SSR message.
+/// Mask messages (subtype 1) carry no correction, they define how the next messages
+/// are decoded. Network corrections (subtypes 8 and 11) only apply to the users
+/// located within that network, see [Self::apply].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClasMessage {
+    /// Compact SSR subtype
+    pub subtype: u8,
+    /// Correction [Epoch]
+    pub t: Epoch,
+    /// Issue of Data SSR
+    pub iod_ssr: u8,
+    /// Network ID, for network corrections
+    pub network_id: Option<u8>,
+    /// Orbit corrections, per [SV]
+    pub orbits: Vec<(SV, SsrOrbitCorrection)>,
+    /// Clock corrections, per [SV]
+    pub clocks: Vec<(SV, SsrClockCorrection)>,
+    /// Code biases, per [SV]. When several tracking codes exist
+    /// on the same band, the first one is retained.
+    pub code_biases: Vec<(SV, Vec<SsrBias>)>,
+    /// Phase biases, per [SV]. When several tracking codes exist
+    /// on the same band, the first one is retained.
+    pub phase_biases: Vec<(SV, Vec<SsrBias>)>,
+    /// Slant ionospheric corrections, per [SV]
+    pub stec: Vec<(SV, ClasStec)>,
+}
+
+/// Stores this bias, unless another tracking code on the same band was already stored
+fn push_bias(biases: &mut Vec<SsrBias>, constellation: Constellation, signal: usize, bias_m: f64) {
+    let carrier = signal_code(constellation, signal)
+        .and_then(|code| Carrier::from_rinex_code(constellation, &format!("C{}", code)));
+    if let Some(carrier) = carrier {
+        if !biases.iter().any(|bias| bias.carrier == carrier) {
+            biases.push(SsrBias { carrier, bias_m });
+        }
+    }
+}
+
+impl ClasMessage {
+    /// Feeds these corrections to the [SsrOrbits] provider, for a user located
+    /// within this CLAS network. Corrections of other networks are ignored.
+    pub fn apply(&self, ssr: &mut SsrOrbits, network_id: u8) {
+        if self.network_id.is_some_and(|id| id != network_id) {
+            return;
+        }
+        for (sv, orbit) in self.orbits.iter() {
+            ssr.insert_orbit_correction(*sv, *orbit);
+        }
+        for (sv, clock) in self.clocks.iter() {
+            ssr.insert_clock_correction(*sv, *clock);
+        }
+        for (sv, biases) in self.code_biases.iter() {
+            ssr.insert_code_biases(*sv, biases.clone());
+        }
+        for (sv, biases) in self.phase_biases.iter() {
+            ssr.insert_phase_biases(*sv, biases.clone());
+        }
+    }
+    /// Returns [ClasStec] of this [SV], for a user located within this CLAS network
+    pub fn stec(&self, sv: SV, network_id: u8) -> Option<&ClasStec> {
+        if self.network_id != Some(network_id) {
+            return None;
+        }
+        self.stec
+            .iter()
+            .find(|(stec_sv, _)| *stec_sv == sv)
+            .map(|(_, stec)| stec)
+    }
+    fn decode_mask(reader: &mut BitReader, state: &mut CssrState) -> Option<u64> {
+        let tow_s = reader.u(20)?;
+        // update interval, multiple message
+        reader.skip(4 + 1)?;
+        let iod_ssr = reader.u(4)? as u8;
+        let num_gnss = reader.u(4)?;
+
+        let mut masks = Vec::new();
+        for _ in 0..num_gnss {
+            let gnss_id = reader.u(4)?;
+            let (constellation, first_prn, iode_bits) = match gnss(gnss_id) {
+                Some(gnss) => gnss,
+                None => {
+                    warn!("cssr: unsupported gnss id {}", gnss_id);
+                    return None;
+                },
+            };
+            let sat_mask = reader.u(SATELLITE_MASK_BITS)?;
+            let sig_mask = reader.u(SIGNAL_MASK_BITS)?;
+            let sv = (0..SATELLITE_MASK_BITS)
+                .filter(|i| sat_mask & (1 << (SATELLITE_MASK_BITS - 1 - i)) != 0)
+                .map(|i| SV::new(constellation, first_prn + i as u8))
+                .collect::<Vec<_>>();
+            let signals = (0..SIGNAL_MASK_BITS)
+                .filter(|i| sig_mask & (1 << (SIGNAL_MASK_BITS - 1 - i)) != 0)
+                .collect::<Vec<_>>();
+            let cells = if reader.u(1)? == 1 {
+                let mut cells = Vec::with_capacity(sv.len());
+                for _ in 0..sv.len() {
+                    let mut sv_signals = Vec::new();
+                    for signal in signals.iter() {
+                        if reader.u(1)? == 1 {
+                            sv_signals.push(*signal);
+                        }
+                    }
+                    cells.push(sv_signals);
+                }
+                cells
+            } else {
+                vec![signals.clone(); sv.len()]
+            };
+            masks.push(GnssMask {
+                iode_bits,
+                sv,
+                signals,
+                cells,
+            });
+        }
+        *state = CssrState {
+            tow_s: Some(tow_s),
+            iod_ssr,
+            masks,
+        };
+        Some(tow_s)
+    }
+    fn decode_orbit(
+        reader: &mut BitReader,
+        t: Epoch,
+        iode_bits: usize,
+    ) -> Option<SsrOrbitCorrection> {
+        let iode = reader.u(iode_bits)? as u16;
+        let radial = reader.i(15)? as f64 * 0.0016;
+        let along = reader.i(13)? as f64 * 0.0064;
+        let cross = reader.i(13)? as f64 * 0.0064;
+        Some(SsrOrbitCorrection {
+            t,
+            iode,
+            delta_m: (radial, along, cross),
+            dot_m_s: (0.0, 0.0, 0.0),
+        })
+    }
+    fn decode_clock(reader: &mut BitReader, t: Epoch) -> Option<SsrClockCorrection> {
+        Some(SsrClockCorrection {
+            t,
+            c0_m: reader.i(15)? as f64 * 0.0016,
+            ..Default::default()
+        })
+    }
+    /// Reads a network ID and its satellite mask (over all mask satellites)
+    fn decode_network(reader: &mut BitReader, state: &CssrState) -> Option<(u8, Vec<bool>)> {
+        let network_id = reader.u(5)? as u8;
+        let mut selected = Vec::with_capacity(state.num_sat());
+        for _ in 0..state.num_sat() {
+            selected.push(reader.u(1)? == 1);
+        }
+        Some((network_id, selected))
+    }
+    pub(crate) fn decode(
+        reader: &mut BitReader,
+        state: &mut CssrState,
+        t_ref: Epoch,
+    ) -> Option<Self> {
+        let subtype = reader.u(4)? as u8;
+        let tow_s = if subtype == 1 {
+            Self::decode_mask(reader, state)?
+        } else {
+            let mask_tow_s = match state.tow_s {
+                Some(tow_s) => tow_s,
+                None => {
+                    warn!("cssr: subtype {} received prior mask", subtype);
+                    return None;
+                },
+            };
+            let hourly_s = reader.u(12)?;
+            // update interval, multiple message
+            reader.skip(4 + 1)?;
+            let iod_ssr = reader.u(4)? as u8;
+            if iod_ssr != state.iod_ssr {
+                warn!("cssr: iod ssr {} does not match mask", iod_ssr);
+                return None;
+            }
+            let mut tow_s = (mask_tow_s / 3600) * 3600 + hourly_s;
+            if tow_s + 1800 < mask_tow_s {
+                tow_s += 3600;
+            } else if tow_s > mask_tow_s + 1800 {
+                tow_s = tow_s.saturating_sub(3600);
+            }
+            tow_s
+        };
+
+        let t = resolve_time_of_week(t_ref, tow_s * 1000, TimeScale::GPST);
+        let mut message = Self {
+            subtype,
+            t,
+            iod_ssr: state.iod_ssr,
+            ..Default::default()
+        };
+
+        match subtype {
+            1 => {},
+            2 => {
+                for (mask, i) in state.satellites() {
+                    let orbit = Self::decode_orbit(reader, t, mask.iode_bits)?;
+                    message.orbits.push((mask.sv[i], orbit));
+                }
+            },
+            3 => {
+                for (mask, i) in state.satellites() {
+                    message
+                        .clocks
+                        .push((mask.sv[i], Self::decode_clock(reader, t)?));
+                }
+            },
+            4 | 5 => {
+                for (mask, i) in state.satellites() {
+                    let sv = mask.sv[i];
+                    let mut biases = Vec::new();
+                    for signal in mask.cells[i].iter() {
+                        let bias_m = if subtype == 4 {
+                            reader.i(11)? as f64 * 0.02
+                        } else {
+                            let bias_m = reader.i(15)? as f64 * 0.001;
+                            // discontinuity indicator
+                            reader.skip(2)?;
+                            bias_m
+                        };
+                        push_bias(&mut biases, sv.constellation, *signal, bias_m);
+                    }
+                    if subtype == 4 {
+                        message.code_biases.push((sv, biases));
+                    } else {
+                        message.phase_biases.push((sv, biases));
+                    }
+                }
+            },
+            8 => {
+                let stec_type = reader.u(2)?;
+                let (network_id, selected) = Self::decode_network(reader, state)?;
+                message.network_id = Some(network_id);
+                for ((mask, i), _) in state
+                    .satellites()
+                    .zip(selected.iter())
+                    .filter(|(_, selected)| **selected)
+                {
+                    let quality = reader.u(6)? as u8;
+                    let mut coefficients = [0.0; 6];
+                    coefficients[0] = reader.i(14)? as f64 * 0.05;
+                    if stec_type >= 1 {
+                        coefficients[1] = reader.i(12)? as f64 * 0.02;
+                        coefficients[2] = reader.i(12)? as f64 * 0.02;
+                    }
+                    if stec_type >= 2 {
+                        coefficients[3] = reader.i(10)? as f64 * 0.02;
+                    }
+                    if stec_type >= 3 {
+                        coefficients[4] = reader.i(8)? as f64 * 0.005;
+                        coefficients[5] = reader.i(8)? as f64 * 0.005;
+                    }
+                    message.stec.push((
+                        mask.sv[i],
+                        ClasStec {
+                            t,
+                            quality,
+                            coefficients,
+                        },
+                    ));
+                }
+            },
+            11 => {
+                let has_orbit = reader.u(1)? == 1;
+                let has_clock = reader.u(1)? == 1;
+                let selected = if reader.u(1)? == 1 {
+                    let (network_id, selected) = Self::decode_network(reader, state)?;
+                    message.network_id = Some(network_id);
+                    selected
+                } else {
+                    vec![true; state.num_sat()]
+                };
+                for ((mask, i), _) in state
+                    .satellites()
+                    .zip(selected.iter())
+                    .filter(|(_, selected)| **selected)
+                {
+                    let sv = mask.sv[i];
+                    if has_orbit {
+                        let orbit = Self::decode_orbit(reader, t, mask.iode_bits)?;
+                        message.orbits.push((sv, orbit));
+                    }
+                    if has_clock {
+                        message.clocks.push((sv, Self::decode_clock(reader, t)?));
+                    }
+                }
+            },
+            _ => return None,
+        }
+        Some(message)
+    }
+    /// Returns true if this Compact SSR subtype is supported
+    pub(crate) fn is_supported(subtype: u8) -> bool {
+        matches!(subtype, 1..=5 | 8 | 11)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        formats::rtcm::{BitWriter, RtcmDecoder, RtcmMessage},
+        prelude::{BroadcastOrbits, Carrier, Constellation, Duration, Epoch, SsrOrbits, SV},
+    };
+    use std::str::FromStr;
+
+    fn header(msg: &mut BitWriter, subtype: i64, hourly_s: i64) {
+        msg.push(4073, 12);
+        msg.push(subtype, 4);
+        msg.push(hourly_s, 12);
+        msg.push(0, 4);
+        msg.push(0, 1);
+        msg.push(2, 4);
+    }
+
+    fn decode(decoder: &mut RtcmDecoder, msg: &BitWriter) -> super::ClasMessage {
+        match &decoder.decode(&msg.frame())[..] {
+            [RtcmMessage::Clas(clas)] => clas.clone(),
+            messages => panic!("unexpected messages {:?}", messages),
+        }
+    }
+
+    #[test]
+    fn clas_compact_ssr() {
+        let t_ref = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let tow_s = 4 * 86_400 + 3_600;
+        let mut decoder = RtcmDecoder::new(t_ref);
+
+        // messages prior mask are dropped
+        let mut msg = BitWriter::default();
+        header(&mut msg, 3, 30);
+        msg.push(0, 15);
+        assert!(decoder.decode(&msg.frame()).is_empty());
+
+        // mask: G05, G12 and J02 (L1 C/A, L2C(L)), no cell mask
+        let mut msg = BitWriter::default();
+        msg.push(4073, 12);
+        msg.push(1, 4);
+        msg.push(tow_s, 20);
+        msg.push(0, 4);
+        msg.push(0, 1);
+        msg.push(2, 4);
+        msg.push(2, 4);
+        msg.push(0, 4);
+        msg.push((1 << 35) | (1 << 28), 40);
+        msg.push((1 << 15) | (1 << 8), 16);
+        msg.push(0, 1);
+        msg.push(4, 4);
+        msg.push(1 << 38, 40);
+        msg.push(1 << 15, 16);
+        msg.push(0, 1);
+        let mask = decode(&mut decoder, &msg);
+        assert_eq!(mask.subtype, 1);
+        assert_eq!(mask.t, Epoch::from_str("2020-06-25T01:00:00 GPST").unwrap());
+
+        let (g05, g12, j02) = (
+            SV::new(Constellation::GPS, 5),
+            SV::new(Constellation::GPS, 12),
+            SV::new(Constellation::QZSS, 2),
+        );
+
+        // code biases
+        let mut msg = BitWriter::default();
+        header(&mut msg, 4, 30);
+        for bias in [50, -25, 10, 0, 5] {
+            msg.push(bias, 11);
+        }
+        let biases = decode(&mut decoder, &msg);
+        assert_eq!(biases.t, mask.t + Duration::from_seconds(30.0));
+        let (sv, g05_biases) = &biases.code_biases[0];
+        assert_eq!(*sv, g05);
+        assert_eq!(g05_biases[0].carrier, Carrier::L1);
+        assert!((g05_biases[0].bias_m - 1.0).abs() < 1.0E-9);
+        assert_eq!(g05_biases[1].carrier, Carrier::L2);
+        assert!((g05_biases[1].bias_m - -0.5).abs() < 1.0E-9);
+        assert_eq!(biases.code_biases[2].0, j02);
+        assert_eq!(biases.code_biases[2].1.len(), 1);
+
+        // network combined orbit and clock: G12 only
+        let mut msg = BitWriter::default();
+        header(&mut msg, 11, 30);
+        msg.push(1, 1);
+        msg.push(1, 1);
+        msg.push(1, 1);
+        msg.push(7, 5);
+        msg.push(0b010, 3);
+        msg.push(42, 8);
+        msg.push(625, 15);
+        msg.push(-125, 13);
+        msg.push(0, 13);
+        msg.push(-1250, 15);
+        let combined = decode(&mut decoder, &msg);
+        assert_eq!(combined.network_id, Some(7));
+        let (sv, orbit) = combined.orbits[0];
+        assert_eq!(sv, g12);
+        assert_eq!(orbit.iode, 42);
+        assert!((orbit.delta_m.0 - 1.0).abs() < 1.0E-9);
+        assert!((orbit.delta_m.1 - -0.8).abs() < 1.0E-9);
+        assert!((combined.clocks[0].1.c0_m - -2.0).abs() < 1.0E-9);
+
+        // only applies within its network
+        let mut ssr = SsrOrbits::new(BroadcastOrbits::new());
+        combined.apply(&mut ssr, 3);
+        assert!(ssr.orbit_correction(g12, combined.t).is_none());
+        combined.apply(&mut ssr, 7);
+        assert!(ssr.orbit_correction(g12, combined.t).is_some());
+
+        // stec polynomial: J02 only
+        let mut msg = BitWriter::default();
+        header(&mut msg, 8, 30);
+        msg.push(1, 2);
+        msg.push(7, 5);
+        msg.push(0b001, 3);
+        msg.push(10, 6);
+        msg.push(200, 14);
+        msg.push(50, 12);
+        msg.push(-50, 12);
+        let stec = decode(&mut decoder, &msg);
+        assert!(stec.stec(j02, 3).is_none());
+        let j02_stec = stec.stec(j02, 7).unwrap();
+        assert_eq!(j02_stec.quality, 10);
+        assert!((j02_stec.stec_tecu(1.0, 1.0) - 10.0).abs() < 1.0E-9);
+
+        // iod ssr mismatch
+        let mut msg = BitWriter::default();
+        msg.push(4073, 12);
+        msg.push(3, 4);
+        msg.push(30, 12);
+        msg.push(0, 4);
+        msg.push(0, 1);
+        msg.push(3, 4);
+        assert!(decoder.decode(&msg.frame()).is_empty());
+    }
+}
@@ -0,0 +1,353 @@
+//! RTCM3 streams decoding
+use crate::logging::warn;
+
+use crate::prelude::{Duration, Epoch, TimeScale};
+
+mod cssr;
+mod msm;
+mod ssr;
+
+pub use cssr::{ClasMessage, ClasStec};
+pub use msm::MsmObservations;
+pub use ssr::SsrMessage;
+
+use cssr::{CssrState, CSSR_MESSAGE};
+
+/// RTCM3 frame preamble
+const PREAMBLE: u8 = 0xD3;
+
+/// Maximal RTCM3 payload length
+const MAX_PAYLOAD_LEN: usize = 1023;
+
+/// CRC-24Q, as used by RTCM3 frames
+pub(crate) fn crc24q(data: &[u8]) -> u32 {
+    let mut crc = 0_u32;
+    for byte in data {
+        crc ^= (*byte as u32) << 16;
+        for _ in 0..8 {
+            crc <<= 1;
+            if crc & 0x100_0000 != 0 {
+                crc ^= 0x186_4CFB;
+            }
+        }
+    }
+    crc & 0xFF_FFFF
+}
+
+/// MSB first bit reader
+pub(crate) struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+    /// Reads `n` bits (up to 64) as unsigned value
+    pub fn u(&mut self, n: usize) -> Option<u64> {
+        if self.pos + n > self.data.len() * 8 {
+            return None;
+        }
+        let mut value = 0_u64;
+        for _ in 0..n {
+            let bit = (self.data[self.pos / 8] >> (7 - self.pos % 8)) & 0x01;
+            value = (value << 1) | bit as u64;
+            self.pos += 1;
+        }
+        Some(value)
+    }
+    /// Reads `n` bits (up to 64) as two's complement signed value
+    pub fn i(&mut self, n: usize) -> Option<i64> {
+        let value = self.u(n)?;
+        if n < 64 && value & (1 << (n - 1)) != 0 {
+            Some(value as i64 - (1_i64 << n))
+        } else {
+            Some(value as i64)
+        }
+    }
+    /// Skips `n` bits
+    pub fn skip(&mut self, n: usize) -> Option<()> {
+        self.u(n).map(|_| ())
+    }
+}
+
+/// MSB first bit writer, to build test frames
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct BitWriter {
+    bits: Vec<bool>,
+}
+
+#[cfg(test)]
+impl BitWriter {
+    /// Writes the `n` least significant bits of `value`
+    pub fn push(&mut self, value: i64, n: usize) {
+        for i in (0..n).rev() {
+            self.bits.push((value >> i) & 0x01 == 1);
+        }
+    }
+    /// Returns complete RTCM3 frame, with this payload
+    pub fn frame(&self) -> Vec<u8> {
+        let mut payload = self
+            .bits
+            .chunks(8)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .enumerate()
+                    .fold(0_u8, |byte, (i, bit)| byte | ((*bit as u8) << (7 - i)))
+            })
+            .collect::<Vec<_>>();
+        let mut frame = vec![PREAMBLE, (payload.len() >> 8) as u8, payload.len() as u8];
+        frame.append(&mut payload);
+        let crc = crc24q(&frame);
+        frame.extend_from_slice(&[(crc >> 16) as u8, (crc >> 8) as u8, crc as u8]);
+        frame
+    }
+}
+
+/// Resolves the week ambiguity of a time of week (in ms) expressed in [TimeScale],
+/// with the closest [Epoch] to `t_ref`.
+pub(crate) fn resolve_time_of_week(t_ref: Epoch, tow_ms: u64, ts: TimeScale) -> Epoch {
+    let (week, _) = t_ref.to_time_scale(ts).to_time_of_week();
+    let t = Epoch::from_time_of_week(week, tow_ms * 1_000_000, ts);
+    let half_week = Duration::from_seconds(302_400.0);
+    let one_week = Duration::from_seconds(604_800.0);
+    if t - t_ref > half_week {
+        t - one_week
+    } else if t_ref - t > half_week {
+        t + one_week
+    } else {
+        t
+    }
+}
+
+/// [ReferenceStation] description (RTCM 1005/1006 messages)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferenceStation {
+    /// Reference station ID
+    pub station_id: u16,
+    /// Antenna Reference Point, ECEF [m]
+    pub arp_ecef_m: (f64, f64, f64),
+    /// Antenna height above marker [m] (1006 only)
+    pub antenna_height_m: Option<f64>,
+}
+
+impl ReferenceStation {
+    fn decode(msg: u16, reader: &mut BitReader) -> Option<Self> {
+        let station_id = reader.u(12)? as u16;
+        // ITRF realization year, GNSS indicators, station indicator
+        reader.skip(6 + 4)?;
+        let x = reader.i(38)? as f64 * 1.0E-4;
+        // single receiver oscillator, reserved
+        reader.skip(2)?;
+        let y = reader.i(38)? as f64 * 1.0E-4;
+        // quarter cycle indicator
+        reader.skip(2)?;
+        let z = reader.i(38)? as f64 * 1.0E-4;
+        let antenna_height_m = if msg == 1006 {
+            Some(reader.u(16)? as f64 * 1.0E-4)
+        } else {
+            None
+        };
+        Some(Self {
+            station_id,
+            arp_ecef_m: (x, y, z),
+            antenna_height_m,
+        })
+    }
+}
+
+/// Decoded [RtcmMessage]
+#[derive(Debug, Clone)]
+pub enum RtcmMessage {
+    /// MSM4 or MSM7 observations
+    Msm(MsmObservations),
+    /// Reference station description
+    ReferenceStation(ReferenceStation),
+    /// State Space Representation corrections
+    Ssr(SsrMessage),
+    /// QZSS CLAS Compact SSR corrections
+    Clas(ClasMessage),
+    /// Valid frame, but this message is not supported
+    Unsupported(u16),
+}
+
+/// [RtcmDecoder] synchronizes on RTCM3 frames from a byte stream (file, serial port, NTRIP..),
+/// verifies their integrity and decodes the supported messages:
+/// - MSM4 and MSM7 observations, converted to [Candidate]s (rover) or remote observations (base)
+/// - 1005/1006 reference station descriptions
+/// - SSR orbit, clock, code bias and phase bias corrections, to be fed to `SsrOrbits`
+/// - QZSS CLAS Compact SSR corrections (message 4073), to be fed to `SsrOrbits`
+///
+/// MSM messages only carry the time of week: it is resolved with a reference [Epoch],
+/// that the decoder updates with each decoded epoch.
+#[derive(Debug, Clone)]
+pub struct RtcmDecoder {
+    buffer: Vec<u8>,
+    t_ref: Epoch,
+    cssr: CssrState,
+}
+
+impl RtcmDecoder {
+    /// Builds new [RtcmDecoder]. `t_ref` should be within half a week of the stream
+    /// (system time, for example).
+    pub fn new(t_ref: Epoch) -> Self {
+        Self {
+            buffer: Vec::with_capacity(MAX_PAYLOAD_LEN + 6),
+            t_ref,
+            cssr: CssrState::default(),
+        }
+    }
+    /// Decodes all complete frames contained in `bytes` (and previously buffered content).
+    /// Incomplete frames are buffered until the next call.
+    pub fn decode(&mut self, bytes: &[u8]) -> Vec<RtcmMessage> {
+        self.buffer.extend_from_slice(bytes);
+        let mut messages = Vec::new();
+        loop {
+            // synchronize
+            match self.buffer.iter().position(|b| *b == PREAMBLE) {
+                Some(0) => {},
+                Some(offset) => {
+                    self.buffer.drain(..offset);
+                },
+                None => {
+                    self.buffer.clear();
+                    break;
+                },
+            }
+            if self.buffer.len() < 3 {
+                break;
+            }
+            let len = (((self.buffer[1] & 0x03) as usize) << 8) | self.buffer[2] as usize;
+            if self.buffer.len() < len + 6 {
+                break;
+            }
+            let frame = &self.buffer[..len + 3];
+            let crc = ((self.buffer[len + 3] as u32) << 16)
+                | ((self.buffer[len + 4] as u32) << 8)
+                | self.buffer[len + 5] as u32;
+            if crc24q(frame) != crc {
+                warn!("rtcm: crc failure");
+                // resynchronize on next preamble
+                self.buffer.drain(..1);
+                continue;
+            }
+            if let Some(msg) = self.decode_payload(&frame[3..]) {
+                messages.push(msg);
+            }
+            self.buffer.drain(..len + 6);
+        }
+        messages
+    }
+    /// Decodes a single RTCM3 message payload (frame header and CRC excluded)
+    pub fn decode_payload(&mut self, payload: &[u8]) -> Option<RtcmMessage> {
+        let mut reader = BitReader::new(payload);
+        let msg = reader.u(12)? as u16;
+        match msg {
+            1005 | 1006 => Some(RtcmMessage::ReferenceStation(ReferenceStation::decode(
+                msg,
+                &mut reader,
+            )?)),
+            _ if MsmObservations::is_supported(msg) => {
+                match MsmObservations::decode(msg, &mut reader, self.t_ref) {
+                    Some(msm) => {
+                        self.t_ref = msm.t;
+                        Some(RtcmMessage::Msm(msm))
+                    },
+                    None => {
+                        warn!("rtcm: invalid message {}", msg);
+                        None
+                    },
+                }
+            },
+            _ if SsrMessage::is_supported(msg) => {
+                match SsrMessage::decode(msg, &mut reader, self.t_ref) {
+                    Some(ssr) => Some(RtcmMessage::Ssr(ssr)),
+                    None => {
+                        warn!("rtcm: invalid message {}", msg);
+                        None
+                    },
+                }
+            },
+            CSSR_MESSAGE => {
+                let subtype = BitReader::new(payload).u(12 + 4)? as u8 & 0x0F;
+                if !ClasMessage::is_supported(subtype) {
+                    return Some(RtcmMessage::Unsupported(msg));
+                }
+                match ClasMessage::decode(&mut reader, &mut self.cssr, self.t_ref) {
+                    Some(clas) => Some(RtcmMessage::Clas(clas)),
+                    None => {
+                        warn!("rtcm: invalid message {} (subtype {})", msg, subtype);
+                        None
+                    },
+                }
+            },
+            _ => Some(RtcmMessage::Unsupported(msg)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{crc24q, BitReader, ReferenceStation, RtcmDecoder, RtcmMessage};
+    use crate::prelude::Epoch;
+    use std::str::FromStr;
+
+    /// RTCM 1005 example, from the standard
+    const MSG_1005: [u8; 25] = [
+        0xD3, 0x00, 0x13, 0x3E, 0xD7, 0xD3, 0x02, 0x02, 0x98, 0x0E, 0xDE, 0xEF, 0x34, 0xB4, 0xBD,
+        0x62, 0xAC, 0x09, 0x41, 0x98, 0x6F, 0x33, 0x36, 0x0B, 0x98,
+    ];
+
+    #[test]
+    fn rtcm_crc24q() {
+        assert_eq!(crc24q(&MSG_1005[..22]), 0x36_0B98);
+    }
+
+    #[test]
+    fn bit_reader() {
+        let data = [0b1010_0000, 0xFF];
+        let mut reader = BitReader::new(&data);
+        assert_eq!(reader.u(3), Some(0b101));
+        assert_eq!(reader.i(5), Some(0));
+        assert_eq!(reader.i(4), Some(-1));
+        assert_eq!(reader.i(4), Some(-1));
+        assert_eq!(reader.u(1), None);
+    }
+
+    #[test]
+    fn reference_station() {
+        let t = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let mut decoder = RtcmDecoder::new(t);
+
+        // garbage, frame split in two, garbage
+        let mut messages = decoder.decode(&[0x00, 0x12]);
+        messages.extend(decoder.decode(&MSG_1005[..10]));
+        assert!(messages.is_empty());
+        messages.extend(decoder.decode(&MSG_1005[10..]));
+        messages.extend(decoder.decode(&[0x00]));
+        assert_eq!(messages.len(), 1);
+
+        match messages[0] {
+            RtcmMessage::ReferenceStation(ReferenceStation {
+                station_id,
+                arp_ecef_m,
+                antenna_height_m,
+            }) => {
+                assert_eq!(station_id, 2003);
+                assert!((arp_ecef_m.0 - 1_114_104.5999).abs() < 1.0E-6);
+                assert!((arp_ecef_m.1 - -4_850_729.7108).abs() < 1.0E-6);
+                assert!((arp_ecef_m.2 - 3_975_521.4643).abs() < 1.0E-6);
+                assert!(antenna_height_m.is_none());
+            },
+            ref msg => panic!("unexpected message {:?}", msg),
+        }
+
+        // corrupted frame
+        let mut corrupted = MSG_1005;
+        corrupted[10] ^= 0x01;
+        assert!(decoder.decode(&corrupted).is_empty());
+    }
+}
@@ -0,0 +1,385 @@
+//! RTCM3 Multiple Signal Messages (MSM4, MSM7)
+use std::collections::BTreeMap;
+
+use crate::{
+    constants::SPEED_OF_LIGHT_M_S,
+    formats::{
+        pool::PoolBuilder,
+        rtcm::{resolve_time_of_week, BitReader},
+    },
+    prelude::{Candidate, Carrier, Constellation, Epoch, Observation, TimeScale, SV},
+};
+
+/// Light distance in one millisecond [m]
+const RANGE_MS_M: f64 = SPEED_OF_LIGHT_M_S * 1.0E-3;
+
+/// Returns ([Constellation], MSM type) of supported MSM messages
+fn msm_type(msg: u16) -> Option<(Constellation, u8)> {
+    let constellation = match msg / 10 {
+        107 => Constellation::GPS,
+        109 => Constellation::Galileo,
+        110 => Constellation::SBAS,
+        111 => Constellation::QZSS,
+        112 => Constellation::BeiDou,
+        _ => return None,
+    };
+    match msg % 10 {
+        4 => Some((constellation, 4)),
+        7 => Some((constellation, 7)),
+        _ => None,
+    }
+}
+
+/// Converts MSM signal ID to RINEX observable code (band and attribute)
+fn signal_code(constellation: Constellation, id: usize) -> Option<&'static str> {
+    let code = match constellation {
+        Constellation::GPS => match id {
+            2 => "1C",
+            3 => "1P",
+            4 => "1W",
+            8 => "2C",
+            9 => "2P",
+            10 => "2W",
+            15 => "2S",
+            16 => "2L",
+            17 => "2X",
+            22 => "5I",
+            23 => "5Q",
+            24 => "5X",
+            30 => "1S",
+            31 => "1L",
+            32 => "1X",
+            _ => return None,
+        },
+        Constellation::Galileo => match id {
+            2 => "1C",
+            3 => "1A",
+            4 => "1B",
+            5 => "1X",
+            6 => "1Z",
+            8 => "6C",
+            9 => "6A",
+            10 => "6B",
+            11 => "6X",
+            12 => "6Z",
+            14 => "7I",
+            15 => "7Q",
+            16 => "7X",
+            18 => "8I",
+            19 => "8Q",
+            20 => "8X",
+            22 => "5I",
+            23 => "5Q",
+            24 => "5X",
+            _ => return None,
+        },
+        Constellation::BeiDou => match id {
+            2 => "2I",
+            3 => "2Q",
+            4 => "2X",
+            8 => "6I",
+            9 => "6Q",
+            10 => "6X",
+            14 => "7I",
+            15 => "7Q",
+            16 => "7X",
+            22 => "5D",
+            23 => "5P",
+            24 => "5X",
+            25 => "7D",
+            30 => "1D",
+            31 => "1P",
+            32 => "1X",
+            _ => return None,
+        },
+        Constellation::QZSS => match id {
+            2 => "1C",
+            9 => "6S",
+            10 => "6L",
+            11 => "6X",
+            15 => "2S",
+            16 => "2L",
+            17 => "2X",
+            22 => "5I",
+            23 => "5Q",
+            24 => "5X",
+            30 => "1S",
+            31 => "1L",
+            32 => "1X",
+            _ => return None,
+        },
+        _ => match id {
+            2 => "1C",
+            22 => "5I",
+            23 => "5Q",
+            24 => "5X",
+            _ => return None,
+        },
+    };
+    Some(code)
+}
+
+/// [MsmObservations] decoded from a single MSM message (one constellation)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MsmObservations {
+    /// Reference station ID
+    pub station_id: u16,
+    /// Sampling [Epoch]
+    pub t: Epoch,
+    /// [Constellation] described by this message
+    pub constellation: Constellation,
+    /// More MSM messages (other constellations) follow for this [Epoch]
+    pub multiple_message: bool,
+    /// Observations (pseudo range and phase range in meters,
+    /// doppler in Hz, SNR in dB-Hz), per [SV]
+    pub observations: BTreeMap<SV, Vec<Observation>>,
+}
+
+impl MsmObservations {
+    /// Returns true if this message is a supported MSM
+    pub(crate) fn is_supported(msg: u16) -> bool {
+        msm_type(msg).is_some()
+    }
+    /// Converts to [Candidate]s, to be proposed to the `Solver` (rover stream)
+    pub fn candidates(&self) -> Vec<Candidate> {
+        self.observations
+            .iter()
+            .map(|(sv, observations)| Candidate::new(*sv, self.t, observations.clone()))
+            .collect()
+    }
+    /// Returns the observations of this [SV], to be attached
+    /// as remote observations (base stream, see `Candidate::set_remote_observations`).
+    pub fn remote_observations(&self, sv: SV) -> Option<&[Observation]> {
+        self.observations.get(&sv).map(|obs| obs.as_slice())
+    }
+    pub(crate) fn decode(msg: u16, reader: &mut BitReader, t_ref: Epoch) -> Option<Self> {
+        let (constellation, msm) = msm_type(msg)?;
+
+        let station_id = reader.u(12)? as u16;
+        let tow_ms = reader.u(30)?;
+        let multiple_message = reader.u(1)? == 1;
+        // IODS, reserved, clock steering, external clock, smoothing
+        reader.skip(3 + 7 + 2 + 2 + 1 + 3)?;
+
+        let timescale = match constellation {
+            Constellation::BeiDou => TimeScale::BDT,
+            Constellation::Galileo => TimeScale::GST,
+            _ => TimeScale::GPST,
+        };
+        let t = resolve_time_of_week(t_ref, tow_ms, timescale);
+
+        let sat_mask = reader.u(64)?;
+        let sig_mask = reader.u(32)?;
+        let sats = (0..64)
+            .filter(|i| sat_mask & (1 << (63 - i)) != 0)
+            .map(|i| i as u8 + 1)
+            .collect::<Vec<_>>();
+        let sigs = (0..32)
+            .filter(|i| sig_mask & (1 << (31 - i)) != 0)
+            .map(|i| i + 1)
+            .collect::<Vec<usize>>();
+
+        if sats.len() * sigs.len() > 64 {
+            return None;
+        }
+
+        let mut cells = Vec::<(usize, usize)>::new();
+        for sat in 0..sats.len() {
+            for sig in 0..sigs.len() {
+                if reader.u(1)? == 1 {
+                    cells.push((sat, sig));
+                }
+            }
+        }
+
+        // satellite data
+        let mut rough_ms = vec![None; sats.len()];
+        let mut rough_rate = vec![None; sats.len()];
+
+        let int_ms = (0..sats.len())
+            .map(|_| reader.u(8))
+            .collect::<Option<Vec<_>>>()?;
+        if msm == 7 {
+            // extended satellite information
+            reader.skip(4 * sats.len())?;
+        }
+        for (i, int_ms) in int_ms.iter().enumerate() {
+            let modulo = reader.u(10)?;
+            if *int_ms != 255 {
+                rough_ms[i] = Some(*int_ms as f64 + modulo as f64 / 1024.0);
+            }
+        }
+        if msm == 7 {
+            for rate in rough_rate.iter_mut() {
+                let value = reader.i(14)?;
+                if value != -8192 {
+                    *rate = Some(value as f64);
+                }
+            }
+        }
+
+        // signal data
+        let (pr_bits, pr_scale, ph_bits, ph_scale, lock_bits, cnr_bits, cnr_scale) = if msm == 7 {
+            (
+                20,
+                2.0_f64.powi(-29),
+                24,
+                2.0_f64.powi(-31),
+                10,
+                10,
+                2.0_f64.powi(-4),
+            )
+        } else {
+            (15, 2.0_f64.powi(-24), 22, 2.0_f64.powi(-29), 4, 6, 1.0)
+        };
+
+        let fine_pr = (0..cells.len())
+            .map(|_| reader.i(pr_bits))
+            .collect::<Option<Vec<_>>>()?;
+        let fine_ph = (0..cells.len())
+            .map(|_| reader.i(ph_bits))
+            .collect::<Option<Vec<_>>>()?;
+        // lock time and half cycle ambiguity indicators
+        reader.skip((lock_bits + 1) * cells.len())?;
+        let cnr = (0..cells.len())
+            .map(|_| reader.u(cnr_bits))
+            .collect::<Option<Vec<_>>>()?;
+        let fine_rate = if msm == 7 {
+            (0..cells.len())
+                .map(|_| reader.i(15))
+                .collect::<Option<Vec<_>>>()?
+        } else {
+            Vec::new()
+        };
+
+        let mut builder = PoolBuilder::default();
+        for (cell, (sat, sig)) in cells.iter().enumerate() {
+            let prn = match constellation {
+                Constellation::SBAS => sats[*sat] + 19,
+                _ => sats[*sat],
+            };
+            let sv = SV::new(constellation, prn);
+            let code = match signal_code(constellation, sigs[*sig]) {
+                Some(code) => code,
+                None => continue,
+            };
+            let carrier = match Carrier::from_rinex_code(constellation, &format!("C{}", code)) {
+                Some(carrier) => carrier,
+                None => continue,
+            };
+            let rough_ms = match rough_ms[*sat] {
+                Some(rough_ms) => rough_ms,
+                None => continue,
+            };
+
+            if fine_pr[cell] != -(1 << (pr_bits - 1)) {
+                let pr_m = (rough_ms + fine_pr[cell] as f64 * pr_scale) * RANGE_MS_M;
+                builder.insert(t, sv, carrier, 'C', pr_m);
+            }
+            if fine_ph[cell] != -(1 << (ph_bits - 1)) {
+                let ph_m = (rough_ms + fine_ph[cell] as f64 * ph_scale) * RANGE_MS_M;
+                builder.insert(t, sv, carrier, 'L', ph_m);
+            }
+            if cnr[cell] != 0 {
+                builder.insert(t, sv, carrier, 'S', cnr[cell] as f64 * cnr_scale);
+            }
+            if let (Some(rough_rate), Some(fine_rate)) = (rough_rate[*sat], fine_rate.get(cell)) {
+                if *fine_rate != -16384 {
+                    let rate_m_s = rough_rate + *fine_rate as f64 * 1.0E-4;
+                    builder.insert(t, sv, carrier, 'D', -rate_m_s / carrier.wavelength());
+                }
+            }
+        }
+
+        Some(Self {
+            station_id,
+            t,
+            constellation,
+            multiple_message,
+            observations: builder
+                .observations
+                .into_iter()
+                .map(|((_, sv), observations)| (sv, observations))
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RANGE_MS_M;
+    use crate::{
+        formats::rtcm::{BitWriter, RtcmDecoder, RtcmMessage},
+        prelude::{Carrier, Constellation, Epoch, SV},
+    };
+    use std::str::FromStr;
+
+    #[test]
+    fn msm4_decoding() {
+        let t_ref = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let tow_ms = 4 * 86_400_000 + 30_000;
+
+        let mut msg = BitWriter::default();
+        msg.push(1074, 12);
+        msg.push(1234, 12);
+        msg.push(tow_ms, 30);
+        msg.push(0, 1);
+        msg.push(0, 18);
+        // G05 only
+        msg.push(1 << 59, 64);
+        // 1C (2) and 2L (16)
+        msg.push((1 << 30) | (1 << 16), 32);
+        // both cells
+        msg.push(0b11, 2);
+        // rough range: 70.5 ms
+        msg.push(70, 8);
+        msg.push(512, 10);
+        // fine pseudo range
+        msg.push(1024, 15);
+        msg.push(-16384, 15);
+        // fine phase range
+        msg.push(-4096, 22);
+        msg.push(8192, 22);
+        // lock time, half cycle
+        msg.push(0, 10);
+        // CNR
+        msg.push(45, 6);
+        msg.push(38, 6);
+
+        let mut decoder = RtcmDecoder::new(t_ref);
+        let messages = decoder.decode(&msg.frame());
+        assert_eq!(messages.len(), 1);
+
+        let msm = match &messages[0] {
+            RtcmMessage::Msm(msm) => msm,
+            msg => panic!("unexpected message {:?}", msg),
+        };
+
+        assert_eq!(msm.station_id, 1234);
+        assert_eq!(msm.constellation, Constellation::GPS);
+        assert_eq!(msm.t, Epoch::from_str("2020-06-25T00:00:30 GPST").unwrap());
+
+        let g05 = SV::new(Constellation::GPS, 5);
+        let observations = msm.remote_observations(g05).unwrap();
+        assert_eq!(observations.len(), 2);
+
+        let (l1, l2) = (&observations[0], &observations[1]);
+        assert_eq!(l1.carrier, Carrier::L1);
+        assert_eq!(l2.carrier, Carrier::L2);
+
+        let pr_m = (70.5 + 1024.0 * 2.0_f64.powi(-24)) * RANGE_MS_M;
+        assert!((l1.pseudo.unwrap() - pr_m).abs() < 1.0E-6);
+        assert!(l2.pseudo.is_none());
+
+        let ph_m = (70.5 - 4096.0 * 2.0_f64.powi(-29)) * RANGE_MS_M;
+        assert!((l1.phase.unwrap() - ph_m).abs() < 1.0E-6);
+
+        assert_eq!(l1.snr, Some(45.0));
+        assert_eq!(l2.snr, Some(38.0));
+        assert!(l1.doppler.is_none());
+
+        let candidates = msm.candidates();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].sv, g05);
+    }
+}
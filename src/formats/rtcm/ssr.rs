@@ -0,0 +1,402 @@
+//! RTCM3 State Space Representation messages
+use crate::{
+    formats::rtcm::{resolve_time_of_week, BitReader},
+    prelude::{
+        Carrier, Constellation, Epoch, SsrBias, SsrClockCorrection, SsrOrbitCorrection, SsrOrbits,
+        TimeScale, SV,
+    },
+};
+
+/// SSR message content
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SsrType {
+    Orbit,
+    Clock,
+    CodeBias,
+    Combined,
+    PhaseBias,
+}
+
+/// Returns ([Constellation], [SsrType]) of supported SSR messages
+fn ssr_type(msg: u16) -> Option<(Constellation, SsrType)> {
+    let (constellation, offset) = match msg {
+        1057..=1060 => (Constellation::GPS, msg - 1057),
+        1240..=1243 => (Constellation::Galileo, msg - 1240),
+        1246..=1249 => (Constellation::QZSS, msg - 1246),
+        1258..=1261 => (Constellation::BeiDou, msg - 1258),
+        1265 => return Some((Constellation::GPS, SsrType::PhaseBias)),
+        1267 => return Some((Constellation::Galileo, SsrType::PhaseBias)),
+        1268 => return Some((Constellation::QZSS, SsrType::PhaseBias)),
+        1270 => return Some((Constellation::BeiDou, SsrType::PhaseBias)),
+        _ => return None,
+    };
+    let ssr = match offset {
+        0 => SsrType::Orbit,
+        1 => SsrType::Clock,
+        2 => SsrType::CodeBias,
+        _ => SsrType::Combined,
+    };
+    Some((constellation, ssr))
+}
+
+/// Returns (satellite ID bits, Issue of Data bits, IOD CRC bits, PRN offset)
+fn satellite_fields(constellation: Constellation) -> (usize, usize, usize, u8) {
+    match constellation {
+        Constellation::Galileo => (6, 10, 0, 0),
+        Constellation::QZSS => (4, 8, 0, 0),
+        Constellation::BeiDou => (6, 10, 24, 1),
+        _ => (6, 8, 0, 0),
+    }
+}
+
+/// Converts SSR signal ID to RINEX observable code (band and attribute)
+fn signal_code(constellation: Constellation, id: u64) -> Option<&'static str> {
+    let code = match constellation {
+        Constellation::GPS => match id {
+            0 => "1C",
+            1 => "1P",
+            2 => "1W",
+            5 => "2C",
+            6 => "2D",
+            7 => "2S",
+            8 => "2L",
+            9 => "2X",
+            10 => "2P",
+            11 => "2W",
+            14 => "5I",
+            15 => "5Q",
+            16 => "5X",
+            17 => "1S",
+            18 => "1L",
+            19 => "1X",
+            _ => return None,
+        },
+        Constellation::Galileo => match id {
+            0 => "1A",
+            1 => "1B",
+            2 => "1C",
+            3 => "1X",
+            4 => "1Z",
+            5 => "5I",
+            6 => "5Q",
+            7 => "5X",
+            8 => "7I",
+            9 => "7Q",
+            10 => "7X",
+            11 => "8I",
+            12 => "8Q",
+            13 => "8X",
+            14 => "6A",
+            15 => "6B",
+            16 => "6C",
+            17 => "6X",
+            18 => "6Z",
+            _ => return None,
+        },
+        Constellation::QZSS => match id {
+            0 => "1C",
+            1 => "1S",
+            2 => "1L",
+            3 => "2S",
+            4 => "2L",
+            5 => "2X",
+            6 => "5I",
+            7 => "5Q",
+            8 => "5X",
+            9 => "6S",
+            10 => "6L",
+            11 => "6X",
+            12 => "1X",
+            _ => return None,
+        },
+        _ => match id {
+            0 => "2I",
+            1 => "2Q",
+            2 => "2X",
+            3 => "6I",
+            4 => "6Q",
+            5 => "6X",
+            6 => "7I",
+            7 => "7Q",
+            8 => "7X",
+            9 => "5D",
+            10 => "5P",
+            11 => "5X",
+            12 => "1D",
+            13 => "1P",
+            14 => "1X",
+            _ => return None,
+        },
+    };
+    Some(code)
+}
+
+/// Stores this bias, unless another tracking code on the same band was already stored
+fn push_bias(biases: &mut Vec<SsrBias>, constellation: Constellation, signal: u64, bias_m: f64) {
+    let carrier = signal_code(constellation, signal)
+        .and_then(|code| Carrier::from_rinex_code(constellation, &format!("C{}", code)));
+    if let Some(carrier) = carrier {
+        if !biases.iter().any(|bias| bias.carrier == carrier) {
+            biases.push(SsrBias { carrier, bias_m });
+        }
+    }
+}
+
+/// [SsrMessage] decoded from a single RTCM3 SSR message (one constellation):
+/// orbit, clock, combined orbit and clock, code biases or phase biases.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SsrMessage {
+    /// Correction [Epoch]
+    pub t: Epoch,
+    /// SSR provider ID
+    pub provider_id: u16,
+    /// SSR solution ID
+    pub solution_id: u8,
+    /// Issue of Data SSR
+    pub iod_ssr: u8,
+    /// More SSR messages follow for this [Epoch]
+    pub multiple_message: bool,
+    /// Orbit corrections, per [SV]
+    pub orbits: Vec<(SV, SsrOrbitCorrection)>,
+    /// Clock corrections, per [SV]
+    pub clocks: Vec<(SV, SsrClockCorrection)>,
+    /// Code biases, per [SV]. When several tracking codes exist
+    /// on the same band, the first one is retained.
+    pub code_biases: Vec<(SV, Vec<SsrBias>)>,
+    /// Phase biases, per [SV]. When several tracking codes exist
+    /// on the same band, the first one is retained.
+    pub phase_biases: Vec<(SV, Vec<SsrBias>)>,
+}
+
+impl SsrMessage {
+    /// Returns true if this message is a supported SSR message
+    pub(crate) fn is_supported(msg: u16) -> bool {
+        ssr_type(msg).is_some()
+    }
+    /// Feeds these corrections to the [SsrOrbits] provider
+    pub fn apply(&self, ssr: &mut SsrOrbits) {
+        for (sv, orbit) in self.orbits.iter() {
+            ssr.insert_orbit_correction(*sv, *orbit);
+        }
+        for (sv, clock) in self.clocks.iter() {
+            ssr.insert_clock_correction(*sv, *clock);
+        }
+        for (sv, biases) in self.code_biases.iter() {
+            ssr.insert_code_biases(*sv, biases.clone());
+        }
+        for (sv, biases) in self.phase_biases.iter() {
+            ssr.insert_phase_biases(*sv, biases.clone());
+        }
+    }
+    fn decode_orbit(
+        reader: &mut BitReader,
+        t: Epoch,
+        iod_bits: usize,
+        crc_bits: usize,
+    ) -> Option<SsrOrbitCorrection> {
+        let iode = reader.u(iod_bits)? as u16;
+        reader.skip(crc_bits)?;
+        let radial = reader.i(22)? as f64 * 1.0E-4;
+        let along = reader.i(20)? as f64 * 4.0E-4;
+        let cross = reader.i(20)? as f64 * 4.0E-4;
+        let dot_radial = reader.i(21)? as f64 * 1.0E-6;
+        let dot_along = reader.i(19)? as f64 * 4.0E-6;
+        let dot_cross = reader.i(19)? as f64 * 4.0E-6;
+        Some(SsrOrbitCorrection {
+            t,
+            iode,
+            delta_m: (radial, along, cross),
+            dot_m_s: (dot_radial, dot_along, dot_cross),
+        })
+    }
+    fn decode_clock(reader: &mut BitReader, t: Epoch) -> Option<SsrClockCorrection> {
+        Some(SsrClockCorrection {
+            t,
+            c0_m: reader.i(22)? as f64 * 1.0E-4,
+            c1_m_s: reader.i(21)? as f64 * 1.0E-6,
+            c2_m_s2: reader.i(27)? as f64 * 2.0E-8,
+        })
+    }
+    pub(crate) fn decode(msg: u16, reader: &mut BitReader, t_ref: Epoch) -> Option<Self> {
+        let (constellation, ssr) = ssr_type(msg)?;
+        let (sat_bits, iod_bits, crc_bits, prn_offset) = satellite_fields(constellation);
+
+        let timescale = match constellation {
+            Constellation::BeiDou => TimeScale::BDT,
+            Constellation::Galileo => TimeScale::GST,
+            _ => TimeScale::GPST,
+        };
+
+        let tow_s = reader.u(20)?;
+        // update interval
+        reader.skip(4)?;
+        let multiple_message = reader.u(1)? == 1;
+        if matches!(ssr, SsrType::Orbit | SsrType::Combined) {
+            // satellite reference datum
+            reader.skip(1)?;
+        }
+        let iod_ssr = reader.u(4)? as u8;
+        let provider_id = reader.u(16)? as u16;
+        let solution_id = reader.u(4)? as u8;
+        if ssr == SsrType::PhaseBias {
+            // dispersive bias and MW consistency indicators
+            reader.skip(2)?;
+        }
+        let num_sat = reader.u(6)?;
+
+        let t = resolve_time_of_week(t_ref, tow_s * 1000, timescale);
+
+        let mut message = Self {
+            t,
+            provider_id,
+            solution_id,
+            iod_ssr,
+            multiple_message,
+            ..Default::default()
+        };
+
+        for _ in 0..num_sat {
+            let sv = SV::new(constellation, reader.u(sat_bits)? as u8 + prn_offset);
+            match ssr {
+                SsrType::Orbit => {
+                    let orbit = Self::decode_orbit(reader, t, iod_bits, crc_bits)?;
+                    message.orbits.push((sv, orbit));
+                },
+                SsrType::Clock => {
+                    message.clocks.push((sv, Self::decode_clock(reader, t)?));
+                },
+                SsrType::Combined => {
+                    let orbit = Self::decode_orbit(reader, t, iod_bits, crc_bits)?;
+                    message.orbits.push((sv, orbit));
+                    message.clocks.push((sv, Self::decode_clock(reader, t)?));
+                },
+                SsrType::CodeBias => {
+                    let mut biases = Vec::new();
+                    for _ in 0..reader.u(5)? {
+                        let signal = reader.u(5)?;
+                        let bias_m = reader.i(14)? as f64 * 1.0E-2;
+                        push_bias(&mut biases, constellation, signal, bias_m);
+                    }
+                    message.code_biases.push((sv, biases));
+                },
+                SsrType::PhaseBias => {
+                    let num_biases = reader.u(5)?;
+                    // yaw angle and rate
+                    reader.skip(9 + 8)?;
+                    let mut biases = Vec::new();
+                    for _ in 0..num_biases {
+                        let signal = reader.u(5)?;
+                        // integer indicator, wide lane integer indicator, discontinuity counter
+                        reader.skip(1 + 2 + 4)?;
+                        let bias_m = reader.i(20)? as f64 * 1.0E-4;
+                        push_bias(&mut biases, constellation, signal, bias_m);
+                    }
+                    message.phase_biases.push((sv, biases));
+                },
+            }
+        }
+        Some(message)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        formats::rtcm::{BitWriter, RtcmDecoder, RtcmMessage},
+        prelude::{Carrier, Constellation, Epoch, SV},
+    };
+    use std::str::FromStr;
+
+    fn header(msg: &mut BitWriter, id: i64, tow_s: i64, orbit: bool, num_sat: i64) {
+        msg.push(id, 12);
+        msg.push(tow_s, 20);
+        msg.push(2, 4);
+        msg.push(0, 1);
+        if orbit {
+            msg.push(0, 1);
+        }
+        msg.push(3, 4);
+        msg.push(7, 16);
+        msg.push(1, 4);
+        msg.push(num_sat, 6);
+    }
+
+    #[test]
+    fn ssr_combined_orbit_clock() {
+        let t_ref = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let tow_s = 4 * 86_400 + 60;
+
+        let mut msg = BitWriter::default();
+        header(&mut msg, 1060, tow_s, true, 1);
+        msg.push(12, 6);
+        msg.push(42, 8);
+        msg.push(10_000, 22);
+        msg.push(-2_500, 20);
+        msg.push(500, 20);
+        msg.push(1_000, 21);
+        msg.push(0, 19);
+        msg.push(-250, 19);
+        msg.push(-30_000, 22);
+        msg.push(100, 21);
+        msg.push(0, 27);
+
+        let mut decoder = RtcmDecoder::new(t_ref);
+        let messages = decoder.decode(&msg.frame());
+        let ssr = match &messages[..] {
+            [RtcmMessage::Ssr(ssr)] => ssr,
+            messages => panic!("unexpected messages {:?}", messages),
+        };
+
+        assert_eq!(ssr.t, Epoch::from_str("2020-06-25T00:01:00 GPST").unwrap());
+        assert_eq!(ssr.provider_id, 7);
+        assert_eq!(ssr.solution_id, 1);
+        assert_eq!(ssr.iod_ssr, 3);
+
+        let g12 = SV::new(Constellation::GPS, 12);
+        let (sv, orbit) = ssr.orbits[0];
+        assert_eq!(sv, g12);
+        assert_eq!(orbit.iode, 42);
+        assert!((orbit.delta_m.0 - 1.0).abs() < 1.0E-9);
+        assert!((orbit.delta_m.1 - -1.0).abs() < 1.0E-9);
+        assert!((orbit.delta_m.2 - 0.2).abs() < 1.0E-9);
+        assert!((orbit.dot_m_s.0 - 1.0E-3).abs() < 1.0E-12);
+        assert!((orbit.dot_m_s.2 - -1.0E-3).abs() < 1.0E-12);
+
+        let (sv, clock) = ssr.clocks[0];
+        assert_eq!(sv, g12);
+        assert!((clock.c0_m - -3.0).abs() < 1.0E-9);
+        assert!((clock.c1_m_s - 1.0E-4).abs() < 1.0E-12);
+    }
+
+    #[test]
+    fn ssr_code_biases() {
+        let t_ref = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+
+        let mut msg = BitWriter::default();
+        header(&mut msg, 1059, 4 * 86_400, false, 1);
+        msg.push(3, 6);
+        msg.push(3, 5);
+        // L1 C/A, L1 P (same band, dropped), L2C(L)
+        msg.push(0, 5);
+        msg.push(-150, 14);
+        msg.push(1, 5);
+        msg.push(20, 14);
+        msg.push(8, 5);
+        msg.push(75, 14);
+
+        let mut decoder = RtcmDecoder::new(t_ref);
+        let messages = decoder.decode(&msg.frame());
+        let ssr = match &messages[..] {
+            [RtcmMessage::Ssr(ssr)] => ssr,
+            messages => panic!("unexpected messages {:?}", messages),
+        };
+
+        let (sv, biases) = &ssr.code_biases[0];
+        assert_eq!(*sv, SV::new(Constellation::GPS, 3));
+        assert_eq!(biases.len(), 2);
+        assert_eq!(biases[0].carrier, Carrier::L1);
+        assert!((biases[0].bias_m - -1.5).abs() < 1.0E-9);
+        assert_eq!(biases[1].carrier, Carrier::L2);
+        assert!((biases[1].bias_m - 0.75).abs() < 1.0E-9);
+    }
+}
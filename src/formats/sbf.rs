@@ -0,0 +1,533 @@
+//! Septentrio Binary Format (SBF) decoding
+use std::{collections::BTreeMap, f64::consts::PI};
+
+use crate::logging::warn;
+
+use crate::{
+    formats::pool::PoolBuilder,
+    orbit::{resolve_week, EphemerisError, KeplerianEphemeris},
+    prelude::{Candidate, Carrier, Constellation, Epoch, Observation, TimeScale, SV},
+};
+
+/// SBF block synchronization characters
+const SYNC: [u8; 2] = [b'$', b'@'];
+
+/// Maximal block length we tolerate
+const MAX_BLOCK_LEN: usize = 65_532;
+
+/// MeasEpoch block number
+const MEAS_EPOCH: u16 = 4027;
+
+/// GPSNav block number
+const GPS_NAV: u16 = 5891;
+
+/// CRC-16 CCITT, as used by SBF blocks
+fn crc16(data: &[u8]) -> u16 {
+    let mut crc = 0_u16;
+    for byte in data {
+        crc ^= (*byte as u16) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+    }
+    crc
+}
+
+fn u16_le(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn u32_le(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+fn f32_le(data: &[u8], offset: usize) -> f64 {
+    f32::from_bits(u32_le(data, offset)) as f64
+}
+
+fn f64_le(data: &[u8], offset: usize) -> f64 {
+    let mut bytes = [0_u8; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    f64::from_le_bytes(bytes)
+}
+
+/// Converts SBF SVID to [SV]
+fn sbf_sv(svid: u8) -> Option<SV> {
+    match svid {
+        1..=37 => Some(SV::new(Constellation::GPS, svid)),
+        71..=106 => Some(SV::new(Constellation::Galileo, svid - 70)),
+        120..=140 => Some(SV::new(Constellation::SBAS, svid - 100)),
+        141..=180 => Some(SV::new(Constellation::BeiDou, svid - 140)),
+        181..=187 => Some(SV::new(Constellation::QZSS, svid - 180)),
+        198..=215 => Some(SV::new(Constellation::SBAS, svid - 57)),
+        223..=245 => Some(SV::new(Constellation::BeiDou, svid - 182)),
+        _ => None,
+    }
+}
+
+/// Converts SBF signal number to RINEX observable code (band and attribute)
+fn signal_code(signal: u8) -> Option<&'static str> {
+    let code = match signal {
+        0 => "1C",
+        1 => "1W",
+        2 => "2W",
+        3 => "2L",
+        4 => "5Q",
+        5 => "1L",
+        6 => "1C",
+        7 => "2L",
+        13 => "1P",
+        14 => "5P",
+        17 => "1C",
+        19 => "6C",
+        20 => "5Q",
+        21 => "7Q",
+        22 => "8Q",
+        24 => "1C",
+        25 => "5I",
+        26 => "5Q",
+        27 => "6L",
+        28 => "2I",
+        29 => "7I",
+        30 => "6I",
+        32 => "1L",
+        33 => "1Z",
+        34 => "7D",
+        _ => return None,
+    };
+    Some(code)
+}
+
+/// Returns signal number, from sub-block Type and ObsInfo fields
+fn signal_number(type_field: u8, obs_info: u8) -> u8 {
+    match type_field & 0x1F {
+        31 => 32 + (obs_info >> 3),
+        signal => signal,
+    }
+}
+
+/// C/N0 [dB-Hz] from its SBF encoding
+fn cn0(value: u8, signal: u8) -> Option<f64> {
+    match value {
+        255 => None,
+        // GPS L1P and L2P are not offset
+        _ if signal == 1 || signal == 2 => Some(value as f64 * 0.25),
+        _ => Some(value as f64 * 0.25 + 10.0),
+    }
+}
+
+/// [SbfObservations] decoded from a single SBF MeasEpoch block
+#[derive(Debug, Clone, PartialEq)]
+pub struct SbfObservations {
+    /// Sampling [Epoch] (receiver time, expressed in GPST)
+    pub t: Epoch,
+    /// Observations (pseudo range and phase range in meters,
+    /// doppler in Hz, SNR in dB-Hz), per [SV]
+    pub observations: BTreeMap<SV, Vec<Observation>>,
+}
+
+impl SbfObservations {
+    /// Converts to [Candidate]s, to be proposed to the `Solver`
+    pub fn candidates(&self) -> Vec<Candidate> {
+        self.observations
+            .iter()
+            .map(|(sv, observations)| Candidate::new(*sv, self.t, observations.clone()))
+            .collect()
+    }
+    fn decode(t: Epoch, block: &[u8]) -> Option<Self> {
+        if block.len() < 20 {
+            return None;
+        }
+        let (n1, sb1_len, sb2_len) = (block[14] as usize, block[15] as usize, block[16] as usize);
+        if sb1_len < 20 || sb2_len < 12 {
+            return None;
+        }
+
+        let mut builder = PoolBuilder::default();
+        let mut offset = 20;
+
+        for _ in 0..n1 {
+            let sb1 = block.get(offset..offset + sb1_len)?;
+            let n2 = sb1[19] as usize;
+            offset += sb1_len;
+
+            let signal = signal_number(sb1[1], sb1[18]);
+            let sv = sbf_sv(sb1[2]);
+
+            let code_m =
+                ((sb1[3] & 0x0F) as u64 * (1 << 32) + u32_le(sb1, 4) as u64) as f64 * 1.0E-3;
+            let doppler_raw = u32_le(sb1, 8) as i32;
+            let doppler_hz = doppler_raw as f64 * 1.0E-4;
+            let carrier_raw = (sb1[14] as i8 as i64) * 65_536 + u16_le(sb1, 12) as i64;
+
+            let main = sv.and_then(|sv| {
+                let code = signal_code(signal)?;
+                let carrier = Carrier::from_rinex_code(sv.constellation, &format!("C{}", code))?;
+                Some((sv, carrier))
+            });
+
+            let code_valid = code_m > 0.0;
+            let doppler_valid = doppler_raw != i32::MIN;
+
+            if let Some((sv, carrier)) = main {
+                if code_valid {
+                    builder.insert(t, sv, carrier, 'C', code_m);
+                    if sb1[14] as i8 != -128 || u16_le(sb1, 12) != 0 {
+                        let phase_cycles =
+                            code_m / carrier.wavelength() + carrier_raw as f64 * 1.0E-3;
+                        builder.insert(t, sv, carrier, 'L', phase_cycles * carrier.wavelength());
+                    }
+                }
+                if doppler_valid {
+                    builder.insert(t, sv, carrier, 'D', doppler_hz);
+                }
+                if let Some(cn0) = cn0(sb1[15], signal) {
+                    builder.insert(t, sv, carrier, 'S', cn0);
+                }
+            }
+
+            for _ in 0..n2 {
+                let sb2 = block.get(offset..offset + sb2_len)?;
+                offset += sb2_len;
+
+                let (sv, main_carrier) = match main {
+                    Some(main) => main,
+                    None => continue,
+                };
+                let signal = signal_number(sb2[0], sb2[5]);
+                let carrier = match signal_code(signal).and_then(|code| {
+                    Carrier::from_rinex_code(sv.constellation, &format!("C{}", code))
+                }) {
+                    Some(carrier) => carrier,
+                    None => continue,
+                };
+
+                // sign extension of the 3 and 5 bits MSB
+                let code_offset_msb = (((sb2[3] & 0x07) << 5) as i8 >> 5) as i64;
+                let doppler_offset_msb = ((sb2[3] & 0xF8) as i8 >> 3) as i64;
+                let code_offset_lsb = u16_le(sb2, 6) as i64;
+                let carrier_lsb = u16_le(sb2, 8);
+                let doppler_offset_lsb = u16_le(sb2, 10) as i64;
+
+                let code_valid = code_valid && !(code_offset_msb == -4 && code_offset_lsb == 0);
+                let code_m = code_m + (code_offset_msb * 65_536 + code_offset_lsb) as f64 * 1.0E-3;
+
+                if code_valid {
+                    builder.insert(t, sv, carrier, 'C', code_m);
+                    if sb2[4] as i8 != -128 || carrier_lsb != 0 {
+                        let carrier_raw = (sb2[4] as i8 as i64) * 65_536 + carrier_lsb as i64;
+                        let phase_cycles =
+                            code_m / carrier.wavelength() + carrier_raw as f64 * 1.0E-3;
+                        builder.insert(t, sv, carrier, 'L', phase_cycles * carrier.wavelength());
+                    }
+                }
+                if doppler_valid && !(doppler_offset_msb == -16 && doppler_offset_lsb == 0) {
+                    let ratio = main_carrier.wavelength() / carrier.wavelength();
+                    let doppler_hz = doppler_hz * ratio
+                        + (doppler_offset_msb * 65_536 + doppler_offset_lsb) as f64 * 1.0E-4;
+                    builder.insert(t, sv, carrier, 'D', doppler_hz);
+                }
+                if let Some(cn0) = cn0(sb2[2], signal) {
+                    builder.insert(t, sv, carrier, 'S', cn0);
+                }
+            }
+        }
+
+        Some(Self {
+            t,
+            observations: builder
+                .observations
+                .into_iter()
+                .map(|((_, sv), observations)| (sv, observations))
+                .collect(),
+        })
+    }
+}
+
+/// Decodes GPSNav block content as [KeplerianEphemeris]
+fn decode_gps_nav(block: &[u8]) -> Option<(SV, KeplerianEphemeris)> {
+    if block.len() < 140 {
+        return None;
+    }
+    let sv = SV::new(Constellation::GPS, block[14]);
+    // week numbers may be truncated (modulo 1024), resolved against the receiver week
+    let wnc = u16_le(block, 12) as u32;
+    let epoch = |week: u16, seconds: u32| {
+        let week = if wnc == u16::MAX as u32 {
+            week as u32
+        } else {
+            resolve_week(week as u32, 1024, wnc)
+        };
+        Epoch::from_time_of_week(week, seconds as u64 * 1_000_000_000, TimeScale::GPST)
+    };
+    let (iode2, iode3) = (block[24] as u16, block[25] as u16);
+    if iode2 != iode3 {
+        warn!(
+            "sbf: {} ephemeris rejected: {}",
+            sv,
+            EphemerisError::SubframeMismatch(iode2, iode3)
+        );
+        return None;
+    }
+    let ephemeris = KeplerianEphemeris {
+        iode: iode2,
+        iodc: Some(u16_le(block, 22)),
+        tgd: f32_le(block, 28),
+        toc: epoch(u16_le(block, 136), u32_le(block, 32)),
+        af2: f32_le(block, 36),
+        af1: f32_le(block, 40),
+        af0: f32_le(block, 44),
+        crs: f32_le(block, 48),
+        delta_n: f32_le(block, 52) * PI,
+        m0: f64_le(block, 56) * PI,
+        cuc: f32_le(block, 64),
+        e: f64_le(block, 68),
+        cus: f32_le(block, 76),
+        sqrt_a: f64_le(block, 80),
+        toe: epoch(u16_le(block, 138), u32_le(block, 88)),
+        cic: f32_le(block, 92),
+        omega0: f64_le(block, 96) * PI,
+        cis: f32_le(block, 104),
+        i0: f64_le(block, 108) * PI,
+        crc: f32_le(block, 116),
+        omega: f64_le(block, 120) * PI,
+        omega_dot: f32_le(block, 128) * PI,
+        idot: f32_le(block, 132) * PI,
+        tgd2: None,
+        galileo_nav: None,
+    };
+    if let Err(e) = ephemeris.verify() {
+        warn!("sbf: {} ephemeris rejected: {}", sv, e);
+        return None;
+    }
+    Some((sv, ephemeris))
+}
+
+/// Decoded [SbfMessage]
+#[derive(Debug, Clone)]
+pub enum SbfMessage {
+    /// MeasEpoch observations
+    MeasEpoch(SbfObservations),
+    /// GPS ephemeris, to be stored in `BroadcastOrbits`
+    GpsNav(SV, KeplerianEphemeris),
+    /// Valid block, but this block number is not supported
+    Unsupported(u16),
+}
+
+/// [SbfDecoder] synchronizes on SBF blocks from a byte stream (file, serial port, IP..),
+/// verifies their integrity and decodes the supported blocks:
+/// - MeasEpoch observations, converted to [Candidate]s
+/// - GPSNav ephemerides
+#[derive(Debug, Clone, Default)]
+pub struct SbfDecoder {
+    buffer: Vec<u8>,
+}
+
+impl SbfDecoder {
+    /// Builds new [SbfDecoder]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Decodes all complete blocks contained in `bytes` (and previously buffered content).
+    /// Incomplete blocks are buffered until the next call.
+    pub fn decode(&mut self, bytes: &[u8]) -> Vec<SbfMessage> {
+        self.buffer.extend_from_slice(bytes);
+        let mut messages = Vec::new();
+        loop {
+            // synchronize
+            match self.buffer.windows(2).position(|w| w == SYNC) {
+                Some(0) => {},
+                Some(offset) => {
+                    self.buffer.drain(..offset);
+                },
+                None => {
+                    // preserve a possible partial sync
+                    let keep = usize::from(self.buffer.last() == Some(&SYNC[0]));
+                    self.buffer.drain(..self.buffer.len() - keep);
+                    break;
+                },
+            }
+            if self.buffer.len() < 8 {
+                break;
+            }
+            let len = u16_le(&self.buffer, 6) as usize;
+            if len < 8 || len % 4 != 0 || len > MAX_BLOCK_LEN {
+                self.buffer.drain(..2);
+                continue;
+            }
+            if self.buffer.len() < len {
+                break;
+            }
+            if crc16(&self.buffer[4..len]) != u16_le(&self.buffer, 2) {
+                warn!("sbf: crc failure");
+                // resynchronize on next sync characters
+                self.buffer.drain(..2);
+                continue;
+            }
+
+            let block = &self.buffer[..len];
+            let number = u16_le(block, 4) & 0x1FFF;
+            let msg = match number {
+                MEAS_EPOCH if block.len() >= 14 => {
+                    let t = Epoch::from_time_of_week(
+                        u16_le(block, 12) as u32,
+                        u32_le(block, 8) as u64 * 1_000_000,
+                        TimeScale::GPST,
+                    );
+                    SbfObservations::decode(t, block).map(SbfMessage::MeasEpoch)
+                },
+                GPS_NAV => decode_gps_nav(block).map(|(sv, eph)| SbfMessage::GpsNav(sv, eph)),
+                MEAS_EPOCH => None,
+                _ => Some(SbfMessage::Unsupported(number)),
+            };
+            match msg {
+                Some(msg) => messages.push(msg),
+                None => warn!("sbf: invalid block {}", number),
+            }
+            self.buffer.drain(..len);
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{crc16, SbfDecoder, SbfMessage, GPS_NAV, MEAS_EPOCH};
+    use crate::prelude::{Carrier, Constellation, Epoch, SV};
+    use std::{f64::consts::PI, str::FromStr};
+
+    /// Forms SBF block (header, padding and CRC) around this body
+    fn block(number: u16, body: &[u8]) -> Vec<u8> {
+        let mut content = number.to_le_bytes().to_vec();
+        let len = (8 + body.len()).div_ceil(4) * 4;
+        content.extend_from_slice(&(len as u16).to_le_bytes());
+        content.extend_from_slice(body);
+        content.resize(len - 4, 0);
+        let crc = crc16(&content);
+        let mut block = vec![b'$', b'@'];
+        block.extend_from_slice(&crc.to_le_bytes());
+        block.append(&mut content);
+        block
+    }
+
+    #[test]
+    fn sbf_crc16() {
+        assert_eq!(crc16(b"123456789"), 0x31C3);
+    }
+
+    #[test]
+    fn sbf_meas_epoch() {
+        let mut body = Vec::new();
+        body.extend_from_slice(&(345_600_000_u32 + 30_000).to_le_bytes());
+        body.extend_from_slice(&2111_u16.to_le_bytes());
+        // N1, SB1Length, SB2Length, flags, clock jumps, reserved
+        body.extend_from_slice(&[1, 20, 12, 0, 0, 0]);
+
+        // type 1: G05 L1CA, PR = 21000 km
+        body.extend_from_slice(&[0, 0, 5, 4]);
+        body.extend_from_slice(&3_820_130_816_u32.to_le_bytes());
+        body.extend_from_slice(&(-12_500_000_i32).to_le_bytes());
+        body.extend_from_slice(&500_u16.to_le_bytes());
+        body.extend_from_slice(&[0, 160]);
+        body.extend_from_slice(&0_u16.to_le_bytes());
+        body.extend_from_slice(&[0, 1]);
+
+        // type 2: L2C, PR + 2m, carrier unknown, doppler offset + 1Hz
+        body.extend_from_slice(&[3, 0, 140, 0, 0x80, 0]);
+        body.extend_from_slice(&2_000_u16.to_le_bytes());
+        body.extend_from_slice(&0_u16.to_le_bytes());
+        body.extend_from_slice(&10_000_u16.to_le_bytes());
+
+        let mut decoder = SbfDecoder::new();
+        let messages = decoder.decode(&block(MEAS_EPOCH, &body));
+        let meas = match &messages[..] {
+            [SbfMessage::MeasEpoch(meas)] => meas,
+            messages => panic!("unexpected messages {:?}", messages),
+        };
+
+        assert_eq!(meas.t, Epoch::from_str("2020-06-25T00:00:30 GPST").unwrap());
+
+        let g05 = SV::new(Constellation::GPS, 5);
+        let observations = meas.observations.get(&g05).unwrap();
+        assert_eq!(observations.len(), 2);
+
+        let (l1, l2) = (&observations[0], &observations[1]);
+        let pr_m = 21_000_000.0;
+
+        assert_eq!(l1.carrier, Carrier::L1);
+        assert!((l1.pseudo.unwrap() - pr_m).abs() < 1.0E-6);
+        assert!((l1.phase.unwrap() - (pr_m + 0.5 * Carrier::L1.wavelength())).abs() < 1.0E-6);
+        assert!((l1.doppler.unwrap() - -1250.0).abs() < 1.0E-6);
+        assert_eq!(l1.snr, Some(50.0));
+
+        assert_eq!(l2.carrier, Carrier::L2);
+        assert!((l2.pseudo.unwrap() - (pr_m + 2.0)).abs() < 1.0E-6);
+        assert!(l2.phase.is_none());
+        let doppler = -1250.0 * Carrier::L1.wavelength() / Carrier::L2.wavelength() + 1.0;
+        assert!((l2.doppler.unwrap() - doppler).abs() < 1.0E-6);
+        assert_eq!(l2.snr, Some(45.0));
+    }
+
+    #[test]
+    fn sbf_gps_nav() {
+        let mut body = vec![0_u8; 132];
+        body[0..4].copy_from_slice(&345_600_000_u32.to_le_bytes());
+        body[4..6].copy_from_slice(&2111_u16.to_le_bytes());
+        body[6] = 12;
+        // IODC, IODE (subframes 2 and 3)
+        body[14..16].copy_from_slice(&(0x200_u16 + 42).to_le_bytes());
+        body[16] = 42;
+        body[17] = 42;
+        body[24..28].copy_from_slice(&345_600_u32.to_le_bytes());
+        body[36..40].copy_from_slice(&(3.0E-5_f32).to_le_bytes());
+        body[48..56].copy_from_slice(&(0.25_f64).to_le_bytes());
+        body[60..68].copy_from_slice(&(0.01_f64).to_le_bytes());
+        body[72..80].copy_from_slice(&(5153.7_f64).to_le_bytes());
+        body[80..84].copy_from_slice(&345_600_u32.to_le_bytes());
+        // full and truncated (modulo 1024) week numbers
+        body[128..130].copy_from_slice(&2111_u16.to_le_bytes());
+        body[130..132].copy_from_slice(&(2111_u16 % 1024).to_le_bytes());
+
+        let mut decoder = SbfDecoder::new();
+        let mut content = vec![b'$'];
+        content.extend(block(GPS_NAV, &body));
+        let messages = decoder.decode(&content);
+
+        let (sv, eph) = match &messages[..] {
+            [SbfMessage::GpsNav(sv, eph)] => (sv, eph),
+            messages => panic!("unexpected messages {:?}", messages),
+        };
+        assert_eq!(*sv, SV::new(Constellation::GPS, 12));
+        assert_eq!(eph.iode, 42);
+        assert!((eph.af0 - 3.0E-5).abs() < 1.0E-12);
+        assert!((eph.m0 - 0.25 * PI).abs() < 1.0E-12);
+        assert_eq!(eph.e, 0.01);
+        assert_eq!(eph.sqrt_a, 5153.7);
+        assert_eq!(
+            eph.toe,
+            Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap()
+        );
+        assert_eq!(eph.toc, eph.toe);
+        assert_eq!(eph.iodc, Some(0x200 + 42));
+
+        // subframes from different issues of data
+        body[17] = 43;
+        let messages = decoder.decode(&block(GPS_NAV, &body));
+        assert!(messages.is_empty());
+
+        // IODC mismatch
+        body[17] = 42;
+        body[14..16].copy_from_slice(&43_u16.to_le_bytes());
+        let messages = decoder.decode(&block(GPS_NAV, &body));
+        assert!(messages.is_empty());
+    }
+}
@@ -0,0 +1,314 @@
+//! Real-time solution streaming, over TCP or UDP
+use std::{
+    io::{ErrorKind as IoErrorKind, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs, UdpSocket},
+    time::Duration as StdDuration,
+};
+
+use crate::coordinates::Ellipsoid;
+use crate::logging::{debug, warn};
+
+use crate::prelude::{Epoch, PVTSolution};
+
+/// Slow TCP clients are dropped, rather than holding the solver
+const WRITE_TIMEOUT: StdDuration = StdDuration::from_millis(500);
+
+/// Formats (d)ddmm.mmmmm angle and its hemisphere, from decimal degrees
+fn nmea_angle(angle_ddeg: f64, degree_digits: usize, hemispheres: (char, char)) -> String {
+    let hemisphere = if angle_ddeg < 0.0 {
+        hemispheres.1
+    } else {
+        hemispheres.0
+    };
+    let angle_ddeg = angle_ddeg.abs();
+    let degrees = angle_ddeg.trunc();
+    let minutes = (angle_ddeg - degrees) * 60.0;
+    format!(
+        "{:0width$}{:08.5},{}",
+        degrees as u32,
+        minutes,
+        hemisphere,
+        width = degree_digits
+    )
+}
+
+/// Output format of the [SolutionStream]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum StreamFormat {
+    /// NMEA GGA sentences
+    #[default]
+    Nmea,
+    /// JSON objects, one per line
+    Json,
+}
+
+impl StreamFormat {
+    /// Formats this [PVTSolution] resolved at [Epoch], including the line terminator
+    pub fn format(&self, t: Epoch, solution: &PVTSolution) -> String {
+        let pos_vel_m = solution.state.to_cartesian_pos_vel() * 1.0E3;
+        let (lat_rad, lon_rad, alt_m) =
+            Ellipsoid::WGS84.ecef_to_geodetic((pos_vel_m[0], pos_vel_m[1], pos_vel_m[2]));
+        match self {
+            Self::Nmea => {
+                let (_, _, _, hh, mm, ss, nanos) = t.to_gregorian_utc();
+                // 6: estimated (clock holdover, position held)
+                let quality = if solution.is_holdover() { 6 } else { 1 };
+                let content = format!(
+                    "GPGGA,{:02}{:02}{:02}.{:02},{},{},{},{:02},{:.1},{:.3},M,0.0,M,,",
+                    hh,
+                    mm,
+                    ss,
+                    nanos / 10_000_000,
+                    nmea_angle(lat_rad.to_degrees(), 2, ('N', 'S')),
+                    nmea_angle(lon_rad.to_degrees(), 3, ('E', 'W')),
+                    quality,
+                    solution.sv.len().min(99),
+                    solution.hdop(lat_rad, lon_rad),
+                    alt_m,
+                );
+                let checksum = content.bytes().fold(0_u8, |ck, byte| ck ^ byte);
+                format!("${}*{:02X}\r\n", content, checksum)
+            },
+            Self::Json => {
+                let sv = solution
+                    .sv
+                    .keys()
+                    .map(|sv| format!("\"{}\"", sv))
+                    .collect::<Vec<_>>()
+                    .join(",");
+                let holdover = match solution.holdover {
+                    Some(holdover) => format!("{}", holdover.to_seconds()),
+                    None => "null".to_string(),
+                };
+                format!(
+                    "{{\"epoch\":\"{}\",\"timescale\":\"{}\",\"x_m\":{},\"y_m\":{},\"z_m\":{},\
+                    \"vx_m_s\":{},\"vy_m_s\":{},\"vz_m_s\":{},\"latitude_ddeg\":{},\
+                    \"longitude_ddeg\":{},\"altitude_m\":{},\"dt_s\":{},\"d_dt_s_s\":{},\
+                    \"gdop\":{},\"pdop\":{},\"tdop\":{},\"sv\":[{}],\"holdover_s\":{}}}\n",
+                    t,
+                    solution.timescale,
+                    pos_vel_m[0],
+                    pos_vel_m[1],
+                    pos_vel_m[2],
+                    pos_vel_m[3],
+                    pos_vel_m[4],
+                    pos_vel_m[5],
+                    lat_rad.to_degrees(),
+                    lon_rad.to_degrees(),
+                    alt_m,
+                    solution.dt.to_seconds(),
+                    solution.d_dt,
+                    solution.gdop(),
+                    solution.pdop(),
+                    solution.tdop(),
+                    sv,
+                    holdover,
+                )
+            },
+        }
+    }
+}
+
+/// Transport of the [SolutionStream]
+#[derive(Debug)]
+enum Transport {
+    /// TCP server, serving all connected clients
+    Tcp {
+        listener: TcpListener,
+        clients: Vec<(SocketAddr, TcpStream)>,
+    },
+    /// UDP datagrams, sent to a single (possibly broadcast) address
+    Udp {
+        socket: UdpSocket,
+        remote: SocketAddr,
+    },
+}
+
+/// [SolutionStream] broadcasts [PVTSolution]s as they are produced,
+/// to feed external displays and loggers in live deployments.
+#[derive(Debug)]
+pub struct SolutionStream {
+    /// Output [StreamFormat]
+    format: StreamFormat,
+    /// Network transport
+    transport: Transport,
+}
+
+impl SolutionStream {
+    /// Serves [PVTSolution]s to all TCP clients connected to this local address.
+    /// Clients are accepted on each [Self::publish], without blocking.
+    pub fn tcp<A: ToSocketAddrs>(local: A, format: StreamFormat) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(local)?;
+        listener.set_nonblocking(true)?;
+        Ok(Self {
+            format,
+            transport: Transport::Tcp {
+                listener,
+                clients: Vec::new(),
+            },
+        })
+    }
+    /// Sends [PVTSolution]s as UDP datagrams to this remote address,
+    /// from an ephemeral local port. Broadcast addresses are supported.
+    pub fn udp<A: ToSocketAddrs>(remote: A, format: StreamFormat) -> std::io::Result<Self> {
+        let remote = remote.to_socket_addrs()?.next().ok_or_else(|| {
+            std::io::Error::new(IoErrorKind::InvalidInput, "invalid remote address")
+        })?;
+        let local = if remote.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        };
+        let socket = UdpSocket::bind(local)?;
+        socket.set_broadcast(true)?;
+        Ok(Self {
+            format,
+            transport: Transport::Udp { socket, remote },
+        })
+    }
+    /// Returns local address of this [SolutionStream]
+    pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
+        match &self.transport {
+            Transport::Tcp { listener, .. } => listener.local_addr(),
+            Transport::Udp { socket, .. } => socket.local_addr(),
+        }
+    }
+    /// Returns the [StreamFormat] in use
+    pub fn format(&self) -> StreamFormat {
+        self.format
+    }
+    /// Publishes this [PVTSolution] resolved at [Epoch]. Disconnected TCP clients are dropped.
+    /// Returns the number of clients (or datagrams) this solution was sent to.
+    pub fn publish(&mut self, t: Epoch, solution: &PVTSolution) -> std::io::Result<usize> {
+        let content = self.format.format(t, solution);
+        match &mut self.transport {
+            Transport::Tcp { listener, clients } => {
+                loop {
+                    match listener.accept() {
+                        Ok((stream, addr)) => {
+                            debug!("stream client {} connected", addr);
+                            stream.set_nonblocking(false)?;
+                            stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+                            stream.set_nodelay(true)?;
+                            clients.push((addr, stream));
+                        },
+                        Err(e) if e.kind() == IoErrorKind::WouldBlock => break,
+                        Err(e) => return Err(e),
+                    }
+                }
+                clients.retain_mut(
+                    |(addr, stream)| match stream.write_all(content.as_bytes()) {
+                        Ok(_) => true,
+                        Err(e) => {
+                            warn!("stream client {} dropped: {}", addr, e);
+                            false
+                        },
+                    },
+                );
+                Ok(clients.len())
+            },
+            Transport::Udp { socket, remote } => {
+                socket.send_to(content.as_bytes(), *remote)?;
+                Ok(1)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SolutionStream, StreamFormat};
+    use crate::{
+        navigation::SVInput,
+        prelude::{Duration, Epoch, Orbit, PVTSolution, TimeScale, EARTH_ITRF93, SV},
+    };
+    use map_3d::{geodetic2ecef, Ellipsoid};
+    use nalgebra::{Matrix4, Vector4};
+    use std::{
+        collections::BTreeMap,
+        io::{BufRead, BufReader},
+        net::{TcpStream, UdpSocket},
+        str::FromStr,
+    };
+
+    fn solution(t: Epoch) -> PVTSolution {
+        let (x_m, y_m, z_m) = geodetic2ecef(
+            (48.0_f64 + 7.038 / 60.0).to_radians(),
+            (-11.0_f64 - 31.0 / 60.0).to_radians(),
+            545.4,
+            Ellipsoid::WGS84,
+        );
+        let mut sv = BTreeMap::new();
+        for prn in [1, 5, 12] {
+            sv.insert(
+                SV::from_str(&format!("G{:02}", prn)).unwrap(),
+                SVInput::default(),
+            );
+        }
+        PVTSolution {
+            state: Orbit::from_position(x_m / 1.0E3, y_m / 1.0E3, z_m / 1.0E3, t, EARTH_ITRF93),
+            frame: Default::default(),
+            timescale: TimeScale::GPST,
+            dt: Duration::from_seconds(1.0E-6),
+            d_dt: 0.0,
+            sv,
+            ambiguities: Default::default(),
+            holdover: None,
+            ggto: None,
+            q: Matrix4::from_diagonal(&Vector4::new(0.5, 0.5, 0.44, 1.0)),
+        }
+    }
+
+    #[test]
+    fn stream_formats() {
+        let t = Epoch::from_str("1994-03-23T12:35:19 UTC").unwrap();
+        let solution = solution(t);
+
+        let gga = StreamFormat::Nmea.format(t, &solution);
+        assert!(gga.starts_with("$GPGGA,123519.00,4807.03800,N,01131.00000,W,1,03,"));
+        assert!(gga.contains(",545.400,M,0.0,M,,*"));
+        assert!(gga.ends_with("\r\n"));
+        let (content, checksum) = gga.trim_end()[1..].split_once('*').unwrap();
+        let expected = content.bytes().fold(0_u8, |ck, byte| ck ^ byte);
+        assert_eq!(u8::from_str_radix(checksum, 16).unwrap(), expected);
+
+        let json = StreamFormat::Json.format(t, &solution);
+        assert!(json.starts_with("{\"epoch\":\"1994-03-23T12:35:19"));
+        assert!(json.contains("\"sv\":[\"G01\",\"G05\",\"G12\"],\"holdover_s\":null}"));
+        assert!(json.ends_with("}\n"));
+    }
+
+    #[test]
+    fn stream_tcp_udp() {
+        let t = Epoch::from_str("1994-03-23T12:35:19 UTC").unwrap();
+        let solution = solution(t);
+
+        let mut stream = SolutionStream::tcp("127.0.0.1:0", StreamFormat::Json).unwrap();
+        let client = TcpStream::connect(stream.local_addr().unwrap()).unwrap();
+        let mut reader = BufReader::new(client);
+
+        // connection may not be accepted right away
+        let mut reached = 0;
+        for _ in 0..100 {
+            reached = stream.publish(t, &solution).unwrap();
+            if reached > 0 {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert_eq!(reached, 1);
+
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert!(line.starts_with("{\"epoch\""));
+
+        let receiver = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let mut stream =
+            SolutionStream::udp(receiver.local_addr().unwrap(), StreamFormat::Nmea).unwrap();
+        assert_eq!(stream.publish(t, &solution).unwrap(), 1);
+
+        let mut buffer = [0_u8; 256];
+        let size = receiver.recv(&mut buffer).unwrap();
+        assert!(buffer[..size].starts_with(b"$GPGGA,123519.00"));
+    }
+}
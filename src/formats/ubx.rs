@@ -0,0 +1,485 @@
+//! u-blox UBX protocol decoding
+use std::collections::BTreeMap;
+
+use crate::coordinates::Ellipsoid;
+use crate::logging::warn;
+
+use crate::{
+    formats::pool::PoolBuilder,
+    prelude::{
+        Candidate, Constellation, Duration, Epoch, Frame, Observation, Orbit, TimeScale, SV,
+    },
+};
+
+/// UBX frame synchronization characters
+const SYNC: [u8; 2] = [0xB5, 0x62];
+
+/// Maximal payload length we tolerate
+const MAX_PAYLOAD_LEN: usize = 8192;
+
+/// RXM-RAWX (class, id)
+const RXM_RAWX: (u8, u8) = (0x02, 0x15);
+
+/// NAV-PVT (class, id)
+const NAV_PVT: (u8, u8) = (0x01, 0x07);
+
+/// 8-bit Fletcher checksum, as used by UBX frames
+fn checksum(data: &[u8]) -> (u8, u8) {
+    data.iter().fold((0_u8, 0_u8), |(ck_a, ck_b), byte| {
+        let ck_a = ck_a.wrapping_add(*byte);
+        (ck_a, ck_b.wrapping_add(ck_a))
+    })
+}
+
+fn u16_le(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn u32_le(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+fn i32_le(data: &[u8], offset: usize) -> i32 {
+    u32_le(data, offset) as i32
+}
+
+fn f32_le(data: &[u8], offset: usize) -> f32 {
+    f32::from_bits(u32_le(data, offset))
+}
+
+fn f64_le(data: &[u8], offset: usize) -> f64 {
+    let mut bytes = [0_u8; 8];
+    bytes.copy_from_slice(&data[offset..offset + 8]);
+    f64::from_le_bytes(bytes)
+}
+
+/// Converts UBX (gnssId, svId) to [SV]
+fn ubx_sv(gnss_id: u8, sv_id: u8) -> Option<SV> {
+    match gnss_id {
+        0 => Some(SV::new(Constellation::GPS, sv_id)),
+        1 if sv_id >= 120 => Some(SV::new(Constellation::SBAS, sv_id - 100)),
+        2 => Some(SV::new(Constellation::Galileo, sv_id)),
+        3 => Some(SV::new(Constellation::BeiDou, sv_id)),
+        5 => Some(SV::new(Constellation::QZSS, sv_id)),
+        _ => None,
+    }
+}
+
+/// Converts UBX signal ID to RINEX observable code (band and attribute)
+fn signal_code(constellation: Constellation, sig_id: u8) -> Option<&'static str> {
+    let code = match constellation {
+        Constellation::GPS => match sig_id {
+            0 => "1C",
+            3 => "2L",
+            4 => "2S",
+            6 => "5I",
+            7 => "5Q",
+            _ => return None,
+        },
+        Constellation::Galileo => match sig_id {
+            0 => "1C",
+            1 => "1B",
+            3 => "5I",
+            4 => "5Q",
+            5 => "7I",
+            6 => "7Q",
+            8 => "6B",
+            9 => "6C",
+            10 => "6A",
+            _ => return None,
+        },
+        Constellation::BeiDou => match sig_id {
+            0 | 1 => "2I",
+            2 | 3 => "7I",
+            4 | 10 => "6I",
+            5 => "1P",
+            6 => "1D",
+            7 => "5P",
+            8 => "5D",
+            _ => return None,
+        },
+        Constellation::QZSS => match sig_id {
+            0 => "1C",
+            1 => "1Z",
+            4 => "2S",
+            5 => "2L",
+            8 => "5I",
+            9 => "5Q",
+            _ => return None,
+        },
+        _ => match sig_id {
+            0 => "1C",
+            _ => return None,
+        },
+    };
+    Some(code)
+}
+
+/// [RawxObservations] decoded from a single UBX-RXM-RAWX message
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawxObservations {
+    /// Sampling [Epoch] (receiver local time, expressed in GPST).
+    pub t: Epoch,
+    /// GPST - UTC leap seconds, when known by the receiver
+    pub leap_seconds: Option<i8>,
+    /// Observations (pseudo range and phase range in meters,
+    /// doppler in Hz, SNR in dB-Hz), per [SV]
+    pub observations: BTreeMap<SV, Vec<Observation>>,
+}
+
+impl RawxObservations {
+    /// Converts to [Candidate]s, to be proposed to the `Solver`
+    pub fn candidates(&self) -> Vec<Candidate> {
+        self.observations
+            .iter()
+            .map(|(sv, observations)| Candidate::new(*sv, self.t, observations.clone()))
+            .collect()
+    }
+    fn decode(payload: &[u8]) -> Option<Self> {
+        if payload.len() < 16 {
+            return None;
+        }
+        let num_meas = payload[11] as usize;
+        if payload.len() < 16 + 32 * num_meas {
+            return None;
+        }
+
+        let tow_s = f64_le(payload, 0);
+        let week = u16_le(payload, 8) as u32;
+        let rec_stat = payload[12];
+
+        let t = Epoch::from_time_of_week(week, (tow_s * 1.0E9).round() as u64, TimeScale::GPST);
+        let leap_seconds = if rec_stat & 0x01 != 0 {
+            Some(payload[10] as i8)
+        } else {
+            None
+        };
+
+        let mut builder = PoolBuilder::default();
+        for meas in payload[16..16 + 32 * num_meas].chunks(32) {
+            let sv = match ubx_sv(meas[20], meas[21]) {
+                Some(sv) => sv,
+                None => continue,
+            };
+            let code = match signal_code(sv.constellation, meas[22]) {
+                Some(code) => code,
+                None => continue,
+            };
+
+            let (cno, trk_stat) = (meas[26], meas[30]);
+            let (pr_valid, cp_valid, half_cycle) = (
+                trk_stat & 0x01 != 0,
+                trk_stat & 0x02 != 0,
+                trk_stat & 0x04 != 0,
+            );
+
+            if pr_valid {
+                builder.add(t, sv, &format!("C{}", code), f64_le(meas, 0));
+            }
+            // phase is only valid once the half cycle ambiguity is resolved
+            if cp_valid && half_cycle {
+                builder.add(t, sv, &format!("L{}", code), f64_le(meas, 8));
+            }
+            builder.add(t, sv, &format!("D{}", code), f32_le(meas, 16) as f64);
+            if cno > 0 {
+                builder.add(t, sv, &format!("S{}", code), cno as f64);
+            }
+        }
+
+        Some(Self {
+            t,
+            leap_seconds,
+            observations: builder
+                .observations
+                .into_iter()
+                .map(|((_, sv), observations)| (sv, observations))
+                .collect(),
+        })
+    }
+}
+
+/// [NavPvt] navigation solution, decoded from UBX-NAV-PVT message.
+/// Used as apriori position, or as reference solution.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NavPvt {
+    /// Solution [Epoch] (UTC, as resolved by the receiver)
+    pub t: Epoch,
+    /// GNSS fix type (0: no fix, 2: 2D, 3: 3D, 4: GNSS + dead reckoning)
+    pub fix_type: u8,
+    /// Number of SV used in the solution
+    pub num_sv: u8,
+    /// Geodetic latitude [ddeg]
+    pub latitude_ddeg: f64,
+    /// Geodetic longitude [ddeg]
+    pub longitude_ddeg: f64,
+    /// Height above ellipsoid [m]
+    pub height_m: f64,
+    /// Horizontal accuracy estimate [m]
+    pub horizontal_accuracy_m: f64,
+    /// Vertical accuracy estimate [m]
+    pub vertical_accuracy_m: f64,
+    /// Velocity (north, east, down) [m/s]
+    pub velocity_ned_m_s: (f64, f64, f64),
+}
+
+impl NavPvt {
+    /// Returns true if this is a valid 3D solution
+    pub fn is_valid(&self) -> bool {
+        self.fix_type == 3 || self.fix_type == 4
+    }
+    /// Returns ECEF position [m]
+    pub fn position_ecef_m(&self) -> (f64, f64, f64) {
+        Ellipsoid::WGS84.geodetic_to_ecef(
+            self.latitude_ddeg.to_radians(),
+            self.longitude_ddeg.to_radians(),
+            self.height_m,
+        )
+    }
+    /// Returns apriori position as [Orbit], to initialize the `Solver`.
+    /// None if this is not a valid 3D solution.
+    pub fn apriori(&self, frame: Frame) -> Option<Orbit> {
+        if !self.is_valid() {
+            return None;
+        }
+        let (x_m, y_m, z_m) = self.position_ecef_m();
+        Some(Orbit::from_position(
+            x_m / 1.0E3,
+            y_m / 1.0E3,
+            z_m / 1.0E3,
+            self.t,
+            frame,
+        ))
+    }
+    fn decode(payload: &[u8]) -> Option<Self> {
+        if payload.len() < 92 {
+            return None;
+        }
+        let nanos = i32_le(payload, 16) as i64;
+        let t = Epoch::from_gregorian(
+            u16_le(payload, 4) as i32,
+            payload[6],
+            payload[7],
+            payload[8],
+            payload[9],
+            payload[10],
+            0,
+            TimeScale::UTC,
+        ) + Duration::from_nanoseconds(nanos as f64);
+
+        Some(Self {
+            t,
+            fix_type: payload[20],
+            num_sv: payload[23],
+            longitude_ddeg: i32_le(payload, 24) as f64 * 1.0E-7,
+            latitude_ddeg: i32_le(payload, 28) as f64 * 1.0E-7,
+            height_m: i32_le(payload, 32) as f64 * 1.0E-3,
+            horizontal_accuracy_m: u32_le(payload, 40) as f64 * 1.0E-3,
+            vertical_accuracy_m: u32_le(payload, 44) as f64 * 1.0E-3,
+            velocity_ned_m_s: (
+                i32_le(payload, 48) as f64 * 1.0E-3,
+                i32_le(payload, 52) as f64 * 1.0E-3,
+                i32_le(payload, 56) as f64 * 1.0E-3,
+            ),
+        })
+    }
+}
+
+/// Decoded [UbxMessage]
+#[derive(Debug, Clone)]
+pub enum UbxMessage {
+    /// Raw measurements
+    Rawx(RawxObservations),
+    /// Navigation solution
+    NavPvt(NavPvt),
+    /// Valid frame, but this (class, id) is not supported
+    Unsupported(u8, u8),
+}
+
+/// [UbxDecoder] synchronizes on UBX frames from a byte stream (file, serial port..),
+/// verifies their integrity and decodes the supported messages:
+/// - UBX-RXM-RAWX raw measurements, converted to [Candidate]s
+/// - UBX-NAV-PVT navigation solutions, to be used as apriori
+#[derive(Debug, Clone, Default)]
+pub struct UbxDecoder {
+    buffer: Vec<u8>,
+}
+
+impl UbxDecoder {
+    /// Builds new [UbxDecoder]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Decodes all complete frames contained in `bytes` (and previously buffered content).
+    /// Incomplete frames are buffered until the next call.
+    pub fn decode(&mut self, bytes: &[u8]) -> Vec<UbxMessage> {
+        self.buffer.extend_from_slice(bytes);
+        let mut messages = Vec::new();
+        loop {
+            // synchronize
+            match self.buffer.windows(2).position(|w| w == SYNC) {
+                Some(0) => {},
+                Some(offset) => {
+                    self.buffer.drain(..offset);
+                },
+                None => {
+                    // preserve a possible partial sync
+                    let keep = usize::from(self.buffer.last() == Some(&SYNC[0]));
+                    self.buffer.drain(..self.buffer.len() - keep);
+                    break;
+                },
+            }
+            if self.buffer.len() < 6 {
+                break;
+            }
+            let len = u16_le(&self.buffer, 4) as usize;
+            if len > MAX_PAYLOAD_LEN {
+                self.buffer.drain(..2);
+                continue;
+            }
+            if self.buffer.len() < len + 8 {
+                break;
+            }
+            let (ck_a, ck_b) = checksum(&self.buffer[2..len + 6]);
+            if (ck_a, ck_b) != (self.buffer[len + 6], self.buffer[len + 7]) {
+                warn!("ubx: checksum failure");
+                // resynchronize on next sync characters
+                self.buffer.drain(..2);
+                continue;
+            }
+            let (class, id) = (self.buffer[2], self.buffer[3]);
+            let payload = &self.buffer[6..len + 6];
+            let msg = match (class, id) {
+                RXM_RAWX => RawxObservations::decode(payload).map(UbxMessage::Rawx),
+                NAV_PVT => NavPvt::decode(payload).map(UbxMessage::NavPvt),
+                _ => Some(UbxMessage::Unsupported(class, id)),
+            };
+            match msg {
+                Some(msg) => messages.push(msg),
+                None => warn!("ubx: invalid message ({:02x}, {:02x})", class, id),
+            }
+            self.buffer.drain(..len + 8);
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{checksum, UbxDecoder, UbxMessage, NAV_PVT, RXM_RAWX};
+    use crate::prelude::{Carrier, Constellation, Epoch, EARTH_ITRF93, SV};
+    use std::str::FromStr;
+
+    fn frame(msg: (u8, u8), payload: &[u8]) -> Vec<u8> {
+        let mut frame = vec![0xB5, 0x62, msg.0, msg.1];
+        frame.extend_from_slice(&(payload.len() as u16).to_le_bytes());
+        frame.extend_from_slice(payload);
+        let (ck_a, ck_b) = checksum(&frame[2..]);
+        frame.extend_from_slice(&[ck_a, ck_b]);
+        frame
+    }
+
+    fn measurement(sv_id: u8, sig_id: u8, pr: f64, cp: f64, trk_stat: u8) -> Vec<u8> {
+        let mut meas = Vec::new();
+        meas.extend_from_slice(&pr.to_le_bytes());
+        meas.extend_from_slice(&cp.to_le_bytes());
+        meas.extend_from_slice(&(-1250.5_f32).to_le_bytes());
+        meas.extend_from_slice(&[0, sv_id, sig_id, 0]);
+        meas.extend_from_slice(&[0, 0, 42, 0, 0, 0, trk_stat, 0]);
+        meas
+    }
+
+    #[test]
+    fn ubx_rawx() {
+        let mut payload = Vec::new();
+        payload.extend_from_slice(&345_600.5_f64.to_le_bytes());
+        payload.extend_from_slice(&2111_u16.to_le_bytes());
+        payload.extend_from_slice(&[18, 2, 0x01, 0x01, 0, 0]);
+        payload.append(&mut measurement(7, 0, 21_000_000.0, 110_000_000.0, 0x07));
+        payload.append(&mut measurement(7, 3, 21_000_005.0, 85_000_000.0, 0x03));
+
+        let mut content = vec![0x00, 0xB5];
+        content.extend(frame(RXM_RAWX, &payload));
+
+        let mut decoder = UbxDecoder::new();
+        let (head, tail) = content.split_at(20);
+        let mut messages = decoder.decode(head);
+        assert!(messages.is_empty());
+        messages.extend(decoder.decode(tail));
+
+        let rawx = match &messages[..] {
+            [UbxMessage::Rawx(rawx)] => rawx,
+            messages => panic!("unexpected messages {:?}", messages),
+        };
+
+        assert_eq!(
+            rawx.t,
+            Epoch::from_str("2020-06-25T00:00:00.5 GPST").unwrap()
+        );
+        assert_eq!(rawx.leap_seconds, Some(18));
+
+        let g07 = SV::new(Constellation::GPS, 7);
+        let observations = rawx.observations.get(&g07).unwrap();
+        assert_eq!(observations.len(), 2);
+
+        let (l1, l2) = (&observations[0], &observations[1]);
+        assert_eq!(l1.carrier, Carrier::L1);
+        assert_eq!(l1.pseudo, Some(21_000_000.0));
+        assert!((l1.phase.unwrap() - 110_000_000.0 * Carrier::L1.wavelength()).abs() < 1.0E-6);
+        assert_eq!(l1.doppler, Some(-1250.5));
+        assert_eq!(l1.snr, Some(42.0));
+
+        // half cycle ambiguity not resolved
+        assert_eq!(l2.carrier, Carrier::L2);
+        assert_eq!(l2.pseudo, Some(21_000_005.0));
+        assert!(l2.phase.is_none());
+
+        assert_eq!(rawx.candidates().len(), 1);
+
+        // corrupted frame
+        let mut corrupted = frame(RXM_RAWX, &payload);
+        corrupted[20] ^= 0x01;
+        assert!(decoder.decode(&corrupted).is_empty());
+    }
+
+    #[test]
+    fn ubx_nav_pvt() {
+        let mut payload = vec![0_u8; 92];
+        payload[4..6].copy_from_slice(&2020_u16.to_le_bytes());
+        payload[6..11].copy_from_slice(&[6, 25, 12, 30, 15]);
+        payload[20] = 3;
+        payload[23] = 12;
+        payload[24..28].copy_from_slice(&(23_000_000_i32).to_le_bytes());
+        payload[28..32].copy_from_slice(&(450_000_000_i32).to_le_bytes());
+        payload[32..36].copy_from_slice(&(150_000_i32).to_le_bytes());
+        payload[40..44].copy_from_slice(&(2_500_u32).to_le_bytes());
+
+        let mut decoder = UbxDecoder::new();
+        let messages = decoder.decode(&frame(NAV_PVT, &payload));
+        let pvt = match &messages[..] {
+            [UbxMessage::NavPvt(pvt)] => pvt,
+            messages => panic!("unexpected messages {:?}", messages),
+        };
+
+        assert_eq!(pvt.t, Epoch::from_str("2020-06-25T12:30:15 UTC").unwrap());
+        assert!(pvt.is_valid());
+        assert_eq!(pvt.num_sv, 12);
+        assert!((pvt.latitude_ddeg - 45.0).abs() < 1.0E-9);
+        assert!((pvt.longitude_ddeg - 2.3).abs() < 1.0E-9);
+        assert!((pvt.height_m - 150.0).abs() < 1.0E-9);
+        assert!((pvt.horizontal_accuracy_m - 2.5).abs() < 1.0E-9);
+
+        let apriori = pvt.apriori(EARTH_ITRF93).unwrap();
+        let pos_km = apriori.to_cartesian_pos_vel();
+        let radius_km = (pos_km[0].powi(2) + pos_km[1].powi(2) + pos_km[2].powi(2)).sqrt();
+        assert!(radius_km > 6_360.0 && radius_km < 6_380.0);
+
+        let mut invalid = *pvt;
+        invalid.fix_type = 0;
+        assert!(invalid.apriori(EARTH_ITRF93).is_none());
+    }
+}
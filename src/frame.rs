@@ -0,0 +1,308 @@
+//! Terrestrial reference frames (realizations) and Helmert transformations
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::prelude::{Duration, Epoch};
+
+/// Milliarcseconds to radians
+const MAS_TO_RAD: f64 = std::f64::consts::PI / 180.0 / 3600.0 / 1.0E3;
+
+/// Returns this [Epoch] as decimal year
+fn decimal_year(t: Epoch) -> f64 {
+    let j2000 = Epoch::from_gregorian_utc_at_noon(2000, 1, 1);
+    2000.0 + (t - j2000).to_seconds() / Duration::from_days(365.25).to_seconds()
+}
+
+/// [Helmert] 14 parameters (7 parameters and their rates) similarity transformation,
+/// following the IERS conventions:
+/// X' = X + T + D·X + R·X, each parameter P(t) = P + Ṗ (t - t0).
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Helmert {
+    /// Translation (x, y, z) [mm]
+    pub translation_mm: (f64, f64, f64),
+    /// Scale factor [ppb]
+    pub scale_ppb: f64,
+    /// Rotation (x, y, z) [mas]
+    pub rotation_mas: (f64, f64, f64),
+    /// Translation rate [mm/yr]
+    pub translation_rate_mm_yr: (f64, f64, f64),
+    /// Scale factor rate [ppb/yr]
+    pub scale_rate_ppb_yr: f64,
+    /// Rotation rate [mas/yr]
+    pub rotation_rate_mas_yr: (f64, f64, f64),
+    /// Reference epoch of the parameters, in decimal years
+    pub epoch_year: f64,
+}
+
+impl Helmert {
+    /// Builds new 7 parameters (static) [Helmert] transformation
+    pub fn new(
+        translation_mm: (f64, f64, f64),
+        scale_ppb: f64,
+        rotation_mas: (f64, f64, f64),
+    ) -> Self {
+        Self {
+            translation_mm,
+            scale_ppb,
+            rotation_mas,
+            ..Default::default()
+        }
+    }
+    /// Copies and returns [Helmert] with these parameter rates, defined at this reference epoch
+    /// (decimal years): 14 parameters transformation.
+    pub fn with_rates(
+        &self,
+        translation_rate_mm_yr: (f64, f64, f64),
+        scale_rate_ppb_yr: f64,
+        rotation_rate_mas_yr: (f64, f64, f64),
+        epoch_year: f64,
+    ) -> Self {
+        let mut s = *self;
+        s.translation_rate_mm_yr = translation_rate_mm_yr;
+        s.scale_rate_ppb_yr = scale_rate_ppb_yr;
+        s.rotation_rate_mas_yr = rotation_rate_mas_yr;
+        s.epoch_year = epoch_year;
+        s
+    }
+    /// Returns the inverse transformation (first order)
+    pub fn inverse(&self) -> Self {
+        let neg = |(x, y, z): (f64, f64, f64)| (-x, -y, -z);
+        Self {
+            translation_mm: neg(self.translation_mm),
+            scale_ppb: -self.scale_ppb,
+            rotation_mas: neg(self.rotation_mas),
+            translation_rate_mm_yr: neg(self.translation_rate_mm_yr),
+            scale_rate_ppb_yr: -self.scale_rate_ppb_yr,
+            rotation_rate_mas_yr: neg(self.rotation_rate_mas_yr),
+            epoch_year: self.epoch_year,
+        }
+    }
+    /// Returns the transformation equivalent to `self` followed by `rhs` (first order)
+    pub fn then(&self, rhs: &Self) -> Self {
+        // static transformations have no reference epoch
+        let epoch_year = if self.epoch_year == 0.0 {
+            rhs.epoch_year
+        } else {
+            self.epoch_year
+        };
+        let dt = if rhs.epoch_year == 0.0 {
+            0.0
+        } else {
+            epoch_year - rhs.epoch_year
+        };
+        let add = |a: (f64, f64, f64), b: (f64, f64, f64), b_rate: (f64, f64, f64)| {
+            (
+                a.0 + b.0 + b_rate.0 * dt,
+                a.1 + b.1 + b_rate.1 * dt,
+                a.2 + b.2 + b_rate.2 * dt,
+            )
+        };
+        let sum = |a: (f64, f64, f64), b: (f64, f64, f64)| (a.0 + b.0, a.1 + b.1, a.2 + b.2);
+        Self {
+            translation_mm: add(
+                self.translation_mm,
+                rhs.translation_mm,
+                rhs.translation_rate_mm_yr,
+            ),
+            scale_ppb: self.scale_ppb + rhs.scale_ppb + rhs.scale_rate_ppb_yr * dt,
+            rotation_mas: add(
+                self.rotation_mas,
+                rhs.rotation_mas,
+                rhs.rotation_rate_mas_yr,
+            ),
+            translation_rate_mm_yr: sum(self.translation_rate_mm_yr, rhs.translation_rate_mm_yr),
+            scale_rate_ppb_yr: self.scale_rate_ppb_yr + rhs.scale_rate_ppb_yr,
+            rotation_rate_mas_yr: sum(self.rotation_rate_mas_yr, rhs.rotation_rate_mas_yr),
+            epoch_year,
+        }
+    }
+    /// Transforms this ECEF position [m], at this [Epoch]
+    pub fn apply(&self, position_m: (f64, f64, f64), t: Epoch) -> (f64, f64, f64) {
+        let dt = if self.epoch_year == 0.0 {
+            0.0
+        } else {
+            decimal_year(t) - self.epoch_year
+        };
+        let t_m = (
+            (self.translation_mm.0 + self.translation_rate_mm_yr.0 * dt) * 1.0E-3,
+            (self.translation_mm.1 + self.translation_rate_mm_yr.1 * dt) * 1.0E-3,
+            (self.translation_mm.2 + self.translation_rate_mm_yr.2 * dt) * 1.0E-3,
+        );
+        let d = (self.scale_ppb + self.scale_rate_ppb_yr * dt) * 1.0E-9;
+        let r = (
+            (self.rotation_mas.0 + self.rotation_rate_mas_yr.0 * dt) * MAS_TO_RAD,
+            (self.rotation_mas.1 + self.rotation_rate_mas_yr.1 * dt) * MAS_TO_RAD,
+            (self.rotation_mas.2 + self.rotation_rate_mas_yr.2 * dt) * MAS_TO_RAD,
+        );
+        let (x, y, z) = position_m;
+        (
+            x + t_m.0 + d * x - r.2 * y + r.1 * z,
+            y + t_m.1 + r.2 * x + d * y - r.0 * z,
+            z + t_m.2 - r.1 * x + r.0 * y + d * z,
+        )
+    }
+}
+
+/// [DatumTransformation] of the solutions, to a national (or any user defined) datum
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DatumTransformation {
+    /// [Helmert] transformation, from the output [ReferenceFrame] to the datum
+    pub helmert: Helmert,
+    /// True when the remote site (base station) coordinates are expressed in this datum
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub remote_site: bool,
+}
+
+impl DatumTransformation {
+    /// Builds new [DatumTransformation] from this [Helmert] transformation
+    pub fn new(helmert: Helmert) -> Self {
+        Self {
+            helmert,
+            remote_site: false,
+        }
+    }
+    /// Copies and returns [DatumTransformation] that also applies to the remote site coordinates
+    pub fn with_remote_site(&self, remote_site: bool) -> Self {
+        let mut s = *self;
+        s.remote_site = remote_site;
+        s
+    }
+}
+
+/// Terrestrial [ReferenceFrame] realizations
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ReferenceFrame {
+    /// ITRF2020 (realized by the IGS20 precise products)
+    #[default]
+    ITRF2020,
+    /// ITRF2014 (realized by the IGb14 precise products)
+    ITRF2014,
+    /// WGS84 (G2139), aligned with ITRF2014 at the centimeter level
+    /// (realized by the GPS broadcast ephemerides)
+    WGS84G2139,
+    /// ETRF2000, European frame attached to the stable part of the Eurasian plate
+    ETRF2000,
+}
+
+impl std::fmt::Display for ReferenceFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::ITRF2020 => write!(f, "ITRF2020"),
+            Self::ITRF2014 => write!(f, "ITRF2014"),
+            Self::WGS84G2139 => write!(f, "WGS84(G2139)"),
+            Self::ETRF2000 => write!(f, "ETRF2000"),
+        }
+    }
+}
+
+impl ReferenceFrame {
+    /// [Helmert] transformation from ITRF2020 to this [ReferenceFrame]
+    fn from_itrf2020(&self) -> Helmert {
+        // IERS, ITRF2020 to ITRF2014
+        let itrf2014 = Helmert::new((-1.4, -0.9, 1.4), -0.42, (0.0, 0.0, 0.0)).with_rates(
+            (0.0, -0.1, 0.2),
+            0.0,
+            (0.0, 0.0, 0.0),
+            2015.0,
+        );
+        match self {
+            Self::ITRF2020 => Helmert::default(),
+            Self::ITRF2014 | Self::WGS84G2139 => itrf2014,
+            Self::ETRF2000 => {
+                // EUREF TN-1, ITRF2014 to ETRF2000
+                let etrf2000 = Helmert::new((54.7, 52.2, -74.1), 2.12, (1.701, 10.290, -16.632))
+                    .with_rates((0.1, 0.1, -1.9), 0.11, (0.081, 0.490, -0.792), 2010.0);
+                itrf2014.then(&etrf2000)
+            },
+        }
+    }
+    /// Returns the [Helmert] transformation from this [ReferenceFrame] to `to`
+    pub fn helmert(&self, to: Self) -> Helmert {
+        if *self == to {
+            Helmert::default()
+        } else {
+            self.from_itrf2020().inverse().then(&to.from_itrf2020())
+        }
+    }
+    /// Transforms this ECEF position [m] at [Epoch], from this [ReferenceFrame] to `to`
+    pub fn transform(&self, to: Self, position_m: (f64, f64, f64), t: Epoch) -> (f64, f64, f64) {
+        self.helmert(to).apply(position_m, t)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DatumTransformation, Helmert, ReferenceFrame};
+    use crate::prelude::{Config, Epoch};
+    use std::str::FromStr;
+
+    #[test]
+    fn frame_transformations() {
+        let t = Epoch::from_str("2015-01-01T12:00:00 UTC").unwrap();
+        let x = (4_027_894.006, 307_045.600, 4_919_474.910);
+
+        let same = ReferenceFrame::ITRF2020.transform(ReferenceFrame::ITRF2020, x, t);
+        assert_eq!(same, x);
+
+        // ITRF2020 -> ITRF2014 at 2015.0: millimeter level
+        let itrf2014 = ReferenceFrame::ITRF2020.transform(ReferenceFrame::ITRF2014, x, t);
+        let dx = (itrf2014.0 - x.0, itrf2014.1 - x.1, itrf2014.2 - x.2);
+        assert!((dx.0 - (-1.4E-3 - 0.42E-9 * x.0)).abs() < 1.0E-4);
+        assert!((dx.1 - (-0.9E-3 - 0.42E-9 * x.1)).abs() < 1.0E-4);
+        assert!((dx.2 - (1.4E-3 - 0.42E-9 * x.2)).abs() < 1.0E-4);
+
+        // round trip
+        let back = ReferenceFrame::ITRF2014.transform(ReferenceFrame::ITRF2020, itrf2014, t);
+        assert!((back.0 - x.0).abs() < 1.0E-6);
+        assert!((back.1 - x.1).abs() < 1.0E-6);
+        assert!((back.2 - x.2).abs() < 1.0E-6);
+
+        // ETRF2000 drifts away from ITRF (Eurasian plate motion): decimeter level
+        let t = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+        let etrf = ReferenceFrame::ITRF2020.transform(ReferenceFrame::ETRF2000, x, t);
+        let norm =
+            ((etrf.0 - x.0).powi(2) + (etrf.1 - x.1).powi(2) + (etrf.2 - x.2).powi(2)).sqrt();
+        assert!(norm > 0.3 && norm < 1.0, "{}", norm);
+
+        // static transformation: no rates
+        let helmert = Helmert::new((1000.0, 0.0, 0.0), 0.0, (0.0, 0.0, 0.0));
+        assert_eq!(helmert.apply(x, t), (x.0 + 1.0, x.1, x.2));
+    }
+
+    #[test]
+    fn datum_transformation() {
+        let t = Epoch::from_str("2020-01-01T00:00:00 UTC").unwrap();
+        let x = (4_027_894.006, 307_045.600, 4_919_474.910);
+
+        let mut cfg = Config::static_rtk_preset(Default::default(), x);
+        assert_eq!(cfg.remote_site_ecef_m(t), Some(x));
+
+        // datum only applies to the solutions
+        let helmert = Helmert::new((1000.0, -500.0, 0.0), 10.0, (0.0, 0.0, 1.0));
+        cfg.datum = Some(DatumTransformation::new(helmert));
+        assert_eq!(cfg.remote_site_ecef_m(t), Some(x));
+
+        // remote site expressed in the datum
+        cfg.datum = Some(DatumTransformation::new(helmert).with_remote_site(true));
+        let datum = helmert.apply(x, t);
+        cfg.remote_site = Some(datum);
+        let remote = cfg.remote_site_ecef_m(t).unwrap();
+        assert!((remote.0 - x.0).abs() < 1.0E-3);
+        assert!((remote.1 - x.1).abs() < 1.0E-3);
+        assert!((remote.2 - x.2).abs() < 1.0E-3);
+
+        // through the output frame
+        cfg.output_frame = Some(ReferenceFrame::ETRF2000);
+        cfg.remote_site = Some(helmert.apply(
+            ReferenceFrame::ITRF2020.transform(ReferenceFrame::ETRF2000, x, t),
+            t,
+        ));
+        let remote = cfg.remote_site_ecef_m(t).unwrap();
+        assert!((remote.0 - x.0).abs() < 1.0E-3);
+        assert!((remote.1 - x.1).abs() < 1.0E-3);
+        assert!((remote.2 - x.2).abs() < 1.0E-3);
+    }
+}
@@ -2,41 +2,135 @@
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
 extern crate gnss_rs as gnss;
-extern crate nyx_space as nyx;
 
 // private modules
 mod ambiguity;
+mod antenna;
+mod apriori;
+mod attitude;
 mod bancroft;
 mod bias;
 mod candidate;
 mod carrier;
 mod cfg;
+mod clock;
+mod coldstart;
+mod constellation;
+mod corrections;
+mod eclipse;
+mod formats;
+mod frame;
 mod navigation;
 mod orbit;
+mod perf;
+mod pipeline;
+mod projection;
 mod solver;
+mod source;
+mod state;
+mod svmap;
+mod time;
+
+pub mod coordinates;
 
 pub(crate) mod constants;
+pub(crate) mod logging;
+pub(crate) mod sync;
 // pub(crate) mod tides;
 
 // mod tracker;
 // pub(crate) mod utils;
 
+#[cfg(feature = "capi")]
+#[cfg_attr(docsrs, doc(cfg(feature = "capi")))]
+pub mod capi;
+
+#[cfg(feature = "python")]
+mod python;
+
+#[cfg(feature = "replay")]
+mod replay;
+
 #[cfg(test)]
 mod tests;
 
 // prelude
 pub mod prelude {
-    pub use crate::ambiguity::Ambiguities;
+    pub use crate::ambiguity::{Ambiguities, AmbiguityStatus, AmbiguityTracking};
+    pub use crate::antenna::{AntennaCalibration, PhaseCenter};
+    #[cfg(feature = "antex")]
+    pub use crate::antenna::{Antex, AntexError};
+    pub use crate::apriori::AprioriPosition;
+    pub use crate::attitude::Attitude;
     pub use crate::bias::{
-        BdModel, IonoComponents, IonosphereBias, KbModel, NgModel, TropoComponents, TropoModel,
-    };
-    pub use crate::candidate::{Candidate, ClockCorrection, Observation};
-    pub use crate::carrier::Carrier;
-    pub use crate::cfg::{Config, Method};
-    pub use crate::navigation::{Filter, InvalidationCause, PVTSolution, PVTSolutionType};
-    pub use crate::orbit::OrbitSource;
-    pub use crate::solver::{Error, Solver};
+        BdModel, IonoComponents, IonosphereBias, KbModel, NgModel, SbasIgp, SbasIonoDegradation,
+        SbasIonoGrid, TropoComponents, TropoModel,
+    };
+    pub use crate::candidate::{
+        BeiDouGroupDelays, Candidate, ClockCorrection, ClockPolynomial, GalileoNavMessage,
+        Observation,
+    };
+    pub use crate::carrier::{Carrier, CarrierDefinition};
+    pub use crate::cfg::{
+        BaselineConstraint, CarrierSnrMask, ClockConstraint, ColdStart, Config, ConfigDiff,
+        ConstellationMonitor, HeightConstraint, Interpolation, InterpolationMethod, Method,
+        NoiseModel, NoiseSpec, CONFIG_VERSION,
+    };
+    pub use crate::clock::ClockSource;
+    #[cfg(feature = "clk")]
+    pub use crate::clock::{ClkError, PreciseClocks};
+    pub use crate::constellation::ConstellationTable;
+    pub use crate::corrections::{
+        CorrectionLoss, CorrectionStatus, DgnssCorrection, DgnssCorrections,
+    };
+    #[cfg(feature = "rinex")]
+    pub use crate::formats::rinex_candidate_pools;
+    #[cfg(feature = "cggtts")]
+    pub use crate::formats::{
+        cggtts_track_start, CggttsHeader, CggttsTrack, CggttsTracker, CggttsWriter,
+    };
+    #[cfg(feature = "jsonl")]
+    pub use crate::formats::{solution_json, SolutionLogger};
+    #[cfg(feature = "rtcm")]
+    pub use crate::formats::{
+        ClasMessage, ClasStec, MsmObservations, ReferenceStation, RtcmDecoder, RtcmMessage,
+        SsrMessage,
+    };
+    #[cfg(feature = "ubx")]
+    pub use crate::formats::{NavPvt, RawxObservations, UbxDecoder, UbxMessage};
+    #[cfg(feature = "nmea")]
+    pub use crate::formats::{NmeaFix, NmeaReader, ReferenceTrack};
+    #[cfg(feature = "ntrip")]
+    pub use crate::formats::{NtripClient, NtripConfig, NtripError};
+    #[cfg(feature = "sbf")]
+    pub use crate::formats::{SbfDecoder, SbfMessage, SbfObservations};
+    #[cfg(feature = "stream")]
+    pub use crate::formats::{SolutionStream, StreamFormat};
+    pub use crate::frame::{DatumTransformation, Helmert, ReferenceFrame};
+    pub use crate::navigation::{
+        Aiding, BatchSolution, Decomposition, Filter, FilterSnapshot, FilterView,
+        InvalidationCause, PVTSolution, PVTSolutionType, StateLabel, StaticBatch,
+    };
+    pub use crate::orbit::{
+        resolve_week, BroadcastOrbits, EphemerisError, GlonassEphemeris, KeplerianEphemeris,
+        OrbitSource, SbasGeoEphemeris, ScreenedOrbits, SsrBias, SsrClockCorrection,
+        SsrOrbitCorrection, SsrOrbits,
+    };
+    #[cfg(feature = "sp3")]
+    pub use crate::orbit::{Sp3Error, Sp3Orbits};
+    pub use crate::perf::Stage;
+    #[cfg(feature = "std")]
+    pub use crate::perf::{CountingAllocator, PerfCounters};
+    pub use crate::pipeline::{CandidateFilter, CandidatePipeline, FilterContext, FilterStage};
+    pub use crate::projection::UTMCoordinates;
+    #[cfg(feature = "replay")]
+    pub use crate::replay::{Recorder, Recording, Replay, ReplayError, ReplaySource};
+    pub use crate::solver::{Error, ErrorCategory, ErrorKind, Solver};
+    pub use crate::source::{Fallback, Memoized};
+    pub use crate::state::{EstimatorView, SolverState};
+    pub use crate::time::{LeapSecondPolicy, TimeSource, Timestamping};
     // re-export
+    pub use crate::constants::SPEED_OF_LIGHT_M_S;
     pub use anise::{
         constants::frames::{EARTH_ITRF93, EARTH_J2000, IAU_EARTH_FRAME, SUN_J2000},
         naif::SPK,
@@ -45,5 +139,5 @@ pub mod prelude {
     pub use gnss::prelude::{Constellation, SV};
     pub use hifitime::{Duration, Epoch, TimeScale};
     pub use nalgebra::Vector3;
-    pub use nyx_space::{cosmic::SPEED_OF_LIGHT_M_S, md::prelude::Arc};
+    pub use std::sync::Arc;
 }
@@ -0,0 +1,7 @@
+//! Diagnostics: emitted as `tracing` events (within the epoch and candidate spans)
+//! when the `tracing` feature is activated, as `log` records otherwise.
+#[cfg(not(feature = "tracing"))]
+pub(crate) use log::{debug, error, info, warn};
+
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::{debug, error, info, warn};
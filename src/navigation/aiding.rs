@@ -0,0 +1,468 @@
+//! External sensors aiding (loose coupling)
+use nalgebra::{Matrix3, Matrix6, SMatrix, SVector, Vector3, Vector6};
+
+use crate::{
+    attitude::Attitude,
+    coordinates::{enu_to_ecef, Ellipsoid},
+    prelude::{Duration, Epoch},
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Normal gravity [m/s²], compensated from the IMU specific force
+const GRAVITY_M_S2: f64 = 9.80665;
+
+/// Acceleration process noise [m²/s³], of a non aided platform
+const ACCELERATION_PSD_M2_S3: f64 = 1.0;
+
+/// Initial velocity uncertainty [m/s]
+const INITIAL_VELOCITY_SIGMA_M_S: f64 = 10.0;
+
+/// Below this speed [m/s], the direction of travel is unknown
+/// and wheel speed measurements are dropped
+const MIN_WHEEL_SPEED_DIRECTION_M_S: f64 = 0.5;
+
+/// Heading constraint: cross track velocity uncertainty floor [m/s],
+/// so static platforms are not over constrained
+const MIN_HEADING_SIGMA_M_S: f64 = 0.01;
+
+/// [Aiding] measurements, from external sensors, that may be proposed
+/// along the [Candidate](crate::prelude::Candidate)s
+/// (see [Solver::resolve_with_aiding](crate::prelude::Solver::resolve_with_aiding)).
+/// They are fused with the GNSS solution (loose coupling) and bridge GNSS gaps.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Aiding {
+    /// IMU increments, accumulated since the previous [Epoch]
+    Imu {
+        /// Accumulation interval
+        interval: Duration,
+        /// Delta velocity (specific force) [m/s], in the body frame
+        delta_velocity_m_s: (f64, f64, f64),
+        /// Delta angle [rad], in the body frame
+        delta_angle_rad: (f64, f64, f64),
+        /// Platform [Attitude] at the start of the interval
+        attitude: Attitude,
+        /// Delta velocity standard deviation [m/s]
+        sigma_m_s: f64,
+    },
+    /// External INS solution
+    Ins {
+        /// ECEF position [m]
+        position_ecef_m: (f64, f64, f64),
+        /// ECEF velocity [m/s]
+        velocity_ecef_m_s: (f64, f64, f64),
+        /// Position standard deviation [m]
+        position_sigma_m: f64,
+        /// Velocity standard deviation [m/s]
+        velocity_sigma_m_s: f64,
+    },
+    /// Odometry: wheel speed, along the direction of travel
+    WheelSpeed {
+        /// Speed [m/s]
+        speed_m_s: f64,
+        /// Speed standard deviation [m/s]
+        sigma_m_s: f64,
+    },
+    /// Zero velocity update (ZUPT): the platform is known to be static
+    ZeroVelocity {
+        /// Velocity standard deviation [m/s]
+        sigma_m_s: f64,
+    },
+    /// Barometric altitude, above the WGS84 ellipsoid
+    /// (see `baro_max_pdop` option)
+    BaroAltitude {
+        /// Altitude [m]
+        altitude_m: f64,
+        /// Altitude standard deviation [m]
+        sigma_m: f64,
+    },
+    /// External heading (magnetometer, dual antenna), constrains the
+    /// direction of travel: the cross track velocity is null
+    Heading {
+        /// Heading, clockwise from North [°]
+        heading_deg: f64,
+        /// Heading standard deviation [°]
+        sigma_deg: f64,
+    },
+    /// External position measurement (map matching, UWB..), with full covariance
+    Position {
+        /// ECEF position [m]
+        position_ecef_m: (f64, f64, f64),
+        /// ECEF covariance [m²]
+        covariance_m2: [[f64; 3]; 3],
+    },
+}
+
+impl Aiding {
+    /// Returns gravity compensated ECEF delta velocity [m/s] and its
+    /// standard deviation, of IMU increments at (latitude, longitude) [rad]
+    fn delta_velocity_ecef(&self, rx_rad: (f64, f64)) -> Option<(Vector3<f64>, f64)> {
+        match self {
+            Self::Imu {
+                interval,
+                delta_velocity_m_s,
+                delta_angle_rad,
+                attitude,
+                sigma_m_s,
+            } => {
+                // mid interval attitude (small angles)
+                let (d_roll, d_pitch, d_yaw) = *delta_angle_rad;
+                let mid = Attitude::new(
+                    attitude.roll_deg + d_roll.to_degrees() / 2.0,
+                    attitude.pitch_deg + d_pitch.to_degrees() / 2.0,
+                    attitude.yaw_deg + d_yaw.to_degrees() / 2.0,
+                );
+                let (x, y, z) = *delta_velocity_m_s;
+                let mut enu = mid.body_to_enu(Vector3::new(x, y, z));
+                enu[2] -= GRAVITY_M_S2 * interval.to_seconds();
+                Some((enu_to_ecef(enu, rx_rad.0, rx_rad.1), *sigma_m_s))
+            },
+            _ => None,
+        }
+    }
+    /// Applies this measurement to the [AidingFilter]
+    fn update(&self, filter: &mut AidingFilter) {
+        match self {
+            Self::Ins {
+                position_ecef_m,
+                velocity_ecef_m_s,
+                position_sigma_m,
+                velocity_sigma_m_s,
+            } => {
+                let (x, y, z) = *position_ecef_m;
+                let (vx, vy, vz) = *velocity_ecef_m_s;
+                let z_m = Vector6::new(x, y, z, vx, vy, vz);
+                let mut r = Matrix6::zeros();
+                for i in 0..3 {
+                    r[(i, i)] = position_sigma_m.powi(2);
+                    r[(3 + i, 3 + i)] = velocity_sigma_m_s.powi(2);
+                }
+                filter.update(Matrix6::identity(), z_m - filter.x, r);
+            },
+            Self::WheelSpeed {
+                speed_m_s,
+                sigma_m_s,
+            } => {
+                // linearized about the current direction of travel
+                let vel_m_s = filter.velocity_m_s();
+                let norm_m_s = vel_m_s.norm();
+                if norm_m_s < MIN_WHEEL_SPEED_DIRECTION_M_S {
+                    return;
+                }
+                let mut h = SMatrix::<f64, 1, 6>::zeros();
+                for i in 0..3 {
+                    h[(0, 3 + i)] = vel_m_s[i] / norm_m_s;
+                }
+                let innovation = SVector::<f64, 1>::new(speed_m_s - norm_m_s);
+                let r = SMatrix::<f64, 1, 1>::new(sigma_m_s.powi(2));
+                filter.update(h, innovation, r);
+            },
+            Self::ZeroVelocity { sigma_m_s } => {
+                let mut h = SMatrix::<f64, 3, 6>::zeros();
+                h.fixed_view_mut::<3, 3>(0, 3)
+                    .copy_from(&Matrix3::identity());
+                let r = Matrix3::identity() * sigma_m_s.powi(2);
+                filter.update(h, -filter.velocity_m_s(), r);
+            },
+            Self::BaroAltitude {
+                altitude_m,
+                sigma_m,
+            } => {
+                // linearized along the local vertical
+                let (x, y, z) = (filter.x[0], filter.x[1], filter.x[2]);
+                let (lat_rad, lon_rad, alt_m) = Ellipsoid::WGS84.ecef_to_geodetic((x, y, z));
+                let up = enu_to_ecef(Vector3::new(0.0, 0.0, 1.0), lat_rad, lon_rad);
+                let mut h = SMatrix::<f64, 1, 6>::zeros();
+                for i in 0..3 {
+                    h[(0, i)] = up[i];
+                }
+                let innovation = SVector::<f64, 1>::new(altitude_m - alt_m);
+                let r = SMatrix::<f64, 1, 1>::new(sigma_m.powi(2));
+                filter.update(h, innovation, r);
+            },
+            Self::Heading {
+                heading_deg,
+                sigma_deg,
+            } => {
+                let (x, y, z) = (filter.x[0], filter.x[1], filter.x[2]);
+                let (lat_rad, lon_rad, _) = Ellipsoid::WGS84.ecef_to_geodetic((x, y, z));
+                let (s_psi, c_psi) = heading_deg.to_radians().sin_cos();
+                let cross_track = enu_to_ecef(Vector3::new(c_psi, -s_psi, 0.0), lat_rad, lon_rad);
+                let up = enu_to_ecef(Vector3::new(0.0, 0.0, 1.0), lat_rad, lon_rad);
+
+                let vel_m_s = filter.velocity_m_s();
+                let horizontal_m_s = (vel_m_s - up * vel_m_s.dot(&up)).norm();
+                let sigma_m_s =
+                    (horizontal_m_s * sigma_deg.to_radians()).max(MIN_HEADING_SIGMA_M_S);
+
+                let mut h = SMatrix::<f64, 1, 6>::zeros();
+                for i in 0..3 {
+                    h[(0, 3 + i)] = cross_track[i];
+                }
+                let innovation = SVector::<f64, 1>::new(-cross_track.dot(&vel_m_s));
+                let r = SMatrix::<f64, 1, 1>::new(sigma_m_s.powi(2));
+                filter.update(h, innovation, r);
+            },
+            Self::Position {
+                position_ecef_m,
+                covariance_m2,
+            } => {
+                let (x, y, z) = *position_ecef_m;
+                let cov_m2 = Matrix3::from_row_slice(&covariance_m2.concat());
+                filter.update_position(Vector3::new(x, y, z), cov_m2);
+            },
+            Self::Imu { .. } => {},
+        }
+    }
+}
+
+/// [AidingFilter] fuses the GNSS solutions and the [Aiding] measurements:
+/// Kalman filter of the ECEF position [m] and velocity [m/s].
+#[derive(Debug, Clone)]
+pub(crate) struct AidingFilter {
+    t: Epoch,
+    x: Vector6<f64>,
+    p: Matrix6<f64>,
+}
+
+impl AidingFilter {
+    /// Initializes [AidingFilter] from a GNSS solution
+    pub fn new(t: Epoch, pos_m: Vector3<f64>, vel_m_s: Vector3<f64>, cov_m2: Matrix3<f64>) -> Self {
+        let mut p = Matrix6::identity() * INITIAL_VELOCITY_SIGMA_M_S.powi(2);
+        p.fixed_view_mut::<3, 3>(0, 0).copy_from(&cov_m2);
+        Self {
+            t,
+            x: Vector6::new(
+                pos_m[0], pos_m[1], pos_m[2], vel_m_s[0], vel_m_s[1], vel_m_s[2],
+            ),
+            p,
+        }
+    }
+    /// Returns [Epoch] of the latest prediction
+    pub fn epoch(&self) -> Epoch {
+        self.t
+    }
+    /// Returns ECEF position [m]
+    pub fn position_m(&self) -> Vector3<f64> {
+        self.x.fixed_rows::<3>(0).into()
+    }
+    /// Returns ECEF velocity [m/s]
+    pub fn velocity_m_s(&self) -> Vector3<f64> {
+        self.x.fixed_rows::<3>(3).into()
+    }
+    /// Predicts state at [Epoch], integrating the IMU [Aiding] increments, if any
+    pub fn predict(&mut self, t: Epoch, aiding: &[Aiding]) {
+        let dt_s = (t - self.t).to_seconds();
+        self.t = t;
+
+        let (lat_rad, lon_rad, _) =
+            Ellipsoid::WGS84.ecef_to_geodetic((self.x[0], self.x[1], self.x[2]));
+
+        let mut f = Matrix6::identity();
+        for i in 0..3 {
+            f[(i, 3 + i)] = dt_s;
+        }
+        self.x = f * self.x;
+
+        let mut q = Matrix6::zeros();
+        let increments = aiding
+            .iter()
+            .filter_map(|aiding| aiding.delta_velocity_ecef((lat_rad, lon_rad)))
+            .collect::<Vec<_>>();
+
+        if increments.is_empty() {
+            // white acceleration model
+            let psd = ACCELERATION_PSD_M2_S3;
+            for i in 0..3 {
+                q[(i, i)] = psd * dt_s.powi(3) / 3.0;
+                q[(i, 3 + i)] = psd * dt_s.powi(2) / 2.0;
+                q[(3 + i, i)] = psd * dt_s.powi(2) / 2.0;
+                q[(3 + i, 3 + i)] = psd * dt_s;
+            }
+        } else {
+            for (dv_m_s, sigma_m_s) in increments {
+                for i in 0..3 {
+                    self.x[i] += dv_m_s[i] * dt_s / 2.0;
+                    self.x[3 + i] += dv_m_s[i];
+                    q[(i, i)] += (sigma_m_s * dt_s / 2.0).powi(2);
+                    q[(3 + i, 3 + i)] += sigma_m_s.powi(2);
+                }
+            }
+        }
+        self.p = f * self.p * f.transpose() + q;
+    }
+    /// Measurement update, from `N` linear measurements: returns false
+    /// if the innovation covariance is singular (measurement is then dropped).
+    pub fn update<const N: usize>(
+        &mut self,
+        h: SMatrix<f64, N, 6>,
+        innovation: SVector<f64, N>,
+        r: SMatrix<f64, N, N>,
+    ) -> bool {
+        let s = h * self.p * h.transpose() + r;
+        let s_inv = match s.try_inverse() {
+            Some(s_inv) => s_inv,
+            None => return false,
+        };
+        let k = self.p * h.transpose() * s_inv;
+        self.x += k * innovation;
+        self.p = (Matrix6::identity() - k * h) * self.p;
+        true
+    }
+    /// Updates with a position [m] and its covariance [m²]
+    pub fn update_position(&mut self, pos_m: Vector3<f64>, cov_m2: Matrix3<f64>) {
+        let mut h = SMatrix::<f64, 3, 6>::zeros();
+        h.fixed_view_mut::<3, 3>(0, 0)
+            .copy_from(&Matrix3::identity());
+        let innovation = pos_m - self.position_m();
+        self.update(h, innovation, cov_m2);
+    }
+    /// Updates with these [Aiding] measurements.
+    /// Barometric altitudes are dropped unless `baro` is set.
+    pub fn update_aiding(&mut self, aiding: &[Aiding], baro: bool) {
+        for aiding in aiding.iter() {
+            if baro || !matches!(aiding, Aiding::BaroAltitude { .. }) {
+                aiding.update(self);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Aiding, AidingFilter};
+    use crate::prelude::{Attitude, Duration, Epoch};
+    use nalgebra::{Matrix3, Vector3};
+    use std::str::FromStr;
+
+    #[test]
+    fn imu_and_ins_aiding() {
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let pos_m = Vector3::new(6378137.0, 0.0, 0.0);
+        let mut filter = AidingFilter::new(t0, pos_m, Vector3::zeros(), Matrix3::identity() * 4.0);
+
+        // static IMU: only gravity is sensed (body frame, z down)
+        let interval = Duration::from_seconds(1.0);
+        let imu = Aiding::Imu {
+            interval,
+            delta_velocity_m_s: (0.0, 0.0, -9.80665),
+            delta_angle_rad: (0.0, 0.0, 0.0),
+            attitude: Attitude::default(),
+            sigma_m_s: 0.01,
+        };
+        filter.predict(t0 + interval, &[imu]);
+        assert!((filter.position_m() - pos_m).norm() < 1.0E-6);
+        assert!(filter.velocity_m_s().norm() < 1.0E-6);
+
+        // accelerating northwards (+z, at lat=lon=0), 1 m/s per second
+        let imu = Aiding::Imu {
+            delta_velocity_m_s: (1.0, 0.0, -9.80665),
+            ..imu
+        };
+        filter.predict(t0 + interval * 2, &[imu]);
+        assert!((filter.velocity_m_s() - Vector3::new(0.0, 0.0, 1.0)).norm() < 1.0E-6);
+        assert!((filter.position_m()[2] - 0.5).abs() < 1.0E-6);
+
+        // accurate INS solution prevails
+        let ins = Aiding::Ins {
+            position_ecef_m: (6378137.0, 0.0, 10.0),
+            velocity_ecef_m_s: (0.0, 0.0, 2.0),
+            position_sigma_m: 0.01,
+            velocity_sigma_m_s: 0.01,
+        };
+        filter.update_aiding(&[ins], true);
+        assert!((filter.position_m()[2] - 10.0).abs() < 0.1);
+        assert!((filter.velocity_m_s()[2] - 2.0).abs() < 0.1);
+    }
+
+    #[test]
+    fn odometry_and_zupt() {
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let pos_m = Vector3::new(6378137.0, 0.0, 0.0);
+        let vel_m_s = Vector3::new(0.0, 3.0, 4.0);
+        let mut filter = AidingFilter::new(t0, pos_m, vel_m_s, Matrix3::identity());
+
+        // wheel speed scales the velocity, direction is preserved
+        let speed = Aiding::WheelSpeed {
+            speed_m_s: 10.0,
+            sigma_m_s: 0.01,
+        };
+        filter.update_aiding(&[speed], true);
+        let vel_m_s = filter.velocity_m_s();
+        assert!((vel_m_s.norm() - 10.0).abs() < 0.1);
+        assert!((vel_m_s[2] / vel_m_s[1] - 4.0 / 3.0).abs() < 1.0E-3);
+
+        // static platform
+        filter.predict(t0 + Duration::from_seconds(1.0), &[]);
+        let zupt = Aiding::ZeroVelocity { sigma_m_s: 0.01 };
+        filter.update_aiding(&[zupt], true);
+        assert!(filter.velocity_m_s().norm() < 0.1);
+
+        // unknown direction of travel: dropped
+        filter.update_aiding(&[speed], true);
+        assert!(filter.velocity_m_s().norm() < 0.1);
+    }
+
+    #[test]
+    fn baro_altitude() {
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let pos_m = Vector3::new(6378137.0 + 100.0, 0.0, 0.0);
+        let mut filter =
+            AidingFilter::new(t0, pos_m, Vector3::zeros(), Matrix3::identity() * 100.0);
+
+        let baro = Aiding::BaroAltitude {
+            altitude_m: 90.0,
+            sigma_m: 0.1,
+        };
+
+        filter.update_aiding(&[baro], false);
+        assert_eq!(filter.position_m(), pos_m);
+
+        // vertical (+x at lat=lon=0) is constrained, horizontal is preserved
+        filter.update_aiding(&[baro], true);
+        let pos_m = filter.position_m();
+        assert!((pos_m[0] - 6378137.0 - 90.0).abs() < 0.1);
+        assert_eq!((pos_m[1], pos_m[2]), (0.0, 0.0));
+    }
+
+    #[test]
+    fn heading_constraint() {
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let pos_m = Vector3::new(6378137.0, 0.0, 0.0);
+
+        // noisy velocity, heading North East (east is +y, north is +z)
+        let vel_m_s = Vector3::new(0.0, 1.2, 0.8);
+        let mut filter = AidingFilter::new(t0, pos_m, vel_m_s, Matrix3::identity());
+
+        let heading = Aiding::Heading {
+            heading_deg: 45.0,
+            sigma_deg: 0.1,
+        };
+        filter.update_aiding(&[heading], true);
+
+        let vel_m_s = filter.velocity_m_s();
+        assert!((vel_m_s[1] - vel_m_s[2]).abs() < 1.0E-2);
+        assert!((vel_m_s[1] - 1.0).abs() < 1.0E-2);
+    }
+
+    #[test]
+    fn external_position() {
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let pos_m = Vector3::new(6378137.0, 0.0, 0.0);
+        let mut filter = AidingFilter::new(t0, pos_m, Vector3::zeros(), Matrix3::identity());
+
+        // map matching: accurate across the road (+y), loose along it (+z)
+        let position = Aiding::Position {
+            position_ecef_m: (6378137.0, 2.0, 2.0),
+            covariance_m2: [[1.0, 0.0, 0.0], [0.0, 1.0E-4, 0.0], [0.0, 0.0, 1.0E4]],
+        };
+        filter.update_aiding(&[position], true);
+
+        let pos_m = filter.position_m();
+        assert!((pos_m[0] - 6378137.0).abs() < 1.0E-6);
+        assert!((pos_m[1] - 2.0).abs() < 1.0E-3);
+        assert!(pos_m[2].abs() < 1.0E-3);
+    }
+}
@@ -0,0 +1,237 @@
+//! Multi epoch batch estimation, for static sessions
+use nalgebra::{Matrix3, Vector3};
+
+use crate::{
+    constants::SPEED_OF_LIGHT_M_S,
+    navigation::Input,
+    prelude::{Duration, Epoch, ErrorKind},
+};
+
+/// Contribution of one [Epoch], once its clock has been eliminated
+#[derive(Debug, Clone)]
+struct EpochBlock {
+    /// [Epoch]
+    t: Epoch,
+    /// Position / clock normal terms
+    n_xc: Vector3<f64>,
+    /// Clock normal term
+    n_cc: f64,
+    /// Clock right hand side
+    b_c: f64,
+    /// Number of observations
+    rows: usize,
+}
+
+/// [BatchSolution] resolved by the [StaticBatch]
+#[derive(Debug, Clone)]
+pub struct BatchSolution {
+    /// Position of the reference point, ECEF [m]
+    pub position_ecef_m: (f64, f64, f64),
+    /// Position standard deviation, per ECEF axis [m]
+    pub sigma_m: (f64, f64, f64),
+    /// A-posteriori standard deviation of unit weight [m]
+    pub sigma0_m: f64,
+    /// Receiver clock offset, per [Epoch]
+    pub clocks: Vec<(Epoch, Duration)>,
+}
+
+/// [StaticBatch] estimates a single position and one clock offset per [Epoch],
+/// from all the epochs of a static session (network style adjustment),
+/// rather than averaging the epoch solutions.
+///
+/// The normal equations have an arrow structure (the clocks only relate
+/// to their own epoch): each clock is eliminated as its epoch is accumulated,
+/// so the batch only stores a few scalars per epoch and always inverts a 3x3 system.
+/// All epochs must be linearized about the same a-priori position: the batch
+/// restarts whenever it changes.
+#[derive(Debug, Clone, Default)]
+pub struct StaticBatch {
+    /// A-priori position (linearization point), ECEF [m]
+    apriori_m: Option<Vector3<f64>>,
+    /// Reduced normal matrix
+    n_xx: Matrix3<f64>,
+    /// Reduced right hand side
+    b_x: Vector3<f64>,
+    /// Position right hand side (not reduced)
+    b_xx: Vector3<f64>,
+    /// Weighted sum of squared observations
+    y_w_y: f64,
+    /// Eliminated epochs
+    blocks: Vec<EpochBlock>,
+}
+
+impl StaticBatch {
+    /// Returns number of accumulated epochs
+    pub fn epochs(&self) -> usize {
+        self.blocks.len()
+    }
+    /// Drops all accumulated epochs
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+    /// Accumulates this navigation [Input], linearized about `apriori_m`
+    pub(crate) fn push(&mut self, t: Epoch, apriori_m: Vector3<f64>, input: &Input) {
+        if self.apriori_m != Some(apriori_m) {
+            self.clear();
+            self.apriori_m = Some(apriori_m);
+        }
+
+        let mut n_xx = Matrix3::<f64>::zeros();
+        let mut n_xc = Vector3::<f64>::zeros();
+        let (mut n_cc, mut b_x, mut b_c) = (0.0_f64, Vector3::<f64>::zeros(), 0.0_f64);
+
+        for i in 0..input.rows {
+            let a = Vector3::new(input.g[(i, 0)], input.g[(i, 1)], input.g[(i, 2)]);
+            let (c, w, y) = (input.g[(i, 3)], input.w[(i, i)], input.y[i]);
+            n_xx += a * a.transpose() * w;
+            n_xc += a * c * w;
+            n_cc += c * c * w;
+            b_x += a * y * w;
+            b_c += c * y * w;
+            self.y_w_y += y * y * w;
+        }
+
+        if n_cc <= 0.0 {
+            return;
+        }
+
+        // eliminate this epoch clock
+        self.n_xx += n_xx - n_xc * n_xc.transpose() / n_cc;
+        self.b_x += b_x - n_xc * b_c / n_cc;
+        self.b_xx += b_x;
+        self.blocks.push(EpochBlock {
+            t,
+            n_xc,
+            n_cc,
+            b_c,
+            rows: input.rows,
+        });
+    }
+    /// Resolves the [BatchSolution] from all accumulated epochs
+    pub fn solve(&self) -> Result<BatchSolution, ErrorKind> {
+        let apriori_m = self.apriori_m.ok_or(ErrorKind::NotEnoughCandidates)?;
+        let q = self
+            .n_xx
+            .try_inverse()
+            .ok_or(ErrorKind::MatrixInversionError)?;
+        let dx = q * self.b_x;
+
+        // back substitution
+        let mut x_b = dx.dot(&self.b_xx);
+        let mut rows = 0;
+        let clocks = self
+            .blocks
+            .iter()
+            .map(|block| {
+                let c = (block.b_c - block.n_xc.dot(&dx)) / block.n_cc;
+                x_b += c * block.b_c;
+                rows += block.rows;
+                (block.t, Duration::from_seconds(c / SPEED_OF_LIGHT_M_S))
+            })
+            .collect::<Vec<_>>();
+
+        let dof = rows.saturating_sub(3 + self.blocks.len());
+        let sigma0_m = if dof > 0 {
+            ((self.y_w_y - x_b).max(0.0) / dof as f64).sqrt()
+        } else {
+            0.0
+        };
+
+        let position_m = apriori_m + dx;
+        Ok(BatchSolution {
+            position_ecef_m: (position_m[0], position_m[1], position_m[2]),
+            sigma_m: (
+                sigma0_m * q[(0, 0)].sqrt(),
+                sigma0_m * q[(1, 1)].sqrt(),
+                sigma0_m * q[(2, 2)].sqrt(),
+            ),
+            sigma0_m,
+            clocks,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::StaticBatch;
+    use crate::{
+        constants::SPEED_OF_LIGHT_M_S,
+        navigation::{Decomposition, Input},
+        prelude::{Duration, Epoch},
+    };
+    use nalgebra::{base::dimension::U8, OMatrix, OVector, Vector3};
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn static_batch() {
+        let apriori_m = Vector3::new(4_000_000.0, 300_000.0, 4_900_000.0);
+        let dx = Vector3::new(1.5, -2.0, 0.5);
+        let t0 = Epoch::default();
+
+        let mut batch = StaticBatch::default();
+        for k in 0..10 {
+            let clock_m = 100.0 + 3.0 * k as f64;
+
+            // rotating geometry
+            let mut g = OMatrix::<f64, U8, U8>::zeros();
+            let mut y = OVector::<f64, U8>::zeros();
+            for i in 0..6 {
+                let az = (i as f64 * 60.0 + k as f64 * 5.0).to_radians();
+                let el = (20.0 + 10.0 * i as f64).to_radians();
+                let los = Vector3::new(el.cos() * az.sin(), el.cos() * az.cos(), el.sin());
+                g[(i, 0)] = -los[0];
+                g[(i, 1)] = -los[1];
+                g[(i, 2)] = -los[2];
+                g[(i, 3)] = 1.0;
+                y[i] = -los.dot(&dx) + clock_m;
+            }
+            let input = Input {
+                y,
+                g,
+                w: OMatrix::<f64, U8, U8>::identity(),
+                sv: BTreeMap::new(),
+                rows: 6,
+                clock_only: false,
+                max_innovation_sigma: None,
+                decomposition: Decomposition::Normal,
+                prior_information: None,
+                constraint_information: None,
+            };
+            batch.push(
+                t0 + k as f64 * Duration::from_seconds(30.0),
+                apriori_m,
+                &input,
+            );
+        }
+        assert_eq!(batch.epochs(), 10);
+
+        let solution = batch.solve().unwrap();
+        let (x, y, z) = solution.position_ecef_m;
+        assert!((x - apriori_m[0] - dx[0]).abs() < 1.0E-6);
+        assert!((y - apriori_m[1] - dx[1]).abs() < 1.0E-6);
+        assert!((z - apriori_m[2] - dx[2]).abs() < 1.0E-6);
+        assert!(solution.sigma0_m < 1.0E-6);
+
+        assert_eq!(solution.clocks.len(), 10);
+        for (k, (_, dt)) in solution.clocks.iter().enumerate() {
+            let clock_m = 100.0 + 3.0 * k as f64;
+            assert!((dt.to_seconds() * SPEED_OF_LIGHT_M_S - clock_m).abs() < 1.0E-6);
+        }
+
+        // new linearization point: batch restarts
+        let input = Input {
+            y: OVector::<f64, U8>::zeros(),
+            g: OMatrix::<f64, U8, U8>::zeros(),
+            w: OMatrix::<f64, U8, U8>::identity(),
+            sv: BTreeMap::new(),
+            rows: 0,
+            clock_only: false,
+            max_innovation_sigma: None,
+            decomposition: Decomposition::Normal,
+            prior_information: None,
+            constraint_information: None,
+        };
+        batch.push(t0, apriori_m * 2.0, &input);
+        assert_eq!(batch.epochs(), 0);
+    }
+}
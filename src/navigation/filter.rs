@@ -1,14 +1,24 @@
-use nalgebra::{base::dimension::U8, OMatrix, OVector, Vector3};
+use crate::logging::debug;
+use nalgebra::{base::dimension::U8, OMatrix, OVector, RealField, Vector3};
 
 #[cfg(feature = "serde")]
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-use super::{Input, Output};
-use crate::prelude::{Epoch, Error};
+use super::{linalg::spd_inverse, sum_information, Input, Output};
+use crate::prelude::{Epoch, ErrorKind};
+
+/// Geometry and weighting precision: single precision with the `f32` feature,
+/// for low power targets where meter level accuracy is sufficient.
+/// Time (and ranges) are always processed in double precision.
+#[cfg(feature = "f32")]
+type Float = f32;
+
+#[cfg(not(feature = "f32"))]
+type Float = f64;
 
 /// Navigation Filter.
 #[derive(Default, Debug, Clone, Copy, PartialEq)]
-#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Filter {
     /// None: solver filter completely bypassed. Lighter calculations, no iterative behavior.
     None,
@@ -20,6 +30,67 @@ pub enum Filter {
     Kalman,
 }
 
+/// [Decomposition] used to solve the least squares problems.
+#[derive(Default, Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Decomposition {
+    /// Explicit inversion of the normal matrix (GᵀWG). Fastest.
+    #[default]
+    Normal,
+    /// QR decomposition of the (weighted) geometry matrix: the normal matrix
+    /// is never formed, which preserves precision in ill conditioned geometries.
+    QR,
+    /// Singular value decomposition of the (weighted) geometry matrix.
+    /// Slowest but most robust: quasi singular directions (DOP spikes)
+    /// are dropped (minimum norm solution) rather than amplified.
+    SVD,
+}
+
+impl Decomposition {
+    /// Inverts this (symmetric) matrix
+    fn inverse<T: RealField + Copy>(&self, m: OMatrix<T, U8, U8>) -> Option<OMatrix<T, U8, U8>> {
+        match self {
+            Self::Normal => spd_inverse(m),
+            Self::QR => m.qr().try_inverse(),
+            Self::SVD => {
+                let svd = m.svd(true, true);
+                let eps = svd.singular_values.max() * T::default_epsilon().sqrt();
+                svd.pseudo_inverse(eps).ok()
+            },
+        }
+    }
+    /// Solves a x = b in the least squares sense, by factorizing a.
+    /// Returns (aᵀa)⁻¹ and the solution.
+    fn factorize<T: RealField + Copy>(
+        &self,
+        a: OMatrix<T, U8, U8>,
+        b: OVector<T, U8>,
+    ) -> Option<(OMatrix<T, U8, U8>, OVector<T, U8>)> {
+        match self {
+            Self::Normal => {
+                let a_prime = a.transpose();
+                let cov = spd_inverse(a_prime * a)?;
+                let x = cov * (a_prime * b);
+                Some((cov, x))
+            },
+            Self::QR => {
+                let qr = a.qr();
+                let r_inv = qr
+                    .r()
+                    .solve_upper_triangular(&OMatrix::<T, U8, U8>::identity())?;
+                let x = r_inv * (qr.q().transpose() * b);
+                Some((r_inv * r_inv.transpose(), x))
+            },
+            Self::SVD => {
+                let svd = a.svd(true, true);
+                let eps = svd.singular_values.max() * T::default_epsilon().sqrt();
+                let a_inv = svd.pseudo_inverse(eps).ok()?;
+                Some((a_inv * a_inv.transpose(), a_inv * b))
+            },
+        }
+    }
+}
+
 impl std::fmt::Display for Filter {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
@@ -50,6 +121,97 @@ pub(crate) enum FilterState {
     Kf(KFState),
 }
 
+/// [FilterSnapshot] is a plain copy of the internal filter state,
+/// that may be stored and restored later on.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FilterSnapshot {
+    /// [Filter] that produced this state
+    pub filter: Filter,
+    /// State vector
+    pub x: Vec<f64>,
+    /// State covariance matrix (column major)
+    pub p: Vec<f64>,
+    /// Process noise matrix (column major), Kalman only
+    pub q: Vec<f64>,
+    /// State transition matrix (column major), Kalman only
+    pub phi: Vec<f64>,
+}
+
+/// [StateLabel] identifies each component of the navigation filter state vector
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StateLabel {
+    /// ECEF X correction to the apriori position [m]
+    DeltaX,
+    /// ECEF Y correction to the apriori position [m]
+    DeltaY,
+    /// ECEF Z correction to the apriori position [m]
+    DeltaZ,
+    /// Receiver clock offset, expressed as range [m]
+    ClockOffset,
+    /// Phase ambiguity (bias) state [m]
+    Ambiguity(usize),
+}
+
+impl StateLabel {
+    /// Returns [StateLabel] of this state vector component
+    fn from_index(index: usize) -> Self {
+        match index {
+            0 => Self::DeltaX,
+            1 => Self::DeltaY,
+            2 => Self::DeltaZ,
+            3 => Self::ClockOffset,
+            i => Self::Ambiguity(i - 4),
+        }
+    }
+}
+
+impl std::fmt::Display for StateLabel {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::DeltaX => write!(f, "dx"),
+            Self::DeltaY => write!(f, "dy"),
+            Self::DeltaZ => write!(f, "dz"),
+            Self::ClockOffset => write!(f, "c.dt"),
+            Self::Ambiguity(i) => write!(f, "amb({})", i),
+        }
+    }
+}
+
+/// [FilterView] is a read-only, labelled view of the navigation filter state,
+/// to display its convergence or debug its tuning.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FilterView {
+    /// [Filter] that produced this state
+    pub filter: Filter,
+    /// Labelled state vector
+    pub states: Vec<(StateLabel, f64)>,
+    /// State covariance matrix (column major)
+    pub covariance: Vec<f64>,
+}
+
+impl FilterView {
+    /// Returns index of this [StateLabel] in the state vector
+    fn index(&self, label: StateLabel) -> Option<usize> {
+        self.states.iter().position(|(l, _)| *l == label)
+    }
+    /// Returns estimate of this [StateLabel]
+    pub fn value(&self, label: StateLabel) -> Option<f64> {
+        Some(self.states[self.index(label)?].1)
+    }
+    /// Returns covariance of these two [StateLabel]s
+    pub fn covariance_of(&self, lhs: StateLabel, rhs: StateLabel) -> Option<f64> {
+        let (i, j) = (self.index(lhs)?, self.index(rhs)?);
+        self.covariance.get(j * self.states.len() + i).copied()
+    }
+    /// Returns standard deviation of this [StateLabel]
+    pub fn sigma(&self, label: StateLabel) -> Option<f64> {
+        Some(self.covariance_of(label, label)?.sqrt())
+    }
+}
+
 impl Default for FilterState {
     fn default() -> Self {
         Self::Lsq(Default::default())
@@ -92,81 +254,124 @@ impl FilterState {
             Self::Kf(state) => state.x,
         }
     }
+    /// Copies [Self] as [FilterSnapshot]
+    pub(crate) fn snapshot(&self) -> FilterSnapshot {
+        match self {
+            Self::Lsq(state) => FilterSnapshot {
+                filter: Filter::LSQ,
+                x: state.x.as_slice().to_vec(),
+                p: state.p.as_slice().to_vec(),
+                q: Vec::new(),
+                phi: Vec::new(),
+            },
+            Self::Kf(state) => FilterSnapshot {
+                filter: Filter::Kalman,
+                x: state.x.as_slice().to_vec(),
+                p: state.p.as_slice().to_vec(),
+                q: state.q.as_slice().to_vec(),
+                phi: state.phi.as_slice().to_vec(),
+            },
+        }
+    }
+    /// Returns a labelled [FilterView] of [Self]
+    pub(crate) fn view(&self) -> FilterView {
+        let snapshot = self.snapshot();
+        FilterView {
+            filter: snapshot.filter,
+            states: snapshot
+                .x
+                .iter()
+                .enumerate()
+                .map(|(i, x)| (StateLabel::from_index(i), *x))
+                .collect(),
+            covariance: snapshot.p,
+        }
+    }
+    /// Rebuilds [Self] from [FilterSnapshot].
+    /// Returns None if snapshot dimensions do not match,
+    /// or for [Filter::None] which has no state to restore.
+    pub(crate) fn from_snapshot(snapshot: &FilterSnapshot) -> Option<Self> {
+        let (n, nn) = (8, 64);
+        if snapshot.x.len() != n || snapshot.p.len() != nn {
+            return None;
+        }
+        let x = OVector::<f64, U8>::from_column_slice(&snapshot.x);
+        let p = OMatrix::<f64, U8, U8>::from_column_slice(&snapshot.p);
+        match snapshot.filter {
+            Filter::Kalman => {
+                if snapshot.q.len() != nn || snapshot.phi.len() != nn {
+                    return None;
+                }
+                Some(Self::kf(KFState {
+                    x,
+                    p,
+                    q: OMatrix::<f64, U8, U8>::from_column_slice(&snapshot.q),
+                    phi: OMatrix::<f64, U8, U8>::from_column_slice(&snapshot.phi),
+                }))
+            },
+            Filter::LSQ => Some(Self::lsq(LSQState { x, p })),
+            Filter::None => None,
+        }
+    }
 }
 
 impl Filter {
-    fn lsq_resolve(input: &Input, p_state: Option<FilterState>) -> Result<Output, Error> {
+    fn lsq_resolve(input: &Input, p_state: Option<FilterState>) -> Result<Output, ErrorKind> {
         match p_state {
             Some(FilterState::Lsq(p_state)) => {
-                let p_1 = p_state.p.try_inverse().ok_or(Error::MatrixInversionError)?;
+                let decomposition = input.decomposition;
+                let p_1 = decomposition
+                    .inverse(p_state.p)
+                    .ok_or(ErrorKind::MatrixInversionError)?;
 
                 let g_prime = input.g.clone().transpose();
-                let q = (g_prime * input.g)
-                    .try_inverse()
-                    .ok_or(Error::MatrixInversionError)?;
+                let q = decomposition
+                    .inverse(g_prime * input.g)
+                    .ok_or(ErrorKind::MatrixInversionError)?;
+
+                let (info, info_x) = input.constraint_information.unwrap_or_default();
 
                 let p = g_prime * input.w * input.g;
-                let p = (p_1 + p).try_inverse().ok_or(Error::MatrixInversionError)?;
+                let p = decomposition
+                    .inverse(p_1 + p + info)
+                    .ok_or(ErrorKind::MatrixInversionError)?;
 
-                let x = p * (p_1 * p_state.x + (g_prime * input.w * input.y));
+                let x = p * (p_1 * p_state.x + (g_prime * input.w * input.y) + info_x);
 
                 Ok(Output {
-                    gdop: (q[(0, 0)] + q[(1, 1)] + q[(2, 2)] + q[(3, 3)]).sqrt(),
-                    pdop: (q[(0, 0)] + q[(1, 1)] + q[(2, 2)]).sqrt(),
-                    tdop: q[(4, 3)].sqrt(),
                     q,
                     state: FilterState::lsq(LSQState { p, x }),
                 })
             },
             _ => {
-                let g_prime = input.g.clone().transpose();
-
-                let q = (g_prime * input.g)
-                    .try_inverse()
-                    .ok_or(Error::MatrixInversionError)?;
-
-                let p = (g_prime * input.w * input.g)
-                    .try_inverse()
-                    .ok_or(Error::MatrixInversionError)?;
-
-                let x = p * (g_prime * input.w * input.y);
-                if x[3].is_nan() {
-                    return Err(Error::TimeIsNan);
-                }
+                let (q, p, x) = Self::normal_equations(input)?;
 
                 Ok(Output {
-                    gdop: (q[(0, 0)] + q[(1, 1)] + q[(2, 2)] + q[(3, 3)]).sqrt(),
-                    pdop: (q[(0, 0)] + q[(1, 1)] + q[(2, 2)]).sqrt(),
-                    tdop: q[(4, 3)].sqrt(),
                     q,
                     state: FilterState::lsq(LSQState { p, x }),
                 })
             },
         }
     }
-    fn kf_resolve(input: &Input, p_state: Option<FilterState>) -> Result<Output, Error> {
+    fn kf_resolve(input: &Input, p_state: Option<FilterState>) -> Result<Output, ErrorKind> {
         match p_state {
             Some(FilterState::Kf(p_state)) => {
                 let x_bn = p_state.phi * p_state.x;
                 let p_bn = p_state.phi * p_state.p * p_state.phi.transpose() + p_state.q;
 
-                let p_bn_inv = p_bn.try_inverse().ok_or(Error::MatrixInversionError)?;
-                let p_n = (input.g.transpose() * input.w * input.g + p_bn_inv)
-                    .try_inverse()
-                    .ok_or(Error::MatrixInversionError)?;
-
-                let w_g = input.g.transpose() * input.w * input.y;
-                let w_gy_pbn = w_g + (p_bn_inv * x_bn);
-                let x_n = p_n * w_gy_pbn;
+                let (x_n, p_n) = Self::kf_sequential_update(input, x_bn, p_bn)?;
+                let (x_n, p_n) = match &input.constraint_information {
+                    Some((info, info_x)) => {
+                        Self::information_update(input, x_n, p_n, info, info_x)?
+                    },
+                    None => (x_n, p_n),
+                };
 
                 let q_n = input.g.transpose() * input.g;
                 let phi_diag = OVector::<f64, U8>::from([1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
                 let q_diag = OVector::<f64, U8>::from([0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
 
                 Ok(Output {
-                    gdop: (q_n[(0, 0)] + q_n[(1, 1)] + q_n[(2, 2)] + q_n[(3, 3)]).sqrt(),
-                    pdop: (q_n[(0, 0)] + q_n[(1, 1)] + q_n[(2, 2)]).sqrt(),
-                    tdop: q_n[(4, 3)].sqrt(),
                     q: q_n,
                     state: FilterState::kf(KFState {
                         p: p_n,
@@ -177,27 +382,12 @@ impl Filter {
                 })
             },
             _ => {
-                let g_prime = input.g.clone().transpose();
-                let q = (g_prime * input.g)
-                    .try_inverse()
-                    .ok_or(Error::MatrixInversionError)?;
-
-                let p = (g_prime * input.w * input.g)
-                    .try_inverse()
-                    .ok_or(Error::MatrixInversionError)?;
-
-                let x = p * (g_prime * input.w * input.y);
-                if x[3].is_nan() {
-                    return Err(Error::TimeIsNan);
-                }
+                let (q, p, x) = Self::normal_equations(input)?;
 
                 let phi_diag = OVector::<f64, U8>::from([1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
                 let q_diag = OVector::<f64, U8>::from([0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
 
                 Ok(Output {
-                    gdop: (q[(0, 0)] + q[(1, 1)] + q[(2, 2)] + q[(3, 3)]).sqrt(),
-                    pdop: (q[(0, 0)] + q[(1, 1)] + q[(2, 2)]).sqrt(),
-                    tdop: q[(4, 3)].sqrt(),
                     q,
                     state: FilterState::kf(KFState {
                         p,
@@ -209,7 +399,186 @@ impl Filter {
             },
         }
     }
-    pub fn resolve(&self, input: &Input, p_state: Option<FilterState>) -> Result<Output, Error> {
+    /// Weighted least squares, solved in [Float] precision (see `f32` feature).
+    /// Returns the geometry (Q) and state covariance (P) matrices, and the state vector.
+    fn normal_equations(
+        input: &Input,
+    ) -> Result<(OMatrix<f64, U8, U8>, OMatrix<f64, U8, U8>, OVector<f64, U8>), ErrorKind> {
+        let information = sum_information(input.prior_information, input.constraint_information);
+        if let Some((info, info_x)) = &information {
+            return Self::constrained_least_squares(input, info, info_x);
+        }
+        let (q, p, x) = Self::weighted_least_squares(
+            input.g.cast::<Float>(),
+            input.w.cast::<Float>(),
+            input.y.cast::<Float>(),
+            input.decomposition,
+        )?;
+        let x = x.cast::<f64>();
+        if x[3].is_nan() {
+            return Err(ErrorKind::TimeIsNan);
+        }
+        Ok((q.cast::<f64>(), p.cast::<f64>(), x))
+    }
+    /// Weighted least squares, constrained by this prior information (information form).
+    /// Returns the geometry (Q) and state covariance (P) matrices, and the state vector.
+    fn constrained_least_squares(
+        input: &Input,
+        info: &OMatrix<f64, U8, U8>,
+        info_x: &OVector<f64, U8>,
+    ) -> Result<(OMatrix<f64, U8, U8>, OMatrix<f64, U8, U8>, OVector<f64, U8>), ErrorKind> {
+        let g_prime = input.g.transpose();
+        let p = input
+            .decomposition
+            .inverse(g_prime * input.w * input.g + info)
+            .ok_or(ErrorKind::MatrixInversionError)?;
+        let x = p * (g_prime * input.w * input.y + info_x);
+        if x[3].is_nan() {
+            return Err(ErrorKind::TimeIsNan);
+        }
+        // DOPs remain defined when the constraint compensates a degenerate geometry
+        let q = input
+            .decomposition
+            .inverse(g_prime * input.g)
+            .or_else(|| Decomposition::SVD.inverse(g_prime * input.g))
+            .ok_or(ErrorKind::MatrixInversionError)?;
+        Ok((q, p, x))
+    }
+    fn weighted_least_squares<T: RealField + Copy>(
+        g: OMatrix<T, U8, U8>,
+        w: OMatrix<T, U8, U8>,
+        y: OVector<T, U8>,
+        decomposition: Decomposition,
+    ) -> Result<(OMatrix<T, U8, U8>, OMatrix<T, U8, U8>, OVector<T, U8>), ErrorKind> {
+        if decomposition == Decomposition::Normal {
+            let g_prime = g.transpose();
+
+            let q = spd_inverse(g_prime * g).ok_or(ErrorKind::MatrixInversionError)?;
+            let p = spd_inverse(g_prime * w * g).ok_or(ErrorKind::MatrixInversionError)?;
+
+            let x = p * (g_prime * w * y);
+            return Ok((q, p, x));
+        }
+
+        // factorize W^1/2 G (W is diagonal)
+        let w_sqrt = w.map(|w_i| w_i.sqrt());
+        let (p, x) = decomposition
+            .factorize(w_sqrt * g, w_sqrt * y)
+            .ok_or(ErrorKind::MatrixInversionError)?;
+        let (q, _) = decomposition
+            .factorize(g, OVector::<T, U8>::zeros())
+            .ok_or(ErrorKind::MatrixInversionError)?;
+        Ok((q, p, x))
+    }
+    /// Kalman measurement update, one (scalar) measurement at a time:
+    /// no innovation covariance inversion, and outliers are rejected early (see innovation gate).
+    fn kf_sequential_update(
+        input: &Input,
+        mut x: OVector<f64, U8>,
+        mut p: OMatrix<f64, U8, U8>,
+    ) -> Result<(OVector<f64, U8>, OMatrix<f64, U8, U8>), ErrorKind> {
+        let identity = OMatrix::<f64, U8, U8>::identity();
+        for i in 0..8 {
+            let w = input.w[(i, i)];
+            if w <= 0.0 {
+                continue;
+            }
+            let h = input.g.row(i);
+            let p_h = p * h.transpose();
+            let s = (h * p_h)[(0, 0)] + 1.0 / w;
+            if s <= 0.0 {
+                return Err(ErrorKind::MatrixInversionError);
+            }
+            let innovation = input.y[i] - (h * x)[(0, 0)];
+            if let Some(max_sigma) = input.max_innovation_sigma {
+                if innovation.abs() > max_sigma * s.sqrt() {
+                    debug!("rejected measurement #{}: innovation {:.3E}", i, innovation);
+                    continue;
+                }
+            }
+            let k = p_h / s;
+            x += k * innovation;
+            p = (identity - k * h) * p;
+        }
+        if x[3].is_nan() {
+            return Err(ErrorKind::TimeIsNan);
+        }
+        Ok((x, p))
+    }
+    /// Kalman measurement update by this information (pseudo-observations, information form)
+    fn information_update(
+        input: &Input,
+        x: OVector<f64, U8>,
+        p: OMatrix<f64, U8, U8>,
+        info: &OMatrix<f64, U8, U8>,
+        info_x: &OVector<f64, U8>,
+    ) -> Result<(OVector<f64, U8>, OMatrix<f64, U8, U8>), ErrorKind> {
+        let decomposition = input.decomposition;
+        let p_1 = decomposition
+            .inverse(p)
+            .ok_or(ErrorKind::MatrixInversionError)?;
+        let p = decomposition
+            .inverse(p_1 + info)
+            .ok_or(ErrorKind::MatrixInversionError)?;
+        let x = p * (p_1 * x + info_x);
+        Ok((x, p))
+    }
+    /// Clock only resolution, position being held fixed: weighted mean of the prefit residuals
+    /// of the (at most 4) [Input] rows.
+    /// DOPs are geometrical when 4 vehicles contribute, otherwise only TDOP is determined.
+    pub(crate) fn clock_resolve(input: &Input) -> Result<Output, ErrorKind> {
+        let (mut sum_w, mut sum_wy) = (0.0_f64, 0.0_f64);
+        for j in 0..input.rows {
+            let w = input.w[(j, j)];
+            sum_w += w;
+            sum_wy += w * input.y[j];
+        }
+        // clock constraint
+        if let Some((info, info_x)) = &input.constraint_information {
+            sum_w += info[(3, 3)];
+            sum_wy += info_x[3];
+        }
+        if sum_w <= 0.0 {
+            return Err(ErrorKind::MatrixInversionError);
+        }
+
+        let cdt = sum_wy / sum_w;
+        if cdt.is_nan() {
+            return Err(ErrorKind::TimeIsNan);
+        }
+
+        let mut x = OVector::<f64, U8>::zeros();
+        x[3] = cdt;
+
+        let mut p = OMatrix::<f64, U8, U8>::zeros();
+        p[(3, 3)] = 1.0 / sum_w;
+
+        let geometry = if input.rows > 3 {
+            (input.g.transpose() * input.g).try_inverse()
+        } else {
+            None
+        };
+
+        let q = match geometry {
+            Some(q) => q,
+            None => {
+                // time only geometry
+                let mut q = OMatrix::<f64, U8, U8>::zeros();
+                q[(3, 3)] = 1.0 / input.rows as f64;
+                q
+            },
+        };
+
+        Ok(Output {
+            q,
+            state: FilterState::lsq(LSQState { p, x }),
+        })
+    }
+    pub fn resolve(
+        &self,
+        input: &Input,
+        p_state: Option<FilterState>,
+    ) -> Result<Output, ErrorKind> {
         match self {
             Filter::None => Self::lsq_resolve(input, None),
             Filter::LSQ => Self::lsq_resolve(input, p_state),
@@ -256,3 +625,327 @@ impl std::fmt::Display for State3D {
 //         self.t = t;
 //     }
 // }
+
+#[cfg(test)]
+mod test {
+    use super::{Decomposition, Filter, FilterState, Input, LSQState, StateLabel};
+    use crate::navigation::solutions;
+    use nalgebra::{base::dimension::U8, OMatrix, OVector};
+    use std::collections::BTreeMap;
+    #[test]
+    fn clock_only_resolution() {
+        let mut y = OVector::<f64, U8>::zeros();
+        y[0] = 10.0;
+        y[1] = 12.0;
+        y[2] = 14.0;
+
+        let input = Input {
+            y,
+            g: OMatrix::<f64, U8, U8>::zeros(),
+            w: OMatrix::<f64, U8, U8>::identity(),
+            sv: BTreeMap::new(),
+            rows: 3,
+            clock_only: true,
+            max_innovation_sigma: None,
+            decomposition: Decomposition::Normal,
+            prior_information: None,
+            constraint_information: None,
+        };
+
+        let output = Filter::clock_resolve(&input).unwrap();
+        let x = output.state.estimate();
+        assert_eq!(x[3], 12.0);
+        assert_eq!((x[0], x[1], x[2]), (0.0, 0.0, 0.0));
+        assert_eq!(output.tdop(), (1.0_f64 / 3.0).sqrt());
+        assert_eq!(output.gdop(), 0.0);
+        assert_eq!(output.pdop(), 0.0);
+
+        // clock constraint (external frequency reference)
+        let mut info = OMatrix::<f64, U8, U8>::zeros();
+        let mut info_x = OVector::<f64, U8>::zeros();
+        info[(3, 3)] = 3.0;
+        info_x[3] = 20.0 * 3.0;
+        let input = Input {
+            constraint_information: Some((info, info_x)),
+            ..input
+        };
+        let output = Filter::clock_resolve(&input).unwrap();
+        assert_eq!(output.state.estimate()[3], 16.0);
+    }
+    #[test]
+    fn geometry_dops() {
+        let mut g = OMatrix::<f64, U8, U8>::identity();
+        let mut y = OVector::<f64, U8>::zeros();
+        let los = [
+            (0.0, 0.0, -1.0),
+            (0.6, 0.0, -0.8),
+            (0.0, 0.6, -0.8),
+            (-0.6, -0.6, -0.5),
+        ];
+        for (i, (x, y_i, z)) in los.iter().enumerate() {
+            g[(i, 0)] = *x;
+            g[(i, 1)] = *y_i;
+            g[(i, 2)] = *z;
+            g[(i, 3)] = 1.0;
+            y[i] = 1.0E3 + i as f64;
+        }
+        let input = Input {
+            y,
+            g,
+            w: OMatrix::<f64, U8, U8>::identity(),
+            sv: BTreeMap::new(),
+            rows: 4,
+            clock_only: false,
+            max_innovation_sigma: None,
+            decomposition: Decomposition::Normal,
+            prior_information: None,
+            constraint_information: None,
+        };
+
+        let output = Filter::lsq_resolve(&input, None).unwrap();
+        let q = (g.transpose() * g).try_inverse().unwrap();
+
+        let tdop = q[(3, 3)].sqrt();
+        let pdop = (q[(0, 0)] + q[(1, 1)] + q[(2, 2)]).sqrt();
+        assert!(tdop > 0.0);
+        assert!((output.tdop() - tdop).abs() < 1.0E-5);
+        assert!((output.pdop() - pdop).abs() < 1.0E-5);
+        assert!((output.gdop() - (pdop.powi(2) + tdop.powi(2)).sqrt()).abs() < 1.0E-5);
+
+        // DOPs reported by the solution are the validated ones
+        let q = output.q_covar4x4();
+        assert_eq!(solutions::gdop(&q), output.gdop());
+        assert_eq!(solutions::pdop(&q), output.pdop());
+        assert_eq!(solutions::tdop(&q), output.tdop());
+    }
+    #[test]
+    fn single_precision_least_squares() {
+        let mut g = OMatrix::<f64, U8, U8>::identity();
+        let mut y = OVector::<f64, U8>::zeros();
+        let los = [
+            (0.0, 0.0, -1.0),
+            (0.6, 0.0, -0.8),
+            (0.0, 0.6, -0.8),
+            (-0.6, -0.6, -0.5),
+        ];
+        for (i, (x, y_i, z)) in los.iter().enumerate() {
+            g[(i, 0)] = *x;
+            g[(i, 1)] = *y_i;
+            g[(i, 2)] = *z;
+            g[(i, 3)] = 1.0;
+            y[i] = 1.0E3 + i as f64;
+        }
+        let w = OMatrix::<f64, U8, U8>::identity() * 0.25;
+
+        let (q, _, x) = Filter::weighted_least_squares(g, w, y, Decomposition::Normal).unwrap();
+        let (q_f32, _, x_f32) = Filter::weighted_least_squares(
+            g.cast::<f32>(),
+            w.cast::<f32>(),
+            y.cast::<f32>(),
+            Decomposition::Normal,
+        )
+        .unwrap();
+
+        // sub meter agreement
+        assert!((x - x_f32.cast::<f64>()).norm() < 0.1);
+        assert!((q - q_f32.cast::<f64>()).norm() < 1.0E-3);
+    }
+    #[test]
+    fn least_squares_decompositions() {
+        let mut g = OMatrix::<f64, U8, U8>::identity();
+        let mut y = OVector::<f64, U8>::zeros();
+        let los = [
+            (0.0, 0.0, -1.0),
+            (0.6, 0.0, -0.8),
+            (0.0, 0.6, -0.8),
+            (-0.6, -0.6, -0.5),
+        ];
+        for (i, (x, y_i, z)) in los.iter().enumerate() {
+            g[(i, 0)] = *x;
+            g[(i, 1)] = *y_i;
+            g[(i, 2)] = *z;
+            g[(i, 3)] = 1.0;
+            y[i] = 1.0E3 + i as f64;
+        }
+        let mut w = OMatrix::<f64, U8, U8>::identity();
+        w[(1, 1)] = 0.25;
+
+        let (q, p, x) = Filter::weighted_least_squares(g, w, y, Decomposition::Normal).unwrap();
+        for decomposition in [Decomposition::QR, Decomposition::SVD] {
+            let (q_d, p_d, x_d) = Filter::weighted_least_squares(g, w, y, decomposition).unwrap();
+            assert!((x - x_d).norm() < 1.0E-6, "{:?}", decomposition);
+            assert!((q - q_d).norm() < 1.0E-9, "{:?}", decomposition);
+            assert!((p - p_d).norm() < 1.0E-9, "{:?}", decomposition);
+        }
+
+        // degenerate geometry (no east/west observability)
+        for i in 0..4 {
+            g[(i, 0)] = 0.0;
+        }
+        assert!(Filter::weighted_least_squares(g, w, y, Decomposition::Normal).is_err());
+        let (_, _, x) = Filter::weighted_least_squares(g, w, y, Decomposition::SVD).unwrap();
+        assert!(x[0].abs() < 1.0E-9);
+    }
+    #[test]
+    fn constrained_least_squares() {
+        let mut g = OMatrix::<f64, U8, U8>::identity();
+        let mut y = OVector::<f64, U8>::zeros();
+        // degenerate geometry (no east/west observability)
+        let los = [
+            (0.0, 0.0, -1.0),
+            (0.0, 0.0, -0.8),
+            (0.0, 0.6, -0.8),
+            (0.0, -0.6, -0.5),
+        ];
+        for (i, (x, y_i, z)) in los.iter().enumerate() {
+            g[(i, 0)] = *x;
+            g[(i, 1)] = *y_i;
+            g[(i, 2)] = *z;
+            g[(i, 3)] = 1.0;
+            y[i] = 1.0E3 + i as f64;
+        }
+        let mut input = Input {
+            y,
+            g,
+            w: OMatrix::<f64, U8, U8>::identity(),
+            sv: BTreeMap::new(),
+            rows: 4,
+            clock_only: false,
+            max_innovation_sigma: None,
+            decomposition: Decomposition::Normal,
+            prior_information: None,
+            constraint_information: None,
+        };
+        assert!(Filter::lsq_resolve(&input, None).is_err());
+
+        // apriori: 2 m east of the linearization point, 1 m sigma
+        let mut info = OMatrix::<f64, U8, U8>::zeros();
+        let mut info_x = OVector::<f64, U8>::zeros();
+        for i in 0..3 {
+            info[(i, i)] = 1.0;
+        }
+        info_x[0] = 2.0;
+        input.prior_information = Some((info, info_x));
+
+        let output = Filter::lsq_resolve(&input, None).unwrap();
+        let x = output.state.estimate();
+        assert!((x[0] - 2.0).abs() < 1.0E-9);
+
+        // identical to the information form of the normal equations
+        let p = (g.transpose() * g + info).try_inverse().unwrap();
+        let x_ref = p * (g.transpose() * y + info_x);
+        assert!((x - x_ref).norm() < 1.0E-6);
+    }
+    #[test]
+    fn constraint_information_update() {
+        let x = OVector::<f64, U8>::zeros();
+        let p = OMatrix::<f64, U8, U8>::identity() * 100.0;
+
+        // vertical pseudo-observation: 3 m, 0.5 m sigma
+        let mut input = Input {
+            y: OVector::<f64, U8>::zeros(),
+            g: OMatrix::<f64, U8, U8>::zeros(),
+            w: OMatrix::<f64, U8, U8>::zeros(),
+            sv: BTreeMap::new(),
+            rows: 1,
+            clock_only: false,
+            max_innovation_sigma: None,
+            decomposition: Decomposition::Normal,
+            prior_information: None,
+            constraint_information: None,
+        };
+        let weight = 0.5_f64.powi(-2);
+        let mut info = OMatrix::<f64, U8, U8>::zeros();
+        let mut info_x = OVector::<f64, U8>::zeros();
+        info[(2, 2)] = weight;
+        info_x[2] = 3.0 * weight;
+        let (x_c, p_c) = Filter::information_update(&input, x, p, &info, &info_x).unwrap();
+
+        // identical to the (scalar) measurement update
+        input.y[0] = 3.0;
+        input.g[(0, 2)] = 1.0;
+        input.w[(0, 0)] = weight;
+        let (x_ref, p_ref) = Filter::kf_sequential_update(&input, x, p).unwrap();
+        assert!((x_c - x_ref).norm() < 1.0E-9);
+        assert!((p_c - p_ref).norm() < 1.0E-9);
+        assert!((x_c[2] - 3.0).abs() < 0.01);
+    }
+    #[test]
+    fn kf_sequential_update() {
+        let mut g = OMatrix::<f64, U8, U8>::zeros();
+        let (mut y, mut w) = (OVector::<f64, U8>::zeros(), OMatrix::<f64, U8, U8>::zeros());
+        let los = [
+            (0.0, 0.0, -1.0),
+            (0.6, 0.0, -0.8),
+            (0.0, 0.6, -0.8),
+            (-0.6, -0.6, -0.5),
+            (0.3, -0.7, -0.6),
+        ];
+        for (i, (x, y_i, z)) in los.iter().enumerate() {
+            g[(i, 0)] = *x;
+            g[(i, 1)] = *y_i;
+            g[(i, 2)] = *z;
+            g[(i, 3)] = 1.0;
+            y[i] = 1.0 + i as f64;
+            w[(i, i)] = 1.0 / (i as f64 + 1.0);
+        }
+        let mut input = Input {
+            y,
+            g,
+            w,
+            sv: BTreeMap::new(),
+            rows: 5,
+            clock_only: false,
+            max_innovation_sigma: None,
+            decomposition: Decomposition::Normal,
+            prior_information: None,
+            constraint_information: None,
+        };
+
+        let x_bn = OVector::<f64, U8>::zeros();
+        let p_bn = OMatrix::<f64, U8, U8>::identity() * 100.0;
+
+        // identical to the batch (information form) update
+        let p_bn_inv = p_bn.try_inverse().unwrap();
+        let p_n = (g.transpose() * w * g + p_bn_inv).try_inverse().unwrap();
+        let x_n = p_n * (g.transpose() * w * y + p_bn_inv * x_bn);
+
+        let (x, p) = Filter::kf_sequential_update(&input, x_bn, p_bn).unwrap();
+        assert!((x - x_n).norm() < 1.0E-9);
+        assert!((p - p_n).norm() < 1.0E-9);
+
+        // outlier: rejected by the innovation gate
+        input.y[0] = 1.0E3;
+        input.max_innovation_sigma = Some(3.0);
+        let (x, _) = Filter::kf_sequential_update(&input, x_bn, p_bn).unwrap();
+
+        input.w[(0, 0)] = 0.0;
+        input.max_innovation_sigma = None;
+        let (x_ref, _) = Filter::kf_sequential_update(&input, x_bn, p_bn).unwrap();
+        assert!((x - x_ref).norm() < 1.0E-9);
+    }
+
+    #[test]
+    fn filter_view() {
+        let mut p = OMatrix::<f64, U8, U8>::from_diagonal_element(4.0);
+        p[(0, 3)] = 1.5;
+        p[(3, 0)] = 1.5;
+        let x = OVector::<f64, U8>::from_iterator((0..8).map(|i| i as f64));
+        let view = FilterState::Lsq(LSQState { x, p }).view();
+
+        assert_eq!(view.filter, Filter::LSQ);
+        assert_eq!(view.states.len(), 8);
+        assert_eq!(view.states[0], (StateLabel::DeltaX, 0.0));
+        assert_eq!(view.states[3], (StateLabel::ClockOffset, 3.0));
+        assert_eq!(view.states[7], (StateLabel::Ambiguity(3), 7.0));
+
+        assert_eq!(view.value(StateLabel::DeltaZ), Some(2.0));
+        assert_eq!(view.sigma(StateLabel::Ambiguity(0)), Some(2.0));
+        assert_eq!(
+            view.covariance_of(StateLabel::DeltaX, StateLabel::ClockOffset),
+            Some(1.5)
+        );
+        assert!(view.value(StateLabel::Ambiguity(4)).is_none());
+        assert_eq!(StateLabel::Ambiguity(1).to_string(), "amb(1)");
+    }
+}
@@ -0,0 +1,174 @@
+//! Line of sight geometry, resolved for all SV at once
+use crate::coordinates::Ellipsoid;
+
+/// SV processed together: fixed size batches are autovectorized
+const LANES: usize = 4;
+
+/// [LineOfSight] from the apriori position to one SV
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub(crate) struct LineOfSight {
+    /// Geometric range [m]
+    pub range_m: f64,
+    /// Unit vector, from the SV to the apriori position (ECEF)
+    pub unit: (f64, f64, f64),
+    /// Elevation [°]
+    pub elevation_deg: f64,
+    /// Azimuth [°], in [0, 360[
+    pub azimuth_deg: f64,
+}
+
+/// [Geometry] of all SV, in structure of arrays layout:
+/// computations are batched in [LANES] wide blocks (no branching),
+/// which makes them amenable to autovectorization on large constellations.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Geometry {
+    range_m: Vec<f64>,
+    unit_x: Vec<f64>,
+    unit_y: Vec<f64>,
+    unit_z: Vec<f64>,
+    elevation_rad: Vec<f64>,
+    azimuth_rad: Vec<f64>,
+    /// SV to apriori vectors (scratch)
+    dx: Vec<f64>,
+    dy: Vec<f64>,
+    dz: Vec<f64>,
+}
+
+impl Geometry {
+    /// Resolves the [Geometry] between the apriori position and these SV positions,
+    /// ECEF [m]. Unresolved SV positions are NaN.
+    #[cfg(test)]
+    pub fn new(apriori_m: (f64, f64, f64), sv_m: &[(f64, f64, f64)]) -> Self {
+        let mut geometry = Self::default();
+        geometry.resolve(apriori_m, sv_m);
+        geometry
+    }
+    /// Resolves the [Geometry] in place (see [Self::new]), reusing the previous allocations
+    pub fn resolve(&mut self, apriori_m: (f64, f64, f64), sv_m: &[(f64, f64, f64)]) {
+        let (x0_m, y0_m, z0_m) = apriori_m;
+        let (lat0, lon0, _) = Ellipsoid::WGS84.ecef_to_geodetic(apriori_m);
+        let (s_lat, c_lat) = lat0.sin_cos();
+        let (s_lon, c_lon) = lon0.sin_cos();
+
+        let len = sv_m.len();
+        let padded = len.div_ceil(LANES) * LANES;
+
+        self.resize(padded);
+
+        // SV to apriori vectors
+        for (i, (x, y, z)) in sv_m.iter().enumerate() {
+            self.dx[i] = x0_m - x;
+            self.dy[i] = y0_m - y;
+            self.dz[i] = z0_m - z;
+        }
+
+        for block in (0..padded).step_by(LANES) {
+            let range = block..block + LANES;
+            let (dx, dy, dz) = (
+                &self.dx[range.clone()],
+                &self.dy[range.clone()],
+                &self.dz[range.clone()],
+            );
+
+            let mut rho = [0.0; LANES];
+            let (mut e, mut n, mut u) = ([0.0; LANES], [0.0; LANES], [0.0; LANES]);
+            for k in 0..LANES {
+                rho[k] = (dx[k] * dx[k] + dy[k] * dy[k] + dz[k] * dz[k]).sqrt();
+                // apriori to SV, in local frame
+                e[k] = s_lon * dx[k] - c_lon * dy[k];
+                n[k] = s_lat * c_lon * dx[k] + s_lat * s_lon * dy[k] - c_lat * dz[k];
+                u[k] = -c_lat * c_lon * dx[k] - c_lat * s_lon * dy[k] - s_lat * dz[k];
+            }
+
+            for k in 0..LANES {
+                let i = block + k;
+                self.range_m[i] = rho[k];
+                self.unit_x[i] = dx[k] / rho[k];
+                self.unit_y[i] = dy[k] / rho[k];
+                self.unit_z[i] = dz[k] / rho[k];
+                self.elevation_rad[i] = u[k].atan2((e[k] * e[k] + n[k] * n[k]).sqrt());
+                self.azimuth_rad[i] = e[k].atan2(n[k]);
+            }
+        }
+
+        self.truncate(len);
+    }
+    /// Zero fills all buffers to this length
+    fn resize(&mut self, len: usize) {
+        for buffer in [
+            &mut self.range_m,
+            &mut self.unit_x,
+            &mut self.unit_y,
+            &mut self.unit_z,
+            &mut self.elevation_rad,
+            &mut self.azimuth_rad,
+            &mut self.dx,
+            &mut self.dy,
+            &mut self.dz,
+        ] {
+            buffer.clear();
+            buffer.resize(len, 0.0);
+        }
+    }
+    fn truncate(&mut self, len: usize) {
+        self.range_m.truncate(len);
+        self.unit_x.truncate(len);
+        self.unit_y.truncate(len);
+        self.unit_z.truncate(len);
+        self.elevation_rad.truncate(len);
+        self.azimuth_rad.truncate(len);
+    }
+    /// Returns [LineOfSight] of the i-th SV, if resolved
+    pub fn line_of_sight(&self, i: usize) -> Option<LineOfSight> {
+        let range_m = *self.range_m.get(i)?;
+        if !range_m.is_normal() {
+            return None;
+        }
+        Some(LineOfSight {
+            range_m,
+            unit: (self.unit_x[i], self.unit_y[i], self.unit_z[i]),
+            elevation_deg: self.elevation_rad[i].to_degrees(),
+            azimuth_deg: self.azimuth_rad[i].to_degrees().rem_euclid(360.0),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Geometry;
+    use map_3d::{ecef2aer, ecef2geodetic, Ellipsoid};
+
+    #[test]
+    fn batched_geometry() {
+        let apriori = (4_696_989.0, 723_994.0, 4_239_678.0);
+        let sv = [
+            (15_365_952.0, 3_511_123.0, 21_357_812.0),
+            (10_753_335.0, -10_639_797.0, 21_563_016.0),
+            (f64::NAN, f64::NAN, f64::NAN),
+            (24_874_816.0, 8_682_108.0, 5_229_218.0),
+            (-3_456_639.0, 15_457_103.0, 21_135_363.0),
+            (18_348_215.0, -17_474_149.0, 6_143_172.0),
+        ];
+
+        let geometry = Geometry::new(apriori, &sv);
+        assert!(geometry.line_of_sight(2).is_none());
+        assert!(geometry.line_of_sight(sv.len()).is_none());
+
+        let (lat0, lon0, alt0) = ecef2geodetic(apriori.0, apriori.1, apriori.2, Ellipsoid::WGS84);
+
+        for (i, (x, y, z)) in sv.iter().enumerate() {
+            if x.is_nan() {
+                continue;
+            }
+            let los = geometry.line_of_sight(i).unwrap();
+            let rho = ((x - apriori.0).powi(2) + (y - apriori.1).powi(2) + (z - apriori.2).powi(2))
+                .sqrt();
+            assert!((los.range_m - rho).abs() < 1.0E-6);
+            assert!((los.unit.0 - (apriori.0 - x) / rho).abs() < 1.0E-12);
+
+            let (az, el, _) = ecef2aer(*x, *y, *z, lat0, lon0, alt0, Ellipsoid::WGS84);
+            assert!((los.elevation_deg - el.to_degrees()).abs() < 1.0E-6);
+            assert!((los.azimuth_deg - az.to_degrees().rem_euclid(360.0)).abs() < 1.0E-6);
+        }
+    }
+}
@@ -0,0 +1,58 @@
+//! Linear algebra backend of the solve path: nalgebra by default,
+//! faer with the `faer` feature (for targets where it benchmarks faster).
+use nalgebra::{base::dimension::U8, OMatrix, RealField};
+
+/// Inverts this symmetric positive definite (normal) matrix
+#[cfg(not(feature = "faer"))]
+pub(crate) fn spd_inverse<T: RealField + Copy>(
+    m: OMatrix<T, U8, U8>,
+) -> Option<OMatrix<T, U8, U8>> {
+    m.try_inverse()
+}
+
+/// Inverts this symmetric positive definite (normal) matrix,
+/// by Cholesky decomposition. Computations are always double precision.
+#[cfg(feature = "faer")]
+pub(crate) fn spd_inverse<T: RealField + Copy>(
+    m: OMatrix<T, U8, U8>,
+) -> Option<OMatrix<T, U8, U8>> {
+    use nalgebra::{convert, convert_unchecked};
+
+    let mat = faer::Mat::<f64>::from_fn(8, 8, |i, j| convert_unchecked::<T, f64>(m[(i, j)]));
+    let inv = mat.cholesky(faer::Side::Lower).ok()?.inverse();
+    Some(OMatrix::<T, U8, U8>::from_fn(|i, j| {
+        convert::<f64, T>(inv.read(i, j))
+    }))
+}
+
+#[cfg(test)]
+mod test {
+    use super::spd_inverse;
+    use nalgebra::{base::dimension::U8, OMatrix};
+
+    #[test]
+    fn normal_matrix_inverse() {
+        let mut g = OMatrix::<f64, U8, U8>::identity();
+        let los = [
+            (0.0, 0.0, -1.0),
+            (0.6, 0.0, -0.8),
+            (0.0, 0.6, -0.8),
+            (-0.6, -0.6, -0.5),
+        ];
+        for (i, (x, y, z)) in los.iter().enumerate() {
+            g[(i, 0)] = *x;
+            g[(i, 1)] = *y;
+            g[(i, 2)] = *z;
+            g[(i, 3)] = 1.0;
+        }
+        let n = g.transpose() * g;
+        let q = spd_inverse(n).unwrap();
+        assert!((q * n - OMatrix::<f64, U8, U8>::identity()).norm() < 1.0E-9);
+
+        let q_f32 = spd_inverse(n.cast::<f32>()).unwrap();
+        assert!((q - q_f32.cast::<f64>()).norm() < 1.0E-3);
+
+        // not invertible
+        assert!(spd_inverse(OMatrix::<f64, U8, U8>::zeros()).is_none());
+    }
+}
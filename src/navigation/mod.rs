@@ -1,30 +1,34 @@
 pub mod solutions;
 pub use solutions::{InvalidationCause, PVTSolution, PVTSolutionType};
 
+mod aiding;
+mod batch;
 mod filter;
+mod geometry;
+mod linalg;
+mod workspace;
 
-pub use filter::Filter;
+pub use aiding::Aiding;
+pub(crate) use aiding::AidingFilter;
+pub use batch::{BatchSolution, StaticBatch};
 pub(crate) use filter::FilterState;
+pub use filter::{Decomposition, Filter, FilterSnapshot, FilterView, StateLabel};
+pub(crate) use geometry::{Geometry, LineOfSight};
+pub(crate) use workspace::Workspace;
 
-use log::{
+use crate::logging::{
     debug,
     //error,
-    warn,
+    //warn,
 };
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 use crate::{
     ambiguity::Ambiguities,
     candidate::Candidate,
     cfg::Config,
     // constants::Constants,
-    prelude::{
-        Duration,
-        Error,
-        IonosphereBias, //Method,
-        Orbit,
-        SV,
-    },
+    prelude::{Duration, Error, ErrorKind, IonosphereBias, Orbit, SV},
 };
 
 use nalgebra::{
@@ -49,6 +53,9 @@ pub struct SVInput {
     pub iono_bias: Option<IonosphereBias>,
     /// Correction to said constellation, expressed as [Duration]
     pub clock_correction: Option<Duration>,
+    /// Post-fit (weighted) pseudo range residual in meters,
+    /// as monitored when validating the solution
+    pub residual_m: Option<f64>,
 }
 
 /// Navigation Input
@@ -61,18 +68,26 @@ pub struct Input {
     /// Weight Diagonal Matrix
     pub w: OMatrix<f64, U8, U8>,
     /// SV dependent data
-    pub sv: HashMap<SV, SVInput>,
+    pub sv: BTreeMap<SV, SVInput>,
+    /// Number of contributing vehicles (rows)
+    pub rows: usize,
+    /// Position is held fixed: only the clock is to be resolved
+    pub clock_only: bool,
+    /// Innovation gate (Kalman filter), in standard deviations
+    pub max_innovation_sigma: Option<f64>,
+    /// [Decomposition] used by the least squares
+    pub decomposition: Decomposition,
+    /// Prior information (information matrix, information vector) on the state,
+    /// applied as a soft constraint when no previous state exists
+    pub prior_information: Option<(OMatrix<f64, U8, U8>, OVector<f64, U8>)>,
+    /// Constraints (pseudo-observations) on the state, in information form,
+    /// applied at every resolution
+    pub constraint_information: Option<(OMatrix<f64, U8, U8>, OVector<f64, U8>)>,
 }
 
 /// Navigation Output
 #[derive(Debug, Clone, Default)]
 pub(crate) struct Output {
-    /// Time Dilution of Precision
-    pub tdop: f64,
-    /// Geometric Dilution of Precision
-    pub gdop: f64,
-    /// Position Dilution of Precision
-    pub pdop: f64,
     /// Q covariance matrix
     pub q: OMatrix<f64, U8, U8>,
     /// Filter state
@@ -100,6 +115,32 @@ impl Output {
             self.q[(3, 3)],
         )
     }
+    /// Geometric Dilution of Precision, as reported by the [PVTSolution]
+    pub(crate) fn gdop(&self) -> f64 {
+        solutions::gdop(&self.q_covar4x4())
+    }
+    /// Position Dilution of Precision, as reported by the [PVTSolution]
+    pub(crate) fn pdop(&self) -> f64 {
+        solutions::pdop(&self.q_covar4x4())
+    }
+    /// Time Dilution of Precision, as reported by the [PVTSolution]
+    pub(crate) fn tdop(&self) -> f64 {
+        solutions::tdop(&self.q_covar4x4())
+    }
+}
+
+/// Sums these (optional) information (information matrix, information vector)
+pub(crate) fn sum_information(
+    lhs: Option<(OMatrix<f64, U8, U8>, OVector<f64, U8>)>,
+    rhs: Option<(OMatrix<f64, U8, U8>, OVector<f64, U8>)>,
+) -> Option<(OMatrix<f64, U8, U8>, OVector<f64, U8>)> {
+    match (lhs, rhs) {
+        (Some((info, info_x)), Some((rhs_info, rhs_info_x))) => {
+            Some((info + rhs_info, info_x + rhs_info_x))
+        },
+        (Some(information), None) | (None, Some(information)) => Some(information),
+        (None, None) => None,
+    }
 }
 
 impl Input {
@@ -108,26 +149,13 @@ impl Input {
         apriori: (f64, f64, f64),
         cfg: &Config,
         cd: &[Candidate],
-        w: OMatrix<f64, U8, U8>,
+        mut w: OMatrix<f64, U8, U8>,
         _: &Ambiguities,
+        workspace: &mut Workspace,
     ) -> Result<Self, Error> {
         let mut y = OVector::<f64, U8>::zeros();
         let mut g = OMatrix::<f64, U8, U8>::zeros();
-        let mut sv = HashMap::<SV, SVInput>::with_capacity(cd.len());
-        /*
-         * Compensate for ARP (if possible)
-         */
-        let apriori = match cfg.arp_enu {
-            Some(_) => {
-                //apriori.0 + offset.0,
-                //apriori.1 + offset.1,
-                //apriori.2 + offset.2,
-                warn!("ARP compensation is not feasible yet");
-                apriori
-            },
-            None => apriori,
-        };
-
+        let mut sv = BTreeMap::<SV, SVInput>::new();
         let mut j = 0;
         let mut max = match cfg.sol_type {
             PVTSolutionType::TimeOnly => 1,
@@ -137,13 +165,53 @@ impl Input {
             max -= 1;
         }
 
+        // latest candidate rejection, reported when too few rows remain
+        let mut rejected = Option::<Error>::None;
+
+        // line of sight geometry, resolved for all SV at once
+        workspace.sv_m.clear();
+        workspace.sv_m.extend(cd.iter().map(|cd| match cd.orbit {
+            Some(orbit) => (
+                orbit.radius_km.x * 1.0E3,
+                orbit.radius_km.y * 1.0E3,
+                orbit.radius_km.z * 1.0E3,
+            ),
+            None => (f64::NAN, f64::NAN, f64::NAN),
+        }));
+        workspace.geometry.resolve(apriori, &workspace.sv_m);
+        let geometry = &workspace.geometry;
+
         for i in 0..cd.len() {
-            match cd[i].matrix_contribution(cfg, j, &mut y, &mut g, apriori) {
+            #[cfg(feature = "tracing")]
+            let _span =
+                tracing::debug_span!("candidate", sv = %cd[i].sv, stage = "navigation").entered();
+
+            let los = geometry.line_of_sight(i);
+            match cd[i].matrix_contribution(cfg, j, &mut y, &mut g, apriori, los) {
                 Ok(input) => {
-                    sv.insert(cd[i].sv, input);
                     g[(4 + j, 4 + j)] = 1.0_f64;
                     y[4 + j] = y[j];
 
+                    // a-priori measurement noise
+                    let code_sigma = cd[i].code_sigma(cfg);
+                    let phase_sigma = cd[i].phase_sigma(cfg);
+                    w[(j, j)] /= code_sigma.powi(2);
+                    w[(4 + j, 4 + j)] /= phase_sigma.powi(2);
+
+                    #[cfg(feature = "tracing")]
+                    debug!(
+                        row = j,
+                        elevation_deg = input.elevation,
+                        azimuth_deg = input.azimuth,
+                        prefit_residual_m = y[j],
+                        tropo_bias_m = input.tropo_bias,
+                        iono_bias_m = input.iono_bias.map(|bias| bias.value()),
+                        code_sigma_m = code_sigma,
+                        "contribution"
+                    );
+
+                    sv.insert(cd[i].sv, input);
+
                     j += 1;
                     if j == cd.len() {
                         break;
@@ -151,6 +219,7 @@ impl Input {
                 },
                 Err(e) => {
                     debug!("{}({}) cannot contribute: {}", cd[i].t, cd[i].sv, e);
+                    rejected = Some(Error::new(e).with_sv(cd[i].sv));
                     continue;
                 },
             }
@@ -162,7 +231,7 @@ impl Input {
             //    if cfg.method == Method::PPP {
             //        let cmb = cd[i]
             //            .phase_if_combination()
-            //            .ok_or(Error::PhaseRangeCombination)?;
+            //            .ok_or(ErrorKind::PhaseRangeCombination)?;
 
             //        let f_1 = cmb.rhs.frequency();
             //        let lambda_j = cmb.lhs.wavelength();
@@ -186,7 +255,7 @@ impl Input {
             //                "{} ({}/{}): unresolved ambiguity",
             //                cd[i].t, cd[i].sv, cmb.rhs
             //            );
-            //            return Err(Error::UnresolvedAmbiguity);
+            //            return Err(ErrorKind::UnresolvedAmbiguity);
             //        };
 
             //        // TODO: conclude windup
@@ -197,6 +266,18 @@ impl Input {
             //}
         }
 
+        let required = if cfg.sol_type == PVTSolutionType::FixedPosition {
+            1
+        } else {
+            max
+        };
+
+        if j < required {
+            if let Some(e) = rejected {
+                return Err(e);
+            }
+        }
+
         // TODO: improve matrix formation
         if max == 3 {
             y[3] = y[2];
@@ -225,7 +306,18 @@ impl Input {
         }
 
         debug!("y: {} g: {}, w: {}", y, g, w);
-        Ok(Self { y, g, w, sv })
+        Ok(Self {
+            y,
+            g,
+            w,
+            sv,
+            rows: j,
+            clock_only: cfg.sol_type == PVTSolutionType::FixedPosition,
+            max_innovation_sigma: cfg.solver.max_innovation_sigma,
+            decomposition: cfg.solver.decomposition,
+            prior_information: None,
+            constraint_information: None,
+        })
     }
 }
 
@@ -248,12 +340,80 @@ impl Navigation {
         self.filter_state = None;
         self.pending = Default::default();
     }
-    pub fn resolve(&mut self, input: &Input) -> Result<Output, Error> {
-        let out = self.filter.resolve(input, self.filter_state.clone())?;
+    pub fn resolve(&mut self, input: &Input) -> Result<Output, ErrorKind> {
+        let out = if input.clock_only {
+            Filter::clock_resolve(input)?
+        } else {
+            self.filter.resolve(input, self.filter_state.clone())?
+        };
         self.pending = out.clone();
         Ok(out)
     }
     pub fn validate(&mut self) {
         self.filter_state = Some(self.pending.state.clone());
     }
+    /// Returns latest validated [FilterState].
+    /// None when the filter is bypassed ([Filter::None]): its state is never used.
+    pub fn state(&self) -> Option<&FilterState> {
+        if self.filter == Filter::None {
+            return None;
+        }
+        self.filter_state.as_ref()
+    }
+    /// Restores [FilterState] (warm start)
+    pub fn restore(&mut self, state: FilterState) {
+        self.filter_state = Some(state);
+        self.pending = Default::default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Input, Workspace};
+    use crate::prelude::{
+        Candidate, Carrier, Config, Epoch, ErrorKind, Observation, Orbit, EARTH_ITRF93, SV,
+    };
+    use nalgebra::{base::dimension::U8, OMatrix};
+    use std::str::FromStr;
+
+    #[test]
+    fn rejection_carries_sv() {
+        let t = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let apriori = (4_696_989.0, 723_994.0, 4_239_678.0);
+        let positions_km = [
+            (15_365.952, 3_511.123, 21_357.812),
+            (10_753.335, -10_639.797, 21_563.016),
+            (20_000.000, 12_000.000, 12_000.000),
+            (-2_000.000, 16_000.000, 20_500.000),
+        ];
+
+        // orbits are resolved, but clock corrections are missing
+        let pool = positions_km
+            .iter()
+            .enumerate()
+            .map(|(i, (x_km, y_km, z_km))| {
+                let sv = SV::from_str(&format!("G{:02}", i + 1)).unwrap();
+                let mut cd = Candidate::new(
+                    sv,
+                    t,
+                    vec![Observation::pseudo_range(Carrier::L1, 20.0E6, None)],
+                );
+                cd.set_orbit(Orbit::from_position(*x_km, *y_km, *z_km, t, EARTH_ITRF93));
+                cd
+            })
+            .collect::<Vec<_>>();
+
+        let e = Input::new(
+            apriori,
+            &Config::default(),
+            &pool,
+            OMatrix::<f64, U8, U8>::identity(),
+            &Default::default(),
+            &mut Workspace::default(),
+        )
+        .unwrap_err();
+
+        assert_eq!(e.kind(), &ErrorKind::UnknownClockCorrection);
+        assert_eq!(e.sv(), Some(SV::from_str("G04").unwrap()));
+    }
 }
@@ -1,7 +1,12 @@
 //! PVT Solutions
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
-use crate::prelude::{Ambiguities, Carrier, Duration, Orbit, TimeScale, SV};
+use crate::{
+    coordinates::{ecef_to_enu_matrix, Ellipsoid},
+    prelude::{
+        Ambiguities, Carrier, Duration, Orbit, ReferenceFrame, TimeScale, UTMCoordinates, SV,
+    },
+};
 
 use super::SVInput;
 use nalgebra::base::{Matrix3, Matrix4};
@@ -11,13 +16,13 @@ pub use validator::InvalidationCause;
 
 /// InstrumentBias, estimated per SV and signal for each solution (ie., in Time),
 /// when navigation is based on Phase Range observations.
-pub type InstrumentBias = HashMap<(SV, Carrier), f64>;
+pub type InstrumentBias = BTreeMap<(SV, Carrier), f64>;
 
 #[cfg(feature = "serde")]
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Copy, Clone, PartialEq, Default)]
-#[cfg_attr(feature = "serde", derive(Deserialize))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum PVTSolutionType {
     /// Default, complete solution with Position,
     /// Velocity and Time components. Requires either
@@ -27,6 +32,12 @@ pub enum PVTSolutionType {
     PositionVelocityTime,
     /// Resolve Time component only. Only requires 1 vehicle in sight.
     TimeOnly,
+    /// Position is held fixed at the apriori coordinates (reference stations,
+    /// timing receivers): only the clock is estimated, from the 4 vehicles (at most)
+    /// with the highest elevation. Other vehicles in sight do not contribute.
+    /// Residuals are still monitored and the position DOPs are reported when 4 vehicles contribute.
+    /// Only requires 1 vehicle in sight, once the apriori position is known.
+    FixedPosition,
 }
 
 impl std::fmt::Display for PVTSolutionType {
@@ -37,10 +48,32 @@ impl std::fmt::Display for PVTSolutionType {
         match self {
             Self::PositionVelocityTime => write!(f, "PVT"),
             Self::TimeOnly => write!(f, "TimeOnly"),
+            Self::FixedPosition => write!(f, "FixedPosition"),
         }
     }
 }
 
+/// Geometric Dilution of Precision, from the geometry matrix (ECEF position, clock).
+/// Null when the position is not resolved (time only geometry).
+pub(crate) fn gdop(q: &Matrix4<f64>) -> f64 {
+    let pdop = pdop(q);
+    if pdop > 0.0 {
+        (pdop.powi(2) + q[(3, 3)]).sqrt()
+    } else {
+        0.0
+    }
+}
+
+/// Position Dilution of Precision, from the geometry matrix (ECEF position, clock)
+pub(crate) fn pdop(q: &Matrix4<f64>) -> f64 {
+    (q[(0, 0)] + q[(1, 1)] + q[(2, 2)]).sqrt()
+}
+
+/// Time Dilution of Precision, from the geometry matrix (ECEF position, clock)
+pub(crate) fn tdop(q: &Matrix4<f64>) -> f64 {
+    q[(3, 3)].sqrt()
+}
+
 /// PVT Solution, always expressed as the correction to apply
 /// to an Apriori / static position.
 #[derive(Debug, Clone)]
@@ -48,6 +81,8 @@ impl std::fmt::Display for PVTSolutionType {
 pub struct PVTSolution {
     /// Receiver state, expressed as ECEF [Orbit]
     pub state: Orbit,
+    /// [ReferenceFrame] of the receiver state, prior the `datum` transformation (if any)
+    pub frame: ReferenceFrame,
     /// Timescale
     pub timescale: TimeScale,
     /// Offset to timescale
@@ -55,23 +90,25 @@ pub struct PVTSolution {
     /// Drift from timescale in [s/s]
     pub d_dt: f64,
     /// Space Vehicles that helped form this solution
-    /// and data associated to each individual SV
-    pub sv: HashMap<SV, SVInput>,
-    /// Geometric Dilution of Precision
-    pub gdop: f64,
-    /// Time Dilution of Precision
-    pub tdop: f64,
-    /// Position Dilution of Precision
-    pub pdop: f64,
+    /// and data associated to each individual SV, sorted by [SV]
+    pub sv: BTreeMap<SV, SVInput>,
     /// Resolved ambiguities (at this point and time), per SV and signal.
     /// Ambiguities are null if navigation does not use them (see [Method]).
     /// This is useful for advanced applications that want or need this level of detail.
     pub ambiguities: Ambiguities,
+    /// Clock holdover: [Duration] elapsed since the latest resolved solution,
+    /// when this solution was obtained by propagating the clock model
+    /// (not enough vehicles in sight). The position is then held (or dead reckoned, see [Aiding](crate::prelude::Aiding)),
+    /// and the longer the holdover, the less accurate the clock offset. `None` for resolved solutions.
+    pub holdover: Option<Duration>,
+    /// GPS to Galileo time offset (GST - GPST) estimate, when estimated (see `estimate_ggto`)
+    /// and both constellations contributed. Galileo clocks were then expressed in GPST.
+    pub ggto: Option<Duration>,
     // // Instrument bias, determined from Phase Range based Navigation (see [Method])
     // // and internal signal ambiguity solving. If Navigation [Method] is not based on Phase Range,
     // // the bias cannot be estimated (null). This is useful for advanced applications that want or need this level of detail.
     // pub bias: InstrumentBias,
-    // Q
+    /// Geometry (DOP) matrix, the DOPs are derived from on access
     pub(crate) q: Matrix4<f64>,
 }
 
@@ -80,18 +117,24 @@ impl PVTSolution {
     pub fn sv(&self) -> Vec<SV> {
         self.sv.keys().copied().collect()
     }
+    /// Returns true if this solution was obtained in clock holdover
+    pub fn is_holdover(&self) -> bool {
+        self.holdover.is_some()
+    }
+    /// Geometric Dilution of Precision. Null when the position was not resolved.
+    pub fn gdop(&self) -> f64 {
+        gdop(&self.q)
+    }
+    /// Position Dilution of Precision
+    pub fn pdop(&self) -> f64 {
+        pdop(&self.q)
+    }
+    /// Time Dilution of Precision
+    pub fn tdop(&self) -> f64 {
+        tdop(&self.q)
+    }
     fn q_enu(&self, lat: f64, lon: f64) -> Matrix3<f64> {
-        let r = Matrix3::<f64>::new(
-            -lon.sin(),
-            -lon.cos() * lat.sin(),
-            lat.cos() * lon.cos(),
-            lon.cos(),
-            -lat.sin() * lon.sin(),
-            lat.cos() * lon.sin(),
-            0.0_f64,
-            lat.cos(),
-            lon.sin(),
-        );
+        let r = ecef_to_enu_matrix(lat, lon);
         let q_3 = Matrix3::<f64>::new(
             self.q[(0, 0)],
             self.q[(0, 1)],
@@ -103,13 +146,28 @@ impl PVTSolution {
             self.q[(2, 1)],
             self.q[(2, 2)],
         );
-        r.clone().transpose() * q_3 * r
+        r * q_3 * r.transpose()
     }
+    /// Horizontal Dilution of Precision, at this latitude and longitude [rad]
     pub fn hdop(&self, lat: f64, lon: f64) -> f64 {
         let q = self.q_enu(lat, lon);
         (q[(0, 0)] + q[(1, 1)]).sqrt()
     }
+    /// Vertical Dilution of Precision, at this latitude and longitude [rad]
     pub fn vdop(&self, lat: f64, lon: f64) -> f64 {
         self.q_enu(lat, lon)[(2, 2)].sqrt()
     }
+    /// Returns the receiver position projected as [UTMCoordinates], in the zone that contains it.
+    /// None in polar regions.
+    pub fn utm(&self) -> Option<UTMCoordinates> {
+        let pos_vel_m = self.state.to_cartesian_pos_vel() * 1.0E3;
+        let (lat_rad, lon_rad, _) =
+            Ellipsoid::WGS84.ecef_to_geodetic((pos_vel_m[0], pos_vel_m[1], pos_vel_m[2]));
+        UTMCoordinates::from_geodetic(lat_rad.to_degrees(), lon_rad.to_degrees())
+    }
+    /// Returns the receiver position as MGRS string, with `precision` digits per axis
+    /// (5 for 1 m resolution). None in polar regions.
+    pub fn mgrs(&self, precision: usize) -> Option<String> {
+        self.utm().map(|utm| utm.mgrs(precision))
+    }
 }
@@ -1,6 +1,6 @@
-use log::debug;
-use nalgebra::{DVector, Vector3};
-use nyx::cosmic::SPEED_OF_LIGHT_M_S;
+use crate::constants::SPEED_OF_LIGHT_M_S;
+use crate::logging::debug;
+use nalgebra::{base::dimension::U8, DVector, OVector, Vector3};
 use thiserror::Error;
 
 use crate::{
@@ -24,17 +24,47 @@ pub enum InvalidationCause {
     CodeResidual(f64),
 }
 
+/// Post-fit residuals: stack allocated for small pools (up to 8 vehicles,
+/// which is the navigation matrix dimension), heap allocated otherwise.
+enum Residuals {
+    Static(usize, OVector<f64, U8>),
+    Dynamic(DVector<f64>),
+}
+
+impl Residuals {
+    fn zeros(len: usize) -> Self {
+        if len <= 8 {
+            Self::Static(len, OVector::<f64, U8>::zeros())
+        } else {
+            Self::Dynamic(DVector::<f64>::zeros(len))
+        }
+    }
+    fn as_slice(&self) -> &[f64] {
+        match self {
+            Self::Static(len, residuals) => &residuals.as_slice()[..*len],
+            Self::Dynamic(residuals) => residuals.as_slice(),
+        }
+    }
+    fn as_mut_slice(&mut self) -> &mut [f64] {
+        match self {
+            Self::Static(len, residuals) => &mut residuals.as_mut_slice()[..*len],
+            Self::Dynamic(residuals) => residuals.as_mut_slice(),
+        }
+    }
+}
+
 pub(crate) struct Validator {
     gdop: f64,
     tdop: f64,
-    residuals: DVector<f64>,
+    residuals: Residuals,
 }
 
 impl Validator {
     pub fn new(apriori: Vector3<f64>, pool: &[Candidate], input: &Input, output: &Output) -> Self {
-        let gdop = output.gdop;
-        let tdop = output.tdop;
-        let mut residuals = DVector::<f64>::zeros(pool.len());
+        let gdop = output.gdop();
+        let tdop = output.tdop();
+        let mut storage = Residuals::zeros(pool.len());
+        let residuals = storage.as_mut_slice();
 
         for (idx, cd) in pool.iter().enumerate() {
             let sv = input
@@ -72,6 +102,7 @@ impl Validator {
             residuals[idx] -= sv.tropo_bias.unwrap_or_default();
             residuals[idx] -= sv.iono_bias.unwrap_or_default().value();
             residuals[idx] /= input.w[(idx, idx)];
+
             debug!(
                 "{} ({}): coderes={}/w={}",
                 cd.t,
@@ -81,11 +112,15 @@ impl Validator {
             );
         }
         Self {
-            residuals,
+            residuals: storage,
             gdop,
             tdop,
         }
     }
+    /// Returns post-fit residuals, in [Candidate]s order
+    pub fn residuals(&self) -> &[f64] {
+        self.residuals.as_slice()
+    }
     /*
      * Solution validation process
      */
@@ -0,0 +1,57 @@
+//! Per epoch scratch buffers
+use super::Geometry;
+use crate::prelude::SV;
+
+/// [Workspace] holds the temporary buffers of one resolution attempt.
+/// It is owned by the Solver, cleared at the beginning of each [Epoch](crate::prelude::Epoch)
+/// and reused: allocations only happen while the pool grows.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Workspace {
+    /// [SV]s in sight
+    pub sv: Vec<SV>,
+    /// SV positions, ECEF [m]
+    pub sv_m: Vec<(f64, f64, f64)>,
+    /// Line of sight [Geometry]
+    pub geometry: Geometry,
+}
+
+impl Workspace {
+    /// Clears all buffers, preserving the allocations
+    pub fn clear(&mut self) {
+        self.sv.clear();
+        self.sv_m.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Workspace;
+    use crate::prelude::SV;
+    use std::str::FromStr;
+
+    #[test]
+    fn workspace_reuse() {
+        let mut workspace = Workspace::default();
+        let apriori = (4_696_989.0, 723_994.0, 4_239_678.0);
+        let sv_m = [
+            (15_365_952.0, 3_511_123.0, 21_357_812.0),
+            (10_753_335.0, -10_639_797.0, 21_563_016.0),
+        ];
+
+        workspace.sv.push(SV::from_str("G01").unwrap());
+        workspace.sv_m.extend_from_slice(&sv_m);
+        workspace.geometry.resolve(apriori, &workspace.sv_m);
+        let los = workspace.geometry.line_of_sight(1).unwrap();
+
+        let capacity = workspace.sv_m.capacity();
+        workspace.clear();
+        assert!(workspace.sv.is_empty());
+        assert_eq!(workspace.sv_m.capacity(), capacity);
+
+        // smaller pool: same buffers, identical geometry
+        workspace.sv_m.push(sv_m[1]);
+        workspace.geometry.resolve(apriori, &workspace.sv_m);
+        assert_eq!(workspace.geometry.line_of_sight(0), Some(los));
+        assert!(workspace.geometry.line_of_sight(1).is_none());
+    }
+}
@@ -0,0 +1,698 @@
+//! Broadcast ephemerides (Keplerian) orbit propagation
+use std::collections::BTreeMap;
+
+use crate::{
+    clock::ClockSource,
+    constants::SPEED_OF_LIGHT_M_S,
+    orbit::{sbas::MAX_AGE_S as SBAS_MAX_AGE_S, GlonassEphemeris, OrbitSource, SbasGeoEphemeris},
+    prelude::{
+        BeiDouGroupDelays, ClockCorrection, ClockPolynomial, Constellation, ConstellationTable,
+        Duration, Epoch, Frame, GalileoNavMessage, Interpolation, Orbit, TimeScale, Vector3, SV,
+    },
+};
+
+use thiserror::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Broadcast ephemeris consistency errors
+#[derive(Debug, Clone, Copy, PartialEq, Error)]
+pub enum EphemerisError {
+    #[error("IODE {iode} does not match IODC {iodc}")]
+    IodMismatch { iode: u16, iodc: u16 },
+    #[error("subframes from different issues of data (IODE {0} and {1})")]
+    SubframeMismatch(u16, u16),
+    #[error("ToE and ToC are more than half a week apart (week number ambiguity)")]
+    WeekMismatch,
+}
+
+/// Resolves the ambiguity of a broadcast week number, transmitted modulo `modulo` weeks
+/// (1024 for GPS LNAV, 8192 for CNAV, 4096 for Galileo, 8192 for BeiDou):
+/// returns the full week number closest to the `reference` week (typically, the receiver week).
+/// Week numbers that are not lower than `modulo` are already unambiguous and returned as is.
+pub fn resolve_week(week: u32, modulo: u32, reference: u32) -> u32 {
+    if week >= modulo {
+        return week;
+    }
+    let (week, modulo, reference) = (week as i64, modulo as i64, reference as i64);
+    let base = reference - reference.rem_euclid(modulo) + week;
+    [base - modulo, base, base + modulo]
+        .into_iter()
+        .filter(|week| *week >= 0)
+        .min_by_key(|week| (week - reference).abs())
+        .unwrap_or(week) as u32
+}
+
+/// [KeplerianEphemeris] as broadcasted by GPS, Galileo, BeiDou and QZSS vehicles.
+/// Angles are expressed in radians (as found in RINEX files), distances in meters
+/// and durations in seconds.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeplerianEphemeris {
+    /// Issue of Data (Ephemeris)
+    pub iode: u16,
+    /// Issue of Data (Clock), when broadcast (GPS and QZSS legacy navigation)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub iodc: Option<u16>,
+    /// Time of Ephemeris
+    pub toe: Epoch,
+    /// Time of Clock
+    pub toc: Epoch,
+    /// Clock bias [s]
+    pub af0: f64,
+    /// Clock drift [s/s]
+    pub af1: f64,
+    /// Clock drift rate [s/s^2]
+    pub af2: f64,
+    /// Total group delay [s] (TGD1 for BeiDou)
+    pub tgd: f64,
+    /// BeiDou TGD2 [s]
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tgd2: Option<f64>,
+    /// Square root of the semi-major axis [m^1/2]
+    pub sqrt_a: f64,
+    /// Eccentricity
+    pub e: f64,
+    /// Inclination angle at reference time
+    pub i0: f64,
+    /// Rate of inclination angle [rad/s]
+    pub idot: f64,
+    /// Longitude of ascending node at weekly epoch
+    pub omega0: f64,
+    /// Rate of right ascension [rad/s]
+    pub omega_dot: f64,
+    /// Argument of perigee
+    pub omega: f64,
+    /// Mean anomaly at reference time
+    pub m0: f64,
+    /// Mean motion difference [rad/s]
+    pub delta_n: f64,
+    /// Argument of latitude cosine harmonic correction
+    pub cuc: f64,
+    /// Argument of latitude sine harmonic correction
+    pub cus: f64,
+    /// Orbit radius cosine harmonic correction [m]
+    pub crc: f64,
+    /// Orbit radius sine harmonic correction [m]
+    pub crs: f64,
+    /// Inclination cosine harmonic correction
+    pub cic: f64,
+    /// Inclination sine harmonic correction
+    pub cis: f64,
+    /// [GalileoNavMessage] this ephemeris was decoded from (Galileo only)
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub galileo_nav: Option<GalileoNavMessage>,
+}
+
+/// Returns (gravitational constant [m^3 s-2], Earth rotation rate [rad/s])
+/// defined by each constellation ICD.
+fn icd_constants(constellation: Constellation) -> (f64, f64) {
+    match constellation {
+        Constellation::GPS | Constellation::QZSS => (3.986005E14, 7.2921151467E-5),
+        Constellation::BeiDou => (3.986004418E14, 7.292115E-5),
+        _ => (3.986004418E14, 7.2921151467E-5),
+    }
+}
+
+/// BeiDou GEO vehicles use a dedicated rotation
+fn is_beidou_geo(sv: SV) -> bool {
+    sv.constellation == Constellation::BeiDou && (sv.prn <= 5 || sv.prn >= 59)
+}
+
+/// Broadcast ephemerides are valid +/- this [Duration] around their ToE.
+/// QZSS ephemerides are refreshed every 15' and fitted over 2 hours only.
+fn max_age(constellation: Constellation) -> Duration {
+    match constellation {
+        Constellation::BeiDou | Constellation::QZSS => Duration::from_seconds(3600.0),
+        _ => Duration::from_seconds(7200.0),
+    }
+}
+
+impl KeplerianEphemeris {
+    /// Returns true if this [KeplerianEphemeris] may be used for [SV] at [Epoch]
+    pub fn is_valid(&self, sv: SV, t: Epoch) -> bool {
+        (t - self.toe).abs() <= max_age(sv.constellation)
+    }
+    /// Verifies the consistency of this [KeplerianEphemeris] before it is used:
+    /// the 8 LSB of the IODC must match the IODE, and the ToE and ToC
+    /// must be expressed in the same week.
+    pub fn verify(&self) -> Result<(), EphemerisError> {
+        if let Some(iodc) = self.iodc {
+            if iodc & 0xFF != self.iode {
+                return Err(EphemerisError::IodMismatch {
+                    iode: self.iode,
+                    iodc,
+                });
+            }
+        }
+        if (self.toe - self.toc).abs() > Duration::from_days(3.5) {
+            return Err(EphemerisError::WeekMismatch);
+        }
+        Ok(())
+    }
+    /// Solves Kepler's equation, returns the eccentric anomaly [rad]
+    fn eccentric_anomaly(&self, mu: f64, tk: f64) -> Option<f64> {
+        let a = self.sqrt_a.powi(2);
+        let n = (mu / a.powi(3)).sqrt() + self.delta_n;
+        let m_k = self.m0 + n * tk;
+        let mut e_k = m_k;
+        for _ in 0..10 {
+            let e_next = m_k + self.e * e_k.sin();
+            if (e_next - e_k).abs() < 1.0E-13 {
+                return Some(e_next);
+            }
+            e_k = e_next;
+        }
+        None
+    }
+    /// Returns ECEF position [m] of [SV] at [Epoch]
+    pub fn position_ecef_m(&self, sv: SV, t: Epoch) -> Option<Vector3<f64>> {
+        let (mu, omega_e) = icd_constants(sv.constellation);
+        let timescale = ConstellationTable::timescale(sv.constellation).unwrap_or(TimeScale::GPST);
+
+        let tk = (t - self.toe).to_seconds();
+        let (_, toe_nanos) = self.toe.to_time_scale(timescale).to_time_of_week();
+        let toe_sow = toe_nanos as f64 * 1.0E-9;
+
+        let e_k = self.eccentric_anomaly(mu, tk)?;
+        let a = self.sqrt_a.powi(2);
+
+        let v_k = ((1.0 - self.e.powi(2)).sqrt() * e_k.sin()).atan2(e_k.cos() - self.e);
+        let phi_k = v_k + self.omega;
+        let (sin_2phi, cos_2phi) = (2.0 * phi_k).sin_cos();
+
+        let u_k = phi_k + self.cus * sin_2phi + self.cuc * cos_2phi;
+        let r_k = a * (1.0 - self.e * e_k.cos()) + self.crs * sin_2phi + self.crc * cos_2phi;
+        let i_k = self.i0 + self.idot * tk + self.cis * sin_2phi + self.cic * cos_2phi;
+
+        let (x_k, y_k) = (r_k * u_k.cos(), r_k * u_k.sin());
+
+        if is_beidou_geo(sv) {
+            // inertial ascending node, then rotation to BDCS
+            let omega_k = self.omega0 + self.omega_dot * tk - omega_e * toe_sow;
+            let (s_omega, c_omega) = omega_k.sin_cos();
+            let (s_i, c_i) = i_k.sin_cos();
+
+            let x_g = x_k * c_omega - y_k * c_i * s_omega;
+            let y_g = x_k * s_omega + y_k * c_i * c_omega;
+            let z_g = y_k * s_i;
+
+            let (s_x, c_x) = (-5.0_f64).to_radians().sin_cos();
+            let (s_z, c_z) = (omega_e * tk).sin_cos();
+
+            let (y_r, z_r) = (c_x * y_g + s_x * z_g, -s_x * y_g + c_x * z_g);
+            Some(Vector3::new(
+                c_z * x_g + s_z * y_r,
+                -s_z * x_g + c_z * y_r,
+                z_r,
+            ))
+        } else {
+            let omega_k = self.omega0 + (self.omega_dot - omega_e) * tk - omega_e * toe_sow;
+            let (s_omega, c_omega) = omega_k.sin_cos();
+            let (s_i, c_i) = i_k.sin_cos();
+            Some(Vector3::new(
+                x_k * c_omega - y_k * c_i * s_omega,
+                x_k * s_omega + y_k * c_i * c_omega,
+                y_k * s_i,
+            ))
+        }
+    }
+    /// Returns [SV] state as ECEF [Orbit] at [Epoch]. Velocity is obtained
+    /// by central difference of the propagated positions.
+    pub fn orbit(&self, sv: SV, t: Epoch, frame: Frame) -> Option<Orbit> {
+        let half_step = Duration::from_seconds(0.5);
+        let pos = self.position_ecef_m(sv, t)? / 1.0E3;
+        let vel = self.position_ecef_m(sv, t + half_step)? / 1.0E3
+            - self.position_ecef_m(sv, t - half_step)? / 1.0E3;
+        Some(Orbit::from_position(pos[0], pos[1], pos[2], t, frame).with_velocity_km_s(vel))
+    }
+    /// Returns the [ClockPolynomial] of this [KeplerianEphemeris], to be evaluated
+    /// at transmission time (see `Candidate::set_clock_polynomial`).
+    pub fn clock_polynomial(&self) -> ClockPolynomial {
+        let poly = ClockPolynomial::new(self.toc, self.af0, self.af1, self.af2);
+        match self.galileo_nav {
+            Some(nav) => poly.with_galileo_nav(nav),
+            None => poly,
+        }
+    }
+    /// Returns [SV] [ClockCorrection] at [Epoch], relativistic effect included.
+    /// The group delay is not included (see `Candidate::set_group_delay`).
+    pub fn clock_correction(&self, sv: SV, t: Epoch) -> Option<ClockCorrection> {
+        let (mu, _) = icd_constants(sv.constellation);
+        let e_k = self.eccentric_anomaly(mu, (t - self.toe).to_seconds())?;
+        let dt = (t - self.toc).to_seconds();
+        let relativistic =
+            -2.0 * mu.sqrt() / SPEED_OF_LIGHT_M_S.powi(2) * self.e * self.sqrt_a * e_k.sin();
+        let correction = self.af0 + self.af1 * dt + self.af2 * dt.powi(2) + relativistic;
+        let correction =
+            ClockCorrection::with_relativistic_correction(Duration::from_seconds(correction))
+                .with_drift(self.af1 + 2.0 * self.af2 * dt);
+        match self.galileo_nav {
+            Some(nav) => Some(correction.with_galileo_nav(nav)),
+            None => Some(correction),
+        }
+    }
+}
+
+/// [BroadcastOrbits] is an [OrbitSource] built from decoded [KeplerianEphemeris],
+/// [GlonassEphemeris] and [SbasGeoEphemeris], for users that do not want to implement
+/// their own orbit provider (typically SPP). The closest valid ephemeris is always selected.
+/// Requested [Frame] is assumed to be an ECEF frame.
+#[derive(Debug, Clone, Default)]
+pub struct BroadcastOrbits {
+    ephemerides: BTreeMap<SV, Vec<KeplerianEphemeris>>,
+    glonass: BTreeMap<SV, Vec<GlonassEphemeris>>,
+    sbas: BTreeMap<SV, Vec<SbasGeoEphemeris>>,
+}
+
+impl BroadcastOrbits {
+    /// Builds new empty [BroadcastOrbits]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Stores new [KeplerianEphemeris] for this [SV].
+    /// Replaces any previous ephemeris with identical ToE (and [GalileoNavMessage]).
+    pub fn insert(&mut self, sv: SV, ephemeris: KeplerianEphemeris) {
+        let ephemerides = self.ephemerides.entry(sv).or_default();
+        ephemerides
+            .retain(|eph| eph.toe != ephemeris.toe || eph.galileo_nav != ephemeris.galileo_nav);
+        ephemerides.push(ephemeris);
+    }
+    /// Verifies and stores new [KeplerianEphemeris] for this [SV]
+    /// (see [KeplerianEphemeris::verify]): inconsistent sets are rejected.
+    pub fn try_insert(
+        &mut self,
+        sv: SV,
+        ephemeris: KeplerianEphemeris,
+    ) -> Result<(), EphemerisError> {
+        ephemeris.verify()?;
+        self.insert(sv, ephemeris);
+        Ok(())
+    }
+    /// Stores new [GlonassEphemeris] for this GLONASS [SV].
+    /// Replaces any previous ephemeris with identical reference [Epoch].
+    pub fn insert_glonass(&mut self, sv: SV, ephemeris: GlonassEphemeris) {
+        let ephemerides = self.glonass.entry(sv).or_default();
+        ephemerides.retain(|eph| eph.toe != ephemeris.toe);
+        ephemerides.push(ephemeris);
+    }
+    /// Stores new [SbasGeoEphemeris] for this SBAS [SV].
+    /// Replaces any previous ephemeris with identical time of applicability.
+    pub fn insert_sbas(&mut self, sv: SV, ephemeris: SbasGeoEphemeris) {
+        let ephemerides = self.sbas.entry(sv).or_default();
+        ephemerides.retain(|eph| eph.t0 != ephemeris.t0);
+        ephemerides.push(ephemeris);
+    }
+    /// Discards ephemerides that are no longer valid at [Epoch]
+    pub fn discard_outdated(&mut self, t: Epoch) {
+        for (sv, ephemerides) in self.ephemerides.iter_mut() {
+            ephemerides.retain(|eph| eph.toe > t || eph.is_valid(*sv, t));
+        }
+        self.ephemerides
+            .retain(|_, ephemerides| !ephemerides.is_empty());
+        for ephemerides in self.glonass.values_mut() {
+            ephemerides.retain(|eph| eph.toe > t || eph.is_valid(t));
+        }
+        self.glonass
+            .retain(|_, ephemerides| !ephemerides.is_empty());
+        for ephemerides in self.sbas.values_mut() {
+            ephemerides.retain(|eph| eph.t0 > t || eph.is_valid(t));
+        }
+        self.sbas.retain(|_, ephemerides| !ephemerides.is_empty());
+    }
+    /// Returns closest valid [KeplerianEphemeris] for [SV] at [Epoch]
+    pub fn ephemeris(&self, sv: SV, t: Epoch) -> Option<&KeplerianEphemeris> {
+        self.ephemerides
+            .get(&sv)?
+            .iter()
+            .filter(|eph| eph.is_valid(sv, t))
+            .min_by_key(|eph| (t - eph.toe).abs())
+    }
+    /// Returns valid [KeplerianEphemeris] for [SV] at [Epoch], with given Issue of Data
+    pub fn ephemeris_iod(&self, sv: SV, t: Epoch, iode: u16) -> Option<&KeplerianEphemeris> {
+        self.ephemerides
+            .get(&sv)?
+            .iter()
+            .filter(|eph| eph.iode == iode && eph.is_valid(sv, t))
+            .min_by_key(|eph| (t - eph.toe).abs())
+    }
+    /// Returns closest valid [GlonassEphemeris] for GLONASS [SV] at [Epoch]
+    pub fn glonass_ephemeris(&self, sv: SV, t: Epoch) -> Option<&GlonassEphemeris> {
+        self.glonass
+            .get(&sv)?
+            .iter()
+            .filter(|eph| eph.is_valid(t))
+            .min_by_key(|eph| (t - eph.toe).abs())
+    }
+    /// Returns closest valid [SbasGeoEphemeris] for SBAS [SV] at [Epoch]
+    pub fn sbas_ephemeris(&self, sv: SV, t: Epoch) -> Option<&SbasGeoEphemeris> {
+        self.sbas
+            .get(&sv)?
+            .iter()
+            .filter(|eph| eph.is_valid(t))
+            .min_by_key(|eph| (t - eph.t0).abs())
+    }
+    /// Returns the validity interval of the ephemerides of this [SV]:
+    /// from the oldest ToE minus the ephemeris age, to the latest ToE plus the ephemeris age.
+    pub fn validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        if sv.constellation == Constellation::Glonass {
+            let age = Duration::from_seconds(1800.0);
+            let toes = self.glonass.get(&sv)?.iter().map(|eph| eph.toe);
+            let (first, last) = (toes.clone().min()?, toes.max()?);
+            Some((first - age, last + age))
+        } else if sv.constellation.is_sbas() {
+            let age = Duration::from_seconds(SBAS_MAX_AGE_S);
+            let t0s = self.sbas.get(&sv)?.iter().map(|eph| eph.t0);
+            let (first, last) = (t0s.clone().min()?, t0s.max()?);
+            Some((first - age, last + age))
+        } else {
+            let age = max_age(sv.constellation);
+            let toes = self.ephemerides.get(&sv)?.iter().map(|eph| eph.toe);
+            let (first, last) = (toes.clone().min()?, toes.max()?);
+            Some((first - age, last + age))
+        }
+    }
+    /// Returns [ClockCorrection] for [SV] at [Epoch], to be attached to the `Candidate`
+    pub fn clock_correction(&self, sv: SV, t: Epoch) -> Option<ClockCorrection> {
+        if sv.constellation == Constellation::Glonass {
+            Some(self.glonass_ephemeris(sv, t)?.clock_correction(t))
+        } else if sv.constellation.is_sbas() {
+            Some(self.sbas_ephemeris(sv, t)?.clock_correction(t))
+        } else {
+            self.ephemeris(sv, t)?.clock_correction(sv, t)
+        }
+    }
+    /// Returns total group delay for [SV] at [Epoch], to be attached to the `Candidate`.
+    /// Not available for GLONASS and SBAS vehicles.
+    pub fn group_delay(&self, sv: SV, t: Epoch) -> Option<Duration> {
+        Some(Duration::from_seconds(self.ephemeris(sv, t)?.tgd))
+    }
+    /// Returns [BeiDouGroupDelays] for BeiDou [SV] at [Epoch], to be attached to the `Candidate`
+    /// (see `Candidate::set_beidou_group_delays`). Requires TGD2.
+    pub fn beidou_group_delays(&self, sv: SV, t: Epoch) -> Option<BeiDouGroupDelays> {
+        if sv.constellation != Constellation::BeiDou {
+            return None;
+        }
+        let eph = self.ephemeris(sv, t)?;
+        Some(BeiDouGroupDelays::new(
+            Duration::from_seconds(eph.tgd),
+            Duration::from_seconds(eph.tgd2?),
+        ))
+    }
+    /// Returns Galileo BGD for [SV] at [Epoch] and the [GalileoNavMessage] it was decoded from,
+    /// to be attached to the `Candidate` (see `Candidate::set_galileo_group_delay`).
+    /// It always matches the message [Self::clock_correction] was decoded from.
+    pub fn galileo_group_delay(&self, sv: SV, t: Epoch) -> Option<(Duration, GalileoNavMessage)> {
+        let eph = self.ephemeris(sv, t)?;
+        Some((Duration::from_seconds(eph.tgd), eph.galileo_nav?))
+    }
+}
+
+impl OrbitSource for BroadcastOrbits {
+    fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, _: Interpolation) -> Option<Orbit> {
+        if sv.constellation == Constellation::Glonass {
+            Some(self.glonass_ephemeris(sv, t)?.orbit(t, fr))
+        } else if sv.constellation.is_sbas() {
+            Some(self.sbas_ephemeris(sv, t)?.orbit(t, fr))
+        } else {
+            self.ephemeris(sv, t)?.orbit(sv, t, fr)
+        }
+    }
+    fn validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        BroadcastOrbits::validity(self, sv)
+    }
+}
+
+impl ClockSource for BroadcastOrbits {
+    fn next_clock_at(&mut self, t: Epoch, sv: SV, _: Interpolation) -> Option<ClockCorrection> {
+        self.clock_correction(sv, t)
+    }
+    fn clock_validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        self.validity(sv)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{resolve_week, BroadcastOrbits, EphemerisError, KeplerianEphemeris};
+    use crate::prelude::{
+        Constellation, Duration, Epoch, GalileoNavMessage, Interpolation, OrbitSource,
+        SbasGeoEphemeris, EARTH_ITRF93, SV,
+    };
+    use std::str::FromStr;
+
+    fn ephemeris(toe: Epoch) -> KeplerianEphemeris {
+        KeplerianEphemeris {
+            toe,
+            toc: toe,
+            sqrt_a: 5153.7,
+            e: 0.01,
+            i0: 0.96,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn keplerian_propagation() {
+        let sv = SV::new(Constellation::GPS, 1);
+        let toe = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let eph = ephemeris(toe);
+        let a = eph.sqrt_a.powi(2);
+
+        for dt_s in [0.0, 600.0, -1800.0, 3600.0] {
+            let t = toe + Duration::from_seconds(dt_s);
+            let pos = eph.position_ecef_m(sv, t).unwrap();
+            // no harmonic correction: keplerian radius
+            assert!(pos.norm() > a * (1.0 - eph.e) - 1.0E-3);
+            assert!(pos.norm() < a * (1.0 + eph.e) + 1.0E-3);
+            // inclination is preserved
+            assert!(pos[2].abs() <= pos.norm() * eph.i0.sin() + 1.0E-3);
+        }
+
+        let orbit = eph.orbit(sv, toe, EARTH_ITRF93).unwrap();
+        let state = orbit.to_cartesian_pos_vel();
+        let velocity_km_s = (state[3].powi(2) + state[4].powi(2) + state[5].powi(2)).sqrt();
+        assert!(
+            velocity_km_s > 2.5 && velocity_km_s < 4.5,
+            "{}",
+            velocity_km_s
+        );
+    }
+
+    #[test]
+    fn broadcast_clock_correction() {
+        let sv = SV::new(Constellation::GPS, 1);
+        let toe = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let eph = KeplerianEphemeris {
+            af0: 1.0E-4,
+            af1: 1.0E-11,
+            e: 0.0,
+            ..ephemeris(toe)
+        };
+        let corr = eph
+            .clock_correction(sv, toe + Duration::from_seconds(100.0))
+            .unwrap();
+        assert!((corr.duration.to_seconds() - (1.0E-4 + 1.0E-9)).abs() < 1.0E-12);
+
+        // circular orbit: no relativistic effect
+        let corr = eph
+            .clock_polynomial()
+            .clock_correction(toe + Duration::from_seconds(100.0));
+        assert!((corr.duration.to_seconds() - (1.0E-4 + 1.0E-9)).abs() < 1.0E-12);
+    }
+
+    #[test]
+    fn qzss_ephemeris_validity() {
+        let sv = SV::new(Constellation::QZSS, 2);
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 QZSST").unwrap();
+        let mut orbits = BroadcastOrbits::new();
+        orbits.insert(sv, ephemeris(t0));
+
+        assert!(orbits
+            .ephemeris(sv, t0 + Duration::from_seconds(3600.0))
+            .is_some());
+        assert!(orbits
+            .ephemeris(sv, t0 + Duration::from_seconds(5400.0))
+            .is_none());
+        assert!(orbits.clock_correction(sv, t0).is_some());
+    }
+
+    #[test]
+    fn ephemeris_selection() {
+        let sv = SV::new(Constellation::GPS, 1);
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let t1 = t0 + Duration::from_seconds(7200.0);
+
+        let mut orbits = BroadcastOrbits::new();
+        orbits.insert(sv, ephemeris(t0));
+        orbits.insert(sv, ephemeris(t1));
+        orbits.insert(sv, ephemeris(t1));
+
+        let t = t0 + Duration::from_seconds(5400.0);
+        assert_eq!(orbits.ephemeris(sv, t).unwrap().toe, t1);
+
+        let t = t0 + Duration::from_seconds(1800.0);
+        assert_eq!(orbits.ephemeris(sv, t).unwrap().toe, t0);
+
+        let t = t1 + Duration::from_seconds(7201.0);
+        assert!(orbits.ephemeris(sv, t).is_none());
+        assert!(orbits
+            .ephemeris(SV::new(Constellation::GPS, 2), t0)
+            .is_none());
+
+        assert_eq!(
+            orbits.validity(sv),
+            Some((
+                t0 - Duration::from_seconds(7200.0),
+                t1 + Duration::from_seconds(7200.0)
+            ))
+        );
+
+        orbits.discard_outdated(t1 + Duration::from_seconds(1.0));
+        assert!(orbits.ephemeris(sv, t0).is_none());
+        assert!(orbits.ephemeris(sv, t1).is_some());
+        assert_eq!(
+            orbits.validity(sv),
+            Some((t0, t1 + Duration::from_seconds(7200.0)))
+        );
+        assert!(orbits.validity(SV::new(Constellation::GPS, 2)).is_none());
+    }
+
+    #[test]
+    fn galileo_nav_messages() {
+        let sv = SV::new(Constellation::Galileo, 1);
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GST").unwrap();
+
+        let mut orbits = BroadcastOrbits::new();
+        for (nav, tgd) in [
+            (GalileoNavMessage::INav, 1.0E-9),
+            (GalileoNavMessage::FNav, 2.0E-9),
+        ] {
+            orbits.insert(
+                sv,
+                KeplerianEphemeris {
+                    tgd,
+                    galileo_nav: Some(nav),
+                    ..ephemeris(t0)
+                },
+            );
+        }
+
+        // both messages are preserved, clock and BGD always match
+        let (bgd, nav) = orbits.galileo_group_delay(sv, t0).unwrap();
+        assert_eq!(nav, GalileoNavMessage::INav);
+        assert_eq!(bgd, Duration::from_seconds(1.0E-9));
+
+        let clock = orbits.clock_correction(sv, t0).unwrap();
+        assert_eq!(clock.galileo_nav, Some(nav));
+    }
+
+    #[test]
+    fn beidou_group_delays() {
+        let sv = SV::new(Constellation::BeiDou, 20);
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 BDT").unwrap();
+        let mut orbits = BroadcastOrbits::new();
+        orbits.insert(
+            sv,
+            KeplerianEphemeris {
+                tgd: 5.0E-9,
+                ..ephemeris(t0)
+            },
+        );
+        assert!(orbits.beidou_group_delays(sv, t0).is_none());
+
+        orbits.insert(
+            sv,
+            KeplerianEphemeris {
+                tgd: 5.0E-9,
+                tgd2: Some(-3.0E-9),
+                ..ephemeris(t0)
+            },
+        );
+        let delays = orbits.beidou_group_delays(sv, t0).unwrap();
+        assert_eq!(delays.tgd1, Duration::from_seconds(5.0E-9));
+        assert_eq!(delays.tgd2, Duration::from_seconds(-3.0E-9));
+    }
+
+    #[test]
+    fn sbas_geo_orbits() {
+        let sv = SV::new(Constellation::SBAS, 23);
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let mut orbits = BroadcastOrbits::new();
+        orbits.insert_sbas(
+            sv,
+            SbasGeoEphemeris {
+                t0,
+                position_m: (40_000_000.0, 10_000_000.0, 0.0),
+                clock_bias: 1.0E-6,
+                ..Default::default()
+            },
+        );
+
+        let t = t0 + Duration::from_seconds(60.0);
+        let orbit = orbits
+            .next_at(t, sv, EARTH_ITRF93, Interpolation::default())
+            .unwrap();
+        assert_eq!(orbit.radius_km.x, 40_000.0);
+        assert!(orbits.clock_correction(sv, t).is_some());
+        assert!(orbits.group_delay(sv, t).is_none());
+
+        let (first, last) = orbits.validity(sv).unwrap();
+        assert_eq!(last - first, Duration::from_seconds(720.0));
+
+        orbits.discard_outdated(t0 + Duration::from_seconds(3600.0));
+        assert!(orbits.sbas_ephemeris(sv, t).is_none());
+    }
+
+    #[test]
+    fn week_rollover() {
+        // GPS LNAV (10 bits): 2111 is transmitted as 63
+        assert_eq!(resolve_week(63, 1024, 2111), 2111);
+        assert_eq!(resolve_week(63, 1024, 2100), 2111);
+        // reference still in the previous cycle
+        assert_eq!(resolve_week(1, 1024, 2047), 2049);
+        assert_eq!(resolve_week(1023, 1024, 2049), 2047);
+        assert_eq!(resolve_week(100, 1024, 0), 100);
+        // already unambiguous
+        assert_eq!(resolve_week(2111, 1024, 0), 2111);
+    }
+
+    #[test]
+    fn ephemeris_consistency() {
+        let sv = SV::new(Constellation::GPS, 1);
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let mut orbits = BroadcastOrbits::new();
+
+        let eph = KeplerianEphemeris {
+            iode: 42,
+            iodc: Some(0x100 + 42),
+            ..ephemeris(t0)
+        };
+        assert!(orbits.try_insert(sv, eph).is_ok());
+
+        let eph = KeplerianEphemeris {
+            iode: 43,
+            iodc: Some(42),
+            ..ephemeris(t0 + Duration::from_seconds(7200.0))
+        };
+        assert_eq!(
+            orbits.try_insert(sv, eph),
+            Err(EphemerisError::IodMismatch { iode: 43, iodc: 42 })
+        );
+
+        // ToC resolved in the wrong week
+        let eph = KeplerianEphemeris {
+            toc: t0 - Duration::from_days(7.0),
+            ..ephemeris(t0 + Duration::from_seconds(7200.0))
+        };
+        assert_eq!(
+            orbits.try_insert(sv, eph),
+            Err(EphemerisError::WeekMismatch)
+        );
+
+        // rejected sets are not used
+        let t = t0 + Duration::from_seconds(7200.0);
+        assert_eq!(orbits.ephemeris(sv, t).unwrap().toe, t0);
+    }
+}
@@ -0,0 +1,165 @@
+//! GLONASS broadcast state vectors numerical integration
+use crate::prelude::{ClockCorrection, Duration, Epoch, Frame, Orbit, Vector3};
+
+use nalgebra::Vector6;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// PZ-90 gravitational constant [km^3 s-2]
+const MU_KM3_S2: f64 = 398_600.4418;
+/// PZ-90 Earth equatorial radius [km]
+const EARTH_RADIUS_KM: f64 = 6378.136;
+/// PZ-90 second zonal harmonic
+const J2: f64 = 1.082_625_75E-3;
+/// PZ-90 Earth rotation rate [rad/s]
+const OMEGA_E_RAD_S: f64 = 7.292115E-5;
+/// Integration step [s]
+const INTEGRATION_STEP_S: f64 = 60.0;
+
+/// [GlonassEphemeris] as broadcasted by GLONASS vehicles: a state vector
+/// in PZ-90 (Earth fixed) frame, that needs to be numerically integrated.
+/// The PZ-90/ITRF difference (few cm) is neglected.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GlonassEphemeris {
+    /// Reference [Epoch] of the state vector
+    pub toe: Epoch,
+    /// Position (x, y, z) [km]
+    pub position_km: (f64, f64, f64),
+    /// Velocity (x, y, z) [km/s]
+    pub velocity_km_s: (f64, f64, f64),
+    /// Luni-solar acceleration (x, y, z) [km/s^2]
+    pub acceleration_km_s2: (f64, f64, f64),
+    /// Clock bias (-TauN, as found in RINEX files) [s]
+    pub clock_bias: f64,
+    /// Relative frequency bias (GammaN)
+    pub clock_drift: f64,
+    /// Frequency channel number
+    pub channel: i8,
+}
+
+impl GlonassEphemeris {
+    /// GLONASS state vectors are refreshed every 30', valid +/-15' around their reference,
+    /// we tolerate up to 30'.
+    pub fn is_valid(&self, t: Epoch) -> bool {
+        (t - self.toe).abs() <= Duration::from_seconds(1800.0)
+    }
+    /// PZ-90 equations of motion (ICD GLONASS 2008, A.3.1.2)
+    fn derivatives(state: &Vector6<f64>, acc: &Vector3<f64>) -> Vector6<f64> {
+        let (x, y, z) = (state[0], state[1], state[2]);
+        let (vx, vy) = (state[3], state[4]);
+        let r2 = x.powi(2) + y.powi(2) + z.powi(2);
+        let r = r2.sqrt();
+        let mu_r3 = MU_KM3_S2 / r.powi(3);
+        let j2_term = 1.5 * J2 * MU_KM3_S2 * EARTH_RADIUS_KM.powi(2) / r.powi(5);
+        let z2_r2 = 5.0 * z.powi(2) / r2;
+        let omega2 = OMEGA_E_RAD_S.powi(2);
+        Vector6::new(
+            vx,
+            vy,
+            state[5],
+            -mu_r3 * x - j2_term * x * (1.0 - z2_r2)
+                + omega2 * x
+                + 2.0 * OMEGA_E_RAD_S * vy
+                + acc[0],
+            -mu_r3 * y - j2_term * y * (1.0 - z2_r2) + omega2 * y - 2.0 * OMEGA_E_RAD_S * vx
+                + acc[1],
+            -mu_r3 * z - j2_term * z * (3.0 - z2_r2) + acc[2],
+        )
+    }
+    /// Returns ECEF state (position [km], velocity [km/s]) at [Epoch],
+    /// by 4th order Runge-Kutta integration of the broadcast state.
+    pub fn state_km(&self, t: Epoch) -> Vector6<f64> {
+        let (x, y, z) = self.position_km;
+        let (vx, vy, vz) = self.velocity_km_s;
+        let (ax, ay, az) = self.acceleration_km_s2;
+        let acc = Vector3::new(ax, ay, az);
+        let mut state = Vector6::new(x, y, z, vx, vy, vz);
+
+        let mut remaining = (t - self.toe).to_seconds();
+        while remaining.abs() > 1.0E-9 {
+            let h = remaining.signum() * remaining.abs().min(INTEGRATION_STEP_S);
+            let k1 = Self::derivatives(&state, &acc);
+            let k2 = Self::derivatives(&(state + k1 * h / 2.0), &acc);
+            let k3 = Self::derivatives(&(state + k2 * h / 2.0), &acc);
+            let k4 = Self::derivatives(&(state + k3 * h), &acc);
+            state += (k1 + k2 * 2.0 + k3 * 2.0 + k4) * h / 6.0;
+            remaining -= h;
+        }
+        state
+    }
+    /// Returns SV state as ECEF [Orbit] at [Epoch]
+    pub fn orbit(&self, t: Epoch, frame: Frame) -> Orbit {
+        let state = self.state_km(t);
+        Orbit::from_position(state[0], state[1], state[2], t, frame)
+            .with_velocity_km_s(Vector3::new(state[3], state[4], state[5]))
+    }
+    /// Returns SV [ClockCorrection] at [Epoch]. GLONASS clock already
+    /// integrates the relativistic effect.
+    pub fn clock_correction(&self, t: Epoch) -> ClockCorrection {
+        let dt = (t - self.toe).to_seconds();
+        ClockCorrection::with_relativistic_correction(Duration::from_seconds(
+            self.clock_bias + self.clock_drift * dt,
+        ))
+        .with_drift(self.clock_drift)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::GlonassEphemeris;
+    use crate::prelude::{Duration, Epoch};
+    use std::str::FromStr;
+
+    fn ephemeris() -> GlonassEphemeris {
+        GlonassEphemeris {
+            toe: Epoch::from_str("2020-06-25T12:15:00 UTC").unwrap(),
+            position_km: (-14_451.994_140_6, -18_146.630_859_4, 10_129.504_882_8),
+            velocity_km_s: (-0.737_836_837_769, -0.821_384_429_932, -3.269_500_732_42),
+            acceleration_km_s2: (0.0, 0.0, -1.862_645_149_23E-9),
+            clock_bias: 1.0E-5,
+            clock_drift: 1.0E-11,
+            channel: 1,
+        }
+    }
+
+    #[test]
+    fn glonass_integration() {
+        let eph = ephemeris();
+        assert_eq!(eph.state_km(eph.toe)[0], eph.position_km.0);
+
+        // forward then backward integration
+        let t = eph.toe + Duration::from_seconds(900.0);
+        let state = eph.state_km(t);
+        let radius_km = (state[0].powi(2) + state[1].powi(2) + state[2].powi(2)).sqrt();
+        assert!(
+            radius_km > 25_000.0 && radius_km < 26_000.0,
+            "{}",
+            radius_km
+        );
+
+        let back = GlonassEphemeris {
+            toe: t,
+            position_km: (state[0], state[1], state[2]),
+            velocity_km_s: (state[3], state[4], state[5]),
+            ..eph
+        };
+        let state = back.state_km(eph.toe);
+        let err_km = ((state[0] - eph.position_km.0).powi(2)
+            + (state[1] - eph.position_km.1).powi(2)
+            + (state[2] - eph.position_km.2).powi(2))
+        .sqrt();
+        assert!(err_km < 1.0E-3, "{}", err_km);
+
+        assert!(eph.is_valid(t));
+        assert!(!eph.is_valid(t + Duration::from_seconds(1000.0)));
+    }
+
+    #[test]
+    fn glonass_clock_correction() {
+        let eph = ephemeris();
+        let corr = eph.clock_correction(eph.toe + Duration::from_seconds(100.0));
+        assert!((corr.duration.to_seconds() - (1.0E-5 + 1.0E-9)).abs() < 1.0E-12);
+    }
+}
@@ -0,0 +1,127 @@
+//! Signal propagation in an inertial frame
+use crate::{
+    constants::Constants,
+    prelude::{Epoch, Frame, Orbit, TimeScale, Vector3},
+};
+
+use nalgebra::{Matrix3, Vector6};
+
+/// Returns the Earth Rotation Angle [rad] at [Epoch] (IERS conventions 2010, 5.4.4),
+/// approximating UT1 by UTC.
+pub(crate) fn earth_rotation_angle(t: Epoch) -> f64 {
+    let j2000 = Epoch::from_gregorian_utc_at_noon(2000, 1, 1);
+    let du = (t.to_duration_in_time_scale(TimeScale::UTC)
+        - j2000.to_duration_in_time_scale(TimeScale::UTC))
+    .to_seconds()
+        / 86_400.0;
+    // fractional part first, to preserve precision
+    let turns = du.rem_euclid(1.0) + 0.779_057_273_264 + 0.002_737_811_911_354_48 * du;
+    turns.rem_euclid(1.0) * 2.0 * std::f64::consts::PI
+}
+
+/// Returns the inertial to ECEF rotation matrix, at [Epoch]
+fn eci_to_ecef_matrix(t: Epoch) -> Matrix3<f64> {
+    let (sin, cos) = earth_rotation_angle(t).sin_cos();
+    Matrix3::new(cos, sin, 0.0, -sin, cos, 0.0, 0.0, 0.0, 1.0)
+}
+
+/// Converts ECEF position [m] and velocity [m/s] at [Epoch] to the inertial frame
+pub(crate) fn ecef_to_eci(
+    t: Epoch,
+    pos_m: Vector3<f64>,
+    vel_m_s: Vector3<f64>,
+) -> (Vector3<f64>, Vector3<f64>) {
+    let omega = Vector3::new(0.0, 0.0, Constants::EARTH_ANGULAR_VEL_RAD);
+    let dcm = eci_to_ecef_matrix(t).transpose();
+    (dcm * pos_m, dcm * (vel_m_s + omega.cross(&pos_m)))
+}
+
+/// Converts inertial position [m] and velocity [m/s] at [Epoch] to ECEF
+pub(crate) fn eci_to_ecef(
+    t: Epoch,
+    pos_m: Vector3<f64>,
+    vel_m_s: Vector3<f64>,
+) -> (Vector3<f64>, Vector3<f64>) {
+    let omega = Vector3::new(0.0, 0.0, Constants::EARTH_ANGULAR_VEL_RAD);
+    let dcm = eci_to_ecef_matrix(t);
+    let pos_m = dcm * pos_m;
+    (pos_m, dcm * vel_m_s - omega.cross(&pos_m))
+}
+
+/// Expresses the SV state at transmission [Epoch], in the ECEF frame of the reception [Epoch]:
+/// the state is transformed to the inertial frame, where light travels in straight line,
+/// then back to ECEF using the Earth orientation at reception.
+/// This is the rigorous counterpart of the Sagnac (ECEF rotation) correction,
+/// and it also preserves the SV velocity.
+pub(crate) fn propagate_inertial(t_tx: Epoch, t_rx: Epoch, tx_orbit: Orbit, frame: Frame) -> Orbit {
+    let state = tx_orbit.to_cartesian_pos_vel() * 1.0E3;
+    let (pos_m, vel_m_s) = (
+        Vector3::new(state[0], state[1], state[2]),
+        Vector3::new(state[3], state[4], state[5]),
+    );
+    let (pos_m, vel_m_s) = ecef_to_eci(t_tx, pos_m, vel_m_s);
+    let (pos_m, vel_m_s) = eci_to_ecef(t_rx, pos_m, vel_m_s);
+    let pos_vel = Vector6::new(
+        pos_m[0], pos_m[1], pos_m[2], vel_m_s[0], vel_m_s[1], vel_m_s[2],
+    ) / 1.0E3;
+    Orbit::from_cartesian_pos_vel(pos_vel, t_rx, frame)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{earth_rotation_angle, ecef_to_eci, eci_to_ecef, propagate_inertial};
+    use crate::{
+        constants::Constants,
+        prelude::{Duration, Epoch, Orbit, Vector3, EARTH_ITRF93},
+    };
+    use std::str::FromStr;
+
+    #[test]
+    fn inertial_geometry() {
+        // ERA at J2000 (UT1 = UTC)
+        let j2000 = Epoch::from_gregorian_utc_at_noon(2000, 1, 1);
+        let era = earth_rotation_angle(j2000);
+        assert!((era - 0.779_057_273_264 * 2.0 * std::f64::consts::PI).abs() < 1.0E-12);
+
+        // round trip
+        let t = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let (pos_m, vel_m_s) = (
+            Vector3::new(15_600.0E3, 7_540.0E3, 20_140.0E3),
+            Vector3::new(-1_200.0, 2_500.0, 300.0),
+        );
+        let (eci_m, eci_m_s) = ecef_to_eci(t, pos_m, vel_m_s);
+        let (ecef_m, ecef_m_s) = eci_to_ecef(t, eci_m, eci_m_s);
+        assert!((ecef_m - pos_m).norm() < 1.0E-6);
+        assert!((ecef_m_s - vel_m_s).norm() < 1.0E-9);
+
+        // equivalent to the Sagnac rotation
+        let dt = Duration::from_seconds(0.075);
+        let t_tx = t - dt;
+        let tx_orbit = Orbit::from_cartesian_pos_vel(
+            nalgebra::Vector6::new(
+                pos_m[0], pos_m[1], pos_m[2], vel_m_s[0], vel_m_s[1], vel_m_s[2],
+            ) / 1.0E3,
+            t_tx,
+            EARTH_ITRF93,
+        );
+        let rx_orbit = propagate_inertial(t_tx, t, tx_orbit, EARTH_ITRF93);
+        assert_eq!(rx_orbit.epoch, t);
+
+        let we = Constants::EARTH_ANGULAR_VEL_RAD * dt.to_seconds();
+        let (we_sin, we_cos) = we.sin_cos();
+        let sagnac_m = Vector3::new(
+            we_cos * pos_m[0] + we_sin * pos_m[1],
+            -we_sin * pos_m[0] + we_cos * pos_m[1],
+            pos_m[2],
+        );
+        let state = rx_orbit.to_cartesian_pos_vel() * 1.0E3;
+        let rx_pos_m = Vector3::new(state[0], state[1], state[2]);
+        assert!((rx_pos_m - sagnac_m).norm() < 1.0E-3);
+
+        // a point fixed on the Earth remains fixed
+        let site_m = Vector3::new(4_696_989.0, 723_994.0, 4_239_678.0);
+        let (eci_m, eci_m_s) = ecef_to_eci(t_tx, site_m, Vector3::zeros());
+        let (_, ecef_m_s) = eci_to_ecef(t, eci_m, eci_m_s);
+        assert!(ecef_m_s.norm() < 1.0E-6);
+    }
+}
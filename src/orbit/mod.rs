@@ -0,0 +1,89 @@
+use crate::{
+    prelude::{Epoch, Frame, Interpolation, Orbit, SV},
+    sync,
+};
+use std::sync::{Arc, Mutex};
+
+mod broadcast;
+mod glonass;
+mod inertial;
+mod sbas;
+mod screening;
+mod ssr;
+
+#[cfg(feature = "sp3")]
+mod sp3;
+
+pub use broadcast::{resolve_week, BroadcastOrbits, EphemerisError, KeplerianEphemeris};
+pub use glonass::GlonassEphemeris;
+pub use sbas::SbasGeoEphemeris;
+pub use screening::ScreenedOrbits;
+pub use ssr::{SsrBias, SsrClockCorrection, SsrOrbitCorrection, SsrOrbits};
+
+pub(crate) use inertial::propagate_inertial;
+
+#[cfg(feature = "sp3")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sp3")))]
+pub use sp3::{Sp3Error, Sp3Orbits};
+
+/// OrbitalStateProvider must be implemented
+/// and provide SV state at specified `t` for the solving process can proceed.
+///
+/// [OrbitSource] is implemented for `Arc<Mutex<O>>`, so a single provider
+/// (and all its internal caches and interpolation buffers) can be shared between
+/// several [Solver]s, for example when processing a network of rovers.
+pub trait OrbitSource {
+    /// Provide Antenna Phase Center state as [Orbit] at requested [Epoch] for requested [SV]
+    /// and expressed in required [Frame]. If you happen to use other [Frame]s,
+    /// you can apply [Frame] conversion (rotations) by means of an [Almanac].
+    /// In case interpolation is used, we propose an [Interpolation] method and orders,
+    /// that would fit current setup, which you can choose to ignore.
+    /// If None is returned for too long, this [Epoch] will eventually get dropped out
+    /// and we will proceed to the next.
+    fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, interp: Interpolation) -> Option<Orbit>;
+    /// Returns the validity interval (first, last [Epoch]) of the states of this [SV],
+    /// when it is bounded (product boundaries, ephemeris age..). The [Solver] refuses
+    /// to use states outside of it, rather than extrapolating. Unbounded by default.
+    fn validity(&self, _sv: SV) -> Option<(Epoch, Epoch)> {
+        None
+    }
+}
+
+impl<O: OrbitSource> OrbitSource for Arc<Mutex<O>> {
+    fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, interp: Interpolation) -> Option<Orbit> {
+        let mut source = sync::lock(self);
+        source.next_at(t, sv, fr, interp)
+    }
+    fn validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        let source = sync::lock(self);
+        source.validity(sv)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::OrbitSource;
+    use crate::{
+        prelude::{Epoch, Interpolation, EARTH_J2000, SV},
+        tests::Counter,
+    };
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn shared_orbit_source() {
+        let shared = Arc::new(Mutex::new(Counter::default()));
+        let (mut rover_a, mut rover_b) = (shared.clone(), shared.clone());
+
+        let t = Epoch::default();
+        let interp = Interpolation::default();
+
+        assert!(rover_a
+            .next_at(t, SV::default(), EARTH_J2000, interp)
+            .is_some());
+        assert!(rover_b
+            .next_at(t, SV::default(), EARTH_J2000, interp)
+            .is_some());
+
+        assert_eq!(shared.lock().unwrap().requests, 2);
+    }
+}
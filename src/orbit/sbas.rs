@@ -0,0 +1,105 @@
+//! SBAS GEO navigation messages (Message Type 9)
+use crate::prelude::{ClockCorrection, Duration, Epoch, Frame, Orbit, Vector3};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// SBAS GEO navigation messages are broadcast every 2', we tolerate up to 6'
+pub(crate) const MAX_AGE_S: f64 = 360.0;
+
+/// [SbasGeoEphemeris] as broadcasted by SBAS GEO vehicles (Message Type 9):
+/// a state vector in WGS84 (Earth fixed) frame, propagated to 2nd order,
+/// so the GEO vehicles may be used as additional ranging sources.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SbasGeoEphemeris {
+    /// Time of applicability
+    pub t0: Epoch,
+    /// Position (x, y, z) [m]
+    pub position_m: (f64, f64, f64),
+    /// Velocity (x, y, z) [m/s]
+    pub velocity_m_s: (f64, f64, f64),
+    /// Acceleration (x, y, z) [m/s^2]
+    pub acceleration_m_s2: (f64, f64, f64),
+    /// Clock bias (aGf0) [s]
+    pub clock_bias: f64,
+    /// Clock drift (aGf1) [s/s]
+    pub clock_drift: f64,
+}
+
+impl SbasGeoEphemeris {
+    /// Returns true if this [SbasGeoEphemeris] may be used at [Epoch]
+    pub fn is_valid(&self, t: Epoch) -> bool {
+        (t - self.t0).abs() <= Duration::from_seconds(MAX_AGE_S)
+    }
+    /// Returns ECEF state (position [m], velocity [m/s]) at [Epoch]
+    pub fn state_m(&self, t: Epoch) -> (Vector3<f64>, Vector3<f64>) {
+        let dt = (t - self.t0).to_seconds();
+        let (x, y, z) = self.position_m;
+        let (vx, vy, vz) = self.velocity_m_s;
+        let (ax, ay, az) = self.acceleration_m_s2;
+        let (pos, vel, acc) = (
+            Vector3::new(x, y, z),
+            Vector3::new(vx, vy, vz),
+            Vector3::new(ax, ay, az),
+        );
+        (pos + vel * dt + acc * dt.powi(2) / 2.0, vel + acc * dt)
+    }
+    /// Returns SV state as ECEF [Orbit] at [Epoch]
+    pub fn orbit(&self, t: Epoch, frame: Frame) -> Orbit {
+        let (pos_m, vel_m_s) = self.state_m(t);
+        Orbit::from_position(
+            pos_m[0] / 1.0E3,
+            pos_m[1] / 1.0E3,
+            pos_m[2] / 1.0E3,
+            t,
+            frame,
+        )
+        .with_velocity_km_s(vel_m_s / 1.0E3)
+    }
+    /// Returns SV [ClockCorrection] at [Epoch]. The periodic relativistic effect
+    /// of (near circular) GEO orbits is neglected.
+    pub fn clock_correction(&self, t: Epoch) -> ClockCorrection {
+        let dt = (t - self.t0).to_seconds();
+        ClockCorrection::with_relativistic_correction(Duration::from_seconds(
+            self.clock_bias + self.clock_drift * dt,
+        ))
+        .with_drift(self.clock_drift)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SbasGeoEphemeris;
+    use crate::prelude::{Duration, Epoch, EARTH_ITRF93};
+    use std::str::FromStr;
+
+    #[test]
+    fn sbas_geo_propagation() {
+        let eph = SbasGeoEphemeris {
+            t0: Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap(),
+            position_m: (40_000_000.0, 10_000_000.0, 0.0),
+            velocity_m_s: (1.0, -2.0, 3.0),
+            acceleration_m_s2: (0.0, 0.0, -0.01),
+            clock_bias: 1.0E-6,
+            clock_drift: 1.0E-12,
+        };
+
+        let t = eph.t0 + Duration::from_seconds(100.0);
+        let (pos_m, vel_m_s) = eph.state_m(t);
+        assert_eq!(pos_m[0], 40_000_100.0);
+        assert_eq!(pos_m[1], 9_999_800.0);
+        assert!((pos_m[2] - 250.0).abs() < 1.0E-9);
+        assert!((vel_m_s[2] - 2.0).abs() < 1.0E-12);
+
+        let orbit = eph.orbit(t, EARTH_ITRF93);
+        assert_eq!(orbit.epoch, t);
+        assert!((orbit.radius_km.x - 40_000.1).abs() < 1.0E-9);
+
+        let corr = eph.clock_correction(t);
+        assert!((corr.duration.to_seconds() - (1.0E-6 + 1.0E-10)).abs() < 1.0E-15);
+
+        assert!(eph.is_valid(t));
+        assert!(!eph.is_valid(eph.t0 - Duration::from_seconds(400.0)));
+    }
+}
@@ -0,0 +1,247 @@
+//! Broadcast ephemerides screening against precise products
+use std::collections::BTreeMap;
+
+use crate::logging::warn;
+
+use crate::{
+    clock::ClockSource,
+    constants::SPEED_OF_LIGHT_M_S,
+    orbit::{BroadcastOrbits, OrbitSource},
+    prelude::{ClockCorrection, Epoch, Frame, Interpolation, Orbit, SV},
+};
+
+/// Compared states
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum Comparison {
+    Orbit,
+    Clock,
+}
+
+/// [ScreenedOrbits] proposes the [BroadcastOrbits] states, screened against
+/// a reference (precise or SSR corrected) [OrbitSource]: whenever both are available,
+/// vehicles whose broadcast state disagrees with the reference by more than
+/// the tolerated error are flagged (see [Self::anomalies]), and excluded
+/// unless [Self::set_exclusion] says otherwise. Vehicles without reference
+/// states are proposed unscreened.
+///
+/// The broadcast clocks are also screened, when reference clocks are attached
+/// (see [Self::set_reference_clock_source]). The tolerated error should account for
+/// the antenna phase center offset of precise orbits (center of mass, up to 2 m),
+/// and the datum of precise clocks (a few ns).
+pub struct ScreenedOrbits<R: OrbitSource> {
+    broadcast: BroadcastOrbits,
+    reference: R,
+    reference_clock: Option<Box<dyn ClockSource + Send>>,
+    max_error_m: f64,
+    exclusion: bool,
+    anomalies: BTreeMap<(SV, Comparison), (Epoch, f64)>,
+}
+
+impl<R: OrbitSource> ScreenedOrbits<R> {
+    /// Builds new [ScreenedOrbits], screening these [BroadcastOrbits] against
+    /// this reference [OrbitSource], tolerating this error [m].
+    pub fn new(broadcast: BroadcastOrbits, reference: R, max_error_m: f64) -> Self {
+        Self {
+            broadcast,
+            reference,
+            reference_clock: None,
+            max_error_m,
+            exclusion: true,
+            anomalies: Default::default(),
+        }
+    }
+    /// Attaches reference clocks (precise or SSR corrected), so the broadcast clocks are screened too
+    pub fn set_reference_clock_source<C: ClockSource + Send + 'static>(&mut self, clock: C) {
+        self.reference_clock = Some(Box::new(clock));
+    }
+    /// Defines whether anomalous vehicles are excluded (default), or only flagged
+    pub fn set_exclusion(&mut self, exclusion: bool) {
+        self.exclusion = exclusion;
+    }
+    /// Returns underlying [BroadcastOrbits]
+    pub fn broadcast(&self) -> &BroadcastOrbits {
+        &self.broadcast
+    }
+    /// Returns mutable underlying [BroadcastOrbits], to store new ephemerides
+    pub fn broadcast_mut(&mut self) -> &mut BroadcastOrbits {
+        &mut self.broadcast
+    }
+    /// Returns mutable reference [OrbitSource], to store new products or corrections
+    pub fn reference_mut(&mut self) -> &mut R {
+        &mut self.reference
+    }
+    /// Returns latest disagreement [m] of this anomalous [SV], as of latest comparison
+    pub fn anomaly(&self, sv: SV) -> Option<f64> {
+        [Comparison::Orbit, Comparison::Clock]
+            .iter()
+            .filter_map(|comparison| self.anomalies.get(&(sv, *comparison)))
+            .map(|(_, error_m)| *error_m)
+            .reduce(f64::max)
+    }
+    /// Returns vehicles whose broadcast states disagree with the reference, as of latest comparison
+    pub fn anomalies(&self) -> Vec<SV> {
+        let mut anomalies = self.anomalies.keys().map(|(sv, _)| *sv).collect::<Vec<_>>();
+        anomalies.dedup();
+        anomalies
+    }
+    /// Updates the status of this [SV], with this disagreement [m] at [Epoch].
+    /// Returns true if the broadcast state should be proposed.
+    fn screen(&mut self, t: Epoch, sv: SV, comparison: Comparison, error_m: f64) -> bool {
+        let key = (sv, comparison);
+        if error_m <= self.max_error_m {
+            self.anomalies.remove(&key);
+            return true;
+        }
+        if !self.anomalies.contains_key(&key) {
+            warn!(
+                "{}({}) - broadcast {:?} disagrees with reference by {:.3}m",
+                t, sv, comparison, error_m
+            );
+        }
+        self.anomalies.insert(key, (t, error_m));
+        !self.exclusion
+    }
+}
+
+impl<R: OrbitSource> OrbitSource for ScreenedOrbits<R> {
+    fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, interp: Interpolation) -> Option<Orbit> {
+        let broadcast = self.broadcast.next_at(t, sv, fr, interp)?;
+        let Some(reference) = self.reference.next_at(t, sv, fr, interp) else {
+            return Some(broadcast);
+        };
+        let error_m = (broadcast.radius_km - reference.radius_km).norm() * 1.0E3;
+        if self.screen(t, sv, Comparison::Orbit, error_m) {
+            Some(broadcast)
+        } else {
+            None
+        }
+    }
+    fn validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        self.broadcast.validity(sv)
+    }
+}
+
+impl<R: OrbitSource> ClockSource for ScreenedOrbits<R> {
+    fn next_clock_at(
+        &mut self,
+        t: Epoch,
+        sv: SV,
+        interp: Interpolation,
+    ) -> Option<ClockCorrection> {
+        let broadcast = self.broadcast.clock_correction(sv, t)?;
+        let Some(reference) = self
+            .reference_clock
+            .as_mut()
+            .and_then(|clock| clock.next_clock_at(t, sv, interp))
+        else {
+            return Some(broadcast);
+        };
+        // only the clock offsets are compared: the reference may not include the relativistic effect
+        let error_m =
+            (broadcast.duration - reference.duration).abs().to_seconds() * SPEED_OF_LIGHT_M_S;
+        if self.screen(t, sv, Comparison::Clock, error_m) {
+            Some(broadcast)
+        } else {
+            None
+        }
+    }
+    fn clock_validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        self.broadcast.validity(sv)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ScreenedOrbits;
+    use crate::{
+        clock::ClockSource,
+        orbit::{BroadcastOrbits, KeplerianEphemeris, OrbitSource},
+        prelude::{
+            Constellation, Duration, Epoch, Interpolation, SsrClockCorrection, SsrOrbitCorrection,
+            SsrOrbits, EARTH_ITRF93, SV,
+        },
+    };
+    use std::str::FromStr;
+
+    #[test]
+    fn broadcast_screening() {
+        let sv = SV::new(Constellation::GPS, 1);
+        let toe = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let t = toe + Duration::from_seconds(600.0);
+        let interp = Interpolation::default();
+
+        let mut broadcast = BroadcastOrbits::new();
+        broadcast.insert(
+            sv,
+            KeplerianEphemeris {
+                iode: 42,
+                toe,
+                toc: toe,
+                af0: 1.0E-4,
+                sqrt_a: 5153.7,
+                e: 0.01,
+                i0: 0.96,
+                ..Default::default()
+            },
+        );
+        let correction = SsrOrbitCorrection {
+            t,
+            iode: 42,
+            delta_m: (1.0, 0.0, 0.0),
+            dot_m_s: (0.0, 0.0, 0.0),
+        };
+
+        let mut screened = ScreenedOrbits::new(broadcast.clone(), SsrOrbits::new(broadcast), 10.0);
+
+        // no reference: unscreened
+        assert!(screened.next_at(t, sv, EARTH_ITRF93, interp).is_some());
+
+        // consistent
+        screened
+            .reference_mut()
+            .insert_orbit_correction(sv, correction);
+        assert!(screened.next_at(t, sv, EARTH_ITRF93, interp).is_some());
+        assert!(screened.anomalies().is_empty());
+
+        // anomalous: excluded
+        screened.reference_mut().insert_orbit_correction(
+            sv,
+            SsrOrbitCorrection {
+                delta_m: (50.0, 0.0, 0.0),
+                ..correction
+            },
+        );
+        assert!(screened.next_at(t, sv, EARTH_ITRF93, interp).is_none());
+        assert_eq!(screened.anomalies(), vec![sv]);
+        assert!((screened.anomaly(sv).unwrap() - 50.0).abs() < 1.0E-3);
+
+        // flagged only
+        screened.set_exclusion(false);
+        assert!(screened.next_at(t, sv, EARTH_ITRF93, interp).is_some());
+        assert_eq!(screened.anomalies(), vec![sv]);
+
+        // consistent again
+        screened
+            .reference_mut()
+            .insert_orbit_correction(sv, correction);
+        assert!(screened.next_at(t, sv, EARTH_ITRF93, interp).is_some());
+        assert!(screened.anomaly(sv).is_none());
+
+        // clock screening
+        assert!(screened.next_clock_at(t, sv, interp).is_some());
+        let mut reference = screened.reference_mut().clone();
+        reference.insert_clock_correction(
+            sv,
+            SsrClockCorrection {
+                t,
+                c0_m: 30.0,
+                c1_m_s: 0.0,
+                c2_m_s2: 0.0,
+            },
+        );
+        screened.set_exclusion(true);
+        screened.set_reference_clock_source(reference);
+        assert!(screened.next_clock_at(t, sv, interp).is_none());
+        assert!((screened.anomaly(sv).unwrap() - 30.0).abs() < 1.0E-3);
+    }
+}
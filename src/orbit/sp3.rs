@@ -0,0 +1,585 @@
+//! SP3 precise orbits
+use std::{
+    collections::BTreeMap,
+    io::{BufRead, BufReader},
+    path::Path,
+    str::FromStr,
+};
+
+use thiserror::Error;
+
+use crate::{
+    orbit::OrbitSource,
+    prelude::{
+        Duration, Epoch, Frame, Interpolation, InterpolationMethod, Orbit, TimeScale, Vector3, SV,
+    },
+};
+
+/// SP3 parsing errors
+#[derive(Debug, Error)]
+pub enum Sp3Error {
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("not a valid sp3 file")]
+    InvalidHeader,
+    #[error("invalid epoch description (line {0})")]
+    InvalidEpoch(usize),
+    #[error("invalid position record (line {0})")]
+    InvalidRecord(usize),
+}
+
+/// [Sp3Orbits] is an [OrbitSource] backed by SP3 (revision c or d) precise orbit products.
+/// SV positions are interpolated with Lagrangian polynomials of the proposed
+/// `orbit_order`, over a window centered on the requested [Epoch] when possible.
+/// When the product describes the SV velocities, [InterpolationMethod::Hermite]
+/// interpolates over both positions and velocities.
+/// It never extrapolates, nor interpolates across data gaps or missing records.
+/// Requested [Frame] is assumed to be the (Earth fixed) frame of the SP3 products.
+#[derive(Debug, Clone, Default)]
+pub struct Sp3Orbits {
+    /// Product [TimeScale]
+    timescale: TimeScale,
+    /// Product sampling period
+    sampling: Option<Duration>,
+    /// Position records [km], per [SV]
+    states: BTreeMap<SV, Vec<(Epoch, Vector3<f64>)>>,
+    /// Velocity records [km/s], per [SV]
+    velocities: BTreeMap<SV, Vec<(Epoch, Vector3<f64>)>>,
+}
+
+fn parse_timescale(content: &str) -> TimeScale {
+    match content.trim() {
+        "GAL" => TimeScale::GST,
+        "BDT" => TimeScale::BDT,
+        "QZS" => TimeScale::QZSST,
+        "UTC" | "GLO" => TimeScale::UTC,
+        "TAI" => TimeScale::TAI,
+        _ => TimeScale::GPST,
+    }
+}
+
+fn parse_epoch(content: &str, ts: TimeScale) -> Option<Epoch> {
+    let items = content.split_whitespace().collect::<Vec<_>>();
+    if items.len() < 6 {
+        return None;
+    }
+    let seconds = f64::from_str(items[5]).ok()?;
+    Some(Epoch::from_gregorian(
+        i32::from_str(items[0]).ok()?,
+        u8::from_str(items[1]).ok()?,
+        u8::from_str(items[2]).ok()?,
+        u8::from_str(items[3]).ok()?,
+        u8::from_str(items[4]).ok()?,
+        seconds.trunc() as u8,
+        (seconds.fract() * 1.0E9).round() as u32,
+        ts,
+    ))
+}
+
+/// Parses [SV] and its three coordinates, from a position or velocity record
+fn parse_record(line: &str, line_num: usize) -> Result<(SV, Vector3<f64>), Sp3Error> {
+    let sv = line
+        .get(1..4)
+        .and_then(|sv| SV::from_str(&sv.replace(' ', "G")).ok())
+        .ok_or(Sp3Error::InvalidRecord(line_num))?;
+
+    let coords = line
+        .get(4..)
+        .ok_or(Sp3Error::InvalidRecord(line_num))?
+        .split_whitespace()
+        .take(3)
+        .map(f64::from_str)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| Sp3Error::InvalidRecord(line_num))?;
+
+    if coords.len() < 3 {
+        return Err(Sp3Error::InvalidRecord(line_num));
+    }
+
+    Ok((sv, Vector3::new(coords[0], coords[1], coords[2])))
+}
+
+/// Inserts a record in time ordered `records`, averaging records of identical [Epoch]
+fn merge_record(records: &mut Vec<(Epoch, Vector3<f64>)>, t: Epoch, value: Vector3<f64>) {
+    match records.binary_search_by(|(t_i, _)| t_i.cmp(&t)) {
+        Ok(idx) => records[idx].1 = (records[idx].1 + value) / 2.0,
+        Err(idx) => records.insert(idx, (t, value)),
+    }
+}
+
+/// Lagrange basis polynomial `i` over the nodes `xs`, evaluated at `x`
+fn basis(xs: &[f64], i: usize, x: f64) -> f64 {
+    xs.iter()
+        .enumerate()
+        .filter(|(j, _)| *j != i)
+        .map(|(_, x_j)| (x - x_j) / (xs[i] - x_j))
+        .product()
+}
+
+/// Derivative of the Lagrange basis polynomial `i` over the nodes `xs`, evaluated at `x`
+fn basis_derivative(xs: &[f64], i: usize, x: f64) -> f64 {
+    xs.iter()
+        .enumerate()
+        .filter(|(k, _)| *k != i)
+        .map(|(k, x_k)| {
+            let others = xs
+                .iter()
+                .enumerate()
+                .filter(|(j, _)| *j != i && *j != k)
+                .map(|(_, x_j)| (x - x_j) / (xs[i] - x_j))
+                .product::<f64>();
+            others / (xs[i] - x_k)
+        })
+        .sum()
+}
+
+/// Evaluates the Hermite polynomial matching both the values and the derivatives
+/// of `nodes` (x, y, dy/dx), at `x`. Returns the interpolated value and its derivative.
+fn hermite(nodes: &[(f64, Vector3<f64>, Vector3<f64>)], x: f64) -> (Vector3<f64>, Vector3<f64>) {
+    let xs = nodes.iter().map(|(x_i, _, _)| *x_i).collect::<Vec<_>>();
+    let mut value = Vector3::<f64>::zeros();
+    let mut derivative = Vector3::<f64>::zeros();
+    for (i, (x_i, y_i, dy_i)) in nodes.iter().enumerate() {
+        let l_i = basis(&xs, i, x);
+        let dl_i = basis_derivative(&xs, i, x);
+        // l_i'(x_i)
+        let c_i = basis_derivative(&xs, i, *x_i);
+        let dx = x - x_i;
+        let a_i = 1.0 - 2.0 * c_i * dx;
+        value += y_i * (a_i * l_i * l_i) + dy_i * (dx * l_i * l_i);
+        derivative +=
+            y_i * (2.0 * l_i * (a_i * dl_i - c_i * l_i)) + dy_i * (l_i * (l_i + 2.0 * dx * dl_i));
+    }
+    (value, derivative)
+}
+
+/// Evaluates the Lagrange polynomial passing through `points` at `x`
+fn lagrange(points: &[(f64, Vector3<f64>)], x: f64) -> Vector3<f64> {
+    let mut result = Vector3::<f64>::zeros();
+    for (i, (x_i, y_i)) in points.iter().enumerate() {
+        let mut l_i = 1.0_f64;
+        for (j, (x_j, _)) in points.iter().enumerate() {
+            if i != j {
+                l_i *= (x - x_j) / (x_i - x_j);
+            }
+        }
+        result += y_i * l_i;
+    }
+    result
+}
+
+/// Evaluates the derivative of the Lagrange polynomial passing through `points` at `x`
+fn lagrange_derivative(points: &[(f64, Vector3<f64>)], x: f64) -> Vector3<f64> {
+    let xs = points.iter().map(|(x_i, _)| *x_i).collect::<Vec<_>>();
+    let mut result = Vector3::<f64>::zeros();
+    for (i, (_, y_i)) in points.iter().enumerate() {
+        result += y_i * basis_derivative(&xs, i, x);
+    }
+    result
+}
+
+impl Sp3Orbits {
+    /// Parses [Sp3Orbits] from local SP3 file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, Sp3Error> {
+        let fd = std::fs::File::open(path)?;
+        Self::from_reader(BufReader::new(fd))
+    }
+    /// Parses [Sp3Orbits] from any SP3 content
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, Sp3Error> {
+        let mut sp3 = Self::default();
+        let mut epoch = Option::<Epoch>::None;
+        let mut timescale_parsed = false;
+
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            let line_num = index + 1;
+
+            if index == 0 {
+                if !line.starts_with('#') {
+                    return Err(Sp3Error::InvalidHeader);
+                }
+                continue;
+            }
+
+            if line.starts_with("%c") && !timescale_parsed {
+                if let Some(content) = line.get(9..12) {
+                    sp3.timescale = parse_timescale(content);
+                }
+                timescale_parsed = true;
+            } else if let Some(content) = line.strip_prefix('*') {
+                let t =
+                    parse_epoch(content, sp3.timescale).ok_or(Sp3Error::InvalidEpoch(line_num))?;
+                if let Some(prev) = epoch {
+                    let dt = t - prev;
+                    if dt > Duration::ZERO
+                        && !matches!(sp3.sampling, Some(sampling) if sampling <= dt)
+                    {
+                        sp3.sampling = Some(dt);
+                    }
+                }
+                epoch = Some(t);
+            } else if line.starts_with('P') || line.starts_with('V') {
+                let t = epoch.ok_or(Sp3Error::InvalidRecord(line_num))?;
+                let (sv, coords) = parse_record(&line, line_num)?;
+
+                // null coordinates: missing or bad record
+                if coords.iter().all(|c| *c == 0.0) {
+                    continue;
+                }
+
+                if line.starts_with('P') {
+                    sp3.states.entry(sv).or_default().push((t, coords));
+                } else {
+                    // [dm/s]
+                    sp3.velocities
+                        .entry(sv)
+                        .or_default()
+                        .push((t, coords * 1.0E-4));
+                }
+            } else if line.starts_with("EOF") {
+                break;
+            }
+        }
+        Ok(sp3)
+    }
+    /// Stitches the following (typically next day) product to this one, so the interpolation
+    /// continues across the day boundary rather than failing (or shifting its window) at 00:00.
+    /// Records of epochs described by both products (overlap) are averaged,
+    /// smoothing the discontinuity between the two orbit solutions.
+    pub fn stitch(&mut self, next: Self) {
+        for (sv, states) in next.states {
+            let merged = self.states.entry(sv).or_default();
+            for (t, pos) in states {
+                merge_record(merged, t.to_time_scale(self.timescale), pos);
+            }
+        }
+        for (sv, velocities) in next.velocities {
+            let merged = self.velocities.entry(sv).or_default();
+            for (t, vel) in velocities {
+                merge_record(merged, t.to_time_scale(self.timescale), vel);
+            }
+        }
+        self.sampling = match (self.sampling, next.sampling) {
+            (Some(lhs), Some(rhs)) => Some(lhs.min(rhs)),
+            (lhs, rhs) => lhs.or(rhs),
+        };
+    }
+    /// [TimeScale] of this product
+    pub fn timescale(&self) -> TimeScale {
+        self.timescale
+    }
+    /// Sampling period of this product
+    pub fn sampling(&self) -> Option<Duration> {
+        self.sampling
+    }
+    /// Returns list of [SV] described by this product
+    pub fn sv(&self) -> Vec<SV> {
+        self.states.keys().copied().collect()
+    }
+    /// Returns `size` consecutive position records of this [SV], surrounding [Epoch].
+    /// The window is centered when possible, shifted at the boundaries,
+    /// and never spans data gaps.
+    fn window(&self, sv: SV, t: Epoch, size: usize) -> Option<&[(Epoch, Vector3<f64>)]> {
+        let states = self.states.get(&sv)?;
+        if size == 0 || states.len() < size {
+            return None;
+        }
+
+        let (first, last) = (states.first()?.0, states.last()?.0);
+        if t < first || t > last {
+            return None;
+        }
+
+        let idx = states.partition_point(|(t_i, _)| *t_i < t);
+        let start = idx.saturating_sub(size / 2).min(states.len() - size);
+        let window = &states[start..start + size];
+
+        // do not interpolate over data gaps
+        if let Some(sampling) = self.sampling {
+            let max_gap = sampling * 1.5;
+            if window.windows(2).any(|w| w[1].0 - w[0].0 > max_gap) {
+                return None;
+            }
+        }
+
+        Some(window)
+    }
+    /// Interpolates [SV] position [km] at [Epoch], with a polynomial of given order.
+    pub fn interpolate(&self, sv: SV, t: Epoch, order: usize) -> Option<Vector3<f64>> {
+        let states = self.states.get(&sv)?;
+        if states.len() < order + 1 {
+            return None;
+        }
+
+        if let Ok(idx) = states.binary_search_by(|(t_i, _)| t_i.cmp(&t)) {
+            return Some(states[idx].1);
+        }
+
+        let window = self.window(sv, t, order + 1)?;
+
+        let t0 = window[0].0;
+        let points = window
+            .iter()
+            .map(|(t_i, pos)| ((*t_i - t0).to_seconds(), *pos))
+            .collect::<Vec<_>>();
+
+        Some(lagrange(&points, (t - t0).to_seconds()))
+    }
+    /// Interpolates [SV] velocity [km/s] at [Epoch], as the derivative of the position
+    /// polynomial of given order. Defined over the whole validity interval, edges included.
+    pub fn interpolate_velocity(&self, sv: SV, t: Epoch, order: usize) -> Option<Vector3<f64>> {
+        let window = self.window(sv, t, order + 1)?;
+
+        let t0 = window[0].0;
+        let points = window
+            .iter()
+            .map(|(t_i, pos)| ((*t_i - t0).to_seconds(), *pos))
+            .collect::<Vec<_>>();
+
+        Some(lagrange_derivative(&points, (t - t0).to_seconds()))
+    }
+    /// Interpolates [SV] position [km] and velocity [km/s] at [Epoch], with a Hermite polynomial
+    /// of given order, over both position and velocity records.
+    /// Returns None if the velocity is not described at each node.
+    pub fn interpolate_hermite(
+        &self,
+        sv: SV,
+        t: Epoch,
+        order: usize,
+    ) -> Option<(Vector3<f64>, Vector3<f64>)> {
+        let velocities = self.velocities.get(&sv)?;
+
+        // each node brings two conditions (position and velocity)
+        let window = self.window(sv, t, order / 2 + 1)?;
+
+        let t0 = window[0].0;
+        let nodes = window
+            .iter()
+            .map(|(t_i, pos)| {
+                let idx = velocities.binary_search_by(|(t_j, _)| t_j.cmp(t_i)).ok()?;
+                Some(((*t_i - t0).to_seconds(), *pos, velocities[idx].1))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(hermite(&nodes, (t - t0).to_seconds()))
+    }
+}
+
+impl OrbitSource for Sp3Orbits {
+    fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, interp: Interpolation) -> Option<Orbit> {
+        if interp.method == InterpolationMethod::Hermite {
+            if let Some((pos, vel)) = self.interpolate_hermite(sv, t, interp.orbit_order) {
+                return Some(
+                    Orbit::from_position(pos[0], pos[1], pos[2], t, fr).with_velocity_km_s(vel),
+                );
+            }
+        }
+        let order = interp.orbit_order;
+        let pos = self.interpolate(sv, t, order)?;
+        let vel = self.interpolate_velocity(sv, t, order)?;
+        Some(Orbit::from_position(pos[0], pos[1], pos[2], t, fr).with_velocity_km_s(vel))
+    }
+    fn validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        let states = self.states.get(&sv)?;
+        Some((states.first()?.0, states.last()?.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Sp3Orbits;
+    use crate::{
+        orbit::OrbitSource,
+        prelude::{
+            Constellation, Duration, Epoch, Interpolation, InterpolationMethod, TimeScale, Vector3,
+            EARTH_J2000, SV,
+        },
+    };
+    use std::str::FromStr;
+
+    const RADIUS_KM: f64 = 26_560.0;
+    const RATE_RAD_S: f64 = 1.458_4E-4;
+
+    /// Circular orbit, sampled every 15'. Epoch index `gap` is missing.
+    /// Velocity records are described when `velocities` is set.
+    fn sp3_content(gap: usize, velocities: bool) -> String {
+        let mut content = "#dP2020  6 25  0  0  0.00000000      24 ORBIT IGS14 HLM  IGS\n\
+            %c G  cc GPS ccc cccc cccc cccc cccc ccccc ccccc ccccc ccccc\n"
+            .to_string();
+        for i in 0..24 {
+            let (hh, mm) = (900 * i / 3600, (900 * i % 3600) / 60);
+            content.push_str(&format!("*  2020  6 25 {:>2} {:>2}  0.00000000\n", hh, mm));
+            let (x, y) = if i == gap {
+                (0.0, 0.0)
+            } else {
+                let phase = RATE_RAD_S * 900.0 * i as f64;
+                (RADIUS_KM * phase.cos(), RADIUS_KM * phase.sin())
+            };
+            content.push_str(&format!(
+                "PG01{:14.6}{:14.6}{:14.6}    100.000000\n",
+                x, y, 0.0
+            ));
+            if velocities && i != gap {
+                // [dm/s]
+                let phase = RATE_RAD_S * 900.0 * i as f64;
+                let (vx, vy) = (-phase.sin() * 1.0E4, phase.cos() * 1.0E4);
+                content.push_str(&format!(
+                    "VG01{:14.6}{:14.6}{:14.6}      0.000000\n",
+                    vx * RADIUS_KM * RATE_RAD_S,
+                    vy * RADIUS_KM * RATE_RAD_S,
+                    0.0
+                ));
+            }
+        }
+        content.push_str("EOF\n");
+        content
+    }
+
+    #[test]
+    fn sp3_interpolation() {
+        let sp3 = Sp3Orbits::from_reader(sp3_content(100, false).as_bytes()).unwrap();
+        let sv = SV::new(Constellation::GPS, 1);
+        assert_eq!(sp3.timescale(), TimeScale::GPST);
+        assert_eq!(sp3.sampling(), Some(Duration::from_seconds(900.0)));
+        assert_eq!(sp3.sv(), vec![sv]);
+
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        for dt_s in [450.0, 5000.0, 10_800.0, 600.0, 20_000.0] {
+            let t = t0 + Duration::from_seconds(dt_s);
+            let pos = sp3.interpolate(sv, t, 9).unwrap();
+            let phase = RATE_RAD_S * dt_s;
+            let err_km = ((pos[0] - RADIUS_KM * phase.cos()).powi(2)
+                + (pos[1] - RADIUS_KM * phase.sin()).powi(2))
+            .sqrt();
+            assert!(err_km < 1.0E-3, "error {} km at {}", err_km, t);
+        }
+
+        // no extrapolation
+        assert_eq!(
+            sp3.validity(sv),
+            Some((t0, t0 + Duration::from_seconds(900.0 * 23.0)))
+        );
+        assert!(sp3
+            .interpolate(sv, t0 - Duration::from_seconds(1.0), 9)
+            .is_none());
+        assert!(sp3
+            .interpolate(sv, t0 + Duration::from_seconds(900.0 * 23.0 + 1.0), 9)
+            .is_none());
+    }
+
+    #[test]
+    fn sp3_velocity() {
+        let mut sp3 = Sp3Orbits::from_reader(sp3_content(100, false).as_bytes()).unwrap();
+        let sv = SV::new(Constellation::GPS, 1);
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let interp = Interpolation {
+            method: InterpolationMethod::Lagrange,
+            orbit_order: 9,
+            ..Default::default()
+        };
+
+        // validity edges included
+        let (first, last) = sp3.validity(sv).unwrap();
+        for t in [first, t0 + Duration::from_seconds(5000.0), last] {
+            let orbit = sp3.next_at(t, sv, EARTH_J2000, interp).unwrap();
+            let pos_vel = orbit.to_cartesian_pos_vel();
+
+            let phase = RATE_RAD_S * (t - t0).to_seconds();
+            let vel_km_s = RADIUS_KM * RATE_RAD_S;
+            let err_km_s = ((pos_vel[3] + vel_km_s * phase.sin()).powi(2)
+                + (pos_vel[4] - vel_km_s * phase.cos()).powi(2))
+            .sqrt();
+            assert!(err_km_s < 1.0E-6, "error {} km/s at {}", err_km_s, t);
+        }
+    }
+
+    #[test]
+    fn sp3_data_gap() {
+        let sp3 = Sp3Orbits::from_reader(sp3_content(12, false).as_bytes()).unwrap();
+        let sv = SV::new(Constellation::GPS, 1);
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        assert!(sp3
+            .interpolate(sv, t0 + Duration::from_seconds(900.0 * 11.5), 9)
+            .is_none());
+        assert!(sp3
+            .interpolate(sv, t0 + Duration::from_seconds(900.0 * 1.5), 3)
+            .is_some());
+    }
+
+    #[test]
+    fn sp3_hermite_interpolation() {
+        let sv = SV::new(Constellation::GPS, 1);
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let interp = Interpolation {
+            method: InterpolationMethod::Hermite,
+            orbit_order: 9,
+            ..Default::default()
+        };
+
+        let mut sp3 = Sp3Orbits::from_reader(sp3_content(100, true).as_bytes()).unwrap();
+        for dt_s in [0.0, 450.0, 5000.0, 10_800.0, 20_000.0, 900.0 * 23.0] {
+            let t = t0 + Duration::from_seconds(dt_s);
+            let (pos, vel) = sp3.interpolate_hermite(sv, t, interp.orbit_order).unwrap();
+            let phase = RATE_RAD_S * dt_s;
+            let err_km = ((pos[0] - RADIUS_KM * phase.cos()).powi(2)
+                + (pos[1] - RADIUS_KM * phase.sin()).powi(2))
+            .sqrt();
+            assert!(err_km < 1.0E-3, "error {} km at {}", err_km, t);
+
+            let vel_km_s = RADIUS_KM * RATE_RAD_S;
+            let err_km_s = ((vel[0] + vel_km_s * phase.sin()).powi(2)
+                + (vel[1] - vel_km_s * phase.cos()).powi(2))
+            .sqrt();
+            assert!(err_km_s < 1.0E-6, "error {} km/s at {}", err_km_s, t);
+
+            let orbit = sp3.next_at(t, sv, EARTH_J2000, interp).unwrap();
+            let pos_vel = orbit.to_cartesian_pos_vel();
+            assert!((pos_vel[3] - vel[0]).abs() < 1.0E-12);
+            assert!((pos_vel[4] - vel[1]).abs() < 1.0E-12);
+        }
+
+        // velocities are not described: falls back to Lagrange
+        let t = t0 + Duration::from_seconds(5000.0);
+        let mut sp3 = Sp3Orbits::from_reader(sp3_content(100, false).as_bytes()).unwrap();
+        assert!(sp3.interpolate_hermite(sv, t, 9).is_none());
+        assert!(sp3.next_at(t, sv, EARTH_J2000, interp).is_some());
+    }
+
+    #[test]
+    fn sp3_day_boundary() {
+        let sv = SV::new(Constellation::GPS, 1);
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let day = Sp3Orbits::from_reader(sp3_content(100, false).as_bytes()).unwrap();
+
+        // following product: same orbit, offset by 5 cm, overlapping on its first epoch
+        let mut next = Sp3Orbits::from_reader(sp3_content(100, false).as_bytes()).unwrap();
+        let offset = Duration::from_seconds(900.0 * 23.0);
+        for states in next.states.values_mut() {
+            for (t, pos) in states.iter_mut() {
+                let phase = RATE_RAD_S * (*t + offset - t0).to_seconds();
+                *t += offset;
+                *pos = Vector3::new(RADIUS_KM * phase.cos(), RADIUS_KM * phase.sin(), 5.0E-5);
+            }
+        }
+
+        // boundary is not covered by a single product
+        let boundary = t0 + Duration::from_seconds(900.0 * 23.5);
+        assert!(day.interpolate(sv, boundary, 9).is_none());
+
+        let mut stitched = day.clone();
+        stitched.stitch(next);
+        assert_eq!(stitched.sampling(), Some(Duration::from_seconds(900.0)));
+        assert_eq!(stitched.states[&sv].len(), 47);
+
+        // overlapping record is averaged
+        let overlap = stitched.interpolate(sv, t0 + offset, 9).unwrap();
+        assert!((overlap[2] - 2.5E-5).abs() < 1.0E-9);
+
+        let pos = stitched.interpolate(sv, boundary, 9).unwrap();
+        let phase = RATE_RAD_S * (boundary - t0).to_seconds();
+        let err_km = ((pos[0] - RADIUS_KM * phase.cos()).powi(2)
+            + (pos[1] - RADIUS_KM * phase.sin()).powi(2))
+        .sqrt();
+        assert!(err_km < 1.0E-3, "error {} km", err_km);
+    }
+}
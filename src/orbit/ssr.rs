@@ -0,0 +1,497 @@
+//! State Space Representation (SSR) corrections
+use std::collections::BTreeMap;
+
+use crate::{
+    candidate::Candidate,
+    clock::ClockSource,
+    constants::SPEED_OF_LIGHT_M_S,
+    corrections::{Correction, CorrectionLoss, CorrectionManager, CorrectionStatus},
+    orbit::{BroadcastOrbits, KeplerianEphemeris, OrbitSource},
+    prelude::{
+        Carrier, ClockCorrection, Duration, Epoch, Frame, Interpolation, Orbit, Vector3, SV,
+    },
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Default maximal age of SSR corrections
+const DEFAULT_MAX_AGE_S: f64 = 90.0;
+
+/// [SsrOrbitCorrection] to the broadcast orbit, in the radial,
+/// along track and cross track frame.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SsrOrbitCorrection {
+    /// Reference [Epoch]
+    pub t: Epoch,
+    /// Issue of Data of the broadcast ephemeris this correction applies to
+    pub iode: u16,
+    /// Radial, along track and cross track corrections [m]
+    pub delta_m: (f64, f64, f64),
+    /// Radial, along track and cross track correction rates [m/s]
+    pub dot_m_s: (f64, f64, f64),
+}
+
+impl Correction for SsrOrbitCorrection {
+    fn epoch(&self) -> Epoch {
+        self.t
+    }
+    fn iod(&self) -> u16 {
+        self.iode
+    }
+}
+
+impl SsrOrbitCorrection {
+    /// Returns ECEF correction [m] at [Epoch], to be subtracted from the broadcast
+    /// position, described by its ECEF position [m] and velocity [m/s].
+    pub fn ecef_correction_m(
+        &self,
+        t: Epoch,
+        pos_m: Vector3<f64>,
+        vel_m_s: Vector3<f64>,
+    ) -> Vector3<f64> {
+        let dt = (t - self.t).to_seconds();
+        let cross = pos_m.cross(&vel_m_s);
+        let e_along = vel_m_s.normalize();
+        let e_cross = cross.normalize();
+        let e_radial = e_along.cross(&e_cross);
+
+        let (radial, along, cross) = self.delta_m;
+        let (dot_radial, dot_along, dot_cross) = self.dot_m_s;
+
+        e_radial * (radial + dot_radial * dt)
+            + e_along * (along + dot_along * dt)
+            + e_cross * (cross + dot_cross * dt)
+    }
+}
+
+/// [SsrClockCorrection] to the broadcast clock, expressed in meters.
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SsrClockCorrection {
+    /// Reference [Epoch]
+    pub t: Epoch,
+    /// Constant term [m]
+    pub c0_m: f64,
+    /// Linear term [m/s]
+    pub c1_m_s: f64,
+    /// Quadratic term [m/s^2]
+    pub c2_m_s2: f64,
+}
+
+impl SsrClockCorrection {
+    /// Returns correction [s] at [Epoch], to be added to the broadcast clock offset
+    pub fn correction_s(&self, t: Epoch) -> f64 {
+        let dt = (t - self.t).to_seconds();
+        (self.c0_m + self.c1_m_s * dt + self.c2_m_s2 * dt.powi(2)) / SPEED_OF_LIGHT_M_S
+    }
+    /// Returns correction rate [s/s] at [Epoch], to be added to the broadcast clock drift
+    pub fn correction_rate_s_s(&self, t: Epoch) -> f64 {
+        let dt = (t - self.t).to_seconds();
+        (self.c1_m_s + 2.0 * self.c2_m_s2 * dt) / SPEED_OF_LIGHT_M_S
+    }
+}
+
+/// [SsrBias] (code or phase) affecting a signal
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SsrBias {
+    /// [Carrier] signal
+    pub carrier: Carrier,
+    /// Bias [m], to be added to the observation
+    pub bias_m: f64,
+}
+
+/// [SsrOrbits] applies State Space Representation corrections (real-time PPP services)
+/// to [BroadcastOrbits]. It is both an [OrbitSource] and a [ClockSource].
+///
+/// Orbit corrections only apply to the broadcast ephemeris of identical Issue of Data,
+/// and clock corrections only apply along an orbit correction.
+/// Vehicles without recent (see [Self::with_max_age]) corrections are not proposed:
+/// mixing broadcast and precise states would deteriorate a PPP solution.
+/// Unless [Self::with_broadcast_fallback] is requested, in which case their broadcast
+/// states are proposed. Each fallback to broadcast-only is reported, see [Self::status].
+/// GLONASS vehicles are not supported.
+#[derive(Debug, Clone)]
+pub struct SsrOrbits {
+    broadcast: BroadcastOrbits,
+    max_age: Duration,
+    broadcast_fallback: bool,
+    orbits: CorrectionManager<SsrOrbitCorrection>,
+    clocks: BTreeMap<SV, SsrClockCorrection>,
+    code_biases: BTreeMap<SV, Vec<SsrBias>>,
+    phase_biases: BTreeMap<SV, Vec<SsrBias>>,
+}
+
+impl SsrOrbits {
+    /// Builds new [SsrOrbits], applying corrections to these [BroadcastOrbits]
+    pub fn new(broadcast: BroadcastOrbits) -> Self {
+        Self {
+            broadcast,
+            max_age: Duration::from_seconds(DEFAULT_MAX_AGE_S),
+            broadcast_fallback: false,
+            orbits: CorrectionManager::new("ssr", Duration::from_seconds(DEFAULT_MAX_AGE_S)),
+            clocks: Default::default(),
+            code_biases: Default::default(),
+            phase_biases: Default::default(),
+        }
+    }
+    /// Copies and returns [SsrOrbits] with maximal age of the corrections
+    pub fn with_max_age(&self, max_age: Duration) -> Self {
+        let mut s = self.clone();
+        s.max_age = max_age;
+        s.orbits.set_max_age(max_age);
+        s
+    }
+    /// Copies and returns [SsrOrbits] proposing broadcast states for vehicles
+    /// without valid corrections, rather than dropping them
+    pub fn with_broadcast_fallback(&self, fallback: bool) -> Self {
+        let mut s = self.clone();
+        s.broadcast_fallback = fallback;
+        s
+    }
+    /// Returns underlying [BroadcastOrbits]
+    pub fn broadcast(&self) -> &BroadcastOrbits {
+        &self.broadcast
+    }
+    /// Returns mutable underlying [BroadcastOrbits], to store new ephemerides
+    pub fn broadcast_mut(&mut self) -> &mut BroadcastOrbits {
+        &mut self.broadcast
+    }
+    /// Stores new [SsrOrbitCorrection] for this [SV], replacing the previous one
+    pub fn insert_orbit_correction(&mut self, sv: SV, correction: SsrOrbitCorrection) {
+        self.orbits.insert(sv, correction);
+    }
+    /// Stores new [SsrClockCorrection] for this [SV], replacing the previous one
+    pub fn insert_clock_correction(&mut self, sv: SV, correction: SsrClockCorrection) {
+        self.clocks.insert(sv, correction);
+    }
+    /// Stores new code [SsrBias]es for this [SV], replacing the previous ones
+    pub fn insert_code_biases(&mut self, sv: SV, biases: Vec<SsrBias>) {
+        self.code_biases.insert(sv, biases);
+    }
+    /// Stores new phase [SsrBias]es for this [SV], replacing the previous ones
+    pub fn insert_phase_biases(&mut self, sv: SV, biases: Vec<SsrBias>) {
+        self.phase_biases.insert(sv, biases);
+    }
+    fn is_recent(&self, t_ref: Epoch, t: Epoch) -> bool {
+        (t - t_ref).abs() <= self.max_age
+    }
+    /// Ages out corrections (and ephemerides) that may no longer be used after [Epoch]
+    pub fn discard_outdated(&mut self, t: Epoch) {
+        self.broadcast.discard_outdated(t);
+        self.orbits.discard_outdated(t);
+        let max_age = self.max_age;
+        self.clocks.retain(|_, corr| t - corr.t <= max_age);
+    }
+    /// Returns [SsrOrbitCorrection] for [SV], if it may be used at [Epoch]
+    pub fn orbit_correction(&self, sv: SV, t: Epoch) -> Option<&SsrOrbitCorrection> {
+        self.orbits.get(sv, t).ok()
+    }
+    /// Returns [SsrOrbitCorrection] for [SV] at [Epoch], along the
+    /// broadcast ephemeris it applies to, or the reason it may not be used
+    fn resolve_orbit_correction(
+        &self,
+        sv: SV,
+        t: Epoch,
+    ) -> Result<(&SsrOrbitCorrection, &KeplerianEphemeris), CorrectionLoss> {
+        let correction = self.orbits.get(sv, t)?;
+        let eph = self
+            .broadcast
+            .ephemeris_iod(sv, t, correction.iode)
+            .ok_or(CorrectionLoss::IodMismatch)?;
+        Ok((correction, eph))
+    }
+    /// Returns corrected [ClockCorrection] for [SV] at [Epoch]
+    pub fn clock_correction(&self, sv: SV, t: Epoch) -> Option<ClockCorrection> {
+        let (_, eph) = self.resolve_orbit_correction(sv, t).ok()?;
+        let clock = self
+            .clocks
+            .get(&sv)
+            .filter(|corr| self.is_recent(corr.t, t))?;
+        let broadcast = eph.clock_correction(sv, t)?;
+        Some(ClockCorrection {
+            duration: broadcast.duration + Duration::from_seconds(clock.correction_s(t)),
+            drift: broadcast
+                .drift
+                .map(|drift| drift + clock.correction_rate_s_s(t)),
+            ..broadcast
+        })
+    }
+    /// Returns latest [CorrectionStatus] of [SV], as of latest orbit request
+    pub fn status(&self, sv: SV) -> Option<CorrectionStatus> {
+        self.orbits.status(sv)
+    }
+    /// Returns vehicles that fell back to broadcast-only, as of latest orbit request
+    pub fn broadcast_only(&self) -> Vec<SV> {
+        self.orbits.broadcast_only()
+    }
+    /// Returns code bias [m] affecting this [SV] signal
+    pub fn code_bias(&self, sv: SV, carrier: Carrier) -> Option<f64> {
+        self.code_biases
+            .get(&sv)?
+            .iter()
+            .find(|bias| bias.carrier == carrier)
+            .map(|bias| bias.bias_m)
+    }
+    /// Returns phase bias [m] affecting this [SV] signal
+    pub fn phase_bias(&self, sv: SV, carrier: Carrier) -> Option<f64> {
+        self.phase_biases
+            .get(&sv)?
+            .iter()
+            .find(|bias| bias.carrier == carrier)
+            .map(|bias| bias.bias_m)
+    }
+    /// Applies known code and phase biases to this [Candidate]'s observations
+    pub fn apply_biases(&self, candidate: &mut Candidate) {
+        let sv = candidate.sv;
+        for obs in candidate.observations.iter_mut() {
+            if let (Some(pseudo), Some(bias)) =
+                (obs.pseudo.as_mut(), self.code_bias(sv, obs.carrier))
+            {
+                *pseudo += bias;
+            }
+            if let (Some(phase), Some(bias)) =
+                (obs.phase.as_mut(), self.phase_bias(sv, obs.carrier))
+            {
+                *phase += bias;
+            }
+        }
+    }
+}
+
+impl OrbitSource for SsrOrbits {
+    fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, interp: Interpolation) -> Option<Orbit> {
+        let (correction, eph) = match self.resolve_orbit_correction(sv, t) {
+            Ok((correction, eph)) => (*correction, eph),
+            Err(loss) => {
+                self.orbits
+                    .report(t, sv, CorrectionStatus::BroadcastOnly(loss));
+                if self.broadcast_fallback {
+                    return self.broadcast.next_at(t, sv, fr, interp);
+                }
+                return None;
+            },
+        };
+        let state = eph.orbit(sv, t, fr)?.to_cartesian_pos_vel() * 1.0E3;
+
+        let pos_m = Vector3::new(state[0], state[1], state[2]);
+        let vel_m_s = Vector3::new(state[3], state[4], state[5]);
+
+        // rate of the correction, for the velocity
+        let half_step = Duration::from_seconds(0.5);
+        let delta_m = correction.ecef_correction_m(t, pos_m, vel_m_s);
+        let delta_dot_m_s = correction.ecef_correction_m(t + half_step, pos_m, vel_m_s)
+            - correction.ecef_correction_m(t - half_step, pos_m, vel_m_s);
+
+        let pos_km = (pos_m - delta_m) / 1.0E3;
+        let vel_km_s = (vel_m_s - delta_dot_m_s) / 1.0E3;
+        self.orbits.report(t, sv, CorrectionStatus::Corrected);
+        Some(
+            Orbit::from_position(pos_km[0], pos_km[1], pos_km[2], t, fr)
+                .with_velocity_km_s(vel_km_s),
+        )
+    }
+    fn validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        // corrections apply to the broadcast states
+        self.broadcast.validity(sv)
+    }
+}
+
+impl ClockSource for SsrOrbits {
+    fn next_clock_at(&mut self, t: Epoch, sv: SV, _: Interpolation) -> Option<ClockCorrection> {
+        if self.broadcast_fallback && self.resolve_orbit_correction(sv, t).is_err() {
+            // consistent with the broadcast orbit
+            return self.broadcast.clock_correction(sv, t);
+        }
+        self.clock_correction(sv, t)
+    }
+    fn clock_validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        self.broadcast.validity(sv)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SsrBias, SsrClockCorrection, SsrOrbitCorrection, SsrOrbits};
+    use crate::{
+        clock::ClockSource,
+        corrections::{CorrectionLoss, CorrectionStatus},
+        orbit::{BroadcastOrbits, KeplerianEphemeris, OrbitSource},
+        prelude::{
+            Candidate, Carrier, Constellation, Duration, Epoch, Interpolation, Observation,
+            EARTH_ITRF93, SPEED_OF_LIGHT_M_S, SV,
+        },
+    };
+    use std::str::FromStr;
+
+    fn ssr_orbits(toe: Epoch) -> SsrOrbits {
+        let sv = SV::new(Constellation::GPS, 1);
+        let mut broadcast = BroadcastOrbits::new();
+        broadcast.insert(
+            sv,
+            KeplerianEphemeris {
+                iode: 42,
+                toe,
+                toc: toe,
+                af0: 1.0E-4,
+                sqrt_a: 5153.7,
+                e: 0.01,
+                i0: 0.96,
+                ..Default::default()
+            },
+        );
+        SsrOrbits::new(broadcast)
+    }
+
+    #[test]
+    fn ssr_orbit_correction() {
+        let sv = SV::new(Constellation::GPS, 1);
+        let toe = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let t = toe + Duration::from_seconds(600.0);
+        let interp = Interpolation::default();
+
+        let mut ssr = ssr_orbits(toe);
+        let broadcast = ssr
+            .broadcast()
+            .clone()
+            .next_at(t, sv, EARTH_ITRF93, interp)
+            .unwrap();
+
+        // no correction: not proposed
+        assert!(ssr.next_at(t, sv, EARTH_ITRF93, interp).is_none());
+
+        // correction to another ephemeris: not proposed
+        let correction = SsrOrbitCorrection {
+            t: t - Duration::from_seconds(10.0),
+            iode: 41,
+            delta_m: (1.0, 0.0, 0.0),
+            dot_m_s: (0.1, 0.0, 0.0),
+        };
+        ssr.insert_orbit_correction(sv, correction);
+        assert!(ssr.next_at(t, sv, EARTH_ITRF93, interp).is_none());
+
+        // radial correction: 1m + 10s * 0.1m/s
+        ssr.insert_orbit_correction(
+            sv,
+            SsrOrbitCorrection {
+                iode: 42,
+                ..correction
+            },
+        );
+        let corrected = ssr.next_at(t, sv, EARTH_ITRF93, interp).unwrap();
+        let (r_brdc, r_corr) = (
+            broadcast.to_cartesian_pos_vel().fixed_rows::<3>(0).norm(),
+            corrected.to_cartesian_pos_vel().fixed_rows::<3>(0).norm(),
+        );
+        assert!(((r_brdc - r_corr) * 1.0E3 - 2.0).abs() < 1.0E-3);
+
+        // outdated correction
+        let t = t + Duration::from_seconds(200.0);
+        assert!(ssr.next_at(t, sv, EARTH_ITRF93, interp).is_none());
+    }
+
+    #[test]
+    fn ssr_clock_correction_and_biases() {
+        let sv = SV::new(Constellation::GPS, 1);
+        let toe = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let t = toe + Duration::from_seconds(600.0);
+
+        let mut ssr = ssr_orbits(toe);
+        let broadcast = ssr.broadcast().clock_correction(sv, t).unwrap();
+
+        ssr.insert_orbit_correction(
+            sv,
+            SsrOrbitCorrection {
+                t,
+                iode: 42,
+                ..Default::default()
+            },
+        );
+        ssr.insert_clock_correction(
+            sv,
+            SsrClockCorrection {
+                t,
+                c0_m: 3.0,
+                ..Default::default()
+            },
+        );
+
+        let corrected = ssr.next_clock_at(t, sv, Interpolation::default()).unwrap();
+        let delta_s = (corrected.duration - broadcast.duration).to_seconds();
+        assert!((delta_s - 3.0 / SPEED_OF_LIGHT_M_S).abs() < 1.0E-12);
+
+        ssr.insert_code_biases(
+            sv,
+            vec![SsrBias {
+                carrier: Carrier::L1,
+                bias_m: -1.5,
+            }],
+        );
+        ssr.insert_phase_biases(
+            sv,
+            vec![SsrBias {
+                carrier: Carrier::L1,
+                bias_m: 0.25,
+            }],
+        );
+
+        let mut candidate = Candidate::new(
+            sv,
+            t,
+            vec![
+                Observation::pseudo_range(Carrier::L1, 20.0E6, None).with_phase_range(21.0E6),
+                Observation::pseudo_range(Carrier::L2, 20.0E6, None),
+            ],
+        );
+        ssr.apply_biases(&mut candidate);
+        assert_eq!(candidate.observations[0].pseudo, Some(20.0E6 - 1.5));
+        assert_eq!(candidate.observations[0].phase, Some(21.0E6 + 0.25));
+        assert_eq!(candidate.observations[1].pseudo, Some(20.0E6));
+    }
+
+    #[test]
+    fn ssr_broadcast_fallback() {
+        let sv = SV::new(Constellation::GPS, 1);
+        let toe = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let t = toe + Duration::from_seconds(600.0);
+        let interp = Interpolation::default();
+
+        let mut ssr = ssr_orbits(toe).with_broadcast_fallback(true);
+        ssr.insert_orbit_correction(
+            sv,
+            SsrOrbitCorrection {
+                t,
+                iode: 42,
+                ..Default::default()
+            },
+        );
+        assert!(ssr.next_at(t, sv, EARTH_ITRF93, interp).is_some());
+        assert_eq!(ssr.status(sv), Some(CorrectionStatus::Corrected));
+
+        // stale correction: broadcast state is proposed, and reported
+        let t = t + Duration::from_seconds(200.0);
+        let broadcast = ssr
+            .broadcast()
+            .clone()
+            .next_at(t, sv, EARTH_ITRF93, interp)
+            .unwrap();
+        let state = ssr.next_at(t, sv, EARTH_ITRF93, interp).unwrap();
+        assert_eq!(state.radius_km, broadcast.radius_km);
+        assert_eq!(
+            ssr.status(sv),
+            Some(CorrectionStatus::BroadcastOnly(CorrectionLoss::Outdated))
+        );
+        assert_eq!(ssr.broadcast_only(), vec![sv]);
+        let clock = ssr.next_clock_at(t, sv, interp).unwrap();
+        let broadcast = ssr.broadcast().clock_correction(sv, t).unwrap();
+        assert_eq!(clock.duration, broadcast.duration);
+
+        // aged out
+        ssr.discard_outdated(t);
+        assert!(ssr
+            .orbit_correction(sv, t - Duration::from_seconds(200.0))
+            .is_none());
+    }
+}
@@ -0,0 +1,186 @@
+//! Opt-in performance counters (per processing stage timings, allocations)
+#[cfg(feature = "std")]
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration as StdDuration, Instant},
+};
+
+#[cfg(feature = "std")]
+use crate::prelude::Epoch;
+
+/// Allocations counted by the [CountingAllocator], if installed
+#[cfg(feature = "std")]
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Processing [Stage]s of each resolution attempt
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Stage {
+    /// SV orbital states and clock corrections interpolation
+    Interpolation,
+    /// SV attitudes, eclipses, ambiguities and bias models
+    Modeling,
+    /// Navigation matrix formation
+    MatrixBuild,
+    /// Navigation filter
+    Solve,
+    /// Solution validation and post processing
+    Validation,
+}
+
+impl Stage {
+    /// All [Stage]s, in processing order
+    pub const ALL: [Self; 5] = [
+        Self::Interpolation,
+        Self::Modeling,
+        Self::MatrixBuild,
+        Self::Solve,
+        Self::Validation,
+    ];
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Interpolation => write!(f, "interpolation"),
+            Self::Modeling => write!(f, "modeling"),
+            Self::MatrixBuild => write!(f, "matrix build"),
+            Self::Solve => write!(f, "solve"),
+            Self::Validation => write!(f, "validation"),
+        }
+    }
+}
+
+/// [CountingAllocator] wraps a [GlobalAlloc] and counts the allocations,
+/// reported by the [PerfCounters]. Install it as `#[global_allocator]`:
+/// `static ALLOCATOR: CountingAllocator<System> = CountingAllocator::new(System);`
+#[cfg(feature = "std")]
+pub struct CountingAllocator<A: GlobalAlloc> {
+    inner: A,
+}
+
+#[cfg(feature = "std")]
+impl<A: GlobalAlloc> CountingAllocator<A> {
+    /// Wraps this [GlobalAlloc]
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+#[cfg(feature = "std")]
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        self.inner.alloc(layout)
+    }
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        self.inner.alloc_zeroed(layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        self.inner.realloc(ptr, layout, new_size)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout)
+    }
+}
+
+/// [PerfCounters] of the latest resolution attempt
+/// (see [Solver::set_perf_counters](crate::prelude::Solver::set_perf_counters)).
+#[cfg(feature = "std")]
+#[derive(Debug, Clone)]
+pub struct PerfCounters {
+    /// [Epoch] of the resolution attempt
+    epoch: Epoch,
+    /// Time spent per [Stage]
+    stages: [StdDuration; 5],
+    /// Allocations count, at the start of the attempt
+    allocations_start: usize,
+    /// Allocations count, at the end of the latest [Stage]
+    allocations_end: usize,
+    /// End of the latest [Stage]
+    last: Instant,
+}
+
+#[cfg(feature = "std")]
+impl Default for PerfCounters {
+    fn default() -> Self {
+        Self {
+            epoch: Default::default(),
+            stages: Default::default(),
+            allocations_start: 0,
+            allocations_end: 0,
+            last: Instant::now(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl PerfCounters {
+    /// Resets the counters, for this new resolution attempt
+    pub(crate) fn start(&mut self, t: Epoch) {
+        self.epoch = t;
+        self.stages = Default::default();
+        self.allocations_start = ALLOCATIONS.load(Ordering::Relaxed);
+        self.allocations_end = self.allocations_start;
+        self.last = Instant::now();
+    }
+    /// Marks the end of this [Stage]
+    pub(crate) fn mark(&mut self, stage: Stage) {
+        let now = Instant::now();
+        self.stages[stage.index()] += now - self.last;
+        self.last = now;
+        self.allocations_end = ALLOCATIONS.load(Ordering::Relaxed);
+    }
+    /// Returns [Epoch] of the resolution attempt
+    pub fn epoch(&self) -> Epoch {
+        self.epoch
+    }
+    /// Returns time spent in this [Stage]
+    pub fn stage(&self, stage: Stage) -> StdDuration {
+        self.stages[stage.index()]
+    }
+    /// Returns total time spent in all [Stage]s
+    pub fn total(&self) -> StdDuration {
+        self.stages.iter().sum()
+    }
+    /// Returns number of allocations, when the [CountingAllocator] is installed (0 otherwise)
+    pub fn allocations(&self) -> usize {
+        self.allocations_end - self.allocations_start
+    }
+}
+
+#[cfg(test)]
+#[cfg(feature = "std")]
+mod test {
+    use super::{PerfCounters, Stage};
+    use crate::prelude::Epoch;
+    use std::{str::FromStr, time::Duration};
+
+    #[test]
+    fn perf_counters() {
+        let t = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let mut perf = PerfCounters::default();
+        perf.start(t);
+        std::thread::sleep(Duration::from_millis(2));
+        perf.mark(Stage::Interpolation);
+        perf.mark(Stage::Solve);
+
+        assert_eq!(perf.epoch(), t);
+        assert!(perf.stage(Stage::Interpolation) >= Duration::from_millis(2));
+        assert_eq!(perf.stage(Stage::Modeling), Duration::ZERO);
+        let sum = Stage::ALL
+            .iter()
+            .map(|stage| perf.stage(*stage))
+            .sum::<Duration>();
+        assert_eq!(perf.total(), sum);
+        assert_eq!(perf.allocations(), 0);
+
+        perf.start(t);
+        assert_eq!(perf.total(), Duration::ZERO);
+    }
+}
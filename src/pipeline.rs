@@ -0,0 +1,305 @@
+//! [Candidate] selection pipeline
+use crate::logging::{debug, error};
+
+use crate::{
+    cfg::{Config, Method},
+    eclipse::{occultation_percent, SunEphemeris},
+    prelude::{Candidate, Epoch, Orbit},
+};
+
+/// [FilterStage] defines when a [CandidateFilter] applies, depending
+/// on the information it requires.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FilterStage {
+    /// Prior orbital states interpolation: observations only
+    Observations,
+    /// Once the SV states ([Orbit]s) are known
+    States,
+    /// Once the SV elevations and azimuths are known
+    Attitude,
+}
+
+/// [FilterContext] is the information available to each [CandidateFilter]
+#[derive(Debug, Clone, Copy)]
+pub struct FilterContext<'a> {
+    /// Sampling [Epoch]
+    pub t: Epoch,
+    /// [Config] of the Solver
+    pub cfg: &'a Config,
+    /// Receiver state, once known ([FilterStage::States] and later)
+    pub rx_orbit: Option<Orbit>,
+}
+
+/// [CandidateFilter] is one stage of the [CandidatePipeline].
+/// Implement it to insert custom selection criteria (geofence visibility,
+/// custom quality metrics..) between the built-in filters.
+pub trait CandidateFilter: Send {
+    /// Name of this filter, to locate it in the [CandidatePipeline]
+    fn name(&self) -> &str;
+    /// [FilterStage] this filter applies to
+    fn stage(&self) -> FilterStage;
+    /// Returns true if this [Candidate] should be retained.
+    /// The [Candidate] may be modified (for example, to drop some observations).
+    fn retain(&mut self, ctx: &FilterContext, cd: &mut Candidate) -> bool;
+}
+
+/// Signal condition: observations must match the navigation [Method]
+struct SignalCondition;
+
+impl CandidateFilter for SignalCondition {
+    fn name(&self) -> &str {
+        "signal"
+    }
+    fn stage(&self) -> FilterStage {
+        FilterStage::Observations
+    }
+    fn retain(&mut self, ctx: &FilterContext, cd: &mut Candidate) -> bool {
+        match ctx.cfg.method {
+            Method::SPP => {
+                if cd.prefered_pseudorange().is_some() {
+                    true
+                } else {
+                    error!("{} ({}) missing pseudo range observation", cd.t, cd.sv);
+                    false
+                }
+            },
+            Method::CPP => {
+                if cd.cpp_compatible() {
+                    true
+                } else {
+                    debug!("{} ({}) missing secondary frequency", cd.t, cd.sv);
+                    false
+                }
+            },
+            Method::PPP => {
+                if cd.ppp_compatible() {
+                    true
+                } else {
+                    debug!("{} ({}) missing phase or phase combination", cd.t, cd.sv);
+                    false
+                }
+            },
+        }
+    }
+}
+
+/// Signal quality: SNR mask, per [Carrier](crate::prelude::Carrier)
+struct SnrMask;
+
+impl CandidateFilter for SnrMask {
+    fn name(&self) -> &str {
+        "snr"
+    }
+    fn stage(&self) -> FilterStage {
+        FilterStage::Observations
+    }
+    fn retain(&mut self, ctx: &FilterContext, cd: &mut Candidate) -> bool {
+        if ctx.cfg.has_snr_mask() {
+            cd.min_snr_mask(ctx.cfg);
+            !cd.observations.is_empty()
+        } else {
+            true
+        }
+    }
+}
+
+/// Eclipse filter (see `max_sv_occultation_percent` option)
+#[derive(Default)]
+struct Eclipse {
+    sun: SunEphemeris,
+}
+
+impl CandidateFilter for Eclipse {
+    fn name(&self) -> &str {
+        "eclipse"
+    }
+    fn stage(&self) -> FilterStage {
+        FilterStage::States
+    }
+    fn retain(&mut self, ctx: &FilterContext, cd: &mut Candidate) -> bool {
+        let Some(max_occultation_rate) = ctx.cfg.max_sv_occultation_percent else {
+            return true;
+        };
+        match cd.orbit {
+            Some(sv_orbit) => {
+                // Sun position: evaluated once per epoch
+                let sun_km = self.sun.position_km(ctx.t);
+                occultation_percent(sv_orbit, sun_km) <= max_occultation_rate
+            },
+            // undefined orbital state
+            // needs to be preversed for some RTK scenarios
+            None => true,
+        }
+    }
+}
+
+/// Elevation and azimuth masks
+struct AttitudeMask;
+
+impl CandidateFilter for AttitudeMask {
+    fn name(&self) -> &str {
+        "mask"
+    }
+    fn stage(&self) -> FilterStage {
+        FilterStage::Attitude
+    }
+    fn retain(&mut self, ctx: &FilterContext, cd: &mut Candidate) -> bool {
+        let min_elev_deg = ctx.cfg.min_sv_elev.unwrap_or(0.0);
+        let min_azim_deg = ctx.cfg.min_sv_azim.unwrap_or(0.0);
+        let max_azim_deg = ctx.cfg.max_sv_azim.unwrap_or(360.0);
+        if let Some((elev, azim)) = cd.attitude() {
+            if elev < min_elev_deg {
+                debug!("{}({}) - rejected (below elevation mask)", cd.t, cd.sv);
+                false
+            } else if azim < min_azim_deg {
+                debug!("{}({}) - rejected (below azimuth mask)", cd.t, cd.sv);
+                false
+            } else if azim > max_azim_deg {
+                debug!("{}({}) - rejected (above azimuth mask)", cd.t, cd.sv);
+                false
+            } else {
+                debug!("{}({}) - elev={:.3}° azim={:.3}°", cd.t, cd.sv, elev, azim);
+                true
+            }
+        } else {
+            true
+        }
+    }
+}
+
+/// [CandidatePipeline] is the ordered list of [CandidateFilter]s each [Candidate]
+/// goes through. Filters of the same [FilterStage] apply in order.
+/// The default pipeline is made of the built-in filters: "signal" (signal condition),
+/// "snr" (SNR mask), "eclipse" (eclipse filter) and "mask" (elevation and azimuth masks).
+pub struct CandidatePipeline {
+    filters: Vec<Box<dyn CandidateFilter>>,
+}
+
+impl Default for CandidatePipeline {
+    fn default() -> Self {
+        Self {
+            filters: vec![
+                Box::new(SignalCondition),
+                Box::new(SnrMask),
+                Box::new(Eclipse::default()),
+                Box::new(AttitudeMask),
+            ],
+        }
+    }
+}
+
+impl CandidatePipeline {
+    /// Returns the names of the filters, in order
+    pub fn names(&self) -> Vec<&str> {
+        self.filters.iter().map(|filter| filter.name()).collect()
+    }
+    /// Returns position of the filter named `name`
+    pub fn position(&self, name: &str) -> Option<usize> {
+        self.filters.iter().position(|filter| filter.name() == name)
+    }
+    /// Inserts this [CandidateFilter] at given position
+    pub fn insert<F: CandidateFilter + 'static>(&mut self, index: usize, filter: F) {
+        self.filters.insert(index, Box::new(filter));
+    }
+    /// Inserts this [CandidateFilter] right after the filter named `name`,
+    /// or at the end of the pipeline if no such filter exists.
+    pub fn insert_after<F: CandidateFilter + 'static>(&mut self, name: &str, filter: F) {
+        let index = match self.position(name) {
+            Some(index) => index + 1,
+            None => self.filters.len(),
+        };
+        self.insert(index, filter);
+    }
+    /// Appends this [CandidateFilter] to the pipeline
+    pub fn push<F: CandidateFilter + 'static>(&mut self, filter: F) {
+        self.filters.push(Box::new(filter));
+    }
+    /// Removes the filter named `name`, returns true if it existed
+    pub fn remove(&mut self, name: &str) -> bool {
+        match self.position(name) {
+            Some(index) => {
+                self.filters.remove(index);
+                true
+            },
+            None => false,
+        }
+    }
+    /// Applies the filters of this [FilterStage] to the pool
+    pub(crate) fn apply(
+        &mut self,
+        stage: FilterStage,
+        ctx: &FilterContext,
+        pool: &mut Vec<Candidate>,
+    ) {
+        for filter in self.filters.iter_mut() {
+            if filter.stage() != stage {
+                continue;
+            }
+            pool.retain_mut(|cd| {
+                let retained = filter.retain(ctx, cd);
+                if !retained {
+                    debug!("{}({}) - rejected by {} filter", cd.t, cd.sv, filter.name());
+                }
+                retained
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CandidateFilter, CandidatePipeline, FilterContext, FilterStage};
+    use crate::prelude::{Candidate, Carrier, Config, Epoch, Observation, SV};
+    use std::str::FromStr;
+
+    /// Rejects a single [SV]
+    struct Blacklist(SV);
+
+    impl CandidateFilter for Blacklist {
+        fn name(&self) -> &str {
+            "blacklist"
+        }
+        fn stage(&self) -> FilterStage {
+            FilterStage::Observations
+        }
+        fn retain(&mut self, _: &FilterContext, cd: &mut Candidate) -> bool {
+            cd.sv != self.0
+        }
+    }
+
+    #[test]
+    fn candidate_pipeline() {
+        let t = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let mut cfg = Config::default();
+        cfg.min_snr = Some(30.0);
+
+        let mut pool = [("G01", 40.0), ("G02", 20.0), ("G03", 40.0)]
+            .iter()
+            .map(|(sv, snr)| {
+                Candidate::new(
+                    SV::from_str(sv).unwrap(),
+                    t,
+                    vec![Observation::pseudo_range(Carrier::L1, 20.0E6, Some(*snr))],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut pipeline = CandidatePipeline::default();
+        assert_eq!(pipeline.names(), vec!["signal", "snr", "eclipse", "mask"]);
+
+        pipeline.insert_after("snr", Blacklist(SV::from_str("G03").unwrap()));
+        assert_eq!(pipeline.position("blacklist"), Some(2));
+
+        let ctx = FilterContext {
+            t,
+            cfg: &cfg,
+            rx_orbit: None,
+        };
+        pipeline.apply(FilterStage::Observations, &ctx, &mut pool);
+        assert_eq!(pool.len(), 1);
+        assert_eq!(pool[0].sv, SV::from_str("G01").unwrap());
+
+        assert!(pipeline.remove("blacklist"));
+        assert!(!pipeline.remove("blacklist"));
+    }
+}
@@ -0,0 +1,189 @@
+//! Map projections: UTM and MGRS
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// WGS84 semi major axis [m]
+const WGS84_A_M: f64 = 6_378_137.0;
+
+/// WGS84 flattening
+const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+/// UTM central meridian scale factor
+const UTM_K0: f64 = 0.9996;
+
+/// UTM false easting [m]
+const UTM_FALSE_EASTING_M: f64 = 500_000.0;
+
+/// UTM false northing, southern hemisphere [m]
+const UTM_FALSE_NORTHING_M: f64 = 10_000_000.0;
+
+/// UTM latitude bands, from 80°S to 84°N
+const UTM_BANDS: &[u8] = b"CDEFGHJKLMNPQRSTUVWX";
+
+/// MGRS 100 km square row letters
+const MGRS_ROWS: &[u8] = b"ABCDEFGHJKLMNPQRSTUV";
+
+/// [UTMCoordinates] (Universal Transverse Mercator, WGS84)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UTMCoordinates {
+    /// Longitude zone (1..=60)
+    pub zone: u8,
+    /// Latitude band letter
+    pub band: char,
+    /// Easting [m]
+    pub easting_m: f64,
+    /// Northing [m] (false northing of 10000 km in the southern hemisphere)
+    pub northing_m: f64,
+}
+
+impl std::fmt::Display for UTMCoordinates {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{} {:.3} {:.3}",
+            self.zone, self.band, self.easting_m, self.northing_m
+        )
+    }
+}
+
+impl UTMCoordinates {
+    /// Projects these WGS84 geodetic coordinates (decimal degrees), in the zone
+    /// that contains them (Norway and Svalbard exceptions included).
+    /// Returns None in polar regions (beyond 80°S and 84°N), not covered by UTM.
+    pub fn from_geodetic(lat_ddeg: f64, long_ddeg: f64) -> Option<Self> {
+        if !(-80.0..=84.0).contains(&lat_ddeg) || !(-180.0..=180.0).contains(&long_ddeg) {
+            return None;
+        }
+        let zone = Self::zone(lat_ddeg, long_ddeg);
+        let band = UTM_BANDS[(((lat_ddeg + 80.0) / 8.0).floor() as usize).min(19)] as char;
+
+        // Krüger series (4th order)
+        let n = WGS84_F / (2.0 - WGS84_F);
+        let big_a = WGS84_A_M / (1.0 + n) * (1.0 + n.powi(2) / 4.0 + n.powi(4) / 64.0);
+        let alpha = [
+            n / 2.0 - 2.0 * n.powi(2) / 3.0 + 5.0 * n.powi(3) / 16.0 + 41.0 * n.powi(4) / 180.0,
+            13.0 * n.powi(2) / 48.0 - 3.0 * n.powi(3) / 5.0 + 557.0 * n.powi(4) / 1440.0,
+            61.0 * n.powi(3) / 240.0 - 103.0 * n.powi(4) / 140.0,
+            49561.0 * n.powi(4) / 161280.0,
+        ];
+
+        let central_meridian_ddeg = (zone as f64 - 1.0) * 6.0 - 180.0 + 3.0;
+        let phi = lat_ddeg.to_radians();
+        let dlambda = (long_ddeg - central_meridian_ddeg).to_radians();
+
+        let c = 2.0 * n.sqrt() / (1.0 + n);
+        let t = (phi.sin().atanh() - c * (c * phi.sin()).atanh()).sinh();
+        let xi = t.atan2(dlambda.cos());
+        let eta = (dlambda.sin() / (1.0 + t * t).sqrt()).atanh();
+
+        let (mut easting, mut northing) = (eta, xi);
+        for (j, alpha_j) in alpha.iter().enumerate() {
+            let k = 2.0 * (j + 1) as f64;
+            easting += alpha_j * (k * xi).cos() * (k * eta).sinh();
+            northing += alpha_j * (k * xi).sin() * (k * eta).cosh();
+        }
+
+        let mut northing_m = UTM_K0 * big_a * northing;
+        if lat_ddeg < 0.0 {
+            northing_m += UTM_FALSE_NORTHING_M;
+        }
+
+        Some(Self {
+            zone,
+            band,
+            easting_m: UTM_FALSE_EASTING_M + UTM_K0 * big_a * easting,
+            northing_m,
+        })
+    }
+    /// Returns the UTM zone containing these coordinates (decimal degrees)
+    fn zone(lat_ddeg: f64, long_ddeg: f64) -> u8 {
+        // Norway
+        if (56.0..64.0).contains(&lat_ddeg) && (3.0..12.0).contains(&long_ddeg) {
+            return 32;
+        }
+        // Svalbard
+        if (72.0..=84.0).contains(&lat_ddeg) && (0.0..42.0).contains(&long_ddeg) {
+            return match long_ddeg {
+                l if l < 9.0 => 31,
+                l if l < 21.0 => 33,
+                l if l < 33.0 => 35,
+                _ => 37,
+            };
+        }
+        (((long_ddeg + 180.0) / 6.0).floor() as u8 + 1).min(60)
+    }
+    /// Returns true if these coordinates lie in the northern hemisphere
+    pub fn is_northern(&self) -> bool {
+        self.band >= 'N'
+    }
+    /// Formats these coordinates as MGRS (Military Grid Reference System) string,
+    /// with `precision` digits per axis (1: 10 km .. 5: 1 m resolution).
+    /// For example "31UDQ4825111943".
+    pub fn mgrs(&self, precision: usize) -> String {
+        let precision = precision.clamp(1, 5);
+        let columns: &[u8] = match self.zone % 3 {
+            1 => b"ABCDEFGH",
+            2 => b"JKLMNPQR",
+            _ => b"STUVWXYZ",
+        };
+        let column = (self.easting_m / 100_000.0).floor() as usize;
+        let column = columns[column.clamp(1, 8) - 1] as char;
+
+        let row = (self.northing_m / 100_000.0).floor() as usize;
+        let row_offset = if self.zone % 2 == 0 { 5 } else { 0 };
+        let row = MGRS_ROWS[(row + row_offset) % 20] as char;
+
+        let divisor = 10_f64.powi(5 - precision as i32);
+        let easting = ((self.easting_m % 100_000.0) / divisor).floor() as u32;
+        let northing = ((self.northing_m % 100_000.0) / divisor).floor() as u32;
+
+        format!(
+            "{:02}{}{}{}{:0width$}{:0width$}",
+            self.zone,
+            self.band,
+            column,
+            row,
+            easting,
+            northing,
+            width = precision
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::UTMCoordinates;
+
+    #[test]
+    fn utm_projection() {
+        // Eiffel tower
+        let utm = UTMCoordinates::from_geodetic(48.8583, 2.2945).unwrap();
+        assert_eq!((utm.zone, utm.band), (31, 'U'));
+        assert!(utm.is_northern());
+        assert!((utm.easting_m - 448_251.898).abs() < 1.0E-2);
+        assert!((utm.northing_m - 5_411_943.794).abs() < 1.0E-2);
+        assert_eq!(utm.mgrs(5), "31UDQ4825111943");
+        assert_eq!(utm.mgrs(2), "31UDQ4811");
+
+        // Sydney opera house
+        let utm = UTMCoordinates::from_geodetic(-33.8568, 151.2153).unwrap();
+        assert_eq!((utm.zone, utm.band), (56, 'H'));
+        assert!(!utm.is_northern());
+        assert!((utm.easting_m - 334_900.570).abs() < 1.0E-2);
+        assert!((utm.northing_m - 6_252_288.753).abs() < 1.0E-2);
+        assert_eq!(utm.mgrs(5), "56HLH3490052288");
+
+        // Norway and Svalbard exceptions
+        let utm = UTMCoordinates::from_geodetic(60.0, 5.0).unwrap();
+        assert_eq!((utm.zone, utm.band), (32, 'V'));
+        assert_eq!(utm.mgrs(5), "32VKM7697958157");
+        let utm = UTMCoordinates::from_geodetic(78.0, 15.0).unwrap();
+        assert_eq!((utm.zone, utm.band), (33, 'X'));
+        assert!((utm.easting_m - 500_000.0).abs() < 1.0E-6);
+
+        // polar regions
+        assert!(UTMCoordinates::from_geodetic(85.0, 0.0).is_none());
+        assert!(UTMCoordinates::from_geodetic(-81.0, 0.0).is_none());
+    }
+}
@@ -0,0 +1,321 @@
+//! Python bindings (PyO3), to script post-processing.
+//! [Epoch]s are exchanged as strings (like "2020-06-25T00:00:00 GPST"),
+//! [SV]s as strings (like "G01") and observations are described by their RINEX codes.
+use std::{collections::HashMap, str::FromStr};
+
+use pyo3::{exceptions::PyValueError, prelude::*};
+
+use crate::{
+    formats::pool::PoolBuilder,
+    prelude::{
+        Candidate, ClockCorrection, Config, Duration, Epoch, Frame, Interpolation, Method, Orbit,
+        OrbitSource, PVTSolution, PVTSolutionType, Solver, EARTH_ITRF93, SV,
+    },
+};
+
+/// Columns of [PySolution::to_array]
+const SOLUTION_COLUMNS: [&str; 12] = [
+    "x_m", "y_m", "z_m", "vx_m_s", "vy_m_s", "vz_m_s", "dt_s", "d_dt_s_s", "gdop", "pdop", "tdop",
+    "num_sv",
+];
+
+fn parse_epoch(t: &str) -> PyResult<Epoch> {
+    Epoch::from_str(t).map_err(|e| PyValueError::new_err(format!("invalid epoch: {}", e)))
+}
+
+fn parse_sv(sv: &str) -> PyResult<SV> {
+    SV::from_str(sv).map_err(|e| PyValueError::new_err(format!("invalid sv: {}", e)))
+}
+
+/// Solver configuration
+#[pyclass(name = "Config")]
+#[derive(Clone, Default)]
+pub struct PyConfig {
+    inner: Config,
+}
+
+#[pymethods]
+impl PyConfig {
+    #[new]
+    fn new() -> Self {
+        Self::default()
+    }
+    /// Navigation method: "SPP", "CPP" or "PPP"
+    #[getter]
+    fn get_method(&self) -> String {
+        self.inner.method.to_string()
+    }
+    #[setter]
+    fn set_method(&mut self, method: &str) -> PyResult<()> {
+        self.inner.method = Method::from_str(method)
+            .map_err(|_| PyValueError::new_err(format!("invalid method: {}", method)))?;
+        Ok(())
+    }
+    /// Solution type: "PVT", "TimeOnly" or "FixedPosition"
+    #[getter]
+    fn get_sol_type(&self) -> String {
+        self.inner.sol_type.to_string()
+    }
+    #[setter]
+    fn set_sol_type(&mut self, sol_type: &str) -> PyResult<()> {
+        self.inner.sol_type = match sol_type {
+            "PVT" => PVTSolutionType::PositionVelocityTime,
+            "TimeOnly" => PVTSolutionType::TimeOnly,
+            "FixedPosition" => PVTSolutionType::FixedPosition,
+            _ => {
+                return Err(PyValueError::new_err(format!(
+                    "invalid solution type: {}",
+                    sol_type
+                )))
+            },
+        };
+        Ok(())
+    }
+    /// Minimal SV elevation [°]
+    #[getter]
+    fn get_min_sv_elev(&self) -> Option<f64> {
+        self.inner.min_sv_elev
+    }
+    #[setter]
+    fn set_min_sv_elev(&mut self, elev: Option<f64>) {
+        self.inner.min_sv_elev = elev;
+    }
+    /// Minimal SNR [dB.Hz]
+    #[getter]
+    fn get_min_snr(&self) -> Option<f64> {
+        self.inner.min_snr
+    }
+    #[setter]
+    fn set_min_snr(&mut self, snr: Option<f64>) {
+        self.inner.min_snr = snr;
+    }
+    /// Fixed altitude [m]
+    #[getter]
+    fn get_fixed_altitude(&self) -> Option<f64> {
+        self.inner.fixed_altitude
+    }
+    #[setter]
+    fn set_fixed_altitude(&mut self, altitude: Option<f64>) {
+        self.inner.fixed_altitude = altitude;
+    }
+    /// Pseudo range smoothing
+    #[getter]
+    fn get_code_smoothing(&self) -> bool {
+        self.inner.code_smoothing
+    }
+    #[setter]
+    fn set_code_smoothing(&mut self, smoothing: bool) {
+        self.inner.code_smoothing = smoothing;
+    }
+    fn __repr__(&self) -> String {
+        format!("{:?}", self.inner)
+    }
+}
+
+/// One SV observed at one [Epoch]
+#[pyclass(name = "Candidate")]
+#[derive(Clone)]
+pub struct PyCandidate {
+    inner: Candidate,
+}
+
+#[pymethods]
+impl PyCandidate {
+    /// Builds a new candidate, from observations described by their RINEX code
+    /// (like {"C1C": 20.0E6, "L1C": 1.05E8}). Phase observations are expressed in cycles.
+    #[new]
+    fn new(sv: &str, t: &str, observations: HashMap<String, f64>) -> PyResult<Self> {
+        let (sv, t) = (parse_sv(sv)?, parse_epoch(t)?);
+        let mut builder = PoolBuilder::default();
+        for (code, value) in observations.iter() {
+            builder.add(t, sv, code, *value);
+        }
+        let observations = builder
+            .observations
+            .remove(&(t, sv))
+            .ok_or_else(|| PyValueError::new_err("no supported observation"))?;
+        Ok(Self {
+            inner: Candidate::new(sv, t, observations),
+        })
+    }
+    /// Defines the SV clock correction [s]
+    fn set_clock_correction(&mut self, correction_s: f64) {
+        self.inner
+            .set_clock_correction(ClockCorrection::without_relativistic_correction(
+                Duration::from_seconds(correction_s),
+            ));
+    }
+    /// Defines the total group delay [s]
+    fn set_group_delay(&mut self, tgd_s: f64) {
+        self.inner.set_group_delay(Duration::from_seconds(tgd_s));
+    }
+    fn __repr__(&self) -> String {
+        format!("Candidate({}, {})", self.inner.sv, self.inner.t)
+    }
+}
+
+/// Resolved solution
+#[pyclass(name = "Solution")]
+#[derive(Clone)]
+pub struct PySolution {
+    /// [Epoch] of this solution
+    #[pyo3(get)]
+    epoch: String,
+    /// SV that contributed
+    #[pyo3(get)]
+    sv: Vec<String>,
+    inner: PVTSolution,
+}
+
+impl PySolution {
+    fn new(t: Epoch, inner: PVTSolution) -> Self {
+        Self {
+            epoch: t.to_string(),
+            sv: inner.sv.keys().map(|sv| sv.to_string()).collect(),
+            inner,
+        }
+    }
+}
+
+#[pymethods]
+impl PySolution {
+    /// ECEF position [m]
+    #[getter]
+    fn position_ecef_m(&self) -> (f64, f64, f64) {
+        let pos_vel_m = self.inner.state.to_cartesian_pos_vel() * 1.0E3;
+        (pos_vel_m[0], pos_vel_m[1], pos_vel_m[2])
+    }
+    /// ECEF velocity [m/s]
+    #[getter]
+    fn velocity_ecef_m_s(&self) -> (f64, f64, f64) {
+        let pos_vel_m = self.inner.state.to_cartesian_pos_vel() * 1.0E3;
+        (pos_vel_m[3], pos_vel_m[4], pos_vel_m[5])
+    }
+    /// Receiver clock offset [s]
+    #[getter]
+    fn dt_s(&self) -> f64 {
+        self.inner.dt.to_seconds()
+    }
+    #[getter]
+    fn gdop(&self) -> f64 {
+        self.inner.gdop()
+    }
+    #[getter]
+    fn pdop(&self) -> f64 {
+        self.inner.pdop()
+    }
+    #[getter]
+    fn tdop(&self) -> f64 {
+        self.inner.tdop()
+    }
+    /// Clock holdover duration [s], None for resolved solutions
+    #[getter]
+    fn holdover_s(&self) -> Option<f64> {
+        self.inner.holdover.map(|dt| dt.to_seconds())
+    }
+    /// Returns this solution as a flat row (see `SOLUTION_COLUMNS`),
+    /// ready for `numpy.asarray`
+    fn to_array(&self) -> Vec<f64> {
+        let pos_vel_m = self.inner.state.to_cartesian_pos_vel() * 1.0E3;
+        vec![
+            pos_vel_m[0],
+            pos_vel_m[1],
+            pos_vel_m[2],
+            pos_vel_m[3],
+            pos_vel_m[4],
+            pos_vel_m[5],
+            self.inner.dt.to_seconds(),
+            self.inner.d_dt,
+            self.inner.gdop(),
+            self.inner.pdop(),
+            self.inner.tdop(),
+            self.inner.sv.len() as f64,
+        ]
+    }
+    fn __repr__(&self) -> String {
+        let (x_m, y_m, z_m) = self.position_ecef_m();
+        format!(
+            "Solution({}, x={:.3}m, y={:.3}m, z={:.3}m)",
+            self.epoch, x_m, y_m, z_m,
+        )
+    }
+}
+
+/// [OrbitSource] backed by a Python callable `(sv: str, t: str) -> (x_km, y_km, z_km) | None`
+struct PyOrbits {
+    callback: PyObject,
+}
+
+impl OrbitSource for PyOrbits {
+    fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, _: Interpolation) -> Option<Orbit> {
+        Python::with_gil(|py| {
+            let ret = self
+                .callback
+                .call1(py, (sv.to_string(), t.to_string()))
+                .ok()?;
+            let (x_km, y_km, z_km) = ret.extract::<Option<(f64, f64, f64)>>(py).ok()??;
+            Some(Orbit::from_position(x_km, y_km, z_km, t, fr))
+        })
+    }
+}
+
+/// Position solver
+#[pyclass(name = "Solver", unsendable)]
+pub struct PySolver {
+    inner: Solver<PyOrbits>,
+}
+
+#[pymethods]
+impl PySolver {
+    /// Builds a new solver. `orbits` is a callable returning the SV position [km, ECEF]
+    /// at given epoch, or None. `apriori` is the (epoch, ECEF [m]) apriori position, if known.
+    #[new]
+    #[pyo3(signature = (cfg, orbits, apriori=None))]
+    fn new(
+        cfg: &PyConfig,
+        orbits: PyObject,
+        apriori: Option<(String, f64, f64, f64)>,
+    ) -> PyResult<Self> {
+        let initial = match apriori {
+            Some((t, x_m, y_m, z_m)) => Some(Orbit::from_position(
+                x_m / 1.0E3,
+                y_m / 1.0E3,
+                z_m / 1.0E3,
+                parse_epoch(&t)?,
+                EARTH_ITRF93,
+            )),
+            None => None,
+        };
+        let inner = Solver::new(&cfg.inner, initial, PyOrbits { callback: orbits })
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(Self { inner })
+    }
+    /// Resolves a solution at `t` from this pool of candidates.
+    /// Raises ValueError on failure.
+    fn resolve(&mut self, t: &str, pool: Vec<PyCandidate>) -> PyResult<PySolution> {
+        let t = parse_epoch(t)?;
+        let pool = pool.into_iter().map(|cd| cd.inner).collect::<Vec<_>>();
+        let (t, solution) = self
+            .inner
+            .resolve(t, &pool)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        Ok(PySolution::new(t, solution))
+    }
+}
+
+/// Stacks solutions as rows (see `SOLUTION_COLUMNS`), ready for `numpy.asarray`
+#[pyfunction]
+fn solutions_to_array(solutions: Vec<PySolution>) -> Vec<Vec<f64>> {
+    solutions.iter().map(|sol| sol.to_array()).collect()
+}
+
+#[pymodule]
+fn gnss_rtk(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyConfig>()?;
+    m.add_class::<PyCandidate>()?;
+    m.add_class::<PySolution>()?;
+    m.add_class::<PySolver>()?;
+    m.add_function(wrap_pyfunction!(solutions_to_array, m)?)?;
+    m.add("SOLUTION_COLUMNS", SOLUTION_COLUMNS.to_vec())?;
+    Ok(())
+}
@@ -0,0 +1,616 @@
+//! Record and replay of the [Solver] inputs, to reproduce field runs offline.
+//! Records are stored as JSON lines: a header (the [Config] and apriori position),
+//! then each [Candidate] pool and each answer of the data providers, in processing order.
+use std::{
+    collections::VecDeque,
+    fs::File,
+    io::{BufRead, BufReader, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use crate::logging::{error, warn};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::{
+    candidate::GalileoNavMessage,
+    clock::ClockSource,
+    orbit::OrbitSource,
+    prelude::{
+        Almanac, Candidate, ClockCorrection, Config, Duration, Epoch, Error, Frame, Interpolation,
+        IonoComponents, Observation, Orbit, PVTSolution, Solver, TimeScale, TropoComponents, SV,
+    },
+    sync,
+    time::TimeSource,
+};
+
+use anise::structure::planetocentric::ellipsoid::Ellipsoid;
+use nalgebra::Vector6;
+
+/// Current record format version
+/// (version 2 describes user defined carriers by their definition)
+const REPLAY_VERSION: u32 = 2;
+
+/// Record and replay errors
+#[derive(Debug, Error)]
+pub enum ReplayError {
+    #[error("i/o error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("invalid record (line {0}): {1}")]
+    InvalidRecord(usize, serde_json::Error),
+    #[error("serialization error: {0}")]
+    Serialization(serde_json::Error),
+    #[error("missing header")]
+    MissingHeader,
+    #[error("non supported record version {0}")]
+    UnsupportedVersion(u32),
+}
+
+/// [Frame] description, including its gravitational parameter and shape
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FrameRecord {
+    ephemeris_id: i32,
+    orientation_id: i32,
+    mu_km3_s2: Option<f64>,
+    /// (semi major equatorial, semi minor equatorial, polar) radii [km]
+    shape: Option<(f64, f64, f64)>,
+}
+
+impl From<Frame> for FrameRecord {
+    fn from(frame: Frame) -> Self {
+        Self {
+            ephemeris_id: frame.ephemeris_id,
+            orientation_id: frame.orientation_id,
+            mu_km3_s2: frame.mu_km3_s2,
+            shape: frame.shape.map(|shape| {
+                (
+                    shape.semi_major_equatorial_radius_km,
+                    shape.semi_minor_equatorial_radius_km,
+                    shape.polar_radius_km,
+                )
+            }),
+        }
+    }
+}
+
+impl From<&FrameRecord> for Frame {
+    fn from(record: &FrameRecord) -> Self {
+        let mut frame = Frame::new(record.ephemeris_id, record.orientation_id);
+        frame.mu_km3_s2 = record.mu_km3_s2;
+        frame.shape = record.shape.map(|(a_km, b_km, c_km)| Ellipsoid {
+            semi_major_equatorial_radius_km: a_km,
+            semi_minor_equatorial_radius_km: b_km,
+            polar_radius_km: c_km,
+        });
+        frame
+    }
+}
+
+/// [Orbit] state, ECEF [km] and [km/s]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct OrbitRecord {
+    epoch: Epoch,
+    pos_vel: [f64; 6],
+    frame: FrameRecord,
+}
+
+impl From<&Orbit> for OrbitRecord {
+    fn from(orbit: &Orbit) -> Self {
+        let pos_vel = orbit.to_cartesian_pos_vel();
+        Self {
+            epoch: orbit.epoch,
+            pos_vel: [
+                pos_vel[0], pos_vel[1], pos_vel[2], pos_vel[3], pos_vel[4], pos_vel[5],
+            ],
+            frame: orbit.frame.into(),
+        }
+    }
+}
+
+impl From<&OrbitRecord> for Orbit {
+    fn from(record: &OrbitRecord) -> Self {
+        Orbit::from_cartesian_pos_vel(
+            Vector6::from_row_slice(&record.pos_vel),
+            record.epoch,
+            (&record.frame).into(),
+        )
+    }
+}
+
+/// User defined [Candidate] content (solver internal states are not recorded)
+#[derive(Clone, Serialize, Deserialize)]
+struct CandidateRecord {
+    sv: SV,
+    t: Epoch,
+    observations: Vec<Observation>,
+    remote_obs: Vec<Observation>,
+    tgd: Option<Duration>,
+    tgd_nav: Option<GalileoNavMessage>,
+    clock_corr: Option<ClockCorrection>,
+    iono_components: IonoComponents,
+    tropo_components: TropoComponents,
+}
+
+impl From<&Candidate> for CandidateRecord {
+    fn from(cd: &Candidate) -> Self {
+        Self {
+            sv: cd.sv,
+            t: cd.t,
+            observations: cd.observations.clone(),
+            remote_obs: cd.remote_obs.clone(),
+            tgd: cd.tgd,
+            tgd_nav: cd.tgd_nav,
+            clock_corr: cd.clock_corr,
+            iono_components: cd.iono_components,
+            tropo_components: cd.tropo_components,
+        }
+    }
+}
+
+impl From<&CandidateRecord> for Candidate {
+    fn from(record: &CandidateRecord) -> Self {
+        let mut cd = Candidate::new(record.sv, record.t, record.observations.clone());
+        cd.remote_obs = record.remote_obs.clone();
+        cd.tgd = record.tgd;
+        cd.tgd_nav = record.tgd_nav;
+        cd.clock_corr = record.clock_corr;
+        cd.iono_components = record.iono_components;
+        cd.tropo_components = record.tropo_components;
+        cd
+    }
+}
+
+/// Single line of a record
+#[derive(Clone, Serialize, Deserialize)]
+enum Record {
+    Header {
+        version: u32,
+        cfg: Config,
+        initial: Option<OrbitRecord>,
+    },
+    Pool {
+        t: Epoch,
+        pool: Vec<CandidateRecord>,
+    },
+    Orbit {
+        t: Epoch,
+        sv: SV,
+        orbit: Option<OrbitRecord>,
+    },
+    Clock {
+        t: Epoch,
+        sv: SV,
+        correction: Option<ClockCorrection>,
+    },
+    Time {
+        t: Epoch,
+        from: TimeScale,
+        to: TimeScale,
+        offset: Option<Duration>,
+    },
+}
+
+/// Writes one [Record] per line
+fn write_record<W: Write>(writer: &Mutex<W>, record: &Record) -> Result<(), ReplayError> {
+    let content = serde_json::to_string(record).map_err(ReplayError::Serialization)?;
+    let mut writer = sync::lock(writer);
+    writeln!(writer, "{}", content)?;
+    Ok(())
+}
+
+/// [Recorder] captures the [Solver] inputs: its [Config], apriori position,
+/// each [Candidate] pool and (by means of [Recording]) each answer of its data providers.
+/// [Recorder] may be cloned: all clones write to the same output.
+pub struct Recorder<W: Write> {
+    writer: Arc<Mutex<W>>,
+}
+
+impl<W: Write> Clone for Recorder<W> {
+    fn clone(&self) -> Self {
+        Self {
+            writer: self.writer.clone(),
+        }
+    }
+}
+
+impl<W: Write> Recorder<W> {
+    /// Builds new [Recorder], writing the [Config] and apriori position right away.
+    /// These must match the [Solver] deployment.
+    pub fn new(writer: W, cfg: &Config, initial: Option<Orbit>) -> Result<Self, ReplayError> {
+        let writer = Arc::new(Mutex::new(writer));
+        write_record(
+            &writer,
+            &Record::Header {
+                version: REPLAY_VERSION,
+                cfg: cfg.clone(),
+                initial: initial.as_ref().map(OrbitRecord::from),
+            },
+        )?;
+        Ok(Self { writer })
+    }
+    /// Records this [Candidate] pool, prior proposing it to [Solver::resolve]
+    pub fn record(&self, t: Epoch, pool: &[Candidate]) -> Result<(), ReplayError> {
+        write_record(
+            &self.writer,
+            &Record::Pool {
+                t,
+                pool: pool.iter().map(CandidateRecord::from).collect(),
+            },
+        )
+    }
+    /// Wraps this data provider ([OrbitSource], [ClockSource] or [TimeSource]),
+    /// so each of its answers is recorded. Deploy the [Solver] with the returned [Recording].
+    pub fn source<S>(&self, source: S) -> Recording<S, W> {
+        Recording {
+            inner: source,
+            writer: self.writer.clone(),
+        }
+    }
+    /// Flushes the output
+    pub fn flush(&self) -> Result<(), ReplayError> {
+        let mut writer = sync::lock(&self.writer);
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// [Recording] records each answer of the wrapped data provider (see [Recorder::source]).
+/// Recording errors are logged, they do not interrupt the processing.
+pub struct Recording<S, W: Write> {
+    inner: S,
+    writer: Arc<Mutex<W>>,
+}
+
+impl<S, W: Write> Recording<S, W> {
+    fn record(&self, record: Record) {
+        if let Err(e) = write_record(&self.writer, &record) {
+            error!("recording error: {}", e);
+        }
+    }
+}
+
+impl<S: OrbitSource, W: Write> OrbitSource for Recording<S, W> {
+    fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, interp: Interpolation) -> Option<Orbit> {
+        let orbit = self.inner.next_at(t, sv, fr, interp);
+        self.record(Record::Orbit {
+            t,
+            sv,
+            orbit: orbit.as_ref().map(OrbitRecord::from),
+        });
+        orbit
+    }
+    fn validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        self.inner.validity(sv)
+    }
+}
+
+impl<S: ClockSource, W: Write> ClockSource for Recording<S, W> {
+    fn next_clock_at(
+        &mut self,
+        t: Epoch,
+        sv: SV,
+        interp: Interpolation,
+    ) -> Option<ClockCorrection> {
+        let correction = self.inner.next_clock_at(t, sv, interp);
+        self.record(Record::Clock { t, sv, correction });
+        correction
+    }
+    fn clock_validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        self.inner.clock_validity(sv)
+    }
+}
+
+impl<S: TimeSource, W: Write> TimeSource for Recording<S, W> {
+    fn next_time_offset_at(
+        &mut self,
+        t: Epoch,
+        from: TimeScale,
+        to: TimeScale,
+    ) -> Option<Duration> {
+        let offset = self.inner.next_time_offset_at(t, from, to);
+        self.record(Record::Time {
+            t,
+            from,
+            to,
+            offset,
+        });
+        offset
+    }
+}
+
+/// [ReplaySource] answers the [Solver] requests with the recorded answers,
+/// in recording order. Each request must match the recorded one,
+/// otherwise the replay has diverged and None is returned.
+#[derive(Debug, Clone, Default)]
+pub struct ReplaySource {
+    orbits: VecDeque<(Epoch, SV, Option<Orbit>)>,
+    clocks: VecDeque<(Epoch, SV, Option<ClockCorrection>)>,
+    times: VecDeque<(Epoch, TimeScale, TimeScale, Option<Duration>)>,
+}
+
+impl OrbitSource for ReplaySource {
+    fn next_at(&mut self, t: Epoch, sv: SV, _: Frame, _: Interpolation) -> Option<Orbit> {
+        match self.orbits.front() {
+            Some((t_i, sv_i, _)) if *t_i == t && *sv_i == sv => self.orbits.pop_front()?.2,
+            _ => {
+                warn!("{} ({}) - replay diverged: unexpected orbit request", t, sv);
+                None
+            },
+        }
+    }
+}
+
+impl ClockSource for ReplaySource {
+    fn next_clock_at(&mut self, t: Epoch, sv: SV, _: Interpolation) -> Option<ClockCorrection> {
+        match self.clocks.front() {
+            Some((t_i, sv_i, _)) if *t_i == t && *sv_i == sv => self.clocks.pop_front()?.2,
+            _ => {
+                warn!("{} ({}) - replay diverged: unexpected clock request", t, sv);
+                None
+            },
+        }
+    }
+}
+
+impl TimeSource for ReplaySource {
+    fn next_time_offset_at(
+        &mut self,
+        t: Epoch,
+        from: TimeScale,
+        to: TimeScale,
+    ) -> Option<Duration> {
+        match self.times.front() {
+            Some((t_i, from_i, to_i, _)) if *t_i == t && *from_i == from && *to_i == to => {
+                self.times.pop_front()?.3
+            },
+            _ => {
+                warn!(
+                    "{} - replay diverged: unexpected {}/{} request",
+                    t, from, to
+                );
+                None
+            },
+        }
+    }
+}
+
+/// [Replay] of a run captured by the [Recorder]: deploy the [Solver]
+/// (see [Self::solver]) and [Self::run] it to reproduce the recorded run bit for bit.
+#[derive(Clone)]
+pub struct Replay {
+    /// Recorded [Config]
+    cfg: Config,
+    /// Recorded apriori position
+    initial: Option<Orbit>,
+    /// Recorded [Candidate] pools
+    epochs: Vec<(Epoch, Vec<Candidate>)>,
+    /// Recorded data providers answers
+    source: ReplaySource,
+}
+
+impl Replay {
+    /// Parses a record file
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+    /// Parses a record, from any [BufRead]able interface
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Self, ReplayError> {
+        let mut header = None;
+        let mut epochs = Vec::new();
+        let mut source = ReplaySource::default();
+
+        for (nth, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record: Record =
+                serde_json::from_str(&line).map_err(|e| ReplayError::InvalidRecord(nth + 1, e))?;
+            match record {
+                Record::Header {
+                    version,
+                    cfg,
+                    initial,
+                } => {
+                    if version > REPLAY_VERSION {
+                        return Err(ReplayError::UnsupportedVersion(version));
+                    }
+                    header = Some((cfg, initial.as_ref().map(Orbit::from)));
+                },
+                Record::Pool { t, pool } => {
+                    epochs.push((t, pool.iter().map(Candidate::from).collect()));
+                },
+                Record::Orbit { t, sv, orbit } => {
+                    source
+                        .orbits
+                        .push_back((t, sv, orbit.as_ref().map(Orbit::from)));
+                },
+                Record::Clock { t, sv, correction } => {
+                    source.clocks.push_back((t, sv, correction));
+                },
+                Record::Time {
+                    t,
+                    from,
+                    to,
+                    offset,
+                } => {
+                    source.times.push_back((t, from, to, offset));
+                },
+            }
+        }
+
+        let (cfg, initial) = header.ok_or(ReplayError::MissingHeader)?;
+        Ok(Self {
+            cfg,
+            initial,
+            epochs,
+            source,
+        })
+    }
+    /// Returns recorded [Config]
+    pub fn config(&self) -> &Config {
+        &self.cfg
+    }
+    /// Returns recorded apriori position
+    pub fn initial(&self) -> Option<Orbit> {
+        self.initial
+    }
+    /// Returns recorded [Candidate] pools
+    pub fn epochs(&self) -> &[(Epoch, Vec<Candidate>)] {
+        &self.epochs
+    }
+    /// Returns recorded answers, as a data provider
+    pub fn source(&self) -> ReplaySource {
+        self.source.clone()
+    }
+    /// Deploys the [Solver] with the recorded setup and data providers
+    pub fn solver(&self) -> Result<Solver<ReplaySource>, Error> {
+        let mut solver = Solver::new(&self.cfg, self.initial, self.source())?;
+        self.attach_sources(&mut solver);
+        Ok(solver)
+    }
+    /// Deploys the [Solver] with the recorded setup and data providers,
+    /// and this [Almanac] and [Frame] (as recorded).
+    pub fn solver_almanac_frame(&self, almanac: Almanac, frame: Frame) -> Solver<ReplaySource> {
+        let mut solver =
+            Solver::new_almanac_frame(&self.cfg, self.initial, self.source(), almanac, frame);
+        self.attach_sources(&mut solver);
+        solver
+    }
+    fn attach_sources(&self, solver: &mut Solver<ReplaySource>) {
+        // each clone only consumes its own type of answers
+        if !self.source.clocks.is_empty() {
+            solver.set_clock_source(self.source());
+        }
+        if !self.source.times.is_empty() {
+            solver.set_time_source(self.source());
+        }
+    }
+    /// Proposes each recorded [Candidate] pool to this [Solver], in recording order.
+    pub fn run(
+        &self,
+        solver: &mut Solver<ReplaySource>,
+    ) -> Vec<Result<(Epoch, PVTSolution), Error>> {
+        self.epochs
+            .iter()
+            .map(|(t, pool)| solver.resolve(*t, pool))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Recorder, Replay};
+    use crate::{
+        clock::ClockSource,
+        orbit::OrbitSource,
+        prelude::{
+            Candidate, Carrier, ClockCorrection, Config, Duration, Epoch, Frame, Interpolation,
+            Method, Observation, Orbit, EARTH_ITRF93, SV,
+        },
+    };
+    use std::str::FromStr;
+
+    struct Source {}
+
+    impl OrbitSource for Source {
+        fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, _: Interpolation) -> Option<Orbit> {
+            if sv.prn == 1 {
+                Some(Orbit::from_position(
+                    15_600.123456789,
+                    7_540.1 / 3.0,
+                    20_140.7,
+                    t,
+                    fr,
+                ))
+            } else {
+                None
+            }
+        }
+    }
+
+    impl ClockSource for Source {
+        fn next_clock_at(&mut self, _: Epoch, _: SV, _: Interpolation) -> Option<ClockCorrection> {
+            Some(ClockCorrection::without_relativistic_correction(
+                Duration::from_seconds(1.0 / 3.0 * 1.0E-4),
+            ))
+        }
+    }
+
+    #[test]
+    fn record_replay() {
+        let t = Epoch::from_str("2020-06-25T12:00:00.123456789 GPST").unwrap();
+        let (g01, g02) = (SV::from_str("G01").unwrap(), SV::from_str("G02").unwrap());
+        let cfg = Config::static_ppp_preset(Method::CPP);
+        let initial = Orbit::from_position(4_696.989, 723.994, 4_239.678, t, EARTH_ITRF93);
+
+        let mut cd = Candidate::new(
+            g01,
+            t,
+            vec![Observation {
+                carrier: Carrier::L1,
+                pseudo: Some(20.0E6 + 1.0 / 7.0),
+                snr: Some(42.5),
+                ..Default::default()
+            }],
+        );
+        cd.set_group_delay(Duration::from_nanoseconds(2.3));
+
+        let recorder = Recorder::new(Vec::new(), &cfg, Some(initial)).unwrap();
+        let mut source = recorder.source(Source {});
+
+        recorder.record(t, &[cd.clone()]).unwrap();
+        let orbit = source.next_at(t, g01, EARTH_ITRF93, Interpolation::default());
+        assert!(source
+            .next_at(t, g02, EARTH_ITRF93, Interpolation::default())
+            .is_none());
+        let clock = source
+            .next_clock_at(t, g01, Interpolation::default())
+            .unwrap();
+
+        let content = {
+            let writer = recorder.writer.lock().unwrap();
+            String::from_utf8(writer.clone()).unwrap()
+        };
+        assert_eq!(content.lines().count(), 5);
+
+        let replay = Replay::from_reader(content.as_bytes()).unwrap();
+        assert_eq!(replay.config(), &cfg);
+        assert_eq!(
+            replay.initial().unwrap().to_cartesian_pos_vel(),
+            initial.to_cartesian_pos_vel()
+        );
+
+        let epochs = replay.epochs();
+        assert_eq!(epochs.len(), 1);
+        assert_eq!(epochs[0].0, t);
+        assert_eq!(epochs[0].1[0].observations, cd.observations);
+        assert_eq!(epochs[0].1[0].tgd, cd.tgd);
+
+        // bit for bit answers, in recording order
+        let mut replayed = replay.source();
+        // diverged request: not answered
+        assert!(replayed
+            .next_at(t, g02, EARTH_ITRF93, Interpolation::default())
+            .is_none());
+        let replayed_orbit = replayed
+            .next_at(t, g01, EARTH_ITRF93, Interpolation::default())
+            .unwrap();
+        assert_eq!(
+            replayed_orbit.to_cartesian_pos_vel(),
+            orbit.unwrap().to_cartesian_pos_vel()
+        );
+        assert_eq!(replayed_orbit.epoch, t);
+        assert!(replayed
+            .next_at(t, g02, EARTH_ITRF93, Interpolation::default())
+            .is_none());
+        assert_eq!(
+            replayed
+                .next_clock_at(t, g01, Interpolation::default())
+                .unwrap()
+                .duration,
+            clock.duration
+        );
+    }
+}
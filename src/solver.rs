@@ -1,27 +1,29 @@
 //! PVT solver
 use hifitime::Unit;
-use nalgebra::Vector3;
+use nalgebra::{base::dimension::U8, Matrix4, OMatrix, OVector, Vector3, Vector6};
 use thiserror::Error;
 
-use nyx::cosmic::{
-    // eclipse::EclipseLocator,
-    SPEED_OF_LIGHT_M_S,
-};
+use std::collections::BTreeMap;
 
+#[cfg(feature = "std")]
 use std::{
-    collections::HashMap,
     fs::{create_dir_all, File},
     io::Write,
 };
 
-use log::{debug, error, info, warn};
+use crate::logging::{debug, error, info, warn};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
+#[cfg(feature = "std")]
 use anise::{
-    almanac::{
-        metaload::{MetaAlmanac, MetaAlmanacError, MetaFile},
-        planetary::PlanetaryDataError,
-    },
-    constants::frames::{EARTH_ITRF93, IAU_EARTH_FRAME, SUN_J2000},
+    almanac::metaload::{MetaAlmanac, MetaAlmanacError, MetaFile},
+    constants::frames::{EARTH_ITRF93, IAU_EARTH_FRAME},
+};
+
+use anise::{
+    almanac::planetary::PlanetaryDataError,
     errors::{AlmanacError, PhysicsError},
     math::Matrix3,
     prelude::{Almanac, Frame},
@@ -29,20 +31,38 @@ use anise::{
 
 use crate::{
     ambiguity::AmbiguitySolver,
+    attitude::Attitude,
     bancroft::Bancroft,
     candidate::Candidate,
-    cfg::{Config, Method},
-    constants::Constants,
+    cfg::{ColdStart, Config, Method},
+    clock::{ClockJumps, ClockSource},
+    coldstart::GaussNewton,
+    constants::{Constants, SPEED_OF_LIGHT_M_S},
+    constellation::ConstellationHealth,
+    coordinates::enu_to_ecef,
     navigation::{
         solutions::validator::{InvalidationCause, Validator as SolutionValidator},
-        Input as NavigationInput, Navigation, PVTSolution, PVTSolutionType,
+        sum_information, Aiding, AidingFilter, Filter, FilterState, Input as NavigationInput,
+        Navigation, PVTSolution, PVTSolutionType, StaticBatch, Workspace,
+    },
+    orbit::{propagate_inertial, OrbitSource},
+    perf::Stage,
+    pipeline::{CandidatePipeline, FilterContext, FilterStage},
+    prelude::{
+        AprioriPosition, Constellation, Duration, Epoch, Interpolation, Orbit, SbasIonoGrid,
+        TimeScale, SV,
     },
-    orbit::OrbitSource,
-    prelude::{Duration, Epoch, Orbit, SV},
+    state::{EstimatorView, SolverState},
+    svmap::SVMap,
+    time::{clock_offset_correction, GgtoEstimator, LeapSecondPolicy, TimeSource},
 };
 
+#[cfg(feature = "std")]
+use crate::perf::PerfCounters;
+
+/// [ErrorKind] describes why a resolution attempt or the [Solver] setup failed.
 #[derive(Debug, PartialEq, Error)]
-pub enum Error {
+pub enum ErrorKind {
     /// Not enough candidates were proposed: we do not attempt resolution
     #[error("not enough candidates provided")]
     NotEnoughCandidates,
@@ -98,6 +118,10 @@ pub enum Error {
     /// it is mandatory for the user to provide [ClockCorrection].
     #[error("missing clock correction")]
     UnknownClockCorrection,
+    /// Galileo clock correction and BGD must be decoded from the same [GalileoNavMessage],
+    /// that must also match the ranging signal (I/NAV: E1 or E5b, F/NAV: E1 or E5a).
+    #[error("inconsistent galileo navigation messages")]
+    InconsistentGalileoNavMessage,
     /// Physical non sense due to bad signal data or invalid orbital state, will cause us
     /// abort with this message.
     #[error("physical non sense: rx prior tx")]
@@ -119,6 +143,14 @@ pub enum Error {
     /// in case unrealistic or bad signal observation or orbital states were forwarded.
     #[error("bancroft solver error: invalid input (imaginary solution)")]
     BancroftImaginarySolution,
+    /// [GaussNewton] initialization process (see [ColdStart]) requires at least
+    /// 4 [Candidate]s with pseudo range, resolved state and clock correction.
+    #[error("cold start requires 4 fully resolved candidates")]
+    NotEnoughCandidatesColdStart,
+    /// [GaussNewton] initialization process (see [ColdStart]) did not converge.
+    /// This is most likely due to bad signal observations or orbital states.
+    #[error("cold start did not converge: invalid input ?")]
+    ColdStartDivergence,
     /// PPP navigation technique requires phase ambiguity to be solved prior any attempt.
     /// It is Okay to wind up here for a few iterations, until the ambiguities are fixed
     /// and we may proceed to precise navigation. We will reject solving attempt until then.
@@ -131,6 +163,7 @@ pub enum Error {
     #[error("issue with Almanac: {0}")]
     Almanac(AlmanacError),
     /// [Solver] uses local [Almanac] storage for efficient deployments
+    #[cfg(feature = "std")]
     #[error("almanac setup issue: {0}")]
     MetaAlmanac(MetaAlmanacError),
     /// [Solver] requires to determine a [Frame] from [Almanac] and we wind-up here if this step is in failure.
@@ -142,12 +175,144 @@ pub enum Error {
     /// Remote observation is required for a [Candidate] to contribute in RTK solving attempt.
     /// You need up to four of them to resolve. We may print this internal message and still
     /// proceed to resolve, as [SV] may go out of sight of rover or reference site.
-    #[error("missing observation on remote site {0}({1})")]
-    MissingRemoteRTKObservation(Epoch, SV),
+    #[error("missing observation on remote site")]
+    MissingRemoteRTKObservation,
     /// In RTK resolution attempt, you need to observe all pending [SV] on reference site as well.
     /// If that is not the case, we abort with this error.
     #[error("missing observations on remote site")]
     MissingRemoteRTKObservations,
+    /// SV states or clock corrections were requested outside the validity interval
+    /// of the [OrbitSource] or [ClockSource] (product boundaries, outdated ephemerides):
+    /// we refuse to extrapolate.
+    #[error("sv state requested outside its validity interval")]
+    OutsideValidityWindow,
+}
+
+/// [ErrorCategory] is a machine-readable classification of [ErrorKind]s,
+/// to help implement retry or exclusion policies.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ErrorCategory {
+    /// Not enough data to proceed: retry at next [Epoch] with more [Candidate]s.
+    NotEnoughData,
+    /// A [Candidate] lacks observations or states: it should be excluded.
+    Measurement,
+    /// Numerical failure, most likely due to bad observations or geometry.
+    Numerical,
+    /// Physical non sense, most likely due to bad observations or orbital states.
+    Physics,
+    /// The solution was resolved but did not pass validation.
+    Invalidated,
+    /// Phase ambiguities are not resolved yet: retry later on.
+    Ambiguity,
+    /// [Solver] setup or configuration issue: retrying will not help.
+    Setup,
+}
+
+impl ErrorKind {
+    /// Returns the [ErrorCategory] of this [ErrorKind]
+    pub fn category(&self) -> ErrorCategory {
+        match self {
+            Self::NotEnoughCandidates
+            | Self::NotEnoughCandidatesBancroft
+            | Self::NotEnoughCandidatesColdStart
+            | Self::NotEnoughPreFitCandidates
+            | Self::NotEnoughPostFitCandidates
+            | Self::MissingRemoteRTKObservations => ErrorCategory::NotEnoughData,
+            Self::MissingPseudoRange
+            | Self::PseudoRangeCombination
+            | Self::PhaseRangeCombination
+            | Self::UnresolvedState
+            | Self::UnresolvedStateBancroft
+            | Self::UnknownClockCorrection
+            | Self::MissingRemoteRTKObservation
+            | Self::OutsideValidityWindow => ErrorCategory::Measurement,
+            Self::InconsistentGalileoNavMessage => ErrorCategory::Setup,
+            Self::MatrixFormationError
+            | Self::MatrixInversionError
+            | Self::TimeIsNan
+            | Self::NavigationError
+            | Self::BancroftError
+            | Self::BancroftImaginarySolution
+            | Self::ColdStartDivergence => ErrorCategory::Numerical,
+            Self::PhysicalNonSenseRxPriorTx
+            | Self::PhysicalNonSenseRxTooLate
+            | Self::Physics(_) => ErrorCategory::Physics,
+            Self::InvalidatedSolution(_) => ErrorCategory::Invalidated,
+            Self::UnresolvedAmbiguity => ErrorCategory::Ambiguity,
+            Self::InvalidStrategy | Self::Almanac(_) | Self::EarthFrame(_) => ErrorCategory::Setup,
+            #[cfg(feature = "std")]
+            Self::MetaAlmanac(_) => ErrorCategory::Setup,
+        }
+    }
+}
+
+/// [Solver] [Error]: describes the [ErrorKind], the [Epoch] of the resolution attempt
+/// and the [SV] that caused it, when applicable.
+#[derive(Debug, PartialEq)]
+pub struct Error {
+    kind: ErrorKind,
+    epoch: Option<Epoch>,
+    sv: Option<SV>,
+}
+
+impl Error {
+    /// Builds new [Error] without context
+    pub(crate) fn new(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            epoch: None,
+            sv: None,
+        }
+    }
+    /// Attaches [Epoch] context (if not already defined)
+    pub(crate) fn with_epoch(mut self, t: Epoch) -> Self {
+        self.epoch = Some(self.epoch.unwrap_or(t));
+        self
+    }
+    /// Attaches [SV] context
+    pub(crate) fn with_sv(mut self, sv: SV) -> Self {
+        self.sv = Some(sv);
+        self
+    }
+    /// Returns the [ErrorKind]
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+    /// Returns the [ErrorCategory]
+    pub fn category(&self) -> ErrorCategory {
+        self.kind.category()
+    }
+    /// Returns [Epoch] of the resolution attempt, if any
+    pub fn epoch(&self) -> Option<Epoch> {
+        self.epoch
+    }
+    /// Returns [SV] that caused this [Error], if any
+    pub fn sv(&self) -> Option<SV> {
+        self.sv
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Self::new(kind)
+    }
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match (self.epoch, self.sv) {
+            (Some(t), Some(sv)) => write!(f, "{}({}): {}", t, sv, self.kind),
+            (Some(t), None) => write!(f, "{}: {}", t, self.kind),
+            (None, Some(sv)) => write!(f, "{}: {}", sv, self.kind),
+            (None, None) => write!(f, "{}", self.kind),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.kind)
+    }
 }
 
 /// [Solver] to resolve [PVTSolution]s.
@@ -160,6 +325,9 @@ pub struct Solver<O: OrbitSource> {
     /// or guess by a first Iteration. The latest [Orbit]
     /// that we have resolved is contained in [prev_solution].
     initial: Option<Orbit>,
+    /// [AprioriPosition] forwarded by User, constraining the solution
+    /// when its covariance is known
+    apriori: Option<AprioriPosition>,
     /// [Almanac]
     almanac: Almanac,
     /// [Frame]
@@ -173,51 +341,83 @@ pub struct Solver<O: OrbitSource> {
     /* prev. solution for internal logic */
     /// Previous solution (internal logic)
     prev_solution: Option<(Epoch, PVTSolution)>,
+    /// True when the previous solution passed validation (or was restored):
+    /// the first solution is stored but never propagated
+    prev_validated: bool,
     /// Stored previous SV state (internal logic)
-    sv_orbits: HashMap<SV, Orbit>,
+    sv_orbits: SVMap<Orbit>,
+    /// Platform [Attitude] at current [Epoch] (moving platforms)
+    attitude: Option<Attitude>,
+    /// [Aiding] measurements at current [Epoch] (moving platforms)
+    aiding: Vec<Aiding>,
+    /// [AidingFilter], fusing GNSS and [Aiding] measurements
+    aiding_filter: Option<AidingFilter>,
+    /// Optional [ClockSource]
+    clock: Option<Box<dyn ClockSource + Send>>,
+    /// Optional [TimeSource]
+    time: Option<Box<dyn TimeSource + Send>>,
+    /// Optional [SbasIonoGrid]
+    iono_grid: Option<SbasIonoGrid>,
+    /// [CandidatePipeline]
+    pipeline: CandidatePipeline,
+    /// Working [Candidate] buffer, reused from one [Epoch] to the next
+    buffer: Vec<Candidate>,
+    /// Optional [StaticBatch] estimator
+    static_batch: Option<StaticBatch>,
+    /// Per [Epoch] scratch [Workspace]
+    workspace: Workspace,
+    /// GPS to Galileo time offset estimate
+    ggto: GgtoEstimator,
+    /// [ConstellationHealth] monitoring
+    health: ConstellationHealth,
+    /// Receiver clock jumps detection and repair
+    clock_jumps: ClockJumps,
+    /// Optional [PerfCounters]
+    #[cfg(feature = "std")]
+    perf: Option<PerfCounters>,
 }
 
-/// Apply signal condition criteria
-fn signal_condition_filter(method: Method, pool: &mut Vec<Candidate>) {
-    pool.retain(|cd| match method {
-        Method::SPP => {
-            if cd.prefered_pseudorange().is_some() {
-                true
-            } else {
-                error!("{} ({}) missing pseudo range observation", cd.t, cd.sv);
-                false
-            }
-        },
-        Method::CPP => {
-            if cd.cpp_compatible() {
-                true
-            } else {
-                debug!("{} ({}) missing secondary frequency", cd.t, cd.sv);
-                false
-            }
-        },
-        Method::PPP => {
-            if cd.ppp_compatible() {
-                true
-            } else {
-                debug!("{} ({}) missing phase or phase combination", cd.t, cd.sv);
-                false
-            }
-        },
-    })
+/// Updates each [Candidate], in parallel with the `parallel` feature
+fn update_candidates<F>(pool: &mut [Candidate], f: F) -> Result<(), Error>
+where
+    F: Fn(&mut Candidate) -> Result<(), Error> + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    let ret = pool.par_iter_mut().try_for_each(f);
+    #[cfg(not(feature = "parallel"))]
+    let ret = pool.iter_mut().try_for_each(f);
+    ret
 }
 
-/// Apply signal quality criteria
-fn signal_quality_filter(min_snr: f64, pool: &mut Vec<Candidate>) {
-    pool.retain_mut(|cd| {
-        cd.min_snr_mask(min_snr);
-        !cd.observations.is_empty()
-    })
+/// Maximal number of light-time iterations
+const LIGHT_TIME_MAX_ITERATIONS: usize = 5;
+
+/// Light-time iterations stop once the transmission [Epoch] varies by this much at most
+const LIGHT_TIME_CONVERGENCE: Duration = Duration::from_parts(0, 1);
+
+/// Sorts the pool by [SV], and the observations of each [Candidate] by
+/// [Carrier](crate::prelude::Carrier): results no longer depend on the input order
+pub(crate) fn canonical_order(pool: &mut [Candidate]) {
+    pool.sort_by(|cd_a, cd_b| cd_a.sv.cmp(&cd_b.sv));
+    for cd in pool.iter_mut() {
+        cd.observations
+            .sort_by(|ob_a, ob_b| ob_a.carrier.cmp(&ob_b.carrier));
+    }
+}
+
+/// Returns true if [Epoch] lies within this (possibly unbounded) validity interval
+fn is_within(validity: Option<(Epoch, Epoch)>, t: Epoch) -> bool {
+    match validity {
+        Some((start, end)) => t >= start && t <= end,
+        None => true,
+    }
 }
 
 impl<O: OrbitSource> Solver<O> {
+    #[cfg(feature = "std")]
     const ALMANAC_LOCAL_STORAGE: &str = ".cache";
 
+    #[cfg(feature = "std")]
     fn nyx_anise_de440s_bsp() -> MetaFile {
         MetaFile {
             crc32: Some(1921414410),
@@ -225,6 +425,7 @@ impl<O: OrbitSource> Solver<O> {
         }
     }
 
+    #[cfg(feature = "std")]
     fn nyx_anise_pck11_pca() -> MetaFile {
         MetaFile {
             crc32: Some(0x8213b6e9),
@@ -232,6 +433,7 @@ impl<O: OrbitSource> Solver<O> {
         }
     }
 
+    #[cfg(feature = "std")]
     fn nyx_anise_jpl_bpc() -> MetaFile {
         MetaFile {
             crc32: None,
@@ -246,6 +448,7 @@ impl<O: OrbitSource> Solver<O> {
     /// so this library is currently limited Earth ground navigation.
     /// We always prefer the highest precision model, which requires daily internet access.
     /// If internet access is in failure, the [Almanac] relies on an offline model.
+    #[cfg(feature = "std")]
     fn build_almanac_frame_model() -> Result<(Almanac, Frame), Error> {
         let mut initial_setup = false;
 
@@ -275,10 +478,10 @@ impl<O: OrbitSource> Solver<O> {
         };
 
         // download (if need be)
-        let almanac = meta_almanac.process(true).map_err(|e| Error::Almanac(e))?;
+        let almanac = meta_almanac.process(true).map_err(ErrorKind::Almanac)?;
 
         if initial_setup {
-            let updated = meta_almanac.dumps().map_err(|e| Error::MetaAlmanac(e))?;
+            let updated = meta_almanac.dumps().map_err(ErrorKind::MetaAlmanac)?;
 
             let _ = create_dir_all(&format!(
                 "{}/{}",
@@ -305,7 +508,7 @@ impl<O: OrbitSource> Solver<O> {
 
         let earth_cef = almanac
             .frame_from_uid(IAU_EARTH_FRAME)
-            .map_err(|e| Error::EarthFrame(e))?;
+            .map_err(ErrorKind::EarthFrame)?;
 
         warn!("deployed with offline model");
         Ok((almanac, earth_cef))
@@ -343,13 +546,33 @@ impl<O: OrbitSource> Solver<O> {
             almanac,
             earth_cef: frame,
             initial,
+            apriori: None,
             cfg: cfg.clone(),
             prev_solution: None,
+            prev_validated: false,
             // TODO
-            ambiguity: AmbiguitySolver::new(Duration::from_seconds(120.0)),
+            ambiguity: AmbiguitySolver::new(
+                Duration::from_seconds(120.0),
+                cfg.solver.tracking_buffer_capacity,
+            ),
             // postfit_kf: None,
-            sv_orbits: HashMap::new(),
+            sv_orbits: SVMap::with_capacity(32),
             nav: Navigation::new(cfg.solver.filter),
+            attitude: None,
+            aiding: Vec::new(),
+            aiding_filter: None,
+            clock: None,
+            time: None,
+            iono_grid: None,
+            pipeline: CandidatePipeline::default(),
+            buffer: Vec::new(),
+            static_batch: None,
+            workspace: Workspace::default(),
+            ggto: GgtoEstimator::default(),
+            health: ConstellationHealth::default(),
+            clock_jumps: ClockJumps::default(),
+            #[cfg(feature = "std")]
+            perf: None,
         }
     }
     /// Create a new Position [Solver] that may support any positioning technique
@@ -357,18 +580,38 @@ impl<O: OrbitSource> Solver<O> {
     /// ## Inputs
     /// - cfg: Solver [Config]
     /// - initial: possible initial position expressed as [Orbit] in ECEF.
-    ///   When not provided, the solver will initialize itself autonomously by consuming at least one [Epoch].
+    ///   When not provided, the solver will initialize itself autonomously by consuming at least one [Epoch],
+    ///   with the [ColdStart] method defined in [Config].
     ///   Note that we need at least 4 valid SV observations to initiliaze the [Solver].
     ///   You have to take that into account, especially when operating in Fixed Altitude
     ///   or Time Only modes.
     /// - orbit: [OrbitSource] must be provided for Direct (1D) PPP
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
     pub fn new(cfg: &Config, initial: Option<Orbit>, orbit: O) -> Result<Self, Error> {
         let (almanac, earth_cef) = Self::build_almanac_frame_model()?;
         Ok(Self::new_almanac_frame(
             cfg, initial, orbit, almanac, earth_cef,
         ))
     }
+    /// Create new Position [Solver] initialized at this [AprioriPosition]
+    /// (ECEF or geodetic coordinates), expressed in the internal ECEF [Frame].
+    /// When the [AprioriPosition] covariance is defined, it constrains the solution
+    /// (soft constraint) whenever no previous state is available (first iteration,
+    /// or every iteration when the navigation filter is bypassed).
+    /// See [Self::new] for other options.
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
+    pub fn new_apriori(cfg: &Config, apriori: AprioriPosition, orbit: O) -> Result<Self, Error> {
+        let (almanac, earth_cef) = Self::build_almanac_frame_model()?;
+        let initial = apriori.to_orbit(earth_cef);
+        let mut solver = Self::new_almanac_frame(cfg, Some(initial), orbit, almanac, earth_cef);
+        solver.apriori = Some(apriori);
+        Ok(solver)
+    }
     /// Create new Position [Solver] without knowledge of apriori position (full survey)
+    #[cfg(feature = "std")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "std")))]
     pub fn new_survey(cfg: &Config, orbit: O) -> Result<Self, Error> {
         Self::new(cfg, None, orbit)
     }
@@ -382,74 +625,645 @@ impl<O: OrbitSource> Solver<O> {
     ) -> Self {
         Self::new_almanac_frame(cfg, None, orbit, almanac, frame)
     }
+    /// Returns a [SolverState] snapshot, that you can store
+    /// and use to warm start a [Solver] later on, with [Self::restore].
+    pub fn state(&self) -> SolverState {
+        let mut state = SolverState::default();
+        if let Some(initial) = self.initial {
+            let pos_vel = initial.to_cartesian_pos_vel() * 1.0E3;
+            state.apriori_ecef_m = Some((pos_vel[0], pos_vel[1], pos_vel[2]));
+        }
+        if let Some((t, solution)) = &self.prev_solution {
+            let pos_vel = solution.state.to_cartesian_pos_vel() * 1.0E3;
+            state.epoch = Some(*t);
+            state.position_ecef_m = Some((pos_vel[0], pos_vel[1], pos_vel[2]));
+            state.velocity_ecef_m_s = Some((pos_vel[3], pos_vel[4], pos_vel[5]));
+            state.clock_offset_s = Some(solution.dt.to_seconds());
+            state.clock_drift_s_s = Some(solution.d_dt);
+        }
+        state.filter = self.nav.state().map(|state| state.snapshot());
+        state.ambiguities = self.ambiguity.snapshot();
+        state
+    }
+    /// Returns a read-only [EstimatorView] of the current estimator state
+    /// (labelled state vector, covariance, ambiguities resolution status).
+    pub fn estimator_view(&self) -> EstimatorView {
+        EstimatorView {
+            epoch: self.prev_solution.as_ref().map(|(t, _)| *t),
+            filter: self.nav.state().map(|state| state.view()),
+            ambiguities: self.ambiguity.status(),
+        }
+    }
+    /// Restores [SolverState] obtained with [Self::state] (warm start).
+    /// The first solution after warm start is not discarded.
+    /// It is up to you to determine whether the [SolverState] is still relevant
+    /// (for example, the receiver may have moved since it was obtained).
+    pub fn restore(&mut self, state: &SolverState) {
+        let t = state.epoch.unwrap_or_default();
+        if let Some((x, y, z)) = state.apriori_ecef_m {
+            self.initial = Some(Orbit::from_position(
+                x / 1.0E3,
+                y / 1.0E3,
+                z / 1.0E3,
+                t,
+                self.earth_cef,
+            ));
+        }
+        self.prev_solution = None;
+        self.prev_validated = false;
+        if let (Some(t), Some((x, y, z))) = (state.epoch, state.position_ecef_m) {
+            let (vx, vy, vz) = state.velocity_ecef_m_s.unwrap_or_default();
+            let pos_vel = Vector6::new(x, y, z, vx, vy, vz) / 1.0E3;
+            let solution = PVTSolution {
+                state: Orbit::from_cartesian_pos_vel(pos_vel, t, self.earth_cef),
+                frame: self.cfg.orbit_frame,
+                timescale: self.cfg.timescale,
+                dt: Duration::from_seconds(state.clock_offset_s.unwrap_or_default()),
+                d_dt: state.clock_drift_s_s.unwrap_or_default(),
+                sv: BTreeMap::new(),
+                ambiguities: Default::default(),
+                holdover: None,
+                ggto: None,
+                q: Matrix4::zeros(),
+            };
+            self.prev_solution = Some((t, solution));
+            self.prev_validated = true;
+        }
+        self.nav.reset();
+        if let Some(snapshot) = &state.filter {
+            match FilterState::from_snapshot(snapshot) {
+                Some(filter_state) => self.nav.restore(filter_state),
+                None if snapshot.filter == Filter::None => {
+                    debug!("bypassed filter snapshot: nothing to restore")
+                },
+                None => error!("invalid filter snapshot: cannot be restored"),
+            }
+        }
+        self.ambiguity.restore(&state.ambiguities);
+        self.clock_jumps = ClockJumps::default();
+        self.sv_orbits.clear();
+        self.aiding_filter = None;
+        self.health = ConstellationHealth::default();
+    }
+    /// [PVTSolution] resolution attempt, for moving platforms
+    /// whose [Attitude] is known at this [Epoch]. The [Attitude] is used
+    /// to rotate the body frame lever arm (see [Config]) into the local frame.
+    /// ## Inputs
+    /// - t: desired [Epoch]
+    /// - pool: list of [Candidate]
+    /// - attitude: platform [Attitude] at this [Epoch]
+    pub fn resolve_with_attitude(
+        &mut self,
+        t: Epoch,
+        pool: &[Candidate],
+        attitude: Attitude,
+    ) -> Result<(Epoch, PVTSolution), Error> {
+        self.attitude = Some(attitude);
+        let ret = self.resolve(t, pool);
+        self.attitude = None;
+        ret
+    }
+    /// [PVTSolution] resolution attempt, for moving platforms
+    /// equipped with external sensors (loose coupling). The [Aiding] measurements
+    /// are fused with the GNSS solution, and bridge the GNSS gaps:
+    /// when not enough vehicles are in sight, the solution is dead reckoned
+    /// from the [Aiding] measurements (clock is then in holdover).
+    /// ## Inputs
+    /// - t: desired [Epoch]
+    /// - pool: list of [Candidate]
+    /// - aiding: [Aiding] measurements at this [Epoch]
+    pub fn resolve_with_aiding(
+        &mut self,
+        t: Epoch,
+        pool: &[Candidate],
+        aiding: &[Aiding],
+    ) -> Result<(Epoch, PVTSolution), Error> {
+        self.aiding = aiding.to_vec();
+        let ret = self.resolve(t, pool);
+        self.aiding.clear();
+        ret
+    }
+    /// Returns mutable [CandidatePipeline], to insert custom [CandidateFilter](crate::prelude::CandidateFilter)s
+    /// between the built-in filters.
+    pub fn candidate_pipeline_mut(&mut self) -> &mut CandidatePipeline {
+        &mut self.pipeline
+    }
+    /// Enables (or disables) the [StaticBatch] estimator: every validated [Epoch]
+    /// then contributes to a single position and per epoch clock adjustment,
+    /// for static sessions. Disabling drops the accumulated epochs.
+    pub fn set_static_batch(&mut self, enabled: bool) {
+        self.static_batch = if enabled {
+            Some(StaticBatch::default())
+        } else {
+            None
+        };
+    }
+    /// Returns [StaticBatch] estimator, if enabled.
+    /// Use [StaticBatch::solve] to resolve the session solution.
+    pub fn static_batch(&self) -> Option<&StaticBatch> {
+        self.static_batch.as_ref()
+    }
+    /// Attach a [ClockSource], that will provide the SV clock corrections
+    /// for each [Candidate] proposed without [ClockCorrection].
+    pub fn set_clock_source<C: ClockSource + Send + 'static>(&mut self, clock: C) {
+        self.clock = Some(Box::new(clock));
+    }
+    /// Attach a [TimeSource], that will provide the offsets between [TimeScale]s,
+    /// used to express the [PVTSolution]s in the [TimeScale] defined in [Config].
+    /// Nominal offsets apply when no [TimeSource] is attached, or it cannot answer.
+    pub fn set_time_source<T: TimeSource + Send + 'static>(&mut self, time: T) {
+        self.time = Some(Box::new(time));
+    }
+    /// Attach a [SbasIonoGrid], that prevails over the [IonoComponents]
+    /// of each [Candidate] in single frequency navigation, when it applies.
+    pub fn set_sbas_iono_grid(&mut self, grid: SbasIonoGrid) {
+        self.iono_grid = Some(grid);
+    }
+    /// Returns mutable attached [SbasIonoGrid], to update the grid points
+    pub fn sbas_iono_grid_mut(&mut self) -> Option<&mut SbasIonoGrid> {
+        self.iono_grid.as_mut()
+    }
+    /// Enables (or disables) the [PerfCounters]: time spent per processing [Stage]
+    /// and allocations (see [CountingAllocator](crate::prelude::CountingAllocator)),
+    /// reported for each resolution attempt. Disabled by default.
+    #[cfg(feature = "std")]
+    pub fn set_perf_counters(&mut self, enabled: bool) {
+        self.perf = if enabled {
+            Some(PerfCounters::default())
+        } else {
+            None
+        };
+    }
+    /// Returns [PerfCounters] of the latest resolution attempt, when enabled
+    #[cfg(feature = "std")]
+    pub fn perf_counters(&self) -> Option<&PerfCounters> {
+        self.perf.as_ref()
+    }
     /// [PVTSolution] resolution attempt.
     /// ## Inputs
     /// - t: desired [Epoch]
     /// - pool: list of [Candidate]
+    /// ## Returns
+    /// - [Epoch] and [PVTSolution] on success, possibly obtained in clock holdover
+    ///   when not enough vehicles are in sight (if enabled),
+    /// - [Error] carrying `t` and the [SV] that caused it (when applicable) otherwise.
+    /// The pool is copied into a working buffer, that is reused (with the [Observation](crate::prelude::Observation)
+    /// buffers of its [Candidate]s) from one [Epoch] to the next.
     pub fn resolve(&mut self, t: Epoch, pool: &[Candidate]) -> Result<(Epoch, PVTSolution), Error> {
+        let mut buffer = std::mem::take(&mut self.buffer);
+        Self::fill_buffer(&mut buffer, pool);
+        let ret = self.resolve_in_place(t, &mut buffer);
+        self.buffer = buffer;
+        ret
+    }
+    /// [PVTSolution] resolution attempt, from a list of [Candidate] that
+    /// the [Solver] takes ownership of: it is then processed in place and never copied.
+    /// Prefer this method when the pool is formed for each [Epoch].
+    /// See [Self::resolve] for more information.
+    pub fn resolve_owned(
+        &mut self,
+        t: Epoch,
+        mut pool: Vec<Candidate>,
+    ) -> Result<(Epoch, PVTSolution), Error> {
+        self.resolve_in_place(t, &mut pool)
+    }
+    /// Resolves a batch of [Epoch]s, in chronological order, for high rate post processing.
+    /// This is equivalent to [Self::resolve] for each [Epoch], the working buffer
+    /// being sized for the largest pool once, and reused for the whole batch.
+    /// Returns one result per [Epoch], in the same order.
+    pub fn run_batch(
+        &mut self,
+        epochs: &[(Epoch, Vec<Candidate>)],
+    ) -> Vec<Result<(Epoch, PVTSolution), Error>> {
+        let capacity = epochs
+            .iter()
+            .map(|(_, pool)| pool.len())
+            .max()
+            .unwrap_or_default();
+        let mut buffer = std::mem::take(&mut self.buffer);
+        buffer.reserve(capacity.saturating_sub(buffer.len()));
+        let mut solutions = Vec::with_capacity(epochs.len());
+        for (t, pool) in epochs.iter() {
+            Self::fill_buffer(&mut buffer, pool);
+            solutions.push(self.resolve_in_place(*t, &mut buffer));
+        }
+        self.buffer = buffer;
+        solutions
+    }
+    /// Copies this pool into the working buffer, reusing the buffered [Candidate]s
+    fn fill_buffer(buffer: &mut Vec<Candidate>, pool: &[Candidate]) {
+        buffer.truncate(pool.len());
+        let reused = buffer.len();
+        for (dst, src) in buffer.iter_mut().zip(pool.iter()) {
+            dst.clone_from(src);
+        }
+        buffer.extend_from_slice(&pool[reused..]);
+    }
+    /// [PVTSolution] resolution attempt, processing this working buffer in place
+    fn resolve_in_place(
+        &mut self,
+        t: Epoch,
+        pool: &mut Vec<Candidate>,
+    ) -> Result<(Epoch, PVTSolution), Error> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!(
+            "epoch",
+            %t,
+            method = ?self.cfg.method,
+            candidates = pool.len()
+        )
+        .entered();
+
+        match self.try_resolve(t, pool) {
+            Ok(solution) => Ok(solution),
+            Err(e) => match e.kind() {
+                ErrorKind::NotEnoughCandidates
+                | ErrorKind::NotEnoughPreFitCandidates
+                | ErrorKind::NotEnoughPostFitCandidates => self
+                    .dead_reckoning(t)
+                    .or_else(|| self.holdover(t))
+                    .ok_or_else(|| e.with_epoch(t)),
+                _ => Err(e.with_epoch(t)),
+            },
+        }
+    }
+    /// Clock holdover attempt (see `max_holdover` option): propagates
+    /// the latest validated clock state to `t`, position is held.
+    /// Latest resolved solution is preserved, so holdover does not accumulate.
+    fn holdover(&mut self, t: Epoch) -> Option<(Epoch, PVTSolution)> {
+        let max_holdover = self.cfg.solver.max_holdover?;
+        if !self.prev_validated {
+            return None;
+        }
+        let (prev_t, prev_sol) = self.prev_solution.as_ref()?;
+        let elapsed = t - *prev_t;
+        if elapsed < Duration::ZERO || elapsed > max_holdover {
+            return None;
+        }
+
+        let pos = prev_sol.state.to_cartesian_pos_vel();
+        let mut solution = prev_sol.clone();
+        solution.state = Orbit::from_position(pos[0], pos[1], pos[2], t, self.earth_cef);
+        solution.dt += Duration::from_seconds(prev_sol.d_dt * elapsed.to_seconds());
+        solution.sv.clear();
+        solution.q = Matrix4::zeros();
+        solution.holdover = Some(elapsed);
+
+        warn!("{} - clock holdover ({} elapsed)", t, elapsed);
+
+        let t = Self::rework_solution(t, self.earth_cef, &self.cfg, &mut self.time, &mut solution);
+        Some((t, solution))
+    }
+    /// Dead reckoning attempt: propagates the [AidingFilter] to `t`
+    /// with the [Aiding] measurements, clock is in holdover.
+    /// Latest resolved solution is preserved.
+    fn dead_reckoning(&mut self, t: Epoch) -> Option<(Epoch, PVTSolution)> {
+        if self.aiding.is_empty() {
+            return None;
+        }
+        let (prev_t, prev_sol) = self.prev_solution.as_ref()?;
+        let filter = self.aiding_filter.as_mut()?;
+        let elapsed = t - *prev_t;
+        if elapsed < Duration::ZERO || t < filter.epoch() {
+            return None;
+        }
+
+        filter.predict(t, &self.aiding);
+        filter.update_aiding(&self.aiding, true);
+
+        let (pos_m, vel_m_s) = (filter.position_m(), filter.velocity_m_s());
+        let pos_vel_m = Vector6::new(
+            pos_m[0], pos_m[1], pos_m[2], vel_m_s[0], vel_m_s[1], vel_m_s[2],
+        );
+        let mut solution = prev_sol.clone();
+        solution.state = Orbit::from_cartesian_pos_vel(pos_vel_m / 1.0E3, t, self.earth_cef);
+        solution.dt += Duration::from_seconds(prev_sol.d_dt * elapsed.to_seconds());
+        solution.sv.clear();
+        solution.q = Matrix4::zeros();
+        solution.holdover = Some(elapsed);
+
+        warn!("{} - dead reckoning ({} elapsed)", t, elapsed);
+
+        let t = Self::rework_solution(t, self.earth_cef, &self.cfg, &mut self.time, &mut solution);
+        Some((t, solution))
+    }
+    /// Fuses this GNSS [PVTSolution] and the [Aiding] measurements, if any
+    fn fuse_aiding(&mut self, t: Epoch, sol: &mut PVTSolution) {
+        if self.aiding.is_empty() && self.aiding_filter.is_none() {
+            return;
+        }
+
+        let pos_vel_m = sol.state.to_cartesian_pos_vel() * 1.0E3;
+        let pos_m = Vector3::new(pos_vel_m[0], pos_vel_m[1], pos_vel_m[2]);
+        let vel_m_s = Vector3::new(pos_vel_m[3], pos_vel_m[4], pos_vel_m[5]);
+
+        // scale the geometry by the post-fit residuals
+        let residuals_m = sol
+            .sv
+            .values()
+            .filter_map(|sv| sv.residual_m)
+            .collect::<Vec<_>>();
+        let var_m2 = if residuals_m.is_empty() {
+            1.0
+        } else {
+            let sum = residuals_m.iter().map(|r| r.powi(2)).sum::<f64>();
+            (sum / residuals_m.len() as f64).max(1.0)
+        };
+        let cov_m2 = sol.q.fixed_view::<3, 3>(0, 0).into_owned() * var_m2;
+
+        let filter = match self.aiding_filter.as_mut() {
+            Some(filter) if t > filter.epoch() => filter,
+            _ => {
+                self.aiding_filter = Some(AidingFilter::new(t, pos_m, vel_m_s, cov_m2));
+                return;
+            },
+        };
+
+        filter.predict(t, &self.aiding);
+        filter.update_position(pos_m, cov_m2);
+
+        let baro = match self.cfg.solver.baro_max_pdop {
+            Some(max_pdop) => sol.pdop() > max_pdop,
+            None => true,
+        };
+        filter.update_aiding(&self.aiding, baro);
+
+        if !self.aiding.is_empty() {
+            let (pos_m, vel_m_s) = (filter.position_m(), filter.velocity_m_s());
+            let pos_vel_m = Vector6::new(
+                pos_m[0], pos_m[1], pos_m[2], vel_m_s[0], vel_m_s[1], vel_m_s[2],
+            );
+            sol.state =
+                Orbit::from_cartesian_pos_vel(pos_vel_m / 1.0E3, sol.state.epoch, self.earth_cef);
+        }
+    }
+    fn try_resolve(
+        &mut self,
+        t: Epoch,
+        pool: &mut Vec<Candidate>,
+    ) -> Result<(Epoch, PVTSolution), Error> {
+        #[cfg(feature = "std")]
+        if let Some(perf) = &mut self.perf {
+            perf.start(t);
+        }
+
+        self.workspace.clear();
+
+        // candidates may be timestamped in any timescale (GST, BDT, UTC for GLONASS..):
+        // they are all processed in the solver timescale
+        let t_in = t;
+        let t = t.to_time_scale(self.cfg.timescale);
+
+        // GLONASS time is UTC based: leap second events
+        let leap_second = self.cfg.glonass_leap_second;
+        if leap_second != LeapSecondPolicy::Step {
+            pool.retain_mut(|cd| {
+                if cd.sv.constellation != Constellation::Glonass
+                    || cd.t.time_scale != TimeScale::UTC
+                {
+                    return true;
+                }
+                match leap_second.apply(cd.t) {
+                    Some(t) => {
+                        cd.t = t;
+                        true
+                    },
+                    None => {
+                        debug!("{}({}) - rejected (leap second event)", cd.t, cd.sv);
+                        false
+                    },
+                }
+            });
+        }
+
+        for cd in pool.iter_mut() {
+            cd.align_time_scale(self.cfg.timescale);
+            cd.prioritize(&self.cfg.signal_priority);
+        }
+
+        // asynchronous observations
+        if let Some(max_misalignment) = self.cfg.solver.max_epoch_misalignment {
+            pool.retain_mut(|cd| {
+                if (cd.t - t).abs() > max_misalignment {
+                    debug!("{}({}) - rejected (misaligned sampling)", cd.t, cd.sv);
+                    false
+                } else if !cd.align_epoch(t) {
+                    debug!(
+                        "{}({}) - rejected (cannot be aligned: missing doppler)",
+                        cd.t, cd.sv
+                    );
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        if self.cfg.solver.repair_clock_jumps {
+            self.clock_jumps.repair(t, pool);
+        }
+
         let min_required = self.min_sv_required();
         if pool.len() < min_required {
             // no need to proceed further
-            return Err(Error::NotEnoughCandidates);
+            return Err(ErrorKind::NotEnoughCandidates.into());
+        }
+
+        if self.cfg.solver.deterministic {
+            canonical_order(pool);
+        }
+
+        // candidates rejected because their states are requested outside the providers validity
+        let mut out_of_validity = 0;
+        // latest candidate rejection, reported when the pool is exhausted
+        let mut rejected = Option::<Error>::None;
+
+        // broadcast clock polynomials, evaluated at transmission time
+        for cd in pool.iter_mut() {
+            if let Some(poly) = cd.clock_poly {
+                if cd.clock_corr.is_none() {
+                    let t_tx = cd.approximate_transmission_time();
+                    // refined by the clock offset itself (up to 1 ms)
+                    let t_tx = t_tx - poly.clock_correction(t_tx).duration;
+                    cd.clock_corr = Some(poly.clock_correction(t_tx));
+                }
+            }
+        }
+
+        let interpolation = self.cfg.interpolation;
+
+        if let Some(clock) = &mut self.clock {
+            pool.retain_mut(|cd| {
+                #[cfg(feature = "tracing")]
+                let _span =
+                    tracing::debug_span!("candidate", sv = %cd.sv, stage = "clock").entered();
+
+                if cd.clock_corr.is_some() {
+                    return true;
+                }
+                let t_tx = cd.approximate_transmission_time();
+                if !is_within(clock.clock_validity(cd.sv), t_tx) {
+                    error!(
+                        "{} ({}) - clock: {}",
+                        cd.t,
+                        cd.sv,
+                        ErrorKind::OutsideValidityWindow
+                    );
+                    out_of_validity += 1;
+                    return false;
+                }
+                if let Some(corr) = clock.next_clock_at(t_tx, cd.sv, interpolation) {
+                    cd.set_clock_correction(corr);
+                }
+                true
+            });
         }
 
-        let mut pool = pool.to_vec();
+        // mixed GPS/Galileo pools: Galileo clocks are expressed in GPST
+        // (QZSST is aligned to GPST: QZSS vehicles are GPS-like here)
+        let ggto = if self.cfg.solver.estimate_ggto
+            && pool.iter().any(|cd| {
+                matches!(
+                    cd.sv.constellation,
+                    Constellation::GPS | Constellation::QZSS
+                )
+            })
+            && pool
+                .iter()
+                .any(|cd| cd.sv.constellation == Constellation::Galileo)
+        {
+            let ggto = self.ggto.value().unwrap_or_default();
+            for cd in pool.iter_mut() {
+                if cd.sv.constellation == Constellation::Galileo {
+                    if let Some(clock_corr) = &mut cd.clock_corr {
+                        clock_corr.duration += ggto;
+                    }
+                }
+            }
+            Some(ggto)
+        } else {
+            None
+        };
+
+        if self.cfg.modeling.sv_total_group_delay {
+            // mixed galileo navigation messages: setup issue, not a bad measurement
+            for cd in pool.iter() {
+                if let Err(ErrorKind::InconsistentGalileoNavMessage) = cd.group_delay() {
+                    return Err(Error::new(ErrorKind::InconsistentGalileoNavMessage).with_sv(cd.sv));
+                }
+            }
+        }
 
         let method = self.cfg.method;
         let modeling = self.cfg.modeling;
-        let interp_order = self.cfg.interp_order;
         let max_iono_bias = self.cfg.max_iono_bias;
         let max_tropo_bias = self.cfg.max_tropo_bias;
         let iono_modeling = self.cfg.modeling.iono_delay;
         let tropo_modeling = self.cfg.modeling.tropo_delay;
 
-        // signal condition filter
-        signal_condition_filter(method, &mut pool);
-
-        // signal quality filter
-        if let Some(min_snr) = self.cfg.min_snr {
-            signal_quality_filter(min_snr, &mut pool);
-        }
+        // signal condition & quality filters
+        let ctx = FilterContext {
+            t,
+            cfg: &self.cfg,
+            rx_orbit: None,
+        };
+        self.pipeline.apply(FilterStage::Observations, &ctx, pool);
 
         if pool.len() < min_required {
             // no need to proceed further
-            return Err(Error::NotEnoughPreFitCandidates);
+            return Err(ErrorKind::NotEnoughPreFitCandidates.into());
         }
 
-        // orbital state solver
-        let mut pool: Vec<Candidate> = pool
-            .iter()
-            .filter_map(|cd| match cd.transmission_time(&self.cfg) {
+        // apriori receiver position [m] and clock offset, for the light-time iteration
+        let light_time_apriori = if self.cfg.solver.light_time_iteration {
+            self.prev_solution.as_ref().map(|(prev_t, prev_sol)| {
+                let pos_vel_m = prev_sol.state.to_cartesian_pos_vel() * 1.0E3;
+                let rx_m = Vector3::new(pos_vel_m[0], pos_vel_m[1], pos_vel_m[2]);
+                let dt = prev_sol.dt
+                    + Duration::from_seconds(prev_sol.d_dt * (t - *prev_t).to_seconds());
+                (rx_m, dt)
+            })
+        } else {
+            None
+        };
+
+        // orbital state solver (in place)
+        pool.retain_mut(|cd| {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("candidate", sv = %cd.sv, stage = "orbit").entered();
+
+            match cd.transmission_time(&self.cfg) {
                 Ok((t_tx, dt_tx)) => {
                     let orbits = &mut self.orbit;
+                    if !is_within(orbits.validity(cd.sv), t_tx) {
+                        error!(
+                            "{} ({}) - orbit: {}",
+                            cd.t,
+                            cd.sv,
+                            ErrorKind::OutsideValidityWindow
+                        );
+                        out_of_validity += 1;
+                        return false;
+                    }
+                    let (t_tx, dt_tx) = match light_time_apriori {
+                        Some((rx_m, dt_rx)) => Self::light_time_iteration(
+                            orbits,
+                            cd.sv,
+                            cd.t - dt_rx,
+                            t_tx,
+                            rx_m,
+                            self.earth_cef,
+                            interpolation,
+                            modeling.earth_rotation,
+                        ),
+                        None => (t_tx, dt_tx),
+                    };
                     debug!("{} ({}) : signal propagation {}", cd.t, cd.sv, dt_tx);
                     if let Some(tx_orbit) =
-                        orbits.next_at(t_tx, cd.sv, self.earth_cef, interp_order)
+                        orbits.next_at(t_tx, cd.sv, self.earth_cef, interpolation)
                     {
-                        let orbit = Self::rotate_orbit_dcm3x3(
-                            cd.t,
-                            dt_tx,
-                            tx_orbit,
-                            modeling.earth_rotation,
-                            self.earth_cef,
-                        );
-                        Some(cd.with_orbit(orbit))
-                    } else {
-                        // preserve: may still apply to RTK
-                        Some(cd.clone())
+                        cd.orbit = Some(if modeling.earth_rotation && modeling.eci_geometry {
+                            propagate_inertial(t_tx, cd.t, tx_orbit, self.earth_cef)
+                        } else {
+                            Self::rotate_orbit_dcm3x3(
+                                cd.t,
+                                dt_tx,
+                                tx_orbit,
+                                modeling.earth_rotation,
+                                self.earth_cef,
+                            )
+                        });
                     }
+                    // preserved without orbit: may still apply to RTK
+                    true
                 },
                 Err(e) => {
-                    error!("{} - transmision time error: {}", cd.sv, e);
-                    None
+                    let e = Error::new(e).with_sv(cd.sv);
+                    error!("{} - transmission time: {}", cd.t, e);
+                    rejected = Some(e);
+                    false
                 },
-            })
-            .collect();
+            }
+        });
+
+        self.perf_mark(Stage::Interpolation);
+
+        if out_of_validity > 0 && pool.len() < min_required {
+            return Err(ErrorKind::OutsideValidityWindow.into());
+        }
+
+        if pool.len() < min_required {
+            if let Some(e) = rejected {
+                return Err(e);
+            }
+        }
 
         // initialize (if need be)
         if self.initial.is_none() {
-            let solver = Bancroft::new(&pool)?;
-            let output = solver.resolve()?;
+            let output = match self.cfg.solver.cold_start {
+                ColdStart::Bancroft => Bancroft::new(pool)?.resolve()?,
+                ColdStart::GaussNewton => GaussNewton::new(pool)?.resolve()?,
+            };
             let (x0, y0, z0) = (output[0], output[1], output[2]);
             let orbit = Orbit::from_position(
                 x0 / 1.0E3,
@@ -458,12 +1272,7 @@ impl<O: OrbitSource> Solver<O> {
                 pool[0].t,
                 self.earth_cef,
             );
-            let (lat_deg, long_deg, alt_km) = orbit.latlongalt().unwrap_or_else(|e| {
-                panic!(
-                    "resolved invalid initial position: {}, verify your input",
-                    e
-                )
-            });
+            let (lat_deg, long_deg, alt_km) = orbit.latlongalt().map_err(ErrorKind::Physics)?;
 
             info!(
                 "{} estimated initial position lat={:.5}°, lon={:.5}°, alt={:.3}m",
@@ -479,49 +1288,61 @@ impl<O: OrbitSource> Solver<O> {
         let rx_orbit = self.initial.unwrap();
 
         let (rx_lat_deg, rx_long_deg, rx_alt_km) =
-            rx_orbit.latlongalt().map_err(|e| Error::Physics(e))?;
+            rx_orbit.latlongalt().map_err(ErrorKind::Physics)?;
         let rx_alt_m = rx_alt_km * 1.0E3;
         let rx_rad = (rx_lat_deg.to_radians(), rx_long_deg.to_radians());
 
         let rx_pos_vel = rx_orbit.to_cartesian_pos_vel() * 1.0E3;
         let (x0, y0, z0) = (rx_pos_vel[0], rx_pos_vel[1], rx_pos_vel[2]);
 
-        // apply eclipse filter (if need be)
-        if let Some(max_occultation_rate) = self.cfg.max_sv_occultation_percent {
-            pool.retain(|cd| {
-                if let Some(sv_orbit) = cd.orbit {
-                    match self
-                        .almanac
-                        .occultation(SUN_J2000, self.earth_cef, sv_orbit, None)
-                    {
-                        Ok(occultation) => {
-                            if occultation.percentage > max_occultation_rate {
-                                false // filter out
-                            } else {
-                                true // preserve
-                            }
-                        },
-                        Err(e) => {
-                            error!("(anise) eclipse: {}", e);
-                            // discard in this situation
-                            false
-                        },
-                    }
-                } else {
-                    // undefined orbital state
-                    // needs to be preversed for some RTK scenarios
-                    true
-                }
-            });
-        }
+        // eclipse filter (if need be)
+        let ctx = FilterContext {
+            t,
+            cfg: &self.cfg,
+            rx_orbit: Some(rx_orbit),
+        };
+        self.pipeline.apply(FilterStage::States, &ctx, pool);
 
         // sv fixup
-        self.fix_sv_states(rx_orbit, &mut pool)?;
-        Self::sv_state_filter(&self.cfg, &mut pool);
+        self.fix_sv_states(rx_orbit, pool)?;
+
+        // elevation & azimuth masks
+        let ctx = FilterContext {
+            t,
+            cfg: &self.cfg,
+            rx_orbit: Some(rx_orbit),
+        };
+        self.pipeline.apply(FilterStage::Attitude, &ctx, pool);
+
+        // Doppler sanity check
+        if let Some(max_mismatch_m_s) = self.cfg.max_doppler_mismatch {
+            let rx_vel_m_s = match &self.prev_solution {
+                Some((_, prev_sol)) => {
+                    let pos_vel_m = prev_sol.state.to_cartesian_pos_vel() * 1.0E3;
+                    Vector3::new(pos_vel_m[3], pos_vel_m[4], pos_vel_m[5])
+                },
+                None => Vector3::zeros(),
+            };
+            Self::doppler_screening(
+                &self.cfg,
+                Vector3::new(x0, y0, z0),
+                rx_vel_m_s,
+                max_mismatch_m_s,
+                pool,
+            );
+        }
 
         // Apply models
-        for cd in &mut pool {
+        let iono_grid = self.iono_grid.as_ref();
+        let bds2_code_bias = self.cfg.modeling.bds2_code_bias;
+        update_candidates(pool, |cd| {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("candidate", sv = %cd.sv, stage = "models").entered();
+
             if let Some((el_deg, az_deg)) = cd.attitude() {
+                if bds2_code_bias {
+                    cd.apply_bds2_code_bias(el_deg);
+                }
                 cd.apply_models(
                     method,
                     tropo_modeling,
@@ -530,13 +1351,16 @@ impl<O: OrbitSource> Solver<O> {
                     el_deg,
                     (rx_lat_deg, rx_long_deg, rx_alt_m),
                     rx_rad,
-                )?;
+                    iono_grid,
+                )
+                .map_err(|e| Error::new(e).with_sv(cd.sv))?;
             }
-        }
+            Ok(())
+        })?;
 
         // Resolve ambiguities
         let ambiguities = if method == Method::PPP {
-            self.ambiguity.resolve(&pool)
+            self.ambiguity.resolve(pool)
         } else {
             Default::default()
         };
@@ -572,7 +1396,30 @@ impl<O: OrbitSource> Solver<O> {
         });
 
         if pool.len() < min_required {
-            return Err(Error::NotEnoughPostFitCandidates);
+            return Err(ErrorKind::NotEnoughPostFitCandidates.into());
+        }
+
+        // constellation consistency, from all candidates in sight
+        if let Some(monitor) = self.cfg.solver.constellation_monitor {
+            let rx_m = match &self.prev_solution {
+                Some((_, prev_sol)) => {
+                    let pos_vel_m = prev_sol.state.to_cartesian_pos_vel() * 1.0E3;
+                    (pos_vel_m[0], pos_vel_m[1], pos_vel_m[2])
+                },
+                None => (x0, y0, z0),
+            };
+            let biases = Self::constellation_biases(pool, rx_m);
+            self.health.update(&monitor, &biases);
+            pool.retain(|cd| {
+                let retained = !self.health.is_faulty(cd.sv.constellation);
+                if !retained {
+                    debug!("{}({}) - rejected (faulty constellation)", cd.t, cd.sv);
+                }
+                retained
+            });
+            if pool.len() < min_required {
+                return Err(ErrorKind::NotEnoughPostFitCandidates.into());
+            }
         }
 
         let rx_orbit = if let Some((_, prev_sol)) = &self.prev_solution {
@@ -581,9 +1428,31 @@ impl<O: OrbitSource> Solver<O> {
             self.initial.unwrap()
         };
 
-        Self::retain_best_elevation(&mut pool, min_required);
+        // GGTO measurement, from all candidates in sight
+        if let Some(ggto) = ggto {
+            let rx_m = match &self.prev_solution {
+                Some((_, prev_sol)) => {
+                    let pos_vel_m = prev_sol.state.to_cartesian_pos_vel() * 1.0E3;
+                    (pos_vel_m[0], pos_vel_m[1], pos_vel_m[2])
+                },
+                None => (x0, y0, z0),
+            };
+            if let Some((z_s, sigma_s)) = Self::ggto_measurement(pool, rx_m, ggto) {
+                debug!("{} ggto measurement: {:.3E}s", t, z_s);
+                self.ggto.update(t, z_s, sigma_s);
+            }
+        }
+
+        // position held fixed: the 4 best vehicles (at most) contribute to the clock estimate,
+        // the navigation rows being limited to 4 vehicles
+        let retained = match self.cfg.sol_type {
+            PVTSolutionType::FixedPosition => pool.len().min(4),
+            _ => min_required,
+        };
 
-        pool.sort_by(|cd_a, cd_b| cd_a.sv.prn.partial_cmp(&cd_b.sv.prn).unwrap());
+        Self::retain_best_elevation(pool, retained);
+
+        pool.sort_by(|cd_a, cd_b| cd_a.sv.cmp(&cd_b.sv));
 
         let w = self.cfg.solver.weight_matrix(); //sv.values().map(|sv| sv.elevation).collect());
                                                  // // Reduce contribution of newer (rising) vehicles (rising)
@@ -594,14 +1463,39 @@ impl<O: OrbitSource> Solver<O> {
                                                  //     }
                                                  // }
 
-        let input = match NavigationInput::new((x0, y0, z0), &self.cfg, &pool, w, &ambiguities) {
+        // linearize about the antenna position: the solution
+        // remains expressed at the reference point
+        let lever_arm = self.antenna_offset_ecef(rx_rad);
+        let apc = (x0 + lever_arm[0], y0 + lever_arm[1], z0 + lever_arm[2]);
+
+        self.perf_mark(Stage::Modeling);
+
+        let mut input = match NavigationInput::new(
+            apc,
+            &self.cfg,
+            pool,
+            w,
+            &ambiguities,
+            &mut self.workspace,
+        ) {
             Ok(input) => input,
             Err(e) => {
                 error!("Failed to form navigation matrix: {}", e);
-                return Err(Error::MatrixFormationError);
+                return Err(e);
             },
         };
 
+        input.prior_information = self.apriori_information(Vector3::new(x0, y0, z0));
+        input.constraint_information = sum_information(
+            sum_information(
+                self.height_information(rx_rad, rx_alt_m),
+                self.clock_information(t),
+            ),
+            self.baseline_information(t, Vector3::new(x0, y0, z0)),
+        );
+
+        self.perf_mark(Stage::MatrixBuild);
+
         // self.prev_used = pool.iter().map(|cd| cd.sv).collect::<Vec<_>>();
 
         // Regular Iteration
@@ -609,10 +1503,12 @@ impl<O: OrbitSource> Solver<O> {
             Ok(output) => output,
             Err(e) => {
                 error!("Failed to resolve: {}", e);
-                return Err(Error::NavigationError);
+                return Err(ErrorKind::NavigationError.into());
             },
         };
 
+        self.perf_mark(Stage::Solve);
+
         let sol_x = output.state.estimate();
         debug!("x: {}", sol_x);
 
@@ -624,7 +1520,7 @@ impl<O: OrbitSource> Solver<O> {
         //if method == Method::PPP {
         //    for i in 0..x.ncols() - 4 {
         //        let b_i = x[i + 4];
-        //        let cd = &pool[i];
+        //        let cd = pool[i];
         //        if let Some(l_c) = cd.phase_if_combination() {
         //            if let Some(amb) = ambiguities.get(&(cd.sv, l_c.reference)) {
         //                //TODO: c'est n_c pas n_1, puisque b_i est lié à la combinaison LC
@@ -643,44 +1539,62 @@ impl<O: OrbitSource> Solver<O> {
                 t,
                 self.earth_cef,
             ),
+            frame: self.cfg.orbit_frame,
             ambiguities,
-            gdop: output.gdop,
-            tdop: output.tdop,
-            pdop: output.pdop,
             sv: input.sv.clone(),
             q: output.q_covar4x4(),
             timescale: self.cfg.timescale,
             dt: Duration::from_seconds(sol_dt),
             d_dt: 0.0_f64,
+            holdover: None,
+            ggto: ggto.and_then(|_| self.ggto.value()),
         };
 
-        let (lat, long, alt_km) = solution.state.latlongalt().map_err(|e| Error::Physics(e))?;
+        let (lat, long, alt_km) = solution.state.latlongalt().map_err(ErrorKind::Physics)?;
 
         debug!(
-            "{} new solution lat={:.5}°, long={:.5}°, alt={:.5}m",
+            "{} new solution lat={:.5}°, long={:.5}°, alt={:.5}m, dt={:.3E}s (gdop={:.2}, pdop={:.2}, {} SV)",
             t,
             lat,
             long,
             alt_km * 1.0E3,
+            sol_dt,
+            output.gdop(),
+            output.pdop(),
+            solution.sv.len(),
         );
 
         // First solution
         if self.prev_solution.is_none() {
             self.prev_solution = Some((t, solution.clone()));
             // always discard 1st solution
-            return Err(Error::InvalidatedSolution(InvalidationCause::FirstSolution));
+            return Err(ErrorKind::InvalidatedSolution(InvalidationCause::FirstSolution).into());
         }
 
-        let validator =
-            SolutionValidator::new(Vector3::<f64>::new(x0, y0, z0), &pool, &input, &output);
+        let validator = SolutionValidator::new(
+            Vector3::<f64>::new(apc.0, apc.1, apc.2),
+            pool,
+            &input,
+            &output,
+        );
 
         match validator.validate(&self.cfg) {
             Ok(_) => {
                 self.nav.validate();
+                for (cd, residual_m) in pool.iter().zip(validator.residuals().iter()) {
+                    if let Some(sv) = solution.sv.get_mut(&cd.sv) {
+                        sv.residual_m = Some(*residual_m);
+                    }
+                }
+                if let Some(batch) = &mut self.static_batch {
+                    if !input.clock_only {
+                        batch.push(t, Vector3::new(x0, y0, z0), &input);
+                    }
+                }
             },
             Err(cause) => {
-                error!("solution invalidated - {}", cause);
-                return Err(Error::InvalidatedSolution(cause));
+                error!("{} - solution invalidated: {}", t, cause);
+                return Err(ErrorKind::InvalidatedSolution(cause).into());
             },
         };
 
@@ -705,17 +1619,35 @@ impl<O: OrbitSource> Solver<O> {
 
         // update & store for next time
         self.update_solution(t, &mut solution);
+        self.fuse_aiding(t, &mut solution);
         self.prev_solution = Some((t, solution.clone()));
-
-        Self::rework_solution(t, self.earth_cef, &self.cfg, &mut solution);
+        self.prev_validated = true;
+
+        let t = Self::rework_solution(
+            t_in,
+            self.earth_cef,
+            &self.cfg,
+            &mut self.time,
+            &mut solution,
+        );
+        self.perf_mark(Stage::Validation);
         Ok((t, solution))
     }
+    /// Marks the end of this processing [Stage], when [PerfCounters] are enabled
+    fn perf_mark(&mut self, _stage: Stage) {
+        #[cfg(feature = "std")]
+        if let Some(perf) = &mut self.perf {
+            perf.mark(_stage);
+        }
+    }
 
     fn fix_sv_states(&mut self, rx_orbit: Orbit, pool: &mut Vec<Candidate>) -> Result<(), Error> {
         // clear loss of sight
-        let svnn = pool.iter().map(|cd| cd.sv).collect::<Vec<_>>();
+        let svnn = &mut self.workspace.sv;
+        svnn.clear();
+        svnn.extend(pool.iter().map(|cd| cd.sv));
         self.sv_orbits.retain(|sv, _| {
-            let retain = svnn.contains(&sv);
+            let retain = svnn.contains(sv);
             if !retain {
                 debug!("{} loss of sight", sv);
             }
@@ -723,34 +1655,36 @@ impl<O: OrbitSource> Solver<O> {
         });
 
         for cd in pool.iter_mut() {
+            #[cfg(feature = "tracing")]
+            let _span = tracing::debug_span!("candidate", sv = %cd.sv, stage = "states").entered();
+
             if let Some(orbit) = &mut cd.orbit {
-                // velocities
-                if let Some(past_orbit) = self.sv_orbits.get(&cd.sv) {
-                    let dt_s = (orbit.epoch - past_orbit.epoch).to_seconds();
-                    let current = orbit.to_cartesian_pos_vel();
-                    let past = past_orbit.to_cartesian_pos_vel();
-                    let der = (
-                        (current[0] - past[0]) / dt_s,
-                        (current[1] - past[1]) / dt_s,
-                        (current[2] - past[2]) / dt_s,
-                    );
-                    *orbit = orbit.with_velocity_km_s(Vector3::new(der.0, der.1, der.2));
+                // velocities: derived from the past state, when the provider did not define them
+                if orbit.vmag_km_s() == 0.0 {
+                    if let Some(past_orbit) = self.sv_orbits.get(&cd.sv) {
+                        let dt_s = (orbit.epoch - past_orbit.epoch).to_seconds();
+                        let current = orbit.to_cartesian_pos_vel();
+                        let past = past_orbit.to_cartesian_pos_vel();
+                        let der = (
+                            (current[0] - past[0]) / dt_s,
+                            (current[1] - past[1]) / dt_s,
+                            (current[2] - past[2]) / dt_s,
+                        );
+                        *orbit = orbit.with_velocity_km_s(Vector3::new(der.0, der.1, der.2));
+                    }
                 }
                 // clock
                 if orbit.vmag_km_s() > 0.0 {
                     if self.cfg.modeling.relativistic_clock_bias {
                         if let Some(clock_corr) = &mut cd.clock_corr {
                             if clock_corr.needs_relativistic_correction {
-                                let w_e = Constants::EARTH_SEMI_MAJOR_AXIS_WGS84;
-                                let mu = Constants::EARTH_GRAVITATION;
-                                let ea_deg = orbit.ea_deg().map_err(Error::Physics)?;
-                                let ea_rad = ea_deg.to_radians();
-                                let gm = (w_e * mu).sqrt();
-                                let ecc = orbit.ecc().map_err(Error::Physics)?;
-                                let bias = -2.0_f64 * ecc * ea_rad.sin() * gm
-                                    / SPEED_OF_LIGHT_M_S
-                                    / SPEED_OF_LIGHT_M_S
-                                    * Unit::Second;
+                                // -2 (r.v) / c², invariant by Earth rotation
+                                let state = orbit.to_cartesian_pos_vel() * 1.0E3;
+                                let r_dot_v =
+                                    state[0] * state[3] + state[1] * state[4] + state[2] * state[5];
+                                let bias =
+                                    -2.0_f64 * r_dot_v / SPEED_OF_LIGHT_M_S / SPEED_OF_LIGHT_M_S
+                                        * Unit::Second;
                                 debug!("{} ({}) : relativistic clock bias: {}", cd.t, cd.sv, bias);
                                 clock_corr.duration += bias;
                             }
@@ -758,54 +1692,221 @@ impl<O: OrbitSource> Solver<O> {
                     } //clockbias
                 } //velocity
 
-                let rx_orbit = Orbit::from_cartesian_pos_vel(
-                    rx_orbit.to_cartesian_pos_vel(),
-                    cd.t,
-                    self.earth_cef,
-                );
+                self.sv_orbits.insert(cd.sv, *orbit);
+            } //has_orbit
+        }
 
-                let elazrg = self
-                    .almanac
-                    .azimuth_elevation_range_sez(*orbit, rx_orbit, None, None)
-                    .map_err(|e| Error::Almanac(e))?;
+        // elevation & azimuth
+        let (almanac, earth_cef) = (&self.almanac, self.earth_cef);
+        update_candidates(pool, |cd| {
+            if let Some(orbit) = cd.orbit {
+                let rx_orbit =
+                    Orbit::from_cartesian_pos_vel(rx_orbit.to_cartesian_pos_vel(), cd.t, earth_cef);
+
+                let elazrg = almanac
+                    .azimuth_elevation_range_sez(orbit, rx_orbit, None, None)
+                    .map_err(|e| Error::new(ErrorKind::Almanac(e)).with_sv(cd.sv))?;
 
                 cd.azimuth_deg = Some(elazrg.azimuth_deg);
                 cd.elevation_deg = Some(elazrg.elevation_deg);
-                self.sv_orbits.insert(cd.sv, *orbit);
-            } //has_orbit
-        }
-        Ok(())
+            }
+            Ok(())
+        })
     }
-    fn sv_state_filter(cfg: &Config, pool: &mut Vec<Candidate>) {
-        let min_elev_deg = cfg.min_sv_elev.unwrap_or(0.0);
-        let min_azim_deg = cfg.min_sv_azim.unwrap_or(0.0);
-        let max_azim_deg = cfg.max_sv_azim.unwrap_or(360.0);
+    /// Discards the [Candidate]s whose Doppler observation does not match the
+    /// range rate predicted from their velocity, by more than `max_mismatch_m_s`
+    /// (or 3 Doppler standard deviations of their signal, if larger).
+    /// The receiver clock drift, common to all vehicles, is estimated as the mean
+    /// mismatch weighted by the Doppler noise, around the median mismatch,
+    /// so at least 3 vehicles with known velocity and Doppler are required.
+    fn doppler_screening(
+        cfg: &Config,
+        rx_pos_m: Vector3<f64>,
+        rx_vel_m_s: Vector3<f64>,
+        max_mismatch_m_s: f64,
+        pool: &mut Vec<Candidate>,
+    ) {
+        let mismatches = pool
+            .iter()
+            .map(|cd| {
+                cd.range_rate_mismatch_m_s(rx_pos_m, rx_vel_m_s)
+                    .map(|mismatch_m_s| (mismatch_m_s, cd.doppler_sigma(cfg)))
+            })
+            .collect::<Vec<_>>();
+
+        let mut sorted = mismatches
+            .iter()
+            .flatten()
+            .map(|(mismatch_m_s, _)| *mismatch_m_s)
+            .collect::<Vec<_>>();
+        if sorted.len() < 3 {
+            return;
+        }
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let median_m_s = sorted[sorted.len() / 2];
+
+        // weighted mean, over the vehicles that agree with the median
+        let (sum_w, sum_wx) = mismatches
+            .iter()
+            .flatten()
+            .filter(|(mismatch_m_s, _)| (mismatch_m_s - median_m_s).abs() <= max_mismatch_m_s)
+            .fold(
+                (0.0_f64, 0.0_f64),
+                |(sum_w, sum_wx), (mismatch_m_s, sigma_m_s)| {
+                    let w = 1.0 / sigma_m_s.powi(2);
+                    (sum_w + w, sum_wx + w * mismatch_m_s)
+                },
+            );
+        let drift_m_s = sum_wx / sum_w;
+
+        let mut mismatches = mismatches.into_iter();
         pool.retain(|cd| {
-            if let Some((elev, azim)) = cd.attitude() {
-                if elev < min_elev_deg {
-                    debug!("{}({}) - rejected (below elevation mask)", cd.t, cd.sv);
-                    false
-                } else if azim < min_azim_deg {
-                    debug!("{}({}) - rejected (below azimuth mask)", cd.t, cd.sv);
-                    false
-                } else if azim > max_azim_deg {
-                    debug!("{}({}) - rejected (above azimuth mask)", cd.t, cd.sv);
-                    false
-                } else {
-                    debug!("{}({}) - elev={:.3}° azim={:.3}°", cd.t, cd.sv, elev, azim);
-                    true
-                }
+            let Some(Some((mismatch_m_s, sigma_m_s))) = mismatches.next() else {
+                return true;
+            };
+            let residual_m_s = mismatch_m_s - drift_m_s;
+            if residual_m_s.abs() > max_mismatch_m_s.max(3.0 * sigma_m_s) {
+                error!(
+                    "{}({}) - rejected (doppler mismatch {:.3}[m/s]): verify the ephemerides",
+                    cd.t, cd.sv, residual_m_s
+                );
+                false
             } else {
+                debug!(
+                    "{}({}) - doppler mismatch {:.3}[m/s]",
+                    cd.t, cd.sv, residual_m_s
+                );
                 true
             }
         });
     }
+    /// Returns the prior information (soft constraint) of the [AprioriPosition]
+    /// covariance, for a state linearized about this position [m]
+    fn apriori_information(
+        &self,
+        linearization_m: Vector3<f64>,
+    ) -> Option<(OMatrix<f64, U8, U8>, OVector<f64, U8>)> {
+        let apriori = self.apriori?;
+        let covariance = Matrix3::from_row_slice(&apriori.covariance_m2?.concat());
+        let Some(info_3x3) = covariance.try_inverse() else {
+            warn!("singular apriori covariance: position constraint ignored");
+            return None;
+        };
+        let (x, y, z) = apriori.ecef_m;
+        let dx = Vector3::new(x, y, z) - linearization_m;
+
+        let mut info = OMatrix::<f64, U8, U8>::zeros();
+        info.fixed_view_mut::<3, 3>(0, 0).copy_from(&info_3x3);
+        let mut info_x = OVector::<f64, U8>::zeros();
+        info_x.fixed_rows_mut::<3>(0).copy_from(&(info_3x3 * dx));
+        Some((info, info_x))
+    }
+    /// Returns the information (pseudo-observation) of the height constraint, for a state
+    /// linearized about this position: (latitude, longitude) [rad], altitude [m]
+    fn height_information(
+        &self,
+        rx_rad: (f64, f64),
+        rx_alt_m: f64,
+    ) -> Option<(OMatrix<f64, U8, U8>, OVector<f64, U8>)> {
+        if self.cfg.fixed_altitude.is_some() {
+            return None;
+        }
+        let constraint = self.cfg.height_constraint?;
+        let (lat_rad, long_rad) = rx_rad;
+        let up = Vector3::new(
+            lat_rad.cos() * long_rad.cos(),
+            lat_rad.cos() * long_rad.sin(),
+            lat_rad.sin(),
+        );
+        let weight = constraint.sigma_m.powi(-2);
+
+        let mut info = OMatrix::<f64, U8, U8>::zeros();
+        info.fixed_view_mut::<3, 3>(0, 0)
+            .copy_from(&(up * up.transpose() * weight));
+        let mut info_x = OVector::<f64, U8>::zeros();
+        info_x
+            .fixed_rows_mut::<3>(0)
+            .copy_from(&(up * (constraint.altitude_m - rx_alt_m) * weight));
+        Some((info, info_x))
+    }
+    /// Returns the information (pseudo-observation) of the clock constraint at [Epoch]:
+    /// the clock offset predicted from the previous solution and the clock drift,
+    /// with an uncertainty that grows with the elapsed time.
+    fn clock_information(&self, t: Epoch) -> Option<(OMatrix<f64, U8, U8>, OVector<f64, U8>)> {
+        let constraint = self.cfg.clock_constraint?;
+        let (prev_t, prev_sol) = self.prev_solution.as_ref()?;
+        let dt_s = (t - *prev_t).to_seconds();
+        if dt_s <= 0.0 {
+            return None;
+        }
+        let drift_s_s = constraint.drift_s_s.unwrap_or(prev_sol.d_dt);
+        let predicted_m = (prev_sol.dt.to_seconds() + drift_s_s * dt_s) * SPEED_OF_LIGHT_M_S;
+        let sigma_m = constraint.drift_sigma_s_s * dt_s * SPEED_OF_LIGHT_M_S;
+        if sigma_m <= 0.0 {
+            return None;
+        }
+        let weight = sigma_m.powi(-2);
+
+        let mut info = OMatrix::<f64, U8, U8>::zeros();
+        let mut info_x = OVector::<f64, U8>::zeros();
+        info[(3, 3)] = weight;
+        info_x[3] = predicted_m * weight;
+        Some((info, info_x))
+    }
+    /// Returns the information (pseudo-observation) of the baseline length constraint at [Epoch],
+    /// for a state linearized about this position [m]. The baseline length is linearized
+    /// about the previous solution, when it exists.
+    fn baseline_information(
+        &self,
+        t: Epoch,
+        linearization_m: Vector3<f64>,
+    ) -> Option<(OMatrix<f64, U8, U8>, OVector<f64, U8>)> {
+        let constraint = self.cfg.baseline_length?;
+        let (x, y, z) = self.cfg.remote_site_ecef_m(t)?;
+        let position_m = match &self.prev_solution {
+            Some((_, prev_sol)) => {
+                let pos_vel_m = prev_sol.state.to_cartesian_pos_vel() * 1.0E3;
+                Vector3::new(pos_vel_m[0], pos_vel_m[1], pos_vel_m[2])
+            },
+            None => linearization_m,
+        };
+        let baseline_m = position_m - Vector3::new(x, y, z);
+        let length_m = baseline_m.norm();
+        if length_m == 0.0 {
+            return None;
+        }
+        let u = baseline_m / length_m;
+        let weight = constraint.sigma_m.powi(-2);
+        let z_m = constraint.length_m - length_m + u.dot(&(position_m - linearization_m));
+
+        let mut info = OMatrix::<f64, U8, U8>::zeros();
+        info.fixed_view_mut::<3, 3>(0, 0)
+            .copy_from(&(u * u.transpose() * weight));
+        let mut info_x = OVector::<f64, U8>::zeros();
+        info_x.fixed_rows_mut::<3>(0).copy_from(&(u * z_m * weight));
+        Some((info, info_x))
+    }
+    /// Returns the offset from the reference point to the antenna, in ECEF [m],
+    /// at (latitude, longitude) in radians.
+    fn antenna_offset_ecef(&self, rx_rad: (f64, f64)) -> Vector3<f64> {
+        let mut enu = Vector3::<f64>::zeros();
+        if let Some((e, n, u)) = self.cfg.arp_enu {
+            enu += Vector3::new(e, n, u);
+        }
+        if let Some((x, y, z)) = self.cfg.lever_arm_body {
+            match self.attitude {
+                Some(attitude) => enu += attitude.body_to_enu(Vector3::new(x, y, z)),
+                None => warn!("lever arm compensation requires attitude information"),
+            }
+        }
+        enu_to_ecef(enu, rx_rad.0, rx_rad.1)
+    }
     fn min_sv_required(&self) -> usize {
         if self.initial.is_none() {
             4
         } else {
             match self.cfg.sol_type {
-                PVTSolutionType::TimeOnly => 1,
+                PVTSolutionType::TimeOnly | PVTSolutionType::FixedPosition => 1,
                 _ => {
                     if self.cfg.fixed_altitude.is_some() {
                         3
@@ -816,6 +1917,38 @@ impl<O: OrbitSource> Solver<O> {
             }
         }
     }
+    /// Iterative light-time solution: refines the transmission [Epoch] `t_tx` (pseudo range
+    /// based) from the geometric range between the SV and the apriori receiver position [m],
+    /// `t_rx` being the reception [Epoch] corrected by the apriori receiver clock offset.
+    /// Returns the transmission [Epoch] and the propagation delay.
+    #[allow(clippy::too_many_arguments)]
+    fn light_time_iteration(
+        orbits: &mut O,
+        sv: SV,
+        t_rx: Epoch,
+        mut t_tx: Epoch,
+        rx_m: Vector3<f64>,
+        frame: Frame,
+        interpolation: Interpolation,
+        earth_rotation: bool,
+    ) -> (Epoch, Duration) {
+        for _ in 0..LIGHT_TIME_MAX_ITERATIONS {
+            let Some(tx_orbit) = orbits.next_at(t_tx, sv, frame, interpolation) else {
+                break;
+            };
+            let sv_orbit =
+                Self::rotate_orbit_dcm3x3(t_rx, t_rx - t_tx, tx_orbit, earth_rotation, frame);
+            let sv_m = sv_orbit.radius_km * 1.0E3;
+            let next_t_tx =
+                t_rx - Duration::from_seconds((sv_m - rx_m).norm() / SPEED_OF_LIGHT_M_S);
+            let converged = (next_t_tx - t_tx).abs() <= LIGHT_TIME_CONVERGENCE;
+            t_tx = next_t_tx;
+            if converged {
+                break;
+            }
+        }
+        (t_tx, t_rx - t_tx)
+    }
     fn rotate_orbit_dcm3x3(
         t: Epoch,
         dt: Duration,
@@ -830,22 +1963,18 @@ impl<O: OrbitSource> Solver<O> {
         } else {
             Matrix3::new(1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0)
         };
-        let state = orbit.to_cartesian_pos_vel() * 1.0E3;
-        let position = Vector3::new(state[0], state[1], state[2]);
-        let position = dcm3 * position;
-        Orbit::from_position(
-            position[0] / 1.0E3,
-            position[1] / 1.0E3,
-            position[2] / 1.0E3,
-            t,
-            frame,
-        )
+        let state = orbit.to_cartesian_pos_vel();
+        // the SV velocity rotates along with the position
+        let position = dcm3 * Vector3::new(state[0], state[1], state[2]);
+        let velocity = dcm3 * Vector3::new(state[3], state[4], state[5]);
+        Orbit::from_position(position[0], position[1], position[2], t, frame)
+            .with_velocity_km_s(velocity)
     }
     fn update_solution(&self, t: Epoch, sol: &mut PVTSolution) {
         if let Some((prev_t, prev_sol)) = &self.prev_solution {
             let dt_s = (t - *prev_t).to_seconds();
             // update clock drift
-            sol.d_dt = dt_s;
+            sol.d_dt = (sol.dt - prev_sol.dt).to_seconds() / dt_s;
             // update velocity
             sol.state = Self::update_velocity(sol.state, prev_sol.state, dt_s);
         }
@@ -861,22 +1990,154 @@ impl<O: OrbitSource> Solver<O> {
             (z - p_z) / dt_sec,
         ))
     }
-    fn rework_solution(t: Epoch, frame: Frame, cfg: &Config, pvt: &mut PVTSolution) {
+    /// Reworks the [PVTSolution] prior publication.
+    /// Returns its [Epoch] expressed in desired [TimeScale].
+    fn rework_solution(
+        t: Epoch,
+        frame: Frame,
+        cfg: &Config,
+        time: &mut Option<Box<dyn TimeSource + Send>>,
+        pvt: &mut PVTSolution,
+    ) -> Epoch {
+        // express in desired timescale
+        let t_ts = t.to_time_scale(cfg.timescale);
+        let correction = if t.time_scale == cfg.timescale {
+            Duration::ZERO
+        } else {
+            time.as_mut()
+                .and_then(|time| time.next_time_offset_at(t, t.time_scale, cfg.timescale))
+                .unwrap_or_else(|| clock_offset_correction(t, t.time_scale, cfg.timescale))
+        };
+        pvt.dt += correction;
+        pvt.timescale = cfg.timescale;
+        let t_ts = cfg.timestamping.apply(t_ts, pvt.dt);
+        pvt.state.epoch = t_ts;
+
+        // express in desired reference frame
+        if let Some(output_frame) = cfg.output_frame {
+            if pvt.frame != output_frame {
+                let pos_vel = pvt.state.to_cartesian_pos_vel() * 1.0E3;
+                let (x, y, z) =
+                    pvt.frame
+                        .transform(output_frame, (pos_vel[0], pos_vel[1], pos_vel[2]), t_ts);
+                let pos_vel = Vector6::new(x, y, z, pos_vel[3], pos_vel[4], pos_vel[5]) / 1.0E3;
+                pvt.state = Orbit::from_cartesian_pos_vel(pos_vel, t_ts, frame);
+                pvt.frame = output_frame;
+            }
+        }
+        if let Some(datum) = cfg.datum {
+            let pos_vel = pvt.state.to_cartesian_pos_vel() * 1.0E3;
+            let (x, y, z) = datum
+                .helmert
+                .apply((pos_vel[0], pos_vel[1], pos_vel[2]), t_ts);
+            let pos_vel = Vector6::new(x, y, z, pos_vel[3], pos_vel[4], pos_vel[5]) / 1.0E3;
+            pvt.state = Orbit::from_cartesian_pos_vel(pos_vel, t_ts, frame);
+        }
+
         // emphazise we only resolve dt by setting null attitude
         if cfg.sol_type == PVTSolutionType::TimeOnly {
-            pvt.state = Orbit::zero_at_epoch(t, frame);
+            pvt.state = Orbit::zero_at_epoch(t_ts, frame);
         }
         // TODO:
         //  1. replace height component with user input
         //  2. static in altitude: needs to reflect on velocity
         // to emphasize that it is being used
         if let Some(_alt_m) = cfg.fixed_altitude {}
+        t_ts
+    }
+    /// Measures the GPS to Galileo time offset (GST - GPST) [s], from the difference
+    /// between the mean Galileo and GPS (or QZSS) pseudo range residuals at this receiver position [m].
+    /// `ggto` is the offset already applied to the Galileo clocks.
+    /// Returns the measurement and its standard deviation [s].
+    fn ggto_measurement(
+        pool: &[Candidate],
+        rx_m: (f64, f64, f64),
+        ggto: Duration,
+    ) -> Option<(f64, f64)> {
+        let (mut gps, mut gal) = ((0, 0.0, 0.0), (0, 0.0, 0.0));
+        for cd in pool.iter() {
+            let stats = match cd.sv.constellation {
+                Constellation::GPS | Constellation::QZSS => &mut gps,
+                Constellation::Galileo => &mut gal,
+                _ => continue,
+            };
+            let Some(res) = Self::prefit_residual_m(cd, rx_m) else {
+                continue;
+            };
+            stats.0 += 1;
+            stats.1 += res;
+            stats.2 += res * res;
+        }
+
+        if gps.0 < 2 || gal.0 < 2 {
+            return None;
+        }
+
+        let (mean_gps, mean_gal) = (gps.1 / gps.0 as f64, gal.1 / gal.0 as f64);
+        let var_gps = (gps.2 / gps.0 as f64 - mean_gps.powi(2)).max(1.0);
+        let var_gal = (gal.2 / gal.0 as f64 - mean_gal.powi(2)).max(1.0);
+        let sigma_m = (var_gps / gps.0 as f64 + var_gal / gal.0 as f64).sqrt();
+
+        let z_s = ggto.to_seconds() - (mean_gal - mean_gps) / SPEED_OF_LIGHT_M_S;
+        Some((z_s, sigma_m / SPEED_OF_LIGHT_M_S))
+    }
+    /// Returns the pseudo range residual [m] of this [Candidate], at this receiver position [m]
+    /// (receiver clock offset included).
+    fn prefit_residual_m(cd: &Candidate, rx_m: (f64, f64, f64)) -> Option<f64> {
+        let (Some(pr), Some(orbit), Some(clock_corr)) = (
+            cd.prefered_pseudorange().and_then(|obs| obs.pseudo),
+            cd.orbit,
+            cd.clock_corr,
+        ) else {
+            return None;
+        };
+        let rho = ((orbit.radius_km.x * 1.0E3 - rx_m.0).powi(2)
+            + (orbit.radius_km.y * 1.0E3 - rx_m.1).powi(2)
+            + (orbit.radius_km.z * 1.0E3 - rx_m.2).powi(2))
+        .sqrt();
+        Some(
+            pr - rho + clock_corr.duration.to_seconds() * SPEED_OF_LIGHT_M_S
+                - cd.tropo_bias
+                - cd.iono_bias,
+        )
+    }
+    /// Returns the systematic bias [m] of each [Constellation] in sight: mean residual
+    /// at this receiver position [m], relative to the median residual of all vehicles
+    /// (receiver clock offset). Requires two vehicles per constellation, and two constellations.
+    fn constellation_biases(
+        pool: &[Candidate],
+        rx_m: (f64, f64, f64),
+    ) -> Vec<(Constellation, f64)> {
+        let mut residuals = BTreeMap::<Constellation, Vec<f64>>::new();
+        for cd in pool.iter() {
+            if let Some(res) = Self::prefit_residual_m(cd, rx_m) {
+                residuals.entry(cd.sv.constellation).or_default().push(res);
+            }
+        }
+        residuals.retain(|_, res| res.len() > 1);
+        if residuals.len() < 2 {
+            return Vec::new();
+        }
+
+        let mut all = residuals.values().flatten().copied().collect::<Vec<_>>();
+        all.sort_by(|a, b| a.total_cmp(b));
+        let median = all[all.len() / 2];
+
+        residuals
+            .iter()
+            .map(|(constellation, res)| {
+                let mean = res.iter().sum::<f64>() / res.len() as f64;
+                (*constellation, mean - median)
+            })
+            .collect()
     }
     fn retain_best_elevation(pool: &mut Vec<Candidate>, min_required: usize) {
         pool.sort_by(|cd_a, cd_b| {
             let elev_a_deg = cd_a.elevation_deg.unwrap_or_default();
             let elev_b_deg = cd_b.elevation_deg.unwrap_or_default();
-            elev_a_deg.partial_cmp(&elev_b_deg).unwrap()
+            elev_a_deg
+                .total_cmp(&elev_b_deg)
+                .then_with(|| cd_a.sv.cmp(&cd_b.sv))
         });
 
         let mut index = 0;
@@ -930,3 +2191,230 @@ impl<O: OrbitSource> Solver<O> {
 //         }
 //     }
 // }
+
+#[cfg(test)]
+mod light_time {
+    use super::Solver;
+    use crate::{
+        constants::SPEED_OF_LIGHT_M_S,
+        prelude::{
+            Duration, Epoch, Frame, Interpolation, Orbit, OrbitSource, Vector3, EARTH_ITRF93, SV,
+        },
+    };
+    use std::str::FromStr;
+
+    /// Vehicle moving along the X axis at 3 km/s
+    struct Moving {
+        t0: Epoch,
+    }
+
+    impl OrbitSource for Moving {
+        fn next_at(&mut self, t: Epoch, _: SV, fr: Frame, _: Interpolation) -> Option<Orbit> {
+            let dt_s = (t - self.t0).to_seconds();
+            Some(Orbit::from_position(
+                15_000.0 + 3.0 * dt_s,
+                10_000.0,
+                18_000.0,
+                t,
+                fr,
+            ))
+        }
+    }
+
+    #[test]
+    fn light_time_iteration() {
+        let t_rx = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let sv = SV::from_str("G01").unwrap();
+        let rx_m = Vector3::new(4_696_989.0, 723_994.0, 4_239_678.0);
+        let mut orbits = Moving { t0: t_rx };
+
+        for earth_rotation in [false, true] {
+            // pseudo range based guess, 1 ms off (receiver clock)
+            let guess = t_rx - Duration::from_milliseconds(71.0);
+            let (t_tx, dt_tx) = Solver::<Moving>::light_time_iteration(
+                &mut orbits,
+                sv,
+                t_rx,
+                guess,
+                rx_m,
+                EARTH_ITRF93,
+                Interpolation::default(),
+                earth_rotation,
+            );
+            assert_eq!(t_tx + dt_tx, t_rx);
+
+            // light-time equation is verified (epochs have a 1 ns resolution)
+            let tx_orbit = orbits
+                .next_at(t_tx, sv, EARTH_ITRF93, Interpolation::default())
+                .unwrap();
+            let sv_orbit = Solver::<Moving>::rotate_orbit_dcm3x3(
+                t_rx,
+                dt_tx,
+                tx_orbit,
+                earth_rotation,
+                EARTH_ITRF93,
+            );
+            let range_m = (sv_orbit.radius_km * 1.0E3 - rx_m).norm();
+            let err_m = range_m - dt_tx.to_seconds() * SPEED_OF_LIGHT_M_S;
+            assert!(err_m.abs() < SPEED_OF_LIGHT_M_S * 1.0E-9, "{} m", err_m);
+        }
+    }
+}
+
+#[cfg(test)]
+mod holdover {
+    use super::Solver;
+    use crate::{
+        prelude::{Config, Duration, Epoch, SolverState},
+        tests::{self, NoOrbits},
+    };
+    use std::str::FromStr;
+
+    fn solver() -> Solver<NoOrbits> {
+        let mut cfg = Config::default();
+        cfg.solver.max_holdover = Some(Duration::from_seconds(30.0));
+        tests::solver(&cfg)
+    }
+
+    fn solver_state(t: Epoch) -> SolverState {
+        SolverState {
+            epoch: Some(t),
+            position_ecef_m: Some((4_696_500.0, 723_500.0, 4_239_500.0)),
+            clock_offset_s: Some(1.0E-3),
+            clock_drift_s_s: Some(1.0E-6),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn clock_holdover() {
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let mut solver = solver();
+
+        // nothing to propagate
+        assert!(solver.holdover(t0).is_none());
+
+        solver.restore(&solver_state(t0));
+
+        let (_, solution) = solver.holdover(t0 + Duration::from_seconds(10.0)).unwrap();
+        assert_eq!(solution.holdover, Some(Duration::from_seconds(10.0)));
+        assert!(solution.sv.is_empty());
+
+        // clock is propagated, position is held
+        let dt_s = solution.dt.to_seconds();
+        assert!((dt_s - (1.0E-3 + 10.0E-6)).abs() < 1.0E-9, "{}", dt_s);
+
+        let pos_m = solution.state.to_cartesian_pos_vel() * 1.0E3;
+        assert!((pos_m[0] - 4_696_500.0).abs() < 1.0E-6);
+        assert!((pos_m[1] - 723_500.0).abs() < 1.0E-6);
+        assert!((pos_m[2] - 4_239_500.0).abs() < 1.0E-6);
+
+        // holdover does not accumulate
+        let (_, solution) = solver.holdover(t0 + Duration::from_seconds(20.0)).unwrap();
+        let dt_s = solution.dt.to_seconds();
+        assert!((dt_s - (1.0E-3 + 20.0E-6)).abs() < 1.0E-9, "{}", dt_s);
+
+        // max_holdover expiry
+        assert!(solver.holdover(t0 + Duration::from_seconds(31.0)).is_none());
+
+        // prior to latest solution
+        assert!(solver.holdover(t0 - Duration::from_seconds(1.0)).is_none());
+    }
+
+    #[test]
+    fn no_holdover_from_first_solution() {
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let mut solver = solver();
+
+        solver.restore(&solver_state(t0));
+        assert!(solver.holdover(t0 + Duration::from_seconds(1.0)).is_some());
+
+        // first solution is stored, but discarded
+        solver.prev_validated = false;
+        assert!(solver.holdover(t0 + Duration::from_seconds(1.0)).is_none());
+    }
+}
+
+#[cfg(test)]
+mod doppler {
+    use super::Solver;
+    use crate::{
+        prelude::{
+            Candidate, Carrier, Config, Constellation, Epoch, NoiseSpec, Observation, Orbit,
+            Vector3, EARTH_ITRF93, SV,
+        },
+        tests::NoOrbits,
+    };
+    use nalgebra::Vector6;
+    use std::str::FromStr;
+
+    /// 4 vehicles, the last one showing a 0.5 m/s Doppler mismatch,
+    /// on top of a 100 m/s receiver clock drift
+    fn pool(rx_pos_m: Vector3<f64>) -> Vec<Candidate> {
+        let t = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let pos_vel_km = [
+            (15_365.952, 3_511.123, 21_357.812, 1.2, -2.1, 0.4),
+            (10_753.335, -10_639.797, 21_563.016, -0.8, 1.5, 2.0),
+            (20_000.000, 12_000.000, 12_000.000, 2.5, 0.3, -1.1),
+            (-2_000.000, 16_000.000, 20_500.000, 0.1, -1.9, 1.6),
+        ];
+        pos_vel_km
+            .iter()
+            .enumerate()
+            .map(|(i, (x, y, z, vx, vy, vz))| {
+                let orbit = Orbit::from_cartesian_pos_vel(
+                    Vector6::new(*x, *y, *z, *vx, *vy, *vz),
+                    t,
+                    EARTH_ITRF93,
+                );
+                let los = Vector3::new(*x, *y, *z) * 1.0E3 - rx_pos_m;
+                let range_rate_m_s = Vector3::new(*vx, *vy, *vz).dot(&los) * 1.0E3 / los.norm();
+                let error_m_s = if i == 3 { 0.5 } else { 0.0 };
+                let doppler = -(range_rate_m_s + 100.0 + error_m_s) / Carrier::L1.wavelength();
+
+                let sv = SV::from_str(&format!("G{:02}", i + 1)).unwrap();
+                let mut cd = Candidate::new(
+                    sv,
+                    t,
+                    vec![Observation::pseudo_range(Carrier::L1, 20.0E6, None).with_doppler(doppler)],
+                );
+                cd.set_orbit(orbit);
+                cd
+            })
+            .collect()
+    }
+
+    #[test]
+    fn doppler_screening() {
+        let rx_pos_m = Vector3::new(4_696_989.0, 723_994.0, 4_239_678.0);
+        let mut cfg = Config::default();
+
+        let mut candidates = pool(rx_pos_m);
+        Solver::<NoOrbits>::doppler_screening(
+            &cfg,
+            rx_pos_m,
+            Vector3::zeros(),
+            0.2,
+            &mut candidates,
+        );
+        assert_eq!(candidates.len(), 3);
+        assert!(candidates.iter().all(|cd| cd.sv.prn != 4));
+
+        // noisier Doppler: the mismatch is within 3 standard deviations
+        cfg.solver.noise.specs.push(NoiseSpec {
+            constellation: Some(Constellation::GPS),
+            doppler_sigma_m_s: Some(0.5),
+            ..Default::default()
+        });
+
+        let mut candidates = pool(rx_pos_m);
+        Solver::<NoOrbits>::doppler_screening(
+            &cfg,
+            rx_pos_m,
+            Vector3::zeros(),
+            0.2,
+            &mut candidates,
+        );
+        assert_eq!(candidates.len(), 4);
+    }
+}
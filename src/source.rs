@@ -0,0 +1,392 @@
+//! Data provisioning combinators.
+//! Orbits ([OrbitSource]), SV clocks ([ClockSource]) and time scales ([TimeSource])
+//! are provided by orthogonal traits, that [Fallback] merges by priority.
+use std::collections::HashMap;
+
+use crate::{
+    clock::ClockSource,
+    orbit::OrbitSource,
+    prelude::{ClockCorrection, Duration, Epoch, Frame, Interpolation, Orbit, TimeScale, SV},
+    time::TimeSource,
+};
+
+/// [Fallback] merges two data providers: the primary provider is always
+/// queried first, the secondary provider only when the primary could not answer.
+/// Nest [Fallback]s to merge more providers, by decreasing priority:
+/// `Fallback::new(precise, Fallback::new(ssr, broadcast))`.
+///
+/// [Fallback] implements [OrbitSource], [ClockSource] and [TimeSource]
+/// as long as both providers do.
+#[derive(Debug, Clone, Default)]
+pub struct Fallback<P, S> {
+    /// Primary provider
+    primary: P,
+    /// Secondary provider
+    secondary: S,
+}
+
+impl<P, S> Fallback<P, S> {
+    /// Merges `primary` and `secondary` providers, by decreasing priority.
+    pub fn new(primary: P, secondary: S) -> Self {
+        Self { primary, secondary }
+    }
+    /// Returns reference to the primary provider
+    pub fn primary(&self) -> &P {
+        &self.primary
+    }
+    /// Returns mutable reference to the primary provider
+    pub fn primary_mut(&mut self) -> &mut P {
+        &mut self.primary
+    }
+    /// Returns reference to the secondary provider
+    pub fn secondary(&self) -> &S {
+        &self.secondary
+    }
+    /// Returns mutable reference to the secondary provider
+    pub fn secondary_mut(&mut self) -> &mut S {
+        &mut self.secondary
+    }
+    /// Decomposes [Self] into (primary, secondary) providers
+    pub fn into_inner(self) -> (P, S) {
+        (self.primary, self.secondary)
+    }
+}
+
+/// Merges the validity intervals of two providers: either may answer,
+/// so the merged interval is unbounded as soon as one of them is.
+fn merged_validity(
+    primary: Option<(Epoch, Epoch)>,
+    secondary: Option<(Epoch, Epoch)>,
+) -> Option<(Epoch, Epoch)> {
+    let ((p_start, p_end), (s_start, s_end)) = (primary?, secondary?);
+    Some((p_start.min(s_start), p_end.max(s_end)))
+}
+
+impl<P: OrbitSource, S: OrbitSource> OrbitSource for Fallback<P, S> {
+    fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, interp: Interpolation) -> Option<Orbit> {
+        self.primary
+            .next_at(t, sv, fr, interp)
+            .or_else(|| self.secondary.next_at(t, sv, fr, interp))
+    }
+    fn validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        merged_validity(self.primary.validity(sv), self.secondary.validity(sv))
+    }
+}
+
+impl<P: ClockSource, S: ClockSource> ClockSource for Fallback<P, S> {
+    fn next_clock_at(
+        &mut self,
+        t: Epoch,
+        sv: SV,
+        interp: Interpolation,
+    ) -> Option<ClockCorrection> {
+        self.primary
+            .next_clock_at(t, sv, interp)
+            .or_else(|| self.secondary.next_clock_at(t, sv, interp))
+    }
+    fn clock_validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        merged_validity(
+            self.primary.clock_validity(sv),
+            self.secondary.clock_validity(sv),
+        )
+    }
+}
+
+impl<P: TimeSource, S: TimeSource> TimeSource for Fallback<P, S> {
+    fn next_time_offset_at(
+        &mut self,
+        t: Epoch,
+        from: TimeScale,
+        to: TimeScale,
+    ) -> Option<Duration> {
+        self.primary
+            .next_time_offset_at(t, from, to)
+            .or_else(|| self.secondary.next_time_offset_at(t, from, to))
+    }
+}
+
+/// [Memoized] caches the answers of the wrapped [OrbitSource] and [ClockSource],
+/// keyed by ([SV], epoch bucket), so repeated queries close to the same [Epoch]
+/// (transmission time iteration, velocity differencing) do not redo the full interpolation.
+///
+/// Within a bucket, cached [Orbit]s are propagated linearly to the requested [Epoch]
+/// using their velocity, when the provider resolves it. The bucket should therefore
+/// remain short: 1 µs by default. Only the latest buckets are retained.
+#[derive(Debug, Clone)]
+pub struct Memoized<S> {
+    /// Wrapped provider
+    inner: S,
+    /// Bucket duration, in nanoseconds
+    bucket_ns: i128,
+    /// Latest bucket queried
+    latest: i128,
+    /// Cached [Orbit]s
+    orbits: HashMap<(SV, i128), Orbit>,
+    /// Cached [ClockCorrection]s
+    clocks: HashMap<(SV, i128), ClockCorrection>,
+    /// Number of queries answered from the cache
+    hits: usize,
+    /// Number of queries forwarded to the provider
+    misses: usize,
+}
+
+impl<S> Memoized<S> {
+    /// Wraps this provider, with default 1 µs bucket
+    pub fn new(inner: S) -> Self {
+        Self {
+            inner,
+            bucket_ns: 1_000,
+            latest: i128::MIN,
+            orbits: HashMap::new(),
+            clocks: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+    /// Copies and returns [Memoized] with desired bucket [Duration]
+    pub fn with_bucket(&self, bucket: Duration) -> Self
+    where
+        S: Clone,
+    {
+        let mut s = self.clone();
+        s.bucket_ns = bucket.total_nanoseconds().max(1);
+        s.orbits.clear();
+        s.clocks.clear();
+        s
+    }
+    /// Returns number of queries answered from the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+    /// Returns number of queries forwarded to the wrapped provider
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+    /// Returns reference to the wrapped provider
+    pub fn inner(&self) -> &S {
+        &self.inner
+    }
+    /// Drops the cache and returns the wrapped provider
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+    /// Returns the bucket `t` belongs to, and drops the outdated buckets
+    fn bucket(&mut self, t: Epoch) -> i128 {
+        let t_ns = t.to_time_scale(TimeScale::TAI).duration.total_nanoseconds();
+        let bucket = t_ns.div_euclid(self.bucket_ns);
+        if bucket > self.latest {
+            self.orbits.retain(|(_, b), _| *b >= bucket - 1);
+            self.clocks.retain(|(_, b), _| *b >= bucket - 1);
+            self.latest = bucket;
+        }
+        bucket
+    }
+}
+
+impl<S: OrbitSource> OrbitSource for Memoized<S> {
+    fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, interp: Interpolation) -> Option<Orbit> {
+        let bucket = self.bucket(t);
+        if let Some(cached) = self.orbits.get(&(sv, bucket)) {
+            if cached.frame == fr {
+                self.hits += 1;
+                let dt_s = (t - cached.epoch).to_seconds();
+                let mut orbit = *cached;
+                orbit.radius_km += orbit.velocity_km_s * dt_s;
+                orbit.epoch = t;
+                return Some(orbit);
+            }
+        }
+        self.misses += 1;
+        let orbit = self.inner.next_at(t, sv, fr, interp)?;
+        self.orbits.insert((sv, bucket), orbit);
+        Some(orbit)
+    }
+    fn validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        self.inner.validity(sv)
+    }
+}
+
+impl<S: ClockSource> ClockSource for Memoized<S> {
+    fn next_clock_at(
+        &mut self,
+        t: Epoch,
+        sv: SV,
+        interp: Interpolation,
+    ) -> Option<ClockCorrection> {
+        let bucket = self.bucket(t);
+        if let Some(cached) = self.clocks.get(&(sv, bucket)) {
+            self.hits += 1;
+            return Some(*cached);
+        }
+        self.misses += 1;
+        let correction = self.inner.next_clock_at(t, sv, interp)?;
+        self.clocks.insert((sv, bucket), correction);
+        Some(correction)
+    }
+    fn clock_validity(&self, sv: SV) -> Option<(Epoch, Epoch)> {
+        self.inner.clock_validity(sv)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{merged_validity, Fallback, Memoized};
+    use crate::{
+        clock::ClockSource,
+        orbit::OrbitSource,
+        prelude::{
+            ClockCorrection, Duration, Epoch, Frame, Interpolation, Orbit, TimeScale, EARTH_J2000,
+            SV,
+        },
+        time::TimeSource,
+    };
+    use std::str::FromStr;
+
+    /// Provider that only knows a single [SV]
+    struct Single {
+        sv: SV,
+        value_km: f64,
+    }
+
+    impl OrbitSource for Single {
+        fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, _: Interpolation) -> Option<Orbit> {
+            (sv == self.sv).then(|| Orbit::from_position(self.value_km, 0.0, 0.0, t, fr))
+        }
+    }
+
+    impl ClockSource for Single {
+        fn next_clock_at(&mut self, _: Epoch, sv: SV, _: Interpolation) -> Option<ClockCorrection> {
+            (sv == self.sv).then(|| {
+                ClockCorrection::without_relativistic_correction(Duration::from_seconds(
+                    self.value_km,
+                ))
+            })
+        }
+    }
+
+    impl TimeSource for Single {
+        fn next_time_offset_at(
+            &mut self,
+            _: Epoch,
+            _: TimeScale,
+            to: TimeScale,
+        ) -> Option<Duration> {
+            (to == TimeScale::GST).then(|| Duration::from_seconds(self.value_km))
+        }
+    }
+
+    #[test]
+    fn fallback_priority() {
+        let t = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let (g01, g02, g03) = (
+            SV::from_str("G01").unwrap(),
+            SV::from_str("G02").unwrap(),
+            SV::from_str("G03").unwrap(),
+        );
+
+        let mut source = Fallback::new(
+            Single {
+                sv: g01,
+                value_km: 1.0,
+            },
+            Fallback::new(
+                Single {
+                    sv: g02,
+                    value_km: 2.0,
+                },
+                Single {
+                    sv: g01,
+                    value_km: 3.0,
+                },
+            ),
+        );
+
+        let interp = Interpolation::default();
+        for (sv, expected) in [(g01, Some(1.0)), (g02, Some(2.0)), (g03, None)] {
+            let orbit = source.next_at(t, sv, EARTH_J2000, interp);
+            assert_eq!(
+                orbit.map(|orbit| orbit.to_cartesian_pos_vel()[0]),
+                expected,
+                "failed for {}",
+                sv
+            );
+            let clock = source.next_clock_at(t, sv, interp);
+            assert_eq!(
+                clock.map(|clock| clock.duration),
+                expected.map(Duration::from_seconds),
+                "failed for {}",
+                sv
+            );
+        }
+
+        assert_eq!(
+            source.next_time_offset_at(t, TimeScale::GPST, TimeScale::GST),
+            Some(Duration::from_seconds(1.0))
+        );
+        assert_eq!(
+            source.next_time_offset_at(t, TimeScale::GPST, TimeScale::BDT),
+            None
+        );
+    }
+
+    /// Provider moving at constant velocity, along x axis
+    #[derive(Default, Clone)]
+    struct Moving {
+        requests: usize,
+    }
+
+    impl OrbitSource for Moving {
+        fn next_at(&mut self, t: Epoch, _: SV, fr: Frame, _: Interpolation) -> Option<Orbit> {
+            self.requests += 1;
+            let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+            let x_km = 20_000.0 + 3.0 * (t - t0).to_seconds();
+            Some(Orbit::new(x_km, 0.0, 0.0, 3.0, 0.0, 0.0, t, fr))
+        }
+    }
+
+    #[test]
+    fn memoized_orbits() {
+        let t = Epoch::from_str("2020-06-25T00:00:01 GPST").unwrap();
+        let (g01, g02) = (SV::from_str("G01").unwrap(), SV::from_str("G02").unwrap());
+        let interp = Interpolation::default();
+
+        let mut source = Memoized::new(Moving::default()).with_bucket(Duration::from_seconds(1.0));
+
+        let first = source.next_at(t, g01, EARTH_J2000, interp).unwrap();
+        assert_eq!(first.to_cartesian_pos_vel()[0], 20_003.0);
+
+        // transmission time iteration: answered from the cache
+        for dt_ms in [70.0, 72.5, 72.4] {
+            let t_tx = t + Duration::from_milliseconds(dt_ms);
+            let orbit = source.next_at(t_tx, g01, EARTH_J2000, interp).unwrap();
+            assert_eq!(orbit.epoch, t_tx);
+            let expected = 20_003.0 + 3.0 * dt_ms / 1.0E3;
+            assert!((orbit.to_cartesian_pos_vel()[0] - expected).abs() < 1.0E-9);
+        }
+        assert_eq!(source.inner().requests, 1);
+
+        // other SV, next bucket
+        assert!(source.next_at(t, g02, EARTH_J2000, interp).is_some());
+        let t_next = t + Duration::from_seconds(1.0);
+        assert!(source.next_at(t_next, g01, EARTH_J2000, interp).is_some());
+
+        assert_eq!(source.hits(), 3);
+        assert_eq!(source.misses(), 3);
+        assert_eq!(source.inner().requests, 3);
+    }
+
+    #[test]
+    fn fallback_validity() {
+        let t0 = Epoch::from_str("2020-06-25T00:00:00 GPST").unwrap();
+        let t1 = t0 + Duration::from_days(1.0);
+        let t2 = t1 + Duration::from_days(1.0);
+
+        assert_eq!(
+            merged_validity(Some((t1, t2)), Some((t0, t1))),
+            Some((t0, t2))
+        );
+        // either provider may answer: unbounded as soon as one is
+        assert!(merged_validity(Some((t0, t1)), None).is_none());
+        assert!(merged_validity(None, Some((t0, t1))).is_none());
+    }
+}
@@ -0,0 +1,141 @@
+//! Solver state persistence and introspection
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ambiguity::{AmbiguityStatus, AmbiguityTracking},
+    navigation::{FilterSnapshot, FilterView},
+    prelude::Epoch,
+};
+
+/// [SolverState] is a snapshot of the [Solver] internal state.
+/// Serialize and store it, to warm start a [Solver] later on
+/// (see [Solver::restore]), instead of going through the initialization process once again.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SolverState {
+    /// Apriori position in use, ECEF [m]
+    pub apriori_ecef_m: Option<(f64, f64, f64)>,
+    /// [Epoch] of latest solution
+    pub epoch: Option<Epoch>,
+    /// Latest resolved position, ECEF [m]
+    pub position_ecef_m: Option<(f64, f64, f64)>,
+    /// Latest resolved velocity, ECEF [m/s]
+    pub velocity_ecef_m_s: Option<(f64, f64, f64)>,
+    /// Latest resolved clock offset [s]
+    pub clock_offset_s: Option<f64>,
+    /// Latest resolved clock drift [s/s]
+    pub clock_drift_s_s: Option<f64>,
+    /// Navigation filter state
+    pub filter: Option<FilterSnapshot>,
+    /// Phase ambiguities tracking
+    pub ambiguities: Vec<AmbiguityTracking>,
+}
+
+/// [EstimatorView] is a read-only view of the [Solver] estimator
+/// (see [Solver::estimator_view]), so applications may display its convergence
+/// or debug its tuning. Unlike [SolverState], it is not meant to be restored.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EstimatorView {
+    /// [Epoch] of latest solution
+    pub epoch: Option<Epoch>,
+    /// Latest validated navigation filter state
+    pub filter: Option<FilterView>,
+    /// Phase ambiguities resolution status
+    pub ambiguities: Vec<AmbiguityStatus>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::SolverState;
+    use crate::{
+        ambiguity::AmbiguityTracking,
+        navigation::FilterState,
+        prelude::{Config, Constellation, Duration, Epoch, Filter, FilterSnapshot, Solver, SV},
+        tests::{self, NoOrbits},
+    };
+    use std::str::FromStr;
+
+    fn solver(filter: Filter) -> Solver<NoOrbits> {
+        let mut cfg = Config::default();
+        cfg.solver.filter = filter;
+        tests::solver(&cfg)
+    }
+
+    fn solver_state() -> SolverState {
+        let t = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let dt = Duration::from_seconds(30.0);
+        SolverState {
+            apriori_ecef_m: Some((4_696_000.0, 723_000.0, 4_239_000.0)),
+            epoch: Some(t),
+            position_ecef_m: Some((4_696_500.0, 723_500.0, 4_239_500.0)),
+            velocity_ecef_m_s: Some((1000.0, -2000.0, 500.0)),
+            clock_offset_s: Some(1.0E-3),
+            clock_drift_s_s: Some(1.0E-9),
+            filter: Some(FilterSnapshot {
+                filter: Filter::Kalman,
+                x: (0..8).map(|i| i as f64).collect(),
+                p: (0..64).map(|i| i as f64 * 0.5).collect(),
+                q: (0..64).map(|i| i as f64 * 0.25).collect(),
+                phi: (0..64)
+                    .map(|i| if i % 9 == 0 { 1.0 } else { 0.0 })
+                    .collect(),
+            }),
+            ambiguities: vec![
+                AmbiguityTracking {
+                    sv: SV::new(Constellation::GPS, 1),
+                    last_seen: Some(t),
+                    mw: (10.25, 0.5, 12),
+                    n1: (-3.5, 0.25, 12),
+                    gf: vec![(t - dt, 0.125), (t, 0.25)],
+                },
+                AmbiguityTracking {
+                    sv: SV::new(Constellation::Galileo, 3),
+                    last_seen: Some(t - dt),
+                    mw: (4.0, 1.0, 2),
+                    n1: (1.5, 0.75, 2),
+                    gf: vec![(t - dt, -0.5)],
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn warm_start_round_trip() {
+        let state = solver_state();
+
+        #[cfg(feature = "serde")]
+        let state = {
+            let content = serde_json::to_string(&state).unwrap();
+            let parsed: SolverState = serde_json::from_str(&content).unwrap();
+            assert_eq!(parsed, state);
+            parsed
+        };
+
+        let mut solver = solver(Filter::Kalman);
+        solver.restore(&state);
+        assert_eq!(solver.state(), state);
+    }
+
+    #[test]
+    fn bypassed_filter_round_trip() {
+        let mut state = solver_state();
+
+        // bypassed filter has no state
+        let mut solver = solver(Filter::None);
+        solver.restore(&state);
+        let restored = solver.state();
+        assert!(restored.filter.is_none());
+        assert_eq!(restored.ambiguities, state.ambiguities);
+
+        if let Some(snapshot) = state.filter.as_mut() {
+            snapshot.filter = Filter::None;
+        }
+        assert!(FilterState::from_snapshot(state.filter.as_ref().unwrap()).is_none());
+
+        let mut solver = solver(Filter::LSQ);
+        solver.restore(&state);
+        assert!(solver.state().filter.is_none());
+    }
+}
@@ -0,0 +1,105 @@
+//! Indexed per [SV] storage
+use crate::prelude::SV;
+
+/// [SVMap] stores one value per [SV], in a vector sorted by [SV].
+/// Pools are small (a few tens of vehicles): binary search is cheaper
+/// than hashing, and iteration order is deterministic.
+#[derive(Debug, Clone)]
+pub(crate) struct SVMap<T> {
+    entries: Vec<(SV, T)>,
+}
+
+impl<T> Default for SVMap<T> {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+        }
+    }
+}
+
+impl<T> SVMap<T> {
+    /// Builds new [SVMap] with preallocated capacity
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+        }
+    }
+    fn position(&self, sv: &SV) -> Result<usize, usize> {
+        self.entries.binary_search_by(|(k, _)| k.cmp(sv))
+    }
+    /// Returns reference to the value stored for this [SV]
+    pub fn get(&self, sv: &SV) -> Option<&T> {
+        let index = self.position(sv).ok()?;
+        Some(&self.entries[index].1)
+    }
+    /// Returns mutable reference to the value stored for this [SV]
+    pub fn get_mut(&mut self, sv: &SV) -> Option<&mut T> {
+        let index = self.position(sv).ok()?;
+        Some(&mut self.entries[index].1)
+    }
+    /// Returns true if a value is stored for this [SV]
+    pub fn contains_key(&self, sv: &SV) -> bool {
+        self.position(sv).is_ok()
+    }
+    /// Stores this value for this [SV], returns the previous value if any
+    pub fn insert(&mut self, sv: SV, value: T) -> Option<T> {
+        match self.position(&sv) {
+            Ok(index) => Some(std::mem::replace(&mut self.entries[index].1, value)),
+            Err(index) => {
+                self.entries.insert(index, (sv, value));
+                None
+            },
+        }
+    }
+    /// Retains the entries for which `f` returns true
+    pub fn retain<F: FnMut(&SV, &mut T) -> bool>(&mut self, mut f: F) {
+        self.entries.retain_mut(|(sv, value)| f(sv, value));
+    }
+    /// Clears all entries, preserving the allocation
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+    /// Iterates entries, by increasing [SV]
+    pub fn iter(&self) -> impl Iterator<Item = (&SV, &T)> {
+        self.entries.iter().map(|(sv, value)| (sv, value))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SVMap;
+    use crate::prelude::SV;
+    use std::str::FromStr;
+
+    #[test]
+    fn sv_map() {
+        let (g01, g05, e03) = (
+            SV::from_str("G01").unwrap(),
+            SV::from_str("G05").unwrap(),
+            SV::from_str("E03").unwrap(),
+        );
+
+        let mut map = SVMap::with_capacity(4);
+        assert_eq!(map.iter().count(), 0);
+        assert_eq!(map.insert(g05, 5), None);
+        assert_eq!(map.insert(e03, 3), None);
+        assert_eq!(map.insert(g01, 1), None);
+        assert_eq!(map.insert(g05, 50), Some(5));
+        assert_eq!(map.iter().count(), 3);
+
+        let mut sorted = vec![g01, g05, e03];
+        sorted.sort();
+        assert_eq!(map.iter().map(|(sv, _)| *sv).collect::<Vec<_>>(), sorted);
+
+        *map.get_mut(&g01).unwrap() += 10;
+        assert_eq!(map.get(&g01), Some(&11));
+        assert_eq!(map.get(&g05), Some(&50));
+
+        map.retain(|sv, _| *sv != e03);
+        assert!(!map.contains_key(&e03));
+        assert_eq!(map.iter().count(), 2);
+
+        map.clear();
+        assert!(map.get(&g01).is_none());
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    navigation::{Input, Workspace},
+    prelude::{Carrier, Config, Observation, Orbit, EARTH_ITRF93, SV},
+    solver::canonical_order,
+    tests::gps_test_data,
+};
+use nalgebra::{base::dimension::U8, OMatrix};
+
+/// Distinct ECEF position [km] of each vehicle of [gps_test_data]
+fn sv_position_km(sv: SV) -> (f64, f64, f64) {
+    match sv.prn {
+        1 => (4577.077035843635, -22996.125649966143, 18062.46236437641),
+        2 => (16576.946499220812, -4619.715035111092, 24092.50915107983),
+        3 => (18846.557032585508, 16144.709835080192, 16160.045068828074),
+        _ => (-15921.905530334785, -5399.928036329342, 24360.75165958442),
+    }
+}
+
+#[test]
+fn canonical_pool_ordering() {
+    let mut cfg = Config::default();
+    cfg.modeling.sv_clock_bias = false;
+    cfg.modeling.sv_total_group_delay = false;
+
+    for data in gps_test_data().iter() {
+        let mut forward = data.pool.clone();
+        for cd in forward.iter_mut() {
+            let (x_km, y_km, z_km) = sv_position_km(cd.sv);
+            cd.set_orbit(Orbit::from_position(
+                x_km,
+                y_km,
+                z_km,
+                data.t_rx,
+                EARTH_ITRF93,
+            ));
+            let pr = cd.observations[0].pseudo.unwrap();
+            cd.observations
+                .push(Observation::pseudo_range(Carrier::L5, pr + 1.0, None));
+        }
+
+        // same pool, presented in another order
+        let mut backward = forward.clone();
+        backward.reverse();
+        for cd in backward.iter_mut() {
+            cd.observations.reverse();
+        }
+
+        canonical_order(&mut forward);
+        canonical_order(&mut backward);
+
+        for (lhs, rhs) in forward.iter().zip(backward.iter()) {
+            assert_eq!(lhs.sv, rhs.sv);
+            assert_eq!(
+                lhs.observations
+                    .iter()
+                    .map(|ob| ob.carrier)
+                    .collect::<Vec<_>>(),
+                rhs.observations
+                    .iter()
+                    .map(|ob| ob.carrier)
+                    .collect::<Vec<_>>(),
+            );
+        }
+
+        let apriori = (4_696_989.0, 723_994.0, 4_239_678.0);
+        let [lhs, rhs] = [&forward, &backward].map(|pool| {
+            Input::new(
+                apriori,
+                &cfg,
+                pool,
+                OMatrix::<f64, U8, U8>::identity(),
+                &Default::default(),
+                &mut Workspace::default(),
+            )
+            .unwrap()
+        });
+
+        assert_eq!(lhs.rows, rhs.rows);
+        assert_eq!(lhs.y, rhs.y);
+        assert_eq!(lhs.g, rhs.g);
+        assert_eq!(
+            lhs.sv.keys().collect::<Vec<_>>(),
+            rhs.sv.keys().collect::<Vec<_>>()
+        );
+    }
+}
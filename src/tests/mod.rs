@@ -1,10 +1,15 @@
-use crate::prelude::{
-    Candidate, Config, Epoch, Error, Frame, InvalidationCause, Orbit, OrbitSource, PVTSolution,
-    Solver, TimeScale, SV,
+use crate::{
+    clock::ClockSource,
+    prelude::{
+        Almanac, Candidate, ClockCorrection, Config, Duration, Epoch, ErrorKind, Frame,
+        Interpolation, InvalidationCause, Orbit, OrbitSource, PVTSolution, Solver, TimeScale,
+        EARTH_ITRF93, SV,
+    },
 };
 
 mod bancroft;
 mod data;
+mod deterministic;
 mod pseudo_range;
 mod pvt;
 
@@ -13,7 +18,7 @@ use data::{gps::test_data as gps_test_data, interp::interp_data};
 struct Orbits {}
 
 impl OrbitSource for Orbits {
-    fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, _: usize) -> Option<Orbit> {
+    fn next_at(&mut self, t: Epoch, sv: SV, fr: Frame, _: Interpolation) -> Option<Orbit> {
         Some(
             interp_data()
                 .iter()
@@ -24,6 +29,48 @@ impl OrbitSource for Orbits {
     }
 }
 
+/// [OrbitSource] that never resolves any state
+pub(crate) struct NoOrbits;
+
+impl OrbitSource for NoOrbits {
+    fn next_at(&mut self, _: Epoch, _: SV, _: Frame, _: Interpolation) -> Option<Orbit> {
+        None
+    }
+}
+
+/// Deploys a [Solver] without [Almanac] (no network access) nor orbital states
+pub(crate) fn solver(cfg: &Config) -> Solver<NoOrbits> {
+    Solver::new_almanac_frame(cfg, None, NoOrbits, Almanac::default(), EARTH_ITRF93)
+}
+
+/// Data provider that counts the requests it answers
+#[derive(Default)]
+pub(crate) struct Counter {
+    pub requests: usize,
+}
+
+impl OrbitSource for Counter {
+    fn next_at(&mut self, t: Epoch, _: SV, fr: Frame, _: Interpolation) -> Option<Orbit> {
+        self.requests += 1;
+        Some(Orbit::from_position(0.0, 0.0, 0.0, t, fr))
+    }
+}
+
+impl ClockSource for Counter {
+    fn next_clock_at(&mut self, _: Epoch, _: SV, _: Interpolation) -> Option<ClockCorrection> {
+        self.requests += 1;
+        Some(ClockCorrection::without_relativistic_correction(
+            Duration::from_microseconds(1.0),
+        ))
+    }
+    fn clock_validity(&self, _: SV) -> Option<(Epoch, Epoch)> {
+        Some((
+            Epoch::default(),
+            Epoch::default() + Duration::from_days(1.0),
+        ))
+    }
+}
+
 struct SolverInput {
     t_rx: Epoch,
     pool: Vec<Candidate>,
@@ -103,61 +150,73 @@ impl Tester {
                     );
                     self.static_run(&cfg, solution);
                 },
-                Err(e) => match e {
-                    Error::NotEnoughCandidates => {},
-                    Error::NotEnoughCandidatesBancroft => {},
-                    Error::NotEnoughPreFitCandidates => {},
-                    Error::NotEnoughPostFitCandidates => {},
-                    Error::MatrixFormationError => {},
-                    Error::UnknownClockCorrection => {},
-                    Error::MissingRemoteRTKObservation(..) => {},
-                    Error::MissingRemoteRTKObservations => {},
-                    Error::MatrixInversionError => {},
-                    Error::TimeIsNan => {
-                        panic!("resolved dt is Not A Number");
-                    },
-                    Error::InvalidStrategy => {},
-                    Error::NavigationError => {},
-                    Error::MissingPseudoRange => {},
-                    Error::PseudoRangeCombination => {},
-                    Error::PhaseRangeCombination => {},
-                    Error::InvalidatedSolution(cause) => match cause {
-                        InvalidationCause::FirstSolution => {},
-                        InvalidationCause::GDOPOutlier(..) => {},
-                        InvalidationCause::TDOPOutlier(..) => {},
-                        InvalidationCause::InnovationOutlier(..) => {},
-                        InvalidationCause::CodeResidual(..) => {},
-                    },
-                    Error::UnresolvedStateBancroft => {
-                        panic!("bancroft resolution attempt, without enough SV");
-                    },
-                    Error::UnresolvedState => {
-                        panic!("navigation attempt while some states still remain unresolved or ambiguous");
-                    },
-                    Error::PhysicalNonSenseRxPriorTx | Error::PhysicalNonSenseRxTooLate => {
-                        panic!("physics_err: error in signal propgation");
-                    },
-                    Error::Physics(e) => {
-                        panic!("physics_err: {}", e);
-                    },
-                    Error::BancroftError => {
-                        panic!("bancroft error");
-                    },
-                    Error::BancroftImaginarySolution => {
-                        panic!("invalid bancroft solution");
-                    },
-                    Error::UnresolvedAmbiguity => {
-                        panic!("navigation attempt while some ambiguities still remain");
-                    },
-                    Error::MetaAlmanac(e) => {
-                        panic!("almanac setup error: {}", e);
-                    },
-                    Error::Almanac(e) => {
-                        panic!("almanac determination error: {}", e);
-                    },
-                    Error::EarthFrame(e) => {
-                        panic!("earth frame error: {}", e);
-                    },
+                Err(e) => {
+                    assert_eq!(e.epoch(), Some(data.t_rx), "missing epoch context");
+                    match e.kind() {
+                        ErrorKind::NotEnoughCandidates => {},
+                        ErrorKind::NotEnoughCandidatesBancroft => {},
+                        ErrorKind::NotEnoughCandidatesColdStart => {},
+                        ErrorKind::NotEnoughPreFitCandidates => {},
+                        ErrorKind::NotEnoughPostFitCandidates => {},
+                        ErrorKind::MatrixFormationError => {},
+                        ErrorKind::OutsideValidityWindow => {},
+                        ErrorKind::UnknownClockCorrection => {},
+                        ErrorKind::InconsistentGalileoNavMessage => {
+                            panic!("mixed galileo navigation messages");
+                        },
+                        ErrorKind::MissingRemoteRTKObservation => {},
+                        ErrorKind::MissingRemoteRTKObservations => {},
+                        ErrorKind::MatrixInversionError => {},
+                        ErrorKind::TimeIsNan => {
+                            panic!("resolved dt is Not A Number");
+                        },
+                        ErrorKind::InvalidStrategy => {},
+                        ErrorKind::NavigationError => {},
+                        ErrorKind::MissingPseudoRange => {},
+                        ErrorKind::PseudoRangeCombination => {},
+                        ErrorKind::PhaseRangeCombination => {},
+                        ErrorKind::InvalidatedSolution(cause) => match cause {
+                            InvalidationCause::FirstSolution => {},
+                            InvalidationCause::GDOPOutlier(..) => {},
+                            InvalidationCause::TDOPOutlier(..) => {},
+                            InvalidationCause::InnovationOutlier(..) => {},
+                            InvalidationCause::CodeResidual(..) => {},
+                        },
+                        ErrorKind::UnresolvedStateBancroft => {
+                            panic!("bancroft resolution attempt, without enough SV");
+                        },
+                        ErrorKind::UnresolvedState => {
+                            panic!("navigation attempt while some states still remain unresolved or ambiguous");
+                        },
+                        ErrorKind::PhysicalNonSenseRxPriorTx
+                        | ErrorKind::PhysicalNonSenseRxTooLate => {
+                            panic!("physics_err: error in signal propgation");
+                        },
+                        ErrorKind::Physics(e) => {
+                            panic!("physics_err: {}", e);
+                        },
+                        ErrorKind::BancroftError => {
+                            panic!("bancroft error");
+                        },
+                        ErrorKind::BancroftImaginarySolution => {
+                            panic!("invalid bancroft solution");
+                        },
+                        ErrorKind::ColdStartDivergence => {
+                            panic!("cold start did not converge");
+                        },
+                        ErrorKind::UnresolvedAmbiguity => {
+                            panic!("navigation attempt while some ambiguities still remain");
+                        },
+                        ErrorKind::MetaAlmanac(e) => {
+                            panic!("almanac setup error: {}", e);
+                        },
+                        ErrorKind::Almanac(e) => {
+                            panic!("almanac determination error: {}", e);
+                        },
+                        ErrorKind::EarthFrame(e) => {
+                            panic!("earth frame error: {}", e);
+                        },
+                    }
                 },
             }
         }
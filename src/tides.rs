@@ -1,6 +1,6 @@
 use crate::{
     constants::Constants,
-    prelude::{Almanac, Epoch, Error, Frame, Vector3},
+    prelude::{Almanac, Epoch, ErrorKind, Frame, Vector3},
 };
 use anise::{
     math::cartesian::CartesianState,
@@ -15,7 +15,7 @@ pub fn solid_tides(
     t: Epoch,
     almanac: &Almanac,
     site_ecef_m: Vector3<f64>,
-) -> Result<Vector3<f64>, Error> {
+) -> Result<Vector3<f64>, ErrorKind> {
     let (site_ecef_x_m, site_ecef_y_m, site_ecef_z_m) =
         (site_ecef_m[0], site_ecef_m[1], site_ecef_m[2]);
 
@@ -32,11 +32,11 @@ pub fn solid_tides(
 
     let earth_sun = almanac
         .transform(EARTH_J2000, SUN_J2000, t, None)
-        .map_err(Error::Almanac)?;
+        .map_err(ErrorKind::Almanac)?;
 
     let earth_moon = almanac
         .transform(EARTH_J2000, MOON_J2000, t, None)
-        .map_err(Error::Almanac)?;
+        .map_err(ErrorKind::Almanac)?;
 
     let earth_moon_m = Vector3::new(
         earth_moon.radius_km.x * 1.0E3,
@@ -59,7 +59,7 @@ pub fn solid_tides(
     );
 
     //let site_latitude = site_orbit.latitude_deg()
-    //    .map_err(|e| Error::Physics(e))?
+    //    .map_err(|e| ErrorKind::Physics(e))?
     //    .to_radians();
 
     let earth_sun_mag = earth_sun_m.magnitude();
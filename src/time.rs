@@ -0,0 +1,250 @@
+//! Time scales
+use crate::{
+    prelude::{Duration, Epoch, TimeScale},
+    sync,
+};
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// [TimeSource] may be attached to the [Solver] (see [Solver::set_time_source])
+/// to provide the actual offsets between [TimeScale]s (like broadcast GGTO
+/// or UTC parameters), rather than their nominal values.
+///
+/// [TimeSource] is implemented for `Arc<Mutex<T>>`, so a single provider
+/// may be shared between several [Solver]s.
+pub trait TimeSource {
+    /// Provide the [Duration] to add to a clock offset expressed relative to `from`,
+    /// to express it relative to `to`, at requested [Epoch].
+    /// Returns None if the offset is unknown: nominal offsets then apply.
+    fn next_time_offset_at(&mut self, t: Epoch, from: TimeScale, to: TimeScale)
+        -> Option<Duration>;
+}
+
+impl<T: TimeSource> TimeSource for Arc<Mutex<T>> {
+    fn next_time_offset_at(
+        &mut self,
+        t: Epoch,
+        from: TimeScale,
+        to: TimeScale,
+    ) -> Option<Duration> {
+        let mut source = sync::lock(self);
+        source.next_time_offset_at(t, from, to)
+    }
+}
+
+/// Returns the offset between `ts` readings and [TimeScale::TAI] readings, at [Epoch] `t`.
+/// Dynamical time scales (ET, TDB) are approximated by [TimeScale::TT].
+fn tai_offset(t: Epoch, ts: TimeScale) -> Duration {
+    match ts {
+        TimeScale::TAI => Duration::ZERO,
+        TimeScale::GPST | TimeScale::GST | TimeScale::QZSST => Duration::from_seconds(-19.0),
+        TimeScale::BDT => Duration::from_seconds(-33.0),
+        TimeScale::UTC => Duration::from_seconds(-t.leap_seconds(true).unwrap_or_default()),
+        _ => Duration::from_seconds(32.184),
+    }
+}
+
+/// [LeapSecondPolicy] defines how UTC timestamped GLONASS [Candidate](crate::prelude::Candidate)s
+/// are processed around leap second events, so they do not corrupt mixed constellation solutions.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LeapSecondPolicy {
+    /// Timestamps step at the leap second event, as defined by the leap second table
+    #[default]
+    Step,
+    /// Timestamps were smeared linearly over this [Duration], ending at the leap second event
+    /// (as some receivers and time servers do): they are unsmeared on intake.
+    Smear(Duration),
+    /// GLONASS candidates are dropped within this [Duration] of the leap second event,
+    /// the other constellations keep contributing.
+    Exclude(Duration),
+}
+
+impl LeapSecondPolicy {
+    /// Applies this policy to this UTC [Epoch]: returns the actual [Epoch],
+    /// or None if it should be excluded.
+    pub(crate) fn apply(&self, t: Epoch) -> Option<Epoch> {
+        match self {
+            Self::Step => Some(t),
+            Self::Smear(window) => match closest_leap_second(t) {
+                Some((event, delta_s)) if t < event && t >= event - *window => {
+                    let fraction = (t - (event - *window)).to_seconds() / window.to_seconds();
+                    Some(t + Duration::from_seconds(fraction * delta_s))
+                },
+                _ => Some(t),
+            },
+            Self::Exclude(window) => match closest_leap_second(t) {
+                Some((event, _)) if (t - event).abs() <= *window => None,
+                _ => Some(t),
+            },
+        }
+    }
+}
+
+/// [Timestamping] convention of the published solutions.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Timestamping {
+    /// Solutions are tagged with the (biased) receiver timestamp
+    #[default]
+    Receiver,
+    /// Solutions are tagged with the system time: the receiver timestamp
+    /// corrected by the estimated receiver clock offset
+    System,
+}
+
+impl Timestamping {
+    /// Applies this convention to this receiver [Epoch], `dt` being the receiver clock offset
+    pub(crate) fn apply(&self, t: Epoch, dt: Duration) -> Epoch {
+        match self {
+            Self::Receiver => t,
+            Self::System => t - dt,
+        }
+    }
+}
+
+/// Returns the leap second event (previous or next UTC midnight) closest to [Epoch],
+/// with the leap second count change, if there is one.
+fn closest_leap_second(t: Epoch) -> Option<(Epoch, f64)> {
+    let (year, month, day, _, _, _, _) = t.to_gregorian_utc();
+    let midnight = Epoch::from_gregorian_utc_at_midnight(year, month, day);
+    [midnight, midnight + Duration::from_days(1.0)]
+        .into_iter()
+        .filter_map(|event| {
+            let before = (event - Duration::from_seconds(1.0)).leap_seconds(true)?;
+            let after = event.leap_seconds(true)?;
+            (after != before).then_some((event, after - before))
+        })
+        .min_by_key(|(event, _)| (t - *event).abs())
+}
+
+/// GPST to GST offset random walk: about 1 ns per hour
+const GGTO_RANDOM_WALK_S2_S: f64 = 1.0E-18 / 3600.0;
+
+/// [GgtoEstimator] estimates the GPS to Galileo time offset (GST - GPST)
+/// as a slowly varying (random walk) scalar state, from the differences
+/// between the GPS and Galileo residuals.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct GgtoEstimator {
+    /// Latest update
+    t: Option<Epoch>,
+    /// Offset estimate [s]
+    x_s: f64,
+    /// Estimate variance [s²]
+    p_s2: f64,
+}
+
+impl GgtoEstimator {
+    /// Returns current estimate, if any
+    pub fn value(&self) -> Option<Duration> {
+        self.t.map(|_| Duration::from_seconds(self.x_s))
+    }
+    /// Updates the estimate with this measurement of the offset [s],
+    /// and its standard deviation [s].
+    pub fn update(&mut self, t: Epoch, z_s: f64, sigma_s: f64) {
+        let r_s2 = sigma_s.powi(2);
+        match self.t {
+            Some(prev_t) => {
+                let dt_s = (t - prev_t).to_seconds().abs();
+                let p_s2 = self.p_s2 + GGTO_RANDOM_WALK_S2_S * dt_s;
+                let k = p_s2 / (p_s2 + r_s2);
+                self.x_s += k * (z_s - self.x_s);
+                self.p_s2 = (1.0 - k) * p_s2;
+            },
+            None => {
+                self.x_s = z_s;
+                self.p_s2 = r_s2;
+            },
+        }
+        self.t = Some(t);
+    }
+}
+
+/// Returns the [Duration] to add to a clock offset, expressed relative to `from`,
+/// to express it relative to `to`, at [Epoch] `t`.
+pub(crate) fn clock_offset_correction(t: Epoch, from: TimeScale, to: TimeScale) -> Duration {
+    tai_offset(t, from) - tai_offset(t, to)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{clock_offset_correction, GgtoEstimator, LeapSecondPolicy, Timestamping};
+    use crate::prelude::{Duration, Epoch, TimeScale};
+    use std::str::FromStr;
+    #[test]
+    fn clock_offset_corrections() {
+        let t = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        for (from, to, expected) in [
+            (TimeScale::GPST, TimeScale::GPST, 0.0),
+            (TimeScale::GPST, TimeScale::GST, 0.0),
+            (TimeScale::GPST, TimeScale::TAI, -19.0),
+            (TimeScale::GPST, TimeScale::BDT, 14.0),
+            (TimeScale::GPST, TimeScale::UTC, 18.0),
+            (TimeScale::UTC, TimeScale::GPST, -18.0),
+        ] {
+            assert_eq!(
+                clock_offset_correction(t, from, to),
+                Duration::from_seconds(expected),
+                "failed for {}->{}",
+                from,
+                to
+            );
+        }
+    }
+    #[test]
+    fn ggto_estimation() {
+        let t0 = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let mut ggto = GgtoEstimator::default();
+        assert!(ggto.value().is_none());
+
+        // noisy measurements of a 20ns offset
+        for i in 0..1000 {
+            let noise_s = if i % 2 == 0 { 5.0E-9 } else { -5.0E-9 };
+            ggto.update(
+                t0 + i as f64 * Duration::from_seconds(30.0),
+                20.0E-9 + noise_s,
+                5.0E-9,
+            );
+        }
+        let value_s = ggto.value().unwrap().to_seconds();
+        assert!((value_s - 20.0E-9).abs() < 1.0E-9, "{}", value_s);
+    }
+    #[test]
+    fn leap_second_policies() {
+        let event = Epoch::from_str("2017-01-01T00:00:00 UTC").unwrap();
+        let before = Epoch::from_str("2016-12-31T23:30:00 UTC").unwrap();
+        let after = Epoch::from_str("2017-01-01T00:30:00 UTC").unwrap();
+        let noon = Epoch::from_str("2016-12-31T12:00:00 UTC").unwrap();
+        let quiet = Epoch::from_str("2020-06-25T00:10:00 UTC").unwrap();
+
+        for t in [before, after, noon, quiet] {
+            assert_eq!(LeapSecondPolicy::Step.apply(t), Some(t));
+        }
+
+        let exclude = LeapSecondPolicy::Exclude(Duration::from_seconds(3600.0));
+        assert!(exclude.apply(before).is_none());
+        assert!(exclude.apply(after).is_none());
+        assert_eq!(exclude.apply(noon), Some(noon));
+        assert_eq!(exclude.apply(quiet), Some(quiet));
+
+        // 24h smear: half the leap second absorbed at noon
+        let smear = LeapSecondPolicy::Smear(Duration::from_days(1.0));
+        assert_eq!(smear.apply(noon), Some(noon + Duration::from_seconds(0.5)));
+        assert_eq!(smear.apply(after), Some(after));
+        assert_eq!(smear.apply(event), Some(event));
+        assert_eq!(smear.apply(quiet), Some(quiet));
+    }
+    #[test]
+    fn timestamping() {
+        let t = Epoch::from_str("2020-06-25T12:00:00 GPST").unwrap();
+        let dt = Duration::from_seconds(1.0E-3);
+        assert_eq!(Timestamping::Receiver.apply(t, dt), t);
+        assert_eq!(Timestamping::System.apply(t, dt), t - dt);
+        assert_eq!(
+            Timestamping::System.apply(t, -dt),
+            Epoch::from_str("2020-06-25T12:00:00.001 GPST").unwrap()
+        );
+    }
+}